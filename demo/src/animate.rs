@@ -0,0 +1,112 @@
+//! Animated GIF/APNG capture of generation steps
+
+use crate::render;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{self, FilterType};
+use image::{Delay, Frame, RgbImage};
+use std::fs::File;
+use std::io::BufWriter;
+use terrain_forge::{config, Grid, Rng, Tile};
+
+pub struct Options {
+    pub scale: u32,
+    pub delay_ms: u32,
+    pub max_frames: usize,
+}
+
+/// Runs the config's pipeline and effects, recording every intermediate
+/// state, and writes the animation. Returns the number of frames written.
+pub fn animate(
+    cfg: &config::Config,
+    seed: u64,
+    output: &str,
+    options: &Options,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let pipeline = cfg.build_pipeline()?;
+    let mut grid = Grid::new(cfg.width, cfg.height);
+    let mut frames: Vec<Grid<Tile>> = vec![grid.clone()];
+    let mut push = |g: &Grid<Tile>| {
+        if frames.last() != Some(g) {
+            frames.push(g.clone());
+        }
+    };
+
+    pipeline.execute_frames(&mut grid, seed, &mut |_, g| push(g))?;
+    let semantic = cfg
+        .effects_need_semantic()
+        .then(|| cfg.extractor().extract(&grid, &mut Rng::new(seed)));
+    cfg.apply_effects(&mut grid, semantic.as_ref())?;
+    push(&grid);
+
+    let frames = sample(frames, options.max_frames);
+    let images: Vec<RgbImage> = frames
+        .iter()
+        .map(|g| {
+            let img = render::render_grid(g);
+            let scale = options.scale.max(1);
+            imageops::resize(
+                &img,
+                img.width() * scale,
+                img.height() * scale,
+                FilterType::Nearest,
+            )
+        })
+        .collect();
+
+    if output.to_lowercase().ends_with(".gif") {
+        write_gif(&images, options.delay_ms, output)?;
+    } else {
+        write_apng(&images, options.delay_ms, output)?;
+    }
+    Ok(images.len())
+}
+
+/// Evenly samples at most `max` frames, always keeping the first and last.
+fn sample(frames: Vec<Grid<Tile>>, max: usize) -> Vec<Grid<Tile>> {
+    let n = frames.len();
+    if max < 2 || n <= max {
+        return frames;
+    }
+    let keep: Vec<usize> = (0..max).map(|i| i * (n - 1) / (max - 1)).collect();
+    frames
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| keep.contains(i))
+        .map(|(_, f)| f)
+        .collect()
+}
+
+fn write_gif(
+    images: &[RgbImage],
+    delay_ms: u32,
+    output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(output)?));
+    encoder.set_repeat(Repeat::Infinite)?;
+    let delay = Delay::from_numer_denom_ms(delay_ms, 1);
+    encoder.encode_frames(images.iter().map(|img| {
+        let rgba = image::DynamicImage::ImageRgb8(img.clone()).to_rgba8();
+        Frame::from_parts(rgba, 0, 0, delay)
+    }))?;
+    Ok(())
+}
+
+fn write_apng(
+    images: &[RgbImage],
+    delay_ms: u32,
+    output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first = images.first().ok_or("no frames to write")?;
+    let file = BufWriter::new(File::create(output)?);
+    let mut encoder = png::Encoder::new(file, first.width(), first.height());
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(images.len() as u32, 0)?;
+    encoder.set_frame_delay(delay_ms.min(u16::MAX as u32) as u16, 1000)?;
+    let mut writer = encoder.write_header()?;
+    for img in images {
+        writer.write_image_data(img.as_raw())?;
+    }
+    writer.finish()?;
+    Ok(())
+}
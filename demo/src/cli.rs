@@ -41,6 +41,9 @@ pub enum Command {
         scale: usize,
         #[arg(short, long)]
         text: bool,
+        /// Also print an ANSI-colored map to the terminal
+        #[arg(long)]
+        ansi: bool,
         #[arg(long)]
         semantic: bool,
         #[arg(long)]
@@ -64,6 +67,9 @@ pub enum Command {
         output: String,
         #[arg(short, long)]
         text: bool,
+        /// Also print an ANSI-colored map to the terminal
+        #[arg(long)]
+        ansi: bool,
         #[arg(long)]
         semantic: bool,
         #[arg(long)]
@@ -88,6 +94,24 @@ pub enum Command {
         #[arg(short, long)]
         configs: bool,
     },
+    /// Diff two generations (seeds or configs) side by side
+    Diff {
+        /// First algorithm name, shorthand, or config path
+        a: String,
+        /// Second spec; defaults to the first (compare seeds)
+        b: Option<String>,
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Seed for the second grid; defaults to --seed
+        #[arg(long)]
+        seed_b: Option<u64>,
+        #[arg(short, long, default_value = "demo/output/diff.png")]
+        output: String,
+        #[arg(short, long, default_value = "80")]
+        width: usize,
+        #[arg(short = 'H', long, default_value = "60")]
+        height: usize,
+    },
     /// Run demos defined in a manifest
     Demo {
         /// Demo id from manifest (use --list to see available demos)
@@ -109,6 +133,99 @@ pub enum Command {
         #[arg(long)]
         constraints_only: bool,
     },
+    /// Export a 16-bit heightmap (.png, .raw/.r16)
+    Heightmap {
+        /// Height source: diamond_square, perlin, simplex, value
+        #[arg(default_value = "diamond_square")]
+        source: String,
+        #[arg(short, long)]
+        seed: Option<u64>,
+        #[arg(short, long, default_value = "demo/output/heightmap.png")]
+        output: String,
+        #[arg(short, long, default_value = "257")]
+        width: usize,
+        #[arg(short = 'H', long, default_value = "257")]
+        height: usize,
+        /// Noise frequency (noise sources only)
+        #[arg(long, default_value = "0.02")]
+        frequency: f64,
+        /// Write big-endian RAW
+        #[arg(long)]
+        big_endian: bool,
+        /// Also write a mesh (.obj, .gltf, .glb)
+        #[arg(long)]
+        mesh: Option<String>,
+        /// Mesh decimation stride
+        #[arg(long, default_value = "1")]
+        step: usize,
+    },
+    /// Animate generation steps as a GIF or APNG
+    Animate {
+        /// Algorithm name, shorthand, or config path
+        spec: String,
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Output path; .gif writes GIF, .png writes APNG
+        #[arg(short, long, default_value = "demo/output/animation.gif")]
+        output: String,
+        #[arg(short, long, default_value = "80")]
+        width: usize,
+        #[arg(short = 'H', long, default_value = "60")]
+        height: usize,
+        /// Pixels per cell
+        #[arg(long, default_value = "4")]
+        scale: u32,
+        /// Delay between frames in milliseconds
+        #[arg(long, default_value = "120")]
+        delay: u32,
+        /// Keep at most this many frames, sampled evenly (final frame always kept)
+        #[arg(long, default_value = "120")]
+        max_frames: usize,
+    },
+    /// Generate many seeds and write per-seed metrics
+    Batch {
+        /// Algorithm name, shorthand, or config path
+        spec: String,
+        /// Number of seeds to generate
+        #[arg(short = 'n', long, default_value = "10")]
+        count: usize,
+        /// First seed; later seeds count up from it
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Output directory for images and metrics
+        #[arg(short, long, default_value = "demo/output/batch")]
+        output: String,
+        #[arg(short, long, default_value = "80")]
+        width: usize,
+        #[arg(short = 'H', long, default_value = "60")]
+        height: usize,
+        /// Write metrics.json instead of metrics.csv
+        #[arg(long)]
+        json: bool,
+        /// Skip writing per-seed PNGs
+        #[arg(long)]
+        no_images: bool,
+    },
+    /// Explore seeds and params interactively in the terminal
+    Explore {
+        /// Algorithm name, shorthand, or config path
+        spec: String,
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Where `s` saves the current config and seed (.json or .toml)
+        #[arg(short, long, default_value = "demo/output/explore.json")]
+        output: String,
+        #[arg(short, long, default_value = "80")]
+        width: usize,
+        #[arg(short = 'H', long, default_value = "60")]
+        height: usize,
+    },
+    /// Serve a local web page for previewing generations
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
     /// List available algorithms
     List,
 }
@@ -0,0 +1,412 @@
+//! Interactive terminal explorer
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use serde_json::{json, Value};
+use std::io::Write;
+use terrain_forge::config::{self, AlgorithmSpec, Config, PipelineStepSpec};
+use terrain_forge::ops::{self, ParamSchema, ParamType};
+use terrain_forge::render::Legend;
+use terrain_forge::{Grid, Rng, SemanticLayers, Tile};
+
+const PANEL_WIDTH: u16 = 44;
+const REGION_COLORS: [Color; 6] = [
+    Color::Blue,
+    Color::Green,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Red,
+    Color::DarkYellow,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Overlay {
+    None,
+    Markers,
+    Regions,
+}
+
+impl Overlay {
+    fn next(self) -> Self {
+        match self {
+            Overlay::None => Overlay::Markers,
+            Overlay::Markers => Overlay::Regions,
+            Overlay::Regions => Overlay::None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Overlay::None => "none",
+            Overlay::Markers => "markers",
+            Overlay::Regions => "regions",
+        }
+    }
+}
+
+/// A numeric or boolean param of a pipeline algorithm step.
+struct Tunable {
+    step: usize,
+    algorithm: String,
+    schema: ParamSchema,
+}
+
+struct Explorer {
+    cfg: Config,
+    seed: u64,
+    save_path: String,
+    tunables: Vec<Tunable>,
+    selected: usize,
+    overlay: Overlay,
+    grid: Grid<Tile>,
+    semantic: Option<SemanticLayers>,
+    status: String,
+}
+
+/// Opens the explorer; returns when the user quits.
+pub fn run(cfg: Config, seed: u64, save_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut explorer = Explorer::new(cfg, seed, save_path);
+    explorer.regenerate();
+
+    let mut out = std::io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = explorer.event_loop(&mut out);
+    execute!(
+        out,
+        ResetColor,
+        cursor::Show,
+        terminal::LeaveAlternateScreen
+    )?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+impl Explorer {
+    fn new(cfg: Config, seed: u64, save_path: &str) -> Self {
+        let tunables = tunables(&cfg);
+        let grid = Grid::new(cfg.width, cfg.height);
+        Self {
+            cfg,
+            seed,
+            save_path: save_path.to_string(),
+            tunables,
+            selected: 0,
+            overlay: Overlay::None,
+            grid,
+            semantic: None,
+            status: String::new(),
+        }
+    }
+
+    fn event_loop(&mut self, out: &mut impl Write) -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            self.draw(out)?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Applies a hotkey; returns false to quit.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('n') | KeyCode::Char(' ') => {
+                self.seed = self.seed.wrapping_add(1);
+                self.regenerate();
+            }
+            KeyCode::Char('p') => {
+                self.seed = self.seed.wrapping_sub(1);
+                self.regenerate();
+            }
+            KeyCode::Char('r') => {
+                self.seed = crate::random_seed();
+                self.regenerate();
+            }
+            KeyCode::Up if self.selected > 0 => self.selected -= 1,
+            KeyCode::Down if self.selected + 1 < self.tunables.len() => self.selected += 1,
+            KeyCode::Left | KeyCode::Char('-') => self.adjust(-1.0),
+            KeyCode::Right | KeyCode::Char('+') | KeyCode::Char('=') => self.adjust(1.0),
+            KeyCode::Char('o') => {
+                self.overlay = self.overlay.next();
+                self.extract_semantic();
+            }
+            KeyCode::Char('s') => {
+                self.status = match self.save() {
+                    Ok(()) => format!("saved {}", self.save_path),
+                    Err(e) => format!("save failed: {}", e),
+                };
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn regenerate(&mut self) {
+        match self.cfg.generate(self.seed) {
+            Ok(grid) => {
+                self.grid = grid;
+                self.status.clear();
+            }
+            Err(e) => self.status = e.to_string(),
+        }
+        self.semantic = None;
+        self.extract_semantic();
+    }
+
+    fn extract_semantic(&mut self) {
+        if self.overlay == Overlay::None || self.semantic.is_some() {
+            return;
+        }
+        let mut semantic = self
+            .cfg
+            .extractor()
+            .extract(&self.grid, &mut Rng::new(self.seed));
+        self.cfg.apply_marker_overrides(&mut semantic);
+        self.semantic = Some(semantic);
+    }
+
+    /// Steps the selected param by `direction` and regenerates.
+    fn adjust(&mut self, direction: f64) {
+        let Some(tunable) = self.tunables.get(self.selected) else {
+            return;
+        };
+        let current = param_value(&self.cfg, tunable);
+        let value = match tunable.schema.ty {
+            ParamType::Bool => json!(!current.as_bool().unwrap_or(false)),
+            ParamType::Integer => {
+                let (min, max) = tunable.schema.range.unwrap_or((0.0, f64::MAX));
+                let v = current.as_f64().unwrap_or(0.0) + direction;
+                json!(v.clamp(min, max) as u64)
+            }
+            _ => {
+                let (min, max) = tunable.schema.range.unwrap_or((f64::MIN, f64::MAX));
+                let step = tunable
+                    .schema
+                    .range
+                    .map(|(lo, hi)| (hi - lo) / 20.0)
+                    .unwrap_or(0.05);
+                let v = current.as_f64().unwrap_or(0.0) + direction * step;
+                json!((v.clamp(min, max) * 1e4).round() / 1e4)
+            }
+        };
+        set_param(&mut self.cfg, tunable.step, &tunable.schema.name, value);
+        self.regenerate();
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut cfg = self.cfg.clone();
+        cfg.seed = Some(self.seed);
+        let text = match config::ConfigFormat::from_extension(&self.save_path) {
+            Some(config::ConfigFormat::Toml) => toml::to_string_pretty(&cfg)?,
+            Some(config::ConfigFormat::Ron) => return Err("saving RON is not supported".into()),
+            _ => serde_json::to_string_pretty(&cfg)?,
+        };
+        if let Some(parent) = std::path::Path::new(&self.save_path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.save_path, text)?;
+        Ok(())
+    }
+
+    fn draw(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let (cols, rows) = terminal::size()?;
+        let view_w = (cols.saturating_sub(PANEL_WIDTH + 1) as usize).min(self.grid.width());
+        let view_h = (rows as usize).min(self.grid.height());
+        queue!(out, terminal::Clear(terminal::ClearType::All))?;
+
+        let cells = self.cells();
+        for y in 0..view_h {
+            queue!(out, cursor::MoveTo(0, y as u16))?;
+            let mut color = None;
+            for x in 0..view_w {
+                let (glyph, c) = cells[y * self.grid.width() + x];
+                if color != Some(c) {
+                    queue!(out, SetForegroundColor(c))?;
+                    color = Some(c);
+                }
+                queue!(out, Print(glyph))?;
+            }
+        }
+
+        let panel_x = view_w as u16 + 1;
+        queue!(out, ResetColor)?;
+        for (i, line) in self.panel(view_w, view_h).iter().enumerate() {
+            if i as u16 >= rows {
+                break;
+            }
+            let line: String = line.chars().take(PANEL_WIDTH as usize).collect();
+            queue!(out, cursor::MoveTo(panel_x, i as u16), Print(line))?;
+        }
+        out.flush()
+    }
+
+    /// Glyph and color for every cell, row-major.
+    fn cells(&self) -> Vec<(char, Color)> {
+        let w = self.grid.width();
+        let mut cells: Vec<(char, Color)> = self
+            .grid
+            .iter()
+            .map(|(_, _, t)| {
+                if t.is_floor() {
+                    ('.', Color::Grey)
+                } else {
+                    ('#', Color::DarkGrey)
+                }
+            })
+            .collect();
+        let Some(semantic) = &self.semantic else {
+            return cells;
+        };
+        match self.overlay {
+            Overlay::None => {}
+            Overlay::Regions => {
+                for (i, region) in semantic.regions.iter().enumerate() {
+                    let color = REGION_COLORS[i % REGION_COLORS.len()];
+                    for &(x, y) in &region.cells {
+                        if let Some(cell) = cells.get_mut(y as usize * w + x as usize) {
+                            cell.1 = color;
+                        }
+                    }
+                }
+            }
+            Overlay::Markers => {
+                let legend = Legend::default();
+                for marker in &semantic.markers {
+                    if let Some(cell) = cells.get_mut(marker.y as usize * w + marker.x as usize) {
+                        let entry = legend.marker(&marker.tag());
+                        let glyph = entry.glyph.chars().next().unwrap_or('?');
+                        let color =
+                            entry
+                                .color
+                                .map_or(Color::Yellow, |[r, g, b]| Color::Rgb { r, g, b });
+                        *cell = (glyph, color);
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    fn panel(&self, view_w: usize, view_h: usize) -> Vec<String> {
+        let floors = self.grid.count(|t| t.is_floor());
+        let total = (self.grid.width() * self.grid.height()).max(1);
+        let mut lines = vec![
+            "terrain-forge explorer".to_string(),
+            String::new(),
+            format!("pipeline: {}", pipeline_summary(&self.cfg)),
+            format!("seed:     {}", self.seed),
+            format!("size:     {}x{}", self.grid.width(), self.grid.height()),
+            format!("floor:    {:.1}%", floors as f64 * 100.0 / total as f64),
+            format!("overlay:  {}", self.overlay.name()),
+        ];
+        if let Some(semantic) = &self.semantic {
+            lines.push(format!(
+                "semantic: {} regions, {} markers",
+                semantic.regions.len(),
+                semantic.markers.len()
+            ));
+        }
+        if view_w < self.grid.width() || view_h < self.grid.height() {
+            lines.push(format!("view:     cropped to {}x{}", view_w, view_h));
+        }
+
+        lines.push(String::new());
+        lines.push("params (up/down, left/right):".to_string());
+        if self.tunables.is_empty() {
+            lines.push("  (none)".to_string());
+        }
+        for (i, tunable) in self.tunables.iter().enumerate() {
+            lines.push(format!(
+                "{} {}.{} = {}",
+                if i == self.selected { '>' } else { ' ' },
+                tunable.algorithm,
+                tunable.schema.name,
+                param_value(&self.cfg, tunable)
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push("n/p  next/prev seed  r random".to_string());
+        lines.push("o    cycle overlay".to_string());
+        lines.push(format!("s    save to {}", self.save_path));
+        lines.push("q    quit".to_string());
+        if !self.status.is_empty() {
+            lines.push(String::new());
+            lines.push(self.status.clone());
+        }
+        lines
+    }
+}
+
+/// Numeric and boolean params of every top-level algorithm step.
+fn tunables(cfg: &Config) -> Vec<Tunable> {
+    let mut out = Vec::new();
+    for (step, spec) in cfg.pipeline.iter().enumerate() {
+        let PipelineStepSpec::Algorithm(spec) = spec else {
+            continue;
+        };
+        let Some(schema) = ops::describe_algorithm(spec.name()) else {
+            continue;
+        };
+        for param in schema.params {
+            if matches!(
+                param.ty,
+                ParamType::Integer | ParamType::Float | ParamType::Bool
+            ) {
+                out.push(Tunable {
+                    step,
+                    algorithm: schema.name.clone(),
+                    schema: param,
+                });
+            }
+        }
+    }
+    out
+}
+
+/// The step's explicit value for a param, else its default.
+fn param_value(cfg: &Config, tunable: &Tunable) -> Value {
+    let explicit = match cfg.pipeline.get(tunable.step) {
+        Some(PipelineStepSpec::Algorithm(spec)) => spec
+            .params()
+            .and_then(|p| p.get(&tunable.schema.name))
+            .cloned(),
+        _ => None,
+    };
+    explicit
+        .or_else(|| tunable.schema.default.clone())
+        .unwrap_or(Value::Null)
+}
+
+fn set_param(cfg: &mut Config, step: usize, name: &str, value: Value) {
+    let Some(PipelineStepSpec::Algorithm(spec)) = cfg.pipeline.get_mut(step) else {
+        return;
+    };
+    let mut params = spec.params().cloned().unwrap_or_default();
+    params.insert(name.to_string(), value);
+    *spec = AlgorithmSpec::WithParams {
+        type_name: spec.name().to_string(),
+        params,
+    };
+}
+
+fn pipeline_summary(cfg: &Config) -> String {
+    let names: Vec<&str> = cfg
+        .pipeline
+        .iter()
+        .filter_map(|step| match step {
+            PipelineStepSpec::Algorithm(spec) => Some(spec.name()),
+            PipelineStepSpec::Op(_) => None,
+        })
+        .collect();
+    if names.is_empty() {
+        "bsp".to_string()
+    } else {
+        names.join(" > ")
+    }
+}
@@ -1,20 +1,24 @@
 //! TerrainForge Demo CLI
 
+mod animate;
+mod batch;
 mod cli;
-mod config;
+mod explore;
 mod manifest;
 mod render;
 mod report;
 mod runner;
+mod serve;
 
 use clap::Parser;
 use cli::{Cli, Command, OutputFlags};
 use std::{fs, time::Instant};
-use terrain_forge::{algorithms, constraints, Grid, SemanticLayers, Tile};
+use terrain_forge::{algorithms, config, constraints, Grid, Rng, SemanticLayers, Tile};
 
 #[derive(Clone, Copy, Default)]
 struct RenderFlags {
     text: bool,
+    ansi: bool,
     semantic: bool,
     regions: bool,
     masks: bool,
@@ -59,6 +63,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             height,
             scale,
             text,
+            ansi,
             semantic,
             regions,
             masks,
@@ -73,6 +78,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             height,
             scale,
             text,
+            ansi,
             semantic,
             regions,
             masks,
@@ -86,6 +92,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             seed,
             output,
             text,
+            ansi,
             semantic,
             regions,
             masks,
@@ -97,6 +104,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             seed,
             output,
             text,
+            ansi,
             semantic,
             regions,
             masks,
@@ -112,6 +120,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             configs,
         } => handle_compare(items, seed, output, configs)?,
 
+        Command::Diff {
+            a,
+            b,
+            seed,
+            seed_b,
+            output,
+            width,
+            height,
+        } => handle_diff(&a, b.as_deref(), seed, seed_b, &output, (width, height))?,
+
         Command::Demo {
             id,
             run,
@@ -130,6 +148,79 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             constraints_only,
         )?,
 
+        Command::Heightmap {
+            source,
+            seed,
+            output,
+            width,
+            height,
+            frequency,
+            big_endian,
+            mesh,
+            step,
+        } => handle_heightmap(
+            &source,
+            seed,
+            &output,
+            (width, height),
+            frequency,
+            big_endian,
+            mesh.as_deref().map(|path| (path, step)),
+        )?,
+
+        Command::Animate {
+            spec,
+            seed,
+            output,
+            width,
+            height,
+            scale,
+            delay,
+            max_frames,
+        } => handle_animate(
+            &spec,
+            seed,
+            &output,
+            (width, height),
+            animate::Options {
+                scale,
+                delay_ms: delay,
+                max_frames,
+            },
+        )?,
+
+        Command::Batch {
+            spec,
+            count,
+            seed,
+            output,
+            width,
+            height,
+            json,
+            no_images,
+        } => handle_batch(
+            &spec,
+            &output,
+            (width, height),
+            seed,
+            batch::Options {
+                count,
+                start_seed: 0,
+                images: !no_images,
+                json,
+            },
+        )?,
+
+        Command::Explore {
+            spec,
+            seed,
+            output,
+            width,
+            height,
+        } => handle_explore(&spec, seed, &output, (width, height))?,
+
+        Command::Serve { addr } => serve::run(&addr)?,
+
         Command::List => handle_list(),
     }
 
@@ -145,6 +236,7 @@ fn handle_gen(
     height: usize,
     scale: usize,
     text: bool,
+    ansi: bool,
     semantic: bool,
     regions: bool,
     masks: bool,
@@ -154,13 +246,14 @@ fn handle_gen(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let seed = seed.unwrap_or_else(random_seed);
     let output_flags = OutputFlags::new(constraints_report, constraints_only);
-    let mut cfg = config::parse_shorthand(&spec);
+    let mut cfg = config::Config::from_shorthand(&spec)?;
     cfg.width = width * scale;
     cfg.height = height * scale;
     cfg.seed = Some(seed);
 
     let render_flags = RenderFlags {
         text,
+        ansi,
         semantic,
         regions,
         masks,
@@ -176,6 +269,7 @@ fn handle_run(
     seed: Option<u64>,
     output: String,
     text: bool,
+    ansi: bool,
     semantic: bool,
     regions: bool,
     masks: bool,
@@ -188,6 +282,7 @@ fn handle_run(
     let output_flags = OutputFlags::new(constraints_report, constraints_only);
     let render_flags = RenderFlags {
         text,
+        ansi,
         semantic,
         regions,
         masks,
@@ -214,13 +309,13 @@ fn handle_compare(
     let mut grids: Vec<(String, Grid<Tile>)> = Vec::new();
 
     for item in &items {
-        let (name, grid) = if configs || item.ends_with(".json") {
+        let (name, grid) = if configs || config::ConfigFormat::from_extension(item).is_some() {
             let cfg = config::Config::load(item)?;
             let name = cfg.name.clone().unwrap_or_else(|| item.clone());
             let (grid, _) = runner::generate(&cfg, seed);
             (name, grid)
         } else {
-            let cfg = config::parse_shorthand(item);
+            let cfg = config::Config::from_shorthand(item)?;
             let (grid, _) = runner::generate(&cfg, seed);
             (item.clone(), grid)
         };
@@ -243,6 +338,62 @@ fn handle_compare(
     Ok(())
 }
 
+fn handle_diff(
+    spec_a: &str,
+    spec_b: Option<&str>,
+    seed: Option<u64>,
+    seed_b: Option<u64>,
+    output: &str,
+    size: (usize, usize),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let spec_b = spec_b.unwrap_or(spec_a);
+    let cfg_a = load_spec(spec_a, size)?;
+    let cfg_b = load_spec(spec_b, size)?;
+    let seed_a = seed.or(cfg_a.seed).unwrap_or_else(random_seed);
+    let seed_b = seed_b.or(seed).or(cfg_b.seed).unwrap_or(seed_a);
+
+    let (grid_a, _) = runner::generate(&cfg_a, seed_a);
+    let (grid_b, _) = runner::generate(&cfg_b, seed_b);
+    if (grid_a.width(), grid_a.height()) != (grid_b.width(), grid_b.height()) {
+        return Err(format!(
+            "diff needs equal sizes ({}x{} vs {}x{})",
+            grid_a.width(),
+            grid_a.height(),
+            grid_b.width(),
+            grid_b.height()
+        )
+        .into());
+    }
+
+    let semantic_a = cfg_a.extractor().extract(&grid_a, &mut Rng::new(seed_a));
+    let semantic_b = cfg_b.extractor().extract(&grid_b, &mut Rng::new(seed_b));
+    let changed = grid_a
+        .iter()
+        .filter(|(x, y, t)| t.is_floor() != grid_b[(*x, *y)].is_floor())
+        .count();
+
+    let label_a = format!("A {}", seed_a);
+    let label_b = format!("B {}", seed_b);
+    render::save_png(
+        &render::render_diff((&label_a, &grid_a), (&label_b, &grid_b)),
+        output,
+    )?;
+
+    println!("A: {} (seed: {})", spec_a, seed_a);
+    println!("B: {} (seed: {})", spec_b, seed_b);
+    print!(
+        "{}",
+        report::format_diff(
+            &report::GridStats::new(&grid_a, &semantic_a),
+            &report::GridStats::new(&grid_b, &semantic_b),
+            changed,
+            grid_a.width() * grid_a.height(),
+        )
+    );
+    println!("Saved to {}", output);
+    Ok(())
+}
+
 fn handle_demo(
     id: Option<String>,
     run: Option<String>,
@@ -284,6 +435,71 @@ fn handle_demo(
     Ok(())
 }
 
+fn handle_heightmap(
+    source: &str,
+    seed: Option<u64>,
+    output: &str,
+    (width, height): (usize, usize),
+    frequency: f64,
+    big_endian: bool,
+    mesh: Option<(&str, usize)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use terrain_forge::algorithms::DiamondSquare;
+    use terrain_forge::export::{heightmap, ByteOrder, HeightmapConfig, Mesh, MeshConfig};
+    use terrain_forge::noise::{NoiseExt, NoiseSource, Perlin, Simplex, Value};
+
+    fn sample(noise: impl NoiseSource, w: usize, h: usize, f: f64) -> Vec<Vec<f64>> {
+        (0..h)
+            .map(|y| {
+                (0..w)
+                    .map(|x| noise.sample(x as f64 * f, y as f64 * f))
+                    .collect()
+            })
+            .collect()
+    }
+
+    let seed = seed.unwrap_or_else(random_seed);
+    let values = match source {
+        "diamond_square" => DiamondSquare::default().heightmap(width, height, seed),
+        "perlin" => sample(Perlin::new(seed).fbm(5, 2.0, 0.5), width, height, frequency),
+        "simplex" => sample(
+            Simplex::new(seed).fbm(5, 2.0, 0.5),
+            width,
+            height,
+            frequency,
+        ),
+        "value" => sample(Value::new(seed).fbm(5, 2.0, 0.5), width, height, frequency),
+        other => return Err(format!("Unknown heightmap source: {}", other).into()),
+    };
+    let config = HeightmapConfig::default().with_byte_order(if big_endian {
+        ByteOrder::Big
+    } else {
+        ByteOrder::Little
+    });
+
+    match std::path::Path::new(output)
+        .extension()
+        .and_then(|e| e.to_str())
+    {
+        Some("raw") | Some("r16") => heightmap::save_raw16(output, &values, &config)?,
+        _ => heightmap::save_png16(output, &values, &config)?,
+    }
+    println!(
+        "Saved {}x{} heightmap ({}, seed {}) to {}",
+        width, height, source, seed, output
+    );
+    if let Some((path, step)) = mesh {
+        let mesh = Mesh::from_heightmap(&values, &MeshConfig::default().with_step(step));
+        terrain_forge::export::mesh::save(path, &mesh)?;
+        println!(
+            "Saved mesh ({} triangles) to {}",
+            mesh.triangle_count(),
+            path
+        );
+    }
+    Ok(())
+}
+
 fn handle_list() {
     println!("Available algorithms:");
     for name in algorithms::list() {
@@ -325,6 +541,14 @@ fn handle_generate(
         render_flags.text_semantic(),
     )?;
 
+    if render_flags.ansi && !output_flags.constraints_only {
+        let mut view = terrain_forge::render::TextRender::new(&grid).with_color(true);
+        if let Some(semantic) = &semantic {
+            view = view.with_semantic(semantic);
+        }
+        println!("{}", view);
+    }
+
     if !output_flags.constraints_only && !render_flags.needs_semantic() {
         print!("{}", report::format_metrics(label, &grid, seed, elapsed));
     }
@@ -360,6 +584,15 @@ fn render_output(
     output_flags: OutputFlags,
     text_semantic: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    if !matches!(kind, manifest::OutputKind::Text) && output.ends_with(".svg") {
+        let svg_config = terrain_forge::export::SvgConfig::default();
+        terrain_forge::export::svg::save(output, grid, semantic.as_ref(), &svg_config)?;
+        if !output_flags.constraints_only {
+            println!("Saved SVG to {}", output);
+        }
+        return Ok(());
+    }
+
     match kind {
         manifest::OutputKind::Text => {
             let txt_path = output.replace(".png", ".txt");
@@ -657,7 +890,7 @@ fn build_config_for_run(
         c.seed = Some(seed);
         c
     } else if let Some(spec) = &run.spec {
-        let mut c = config::parse_shorthand(spec);
+        let mut c = config::Config::from_shorthand(spec)?;
         c.width = width;
         c.height = height;
         c.seed = Some(seed);
@@ -697,6 +930,82 @@ fn print_demo_list(manifest: &manifest::Manifest) {
     }
 }
 
+fn handle_animate(
+    spec: &str,
+    seed: Option<u64>,
+    output: &str,
+    (width, height): (usize, usize),
+    options: animate::Options,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = load_spec(spec, (width, height))?;
+    let seed = seed.or(cfg.seed).unwrap_or_else(random_seed);
+    if let Some(parent) = std::path::Path::new(output).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let frames = animate::animate(&cfg, seed, output, &options)?;
+    println!(
+        "Animated {} (seed: {}): {} frames -> {}",
+        spec, seed, frames, output
+    );
+    Ok(())
+}
+
+fn handle_batch(
+    spec: &str,
+    output: &str,
+    size: (usize, usize),
+    seed: Option<u64>,
+    mut options: batch::Options,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = load_spec(spec, size)?;
+    options.start_seed = seed.or(cfg.seed).unwrap_or(1);
+    let rows = batch::run(&cfg, output, &options)?;
+
+    let n = rows.len().max(1) as f64;
+    let density = rows.iter().map(|r| r.density).sum::<f64>() / n;
+    let time = rows.iter().map(|r| r.time_ms).sum::<f64>() / n;
+    println!(
+        "Batch {}: {} seeds from {} -> {}",
+        spec,
+        rows.len(),
+        options.start_seed,
+        output
+    );
+    println!("  Mean density: {:.1}%", density * 100.0);
+    println!("  Mean time: {:.2}ms", time);
+    let checked: Vec<bool> = rows.iter().filter_map(|r| r.constraints_passed).collect();
+    if !checked.is_empty() {
+        let passed = checked.iter().filter(|p| **p).count();
+        println!("  Constraints passed: {}/{}", passed, checked.len());
+    }
+    Ok(())
+}
+
+fn handle_explore(
+    spec: &str,
+    seed: Option<u64>,
+    output: &str,
+    size: (usize, usize),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = load_spec(spec, size)?;
+    let seed = seed.or(cfg.seed).unwrap_or_else(random_seed);
+    explore::run(cfg, seed, output)
+}
+
+/// Loads a config file, or builds one from shorthand at the given size.
+fn load_spec(
+    spec: &str,
+    (width, height): (usize, usize),
+) -> Result<config::Config, Box<dyn std::error::Error>> {
+    if config::ConfigFormat::from_extension(spec).is_some() {
+        return Ok(config::Config::load(spec)?);
+    }
+    let mut cfg = config::Config::from_shorthand(spec)?;
+    cfg.width = width;
+    cfg.height = height;
+    Ok(cfg)
+}
+
 fn random_seed() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
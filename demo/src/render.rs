@@ -3,6 +3,7 @@
 use image::{ImageBuffer, Rgb, RgbImage};
 use std::collections::HashMap;
 use terrain_forge::constraints::ConstraintReport;
+use terrain_forge::render::{self, Legend, TextRender};
 use terrain_forge::{Grid, SemanticLayers, Tile};
 
 const FLOOR_COLOR: Rgb<u8> = Rgb([200, 200, 200]);
@@ -30,6 +31,10 @@ const MEDIUM_COLOR: Rgb<u8> = Rgb([120, 120, 180]);
 const SMALL_COLOR: Rgb<u8> = Rgb([180, 120, 120]);
 const TINY_COLOR: Rgb<u8> = Rgb([180, 180, 120]);
 
+// Diff colors
+const REMOVED_COLOR: Rgb<u8> = Rgb([220, 70, 70]); // Floor only in the first grid
+const ADDED_COLOR: Rgb<u8> = Rgb([70, 220, 70]); // Floor only in the second grid
+
 // Mask colors
 const WALKABLE_COLOR: Rgb<u8> = Rgb([0, 255, 0]);
 const NO_SPAWN_COLOR: Rgb<u8> = Rgb([255, 0, 0]);
@@ -49,13 +54,8 @@ pub fn render_grid(grid: &Grid<Tile>) -> RgbImage {
 }
 
 pub fn render_text(grid: &Grid<Tile>) -> String {
-    let mut out = String::new();
-    for y in 0..grid.height() {
-        for x in 0..grid.width() {
-            out.push(if grid[(x, y)].is_floor() { '.' } else { '#' });
-        }
-        out.push('\n');
-    }
+    let mut out = TextRender::new(grid).to_string();
+    out.push('\n');
     out
 }
 
@@ -99,6 +99,28 @@ pub fn render_comparison(grids: &[(&str, &Grid<Tile>)], cols: usize) -> RgbImage
     img
 }
 
+/// Renders `a` and `b` side by side plus a third panel highlighting changed
+/// cells (red: floor only in `a`, green: floor only in `b`). Grids must be
+/// the same size.
+pub fn render_diff(a: (&str, &Grid<Tile>), b: (&str, &Grid<Tile>)) -> RgbImage {
+    let mut img = render_comparison(&[a, b], 3);
+    let ox = a.1.width() as u32 * 2;
+    let oy = 12;
+
+    for (x, y, tile) in a.1.iter() {
+        let other = b.1[(x, y)];
+        let color = match (tile.is_floor(), other.is_floor()) {
+            (true, false) => REMOVED_COLOR,
+            (false, true) => ADDED_COLOR,
+            (true, true) => FLOOR_COLOR,
+            (false, false) => WALL_COLOR,
+        };
+        img.put_pixel(ox + x as u32, oy + y as u32, color);
+    }
+    draw_text(&mut img, "diff", ox + 2, 2, Rgb([100, 180, 255]));
+    img
+}
+
 // Simple 5x7 bitmap font for basic ASCII
 fn draw_text(img: &mut RgbImage, text: &str, x: u32, y: u32, color: Rgb<u8>) {
     let mut cx = x;
@@ -272,43 +294,10 @@ pub fn render_grid_with_semantic(grid: &Grid<Tile>, semantic: &Option<SemanticLa
 }
 
 pub fn render_text_with_semantic(grid: &Grid<Tile>, semantic: &Option<SemanticLayers>) -> String {
-    let mut out = String::new();
-
-    if let Some(semantic) = semantic {
-        // Create marker lookup
-        let mut marker_map = std::collections::HashMap::new();
-        for marker in &semantic.markers {
-            marker_map.insert((marker.x, marker.y), marker.tag());
-        }
-
-        for y in 0..grid.height() {
-            for x in 0..grid.width() {
-                let tile = grid[(x, y)];
-
-                if let Some(tag) = marker_map.get(&(x as u32, y as u32)) {
-                    // Show marker with specific character
-                    let marker_char = match tag.as_str() {
-                        "loot_slot" => '$',
-                        "boss_spawn" => 'B',
-                        "light_anchor" => '*',
-                        "terminal_a" => 'A',
-                        "terminal_b" => 'B',
-                        _ => '?',
-                    };
-                    out.push(marker_char);
-                } else if tile.is_floor() {
-                    out.push('.');
-                } else {
-                    out.push('#');
-                }
-            }
-            out.push('\n');
-        }
-    } else {
-        out = render_text(grid);
+    match semantic {
+        Some(semantic) => render::render_text_with_semantic(grid, semantic, &Legend::default()),
+        None => render_text(grid),
     }
-
-    out
 }
 
 pub fn render_text_with_semantic_and_report(
@@ -72,3 +72,59 @@ pub fn format_metrics(name: &str, grid: &Grid<Tile>, seed: u64, elapsed: Duratio
     out.push_str(&format!("  Time: {:?}\n", elapsed));
     out
 }
+
+/// Summary numbers compared by `format_diff`.
+pub struct GridStats {
+    pub floors: usize,
+    pub density: f64,
+    pub connectivity: f32,
+    pub regions: usize,
+    pub markers: usize,
+}
+
+impl GridStats {
+    pub fn new(grid: &Grid<Tile>, semantic: &SemanticLayers) -> Self {
+        let floors = grid.count(|t| t.is_floor());
+        Self {
+            floors,
+            density: floors as f64 / (grid.width() * grid.height()).max(1) as f64,
+            connectivity: constraints::validate_connectivity(grid),
+            regions: semantic.regions.len(),
+            markers: semantic.markers.len(),
+        }
+    }
+}
+
+pub fn format_diff(a: &GridStats, b: &GridStats, changed: usize, total: usize) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "{:<14} {:>10} {:>10} {:>10}\n",
+        "Metric", "A", "B", "Delta"
+    ));
+    let mut row = |name: &str, a: f64, b: f64, precision: usize| {
+        out.push_str(&format!(
+            "{:<14} {:>10.p$} {:>10.p$} {:>+10.p$}\n",
+            name,
+            a,
+            b,
+            b - a,
+            p = precision
+        ));
+    };
+    row("Floors", a.floors as f64, b.floors as f64, 0);
+    row("Density %", a.density * 100.0, b.density * 100.0, 1);
+    row(
+        "Connectivity",
+        a.connectivity as f64,
+        b.connectivity as f64,
+        2,
+    );
+    row("Regions", a.regions as f64, b.regions as f64, 0);
+    row("Markers", a.markers as f64, b.markers as f64, 0);
+    out.push_str(&format!(
+        "Changed cells: {} ({:.1}%)\n",
+        changed,
+        changed as f64 / total.max(1) as f64 * 100.0
+    ));
+    out
+}
@@ -1,24 +1,26 @@
 use std::time::{Duration, Instant};
 
-use terrain_forge::{constraints, Grid, SemanticExtractor, SemanticLayers, Tile};
-
-use crate::config;
+use terrain_forge::{config, constraints, Grid, SemanticLayers, Tile};
 
 pub fn generate(cfg: &config::Config, seed: u64) -> (Grid<Tile>, Duration) {
     let mut grid = Grid::new(cfg.width, cfg.height);
-    let pipeline = config::build_pipeline(cfg);
+    let pipeline = cfg.build_pipeline().unwrap_or_else(|err| {
+        eprintln!("Failed to build pipeline: {}", err);
+        config::Config::default()
+            .build_pipeline()
+            .expect("default pipeline")
+    });
 
     let start = Instant::now();
     if let Err(err) = pipeline.execute_seed(&mut grid, seed) {
         eprintln!("Pipeline error: {}", err);
     }
-    if config::effects_need_semantic(&cfg.effects) {
-        let mut rng = terrain_forge::Rng::new(seed);
-        let extractor = select_extractor(cfg);
-        let semantic = extractor.extract(&grid, &mut rng);
-        config::apply_effects(&mut grid, &cfg.effects, Some(&semantic));
-    } else {
-        config::apply_effects(&mut grid, &cfg.effects, None);
+    let semantic = cfg.effects_need_semantic().then(|| {
+        cfg.extractor()
+            .extract(&grid, &mut terrain_forge::Rng::new(seed))
+    });
+    if let Err(err) = cfg.apply_effects(&mut grid, semantic.as_ref()) {
+        eprintln!("{}", err);
     }
     let elapsed = start.elapsed();
 
@@ -41,7 +43,7 @@ pub fn generate_grid_and_semantic(
     need_semantic: bool,
 ) -> GenerateResult {
     if let Some(req) = &cfg.requirements {
-        let extractor = select_extractor(cfg);
+        let extractor = cfg.extractor();
         let attempts = req.attempts();
         let requirements = req.to_requirements();
         let mut constraint_set = constraints::ConstraintSet::new();
@@ -55,7 +57,7 @@ pub fn generate_grid_and_semantic(
             let mut rng = terrain_forge::Rng::new(attempt_seed);
             let mut semantic = extractor.extract(&grid, &mut rng);
             if !cfg.markers.is_empty() {
-                config::apply_marker_overrides(&cfg.markers, &mut semantic);
+                cfg.apply_marker_overrides(&mut semantic);
             }
 
             let mut ctx = constraints::ConstraintContext::new(&grid);
@@ -85,24 +87,15 @@ pub fn generate_grid_and_semantic(
     }
 
     let mut rng = terrain_forge::Rng::new(seed);
-    let extractor = select_extractor(cfg);
+    let extractor = cfg.extractor();
     let mut semantic = extractor.extract(&grid, &mut rng);
     if !cfg.markers.is_empty() {
-        config::apply_marker_overrides(&cfg.markers, &mut semantic);
+        cfg.apply_marker_overrides(&mut semantic);
     }
     let report = build_constraint_report(cfg, &grid, Some(&semantic));
     Ok((grid, Some(semantic), elapsed, report))
 }
 
-pub fn select_extractor(cfg: &config::Config) -> SemanticExtractor {
-    match config::primary_algorithm_name(cfg) {
-        Some("cellular") => SemanticExtractor::for_caves(),
-        Some("bsp" | "rooms" | "room_accretion") => SemanticExtractor::for_rooms(),
-        Some("maze") => SemanticExtractor::for_mazes(),
-        _ => SemanticExtractor::default(),
-    }
-}
-
 pub fn build_constraint_report(
     cfg: &config::Config,
     grid: &Grid<Tile>,
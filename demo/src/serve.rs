@@ -0,0 +1,267 @@
+//! Local HTTP preview server
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+use terrain_forge::{config, ops};
+
+use crate::{batch, render};
+
+/// Largest accepted map side, to keep requests cheap.
+const MAX_SIZE: usize = 1024;
+/// Largest accepted request head in bytes.
+const MAX_HEAD: usize = 64 * 1024;
+
+/// Serves the preview page on `addr` until the process is killed.
+pub fn run(addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Serving on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        std::thread::spawn(move || {
+            if let Err(e) = handle(stream) {
+                eprintln!("serve: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: "200 OK",
+            content_type,
+            body,
+        }
+    }
+
+    fn error(status: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: message.into().into_bytes(),
+        }
+    }
+}
+
+fn handle(stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain headers; bodies are not used.
+    let mut head = request_line.len();
+    loop {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line)?;
+        head += n;
+        if n == 0 || line == "\r\n" || line == "\n" || head > MAX_HEAD {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => route(target),
+        (Some(_), Some(_)) => Response::error("405 Method Not Allowed", "only GET is supported"),
+        _ => Response::error("400 Bad Request", "malformed request"),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn route(target: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = parse_query(query);
+    match path {
+        "/" | "/index.html" => Response::ok("text/html; charset=utf-8", page().into_bytes()),
+        "/map.png" => match map_png(&query) {
+            Ok(png) => Response::ok("image/png", png),
+            Err(e) => Response::error("400 Bad Request", e.to_string()),
+        },
+        "/metrics.json" => match metrics(&query) {
+            Ok(json) => Response::ok("application/json", json.into_bytes()),
+            Err(e) => Response::error("400 Bad Request", e.to_string()),
+        },
+        _ => Response::error("404 Not Found", "not found"),
+    }
+}
+
+/// Builds the config from `config` (JSON text) or `spec` (shorthand),
+/// sized by `width`/`height`, and the seed.
+fn request_config(
+    query: &HashMap<String, String>,
+) -> Result<(config::Config, u64), Box<dyn std::error::Error>> {
+    let mut cfg = match query.get("config").filter(|c| !c.trim().is_empty()) {
+        Some(json) => config::Config::from_json(json)?,
+        None => config::Config::from_shorthand(query.get("spec").map_or("bsp", |s| s.as_str()))?,
+    };
+    let size = |key: &str, default: usize| -> Result<usize, String> {
+        match query.get(key).filter(|v| !v.is_empty()) {
+            Some(v) => v
+                .parse::<usize>()
+                .map_err(|_| format!("invalid {}: {}", key, v)),
+            None => Ok(default),
+        }
+    };
+    cfg.width = size("width", cfg.width)?.clamp(1, MAX_SIZE);
+    cfg.height = size("height", cfg.height)?.clamp(1, MAX_SIZE);
+    cfg.build_pipeline()?;
+
+    let seed = match query.get("seed").filter(|v| !v.is_empty()) {
+        Some(v) => v.parse().map_err(|_| format!("invalid seed: {}", v))?,
+        None => cfg.seed.unwrap_or(1),
+    };
+    Ok((cfg, seed))
+}
+
+fn map_png(query: &HashMap<String, String>) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (cfg, seed) = request_config(query)?;
+    let (grid, semantic, _) = batch::measure(&cfg, seed);
+    let img = match query.get("overlay").map_or("none", |s| s.as_str()) {
+        "markers" => render::render_grid_with_semantic(&grid, &Some(semantic)),
+        "regions" => render::render_regions_png(&grid, &semantic),
+        "masks" => render::render_masks_png(&grid, &semantic),
+        "connectivity" => render::render_connectivity_png(&grid, &semantic),
+        _ => render::render_grid(&grid),
+    };
+    let mut png = Vec::new();
+    img.write_to(
+        &mut std::io::Cursor::new(&mut png),
+        image::ImageOutputFormat::Png,
+    )?;
+    Ok(png)
+}
+
+fn metrics(query: &HashMap<String, String>) -> Result<String, Box<dyn std::error::Error>> {
+    let (cfg, seed) = request_config(query)?;
+    let (_, _, row) = batch::measure(&cfg, seed);
+    Ok(serde_json::to_string(&row)?)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(k), percent_decode(v))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(hi), Some(lo)) => {
+                        out.push((hi * 16 + lo) as u8);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn page() -> String {
+    let options: String = ops::ALGORITHM_NAMES
+        .iter()
+        .map(|name| format!("<option value=\"{}\">", name))
+        .collect();
+    PAGE.replace("{{ALGORITHMS}}", &options)
+}
+
+const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>terrain-forge preview</title>
+<style>
+  body { font-family: sans-serif; background: #1e1e1e; color: #ddd; margin: 0; display: flex; }
+  form { width: 320px; padding: 16px; display: flex; flex-direction: column; gap: 8px; }
+  label { font-size: 12px; color: #aaa; }
+  input, select, textarea, button { background: #2b2b2b; color: #eee; border: 1px solid #444; padding: 4px; }
+  textarea { height: 160px; font-family: monospace; font-size: 11px; }
+  .row { display: flex; gap: 4px; }
+  .row > * { flex: 1; }
+  main { flex: 1; padding: 16px; }
+  img { image-rendering: pixelated; width: 100%; max-width: 960px; border: 1px solid #444; }
+  pre { color: #9cf; }
+</style>
+</head>
+<body>
+<form id="f">
+  <label>Spec (name or shorthand, e.g. "bsp > cellular")</label>
+  <input name="spec" list="algos" value="cellular">
+  <datalist id="algos">{{ALGORITHMS}}</datalist>
+  <label>Config JSON (optional, overrides spec)</label>
+  <textarea name="config" placeholder='{"pipeline": [{"type": "cellular", "iterations": 6}]}'></textarea>
+  <div class="row">
+    <div><label>Width</label><input name="width" type="number" value="80" min="1" max="1024"></div>
+    <div><label>Height</label><input name="height" type="number" value="60" min="1" max="1024"></div>
+  </div>
+  <label>Seed</label>
+  <div class="row">
+    <button type="button" onclick="step(-1)">&lt;</button>
+    <input name="seed" type="number" value="1" min="0">
+    <button type="button" onclick="step(1)">&gt;</button>
+    <button type="button" onclick="randomSeed()">random</button>
+  </div>
+  <label>Overlay</label>
+  <select name="overlay">
+    <option>none</option><option>markers</option><option>regions</option>
+    <option>masks</option><option>connectivity</option>
+  </select>
+  <button type="submit">Generate</button>
+</form>
+<main>
+  <img id="map" alt="">
+  <pre id="metrics"></pre>
+</main>
+<script>
+const form = document.getElementById('f');
+function params() { return new URLSearchParams(new FormData(form)).toString(); }
+async function refresh() {
+  const q = params();
+  document.getElementById('map').src = '/map.png?' + q;
+  const res = await fetch('/metrics.json?' + q);
+  const text = await res.text();
+  document.getElementById('metrics').textContent =
+    res.ok ? JSON.stringify(JSON.parse(text), null, 2) : text;
+}
+function step(d) { form.seed.value = Math.max(0, Number(form.seed.value) + d); refresh(); }
+function randomSeed() { form.seed.value = Math.floor(Math.random() * 1e9); refresh(); }
+form.addEventListener('submit', e => { e.preventDefault(); refresh(); });
+form.overlay.addEventListener('change', refresh);
+refresh();
+</script>
+</body>
+</html>
+"#;
@@ -247,13 +247,14 @@ fn create_sample_library() -> PrefabLibrary {
     library.add_prefab(terrain_forge::algorithms::Prefab::from_data(corridor));
 
     // L-shaped room (rare)
-    let mut legend = std::collections::HashMap::new();
+    let mut legend = std::collections::BTreeMap::new();
     legend.insert(
         "M".to_string(),
         terrain_forge::algorithms::PrefabLegendEntry {
             tile: Some("floor".to_string()),
             marker: Some("loot_slot".to_string()),
             mask: None,
+            ..Default::default()
         },
     );
     let l_room = PrefabData {
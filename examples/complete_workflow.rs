@@ -11,7 +11,7 @@ fn main() {
     let library = TemplateLibrary::new();
     let template = library.get_template("simple_dungeon").unwrap();
 
-    let mut custom_params = std::collections::HashMap::new();
+    let mut custom_params = std::collections::BTreeMap::new();
     custom_params.insert("seed".to_string(), "12345".to_string());
 
     let pipeline = template.instantiate(Some(custom_params));
@@ -30,6 +30,7 @@ fn main() {
         floor_weight: 0.4,
         pattern_size: 3,
         enable_backtracking: false,
+        ..WfcConfig::default()
     });
     wfc_no_backtrack.generate_with_patterns(&mut grid1, patterns.clone(), 12345);
     print_grid(&grid1, "Without Backtracking");
@@ -41,6 +42,7 @@ fn main() {
         floor_weight: 0.4,
         pattern_size: 3,
         enable_backtracking: true,
+        ..WfcConfig::default()
     });
     wfc_backtrack.generate_with_patterns(&mut grid2, patterns.clone(), 12345);
     print_grid(&grid2, "With Backtracking");
@@ -70,6 +72,7 @@ fn main() {
             floor_weight: 0.4,
             pattern_size: size,
             enable_backtracking: true,
+            ..WfcConfig::default()
         });
         wfc.generate_with_patterns(&mut grid, patterns.clone(), 98765);
 
@@ -98,7 +98,7 @@ fn demo_pipeline_templates() {
         ));
 
     // Instantiate with custom parameters
-    let mut custom_params = std::collections::HashMap::new();
+    let mut custom_params = std::collections::BTreeMap::new();
     custom_params.insert("algorithm".to_string(), "bsp".to_string());
     custom_params.insert("type".to_string(), "fortress".to_string());
 
@@ -43,6 +43,7 @@ fn main() {
         floor_weight: 0.45,
         pattern_size: 3,
         enable_backtracking: true,
+        ..WfcConfig::default()
     });
     wfc.generate_with_patterns(&mut wfc_grid, learned_patterns.clone(), 54321);
 
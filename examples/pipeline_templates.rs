@@ -86,7 +86,7 @@ fn main() {
 
     // Instantiate with custom parameters
     println!("  Custom parameters:");
-    let mut custom_params = std::collections::HashMap::new();
+    let mut custom_params = std::collections::BTreeMap::new();
     custom_params.insert("size".to_string(), "large".to_string());
     custom_params.insert("algorithm".to_string(), "cellular".to_string());
     custom_params.insert("complexity".to_string(), "high".to_string());
@@ -0,0 +1,64 @@
+/*
+ * terrain-forge C API (Cargo feature `ffi`).
+ *
+ * Build: cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * Functions returning int32_t return 0 on success and -1 on failure;
+ * tf_last_error() then describes the failure (per thread).
+ *
+ * Panics never unwind into C: every function catches them and fails as
+ * usual (-1, NULL, or 0 for the size/count getters) with a "panicked: ..."
+ * message from tf_last_error(). The grid may then be partially modified but
+ * remains valid to read and free.
+ */
+#ifndef TERRAIN_FORGE_H
+#define TERRAIN_FORGE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct TfGrid TfGrid;
+
+typedef struct TfMarker {
+    uint32_t x;
+    uint32_t y;
+    float weight;
+    /* -1 if the marker has no region. */
+    int64_t region_id;
+    /* Owned by the grid; valid until its semantics change or it is freed. */
+    const char *tag;
+} TfMarker;
+
+const char *tf_last_error(void);
+const char *tf_version(void);
+
+TfGrid *tf_grid_new(uint32_t width, uint32_t height);
+void tf_grid_free(TfGrid *grid);
+uint32_t tf_grid_width(const TfGrid *grid);
+uint32_t tf_grid_height(const TfGrid *grid);
+
+/* params_json may be NULL. Both clear extracted semantics on success. */
+int32_t tf_generate(TfGrid *grid, const char *name, const char *params_json, uint64_t seed);
+int32_t tf_effect(TfGrid *grid, const char *name, const char *params_json);
+/* profile: "default", "caves", "rooms", "mazes", or NULL for "default". */
+int32_t tf_extract_semantic(TfGrid *grid, const char *profile, uint64_t seed);
+/* Runs a JSON op script; returns NULL on failure. */
+TfGrid *tf_run_script(const char *script_json);
+
+/* 1 = floor, 0 = wall, -1 = out of bounds. */
+int32_t tf_grid_get(const TfGrid *grid, int32_t x, int32_t y);
+/* Row-major copy, 1 byte per cell; returns cells written. */
+size_t tf_grid_copy_tiles(const TfGrid *grid, uint8_t *out, size_t len);
+
+size_t tf_marker_count(const TfGrid *grid);
+int32_t tf_marker_get(const TfGrid *grid, size_t index, TfMarker *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TERRAIN_FORGE_H */
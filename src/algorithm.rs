@@ -1,6 +1,6 @@
 //! Algorithm trait for procedural generation
 
-use crate::{Cell, Grid};
+use crate::{Cell, Grid, Tile};
 
 /// Trait for procedural generation algorithms.
 ///
@@ -22,6 +22,30 @@ pub trait Algorithm<C: Cell = crate::Tile>: Send + Sync {
     /// Generate content into the grid using the given seed
     fn generate(&self, grid: &mut Grid<C>, seed: u64);
 
+    /// Like [`generate`](Self::generate), calling `frame` with intermediate
+    /// states for visualization. The default reports only the final state.
+    fn generate_frames(&self, grid: &mut Grid<C>, seed: u64, frame: &mut dyn FnMut(&Grid<C>)) {
+        self.generate(grid, seed);
+        frame(grid);
+    }
+
+    /// Like [`generate`](Self::generate), but only cells where `mask` is
+    /// `true` (indexed `[y][x]`) are written; everything else is left as
+    /// is. The default runs the algorithm on a blank grid of the same size
+    /// and copies the masked cells over, so algorithms that refine existing
+    /// content (such as `glass_seam`) see nothing unless they override it.
+    fn generate_masked(&self, grid: &mut Grid<C>, mask: &[Vec<bool>], seed: u64) {
+        let mut scratch = Grid::new(grid.width(), grid.height());
+        self.generate(&mut scratch, seed);
+        for (y, row) in mask.iter().enumerate().take(grid.height()) {
+            for (x, _) in row.iter().enumerate().filter(|(_, &m)| m) {
+                if let Some(cell) = scratch.get(x as i32, y as i32) {
+                    grid.set(x as i32, y as i32, cell.clone());
+                }
+            }
+        }
+    }
+
     /// Algorithm name for identification
     fn name(&self) -> &'static str;
 }
@@ -31,7 +55,142 @@ impl<C: Cell> Algorithm<C> for Box<dyn Algorithm<C> + Send + Sync> {
         (**self).generate(grid, seed)
     }
 
+    fn generate_frames(&self, grid: &mut Grid<C>, seed: u64, frame: &mut dyn FnMut(&Grid<C>)) {
+        (**self).generate_frames(grid, seed, frame)
+    }
+
+    fn generate_masked(&self, grid: &mut Grid<C>, mask: &[Vec<bool>], seed: u64) {
+        (**self).generate_masked(grid, mask, seed)
+    }
+
     fn name(&self) -> &'static str {
         (**self).name()
     }
 }
+
+/// Runs a [`Tile`] algorithm on a grid of any [`Cell`] type.
+///
+/// The grid's passability is mirrored into a scratch `Grid<Tile>`, the inner
+/// algorithm runs on it, and changed cells are written back with
+/// [`Grid::apply_tiles`] so extra per-cell data is preserved.
+///
+/// # Examples
+///
+/// ```
+/// use terrain_forge::{Algorithm, CellAdapter, Cell, Grid};
+/// use terrain_forge::algorithms::Bsp;
+///
+/// #[derive(Clone, Default)]
+/// struct MyCell { open: bool, light: u8 }
+///
+/// impl Cell for MyCell {
+///     fn is_passable(&self) -> bool { self.open }
+///     fn set_passable(&mut self) { self.open = true; }
+///     fn set_impassable(&mut self) { self.open = false; }
+/// }
+///
+/// let mut grid: Grid<MyCell> = Grid::new(40, 30);
+/// CellAdapter::new(Bsp::default()).generate(&mut grid, 42);
+/// assert!(grid.count(|c| c.is_passable()) > 0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CellAdapter<A> {
+    inner: A,
+}
+
+impl<A: Algorithm<Tile>> CellAdapter<A> {
+    /// Wraps a `Tile` algorithm.
+    pub fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// Returns the wrapped algorithm.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Unwraps the adapter.
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<C: Cell, A: Algorithm<Tile>> Algorithm<C> for CellAdapter<A> {
+    fn generate(&self, grid: &mut Grid<C>, seed: u64) {
+        let mut tiles = grid.to_tiles();
+        self.inner.generate(&mut tiles, seed);
+        grid.apply_tiles(&tiles);
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// Restricts an algorithm to a mask, for regenerating one region or
+/// filling a courtyard without touching the rest of the map.
+///
+/// # Examples
+///
+/// ```
+/// use terrain_forge::{Algorithm, Grid, Masked, Tile};
+/// use terrain_forge::algorithms::CellularAutomata;
+///
+/// let mut grid = Grid::new(40, 30);
+/// grid.fill_rect(0, 0, 40, 30, Tile::Floor);
+/// let courtyard = Masked::from_cells(CellularAutomata::default(), 40, 30, (10..20).flat_map(|y| (10..30).map(move |x| (x, y))));
+/// courtyard.generate(&mut grid, 7);
+/// assert!(grid[(5, 5)].is_floor());
+/// assert!(grid.count(|t| !t.is_floor()) <= 200);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Masked<A> {
+    inner: A,
+    mask: Vec<Vec<bool>>,
+}
+
+impl<A> Masked<A> {
+    /// Wraps `inner`; `mask` is indexed `[y][x]`, cells outside it are unmasked.
+    pub fn new(inner: A, mask: Vec<Vec<bool>>) -> Self {
+        Self { inner, mask }
+    }
+
+    /// Wraps `inner` with a `width` x `height` mask set at `cells`, e.g. a
+    /// [`Region`](crate::Region)'s cells.
+    pub fn from_cells<I, T>(inner: A, width: usize, height: usize, cells: I) -> Self
+    where
+        I: IntoIterator<Item = (T, T)>,
+        T: TryInto<usize>,
+    {
+        let mut mask = vec![vec![false; width]; height];
+        for (x, y) in cells {
+            let (Ok(x), Ok(y)) = (x.try_into(), y.try_into()) else {
+                continue;
+            };
+            if x < width && y < height {
+                mask[y][x] = true;
+            }
+        }
+        Self { inner, mask }
+    }
+
+    /// Returns the mask.
+    pub fn mask(&self) -> &[Vec<bool>] {
+        &self.mask
+    }
+
+    /// Returns the wrapped algorithm.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+}
+
+impl<C: Cell, A: Algorithm<C>> Algorithm<C> for Masked<A> {
+    fn generate(&self, grid: &mut Grid<C>, seed: u64) {
+        self.inner.generate_masked(grid, &self.mask, seed);
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
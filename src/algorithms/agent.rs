@@ -1,8 +1,11 @@
+use super::{Deadline, TimedRun};
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for agent-based carving.
+#[serde(default)]
 pub struct AgentConfig {
     /// Number of carving agents. Default: 5.
     pub num_agents: usize,
@@ -10,6 +13,9 @@ pub struct AgentConfig {
     pub steps_per_agent: usize,
     /// Probability of turning each step (0.0–1.0). Default: 0.3.
     pub turn_chance: f64,
+    /// Soft time limit in milliseconds; when it passes, generation stops
+    /// and keeps the partial result. Default: None.
+    pub max_duration_ms: Option<u64>,
 }
 
 impl Default for AgentConfig {
@@ -18,10 +24,37 @@ impl Default for AgentConfig {
             num_agents: 5,
             steps_per_agent: 200,
             turn_chance: 0.3,
+            max_duration_ms: None,
         }
     }
 }
 
+impl AgentConfig {
+    /// Sets `num_agents`.
+    pub fn with_num_agents(mut self, num_agents: usize) -> Self {
+        self.num_agents = num_agents;
+        self
+    }
+
+    /// Sets `steps_per_agent`.
+    pub fn with_steps_per_agent(mut self, steps_per_agent: usize) -> Self {
+        self.steps_per_agent = steps_per_agent;
+        self
+    }
+
+    /// Sets `turn_chance`.
+    pub fn with_turn_chance(mut self, turn_chance: f64) -> Self {
+        self.turn_chance = turn_chance;
+        self
+    }
+
+    /// Sets `max_duration_ms`.
+    pub fn with_max_duration(mut self, limit: Duration) -> Self {
+        self.max_duration_ms = Some(limit.as_millis() as u64);
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Agent-based terrain carver.
 pub struct AgentBased {
@@ -33,26 +66,27 @@ impl AgentBased {
     pub fn new(config: AgentConfig) -> Self {
         Self { config }
     }
-}
-
-impl Default for AgentBased {
-    fn default() -> Self {
-        Self::new(AgentConfig::default())
-    }
-}
 
-impl Algorithm<Tile> for AgentBased {
-    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+    /// Like [`generate`](Algorithm::generate), reporting whether
+    /// `max_duration_ms` stopped the agents early.
+    pub fn generate_timed(&self, grid: &mut Grid<Tile>, seed: u64) -> TimedRun {
+        let mut deadline = Deadline::new(self.config.max_duration_ms);
         let mut rng = Rng::new(seed);
         let dirs: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
         let (w, h) = (grid.width() as i32, grid.height() as i32);
+        if w < 3 || h < 3 {
+            return deadline.finish();
+        }
 
-        for _ in 0..self.config.num_agents {
+        'agents: for _ in 0..self.config.num_agents {
             let mut x = rng.range(1, w - 1);
             let mut y = rng.range(1, h - 1);
             let mut dir = rng.range_usize(0, 4);
 
-            for _ in 0..self.config.steps_per_agent {
+            for step in 0..self.config.steps_per_agent {
+                if step % 256 == 0 && deadline.expired() {
+                    break 'agents;
+                }
                 grid.set(x, y, Tile::Floor);
 
                 if rng.chance(self.config.turn_chance) {
@@ -74,6 +108,19 @@ impl Algorithm<Tile> for AgentBased {
                 }
             }
         }
+        deadline.finish()
+    }
+}
+
+impl Default for AgentBased {
+    fn default() -> Self {
+        Self::new(AgentConfig::default())
+    }
+}
+
+impl Algorithm<Tile> for AgentBased {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        self.generate_timed(grid, seed);
     }
 
     fn name(&self) -> &'static str {
@@ -1,8 +1,26 @@
+use super::prefab::{Prefab, PrefabData};
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
 
+/// Shape a BSP leaf room is carved as, inside its leaf's padded bounds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BspRoomShape {
+    /// Axis-aligned rectangle.
+    Rectangle,
+    /// Rectangle with a corner notch cut out, leaving arms at least 3
+    /// cells wide. Leaves too small for that get a rectangle.
+    LShape,
+    /// Disc as wide as the leaf's shorter side allows.
+    Circle,
+    /// The prefab's floor cells at a random position in the leaf. Leaves
+    /// it doesn't fit in get a rectangle.
+    Prefab(PrefabData),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for BSP (Binary Space Partitioning) dungeon generation.
+#[serde(default)]
 pub struct BspConfig {
     /// Minimum room dimension. Default: 5.
     pub min_room_size: usize,
@@ -10,6 +28,9 @@ pub struct BspConfig {
     pub max_depth: usize,
     /// Padding between rooms and partition edges. Default: 1.
     pub room_padding: usize,
+    /// Weighted shapes each leaf room is drawn from. Default: rectangles
+    /// only.
+    pub room_shapes: Vec<(BspRoomShape, f64)>,
 }
 
 impl Default for BspConfig {
@@ -18,10 +39,37 @@ impl Default for BspConfig {
             min_room_size: 5,
             max_depth: 4,
             room_padding: 1,
+            room_shapes: vec![(BspRoomShape::Rectangle, 1.0)],
         }
     }
 }
 
+impl BspConfig {
+    /// Sets `min_room_size`.
+    pub fn with_min_room_size(mut self, min_room_size: usize) -> Self {
+        self.min_room_size = min_room_size;
+        self
+    }
+
+    /// Sets `max_depth`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets `room_padding`.
+    pub fn with_room_padding(mut self, room_padding: usize) -> Self {
+        self.room_padding = room_padding;
+        self
+    }
+
+    /// Sets `room_shapes`.
+    pub fn with_room_shapes(mut self, room_shapes: Vec<(BspRoomShape, f64)>) -> Self {
+        self.room_shapes = room_shapes;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// BSP dungeon generator.
 pub struct Bsp {
@@ -48,7 +96,26 @@ struct BspNode {
     h: usize,
     left: Option<Box<BspNode>>,
     right: Option<Box<BspNode>>,
-    room: Option<(usize, usize, usize, usize)>,
+    room: Option<BspRoom>,
+}
+
+/// A carved leaf room.
+struct BspRoom {
+    /// Floor cells; `None` for a full rectangle `(x, y, w, h)`.
+    cells: Option<Vec<(usize, usize)>>,
+    rect: (usize, usize, usize, usize),
+    /// Floor cell corridors connect to.
+    anchor: (usize, usize),
+}
+
+impl BspRoom {
+    fn rect(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Self {
+            cells: None,
+            rect: (x, y, w, h),
+            anchor: (x + w / 2, y + h / 2),
+        }
+    }
 }
 
 impl BspNode {
@@ -109,13 +176,13 @@ impl BspNode {
         }
     }
 
-    fn create_rooms(&mut self, rng: &mut Rng, padding: usize) {
+    fn create_rooms(&mut self, rng: &mut Rng, padding: usize, shapes: &[(BspRoomShape, f64)]) {
         if self.left.is_some() || self.right.is_some() {
             if let Some(ref mut l) = self.left {
-                l.create_rooms(rng, padding);
+                l.create_rooms(rng, padding, shapes);
             }
             if let Some(ref mut r) = self.right {
-                r.create_rooms(rng, padding);
+                r.create_rooms(rng, padding, shapes);
             }
         } else {
             let min_w = 3.min(self.w.saturating_sub(padding * 2));
@@ -126,17 +193,27 @@ impl BspNode {
 
             let max_w = self.w.saturating_sub(padding * 2);
             let max_h = self.h.saturating_sub(padding * 2);
-            let w = rng.range_usize(min_w, max_w + 1);
-            let h = rng.range_usize(min_h, max_h + 1);
-            let x = self.x + padding + rng.range_usize(0, max_w - w + 1);
-            let y = self.y + padding + rng.range_usize(0, max_h - h + 1);
-            self.room = Some((x, y, w, h));
+            // A lone shape needs no draw, keeping rectangle-only output stable.
+            let shape = match shapes {
+                [(shape, _)] => Some(shape),
+                _ => rng.pick_weighted(shapes),
+            };
+            let (x0, y0) = (self.x + padding, self.y + padding);
+            self.room = Some(match shape {
+                Some(BspRoomShape::LShape) if max_w >= 5 && max_h >= 5 => {
+                    l_shape(rng, x0, y0, max_w, max_h)
+                }
+                Some(BspRoomShape::Circle) => circle(rng, x0, y0, max_w, max_h),
+                Some(BspRoomShape::Prefab(data)) => prefab_room(rng, data, x0, y0, max_w, max_h)
+                    .unwrap_or_else(|| rectangle(rng, x0, y0, max_w, max_h)),
+                _ => rectangle(rng, x0, y0, max_w, max_h),
+            });
         }
     }
 
     fn get_center(&self) -> Option<(usize, usize)> {
-        if let Some((x, y, w, h)) = self.room {
-            return Some((x + w / 2, y + h / 2));
+        if let Some(room) = &self.room {
+            return Some(room.anchor);
         }
         self.left
             .as_ref()
@@ -145,8 +222,18 @@ impl BspNode {
     }
 
     fn carve(&self, grid: &mut Grid<Tile>) {
-        if let Some((x, y, w, h)) = self.room {
-            grid.fill_rect(x as i32, y as i32, w, h, Tile::Floor);
+        if let Some(room) = &self.room {
+            match &room.cells {
+                Some(cells) => {
+                    for &(x, y) in cells {
+                        grid.set(x as i32, y as i32, Tile::Floor);
+                    }
+                }
+                None => {
+                    let (x, y, w, h) = room.rect;
+                    grid.fill_rect(x as i32, y as i32, w, h, Tile::Floor);
+                }
+            }
         }
         if let (Some(ref left), Some(ref right)) = (&self.left, &self.right) {
             left.carve(grid);
@@ -163,8 +250,101 @@ impl BspNode {
     }
 }
 
+/// A random rectangle of at least 3x3 within `max_w` x `max_h` at `(x0, y0)`.
+fn rectangle(rng: &mut Rng, x0: usize, y0: usize, max_w: usize, max_h: usize) -> BspRoom {
+    let w = rng.range_usize(3, max_w + 1);
+    let h = rng.range_usize(3, max_h + 1);
+    let x = x0 + rng.range_usize(0, max_w - w + 1);
+    let y = y0 + rng.range_usize(0, max_h - h + 1);
+    BspRoom::rect(x, y, w, h)
+}
+
+/// A rectangle of at least 5x5 missing one corner; the anchor is in the
+/// elbow opposite the notch.
+fn l_shape(rng: &mut Rng, x0: usize, y0: usize, max_w: usize, max_h: usize) -> BspRoom {
+    let w = rng.range_usize(5, max_w + 1);
+    let h = rng.range_usize(5, max_h + 1);
+    let x = x0 + rng.range_usize(0, max_w - w + 1);
+    let y = y0 + rng.range_usize(0, max_h - h + 1);
+    let (notch_w, notch_h) = (rng.range_usize(w / 3, w - 2), rng.range_usize(h / 3, h - 2));
+    let (right, bottom) = (rng.chance(0.5), rng.chance(0.5));
+    let notch_x = if right { x + w - notch_w } else { x };
+    let notch_y = if bottom { y + h - notch_h } else { y };
+    let in_notch = |cx: usize, cy: usize| {
+        (notch_x..notch_x + notch_w).contains(&cx) && (notch_y..notch_y + notch_h).contains(&cy)
+    };
+    let cells = (y..y + h)
+        .flat_map(|cy| (x..x + w).map(move |cx| (cx, cy)))
+        .filter(|&(cx, cy)| !in_notch(cx, cy))
+        .collect();
+    let (elbow_w, elbow_h) = (w - notch_w, h - notch_h);
+    let elbow_x = if right { x } else { x + notch_w };
+    let elbow_y = if bottom { y } else { y + notch_h };
+    BspRoom {
+        cells: Some(cells),
+        rect: (x, y, w, h),
+        anchor: (elbow_x + elbow_w / 2, elbow_y + elbow_h / 2),
+    }
+}
+
+/// A disc of diameter 3 up to the shorter side, centered on a random cell.
+fn circle(rng: &mut Rng, x0: usize, y0: usize, max_w: usize, max_h: usize) -> BspRoom {
+    let d = rng.range_usize(3, max_w.min(max_h) + 1);
+    let x = x0 + rng.range_usize(0, max_w - d + 1);
+    let y = y0 + rng.range_usize(0, max_h - d + 1);
+    let r = d as f64 / 2.0;
+    let cells = (0..d)
+        .flat_map(|dy| (0..d).map(move |dx| (dx, dy)))
+        .filter(|&(dx, dy)| {
+            let (fx, fy) = (dx as f64 + 0.5 - r, dy as f64 + 0.5 - r);
+            fx * fx + fy * fy <= r * r
+        })
+        .map(|(dx, dy)| (x + dx, y + dy))
+        .collect();
+    BspRoom {
+        cells: Some(cells),
+        rect: (x, y, d, d),
+        anchor: (x + d / 2, y + d / 2),
+    }
+}
+
+/// The prefab's floor cells at a random position, anchored at the floor
+/// cell nearest its center; `None` if it doesn't fit or has no floor.
+fn prefab_room(
+    rng: &mut Rng,
+    data: &PrefabData,
+    x0: usize,
+    y0: usize,
+    max_w: usize,
+    max_h: usize,
+) -> Option<BspRoom> {
+    let prefab = Prefab::from_data(data.clone());
+    let (w, h) = (prefab.width, prefab.height);
+    if w == 0 || h == 0 || w > max_w || h > max_h {
+        return None;
+    }
+    let x = x0 + rng.range_usize(0, max_w - w + 1);
+    let y = y0 + rng.range_usize(0, max_h - h + 1);
+    let floor: Vec<(usize, usize)> = (0..h)
+        .flat_map(|py| (0..w).map(move |px| (px, py)))
+        .filter(|&(px, py)| prefab.cell_tile(px, py) == Some(Tile::Floor))
+        .collect();
+    let anchor = floor
+        .iter()
+        .min_by_key(|&&(px, py)| (2 * px).abs_diff(w) + (2 * py).abs_diff(h))
+        .map(|&(px, py)| (x + px, y + py))?;
+    Some(BspRoom {
+        cells: Some(floor.iter().map(|&(px, py)| (x + px, y + py)).collect()),
+        rect: (x, y, w, h),
+        anchor,
+    })
+}
+
 impl Algorithm<Tile> for Bsp {
     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        if grid.width() < 3 || grid.height() < 3 {
+            return;
+        }
         let mut rng = Rng::new(seed);
         let mut root = BspNode::new(1, 1, grid.width() - 2, grid.height() - 2);
         root.split(
@@ -173,7 +353,7 @@ impl Algorithm<Tile> for Bsp {
             0,
             self.config.max_depth,
         );
-        root.create_rooms(&mut rng, self.config.room_padding);
+        root.create_rooms(&mut rng, self.config.room_padding, &self.config.room_shapes);
         root.carve(grid);
     }
 
@@ -1,8 +1,11 @@
+use crate::parallel;
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for cellular automata cave generation.
+#[serde(default)]
 pub struct CellularConfig {
     /// Probability of a cell starting as floor. Default: 0.45.
     pub initial_floor_chance: f64,
@@ -12,6 +15,12 @@ pub struct CellularConfig {
     pub birth_limit: usize,
     /// Neighbor count below which a floor cell dies. Default: 4.
     pub death_limit: usize,
+    /// After the iterations, keep the largest cave and join every other
+    /// cave of at least `min_cave_size` cells to it with the shortest
+    /// tunnel; smaller caves are filled in. Default: false.
+    pub connect: bool,
+    /// Smallest cave kept by `connect`. Default: 12.
+    pub min_cave_size: usize,
 }
 
 impl Default for CellularConfig {
@@ -21,10 +30,50 @@ impl Default for CellularConfig {
             iterations: 4,
             birth_limit: 5,
             death_limit: 4,
+            connect: false,
+            min_cave_size: 12,
         }
     }
 }
 
+impl CellularConfig {
+    /// Sets `initial_floor_chance`.
+    pub fn with_initial_floor_chance(mut self, initial_floor_chance: f64) -> Self {
+        self.initial_floor_chance = initial_floor_chance;
+        self
+    }
+
+    /// Sets `iterations`.
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets `birth_limit`.
+    pub fn with_birth_limit(mut self, birth_limit: usize) -> Self {
+        self.birth_limit = birth_limit;
+        self
+    }
+
+    /// Sets `death_limit`.
+    pub fn with_death_limit(mut self, death_limit: usize) -> Self {
+        self.death_limit = death_limit;
+        self
+    }
+
+    /// Sets `connect`.
+    pub fn with_connect(mut self, connect: bool) -> Self {
+        self.connect = connect;
+        self
+    }
+
+    /// Sets `min_cave_size`.
+    pub fn with_min_cave_size(mut self, min_cave_size: usize) -> Self {
+        self.min_cave_size = min_cave_size;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Cellular automata cave generator.
 pub struct CellularAutomata {
@@ -46,43 +95,140 @@ impl Default for CellularAutomata {
 
 impl Algorithm<Tile> for CellularAutomata {
     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        self.seed_floors(grid, seed);
+        for _ in 0..self.config.iterations {
+            step(grid, self.config.birth_limit, self.config.death_limit);
+        }
+        if self.config.connect {
+            connect_caves(grid, self.config.min_cave_size);
+        }
+    }
+
+    /// Reports the random fill, each iteration, and the connected result
+    /// when `connect` is set.
+    fn generate_frames(
+        &self,
+        grid: &mut Grid<Tile>,
+        seed: u64,
+        frame: &mut dyn FnMut(&Grid<Tile>),
+    ) {
+        self.seed_floors(grid, seed);
+        frame(grid);
+        for _ in 0..self.config.iterations {
+            step(grid, self.config.birth_limit, self.config.death_limit);
+            frame(grid);
+        }
+        if self.config.connect {
+            connect_caves(grid, self.config.min_cave_size);
+            frame(grid);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "CellularAutomata"
+    }
+}
+
+impl CellularAutomata {
+    /// Randomly opens interior cells; the state before the first iteration.
+    pub(crate) fn seed_floors(&self, grid: &mut Grid<Tile>, seed: u64) {
         let mut rng = Rng::new(seed);
         let (w, h) = (grid.width(), grid.height());
-
-        for y in 1..h - 1 {
-            for x in 1..w - 1 {
+        for y in 1..h.saturating_sub(1) {
+            for x in 1..w.saturating_sub(1) {
                 if rng.chance(self.config.initial_floor_chance) {
                     grid.set(x as i32, y as i32, Tile::Floor);
                 }
             }
         }
+    }
+}
 
-        for _ in 0..self.config.iterations {
-            let snapshot: Vec<bool> = (0..w * h)
-                .map(|i| grid[(i % w, i / w)].is_floor())
-                .collect();
-
-            for y in 1..h - 1 {
-                for x in 1..w - 1 {
-                    let neighbors = count_neighbors(&snapshot, x, y, w);
-                    let is_floor = snapshot[y * w + x];
-                    let new_floor = if is_floor {
-                        neighbors >= self.config.death_limit
-                    } else {
-                        neighbors >= self.config.birth_limit
-                    };
-                    grid.set(
-                        x as i32,
-                        y as i32,
-                        if new_floor { Tile::Floor } else { Tile::Wall },
-                    );
+/// One automata pass over interior cells; borders are left unchanged.
+pub(crate) fn step(grid: &mut Grid<Tile>, birth_limit: usize, death_limit: usize) {
+    let (w, h) = (grid.width(), grid.height());
+    let snapshot: Vec<bool> = grid.cells().iter().map(Tile::is_floor).collect();
+
+    parallel::for_each_row(grid.cells_mut(), w, |y, row| {
+        if y == 0 || y + 1 >= h {
+            return;
+        }
+        for x in 1..w - 1 {
+            let neighbors = count_neighbors(&snapshot, x, y, w);
+            let new_floor = if snapshot[y * w + x] {
+                neighbors >= death_limit
+            } else {
+                neighbors >= birth_limit
+            };
+            row[x] = if new_floor { Tile::Floor } else { Tile::Wall };
+        }
+    });
+}
+
+/// Fills caves smaller than `min_size`, then tunnels each remaining cave
+/// to the largest through the fewest interior wall cells.
+pub(crate) fn connect_caves(grid: &mut Grid<Tile>, min_size: usize) {
+    let (w, h) = (grid.width(), grid.height());
+    let mut caves = grid.flood_regions();
+    caves.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    for cave in caves.iter().skip(1).filter(|c| c.len() < min_size) {
+        for &(x, y) in cave {
+            grid.set(x as i32, y as i32, Tile::Wall);
+        }
+    }
+    let Some(main) = caves.first() else {
+        return;
+    };
+
+    let mut connected = vec![false; w * h];
+    for &(x, y) in main {
+        connected[y * w + x] = true;
+    }
+    loop {
+        // Multi-source BFS out of the connected area to the nearest other cave.
+        let mut parent = vec![usize::MAX; w * h];
+        let mut queue: VecDeque<usize> = (0..w * h).filter(|&i| connected[i]).collect();
+        for &i in &queue {
+            parent[i] = i;
+        }
+        let mut reached = None;
+        while let Some(i) = queue.pop_front() {
+            let (x, y) = (i % w, i / w);
+            if !connected[i] && grid[(x, y)].is_floor() {
+                reached = Some(i);
+                break;
+            }
+            for (nx, ny) in grid.neighbors_4(x, y) {
+                let interior = nx > 0 && ny > 0 && nx + 1 < w && ny + 1 < h;
+                let n = ny * w + nx;
+                if interior && parent[n] == usize::MAX {
+                    parent[n] = i;
+                    queue.push_back(n);
                 }
             }
         }
-    }
+        let Some(end) = reached else {
+            return;
+        };
 
-    fn name(&self) -> &'static str {
-        "CellularAutomata"
+        let mut i = end;
+        while !connected[i] {
+            grid.set((i % w) as i32, (i / w) as i32, Tile::Floor);
+            i = parent[i];
+        }
+        // Everything reachable from the tunnel is now connected.
+        let mut stack = vec![end];
+        connected[end] = true;
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            for (nx, ny) in grid.neighbors_4(x, y) {
+                let n = ny * w + nx;
+                if !connected[n] && grid[(nx, ny)].is_floor() {
+                    connected[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
     }
 }
 
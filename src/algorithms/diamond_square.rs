@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for diamond-square heightmap generation.
+#[serde(default)]
 pub struct DiamondSquareConfig {
     /// Roughness factor controlling height variation. Default: 0.6.
     pub roughness: f64,
@@ -19,6 +20,20 @@ impl Default for DiamondSquareConfig {
     }
 }
 
+impl DiamondSquareConfig {
+    /// Sets `roughness`.
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.roughness = roughness;
+        self
+    }
+
+    /// Sets `threshold`.
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Diamond-square heightmap terrain generator.
 pub struct DiamondSquare {
@@ -38,10 +53,12 @@ impl Default for DiamondSquare {
     }
 }
 
-impl Algorithm<Tile> for DiamondSquare {
-    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+impl DiamondSquare {
+    /// Generates the underlying heightmap (rows of values in `[0, 1]`).
+    ///
+    /// [`Algorithm::generate`] thresholds this at `config.threshold`.
+    pub fn heightmap(&self, w: usize, h: usize, seed: u64) -> Vec<Vec<f64>> {
         let mut rng = Rng::new(seed);
-        let (w, h) = (grid.width(), grid.height());
 
         // Create heightmap
         let mut heights = vec![vec![0.0f64; w]; h];
@@ -94,7 +111,11 @@ impl Algorithm<Tile> for DiamondSquare {
 
             // Square step - set edge midpoints
             for y in 0..h {
-                let x_start = if (y / step) % 2 == 0 { step } else { 0 };
+                let x_start = if (y / step).is_multiple_of(2) {
+                    step
+                } else {
+                    0
+                };
                 let mut x = x_start;
                 while x < w {
                     let mut sum = 0.0;
@@ -127,6 +148,14 @@ impl Algorithm<Tile> for DiamondSquare {
             scale *= 0.5;
         }
 
+        heights
+    }
+}
+
+impl Algorithm<Tile> for DiamondSquare {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        let heights = self.heightmap(grid.width(), grid.height(), seed);
+
         // Convert to tiles
         for (y, row) in heights.iter().enumerate() {
             for (x, &height) in row.iter().enumerate() {
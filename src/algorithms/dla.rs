@@ -1,13 +1,19 @@
+use super::{Deadline, TimedRun};
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for diffusion-limited aggregation.
+#[serde(default)]
 pub struct DlaConfig {
     /// Number of particles to release. Default: 500.
     pub num_particles: usize,
     /// Maximum random walk steps per particle. Default: 1000.
     pub max_walk_steps: usize,
+    /// Soft time limit in milliseconds; when it passes, generation stops
+    /// and keeps the partial result. Default: None.
+    pub max_duration_ms: Option<u64>,
 }
 
 impl Default for DlaConfig {
@@ -15,10 +21,31 @@ impl Default for DlaConfig {
         Self {
             num_particles: 500,
             max_walk_steps: 1000,
+            max_duration_ms: None,
         }
     }
 }
 
+impl DlaConfig {
+    /// Sets `num_particles`.
+    pub fn with_num_particles(mut self, num_particles: usize) -> Self {
+        self.num_particles = num_particles;
+        self
+    }
+
+    /// Sets `max_walk_steps`.
+    pub fn with_max_walk_steps(mut self, max_walk_steps: usize) -> Self {
+        self.max_walk_steps = max_walk_steps;
+        self
+    }
+
+    /// Sets `max_duration_ms`.
+    pub fn with_max_duration(mut self, limit: Duration) -> Self {
+        self.max_duration_ms = Some(limit.as_millis() as u64);
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Diffusion-limited aggregation generator.
 pub struct Dla {
@@ -30,24 +57,25 @@ impl Dla {
     pub fn new(config: DlaConfig) -> Self {
         Self { config }
     }
-}
 
-impl Default for Dla {
-    fn default() -> Self {
-        Self::new(DlaConfig::default())
-    }
-}
-
-impl Algorithm<Tile> for Dla {
-    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+    /// Like [`generate`](Algorithm::generate), reporting whether
+    /// `max_duration_ms` cut the run short. Particles already stuck stay.
+    pub fn generate_timed(&self, grid: &mut Grid<Tile>, seed: u64) -> TimedRun {
+        let mut deadline = Deadline::new(self.config.max_duration_ms);
         let mut rng = Rng::new(seed);
         let (w, h) = (grid.width(), grid.height());
         let dirs: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+        if w < 3 || h < 3 {
+            return deadline.finish();
+        }
 
         // Seed in center
         grid.set(w as i32 / 2, h as i32 / 2, Tile::Floor);
 
         for _ in 0..self.config.num_particles {
+            if deadline.expired() {
+                break;
+            }
             let mut x = rng.range(1, w as i32 - 1);
             let mut y = rng.range(1, h as i32 - 1);
 
@@ -71,6 +99,19 @@ impl Algorithm<Tile> for Dla {
                 }
             }
         }
+        deadline.finish()
+    }
+}
+
+impl Default for Dla {
+    fn default() -> Self {
+        Self::new(DlaConfig::default())
+    }
+}
+
+impl Algorithm<Tile> for Dla {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        self.generate_timed(grid, seed);
     }
 
     fn name(&self) -> &'static str {
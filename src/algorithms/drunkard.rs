@@ -1,13 +1,19 @@
+use super::{Deadline, TimedRun};
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for drunkard walk generation.
+#[serde(default)]
 pub struct DrunkardConfig {
     /// Target floor percentage (0.0–1.0). Default: 0.4.
     pub floor_percent: f64,
     /// Maximum walk steps. Default: 50000.
     pub max_iterations: usize,
+    /// Soft time limit in milliseconds; when it passes, generation stops
+    /// and keeps the partial result. Default: None.
+    pub max_duration_ms: Option<u64>,
 }
 
 impl Default for DrunkardConfig {
@@ -15,10 +21,31 @@ impl Default for DrunkardConfig {
         Self {
             floor_percent: 0.4,
             max_iterations: 50000,
+            max_duration_ms: None,
         }
     }
 }
 
+impl DrunkardConfig {
+    /// Sets `floor_percent`.
+    pub fn with_floor_percent(mut self, floor_percent: f64) -> Self {
+        self.floor_percent = floor_percent;
+        self
+    }
+
+    /// Sets `max_iterations`.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Sets `max_duration_ms`.
+    pub fn with_max_duration(mut self, limit: Duration) -> Self {
+        self.max_duration_ms = Some(limit.as_millis() as u64);
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Drunkard walk cave generator.
 pub struct DrunkardWalk {
@@ -30,18 +57,16 @@ impl DrunkardWalk {
     pub fn new(config: DrunkardConfig) -> Self {
         Self { config }
     }
-}
 
-impl Default for DrunkardWalk {
-    fn default() -> Self {
-        Self::new(DrunkardConfig::default())
-    }
-}
-
-impl Algorithm<Tile> for DrunkardWalk {
-    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+    /// Like [`generate`](Algorithm::generate), reporting whether
+    /// `max_duration_ms` cut the walk short.
+    pub fn generate_timed(&self, grid: &mut Grid<Tile>, seed: u64) -> TimedRun {
+        let mut deadline = Deadline::new(self.config.max_duration_ms);
         let mut rng = Rng::new(seed);
         let (w, h) = (grid.width(), grid.height());
+        if w < 3 || h < 3 {
+            return deadline.finish();
+        }
         let target = ((w * h) as f64 * self.config.floor_percent) as usize;
         let dirs: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
 
@@ -49,10 +74,14 @@ impl Algorithm<Tile> for DrunkardWalk {
         let mut y = h as i32 / 2;
         let mut floor_count = 0;
 
-        for _ in 0..self.config.max_iterations {
+        for i in 0..self.config.max_iterations {
             if floor_count >= target {
                 break;
             }
+            // Steps are cheap; only look at the clock now and then.
+            if i % 1024 == 0 && deadline.expired() {
+                break;
+            }
 
             if !grid.get(x, y).map(|t| t.is_floor()).unwrap_or(true) {
                 grid.set(x, y, Tile::Floor);
@@ -66,6 +95,19 @@ impl Algorithm<Tile> for DrunkardWalk {
                 y = ny;
             }
         }
+        deadline.finish()
+    }
+}
+
+impl Default for DrunkardWalk {
+    fn default() -> Self {
+        Self::new(DrunkardConfig::default())
+    }
+}
+
+impl Algorithm<Tile> for DrunkardWalk {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        self.generate_timed(grid, seed);
     }
 
     fn name(&self) -> &'static str {
@@ -13,6 +13,7 @@ pub enum FractalType {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for fractal terrain generation.
+#[serde(default)]
 pub struct FractalConfig {
     /// Which fractal set to use. Default: Mandelbrot.
     pub fractal_type: FractalType,
@@ -29,6 +30,20 @@ impl Default for FractalConfig {
     }
 }
 
+impl FractalConfig {
+    /// Sets `fractal_type`.
+    pub fn with_fractal_type(mut self, fractal_type: FractalType) -> Self {
+        self.fractal_type = fractal_type;
+        self
+    }
+
+    /// Sets `max_iterations`.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Fractal terrain generator.
 pub struct Fractal {
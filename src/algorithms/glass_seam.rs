@@ -2,10 +2,11 @@ use crate::effects::carve_path;
 use crate::grid::line_points;
 use crate::{Algorithm, Grid, Tile};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for glass seam bridging connectivity.
+#[serde(default)]
 pub struct GlassSeamConfig {
     /// Target connectivity coverage (0.0–1.0). Default: 0.8.
     pub coverage_threshold: f64,
@@ -28,6 +29,32 @@ impl Default for GlassSeamConfig {
     }
 }
 
+impl GlassSeamConfig {
+    /// Sets `coverage_threshold`.
+    pub fn with_coverage_threshold(mut self, coverage_threshold: f64) -> Self {
+        self.coverage_threshold = coverage_threshold;
+        self
+    }
+
+    /// Sets `required_points`.
+    pub fn with_required_points(mut self, required_points: Vec<(usize, usize)>) -> Self {
+        self.required_points = required_points;
+        self
+    }
+
+    /// Sets `carve_radius`.
+    pub fn with_carve_radius(mut self, carve_radius: usize) -> Self {
+        self.carve_radius = carve_radius;
+        self
+    }
+
+    /// Sets `use_mst_terminals`.
+    pub fn with_use_mst_terminals(mut self, use_mst_terminals: bool) -> Self {
+        self.use_mst_terminals = use_mst_terminals;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 /// Glass seam bridging algorithm for connecting disconnected regions.
 pub struct GlassSeam {
@@ -96,7 +123,7 @@ fn ensure_connectivity(grid: &mut Grid<Tile>, spawn: (usize, usize), config: &Gl
         None => return,
     };
     let total_floor: usize = regions.iter().map(|r| r.len()).sum();
-    let mut connected: HashSet<usize> = HashSet::new();
+    let mut connected: BTreeSet<usize> = BTreeSet::new();
     connected.insert(spawn_region);
     let mut coverage = coverage_for_regions(&regions, &connected, total_floor);
 
@@ -196,7 +223,7 @@ fn required_regions(
     points: &[(usize, usize)],
     spawn_region: usize,
 ) -> Vec<usize> {
-    let mut set = HashSet::new();
+    let mut set = BTreeSet::new();
     set.insert(spawn_region);
     for &point in points {
         if let Some(region) = region_for_point(labels, width, point) {
@@ -208,7 +235,7 @@ fn required_regions(
 
 fn coverage_for_regions(
     regions: &[Vec<(usize, usize)>],
-    connected: &HashSet<usize>,
+    connected: &BTreeSet<usize>,
     total: usize,
 ) -> f64 {
     if total == 0 {
@@ -223,7 +250,7 @@ fn mst_edges(required: &[usize], regions: &[Vec<(usize, usize)>]) -> Vec<(usize,
         return Vec::new();
     }
 
-    let mut in_tree = HashSet::new();
+    let mut in_tree = BTreeSet::new();
     in_tree.insert(required[0]);
     let mut edges = Vec::new();
 
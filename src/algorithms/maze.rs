@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for perfect maze generation.
+#[serde(default)]
 pub struct MazeConfig {
     /// Width of corridors in cells. Default: 1.
     pub corridor_width: usize,
@@ -14,6 +15,14 @@ impl Default for MazeConfig {
     }
 }
 
+impl MazeConfig {
+    /// Sets `corridor_width`.
+    pub fn with_corridor_width(mut self, corridor_width: usize) -> Self {
+        self.corridor_width = corridor_width;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Perfect maze generator (recursive backtracker).
 pub struct Maze {
@@ -12,13 +12,16 @@ mod maze;
 mod noise_fill;
 mod percolation;
 mod prefab;
+mod presets;
 mod room_accretion;
 mod rooms;
 mod voronoi;
 mod wfc;
 
 pub use agent::{AgentBased, AgentConfig};
-pub use bsp::{Bsp, BspConfig};
+pub use bsp::{Bsp, BspConfig, BspRoomShape};
+#[cfg(feature = "gpu")]
+pub(crate) use cellular::{connect_caves, step as cellular_step};
 pub use cellular::{CellularAutomata, CellularConfig};
 pub use diamond_square::{DiamondSquare, DiamondSquareConfig};
 pub use dla::{Dla, DlaConfig};
@@ -29,15 +32,98 @@ pub use maze::{Maze, MazeConfig};
 pub use noise_fill::{NoiseFill, NoiseFillConfig, NoiseType};
 pub use percolation::{Percolation, PercolationConfig};
 pub use prefab::{
-    Prefab, PrefabConfig, PrefabData, PrefabLegendEntry, PrefabLibrary, PrefabPlacementMode,
-    PrefabPlacer, PrefabTransform,
+    PathStampConfig, Prefab, PrefabConfig, PrefabData, PrefabLegendEntry, PrefabLibrary,
+    PrefabPlacementMode, PrefabPlacer, PrefabTransform,
 };
+pub use presets::{preset, preset_params, presets};
 pub use room_accretion::{RoomAccretion, RoomAccretionConfig, RoomTemplate};
-pub use rooms::{SimpleRooms, SimpleRoomsConfig};
+pub use rooms::{RoomsOnMask, RoomsOnMaskConfig, SimpleRooms, SimpleRoomsConfig};
 pub use voronoi::{Voronoi, VoronoiConfig};
-pub use wfc::{Pattern, Wfc, WfcBacktracker, WfcConfig, WfcPatternExtractor};
+pub use wfc::{
+    Pattern, PatternExtractConfig, Wfc, WfcBacktracker, WfcCheckpoint, WfcConfig,
+    WfcPatternExtractor,
+};
 
 use crate::{Algorithm, Tile};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// Outcome of a run with a soft time limit, from the `generate_timed`
+/// methods of [`Dla`], [`DrunkardWalk`], [`AgentBased`], and [`Wfc`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimedRun {
+    /// The run hit its `max_duration_ms` (or an enclosing
+    /// [`TimeBudgeted`](crate::bench::TimeBudgeted) budget) and stopped
+    /// early; the grid holds what was generated up to then.
+    pub timed_out: bool,
+    /// Wall-clock time spent, or zero when no limit was set.
+    pub elapsed: Duration,
+}
+
+thread_local! {
+    /// Deadline every [`Deadline`] on this thread also honors; set by
+    /// [`with_deadline`].
+    static AMBIENT_DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Runs `f` with every [`Deadline`] created on this thread expiring at
+/// `end` at the latest. Nested calls keep the earlier end.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn with_deadline<R>(end: Instant, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Instant>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            AMBIENT_DEADLINE.with(|d| d.set(self.0));
+        }
+    }
+    let previous = AMBIENT_DEADLINE.with(|d| d.get());
+    let _restore = Restore(previous);
+    AMBIENT_DEADLINE.with(|d| d.set(Some(previous.map_or(end, |p| p.min(end)))));
+    f()
+}
+
+/// Soft deadline checked between iterations. The clock is only read when a
+/// limit is set, since `Instant` is unavailable on some targets (wasm32).
+pub(crate) struct Deadline {
+    start: Option<Instant>,
+    end: Option<Instant>,
+    expired: bool,
+}
+
+impl Deadline {
+    pub(crate) fn new(max_duration_ms: Option<u64>) -> Self {
+        let ambient = AMBIENT_DEADLINE.with(|d| d.get());
+        if max_duration_ms.is_none() && ambient.is_none() {
+            return Self {
+                start: None,
+                end: None,
+                expired: false,
+            };
+        }
+        let start = Instant::now();
+        let own = max_duration_ms.map(|ms| start + Duration::from_millis(ms));
+        Self {
+            start: Some(start),
+            end: own.into_iter().chain(ambient).min(),
+            expired: false,
+        }
+    }
+
+    /// Whether the limit has passed; stays true once it has.
+    pub(crate) fn expired(&mut self) -> bool {
+        if !self.expired {
+            self.expired = self.end.is_some_and(|end| Instant::now() >= end);
+        }
+        self.expired
+    }
+
+    pub(crate) fn finish(self) -> TimedRun {
+        TimedRun {
+            timed_out: self.expired,
+            elapsed: self.start.map_or(Duration::ZERO, |start| start.elapsed()),
+        }
+    }
+}
 
 /// Get algorithm by name
 #[must_use]
@@ -58,6 +144,7 @@ pub fn get(name: &str) -> Option<Box<dyn Algorithm<Tile> + Send + Sync>> {
         "noise_fill" | "noise" => Some(Box::new(NoiseFill::default())),
         "glass_seam" | "gsb" => Some(Box::new(GlassSeam::default())),
         "room_accretion" | "accretion" => Some(Box::new(RoomAccretion::default())),
+        "rooms_on_mask" => Some(Box::new(RoomsOnMask::default())),
         _ => None,
     }
 }
@@ -81,5 +168,6 @@ pub fn list() -> &'static [&'static str] {
         "noise_fill",
         "glass_seam",
         "room_accretion",
+        "rooms_on_mask",
     ]
 }
@@ -1,4 +1,6 @@
 use crate::noise::{NoiseExt, Perlin, Simplex, Value, Worley};
+use crate::parallel;
+use crate::stream::ChunkGenerator;
 use crate::{Algorithm, Grid, Tile};
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +16,7 @@ pub enum NoiseType {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for noise-driven threshold fill.
+#[serde(default)]
 pub struct NoiseFillConfig {
     /// Noise algorithm to use. Default: Perlin.
     pub noise: NoiseType,
@@ -51,6 +54,62 @@ impl Default for NoiseFillConfig {
     }
 }
 
+impl NoiseFillConfig {
+    /// Sets `noise`.
+    pub fn with_noise(mut self, noise: NoiseType) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    /// Sets `frequency`.
+    pub fn with_frequency(mut self, frequency: f64) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Sets `scale`.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets `output_range`.
+    pub fn with_output_range(mut self, output_range: (f64, f64)) -> Self {
+        self.output_range = output_range;
+        self
+    }
+
+    /// Sets `threshold`.
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets `fill_range`.
+    pub fn with_fill_range(mut self, fill_range: (f64, f64)) -> Self {
+        self.fill_range = Some(fill_range);
+        self
+    }
+
+    /// Sets `octaves`.
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    /// Sets `lacunarity`.
+    pub fn with_lacunarity(mut self, lacunarity: f64) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    /// Sets `persistence`.
+    pub fn with_persistence(mut self, persistence: f64) -> Self {
+        self.persistence = persistence;
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Noise-driven threshold fill generator.
 pub struct NoiseFill {
@@ -72,7 +131,23 @@ impl Default for NoiseFill {
 
 impl Algorithm<Tile> for NoiseFill {
     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
-        let (w, h) = (grid.width(), grid.height());
+        let size = (grid.width(), grid.height());
+        self.generate_chunk(grid, (0, 0), size, seed);
+    }
+
+    fn name(&self) -> &'static str {
+        "NoiseFill"
+    }
+}
+
+impl ChunkGenerator for NoiseFill {
+    fn generate_chunk(
+        &self,
+        grid: &mut Grid<Tile>,
+        origin: (usize, usize),
+        map_size: (usize, usize),
+        seed: u64,
+    ) {
         let scale = if self.config.scale > 0.0 {
             self.config.scale
         } else {
@@ -83,44 +158,42 @@ impl Algorithm<Tile> for NoiseFill {
         match self.config.noise {
             NoiseType::Perlin => {
                 let noise = Perlin::new(seed).with_frequency(frequency);
-                fill_with_config(grid, noise, &self.config);
+                fill_with_config(grid, noise, &self.config, origin);
             }
             NoiseType::Simplex => {
                 let noise = Simplex::new(seed).with_frequency(frequency);
-                fill_with_config(grid, noise, &self.config);
+                fill_with_config(grid, noise, &self.config, origin);
             }
             NoiseType::Value => {
                 let noise = Value::new(seed).with_frequency(frequency);
-                fill_with_config(grid, noise, &self.config);
+                fill_with_config(grid, noise, &self.config, origin);
             }
             NoiseType::Worley => {
                 let noise = Worley::new(seed).with_frequency(frequency);
-                fill_with_config(grid, noise, &self.config);
+                fill_with_config(grid, noise, &self.config, origin);
             }
         }
 
         // Keep borders as walls for consistency with standard algorithms.
-        if w > 0 && h > 0 {
-            for x in 0..w {
-                grid.set(x as i32, 0, Tile::Wall);
-                grid.set(x as i32, (h - 1) as i32, Tile::Wall);
-            }
-            for y in 0..h {
-                grid.set(0, y as i32, Tile::Wall);
-                grid.set((w - 1) as i32, y as i32, Tile::Wall);
+        let w = grid.width();
+        let (mw, mh) = map_size;
+        parallel::for_each_row(grid.cells_mut(), w, |y, row| {
+            let gy = origin.1 + y;
+            for (x, cell) in row.iter_mut().enumerate() {
+                let gx = origin.0 + x;
+                if gx == 0 || gy == 0 || gx + 1 >= mw || gy + 1 >= mh {
+                    *cell = Tile::Wall;
+                }
             }
-        }
-    }
-
-    fn name(&self) -> &'static str {
-        "NoiseFill"
+        });
     }
 }
 
-fn fill_with_config<N: crate::noise::NoiseSource>(
+fn fill_with_config<N: crate::noise::NoiseSource + Sync>(
     grid: &mut Grid<Tile>,
     noise: N,
     config: &NoiseFillConfig,
+    origin: (usize, usize),
 ) {
     let (mut out_min, mut out_max) = config.output_range;
     if out_min > out_max {
@@ -140,6 +213,7 @@ fn fill_with_config<N: crate::noise::NoiseSource>(
             range_span,
             fill_range,
             config.threshold,
+            origin,
         );
     } else {
         fill_from_noise(
@@ -149,22 +223,25 @@ fn fill_with_config<N: crate::noise::NoiseSource>(
             range_span,
             fill_range,
             config.threshold,
+            origin,
         );
     }
 }
 
-fn fill_from_noise<N: crate::noise::NoiseSource>(
+fn fill_from_noise<N: crate::noise::NoiseSource + Sync>(
     grid: &mut Grid<Tile>,
     noise: &N,
     out_min: f64,
     range_span: f64,
     fill_range: Option<(f64, f64)>,
     threshold: f64,
+    origin: (usize, usize),
 ) {
-    let (w, h) = (grid.width(), grid.height());
-    for y in 0..h {
-        for x in 0..w {
-            let raw = noise.sample(x as f64, y as f64);
+    let w = grid.width();
+    parallel::for_each_row(grid.cells_mut(), w, |y, row| {
+        let gy = (origin.1 + y) as f64;
+        for (x, cell) in row.iter_mut().enumerate() {
+            let raw = noise.sample((origin.0 + x) as f64, gy);
             let mut value = (raw + 1.0) * 0.5;
             value = out_min + value * range_span;
 
@@ -173,8 +250,7 @@ fn fill_from_noise<N: crate::noise::NoiseSource>(
                 None => value >= threshold,
             };
 
-            let tile = if fill { Tile::Floor } else { Tile::Wall };
-            grid.set(x as i32, y as i32, tile);
+            *cell = if fill { Tile::Floor } else { Tile::Wall };
         }
-    }
+    });
 }
@@ -1,8 +1,11 @@
+use crate::rng;
+use crate::stream::ChunkGenerator;
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for percolation-based generation.
+#[serde(default)]
 pub struct PercolationConfig {
     /// Probability of each cell being floor. Default: 0.45.
     pub fill_probability: f64,
@@ -19,6 +22,20 @@ impl Default for PercolationConfig {
     }
 }
 
+impl PercolationConfig {
+    /// Sets `fill_probability`.
+    pub fn with_fill_probability(mut self, fill_probability: f64) -> Self {
+        self.fill_probability = fill_probability;
+        self
+    }
+
+    /// Sets `keep_largest`.
+    pub fn with_keep_largest(mut self, keep_largest: bool) -> Self {
+        self.keep_largest = keep_largest;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Percolation cluster generator.
 pub struct Percolation {
@@ -78,3 +95,28 @@ impl Algorithm<Tile> for Percolation {
         "Percolation"
     }
 }
+
+/// Streams with a per-cell position hash instead of the sequential RNG, and
+/// skips `keep_largest` (it needs the whole map), so output differs from
+/// [`Algorithm::generate`].
+impl ChunkGenerator for Percolation {
+    fn generate_chunk(
+        &self,
+        grid: &mut Grid<Tile>,
+        origin: (usize, usize),
+        map_size: (usize, usize),
+        seed: u64,
+    ) {
+        let (w, h) = map_size;
+        for cy in 0..grid.height() {
+            let y = origin.1 + cy;
+            for cx in 0..grid.width() {
+                let x = origin.0 + cx;
+                let interior = x > 0 && y > 0 && x + 1 < w && y + 1 < h;
+                if interior && rng::cell_unit(seed, x, y) < self.config.fill_probability {
+                    grid.set(cx as i32, cy as i32, Tile::Floor);
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
+use crate::error::{Error, Result};
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+#[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for prefab placement.
+#[serde(default)]
 pub struct PrefabConfig {
     /// Maximum prefabs to place. Default: 3.
     pub max_prefabs: usize,
@@ -36,6 +39,50 @@ impl Default for PrefabConfig {
     }
 }
 
+impl PrefabConfig {
+    /// Sets `max_prefabs`.
+    pub fn with_max_prefabs(mut self, max_prefabs: usize) -> Self {
+        self.max_prefabs = max_prefabs;
+        self
+    }
+
+    /// Sets `min_spacing`.
+    pub fn with_min_spacing(mut self, min_spacing: usize) -> Self {
+        self.min_spacing = min_spacing;
+        self
+    }
+
+    /// Sets `allow_rotation`.
+    pub fn with_allow_rotation(mut self, allow_rotation: bool) -> Self {
+        self.allow_rotation = allow_rotation;
+        self
+    }
+
+    /// Sets `allow_mirroring`.
+    pub fn with_allow_mirroring(mut self, allow_mirroring: bool) -> Self {
+        self.allow_mirroring = allow_mirroring;
+        self
+    }
+
+    /// Sets `weighted_selection`.
+    pub fn with_weighted_selection(mut self, weighted_selection: bool) -> Self {
+        self.weighted_selection = weighted_selection;
+        self
+    }
+
+    /// Sets `placement_mode`.
+    pub fn with_placement_mode(mut self, placement_mode: PrefabPlacementMode) -> Self {
+        self.placement_mode = placement_mode;
+        self
+    }
+
+    /// Sets `tags`.
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 /// How a prefab is applied to the grid.
 pub enum PrefabPlacementMode {
@@ -45,12 +92,63 @@ pub enum PrefabPlacementMode {
     PaintWall,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Configuration for stamping prefabs along a path.
+#[serde(default)]
+pub struct PathStampConfig {
+    /// Path steps between consecutive stamps. Default: 8.
+    pub interval: usize,
+    /// Index of the first path point to stamp. Default: 0.
+    pub offset: usize,
+    /// Rotate prefabs to follow the path direction. Default: true.
+    pub align_to_path: bool,
+}
+
+impl Default for PathStampConfig {
+    fn default() -> Self {
+        Self {
+            interval: 8,
+            offset: 0,
+            align_to_path: true,
+        }
+    }
+}
+
+impl PathStampConfig {
+    /// Sets `interval`.
+    pub fn with_interval(mut self, interval: usize) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets `offset`.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets `align_to_path`.
+    pub fn with_align_to_path(mut self, align_to_path: bool) -> Self {
+        self.align_to_path = align_to_path;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 /// Maps a pattern character to tile, marker, and mask values.
 pub struct PrefabLegendEntry {
     pub tile: Option<String>,
     pub marker: Option<String>,
     pub mask: Option<String>,
+    /// Additional markers emitted for this symbol (after `marker`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<String>,
+    /// Additional mask names applied to this symbol (after `mask`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masks: Vec<String>,
+    /// Metadata attached to every marker emitted for this symbol.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -63,15 +161,16 @@ pub struct PrefabData {
     pub weight: f32,
     pub tags: Vec<String>,
     #[serde(default)]
-    pub legend: Option<HashMap<String, PrefabLegendEntry>>,
+    pub legend: Option<BTreeMap<String, PrefabLegendEntry>>,
 }
 
 #[derive(Clone, Debug, Default)]
 /// A single cell in a resolved prefab.
 pub struct PrefabCell {
     pub tile: Option<Tile>,
-    pub marker: Option<String>,
-    pub mask: Option<String>,
+    pub markers: Vec<String>,
+    pub masks: Vec<String>,
+    pub metadata: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,7 +181,7 @@ pub struct Prefab {
     pub height: usize,
     pub cells: Vec<PrefabCell>,
     pub symbols: Vec<char>,
-    pub legend: Option<HashMap<char, PrefabLegendEntry>>,
+    pub legend: Option<BTreeMap<char, PrefabLegendEntry>>,
     pub weight: f32,
     pub tags: Vec<String>,
 }
@@ -133,8 +232,7 @@ impl Prefab {
             cells: vec![
                 PrefabCell {
                     tile: Some(Tile::Floor),
-                    marker: None,
-                    mask: None,
+                    ..Default::default()
                 };
                 w * h
             ],
@@ -241,19 +339,30 @@ impl Prefab {
         }
     }
 
-    /// Returns the marker string at `(x, y)`, if set.
+    /// Returns the first marker string at `(x, y)`, if set.
     pub fn cell_marker(&self, x: usize, y: usize) -> Option<&str> {
-        if x < self.width && y < self.height {
-            self.cells[y * self.width + x].marker.as_deref()
-        } else {
-            None
-        }
+        self.cell_markers(x, y).first().map(String::as_str)
+    }
+
+    /// Returns all marker strings at `(x, y)`.
+    pub fn cell_markers(&self, x: usize, y: usize) -> &[String] {
+        self.cell(x, y).map(|c| c.markers.as_slice()).unwrap_or(&[])
     }
 
-    /// Returns the mask string at `(x, y)`, if set.
+    /// Returns the first mask string at `(x, y)`, if set.
     pub fn cell_mask(&self, x: usize, y: usize) -> Option<&str> {
+        self.cell_masks(x, y).first().map(String::as_str)
+    }
+
+    /// Returns all mask names at `(x, y)`.
+    pub fn cell_masks(&self, x: usize, y: usize) -> &[String] {
+        self.cell(x, y).map(|c| c.masks.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the resolved cell at `(x, y)`, if in bounds.
+    pub fn cell(&self, x: usize, y: usize) -> Option<&PrefabCell> {
         if x < self.width && y < self.height {
-            self.cells[y * self.width + x].mask.as_deref()
+            self.cells.get(y * self.width + x)
         } else {
             None
         }
@@ -298,10 +407,10 @@ impl PrefabLibrary {
         self.prefabs.push(prefab);
     }
 
-    /// Loads a library from a JSON file.
-    pub fn load_from_json<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = std::fs::read_to_string(path)?;
-        let data: PrefabLibraryData = serde_json::from_str(&content)?;
+    /// Parses a library from a JSON string.
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let data: PrefabLibraryData =
+            serde_json::from_str(json).map_err(|e| Error::parse("prefab library", e))?;
 
         let mut library = Self::new();
         for prefab_data in data.prefabs {
@@ -311,8 +420,16 @@ impl PrefabLibrary {
         Ok(library)
     }
 
+    /// Loads a library from a JSON file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_from_json<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json_str(&content)
+    }
+
     /// Loads and merges libraries from multiple JSON paths.
-    pub fn load_from_paths<I, P>(paths: I) -> Result<Self, Box<dyn std::error::Error>>
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_from_paths<I, P>(paths: I) -> Result<Self>
     where
         I: IntoIterator<Item = P>,
         P: AsRef<Path>,
@@ -326,7 +443,8 @@ impl PrefabLibrary {
     }
 
     /// Loads all JSON prefab files from a directory.
-    pub fn load_from_dir<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_from_dir<P: AsRef<Path>>(path: P) -> Result<Self> {
         let mut entries: Vec<std::path::PathBuf> = std::fs::read_dir(path)?
             .filter_map(|entry| entry.ok().map(|e| e.path()))
             .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
@@ -339,7 +457,8 @@ impl PrefabLibrary {
     }
 
     /// Saves the library to a JSON file.
-    pub fn save_to_json<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_to_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let data = PrefabLibraryData {
             prefabs: self
                 .prefabs
@@ -428,20 +547,11 @@ impl PrefabLibrary {
             return rng.pick(&candidates).copied();
         }
 
-        let total_weight: f32 = candidates.iter().map(|p| p.weight).sum();
-        if total_weight <= 0.0 {
-            return rng.pick(&candidates).copied();
+        let weights: Vec<f64> = candidates.iter().map(|p| p.weight as f64).collect();
+        match rng.weighted_index(&weights) {
+            Some(i) => Some(candidates[i]),
+            None => rng.pick(&candidates).copied(),
         }
-
-        let mut target = rng.random() as f32 * total_weight;
-        for prefab in &candidates {
-            target -= prefab.weight;
-            if target <= 0.0 {
-                return Some(prefab);
-            }
-        }
-
-        candidates.last().copied()
     }
 
     /// Returns prefabs matching any of the given tags.
@@ -522,6 +632,29 @@ impl PrefabTransform {
         result
     }
 
+    /// Maps a cell position in a `width` x `height` prefab to its position after
+    /// [`apply`](Self::apply).
+    pub fn apply_to_point(
+        &self,
+        point: (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> (usize, usize) {
+        let (mut x, mut y) = point;
+        let (mut w, mut h) = (width, height);
+        if self.mirror_h {
+            x = w - 1 - x;
+        }
+        if self.mirror_v {
+            y = h - 1 - y;
+        }
+        for _ in 0..self.rotation {
+            (x, y) = (h - 1 - y, x);
+            (w, h) = (h, w);
+        }
+        (x, y)
+    }
+
     /// Creates a random transform.
     pub fn random(rng: &mut Rng, allow_rotation: bool, allow_mirroring: bool) -> Self {
         Self {
@@ -632,54 +765,283 @@ impl PrefabPlacer {
                 continue;
             }
 
-            for py in 0..prefab.height {
-                for px in 0..prefab.width {
-                    let cell_tile = prefab.cell_tile(px, py);
-                    let cell_marker = prefab.cell_marker(px, py);
-                    let cell_mask = prefab.cell_mask(px, py);
-                    let gx = (x + px) as i32;
-                    let gy = (y + py) as i32;
-
-                    let mut applied = false;
-                    if let Some(tile) = cell_tile {
-                        let current = *grid.get(gx, gy).unwrap_or(&Tile::Wall);
-                        let should_place = match self.config.placement_mode {
-                            PrefabPlacementMode::Overwrite => true,
-                            PrefabPlacementMode::Merge => matches!(current, Tile::Wall),
-                            PrefabPlacementMode::PaintFloor => matches!(current, Tile::Floor),
-                            PrefabPlacementMode::PaintWall => matches!(current, Tile::Wall),
-                        };
-                        if should_place {
-                            grid.set(gx, gy, tile);
-                            applied = true;
-                        }
+            self.stamp(grid, &prefab, x, y, semantic.as_deref_mut());
+            placed.push((x, y, prefab.width, prefab.height));
+        }
+    }
+}
+
+impl PrefabPlacer {
+    /// Stamps prefabs at regular intervals along a path (bridges, gates, checkpoints).
+    ///
+    /// Each stamp is centred on a path point. With `align_to_path` set, prefabs are
+    /// rotated so that their authored left-to-right axis follows the local path
+    /// direction. Stamps that would not fit inside the grid are skipped. Returns the
+    /// path points where a prefab was stamped.
+    pub fn stamp_along_path(
+        &self,
+        grid: &mut Grid<Tile>,
+        path: &[(usize, usize)],
+        stamp: &PathStampConfig,
+        seed: u64,
+    ) -> Vec<(usize, usize)> {
+        self.stamp_along_path_internal(grid, path, stamp, seed, None)
+    }
+
+    /// Like [`stamp_along_path`](Self::stamp_along_path), also writing prefab markers and masks.
+    pub fn stamp_along_path_with_semantic(
+        &self,
+        grid: &mut Grid<Tile>,
+        path: &[(usize, usize)],
+        stamp: &PathStampConfig,
+        seed: u64,
+        semantic: &mut crate::semantic::SemanticLayers,
+    ) -> Vec<(usize, usize)> {
+        self.stamp_along_path_internal(grid, path, stamp, seed, Some(semantic))
+    }
+
+    fn stamp_along_path_internal(
+        &self,
+        grid: &mut Grid<Tile>,
+        path: &[(usize, usize)],
+        stamp: &PathStampConfig,
+        seed: u64,
+        mut semantic: Option<&mut crate::semantic::SemanticLayers>,
+    ) -> Vec<(usize, usize)> {
+        let mut rng = Rng::new(seed);
+        let mut stamped = Vec::new();
+        let interval = stamp.interval.max(1);
+
+        for i in (stamp.offset..path.len()).step_by(interval) {
+            if stamped.len() >= self.config.max_prefabs {
+                break;
+            }
+            let Some(base_prefab) = self.library.select_with_tags(
+                &mut rng,
+                self.config.tags.as_deref(),
+                self.config.weighted_selection,
+            ) else {
+                break;
+            };
+
+            let mut transform = PrefabTransform::random(
+                &mut rng,
+                self.config.allow_rotation,
+                self.config.allow_mirroring,
+            );
+            if stamp.align_to_path {
+                transform.rotation = path_rotation(path, i);
+            }
+            let prefab = transform.apply(base_prefab);
+
+            let (cx, cy) = path[i];
+            let x = cx as i32 - (prefab.width / 2) as i32;
+            let y = cy as i32 - (prefab.height / 2) as i32;
+            if x < 0
+                || y < 0
+                || x as usize + prefab.width > grid.width()
+                || y as usize + prefab.height > grid.height()
+            {
+                continue;
+            }
+
+            self.stamp(
+                grid,
+                &prefab,
+                x as usize,
+                y as usize,
+                semantic.as_deref_mut(),
+            );
+            stamped.push((cx, cy));
+        }
+
+        stamped
+    }
+
+    /// Caps dead-end corridor stubs with prefabs (alcoves, shrines, stashes).
+    ///
+    /// Caps are authored with their entrance on the middle of the left edge and
+    /// extend to the right; they are rotated to continue the corridor heading and
+    /// attached just past the dead-end cell. A cap is only placed where its whole
+    /// footprint is solid wall and at least one cell from the grid border. Library
+    /// selection honours `config.tags`, and at most `config.max_prefabs` caps are
+    /// placed. Returns the dead-end cells that were capped.
+    pub fn cap_dead_ends(&self, grid: &mut Grid<Tile>, seed: u64) -> Vec<(usize, usize)> {
+        self.cap_dead_ends_internal(grid, seed, None)
+    }
+
+    /// Like [`cap_dead_ends`](Self::cap_dead_ends), also writing prefab markers and masks.
+    pub fn cap_dead_ends_with_semantic(
+        &self,
+        grid: &mut Grid<Tile>,
+        seed: u64,
+        semantic: &mut crate::semantic::SemanticLayers,
+    ) -> Vec<(usize, usize)> {
+        self.cap_dead_ends_internal(grid, seed, Some(semantic))
+    }
+
+    fn cap_dead_ends_internal(
+        &self,
+        grid: &mut Grid<Tile>,
+        seed: u64,
+        mut semantic: Option<&mut crate::semantic::SemanticLayers>,
+    ) -> Vec<(usize, usize)> {
+        const ATTEMPTS_PER_DEAD_END: usize = 4;
+
+        let mut rng = Rng::new(seed);
+        let mut dead_ends = crate::effects::find_dead_ends(grid);
+        rng.shuffle(&mut dead_ends);
+        let mut capped = Vec::new();
+
+        for (x, y) in dead_ends {
+            if capped.len() >= self.config.max_prefabs {
+                break;
+            }
+            let Some((nx, ny)) = grid
+                .neighbors_4(x, y)
+                .find(|&(nx, ny)| grid[(nx, ny)].is_floor())
+            else {
+                continue;
+            };
+            let heading = (x as i32 - nx as i32, y as i32 - ny as i32);
+            let rotation = path_rotation(&[(nx, ny), (x, y)], 1);
+            let tip = (x as i32 + heading.0, y as i32 + heading.1);
+
+            for _ in 0..ATTEMPTS_PER_DEAD_END {
+                let Some(base_prefab) = self.library.select_with_tags(
+                    &mut rng,
+                    self.config.tags.as_deref(),
+                    self.config.weighted_selection,
+                ) else {
+                    break;
+                };
+                let transform = PrefabTransform {
+                    rotation,
+                    mirror_h: false,
+                    mirror_v: self.config.allow_mirroring && rng.chance(0.5),
+                };
+                let prefab = transform.apply(base_prefab);
+                let (ax, ay) = transform.apply_to_point(
+                    (0, base_prefab.height / 2),
+                    base_prefab.width,
+                    base_prefab.height,
+                );
+                if prefab.cell_tile(ax, ay) != Some(Tile::Floor) {
+                    continue;
+                }
+
+                let ox = tip.0 - ax as i32;
+                let oy = tip.1 - ay as i32;
+                if ox < 1
+                    || oy < 1
+                    || ox as usize + prefab.width + 1 > grid.width()
+                    || oy as usize + prefab.height + 1 > grid.height()
+                {
+                    continue;
+                }
+                let footprint_solid = (0..prefab.height).all(|py| {
+                    (0..prefab.width).all(|px| {
+                        grid.get(ox + px as i32, oy + py as i32)
+                            .is_some_and(|t| t.is_wall())
+                    })
+                });
+                if !footprint_solid {
+                    continue;
+                }
+
+                self.stamp(
+                    grid,
+                    &prefab,
+                    ox as usize,
+                    oy as usize,
+                    semantic.as_deref_mut(),
+                );
+                capped.push((x, y));
+                break;
+            }
+        }
+
+        capped
+    }
+
+    fn stamp(
+        &self,
+        grid: &mut Grid<Tile>,
+        prefab: &Prefab,
+        x: usize,
+        y: usize,
+        mut semantic: Option<&mut crate::semantic::SemanticLayers>,
+    ) {
+        for py in 0..prefab.height {
+            for px in 0..prefab.width {
+                let Some(cell) = prefab.cell(px, py) else {
+                    continue;
+                };
+                let gx = (x + px) as i32;
+                let gy = (y + py) as i32;
+
+                let mut applied = false;
+                if let Some(tile) = cell.tile {
+                    let current = *grid.get(gx, gy).unwrap_or(&Tile::Wall);
+                    let should_place = match self.config.placement_mode {
+                        PrefabPlacementMode::Overwrite => true,
+                        PrefabPlacementMode::Merge => matches!(current, Tile::Wall),
+                        PrefabPlacementMode::PaintFloor => matches!(current, Tile::Floor),
+                        PrefabPlacementMode::PaintWall => matches!(current, Tile::Wall),
+                    };
+                    if should_place {
+                        grid.set(gx, gy, tile);
+                        applied = true;
                     }
+                }
 
-                    if let Some(layers) = semantic.as_deref_mut() {
-                        let marker_allowed = cell_tile.is_none() || applied;
-                        if marker_allowed {
-                            if let Some(tag) = cell_marker {
-                                layers.markers.push(crate::semantic::Marker::with_tag(
-                                    gx as u32,
-                                    gy as u32,
-                                    tag.to_string(),
-                                ));
-                            }
-                            if let Some(mask) = cell_mask {
-                                apply_prefab_mask(&mut layers.masks, gx, gy, mask);
-                            }
+                if let Some(layers) = semantic.as_deref_mut() {
+                    let marker_allowed = cell.tile.is_none() || applied;
+                    if marker_allowed {
+                        for tag in &cell.markers {
+                            let mut marker = crate::semantic::Marker::with_tag(
+                                gx as u32,
+                                gy as u32,
+                                tag.to_string(),
+                            );
+                            marker
+                                .metadata
+                                .extend(cell.metadata.iter().map(|(k, v)| (k.clone(), v.clone())));
+                            layers.markers.push(marker);
+                        }
+                        for mask in &cell.masks {
+                            apply_prefab_mask(&mut layers.masks, gx, gy, mask);
                         }
                     }
                 }
             }
-            placed.push((x, y, prefab.width, prefab.height));
         }
     }
 }
 
+/// Clockwise quarter turns that align a prefab's authored east-facing axis
+/// with the path direction at `index`.
+fn path_rotation(path: &[(usize, usize)], index: usize) -> u8 {
+    let prev = path[index.saturating_sub(1)];
+    let next = path[(index + 1).min(path.len() - 1)];
+    let dx = next.0 as i32 - prev.0 as i32;
+    let dy = next.1 as i32 - prev.1 as i32;
+    if dx.abs() >= dy.abs() {
+        if dx >= 0 {
+            0
+        } else {
+            2
+        }
+    } else if dy > 0 {
+        1
+    } else {
+        3
+    }
+}
+
 fn parse_pattern_with_legend(
     pattern: &[impl AsRef<str>],
-    legend: Option<&HashMap<char, PrefabLegendEntry>>,
+    legend: Option<&BTreeMap<char, PrefabLegendEntry>>,
 ) -> (Vec<PrefabCell>, Vec<char>) {
     let mut cells = Vec::new();
     let mut symbols = Vec::new();
@@ -704,8 +1066,7 @@ fn default_cell_from_symbol(ch: char) -> PrefabCell {
     match ch {
         '.' => PrefabCell {
             tile: Some(Tile::Floor),
-            marker: None,
-            mask: None,
+            ..Default::default()
         },
         '#' => PrefabCell::default(),
         _ => PrefabCell::default(),
@@ -715,8 +1076,9 @@ fn default_cell_from_symbol(ch: char) -> PrefabCell {
 fn parse_legend_entry(entry: &PrefabLegendEntry) -> PrefabCell {
     PrefabCell {
         tile: parse_tile_name(entry.tile.as_deref()),
-        marker: entry.marker.clone(),
-        mask: entry.mask.clone(),
+        markers: entry.marker.iter().chain(&entry.markers).cloned().collect(),
+        masks: entry.mask.iter().chain(&entry.masks).cloned().collect(),
+        metadata: entry.metadata.clone(),
     }
 }
 
@@ -730,8 +1092,10 @@ fn parse_tile_name(value: Option<&str>) -> Option<Tile> {
     }
 }
 
-fn convert_legend(legend: &HashMap<String, PrefabLegendEntry>) -> HashMap<char, PrefabLegendEntry> {
-    let mut out = HashMap::new();
+fn convert_legend(
+    legend: &BTreeMap<String, PrefabLegendEntry>,
+) -> BTreeMap<char, PrefabLegendEntry> {
+    let mut out = BTreeMap::new();
     for (key, value) in legend {
         let mut chars = key.chars();
         if let Some(ch) = chars.next() {
@@ -742,8 +1106,8 @@ fn convert_legend(legend: &HashMap<String, PrefabLegendEntry>) -> HashMap<char,
 }
 
 fn convert_legend_to_strings(
-    legend: &HashMap<char, PrefabLegendEntry>,
-) -> HashMap<String, PrefabLegendEntry> {
+    legend: &BTreeMap<char, PrefabLegendEntry>,
+) -> BTreeMap<String, PrefabLegendEntry> {
     legend
         .iter()
         .map(|(ch, entry)| (ch.to_string(), entry.clone()))
@@ -758,7 +1122,8 @@ fn apply_prefab_mask(masks: &mut crate::semantic::Masks, x: i32, y: i32, mask: &
     if y >= masks.height || x >= masks.width {
         return;
     }
-    match mask.trim().to_ascii_lowercase().as_str() {
+    let name = mask.trim().to_ascii_lowercase();
+    match name.as_str() {
         "no_spawn" | "nospawn" | "reserved" => {
             if let Some(row) = masks.no_spawn.get_mut(y) {
                 if let Some(cell) = row.get_mut(x) {
@@ -766,6 +1131,7 @@ fn apply_prefab_mask(masks: &mut crate::semantic::Masks, x: i32, y: i32, mask: &
                 }
             }
         }
-        _ => {}
+        "" => {}
+        _ => masks.set_named(&name, x, y, true),
     }
 }
@@ -0,0 +1,124 @@
+//! Named parameter presets for built-in algorithms.
+//!
+//! Presets are plain [`Params`] maps, so they can be inspected, tweaked, and
+//! passed to [`ops::build_algorithm`]. Ops params
+//! also accept a `"preset"` key; explicit params override the preset's values.
+//!
+//! ```rust
+//! use terrain_forge::{algorithms, Algorithm, Grid};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::preset("cellular", "dense_caves")
+//!     .unwrap()
+//!     .generate(&mut grid, 42);
+//! assert!(grid.count(|t| t.is_floor()) > 0);
+//! ```
+
+use crate::ops::{self, Params};
+use crate::{Algorithm, Tile};
+use serde_json::json;
+
+/// Lists preset names available for an algorithm (aliases accepted).
+#[must_use]
+pub fn presets(algorithm: &str) -> &'static [&'static str] {
+    match canonical(algorithm) {
+        "bsp" => &["tight_rooms", "grand_halls"],
+        "cellular" => &["dense_caves", "open_caverns", "sparse_pockets"],
+        "drunkard" => &["winding_tunnels", "open_caves"],
+        "maze" => &["narrow", "wide_halls"],
+        "rooms" => &["sparse", "crowded"],
+        "voronoi" => &["islands", "continents"],
+        "dla" => &["coral", "dense_growth"],
+        "wfc" => &["sparse", "open"],
+        "percolation" => &["sparse", "porous"],
+        "diamond_square" => &["smooth", "rough"],
+        "agent" => &["tunnels", "swarm"],
+        "noise_fill" => &["blobs", "archipelago", "cellular_cracks"],
+        "room_accretion" => &["compact", "looping"],
+        _ => &[],
+    }
+}
+
+/// Returns the params for a named preset, or `None` if unknown.
+#[must_use]
+pub fn preset_params(algorithm: &str, preset: &str) -> Option<Params> {
+    let value = match (canonical(algorithm), preset) {
+        ("bsp", "tight_rooms") => json!({ "min_room_size": 4, "max_depth": 6, "room_padding": 1 }),
+        ("bsp", "grand_halls") => json!({ "min_room_size": 8, "max_depth": 3, "room_padding": 2 }),
+        ("cellular", "dense_caves") => json!({ "initial_floor_chance": 0.55, "iterations": 6 }),
+        ("cellular", "open_caverns") => json!({
+            "initial_floor_chance": 0.6,
+            "iterations": 4,
+            "birth_limit": 4,
+            "death_limit": 3
+        }),
+        ("cellular", "sparse_pockets") => json!({
+            "initial_floor_chance": 0.4,
+            "iterations": 5,
+            "birth_limit": 6,
+            "death_limit": 4
+        }),
+        ("drunkard", "winding_tunnels") => json!({ "floor_percent": 0.25 }),
+        ("drunkard", "open_caves") => json!({ "floor_percent": 0.55 }),
+        ("maze", "narrow") => json!({ "corridor_width": 1 }),
+        ("maze", "wide_halls") => json!({ "corridor_width": 2 }),
+        ("rooms", "sparse") => {
+            json!({ "max_rooms": 6, "min_room_size": 5, "max_room_size": 12, "min_spacing": 3 })
+        }
+        ("rooms", "crowded") => {
+            json!({ "max_rooms": 30, "min_room_size": 3, "max_room_size": 8, "min_spacing": 1 })
+        }
+        ("voronoi", "islands") => json!({ "num_points": 30, "floor_chance": 0.35 }),
+        ("voronoi", "continents") => json!({ "num_points": 8, "floor_chance": 0.6 }),
+        ("dla", "coral") => json!({ "num_particles": 300, "max_walk_steps": 2000 }),
+        ("dla", "dense_growth") => json!({ "num_particles": 1200, "max_walk_steps": 1000 }),
+        ("wfc", "sparse") => json!({ "floor_weight": 0.3 }),
+        ("wfc", "open") => json!({ "floor_weight": 0.6 }),
+        ("percolation", "sparse") => json!({ "fill_probability": 0.4, "keep_largest": true }),
+        ("percolation", "porous") => json!({ "fill_probability": 0.55, "keep_largest": false }),
+        ("diamond_square", "smooth") => json!({ "roughness": 0.3, "threshold": 0.45 }),
+        ("diamond_square", "rough") => json!({ "roughness": 0.9, "threshold": 0.4 }),
+        ("agent", "tunnels") => {
+            json!({ "num_agents": 3, "steps_per_agent": 400, "turn_chance": 0.15 })
+        }
+        ("agent", "swarm") => {
+            json!({ "num_agents": 12, "steps_per_agent": 120, "turn_chance": 0.5 })
+        }
+        ("noise_fill", "blobs") => {
+            json!({ "noise": "perlin", "frequency": 0.06, "threshold": 0.45, "octaves": 2 })
+        }
+        ("noise_fill", "archipelago") => json!({
+            "noise": "simplex",
+            "frequency": 0.05,
+            "threshold": 0.35,
+            "octaves": 4,
+            "persistence": 0.5
+        }),
+        ("noise_fill", "cellular_cracks") => {
+            json!({ "noise": "worley", "frequency": 0.1, "fill_range": [0.0, 0.2] })
+        }
+        ("room_accretion", "compact") => json!({ "max_rooms": 30, "loop_chance": 0.05 }),
+        ("room_accretion", "looping") => json!({ "max_rooms": 20, "loop_chance": 0.35 }),
+        _ => return None,
+    };
+    serde_json::from_value(value).ok()
+}
+
+/// Builds an algorithm configured with a named preset.
+#[must_use]
+pub fn preset(algorithm: &str, preset: &str) -> Option<Box<dyn Algorithm<Tile> + Send + Sync>> {
+    let params = preset_params(algorithm, preset)?;
+    ops::build_algorithm(algorithm, Some(&params)).ok()
+}
+
+/// Maps algorithm aliases to the name used by the preset tables.
+fn canonical(algorithm: &str) -> &str {
+    match algorithm.trim() {
+        "cellular_automata" => "cellular",
+        "simple_rooms" => "rooms",
+        "wave_function_collapse" => "wfc",
+        "noise" => "noise_fill",
+        "accretion" => "room_accretion",
+        other => other,
+    }
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for Brogue-style room accretion.
+#[serde(default)]
 pub struct RoomAccretionConfig {
     /// Room shape templates to use.
     pub templates: Vec<RoomTemplate>,
@@ -49,6 +50,26 @@ impl Default for RoomAccretionConfig {
     }
 }
 
+impl RoomAccretionConfig {
+    /// Sets `templates`.
+    pub fn with_templates(mut self, templates: Vec<RoomTemplate>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Sets `max_rooms`.
+    pub fn with_max_rooms(mut self, max_rooms: usize) -> Self {
+        self.max_rooms = max_rooms;
+        self
+    }
+
+    /// Sets `loop_chance`.
+    pub fn with_loop_chance(mut self, loop_chance: f64) -> Self {
+        self.loop_chance = loop_chance;
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Brogue-style organic room accretion generator.
 pub struct RoomAccretion {
@@ -72,6 +93,10 @@ impl Algorithm<Tile> for RoomAccretion {
     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
         let mut rng = Rng::new(seed);
         let (w, h) = (grid.width(), grid.height());
+        // Rooms slide in from at least 5 cells off each edge.
+        if w < 11 || h < 11 {
+            return;
+        }
 
         // Start with first room in center
         let center_x = w / 2;
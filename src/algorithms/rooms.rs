@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for simple room placement.
+#[serde(default)]
 pub struct SimpleRoomsConfig {
     /// Minimum room dimension. Default: 4.
     pub min_room_size: usize,
@@ -25,6 +26,32 @@ impl Default for SimpleRoomsConfig {
     }
 }
 
+impl SimpleRoomsConfig {
+    /// Sets `min_room_size`.
+    pub fn with_min_room_size(mut self, min_room_size: usize) -> Self {
+        self.min_room_size = min_room_size;
+        self
+    }
+
+    /// Sets `max_room_size`.
+    pub fn with_max_room_size(mut self, max_room_size: usize) -> Self {
+        self.max_room_size = max_room_size;
+        self
+    }
+
+    /// Sets `max_rooms`.
+    pub fn with_max_rooms(mut self, max_rooms: usize) -> Self {
+        self.max_rooms = max_rooms;
+        self
+    }
+
+    /// Sets `min_spacing`.
+    pub fn with_min_spacing(mut self, min_spacing: usize) -> Self {
+        self.min_spacing = min_spacing;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Simple rectangular room placement generator.
 pub struct SimpleRooms {
@@ -122,3 +149,226 @@ fn carve_v(grid: &mut Grid<Tile>, y1: usize, y2: usize, x: usize) {
         grid.set(x as i32, y as i32, Tile::Floor);
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Configuration for [`RoomsOnMask`].
+#[serde(default)]
+pub struct RoomsOnMaskConfig {
+    /// Minimum room dimension. Default: 4.
+    pub min_room_size: usize,
+    /// Maximum room dimension. Default: 10.
+    pub max_room_size: usize,
+    /// Maximum number of rooms to place. Default: 12.
+    pub max_rooms: usize,
+    /// Minimum gap between rooms. Default: 1.
+    pub min_spacing: usize,
+    /// Random room sizes tried per room before placement stops.
+    /// Default: 8.
+    pub attempts: usize,
+    /// Use the grid's current floor as the site, so rooms can be placed
+    /// on an earlier pass's open ground (combined with any explicit mask).
+    /// Default: false.
+    pub on_floor: bool,
+}
+
+impl Default for RoomsOnMaskConfig {
+    fn default() -> Self {
+        Self {
+            min_room_size: 4,
+            max_room_size: 10,
+            max_rooms: 12,
+            min_spacing: 1,
+            attempts: 8,
+            on_floor: false,
+        }
+    }
+}
+
+impl RoomsOnMaskConfig {
+    /// Sets `min_room_size`.
+    pub fn with_min_room_size(mut self, min_room_size: usize) -> Self {
+        self.min_room_size = min_room_size;
+        self
+    }
+
+    /// Sets `max_room_size`.
+    pub fn with_max_room_size(mut self, max_room_size: usize) -> Self {
+        self.max_room_size = max_room_size;
+        self
+    }
+
+    /// Sets `max_rooms`.
+    pub fn with_max_rooms(mut self, max_rooms: usize) -> Self {
+        self.max_rooms = max_rooms;
+        self
+    }
+
+    /// Sets `min_spacing`.
+    pub fn with_min_spacing(mut self, min_spacing: usize) -> Self {
+        self.min_spacing = min_spacing;
+        self
+    }
+
+    /// Sets `on_floor`.
+    pub fn with_on_floor(mut self, on_floor: bool) -> Self {
+        self.on_floor = on_floor;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Rectangular rooms placed only inside a mask, joined by corridors that
+/// stay inside it.
+///
+/// Run it through [`Masked`](crate::Masked) (or
+/// [`Algorithm::generate_masked`]) to restrict it to a shaped site such as
+/// flat land or a courtyard; plain [`generate`](Algorithm::generate) uses
+/// the whole grid. Masked cells are walled, then rooms are placed greedily
+/// at random positions where they fit entirely on the site, and each room
+/// is joined to its nearest reachable predecessor by a shortest path over
+/// masked cells. Cells outside the mask are left as they are.
+pub struct RoomsOnMask {
+    config: RoomsOnMaskConfig,
+}
+
+impl RoomsOnMask {
+    /// Creates a new masked room generator with the given config.
+    pub fn new(config: RoomsOnMaskConfig) -> Self {
+        Self { config }
+    }
+
+    fn place(&self, grid: &mut Grid<Tile>, mask: &[Vec<bool>], seed: u64) {
+        let (w, h) = (grid.width(), grid.height());
+        let cfg = &self.config;
+        let site: Vec<bool> = (0..w * h)
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                x > 0
+                    && y > 0
+                    && x + 1 < w
+                    && y + 1 < h
+                    && mask.get(y).and_then(|row| row.get(x)).copied() == Some(true)
+                    && (!cfg.on_floor || grid[(x, y)].is_floor())
+            })
+            .collect();
+        for (i, _) in site.iter().enumerate().filter(|(_, &s)| s) {
+            grid.set((i % w) as i32, (i / w) as i32, Tile::Wall);
+        }
+
+        // Summed-area table: a room fits where its sum equals its area.
+        let mut sums = vec![0usize; (w + 1) * (h + 1)];
+        for y in 0..h {
+            for x in 0..w {
+                sums[(y + 1) * (w + 1) + x + 1] = site[y * w + x] as usize
+                    + sums[y * (w + 1) + x + 1]
+                    + sums[(y + 1) * (w + 1) + x]
+                    - sums[y * (w + 1) + x];
+            }
+        }
+        let area = |x: usize, y: usize, rw: usize, rh: usize| {
+            sums[(y + rh) * (w + 1) + x + rw] + sums[y * (w + 1) + x]
+                - sums[y * (w + 1) + x + rw]
+                - sums[(y + rh) * (w + 1) + x]
+        };
+
+        let mut rng = Rng::new(seed);
+        let min = cfg.min_room_size.max(1);
+        let max = cfg.max_room_size.max(min);
+        let mut rooms: Vec<Room> = Vec::new();
+        for _ in 0..cfg.max_rooms * cfg.attempts.max(1) {
+            if rooms.len() >= cfg.max_rooms {
+                break;
+            }
+            let rw = rng.range_usize(min, max + 1);
+            let rh = rng.range_usize(min, max + 1);
+            if rw > w || rh > h {
+                continue;
+            }
+            let fits: Vec<(usize, usize)> = (0..=h - rh)
+                .flat_map(|y| (0..=w - rw).map(move |x| (x, y)))
+                .filter(|&(x, y)| area(x, y, rw, rh) == rw * rh)
+                .filter(|&(x, y)| {
+                    let room = Room { x, y, w: rw, h: rh };
+                    !rooms.iter().any(|r| r.intersects(&room, cfg.min_spacing))
+                })
+                .collect();
+            let Some(&(x, y)) = rng.pick(&fits) else {
+                continue;
+            };
+            grid.fill_rect(x as i32, y as i32, rw, rh, Tile::Floor);
+            rooms.push(Room { x, y, w: rw, h: rh });
+        }
+
+        for i in 1..rooms.len() {
+            let (cx, cy) = rooms[i].center();
+            let mut targets: Vec<usize> = (0..i).collect();
+            targets.sort_by_key(|&j| {
+                let (tx, ty) = rooms[j].center();
+                (cx.abs_diff(tx) + cy.abs_diff(ty), j)
+            });
+            for j in targets {
+                if let Some(path) = site_path(&site, w, h, rooms[i].center(), rooms[j].center()) {
+                    for (x, y) in path {
+                        grid.set(x as i32, y as i32, Tile::Floor);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Algorithm<Tile> for RoomsOnMask {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        let mask = vec![vec![true; grid.width()]; grid.height()];
+        self.place(grid, &mask, seed);
+    }
+
+    fn generate_masked(&self, grid: &mut Grid<Tile>, mask: &[Vec<bool>], seed: u64) {
+        self.place(grid, mask, seed);
+    }
+
+    fn name(&self) -> &'static str {
+        "RoomsOnMask"
+    }
+}
+
+/// Shortest 4-connected path over site cells, endpoints included.
+fn site_path(
+    site: &[bool],
+    w: usize,
+    h: usize,
+    from: (usize, usize),
+    to: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    let start = from.1 * w + from.0;
+    let goal = to.1 * w + to.0;
+    let mut prev = vec![usize::MAX; w * h];
+    prev[start] = start;
+    let mut queue = std::collections::VecDeque::from([start]);
+    while let Some(i) = queue.pop_front() {
+        if i == goal {
+            let mut path = vec![(i % w, i / w)];
+            let mut at = i;
+            while at != start {
+                at = prev[at];
+                path.push((at % w, at / w));
+            }
+            return Some(path);
+        }
+        let (x, y) = (i % w, i / w);
+        let neighbors = [
+            (x > 0).then(|| i - 1),
+            (x + 1 < w).then(|| i + 1),
+            (y > 0).then(|| i - w),
+            (y + 1 < h).then(|| i + w),
+        ];
+        for n in neighbors.into_iter().flatten() {
+            if site[n] && prev[n] == usize::MAX {
+                prev[n] = i;
+                queue.push_back(n);
+            }
+        }
+    }
+    None
+}
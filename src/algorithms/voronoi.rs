@@ -1,8 +1,10 @@
+use crate::stream::ChunkGenerator;
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for Voronoi region generation.
+#[serde(default)]
 pub struct VoronoiConfig {
     /// Number of Voronoi seed points. Default: 15.
     pub num_points: usize,
@@ -19,6 +21,20 @@ impl Default for VoronoiConfig {
     }
 }
 
+impl VoronoiConfig {
+    /// Sets `num_points`.
+    pub fn with_num_points(mut self, num_points: usize) -> Self {
+        self.num_points = num_points;
+        self
+    }
+
+    /// Sets `floor_chance`.
+    pub fn with_floor_chance(mut self, floor_chance: f64) -> Self {
+        self.floor_chance = floor_chance;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Voronoi diagram region generator.
 pub struct Voronoi {
@@ -40,8 +56,28 @@ impl Default for Voronoi {
 
 impl Algorithm<Tile> for Voronoi {
     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        let size = (grid.width(), grid.height());
+        self.generate_chunk(grid, (0, 0), size, seed);
+    }
+
+    fn name(&self) -> &'static str {
+        "Voronoi"
+    }
+}
+
+impl ChunkGenerator for Voronoi {
+    fn generate_chunk(
+        &self,
+        grid: &mut Grid<Tile>,
+        origin: (usize, usize),
+        map_size: (usize, usize),
+        seed: u64,
+    ) {
         let mut rng = Rng::new(seed);
-        let (w, h) = (grid.width(), grid.height());
+        let (w, h) = map_size;
+        if w < 3 || h < 3 {
+            return;
+        }
 
         let points: Vec<(usize, usize)> = (0..self.config.num_points)
             .map(|_| (rng.range_usize(1, w - 1), rng.range_usize(1, h - 1)))
@@ -51,8 +87,16 @@ impl Algorithm<Tile> for Voronoi {
             .map(|_| rng.chance(self.config.floor_chance))
             .collect();
 
-        for y in 1..h - 1 {
-            for x in 1..w - 1 {
+        for cy in 0..grid.height() {
+            let y = origin.1 + cy;
+            if y == 0 || y + 1 >= h {
+                continue;
+            }
+            for cx in 0..grid.width() {
+                let x = origin.0 + cx;
+                if x == 0 || x + 1 >= w {
+                    continue;
+                }
                 let mut min_dist = usize::MAX;
                 let mut closest = 0;
                 for (i, &(px, py)) in points.iter().enumerate() {
@@ -64,13 +108,9 @@ impl Algorithm<Tile> for Voronoi {
                     }
                 }
                 if is_floor[closest] {
-                    grid.set(x as i32, y as i32, Tile::Floor);
+                    grid.set(cx as i32, cy as i32, Tile::Floor);
                 }
             }
         }
     }
-
-    fn name(&self) -> &'static str {
-        "Voronoi"
-    }
 }
@@ -1,9 +1,15 @@
+use super::{Deadline, TimedRun};
+use crate::error::{Error, Result};
 use crate::{Algorithm, Grid, Rng, Tile};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+type FrameSink<'f> = dyn FnMut(&Grid<Tile>) + 'f;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Configuration for Wave Function Collapse generation.
+#[serde(default)]
 pub struct WfcConfig {
     /// Weight for floor tiles in random collapse. Default: 0.4.
     pub floor_weight: f64,
@@ -11,6 +17,9 @@ pub struct WfcConfig {
     pub pattern_size: usize,
     /// Enable backtracking on contradiction. Default: true.
     pub enable_backtracking: bool,
+    /// Soft time limit in milliseconds; when it passes, generation stops
+    /// and keeps the partial result. Default: None.
+    pub max_duration_ms: Option<u64>,
 }
 
 impl Default for WfcConfig {
@@ -19,23 +28,97 @@ impl Default for WfcConfig {
             floor_weight: 0.4,
             pattern_size: 3,
             enable_backtracking: true,
+            max_duration_ms: None,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+impl WfcConfig {
+    /// Sets `floor_weight`.
+    pub fn with_floor_weight(mut self, floor_weight: f64) -> Self {
+        self.floor_weight = floor_weight;
+        self
+    }
+
+    /// Sets `pattern_size`.
+    pub fn with_pattern_size(mut self, pattern_size: usize) -> Self {
+        self.pattern_size = pattern_size;
+        self
+    }
+
+    /// Sets `enable_backtracking`.
+    pub fn with_enable_backtracking(mut self, enable_backtracking: bool) -> Self {
+        self.enable_backtracking = enable_backtracking;
+        self
+    }
+
+    /// Sets `max_duration_ms`.
+    pub fn with_max_duration(mut self, limit: Duration) -> Self {
+        self.max_duration_ms = Some(limit.as_millis() as u64);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 /// A tile pattern extracted from an example grid.
+///
+/// Equality and hashing look at the tiles only, not the frequency.
 pub struct Pattern {
     tiles: Vec<Vec<Tile>>,
+    /// Occurrences in the samples (augmented copies included); WFC picks
+    /// patterns in proportion to it.
+    #[serde(default = "default_frequency")]
+    frequency: u32,
+}
+
+fn default_frequency() -> u32 {
+    1
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.tiles == other.tiles
+    }
+}
+
+impl std::hash::Hash for Pattern {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.tiles.hash(state);
+    }
 }
 
 impl Pattern {
     fn new(size: usize) -> Self {
+        Self::from_tiles(vec![vec![Tile::Wall; size]; size])
+    }
+
+    fn from_tiles(tiles: Vec<Vec<Tile>>) -> Self {
         Self {
-            tiles: vec![vec![Tile::Wall; size]; size],
+            tiles,
+            frequency: 1,
         }
     }
 
+    /// Rows of tiles, `size` by `size`.
+    pub fn tiles(&self) -> &[Vec<Tile>] {
+        &self.tiles
+    }
+
+    pub fn size(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// How often the pattern occurred in the samples.
+    pub fn frequency(&self) -> u32 {
+        self.frequency
+    }
+
+    /// Sets `frequency`.
+    pub fn with_frequency(mut self, frequency: u32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
     fn from_grid(grid: &Grid<Tile>, x: usize, y: usize, size: usize) -> Option<Self> {
         let mut tiles = vec![vec![Tile::Wall; size]; size];
         for (dy, row) in tiles.iter_mut().enumerate() {
@@ -47,7 +130,33 @@ impl Pattern {
                 }
             }
         }
-        Some(Self { tiles })
+        Some(Self::from_tiles(tiles))
+    }
+
+    /// Like `from_grid`, wrapping around the grid edges.
+    fn from_grid_wrapping(grid: &Grid<Tile>, x: usize, y: usize, size: usize) -> Self {
+        let (w, h) = (grid.width(), grid.height());
+        let tiles = (0..size)
+            .map(|dy| {
+                (0..size)
+                    .map(|dx| grid[((x + dx) % w, (y + dy) % h)])
+                    .collect()
+            })
+            .collect();
+        Self::from_tiles(tiles)
+    }
+
+    fn reflected(&self) -> Self {
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+        Self::from_tiles(tiles)
+    }
+
+    fn flipped(&self) -> Self {
+        Self::from_tiles(self.tiles.iter().rev().cloned().collect())
     }
 
     fn rotated(&self) -> Self {
@@ -58,7 +167,7 @@ impl Pattern {
                 tiles[x][size - 1 - y] = tile;
             }
         }
-        Self { tiles }
+        Self::from_tiles(tiles)
     }
 }
 
@@ -227,30 +336,133 @@ impl WfcState {
     }
 }
 
+/// Options for [`WfcPatternExtractor::extract_from_samples`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatternExtractConfig {
+    /// Pattern side (NxN). Default: 3.
+    pub pattern_size: usize,
+    /// Also count each window rotated by 90, 180, and 270 degrees.
+    /// Default: true.
+    pub rotations: bool,
+    /// Also count each window mirrored horizontally and vertically (with
+    /// `rotations`, all eight symmetries). Default: false.
+    pub reflections: bool,
+    /// Treat samples as tiling: windows wrap around the edges, so every
+    /// cell starts one. Default: false.
+    pub periodic: bool,
+}
+
+impl Default for PatternExtractConfig {
+    fn default() -> Self {
+        Self {
+            pattern_size: 3,
+            rotations: true,
+            reflections: false,
+            periodic: false,
+        }
+    }
+}
+
+impl PatternExtractConfig {
+    /// Sets `pattern_size`.
+    pub fn with_pattern_size(mut self, pattern_size: usize) -> Self {
+        self.pattern_size = pattern_size;
+        self
+    }
+
+    /// Sets `rotations`.
+    pub fn with_rotations(mut self, rotations: bool) -> Self {
+        self.rotations = rotations;
+        self
+    }
+
+    /// Sets `reflections`.
+    pub fn with_reflections(mut self, reflections: bool) -> Self {
+        self.reflections = reflections;
+        self
+    }
+
+    /// Sets `periodic`.
+    pub fn with_periodic(mut self, periodic: bool) -> Self {
+        self.periodic = periodic;
+        self
+    }
+}
+
 /// Extracts tile patterns from example grids for WFC.
 pub struct WfcPatternExtractor;
 
 impl WfcPatternExtractor {
-    /// Extracts all unique NxN patterns (with rotations) from the grid.
+    /// Extracts all unique NxN patterns (with rotations) from the grid,
+    /// counting how often each occurs.
     pub fn extract_patterns(grid: &Grid<Tile>, pattern_size: usize) -> Vec<Pattern> {
-        let mut patterns = Vec::new();
-        let mut pattern_set = std::collections::HashSet::new();
-
-        for y in 0..=grid.height().saturating_sub(pattern_size) {
-            for x in 0..=grid.width().saturating_sub(pattern_size) {
-                if let Some(pattern) = Pattern::from_grid(grid, x, y, pattern_size) {
-                    if pattern_set.insert(pattern.clone()) {
-                        patterns.push(pattern.clone());
-                        // Add rotations
-                        let mut rotated = pattern;
+        let config = PatternExtractConfig::default().with_pattern_size(pattern_size);
+        Self::extract(std::iter::once(grid), &config)
+    }
+
+    /// Extracts unique patterns from several samples, in first-seen order.
+    ///
+    /// Each window, and each augmented copy of it, adds one to its
+    /// pattern's [`frequency`](Pattern::frequency), so patterns common
+    /// across the samples are chosen more often. Samples smaller than the
+    /// pattern contribute nothing unless `periodic` is set.
+    pub fn extract_from_samples(
+        samples: &[Grid<Tile>],
+        config: &PatternExtractConfig,
+    ) -> Vec<Pattern> {
+        Self::extract(samples, config)
+    }
+
+    fn extract<'a>(
+        samples: impl IntoIterator<Item = &'a Grid<Tile>>,
+        config: &PatternExtractConfig,
+    ) -> Vec<Pattern> {
+        let pattern_size = config.pattern_size.max(1);
+        let mut patterns: Vec<Pattern> = Vec::new();
+        let mut index: HashMap<Pattern, usize> = HashMap::new();
+        let mut count = |pattern: Pattern| match index.get(&pattern) {
+            Some(&i) => patterns[i].frequency += 1,
+            None => {
+                index.insert(pattern.clone(), patterns.len());
+                patterns.push(pattern);
+            }
+        };
+
+        for grid in samples {
+            let (w, h) = (grid.width(), grid.height());
+            let windows: Vec<Pattern> = if config.periodic {
+                if w == 0 || h == 0 {
+                    continue;
+                }
+                (0..h)
+                    .flat_map(|y| (0..w).map(move |x| (x, y)))
+                    .map(|(x, y)| Pattern::from_grid_wrapping(grid, x, y, pattern_size))
+                    .collect()
+            } else {
+                (0..=h.saturating_sub(pattern_size))
+                    .flat_map(|y| (0..=w.saturating_sub(pattern_size)).map(move |x| (x, y)))
+                    .filter_map(|(x, y)| Pattern::from_grid(grid, x, y, pattern_size))
+                    .collect()
+            };
+            for window in windows {
+                let mut variants = vec![window];
+                if config.reflections {
+                    variants.push(variants[0].reflected());
+                    variants.push(variants[0].flipped());
+                }
+                if config.rotations {
+                    for i in 0..variants.len() {
+                        let mut rotated = variants[i].clone();
                         for _ in 0..3 {
                             rotated = rotated.rotated();
-                            if pattern_set.insert(rotated.clone()) {
-                                patterns.push(rotated.clone());
-                            }
+                            variants.push(rotated.clone());
                         }
                     }
                 }
+                for variant in variants {
+                    count(variant);
+                }
             }
         }
 
@@ -271,7 +483,7 @@ impl WfcPatternExtractor {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 /// Backtracking state manager for WFC.
 pub struct WfcBacktracker {
     states: Vec<WfcState>,
@@ -294,6 +506,45 @@ impl WfcBacktracker {
     }
 }
 
+/// A WFC solve paused between collapses, from [`Wfc::start`].
+///
+/// Holds the wave, the backtracking history, and the RNG position, so it
+/// can be serialized (e.g. to survive a preempted batch job) and handed
+/// back to [`Wfc::resume`] to continue exactly where it stopped: resuming
+/// in any number of slices gives the same map as one uninterrupted run.
+/// The backtracking history keeps one wave per collapse, so checkpoints of
+/// large solves get large; turn off `enable_backtracking` to keep them
+/// small.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WfcCheckpoint {
+    state: WfcState,
+    backtracker: WfcBacktracker,
+    rng: Rng,
+    collapses: usize,
+    finished: bool,
+}
+
+impl WfcCheckpoint {
+    /// Collapses made so far, including ones later undone by backtracking.
+    pub fn collapses(&self) -> usize {
+        self.collapses
+    }
+
+    /// Whether the solve is over, either fully collapsed or failed.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Parses a checkpoint saved with [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::parse("wfc checkpoint", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Wave Function Collapse terrain generator.
 pub struct Wfc {
@@ -308,46 +559,158 @@ impl Wfc {
 
     /// Generates terrain using pre-extracted patterns.
     pub fn generate_with_patterns(&self, grid: &mut Grid<Tile>, patterns: Vec<Pattern>, seed: u64) {
-        let mut rng = Rng::new(seed);
-        let mut state = WfcState::new(grid.width(), grid.height(), patterns);
-        let mut backtracker = WfcBacktracker::new();
+        self.solve(grid, patterns, seed, None);
+    }
+
+    /// Like [`generate_with_patterns`](Self::generate_with_patterns),
+    /// reporting whether `max_duration_ms` stopped the solver early. Cells
+    /// collapsed by then are written; the rest keep their tiles.
+    pub fn generate_with_patterns_timed(
+        &self,
+        grid: &mut Grid<Tile>,
+        patterns: Vec<Pattern>,
+        seed: u64,
+    ) -> TimedRun {
+        self.solve(grid, patterns, seed, None)
+    }
+
+    /// Like [`generate`](Algorithm::generate), reporting whether
+    /// `max_duration_ms` stopped the solver early.
+    pub fn generate_timed(&self, grid: &mut Grid<Tile>, seed: u64) -> TimedRun {
+        self.solve(grid, default_patterns(), seed, None)
+    }
 
-        // Set border constraints
+    /// Like [`generate_with_patterns`](Self::generate_with_patterns), calling
+    /// `frame` after every collapse with the cells collapsed so far.
+    pub fn generate_with_patterns_frames(
+        &self,
+        grid: &mut Grid<Tile>,
+        patterns: Vec<Pattern>,
+        seed: u64,
+        frame: &mut dyn FnMut(&Grid<Tile>),
+    ) {
+        self.solve(grid, patterns, seed, Some(frame));
+    }
+
+    /// Starts a `width` x `height` solve over `patterns` without running
+    /// it; step it with [`resume`](Self::resume).
+    pub fn start(
+        &self,
+        width: usize,
+        height: usize,
+        patterns: Vec<Pattern>,
+        seed: u64,
+    ) -> WfcCheckpoint {
+        let mut state = WfcState::new(width, height, patterns);
         self.set_border_constraints(&mut state);
+        WfcCheckpoint {
+            state,
+            backtracker: WfcBacktracker::new(),
+            rng: Rng::new(seed),
+            collapses: 0,
+            finished: false,
+        }
+    }
+
+    /// Continues `checkpoint` for at most `max_collapses` collapses, or
+    /// until `max_duration_ms` passes, then writes the cells collapsed so
+    /// far to `grid`. Check [`WfcCheckpoint::is_finished`] to see whether
+    /// to call it again.
+    pub fn resume(
+        &self,
+        grid: &mut Grid<Tile>,
+        checkpoint: &mut WfcCheckpoint,
+        max_collapses: usize,
+    ) -> TimedRun {
+        let mut deadline = Deadline::new(self.config.max_duration_ms);
+        self.advance(checkpoint, max_collapses, &mut deadline, grid, None);
+        self.apply_to_grid(&checkpoint.state, grid);
+        deadline.finish()
+    }
+
+    fn solve(
+        &self,
+        grid: &mut Grid<Tile>,
+        patterns: Vec<Pattern>,
+        seed: u64,
+        mut frame: Option<&mut FrameSink<'_>>,
+    ) -> TimedRun {
+        let mut deadline = Deadline::new(self.config.max_duration_ms);
+        let mut run = self.start(grid.width(), grid.height(), patterns, seed);
+        self.advance(
+            &mut run,
+            usize::MAX,
+            &mut deadline,
+            grid,
+            frame.as_deref_mut(),
+        );
+        self.apply_to_grid(&run.state, grid);
+        if let Some(frame) = frame {
+            frame(grid);
+        }
+        deadline.finish()
+    }
 
-        loop {
+    /// Runs the solve loop for up to `budget` collapses.
+    fn advance(
+        &self,
+        run: &mut WfcCheckpoint,
+        budget: usize,
+        deadline: &mut Deadline,
+        grid: &Grid<Tile>,
+        mut frame: Option<&mut FrameSink<'_>>,
+    ) {
+        let WfcCheckpoint {
+            state,
+            backtracker,
+            rng,
+            collapses,
+            finished,
+        } = run;
+        let mut made = 0;
+        while !*finished && made < budget {
+            if deadline.expired() {
+                break;
+            }
             if !state.propagate() {
                 if self.config.enable_backtracking {
                     if let Some(prev_state) = backtracker.backtrack() {
-                        state = prev_state;
+                        *state = prev_state;
                         continue;
                     }
                 }
-                break; // Failed to solve
+                *finished = true; // Failed to solve
+                break;
             }
 
             // Find cell with minimum entropy > 1
-            if let Some((x, y)) = self.find_min_entropy_cell(&state) {
+            if let Some((x, y)) = self.find_min_entropy_cell(state) {
                 if self.config.enable_backtracking {
-                    backtracker.save_state(&state);
+                    backtracker.save_state(state);
                 }
 
-                let pattern_id = self.choose_pattern(&state, x, y, &mut rng);
+                let pattern_id = self.choose_pattern(state, x, y, rng);
+                *collapses += 1;
+                made += 1;
                 if !state.collapse(x, y, pattern_id) {
                     if self.config.enable_backtracking {
                         if let Some(prev_state) = backtracker.backtrack() {
-                            state = prev_state;
+                            *state = prev_state;
                             continue;
                         }
                     }
+                    *finished = true;
                     break;
                 }
+                if let Some(frame) = frame.as_deref_mut() {
+                    let mut partial = grid.clone();
+                    self.apply_to_grid(state, &mut partial);
+                    frame(&partial);
+                }
             } else {
-                break; // All cells collapsed
+                *finished = true; // All cells collapsed
             }
         }
-
-        self.apply_to_grid(&state, grid);
     }
 
     fn set_border_constraints(&self, state: &mut WfcState) {
@@ -400,7 +763,14 @@ impl Wfc {
 
     fn choose_pattern(&self, state: &WfcState, x: usize, y: usize, rng: &mut Rng) -> usize {
         let patterns = &state.possibilities[y][x];
-        *rng.pick(patterns).unwrap_or(&0)
+        let weights: Vec<f64> = patterns
+            .iter()
+            .map(|&i| state.patterns[i].frequency as f64)
+            .collect();
+        if weights.windows(2).all(|w| w[0] == w[1]) {
+            return *rng.pick(patterns).unwrap_or(&0);
+        }
+        rng.weighted_index(&weights).map_or(0, |i| patterns[i])
     }
 
     fn apply_to_grid(&self, state: &WfcState, grid: &mut Grid<Tile>) {
@@ -434,34 +804,37 @@ impl Default for Wfc {
 
 impl Algorithm<Tile> for Wfc {
     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
-        // Create basic patterns for default generation
-        let patterns = vec![
-            Pattern {
-                tiles: vec![vec![Tile::Wall; 3]; 3],
-            },
-            Pattern {
-                tiles: vec![vec![Tile::Floor; 3]; 3],
-            },
-            Pattern {
-                tiles: vec![
-                    vec![Tile::Wall, Tile::Wall, Tile::Wall],
-                    vec![Tile::Wall, Tile::Floor, Tile::Wall],
-                    vec![Tile::Wall, Tile::Wall, Tile::Wall],
-                ],
-            },
-            Pattern {
-                tiles: vec![
-                    vec![Tile::Floor, Tile::Floor, Tile::Floor],
-                    vec![Tile::Floor, Tile::Floor, Tile::Floor],
-                    vec![Tile::Wall, Tile::Wall, Tile::Wall],
-                ],
-            },
-        ];
-
-        self.generate_with_patterns(grid, patterns, seed);
+        self.generate_with_patterns(grid, default_patterns(), seed);
+    }
+
+    fn generate_frames(
+        &self,
+        grid: &mut Grid<Tile>,
+        seed: u64,
+        frame: &mut dyn FnMut(&Grid<Tile>),
+    ) {
+        self.generate_with_patterns_frames(grid, default_patterns(), seed, frame);
     }
 
     fn name(&self) -> &'static str {
         "WFC"
     }
 }
+
+/// Basic patterns for default generation.
+fn default_patterns() -> Vec<Pattern> {
+    vec![
+        Pattern::from_tiles(vec![vec![Tile::Wall; 3]; 3]),
+        Pattern::from_tiles(vec![vec![Tile::Floor; 3]; 3]),
+        Pattern::from_tiles(vec![
+            vec![Tile::Wall, Tile::Wall, Tile::Wall],
+            vec![Tile::Wall, Tile::Floor, Tile::Wall],
+            vec![Tile::Wall, Tile::Wall, Tile::Wall],
+        ]),
+        Pattern::from_tiles(vec![
+            vec![Tile::Floor, Tile::Floor, Tile::Floor],
+            vec![Tile::Floor, Tile::Floor, Tile::Floor],
+            vec![Tile::Wall, Tile::Wall, Tile::Wall],
+        ]),
+    ]
+}
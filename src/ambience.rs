@@ -0,0 +1,235 @@
+//! Ambience zones for driving engine audio and visual effects.
+//!
+//! [`zones`] groups semantic regions into [`AmbienceZone`]s: regions join
+//! a zone when they share a kind and a theme tag (see
+//! [`theme`](crate::theme)) and, by default, touch through the
+//! connectivity graph. Each zone carries its boundary polygons, traced
+//! with [`outlines`], and the zones it borders, so engines can switch or
+//! crossfade ambient loops, fog, and lighting as the player moves between
+//! them. [`to_json`] exports the zone list.
+//!
+//! ```rust
+//! use terrain_forge::ambience::{self, AmbienceConfig};
+//! use terrain_forge::semantic::{ConnectivityGraph, Masks, Region, SemanticLayers};
+//!
+//! let region = |id: u32, kind: &str, x0: u32| {
+//!     let mut region = Region::new(id, kind);
+//!     for y in 1..4 {
+//!         for x in x0..x0 + 3 {
+//!             region.add_cell(x, y);
+//!         }
+//!     }
+//!     region
+//! };
+//! let mut connectivity = ConnectivityGraph::new();
+//! connectivity.add_edge(1, 2);
+//! connectivity.add_edge(2, 3);
+//! let semantic = SemanticLayers {
+//!     regions: vec![region(1, "cave", 1), region(2, "cave", 4), region(3, "lake", 7)],
+//!     markers: Vec::new(),
+//!     masks: Masks::new(12, 5),
+//!     connectivity,
+//! };
+//!
+//! let zones = ambience::zones(&semantic, &AmbienceConfig::default());
+//! assert_eq!(zones.len(), 2);
+//! assert_eq!(zones[0].regions, vec![1, 2]);
+//! assert_eq!(zones[0].neighbors, vec![1]);
+//! assert_eq!(zones[1].kind, "lake");
+//! assert_eq!(zones[0].polygons.len(), 1);
+//! ```
+
+use crate::export::svg::outlines;
+use crate::semantic::{Region, SemanticLayers};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Configuration for [`zones`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmbienceConfig {
+    /// Keep regions of different kinds apart. Default: true.
+    pub by_kind: bool,
+    /// Keep regions with different theme tags apart. Default: true.
+    pub by_theme: bool,
+    /// Prefix marking a region's theme tag. Default: `"theme:"`.
+    pub theme_prefix: String,
+    /// Only merge matching regions that are linked in the connectivity
+    /// graph; otherwise every match shares one zone, however far apart.
+    /// Default: true.
+    pub connected: bool,
+}
+
+impl Default for AmbienceConfig {
+    fn default() -> Self {
+        Self {
+            by_kind: true,
+            by_theme: true,
+            theme_prefix: "theme:".to_string(),
+            connected: true,
+        }
+    }
+}
+
+impl AmbienceConfig {
+    /// Sets `by_kind` and `by_theme`.
+    pub fn with_grouping(mut self, by_kind: bool, by_theme: bool) -> Self {
+        self.by_kind = by_kind;
+        self.by_theme = by_theme;
+        self
+    }
+
+    /// Sets `theme_prefix`.
+    pub fn with_theme_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.theme_prefix = prefix.into();
+        self
+    }
+
+    /// Sets `connected`.
+    pub fn with_connected(mut self, connected: bool) -> Self {
+        self.connected = connected;
+        self
+    }
+}
+
+/// A group of regions sharing one ambience.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AmbienceZone {
+    /// Index in the [`zones`] output.
+    pub id: u32,
+    /// Kind of the zone's first region; empty if `by_kind` is off.
+    pub kind: String,
+    /// Theme tag without its prefix, if the regions have one and
+    /// `by_theme` is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Member region ids, ascending.
+    pub regions: Vec<u32>,
+    /// Cells covered.
+    pub area: usize,
+    /// Closed boundary rings in cell units, outer edges and holes alike;
+    /// each ring's last point connects back to its first.
+    pub polygons: Vec<Vec<(f32, f32)>>,
+    /// Ids of zones linked to this one in the connectivity graph.
+    pub neighbors: Vec<u32>,
+}
+
+/// Groups `semantic.regions` into zones, ordered by their lowest region
+/// id. Adjacency comes from both walk and swim edges.
+pub fn zones(semantic: &SemanticLayers, config: &AmbienceConfig) -> Vec<AmbienceZone> {
+    let mut regions: Vec<&Region> = semantic.regions.iter().collect();
+    regions.sort_by_key(|r| r.id);
+    let key = |region: &Region| {
+        let kind = if config.by_kind {
+            region.kind.clone()
+        } else {
+            String::new()
+        };
+        let theme = config
+            .by_theme
+            .then(|| {
+                region
+                    .tags
+                    .iter()
+                    .find_map(|t| t.strip_prefix(config.theme_prefix.as_str()))
+                    .map(str::to_string)
+            })
+            .flatten();
+        (kind, theme)
+    };
+
+    let mut links: HashMap<u32, Vec<u32>> = HashMap::new();
+    let connectivity = &semantic.connectivity;
+    for &(a, b) in connectivity.edges.iter().chain(&connectivity.swim_edges) {
+        links.entry(a).or_default().push(b);
+        links.entry(b).or_default().push(a);
+    }
+    let index: HashMap<u32, usize> = regions.iter().enumerate().map(|(i, r)| (r.id, i)).collect();
+    let keys: Vec<_> = regions.iter().map(|r| key(r)).collect();
+
+    // Zone of every region, by index into `regions`.
+    let mut zone_of: Vec<Option<u32>> = vec![None; regions.len()];
+    let mut by_key: BTreeMap<&(String, Option<String>), u32> = BTreeMap::new();
+    let mut zones: Vec<AmbienceZone> = Vec::new();
+    for start in 0..regions.len() {
+        if zone_of[start].is_some() {
+            continue;
+        }
+        if !config.connected {
+            if let Some(&id) = by_key.get(&keys[start]) {
+                zone_of[start] = Some(id);
+                continue;
+            }
+        }
+        let id = zones.len() as u32;
+        by_key.insert(&keys[start], id);
+        let (kind, theme) = keys[start].clone();
+        zones.push(AmbienceZone {
+            id,
+            kind,
+            theme,
+            regions: Vec::new(),
+            area: 0,
+            polygons: Vec::new(),
+            neighbors: Vec::new(),
+        });
+        zone_of[start] = Some(id);
+        if !config.connected {
+            continue;
+        }
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            for other in links.get(&regions[i].id).into_iter().flatten() {
+                if let Some(&j) = index.get(other) {
+                    if zone_of[j].is_none() && keys[j] == keys[start] {
+                        zone_of[j] = Some(id);
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+    }
+
+    let (width, height) = (semantic.masks.width, semantic.masks.height);
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); zones.len()];
+    for (i, zone) in zone_of.iter().enumerate() {
+        if let Some(zone) = zone {
+            members[*zone as usize].push(i);
+        }
+    }
+    for (zone, members) in zones.iter_mut().zip(&members) {
+        let mut grid = Grid::new(width, height);
+        for &i in members {
+            zone.regions.push(regions[i].id);
+            zone.area += regions[i].cells.len();
+            for &(x, y) in &regions[i].cells {
+                grid.set(x as i32, y as i32, Tile::Floor);
+            }
+        }
+        zone.polygons = outlines(&grid);
+    }
+
+    let mut neighbors: Vec<BTreeSet<u32>> = vec![BTreeSet::new(); zones.len()];
+    for (&a, others) in &links {
+        for b in others {
+            let (Some(&i), Some(&j)) = (index.get(&a), index.get(b)) else {
+                continue;
+            };
+            if let (Some(za), Some(zb)) = (zone_of[i], zone_of[j]) {
+                if za != zb {
+                    neighbors[za as usize].insert(zb);
+                }
+            }
+        }
+    }
+    for (zone, neighbors) in zones.iter_mut().zip(neighbors) {
+        zone.neighbors = neighbors.into_iter().collect();
+    }
+    zones
+}
+
+/// Serializes zones as a JSON array.
+pub fn to_json(zones: &[AmbienceZone]) -> String {
+    serde_json::to_string(zones).unwrap_or_default()
+}
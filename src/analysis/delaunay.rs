@@ -1,7 +1,7 @@
 //! Delaunay triangulation for natural room connections
 
 use crate::{Cell, Grid};
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Point {
@@ -15,7 +15,8 @@ impl Point {
     }
 
     pub fn distance_to(&self, other: &Point) -> f32 {
-        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+        let (dx, dy) = (self.x - other.x, self.y - other.y);
+        (dx * dx + dy * dy).sqrt()
     }
 }
 
@@ -70,7 +71,7 @@ impl Triangle {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Edge {
     pub a: usize,
     pub b: usize,
@@ -205,7 +206,7 @@ impl DelaunayTriangulation {
         self.points.truncate(super_a);
 
         // Extract edges
-        let mut edge_set = HashSet::new();
+        let mut edge_set = BTreeSet::new();
         for triangle in &self.triangles {
             edge_set.insert(Edge::new(triangle.a, triangle.b));
             edge_set.insert(Edge::new(triangle.b, triangle.c));
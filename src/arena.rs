@@ -0,0 +1,296 @@
+//! Rotationally symmetric multiplayer arenas.
+//!
+//! [`ArenaGenerator`] wraps any algorithm: it generates a base map, copies
+//! one sector onto the others by 180° (`folds: 2`) or 90° (`folds: 4`,
+//! square maps only) rotation, joins disconnected areas with corridors
+//! carved symmetrically, and places one spawn per player plus resources,
+//! each set the rotated image of the first. Every attempt is checked with
+//! [`SpawnBalanceConstraint`]; the first one that passes is returned.
+//!
+//! Spawn markers are [`MarkerType::Spawn`] and resources are tagged
+//! [`RESOURCE_TAG`]; both carry the owning player's index under
+//! [`PLAYER_KEY`].
+//!
+//! ```rust
+//! use terrain_forge::arena::{ArenaConfig, ArenaGenerator};
+//! use terrain_forge::algorithms::CellularAutomata;
+//! use terrain_forge::constraints::spawn_imbalance;
+//!
+//! let arena = ArenaGenerator::new(CellularAutomata::default(), ArenaConfig::default().with_folds(4))
+//!     .generate(64, 64, 21)
+//!     .unwrap();
+//! assert_eq!(arena.spawns.len(), 4);
+//! assert_eq!(spawn_imbalance(&arena.grid, &arena.semantic), Some(0));
+//! ```
+//!
+//! [`MarkerType::Spawn`]: crate::semantic::MarkerType::Spawn
+
+use crate::constraints::{
+    ConstraintContext, ConstraintSet, SpawnBalanceConstraint, PLAYER_KEY, RESOURCE_TAG,
+};
+use crate::error::{Error, Result};
+use crate::semantic::{ConnectivityGraph, Marker, MarkerType, Masks, Region, SemanticLayers};
+use crate::{Algorithm, Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Configuration for [`ArenaGenerator`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArenaConfig {
+    /// Rotational symmetry and player count: 2 or 4. Default: 2.
+    pub folds: usize,
+    /// Resource markers per player. Default: 2.
+    pub resources_per_player: usize,
+    /// Largest allowed [`spawn_imbalance`](crate::constraints::spawn_imbalance).
+    /// Default: 0.
+    pub max_imbalance: usize,
+    /// Spawns are picked among this share of floor cells farthest from the
+    /// center. Default: 0.1.
+    pub spawn_edge_share: f64,
+    /// Default: 8.
+    pub max_attempts: usize,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        Self {
+            folds: 2,
+            resources_per_player: 2,
+            max_imbalance: 0,
+            spawn_edge_share: 0.1,
+            max_attempts: 8,
+        }
+    }
+}
+
+impl ArenaConfig {
+    /// Sets `folds`.
+    pub fn with_folds(mut self, folds: usize) -> Self {
+        self.folds = folds;
+        self
+    }
+
+    /// Sets `resources_per_player`.
+    pub fn with_resources_per_player(mut self, count: usize) -> Self {
+        self.resources_per_player = count;
+        self
+    }
+
+    /// Sets `max_imbalance`.
+    pub fn with_max_imbalance(mut self, max: usize) -> Self {
+        self.max_imbalance = max;
+        self
+    }
+}
+
+/// A generated arena.
+#[derive(Debug, Clone)]
+pub struct Arena {
+    pub grid: Grid<Tile>,
+    /// Spawn cells, indexed by player.
+    pub spawns: Vec<(u32, u32)>,
+    /// One `"Arena"` region with every floor cell, plus spawn and resource
+    /// markers.
+    pub semantic: SemanticLayers,
+}
+
+/// Generates symmetric arenas from a base algorithm.
+pub struct ArenaGenerator {
+    algorithm: Box<dyn Algorithm + Send + Sync>,
+    config: ArenaConfig,
+}
+
+impl ArenaGenerator {
+    pub fn new<A: Algorithm + 'static>(algorithm: A, config: ArenaConfig) -> Self {
+        Self {
+            algorithm: Box::new(algorithm),
+            config,
+        }
+    }
+
+    /// Generates a `width` x `height` arena.
+    ///
+    /// Fails on an unsupported `folds` (or `folds: 4` on a non-square
+    /// size), or with [`Error::ConstraintUnsatisfied`] if no attempt
+    /// balanced.
+    pub fn generate(&self, width: usize, height: usize, seed: u64) -> Result<Arena> {
+        let folds = self.config.folds;
+        if folds != 2 && !(folds == 4 && width == height) {
+            return Err(Error::InvalidParam {
+                op: "arena".to_string(),
+                name: "folds".to_string(),
+                message: format!(
+                    "{}-fold symmetry is not supported on a {}x{} map (use 2, or 4 on square maps)",
+                    folds, width, height
+                ),
+            });
+        }
+
+        let mut rng = Rng::new(seed);
+        let mut constraints = ConstraintSet::new();
+        constraints.push(SpawnBalanceConstraint {
+            max_imbalance: self.config.max_imbalance,
+        });
+        let attempts = self.config.max_attempts.max(1);
+        let mut last_report = None;
+        for _ in 0..attempts {
+            let mut grid = Grid::new(width, height);
+            self.algorithm.generate(&mut grid, rng.next_u64());
+            symmetrize(&mut grid, folds);
+            connect_symmetric(&mut grid, folds);
+            let Some(arena) = self.populate(grid, &mut rng) else {
+                continue;
+            };
+            let mut ctx = ConstraintContext::new(&arena.grid);
+            ctx.semantic = Some(&arena.semantic);
+            let report = constraints.evaluate(&ctx);
+            if report.passed {
+                return Ok(arena);
+            }
+            last_report = Some(report);
+        }
+        match last_report {
+            Some(report) => Err(Error::ConstraintUnsatisfied { attempts, report }),
+            None => Err(Error::new(format!(
+                "arena: no room for {} spawns after {} attempts",
+                folds, attempts
+            ))),
+        }
+    }
+
+    /// Places spawns and resources; `None` if the map has no usable floor.
+    fn populate(&self, grid: Grid<Tile>, rng: &mut Rng) -> Option<Arena> {
+        let (w, h) = (grid.width(), grid.height());
+        let folds = self.config.folds;
+        // Cells whose rotations are all distinct, so each player gets one.
+        let candidates: Vec<(usize, usize)> = grid
+            .iter()
+            .filter(|(_, _, t)| t.is_floor())
+            .map(|(x, y, _)| (x, y))
+            .filter(|&(x, y)| {
+                let orbit = orbit(x, y, w, h, folds);
+                orbit.iter().collect::<HashSet<_>>().len() == folds
+            })
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let (cx, cy) = ((w as f64 - 1.0) / 2.0, (h as f64 - 1.0) / 2.0);
+        let center_distance = |&(x, y): &(usize, usize)| (x as f64 - cx).hypot(y as f64 - cy);
+        let mut by_distance = candidates.clone();
+        by_distance.sort_by(|a, b| center_distance(b).total_cmp(&center_distance(a)));
+        let edge = ((by_distance.len() as f64 * self.config.spawn_edge_share) as usize)
+            .clamp(1, by_distance.len());
+        let &(sx, sy) = rng.pick(&by_distance[..edge])?;
+        let spawns = orbit(sx, sy, w, h, folds);
+
+        let mut used: HashSet<(usize, usize)> = spawns.iter().copied().collect();
+        let mut markers: Vec<Marker> = spawns
+            .iter()
+            .enumerate()
+            .map(|(player, &(x, y))| {
+                Marker::new(x as u32, y as u32, MarkerType::Spawn)
+                    .with_metadata(PLAYER_KEY, player.to_string())
+            })
+            .collect();
+        for _ in 0..self.config.resources_per_player {
+            let free: Vec<(usize, usize)> = candidates
+                .iter()
+                .copied()
+                .filter(|&(x, y)| !orbit(x, y, w, h, folds).iter().any(|c| used.contains(c)))
+                .collect();
+            let Some(&(rx, ry)) = rng.pick(&free) else {
+                break;
+            };
+            for (player, (x, y)) in orbit(rx, ry, w, h, folds).into_iter().enumerate() {
+                used.insert((x, y));
+                markers.push(
+                    Marker::with_tag(x as u32, y as u32, RESOURCE_TAG.to_string())
+                        .with_metadata(PLAYER_KEY, player.to_string()),
+                );
+            }
+        }
+
+        let mut region = Region::new(1, "Arena");
+        for (x, y, tile) in grid.iter() {
+            if tile.is_floor() {
+                region.add_cell(x as u32, y as u32);
+            }
+        }
+        let semantic = SemanticLayers {
+            regions: vec![region],
+            markers,
+            masks: Masks::from_tiles(&grid),
+            connectivity: ConnectivityGraph::new(),
+        };
+        Some(Arena {
+            grid,
+            spawns: spawns
+                .into_iter()
+                .map(|(x, y)| (x as u32, y as u32))
+                .collect(),
+            semantic,
+        })
+    }
+}
+
+/// `(x, y)` and its rotations, in player order: 180° for 2 folds, 90°
+/// steps (square maps) for 4.
+fn orbit(x: usize, y: usize, w: usize, h: usize, folds: usize) -> Vec<(usize, usize)> {
+    let mut cells = vec![(x, y)];
+    for _ in 1..folds {
+        let &(px, py) = cells.last().unwrap();
+        cells.push(if folds == 4 {
+            (w - 1 - py, px)
+        } else {
+            (w - 1 - px, h - 1 - py)
+        });
+    }
+    cells
+}
+
+/// Makes `grid` symmetric: every cell takes the tile of the first cell of
+/// its orbit in row-major order.
+fn symmetrize(grid: &mut Grid<Tile>, folds: usize) {
+    let (w, h) = (grid.width(), grid.height());
+    let base = grid.clone();
+    for y in 0..h {
+        for x in 0..w {
+            let (sx, sy) = orbit(x, y, w, h, folds)
+                .into_iter()
+                .min_by_key(|&(ox, oy)| (oy, ox))
+                .unwrap();
+            grid.set(x as i32, y as i32, base[(sx, sy)]);
+        }
+    }
+}
+
+/// Joins every floor region to the largest with L-shaped corridors,
+/// carving each corridor's rotations too so symmetry is kept.
+fn connect_symmetric(grid: &mut Grid<Tile>, folds: usize) {
+    let (w, h) = (grid.width(), grid.height());
+    for _ in 0..8 {
+        let mut regions = grid.flood_regions();
+        if regions.len() <= 1 {
+            return;
+        }
+        regions.sort_by_key(|r| std::cmp::Reverse(r.len()));
+        let main = &regions[0];
+        for region in &regions[1..] {
+            let (ax, ay) = region[0];
+            let &(bx, by) = main
+                .iter()
+                .min_by_key(|&&(x, y)| x.abs_diff(ax) + y.abs_diff(ay))
+                .unwrap();
+            let horizontal = (ax.min(bx)..=ax.max(bx)).map(|x| (x, ay));
+            let vertical = (ay.min(by)..=ay.max(by)).map(|y| (bx, y));
+            for (x, y) in horizontal.chain(vertical) {
+                for (ox, oy) in orbit(x, y, w, h, folds) {
+                    grid.set(ox as i32, oy as i32, Tile::Floor);
+                }
+            }
+        }
+    }
+}
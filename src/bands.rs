@@ -0,0 +1,176 @@
+//! Elevation bands: heightmap to tile classes.
+//!
+//! [`classify`] maps each height through ordered thresholds to a band index,
+//! and [`paint`] turns the result into a [`TileCell`] grid using each band's
+//! [`TileDef`]. The default bands are deep water, shallow water, beach,
+//! grass, rock, and snow; [`BandConfig::tileset`] returns a palette that
+//! renders them.
+//!
+//! With `dither` set, band edges are jittered by noise so coastlines and
+//! snow lines fray instead of following contour lines exactly.
+//!
+//! ```rust
+//! use terrain_forge::algorithms::DiamondSquare;
+//! use terrain_forge::bands::{self, BandConfig};
+//!
+//! let heights = DiamondSquare::default().heightmap(64, 48, 3);
+//! let config = BandConfig::default().with_dither(0.03);
+//! let grid = bands::paint(&heights, &config, 3);
+//! let text = config.tileset().render_text(&grid);
+//! assert_eq!(text.lines().count(), 48);
+//! ```
+
+use crate::noise::{NoiseExt, NoiseSource, Perlin};
+use crate::tileset::{TileCell, TileDef, TileId, TileSet};
+use crate::Grid;
+use serde::{Deserialize, Serialize};
+
+/// One elevation band.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Band {
+    /// Heights below this (after dithering) fall in this band or a lower one.
+    pub max: f64,
+    pub tile: TileDef,
+}
+
+impl Band {
+    pub fn new(max: f64, tile: TileDef) -> Self {
+        Self { max, tile }
+    }
+}
+
+/// Configuration for [`classify`] and [`paint`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandConfig {
+    /// Bands in ascending `max` order. Heights at or above the last band's
+    /// `max` still fall in the last band.
+    pub bands: Vec<Band>,
+    /// Largest height offset applied at band edges, in heightmap units;
+    /// 0 disables dithering. Default: 0.0.
+    pub dither: f64,
+    /// Dither noise frequency. Default: 0.15.
+    pub dither_frequency: f64,
+}
+
+impl Default for BandConfig {
+    fn default() -> Self {
+        let tile = |id, name: &str, passable, color, glyph| {
+            TileDef::new(TileId(id), name)
+                .with_passable(passable)
+                .with_color(color)
+                .with_glyph(glyph)
+        };
+        Self {
+            bands: vec![
+                Band::new(0.3, tile(2, "deep_water", false, [20, 50, 140], '~')),
+                Band::new(0.4, tile(3, "shallow_water", true, [60, 110, 200], '-')),
+                Band::new(0.45, tile(4, "beach", true, [220, 200, 140], ',')),
+                Band::new(0.65, tile(5, "grass", true, [80, 160, 60], '"')),
+                Band::new(0.85, tile(6, "rock", true, [120, 110, 100], '^')),
+                Band::new(1.0, tile(7, "snow", true, [240, 240, 250], '*')),
+            ],
+            dither: 0.0,
+            dither_frequency: 0.15,
+        }
+    }
+}
+
+impl BandConfig {
+    /// Bands from `(max, tile)` pairs, sorted by `max`.
+    pub fn new(bands: impl IntoIterator<Item = (f64, TileDef)>) -> Self {
+        let mut bands: Vec<Band> = bands.into_iter().map(|(m, t)| Band::new(m, t)).collect();
+        bands.sort_by(|a, b| a.max.total_cmp(&b.max));
+        Self {
+            bands,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a band, keeping `bands` sorted; replaces a band with the same
+    /// tile name.
+    pub fn with_band(mut self, max: f64, tile: TileDef) -> Self {
+        self.bands.retain(|b| b.tile.name != tile.name);
+        let at = self.bands.partition_point(|b| b.max <= max);
+        self.bands.insert(at, Band::new(max, tile));
+        self
+    }
+
+    /// Sets `dither`.
+    pub fn with_dither(mut self, dither: f64) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Sets `dither_frequency`.
+    pub fn with_dither_frequency(mut self, frequency: f64) -> Self {
+        self.dither_frequency = frequency;
+        self
+    }
+
+    /// Index of the band called `name`.
+    pub fn band(&self, name: &str) -> Option<usize> {
+        self.bands.iter().position(|b| b.tile.name == name)
+    }
+
+    /// The default palette plus every band's tile.
+    pub fn tileset(&self) -> TileSet {
+        self.bands
+            .iter()
+            .fold(TileSet::default(), |set, b| set.with_tile(b.tile.clone()))
+    }
+
+    fn index(&self, height: f64) -> usize {
+        self.bands
+            .iter()
+            .position(|b| height < b.max)
+            .unwrap_or(self.bands.len().saturating_sub(1))
+    }
+}
+
+/// Band index per cell, indexed `[y][x]`.
+///
+/// `seed` only drives the dither noise. With no bands every cell is band 0.
+pub fn classify(heights: &[Vec<f64>], config: &BandConfig, seed: u64) -> Vec<Vec<usize>> {
+    let noise = (config.dither > 0.0).then(|| Perlin::new(seed).fbm(2, 2.0, 0.5));
+    let f = config.dither_frequency;
+    heights
+        .iter()
+        .enumerate()
+        .map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(x, &height)| {
+                    let offset = noise.as_ref().map_or(0.0, |n| {
+                        config.dither * n.sample(x as f64 * f, y as f64 * f).clamp(-1.0, 1.0)
+                    });
+                    config.index(height + offset)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Classifies `heights` and paints each cell with its band's tile.
+///
+/// Passability comes from the band's [`TileDef`]. With no bands every cell
+/// is [`TileId::WALL`].
+pub fn paint(heights: &[Vec<f64>], config: &BandConfig, seed: u64) -> Grid<TileCell> {
+    let tileset = config.tileset();
+    let cells: Vec<TileCell> = config
+        .bands
+        .iter()
+        .map(|b| tileset.cell(b.tile.id))
+        .collect();
+    let h = heights.len();
+    let w = heights.first().map_or(0, Vec::len);
+    let mut grid = Grid::new(w, h);
+    for (y, row) in classify(heights, config, seed).into_iter().enumerate() {
+        for (x, band) in row.into_iter().enumerate() {
+            if let Some(cell) = cells.get(band) {
+                grid.set(x as i32, y as i32, *cell);
+            }
+        }
+    }
+    grid
+}
@@ -0,0 +1,231 @@
+//! Timing benchmarks and wall-clock budgets for algorithms.
+//!
+//! [`measure`] runs an algorithm over a set of grid sizes and seeds and
+//! reports per-size timing; with feature `profile` and
+//! [`CountingAllocator`](crate::profile::CountingAllocator) installed it also
+//! reports allocations. [`TimeBudgeted`] wraps an algorithm with a runtime
+//! budget: a run that overshoots is cancelled and either replaced by a
+//! cheaper fallback or reported as
+//! [`Error::BudgetExceeded`](crate::Error::BudgetExceeded).
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use terrain_forge::algorithms::{Bsp, CellularAutomata};
+//! use terrain_forge::bench::{self, BudgetOutcome, TimeBudgeted};
+//! use terrain_forge::Grid;
+//!
+//! let report = bench::measure(&CellularAutomata::default(), &[(40, 30), (80, 60)], &[1, 2]);
+//! assert_eq!(report.sizes.len(), 2);
+//! assert_eq!(report.sizes[1].runs, 2);
+//!
+//! let budgeted = TimeBudgeted::new(CellularAutomata::default(), Duration::from_secs(5))
+//!     .with_fallback(Bsp::default());
+//! let mut grid = Grid::new(80, 60);
+//! let outcome = budgeted.try_generate(&mut grid, 7).unwrap();
+//! assert!(matches!(outcome, BudgetOutcome::Completed(_)));
+//! ```
+
+use crate::{Algorithm, Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Timing for one grid size over all seeds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeStats {
+    pub width: usize,
+    pub height: usize,
+    pub runs: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+    /// Allocations per run, averaged. `None` without feature `profile`;
+    /// zero unless `CountingAllocator` is the global allocator.
+    pub allocations: Option<u64>,
+    /// Bytes allocated per run, averaged; see `allocations`.
+    pub allocated_bytes: Option<u64>,
+}
+
+impl SizeStats {
+    /// Microseconds per cell at the mean time.
+    pub fn micros_per_cell(&self) -> f64 {
+        let cells = (self.width * self.height).max(1) as f64;
+        self.mean.as_secs_f64() * 1e6 / cells
+    }
+}
+
+/// Result of [`measure`], one entry per requested size.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub algorithm: String,
+    pub sizes: Vec<SizeStats>,
+}
+
+impl BenchReport {
+    /// Largest measured size whose worst run fits in `budget`.
+    pub fn largest_within(&self, budget: Duration) -> Option<&SizeStats> {
+        self.sizes
+            .iter()
+            .filter(|s| s.runs > 0 && s.max <= budget)
+            .max_by_key(|s| s.width * s.height)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Runs `algorithm` once per seed at each `(width, height)` and collects
+/// timings. Runs happen on the calling thread, one after another.
+pub fn measure<A: Algorithm + ?Sized>(
+    algorithm: &A,
+    sizes: &[(usize, usize)],
+    seeds: &[u64],
+) -> BenchReport {
+    let sizes = sizes
+        .iter()
+        .map(|&(width, height)| {
+            let mut times = Vec::with_capacity(seeds.len());
+            let (mut allocations, mut bytes) = (0, 0);
+            for &seed in seeds {
+                let mut grid = Grid::new(width, height);
+                let before = allocation_totals();
+                let start = Instant::now();
+                algorithm.generate(&mut grid, seed);
+                times.push(start.elapsed());
+                let after = allocation_totals();
+                allocations += after.0 - before.0;
+                bytes += after.1 - before.1;
+            }
+            let runs = times.len();
+            let per_run =
+                |total: u64| cfg!(feature = "profile").then(|| total / runs.max(1) as u64);
+            SizeStats {
+                width,
+                height,
+                runs,
+                min: times.iter().copied().min().unwrap_or_default(),
+                mean: times.iter().sum::<Duration>() / runs.max(1) as u32,
+                max: times.iter().copied().max().unwrap_or_default(),
+                allocations: per_run(allocations),
+                allocated_bytes: per_run(bytes),
+            }
+        })
+        .collect();
+    BenchReport {
+        algorithm: algorithm.name().to_string(),
+        sizes,
+    }
+}
+
+#[cfg(feature = "profile")]
+fn allocation_totals() -> (u64, u64) {
+    crate::profile::allocation_totals()
+}
+
+#[cfg(not(feature = "profile"))]
+fn allocation_totals() -> (u64, u64) {
+    (0, 0)
+}
+
+/// How a [`TimeBudgeted`] run finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetOutcome {
+    /// The algorithm finished within budget in this time.
+    Completed(Duration),
+    /// The algorithm overshot and the fallback produced the grid; the
+    /// duration covers both.
+    Degraded(Duration),
+}
+
+/// Wraps an algorithm with a wall-clock budget.
+///
+/// The algorithm runs on the calling thread against a copy of the grid; no
+/// threads are spawned. Cancellation is cooperative: the budget applies to
+/// every soft time limit checked during the run (see
+/// [`TimedRun`](crate::algorithms::TimedRun)), so [`Dla`](crate::algorithms::Dla),
+/// [`DrunkardWalk`](crate::algorithms::DrunkardWalk),
+/// [`AgentBased`](crate::algorithms::AgentBased), and
+/// [`Wfc`](crate::algorithms::Wfc), including inside pipelines, stop
+/// once it passes. Other algorithms can't be interrupted and finish their
+/// run, so only wrap those whose [`measure`] numbers roughly fit.
+///
+/// If the run finishes in time its result is copied back. Otherwise the
+/// partial result is discarded, the grid is left untouched, and the
+/// fallback (typically the same algorithm with cheaper params) runs; without
+/// a fallback [`try_generate`](Self::try_generate) fails with
+/// [`Error::BudgetExceeded`](crate::Error::BudgetExceeded). The fallback
+/// itself is not timed out.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct TimeBudgeted {
+    inner: Box<dyn Algorithm + Send + Sync>,
+    fallback: Option<Box<dyn Algorithm + Send + Sync>>,
+    budget: Duration,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TimeBudgeted {
+    pub fn new<A: Algorithm + 'static>(algorithm: A, budget: Duration) -> Self {
+        Self {
+            inner: Box::new(algorithm),
+            fallback: None,
+            budget,
+        }
+    }
+
+    /// Sets the algorithm run when the budget is exceeded.
+    pub fn with_fallback<A: Algorithm + 'static>(mut self, fallback: A) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Generates into `grid`, reporting whether the fallback was needed.
+    pub fn try_generate(
+        &self,
+        grid: &mut Grid<Tile>,
+        seed: u64,
+    ) -> crate::error::Result<BudgetOutcome> {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let start = Instant::now();
+        let mut scratch = grid.clone();
+        let run = catch_unwind(AssertUnwindSafe(|| {
+            crate::algorithms::with_deadline(start + self.budget, || {
+                self.inner.generate(&mut scratch, seed)
+            })
+        }));
+        if run.is_err() {
+            return Err(crate::Error::new(format!("{} panicked", self.inner.name())));
+        }
+        if start.elapsed() < self.budget {
+            *grid = scratch;
+            return Ok(BudgetOutcome::Completed(start.elapsed()));
+        }
+        match &self.fallback {
+            Some(fallback) => {
+                fallback.generate(grid, seed);
+                Ok(BudgetOutcome::Degraded(start.elapsed()))
+            }
+            None => Err(crate::Error::BudgetExceeded {
+                algorithm: self.inner.name().to_string(),
+                budget: self.budget,
+            }),
+        }
+    }
+}
+
+/// [`Algorithm::generate`] ignores the outcome; an aborted run (no
+/// fallback) leaves the grid unchanged.
+#[cfg(not(target_arch = "wasm32"))]
+impl Algorithm for TimeBudgeted {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        let _ = self.try_generate(grid, seed);
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
@@ -0,0 +1,340 @@
+//! Bevy integration (feature `bevy`).
+//!
+//! Add [`TerrainForgePlugin`], then spawn an entity with [`GenerateTerrain`].
+//! The op script runs on Bevy's `AsyncComputeTaskPool`; when it finishes the
+//! entity receives a [`TerrainMap`], each semantic marker is spawned as a
+//! [`TerrainMarker`] child, and a [`TerrainGenerated`] event fires. With
+//! the `bevy-tilemap` feature, `TerrainTilemapPlugin` also turns each map
+//! and its markers into `bevy_ecs_tilemap` tilemaps; without it, apps can
+//! build their own from [`TerrainMap::tiles`].
+//!
+//! ```rust,no_run
+//! use bevy_app::{App, TaskPoolPlugin, Update};
+//! use bevy_ecs::prelude::*;
+//! use terrain_forge::bevy::{GenerateTerrain, TerrainForgePlugin, TerrainGenerated, TerrainMap};
+//!
+//! fn on_generated(mut events: EventReader<TerrainGenerated>, maps: Query<&TerrainMap>) {
+//!     for event in events.read() {
+//!         let map = maps.get(event.entity).unwrap();
+//!         for (x, y, tile) in map.tiles() {
+//!             // e.g. spawn a sprite at (x, map.flipped_y(y))
+//!             let _ = (x, y, tile);
+//!         }
+//!     }
+//! }
+//!
+//! let mut app = App::new();
+//! app.add_plugins((TaskPoolPlugin::default(), TerrainForgePlugin))
+//!     .add_systems(Update, on_generated);
+//! app.world_mut()
+//!     .spawn(GenerateTerrain::new("cellular", 80, 60, 42).with_semantics("caves"));
+//! app.update();
+//! ```
+
+use crate::ops::{OpScript, Params, ScriptOp, ScriptOutput};
+use crate::semantic::{Marker, SemanticLayers};
+use crate::{Grid, Tile};
+use ::bevy_app::{App, Plugin, Update};
+use ::bevy_ecs::prelude::*;
+use ::bevy_tasks::{futures::check_ready, AsyncComputeTaskPool, Task};
+
+/// Registers the generation systems and the [`TerrainGenerated`] /
+/// [`TerrainGenerationFailed`] events.
+///
+/// Requires Bevy's task pools (`TaskPoolPlugin`, included in `MinimalPlugins`
+/// and `DefaultPlugins`).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TerrainForgePlugin;
+
+impl Plugin for TerrainForgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TerrainGenerated>()
+            .add_event::<TerrainGenerationFailed>()
+            .add_systems(Update, (start_generation, finish_generation).chain());
+    }
+}
+
+/// Request to generate terrain on this entity.
+///
+/// Removed once the task starts; the entity then carries a
+/// [`TerrainGenerating`] component until the result arrives.
+#[derive(Component, Debug, Clone)]
+pub struct GenerateTerrain {
+    pub script: OpScript,
+}
+
+impl GenerateTerrain {
+    /// Generates a single algorithm with default params.
+    pub fn new(algorithm: impl Into<String>, width: usize, height: usize, seed: u64) -> Self {
+        Self::script(OpScript {
+            width,
+            height,
+            seed: Some(seed),
+            ops: vec![ScriptOp::Generate {
+                name: algorithm.into(),
+                seed: Some(seed),
+                params: None,
+            }],
+        })
+    }
+
+    /// Runs an arbitrary op script.
+    pub fn script(script: OpScript) -> Self {
+        Self { script }
+    }
+
+    /// Sets params on the first `generate` op.
+    pub fn with_params(mut self, params: Params) -> Self {
+        let first = self.script.ops.iter_mut().find_map(|op| match op {
+            ScriptOp::Generate { params, .. } => Some(params),
+            _ => None,
+        });
+        if let Some(slot) = first {
+            *slot = Some(params);
+        }
+        self
+    }
+
+    /// Appends an effect op.
+    pub fn with_effect(mut self, name: impl Into<String>, params: Option<Params>) -> Self {
+        self.script.ops.push(ScriptOp::Effect {
+            name: name.into(),
+            params,
+        });
+        self
+    }
+
+    /// Appends a semantic extraction op with the given profile
+    /// (`default`, `caves`, `rooms`, `mazes`).
+    pub fn with_semantics(mut self, profile: impl Into<String>) -> Self {
+        self.script.ops.push(ScriptOp::Extract {
+            profile: Some(profile.into()),
+            seed: None,
+        });
+        self
+    }
+}
+
+/// In-flight generation task.
+#[derive(Component)]
+pub struct TerrainGenerating(Task<Result<ScriptOutput, String>>);
+
+/// Generated terrain attached to the requesting entity.
+#[derive(Component, Debug, Clone)]
+pub struct TerrainMap {
+    pub grid: Grid<Tile>,
+    pub semantic: Option<SemanticLayers>,
+}
+
+impl TerrainMap {
+    /// Iterates cells as `(x, y, tile)` in grid coordinates (y down).
+    pub fn tiles(&self) -> impl Iterator<Item = (u32, u32, Tile)> + '_ {
+        self.grid.iter().map(|(x, y, t)| (x as u32, y as u32, *t))
+    }
+
+    /// Converts a grid row to a y-up row, as used by Bevy world space and
+    /// `bevy_ecs_tilemap`'s `TilePos`.
+    pub fn flipped_y(&self, y: u32) -> u32 {
+        self.grid.height() as u32 - 1 - y
+    }
+}
+
+/// A semantic marker spawned as a child of the terrain entity.
+#[derive(Component, Debug, Clone)]
+pub struct TerrainMarker {
+    /// Marker tag (see [`Marker::tag`]).
+    pub tag: String,
+    pub marker: Marker,
+}
+
+/// Fired when generation for `entity` completes.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TerrainGenerated {
+    pub entity: Entity,
+}
+
+/// Fired when generation for `entity` fails.
+#[derive(Event, Debug, Clone)]
+pub struct TerrainGenerationFailed {
+    pub entity: Entity,
+    pub error: String,
+}
+
+fn start_generation(mut commands: Commands, requests: Query<(Entity, &GenerateTerrain)>) {
+    let pool = AsyncComputeTaskPool::get();
+    for (entity, request) in &requests {
+        let script = request.script.clone();
+        let task = pool.spawn(async move { script.run().map_err(|e| e.to_string()) });
+        commands
+            .entity(entity)
+            .remove::<GenerateTerrain>()
+            .insert(TerrainGenerating(task));
+    }
+}
+
+fn finish_generation(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut TerrainGenerating)>,
+    mut generated: EventWriter<TerrainGenerated>,
+    mut failed: EventWriter<TerrainGenerationFailed>,
+) {
+    for (entity, mut task) in &mut tasks {
+        let Some(result) = check_ready(&mut task.0) else {
+            continue;
+        };
+        commands.entity(entity).remove::<TerrainGenerating>();
+        match result {
+            Ok(output) => {
+                if let Some(semantic) = &output.semantic {
+                    for marker in &semantic.markers {
+                        commands.spawn((
+                            TerrainMarker {
+                                tag: marker.tag(),
+                                marker: marker.clone(),
+                            },
+                            ChildOf(entity),
+                        ));
+                    }
+                }
+                commands.entity(entity).insert(TerrainMap {
+                    grid: output.grid,
+                    semantic: output.semantic,
+                });
+                generated.write(TerrainGenerated { entity });
+            }
+            Err(error) => {
+                failed.write(TerrainGenerationFailed { entity, error });
+            }
+        }
+    }
+}
+
+/// Builds `bevy_ecs_tilemap` tile layers for generated terrain (feature
+/// `bevy-tilemap`).
+///
+/// Add it next to [`TerrainForgePlugin`]. Each new [`TerrainMap`] entity
+/// becomes a square tilemap: it gets a `TileStorage`, `TilemapSize`, and
+/// `TilemapType`, and every grid cell is spawned as a child `TileBundle`
+/// whose texture index comes from [`TerrainTileIndices`], with `y` flipped
+/// so row 0 is at the top. Markers whose tag has an index get a second
+/// tilemap, a [`TerrainMarkerLayer`] child, at the same positions.
+///
+/// Rendering needs the rest of a `TilemapBundle` (texture, tile and grid
+/// sizes, transform) on those entities, which the app inserts, for example
+/// on [`TerrainGenerated`].
+#[cfg(feature = "bevy-tilemap")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TerrainTilemapPlugin;
+
+#[cfg(feature = "bevy-tilemap")]
+impl Plugin for TerrainTilemapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainTileIndices>()
+            .add_systems(Update, build_tilemaps.after(finish_generation));
+    }
+}
+
+/// Texture indices used by [`TerrainTilemapPlugin`].
+#[cfg(feature = "bevy-tilemap")]
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct TerrainTileIndices {
+    /// Index for wall cells, or `None` to leave them empty. Default: 0.
+    pub wall: Option<u32>,
+    /// Index for floor cells, or `None` to leave them empty. Default: 1.
+    pub floor: Option<u32>,
+    /// Index per marker tag (see [`Marker::tag`]); markers with other tags
+    /// get no tile. Default: empty, so no marker layer.
+    pub markers: std::collections::BTreeMap<String, u32>,
+}
+
+#[cfg(feature = "bevy-tilemap")]
+impl Default for TerrainTileIndices {
+    fn default() -> Self {
+        Self {
+            wall: Some(0),
+            floor: Some(1),
+            markers: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(feature = "bevy-tilemap")]
+impl TerrainTileIndices {
+    /// Sets the index for markers tagged `tag`.
+    pub fn with_marker(mut self, tag: impl Into<String>, index: u32) -> Self {
+        self.markers.insert(tag.into(), index);
+        self
+    }
+}
+
+/// The tilemap of a terrain entity's markers, spawned as its child by
+/// [`TerrainTilemapPlugin`].
+#[cfg(feature = "bevy-tilemap")]
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TerrainMarkerLayer;
+
+#[cfg(feature = "bevy-tilemap")]
+fn build_tilemaps(
+    mut commands: Commands,
+    indices: Res<TerrainTileIndices>,
+    maps: Query<(Entity, &TerrainMap), Added<TerrainMap>>,
+) {
+    for (entity, map) in &maps {
+        let cells = map.tiles().filter_map(|(x, y, tile)| {
+            let index = if tile.is_floor() {
+                indices.floor
+            } else {
+                indices.wall
+            };
+            Some((x, map.flipped_y(y), index?))
+        });
+        insert_tilemap(&mut commands, entity, &map.grid, cells);
+
+        let markers: Vec<(u32, u32, u32)> = map
+            .semantic
+            .iter()
+            .flat_map(|semantic| &semantic.markers)
+            .filter(|m| map.grid.in_bounds(m.x as i32, m.y as i32))
+            .filter_map(|m| Some((m.x, map.flipped_y(m.y), *indices.markers.get(&m.tag())?)))
+            .collect();
+        if !markers.is_empty() {
+            let layer = commands.spawn((TerrainMarkerLayer, ChildOf(entity))).id();
+            insert_tilemap(&mut commands, layer, &map.grid, markers);
+        }
+    }
+}
+
+/// Makes `layer` a square tilemap the size of `grid`, spawning `tiles` as
+/// `(x, y, texture index)` in tilemap coordinates as its children.
+#[cfg(feature = "bevy-tilemap")]
+fn insert_tilemap(
+    commands: &mut Commands,
+    layer: Entity,
+    grid: &Grid<Tile>,
+    tiles: impl IntoIterator<Item = (u32, u32, u32)>,
+) {
+    use ::bevy_ecs_tilemap::prelude::{
+        TileBundle, TilePos, TileStorage, TileTextureIndex, TilemapId, TilemapSize, TilemapType,
+    };
+
+    let size = TilemapSize::new(grid.width() as u32, grid.height() as u32);
+    let mut storage = TileStorage::empty(size);
+    for (x, y, index) in tiles {
+        let position = TilePos::new(x, y);
+        let tile = commands
+            .spawn((
+                TileBundle {
+                    position,
+                    tilemap_id: TilemapId(layer),
+                    texture_index: TileTextureIndex(index),
+                    ..Default::default()
+                },
+                ChildOf(layer),
+            ))
+            .id();
+        storage.set(&position, tile);
+    }
+    commands
+        .entity(layer)
+        .insert((storage, size, TilemapType::Square));
+}
@@ -0,0 +1,220 @@
+//! Compact binary serialization for grids, semantics, and pipeline snapshots
+//! (features `bincode` and/or `msgpack`).
+//!
+//! Payloads are bincode (feature `bincode`) or MessagePack (feature
+//! `msgpack`) behind an 8-byte header:
+//!
+//! | bytes | content                              |
+//! |-------|--------------------------------------|
+//! | 0..4  | magic `TFBN`                         |
+//! | 4..6  | [`FORMAT_VERSION`], little-endian    |
+//! | 6     | codec (`1` bincode, `2` MessagePack) |
+//! | 7     | reserved, `0`                        |
+//!
+//! [`decode`] reads the codec from the header, so callers only pick one when
+//! encoding. Any `Serialize` type works; [`Snapshot`] bundles a generated
+//! map with its semantic layers and pipeline context.
+//!
+//! ```rust
+//! # #[cfg(feature = "bincode")] {
+//! use terrain_forge::binary::{self, Codec};
+//! use terrain_forge::{ops, Grid, Tile};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! ops::generate("bsp", &mut grid, Some(7), None).unwrap();
+//! let bytes = binary::encode(&grid, Codec::Bincode).unwrap();
+//! let back: Grid<Tile> = binary::decode(&bytes).unwrap();
+//! assert_eq!(back, grid);
+//! # }
+//! ```
+
+use crate::pipeline::PipelineContext;
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Tile};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Leading bytes of every encoded payload.
+pub const MAGIC: [u8; 4] = *b"TFBN";
+/// Current header version; bumped when encoded layouts change.
+pub const FORMAT_VERSION: u16 = 1;
+/// Header length in bytes.
+pub const HEADER_LEN: usize = 8;
+
+/// Binary codec for the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Requires feature `bincode`.
+    Bincode,
+    /// Requires feature `msgpack`.
+    MessagePack,
+}
+
+impl Codec {
+    fn id(self) -> u8 {
+        match self {
+            Codec::Bincode => 1,
+            Codec::MessagePack => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Codec::Bincode),
+            2 => Some(Codec::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed payload header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub version: u16,
+    pub codec: Codec,
+}
+
+impl Header {
+    /// Reads and validates the header at the start of `bytes`.
+    pub fn read(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        if bytes.len() < HEADER_LEN || bytes[..4] != MAGIC {
+            return Err("not a terrain-forge binary payload".into());
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version > FORMAT_VERSION {
+            return Err(format!(
+                "unsupported format version {} (newest supported: {})",
+                version, FORMAT_VERSION
+            )
+            .into());
+        }
+        let codec =
+            Codec::from_id(bytes[6]).ok_or_else(|| format!("unknown codec id {}", bytes[6]))?;
+        Ok(Self { version, codec })
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let v = self.version.to_le_bytes();
+        [
+            MAGIC[0],
+            MAGIC[1],
+            MAGIC[2],
+            MAGIC[3],
+            v[0],
+            v[1],
+            self.codec.id(),
+            0,
+        ]
+    }
+}
+
+/// A generated map with the state needed to inspect or resume it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub grid: Grid<Tile>,
+    pub seed: Option<u64>,
+    pub semantic: Option<SemanticLayers>,
+    pub context: Option<PipelineContext>,
+}
+
+impl Snapshot {
+    pub fn new(grid: Grid<Tile>) -> Self {
+        Self {
+            grid,
+            seed: None,
+            semantic: None,
+            context: None,
+        }
+    }
+
+    /// Sets `seed`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets `semantic`.
+    pub fn with_semantic(mut self, semantic: SemanticLayers) -> Self {
+        self.semantic = Some(semantic);
+        self
+    }
+
+    /// Sets `context`.
+    pub fn with_context(mut self, context: PipelineContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+}
+
+/// Encodes `value` with a versioned header.
+pub fn encode<T: Serialize>(
+    value: &T,
+    codec: Codec,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut out = Header {
+        version: FORMAT_VERSION,
+        codec,
+    }
+    .to_bytes()
+    .to_vec();
+    match codec {
+        #[cfg(feature = "bincode")]
+        Codec::Bincode => {
+            out.extend(bincode::serde::encode_to_vec(
+                value,
+                bincode::config::standard(),
+            )?);
+        }
+        #[cfg(feature = "msgpack")]
+        Codec::MessagePack => out.extend(rmp_serde::to_vec(value)?),
+        #[cfg(not(all(feature = "bincode", feature = "msgpack")))]
+        _ => return Err(codec_disabled(codec)),
+    }
+    Ok(out)
+}
+
+/// Decodes a payload written by [`encode`], using the codec in its header.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Box<dyn std::error::Error>> {
+    let header = Header::read(bytes)?;
+    let payload = &bytes[HEADER_LEN..];
+    match header.codec {
+        #[cfg(feature = "bincode")]
+        Codec::Bincode => {
+            let (value, _) =
+                bincode::serde::decode_from_slice(payload, bincode::config::standard())?;
+            Ok(value)
+        }
+        #[cfg(feature = "msgpack")]
+        Codec::MessagePack => Ok(rmp_serde::from_slice(payload)?),
+        #[cfg(not(all(feature = "bincode", feature = "msgpack")))]
+        codec => Err(codec_disabled(codec)),
+    }
+}
+
+/// Encodes `value` and writes it to `path`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save<T: Serialize, P: AsRef<std::path::Path>>(
+    path: P,
+    value: &T,
+    codec: Codec,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, encode(value, codec)?)?;
+    Ok(())
+}
+
+/// Reads and decodes a payload from `path`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load<T: DeserializeOwned, P: AsRef<std::path::Path>>(
+    path: P,
+) -> Result<T, Box<dyn std::error::Error>> {
+    decode(&std::fs::read(path)?)
+}
+
+#[cfg(not(all(feature = "bincode", feature = "msgpack")))]
+fn codec_disabled(codec: Codec) -> Box<dyn std::error::Error> {
+    let feature = match codec {
+        Codec::Bincode => "bincode",
+        Codec::MessagePack => "msgpack",
+    };
+    format!("{:?} support requires the `{}` feature", codec, feature).into()
+}
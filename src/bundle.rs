@@ -0,0 +1,217 @@
+//! Self-describing map files.
+//!
+//! A [`MapBundle`] packages a generated map with everything needed to
+//! explain or reproduce it later: the grid (plus any extra named grids),
+//! semantic layers, the generation [`Config`], the seed, the crate version
+//! that made it, and free-form metadata. [`MapBundle::save`] and
+//! [`MapBundle::load`] read and write it as JSON in one call.
+//!
+//! Bundles are forward compatible: every field has a default, so older
+//! files load, and fields added by newer versions are kept in
+//! [`extra`](MapBundle::extra) and written back on save.
+//! [`BUNDLE_VERSION`] is bumped when the meaning of an existing field
+//! changes.
+//!
+//! ```rust
+//! use terrain_forge::bundle::MapBundle;
+//! use terrain_forge::config::Config;
+//!
+//! let config = Config::from_shorthand("bsp").unwrap();
+//! let bundle = MapBundle::generate(&config, 42)
+//!     .unwrap()
+//!     .with_metadata("author", "level-team");
+//!
+//! let json = bundle.to_json().unwrap();
+//! let loaded = MapBundle::from_json(&json).unwrap();
+//! assert_eq!(loaded.seed, Some(42));
+//! assert!(loaded.reproduces().unwrap());
+//! ```
+
+use crate::config::Config;
+use crate::entities::{EntityDescriptor, EntityMapping};
+use crate::error::{Error, Result};
+use crate::ops::OpResult;
+use crate::semantic::SemanticLayers;
+use crate::spawns::{Spawn, SpawnTables};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Current bundle format version.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// A map plus how it was made.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapBundle {
+    /// Format version the bundle was written with.
+    pub version: u32,
+    /// terrain-forge version that wrote the bundle.
+    pub crate_version: String,
+    pub seed: Option<u64>,
+    pub config: Option<Config>,
+    pub grid: Grid<Tile>,
+    /// Extra named grids (other floors, masks, previews).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub layers: BTreeMap<String, Grid<Tile>>,
+    pub semantic: Option<SemanticLayers>,
+    /// Entities mapped from the semantic markers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<EntityDescriptor>,
+    /// Spawns rolled at the semantic markers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spawns: Vec<Spawn>,
+    /// Free-form user metadata.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Value>,
+    /// Fields this version doesn't know, kept so re-saving loses nothing.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl Default for MapBundle {
+    fn default() -> Self {
+        Self::new(Grid::new(0, 0))
+    }
+}
+
+impl MapBundle {
+    /// A bundle holding just `grid`, stamped with the current versions.
+    pub fn new(grid: Grid<Tile>) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: None,
+            config: None,
+            grid,
+            layers: BTreeMap::new(),
+            semantic: None,
+            entities: Vec::new(),
+            spawns: Vec::new(),
+            metadata: BTreeMap::new(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Generates a map from `config` and `seed`, extracts its semantic
+    /// layers, places `config.exits`, maps `config.entities` and rolls
+    /// `config.spawns` over them, and records both inputs.
+    pub fn generate(config: &Config, seed: u64) -> OpResult<Self> {
+        let mut grid = config.generate(seed)?;
+        let mut semantic = config.extractor().extract(&grid, &mut Rng::new(seed));
+        config.apply_marker_overrides(&mut semantic);
+        if let Some(exits) = &config.exits {
+            crate::exits::place_multi(&mut grid, &mut semantic, exits);
+        }
+        let mut bundle = Self::new(grid)
+            .with_seed(seed)
+            .with_config(config.clone())
+            .with_semantic(semantic);
+        if let Some(mapping) = &config.entities {
+            bundle = bundle.with_entities(mapping);
+        }
+        if let Some(tables) = &config.spawns {
+            bundle = bundle.with_spawns(tables);
+        }
+        Ok(bundle)
+    }
+
+    /// Sets `seed`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets `config`.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Sets `semantic`.
+    pub fn with_semantic(mut self, semantic: SemanticLayers) -> Self {
+        self.semantic = Some(semantic);
+        self
+    }
+
+    /// Sets `entities` by running `mapping` over the bundle's semantic
+    /// markers; clears them if there are no semantic layers.
+    pub fn with_entities(mut self, mapping: &EntityMapping) -> Self {
+        self.entities = self
+            .semantic
+            .as_ref()
+            .map(|s| mapping.map(s))
+            .unwrap_or_default();
+        self
+    }
+
+    /// Sets `spawns` by sampling `tables` at the bundle's semantic markers
+    /// with its seed (0 if unset); clears them if there are no semantic
+    /// layers.
+    pub fn with_spawns(mut self, tables: &SpawnTables) -> Self {
+        let seed = self.seed.unwrap_or(0);
+        self.spawns = self
+            .semantic
+            .as_ref()
+            .map(|s| tables.sample(&s.markers, seed))
+            .unwrap_or_default();
+        self
+    }
+
+    /// Adds a named grid to `layers`.
+    pub fn with_layer(mut self, name: impl Into<String>, grid: Grid<Tile>) -> Self {
+        self.layers.insert(name.into(), grid);
+        self
+    }
+
+    /// Adds a metadata entry.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Whether the bundle was written by a newer format version than this
+    /// crate understands; its unknown fields are in `extra`.
+    pub fn is_newer(&self) -> bool {
+        self.version > BUNDLE_VERSION
+    }
+
+    /// Regenerates the grid from the recorded config and seed.
+    ///
+    /// Fails if either is missing.
+    pub fn regenerate(&self) -> Result<Grid<Tile>> {
+        let (Some(config), Some(seed)) = (&self.config, self.seed) else {
+            return Err(Error::new(
+                "bundle has no config and seed to regenerate from",
+            ));
+        };
+        config.generate(seed)
+    }
+
+    /// Whether regenerating reproduces the stored grid exactly.
+    pub fn reproduces(&self) -> Result<bool> {
+        Ok(self.regenerate()? == self.grid)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::parse("map bundle", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::parse("map bundle", e))
+    }
+
+    /// Writes the bundle as JSON.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Reads a bundle written by [`save`](Self::save).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
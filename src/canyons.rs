@@ -0,0 +1,419 @@
+//! Braided canyons and ravines carved into heightmaps.
+//!
+//! [`carve`] runs directed random walks across a heightmap: each main
+//! canyon enters from a map edge and wanders toward the far side, keeping
+//! its general bearing while its heading drifts step by step. Along the
+//! way it forks into side ravines, which either peter out or, with
+//! [`CanyonConfig::braid`], bend back and rejoin their parent, leaving a
+//! mesa between the two channels. Every walk is cut into the terrain with
+//! a cross-section [`CanyonProfile`] whose width and depth wobble along the
+//! path and taper to nothing at free tips.
+//!
+//! The result holds the lowered heights, the canyon centerlines, and a
+//! chasm mask of the cells cut deep enough to be impassable.
+//! [`Canyons::apply`] walls those cells off in a tile grid, and
+//! [`Canyons::features`] marks them [`TerrainFeature::Chasm`] for
+//! [`SemanticExtractor::extract_terrain`](crate::SemanticExtractor::extract_terrain).
+//!
+//! ```rust
+//! use terrain_forge::algorithms::DiamondSquare;
+//! use terrain_forge::canyons::{self, CanyonConfig};
+//!
+//! let heights = DiamondSquare::default().heightmap(96, 64, 7);
+//! let canyons = canyons::carve(&heights, &CanyonConfig::default(), 7);
+//! assert!(canyons.paths.len() >= 2);
+//! assert!(canyons.chasm_cells() > 0);
+//! let (x, y) = canyons.paths[0][10];
+//! assert!(canyons.heights[y][x] < heights[y][x]);
+//! ```
+
+use crate::semantic::TerrainFeature;
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+
+/// Shape of a canyon's cross-section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanyonProfile {
+    /// Straight walls meeting at a sharp bottom.
+    V,
+    /// Rounded bottom with walls steepening toward the rim.
+    #[default]
+    U,
+    /// Sheer walls around a flat floor.
+    Slot,
+}
+
+impl CanyonProfile {
+    /// Depth fraction at `d`, the distance from the centerline over the
+    /// half-width: 1 at the center, 0 at and beyond the rim.
+    pub fn depth(self, d: f64) -> f64 {
+        if d >= 1.0 {
+            return 0.0;
+        }
+        let d = d.max(0.0);
+        match self {
+            CanyonProfile::V => 1.0 - d,
+            CanyonProfile::U => (1.0 - d * d).sqrt(),
+            CanyonProfile::Slot => ((1.0 - d) * 5.0).min(1.0),
+        }
+    }
+}
+
+/// Configuration for [`carve`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanyonConfig {
+    /// Main canyons. Default: 2.
+    pub count: usize,
+    /// Steps per main canyon as a fraction of the larger map side; walks
+    /// also stop when they leave the map. Default: 1.2.
+    pub length: f64,
+    /// Largest heading change per step, in radians. Default: 0.35.
+    pub turn: f64,
+    /// Chance per step of forking a side ravine. Default: 0.05.
+    pub branch_chance: f64,
+    /// Levels of side ravines below the main canyons. Default: 2.
+    pub branch_levels: usize,
+    /// Side ravine length as a fraction of what remains of its parent.
+    /// Default: 0.4.
+    pub branch_length: f64,
+    /// Chance a side ravine bends back to rejoin its parent. Default: 0.5.
+    pub braid: f64,
+    /// Half-width of a main canyon in cells. Side ravines are 0.6 times
+    /// as wide and deep as their parent. Default: 2.5.
+    pub width: f64,
+    /// Depth cut at a main canyon's center, in heightmap units.
+    /// Default: 0.3.
+    pub depth: f64,
+    pub profile: CanyonProfile,
+    /// Cells cut to at least this fraction of their canyon's full depth
+    /// become chasm. Default: 0.75.
+    pub chasm: f64,
+}
+
+impl Default for CanyonConfig {
+    fn default() -> Self {
+        Self {
+            count: 2,
+            length: 1.2,
+            turn: 0.35,
+            branch_chance: 0.05,
+            branch_levels: 2,
+            branch_length: 0.4,
+            braid: 0.5,
+            width: 2.5,
+            depth: 0.3,
+            profile: CanyonProfile::U,
+            chasm: 0.75,
+        }
+    }
+}
+
+impl CanyonConfig {
+    /// Sets `count`.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Sets `branch_chance` and `branch_levels`.
+    pub fn with_branching(mut self, chance: f64, levels: usize) -> Self {
+        self.branch_chance = chance;
+        self.branch_levels = levels;
+        self
+    }
+
+    /// Sets `braid`.
+    pub fn with_braid(mut self, braid: f64) -> Self {
+        self.braid = braid;
+        self
+    }
+
+    /// Sets `width` and `depth`.
+    pub fn with_size(mut self, width: f64, depth: f64) -> Self {
+        self.width = width;
+        self.depth = depth;
+        self
+    }
+
+    /// Sets `profile`.
+    pub fn with_profile(mut self, profile: CanyonProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Sets `chasm`.
+    pub fn with_chasm(mut self, chasm: f64) -> Self {
+        self.chasm = chasm;
+        self
+    }
+}
+
+/// Carved terrain from [`carve`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Canyons {
+    /// Lowered heights, indexed `[y][x]`.
+    pub heights: Vec<Vec<f64>>,
+    /// Impassable canyon cells, indexed `[y][x]`.
+    pub chasm: Vec<Vec<bool>>,
+    /// Centerline cells of every walk, main canyons first, then side
+    /// ravines in the order they forked.
+    pub paths: Vec<Vec<(usize, usize)>>,
+}
+
+impl Canyons {
+    /// Number of chasm cells.
+    pub fn chasm_cells(&self) -> usize {
+        self.chasm.iter().flatten().filter(|&&c| c).count()
+    }
+
+    /// Turns chasm cells of `grid` into walls. Returns how many were floor.
+    pub fn apply(&self, grid: &mut Grid<Tile>) -> usize {
+        let mut walled = 0;
+        for (y, row) in self.chasm.iter().enumerate() {
+            for (x, _) in row.iter().enumerate().filter(|(_, &c)| c) {
+                if grid.get(x as i32, y as i32).is_some_and(|t| t.is_floor()) {
+                    grid.set(x as i32, y as i32, Tile::Wall);
+                    walled += 1;
+                }
+            }
+        }
+        walled
+    }
+
+    /// Chasm cells as [`TerrainFeature::Chasm`], for
+    /// [`SemanticExtractor::extract_terrain`](crate::SemanticExtractor::extract_terrain).
+    pub fn features(&self) -> Vec<Vec<Option<TerrainFeature>>> {
+        self.chasm
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&c| c.then_some(TerrainFeature::Chasm))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// A walk to cut: centerline points with their half-width and depth.
+struct Walk {
+    /// Depth before tapering.
+    depth: f64,
+    points: Vec<(f64, f64)>,
+    headings: Vec<f64>,
+    widths: Vec<f64>,
+    depths: Vec<f64>,
+}
+
+/// Carves canyons into `heights` (rows of equal length, indexed `[y][x]`).
+///
+/// Each main canyon draws from its own stream of `seed`, so adding one
+/// leaves the others in place.
+pub fn carve(heights: &[Vec<f64>], config: &CanyonConfig, seed: u64) -> Canyons {
+    let h = heights.len();
+    let w = heights.first().map_or(0, Vec::len);
+    let mut carved = heights.to_vec();
+    let mut chasm = vec![vec![false; w]; h];
+    if w == 0 || h == 0 {
+        return Canyons {
+            heights: carved,
+            chasm,
+            paths: Vec::new(),
+        };
+    }
+
+    let root = Rng::new(seed);
+    let mut walks = Vec::new();
+    let mut side_walks = Vec::new();
+    for i in 0..config.count {
+        let mut rng = root.stream(i as u64);
+        // Enter from a random edge, bearing roughly across the map.
+        let side = rng.range_usize(0, 4);
+        let along = rng.range_f64(0.15, 0.85);
+        let (start, bearing) = match side {
+            0 => ((along * w as f64, 0.0), PI / 2.0),
+            1 => ((w as f64 - 0.5, along * h as f64), PI),
+            2 => ((along * w as f64, h as f64 - 0.5), -PI / 2.0),
+            _ => ((0.0, along * h as f64), 0.0),
+        };
+        let bearing = bearing + rng.range_f64(-0.5, 0.5);
+        let steps = (config.length * w.max(h) as f64).round() as usize;
+        let main = walk(
+            (start, bearing),
+            steps,
+            None,
+            (config.width, config.depth),
+            (w, h),
+            config,
+            &mut rng,
+        );
+        branch(&main, 1, (w, h), config, &mut rng, &mut side_walks);
+        walks.push(main);
+    }
+    walks.append(&mut side_walks);
+
+    // Deepest cut and its fraction of the cutting walk's depth, per cell.
+    let mut cut = vec![vec![(0.0f64, 0.0f64); w]; h];
+    for walk in &walks {
+        for (i, &(px, py)) in walk.points.iter().enumerate() {
+            let (radius, depth) = (walk.widths[i], walk.depths[i]);
+            let r = radius.ceil() as i64;
+            let (cx, cy) = (px.floor() as i64, py.floor() as i64);
+            for y in (cy - r).max(0)..=(cy + r).min(h as i64 - 1) {
+                for x in (cx - r).max(0)..=(cx + r).min(w as i64 - 1) {
+                    let d = ((x as f64 + 0.5 - px).hypot(y as f64 + 0.5 - py)) / radius;
+                    let fraction = config.profile.depth(d);
+                    let cell = &mut cut[y as usize][x as usize];
+                    cell.0 = cell.0.max(depth * fraction);
+                    cell.1 = cell.1.max(fraction * depth / walk.depth.max(f64::EPSILON));
+                }
+            }
+        }
+    }
+    for y in 0..h {
+        for x in 0..w {
+            let (depth, fraction) = cut[y][x];
+            carved[y][x] -= depth;
+            chasm[y][x] = depth > 0.0 && fraction >= config.chasm;
+        }
+    }
+
+    let paths = walks
+        .iter()
+        .map(|walk| {
+            let mut cells: Vec<(usize, usize)> = Vec::new();
+            for &(x, y) in &walk.points {
+                let cell = (
+                    (x.max(0.0) as usize).min(w - 1),
+                    (y.max(0.0) as usize).min(h - 1),
+                );
+                if cells.last() != Some(&cell) {
+                    cells.push(cell);
+                }
+            }
+            cells
+        })
+        .collect();
+    Canyons {
+        heights: carved,
+        chasm,
+        paths,
+    }
+}
+
+/// Forks side ravines off `parent`, recursing up to `branch_levels`.
+fn branch(
+    parent: &Walk,
+    level: usize,
+    size: (usize, usize),
+    config: &CanyonConfig,
+    rng: &mut Rng,
+    out: &mut Vec<Walk>,
+) {
+    if level > config.branch_levels || config.branch_chance <= 0.0 {
+        return;
+    }
+    let n = parent.points.len();
+    // Keep forks off the very ends, where the parent is tapering.
+    for i in 3..n.saturating_sub(3) {
+        if !rng.chance(config.branch_chance) {
+            continue;
+        }
+        let steps = ((n - i) as f64 * config.branch_length).round() as usize;
+        if steps < 4 {
+            continue;
+        }
+        let side = if rng.chance(0.5) { 1.0 } else { -1.0 };
+        let heading = parent.headings[i] + side * rng.range_f64(0.5, 1.0);
+        let target = rng
+            .chance(config.braid)
+            .then(|| parent.points[(i + steps).min(n - 1)]);
+        let child = walk(
+            (parent.points[i], heading),
+            steps,
+            target,
+            (parent.widths[i] * 0.6, parent.depths[i] * 0.6),
+            size,
+            config,
+            rng,
+        );
+        branch(&child, level + 1, size, config, rng, out);
+        out.push(child);
+    }
+}
+
+/// One directed random walk from `start` along `bearing`. With a
+/// `target`, the walk turns toward it after half its steps and ends on
+/// reaching it; otherwise it tapers out at its tip. Walks end early on
+/// leaving the map.
+fn walk(
+    (start, bearing): ((f64, f64), f64),
+    steps: usize,
+    target: Option<(f64, f64)>,
+    (width, depth): (f64, f64),
+    (w, h): (usize, usize),
+    config: &CanyonConfig,
+    rng: &mut Rng,
+) -> Walk {
+    let mut points = vec![start];
+    let mut headings = vec![bearing];
+    let mut heading = bearing;
+    let mut bearing = bearing;
+    let mut wobble = 1.0;
+    let mut widths = vec![width];
+    let mut joined = false;
+    let mut exited = false;
+    // A rejoining walk may need longer than planned to get back.
+    let limit = if target.is_some() { steps * 2 } else { steps };
+    for step in 1..limit {
+        let (x, y) = points[step - 1];
+        if let Some((tx, ty)) = target {
+            if step >= steps / 2 {
+                bearing = (ty - y).atan2(tx - x);
+            }
+            if (tx - x).hypot(ty - y) < 1.5 {
+                joined = true;
+                break;
+            }
+        }
+        // Drift randomly, but keep pulling back toward the bearing.
+        let pull = (bearing - heading + PI).rem_euclid(TAU) - PI;
+        heading += rng.range_f64(-config.turn, config.turn) + pull * 0.06;
+        let next = (x + heading.cos(), y + heading.sin());
+        if next.0 < 0.0 || next.1 < 0.0 || next.0 >= w as f64 || next.1 >= h as f64 {
+            exited = true;
+            break;
+        }
+        wobble = (wobble + rng.range_f64(-0.08, 0.08)).clamp(0.7, 1.3);
+        points.push(next);
+        headings.push(heading);
+        widths.push(width * wobble);
+    }
+
+    // Taper free tips over a few widths.
+    let n = points.len();
+    let taper_steps = (width * 4.0).max(1.0);
+    let depths = (0..n)
+        .map(|i| {
+            let remaining = (n - 1 - i) as f64;
+            let taper = if joined || exited {
+                1.0
+            } else {
+                (remaining / taper_steps).min(1.0)
+            };
+            depth * taper.max(0.1)
+        })
+        .collect::<Vec<_>>();
+    for (i, width) in widths.iter_mut().enumerate() {
+        *width *= (depths[i] / depth.max(f64::EPSILON)).sqrt().max(0.3);
+    }
+    Walk {
+        depth,
+        points,
+        headings,
+        widths,
+        depths,
+    }
+}
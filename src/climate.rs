@@ -0,0 +1,243 @@
+//! Climate layers derived from a heightmap.
+//!
+//! [`simulate`] turns a heightmap (rows of `f64` in `[0, 1]`, as produced by
+//! [`DiamondSquare::heightmap`](crate::algorithms::DiamondSquare::heightmap))
+//! into per-cell temperature, moisture, and wind:
+//!
+//! - **Temperature** falls off with latitude (cosine from the equator) and
+//!   with elevation above sea level (lapse rate).
+//! - **Moisture** comes from air parcels carried by the prevailing wind. They
+//!   pick up water over the sea and rain it out over land, heavily on
+//!   windward slopes, so leeward sides of ranges end up in a rain shadow.
+//! - **Wind** is the prevailing direction, sped up over windward slopes and
+//!   ridges and slowed in the lee of high ground.
+//!
+//! Output grids are rows like the input, so they feed straight into
+//! [`Heatmap::from_rows`](crate::render::Heatmap::from_rows) or the heightmap
+//! exporters.
+//!
+//! ```rust
+//! use terrain_forge::algorithms::DiamondSquare;
+//! use terrain_forge::climate::{self, ClimateConfig};
+//!
+//! let heights = DiamondSquare::default().heightmap(64, 48, 3);
+//! let climate = climate::simulate(&heights, &ClimateConfig::default());
+//! assert_eq!(climate.moisture.len(), 48);
+//! assert!(climate.temperature(32, 24) > climate.temperature(32, 0) - 40.0);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for [`simulate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClimateConfig {
+    /// Heights below this are water. Default: 0.4.
+    pub sea_level: f64,
+    /// Latitude in degrees of the top and bottom rows. Default: `(60.0, -60.0)`.
+    pub latitude: (f64, f64),
+    /// Sea-level temperature at the equator, °C. Default: 30.0.
+    pub equator_temperature: f64,
+    /// Sea-level temperature at the poles, °C. Default: -25.0.
+    pub pole_temperature: f64,
+    /// Cooling per unit of height above sea level, °C. Default: 40.0.
+    pub lapse_rate: f64,
+    /// Prevailing wind direction `(dx, dy)`; need not be normalized.
+    /// Default: `(1.0, 0.0)` (blowing east).
+    pub wind: (f64, f64),
+    /// Humidity of air entering from the map edge, `0..=1`. Default: 0.6.
+    pub inflow_humidity: f64,
+    /// Humidity picked up per water cell crossed. Default: 0.1.
+    pub evaporation: f64,
+    /// Fraction of carried humidity rained out per flat land cell. Default: 0.02.
+    pub rainfall: f64,
+    /// Extra rain-out per unit of uphill climb along the wind. Default: 8.0.
+    pub orographic: f64,
+}
+
+impl Default for ClimateConfig {
+    fn default() -> Self {
+        Self {
+            sea_level: 0.4,
+            latitude: (60.0, -60.0),
+            equator_temperature: 30.0,
+            pole_temperature: -25.0,
+            lapse_rate: 40.0,
+            wind: (1.0, 0.0),
+            inflow_humidity: 0.6,
+            evaporation: 0.1,
+            rainfall: 0.02,
+            orographic: 8.0,
+        }
+    }
+}
+
+impl ClimateConfig {
+    /// Sets `sea_level`.
+    pub fn with_sea_level(mut self, sea_level: f64) -> Self {
+        self.sea_level = sea_level;
+        self
+    }
+
+    /// Sets the latitude of the top and bottom rows.
+    pub fn with_latitude(mut self, top: f64, bottom: f64) -> Self {
+        self.latitude = (top, bottom);
+        self
+    }
+
+    /// Sets the prevailing wind direction.
+    pub fn with_wind(mut self, dx: f64, dy: f64) -> Self {
+        self.wind = (dx, dy);
+        self
+    }
+}
+
+/// Per-cell climate grids, indexed `[y][x]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Climate {
+    /// Temperature, °C.
+    pub temperature: Vec<Vec<f64>>,
+    /// Moisture in `[0, 1]`: 1 over water, otherwise rainfall relative to
+    /// the wettest land cell.
+    pub moisture: Vec<Vec<f64>>,
+    /// Wind vector; its length is speed relative to the prevailing wind.
+    pub wind: Vec<Vec<(f64, f64)>>,
+}
+
+impl Climate {
+    pub fn width(&self) -> usize {
+        self.temperature.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.temperature.len()
+    }
+
+    pub fn temperature(&self, x: usize, y: usize) -> f64 {
+        self.temperature[y][x]
+    }
+
+    pub fn moisture(&self, x: usize, y: usize) -> f64 {
+        self.moisture[y][x]
+    }
+
+    pub fn wind(&self, x: usize, y: usize) -> (f64, f64) {
+        self.wind[y][x]
+    }
+}
+
+/// Derives climate layers from `heights`.
+pub fn simulate(heights: &[Vec<f64>], config: &ClimateConfig) -> Climate {
+    let h = heights.len();
+    let w = heights.first().map_or(0, Vec::len);
+    let height = |x: usize, y: usize| heights[y].get(x).copied().unwrap_or(0.0);
+
+    let mut temperature = vec![vec![0.0; w]; h];
+    for (y, row) in temperature.iter_mut().enumerate() {
+        let t = if h > 1 {
+            y as f64 / (h - 1) as f64
+        } else {
+            0.5
+        };
+        let (top, bottom) = config.latitude;
+        let latitude = (top + (bottom - top) * t).to_radians();
+        let base = config.pole_temperature
+            + (config.equator_temperature - config.pole_temperature) * latitude.cos().max(0.0);
+        for (x, cell) in row.iter_mut().enumerate() {
+            let elevation = (height(x, y) - config.sea_level).max(0.0);
+            *cell = base - config.lapse_rate * elevation;
+        }
+    }
+
+    let (dx, dy) = config.wind;
+    let scale = dx.abs().max(dy.abs());
+    let (dx, dy) = if scale > 0.0 {
+        (dx / scale, dy / scale)
+    } else {
+        (1.0, 0.0)
+    };
+    // Each cell draws air from its upwind axis neighbor and diagonal
+    // neighbor, weighted by the wind's off-axis component. Both have a lower
+    // projection onto the wind, so visiting cells in projection order
+    // always finds upwind values ready.
+    let (sx, sy) = (dx.signum() as i64, dy.signum() as i64);
+    let (axis, diagonal, blend) = if dx.abs() >= dy.abs() {
+        ((sx, 0), (sx, sy), dy.abs())
+    } else {
+        ((0, sy), (sx, sy), dx.abs())
+    };
+    let mut order: Vec<(usize, usize)> = (0..h).flat_map(|y| (0..w).map(move |x| (x, y))).collect();
+    order.sort_by(|a, b| {
+        let pa = a.0 as f64 * dx + a.1 as f64 * dy;
+        let pb = b.0 as f64 * dx + b.1 as f64 * dy;
+        pa.total_cmp(&pb)
+    });
+
+    let mut humidity = vec![vec![0.0; w]; h];
+    let mut rain = vec![vec![0.0; w]; h];
+    // Highest recent terrain upwind, decaying with distance.
+    let mut ridge = vec![vec![0.0; w]; h];
+    let mut wind = vec![vec![(0.0, 0.0); w]; h];
+    let upwind = |x: usize, y: usize, fallback: f64, value: &dyn Fn(usize, usize) -> f64| {
+        let at = |(ox, oy): (i64, i64)| {
+            let (ux, uy) = (x as i64 - ox, y as i64 - oy);
+            if ux < 0 || uy < 0 || ux >= w as i64 || uy >= h as i64 {
+                fallback
+            } else {
+                value(ux as usize, uy as usize)
+            }
+        };
+        at(axis) * (1.0 - blend) + at(diagonal) * blend
+    };
+
+    for &(x, y) in &order {
+        let here = height(x, y);
+        let before = upwind(x, y, config.inflow_humidity, &|ux, uy| humidity[uy][ux]);
+        let behind = upwind(x, y, here, &|ux, uy| ridge[uy][ux]);
+        let ground_behind = upwind(x, y, here, &height).max(config.sea_level);
+
+        if here < config.sea_level {
+            humidity[y][x] = (before + config.evaporation).min(1.0);
+        } else {
+            let climb = (here - ground_behind).max(0.0);
+            let fraction = (config.rainfall + config.orographic * climb).min(1.0);
+            rain[y][x] = before * fraction;
+            humidity[y][x] = before - rain[y][x];
+        }
+
+        let water = here.max(config.sea_level);
+        ridge[y][x] = water.max(behind - 0.01);
+        let shelter = (behind - water).max(0.0);
+        let climb = (water - ground_behind).max(0.0);
+        let speed = (1.0 + 4.0 * climb - 4.0 * shelter).clamp(0.1, 2.0);
+        let length = (dx * dx + dy * dy).sqrt();
+        wind[y][x] = (dx / length * speed, dy / length * speed);
+    }
+
+    let wettest = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .filter(|&(x, y)| height(x, y) >= config.sea_level)
+        .map(|(x, y)| rain[y][x])
+        .fold(0.0f64, f64::max);
+    let moisture = (0..h)
+        .map(|y| {
+            (0..w)
+                .map(|x| {
+                    if height(x, y) < config.sea_level {
+                        1.0
+                    } else if wettest > 0.0 {
+                        rain[y][x] / wettest
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Climate {
+        temperature,
+        moisture,
+        wind,
+    }
+}
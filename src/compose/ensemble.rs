@@ -0,0 +1,102 @@
+//! Multi-seed ensemble blending
+
+use crate::constraints::{ConstraintContext, ConstraintSet};
+use crate::parallel;
+use crate::{Algorithm, Grid, Rng, Tile};
+
+/// Runs an algorithm with several derived seeds and keeps a cell as floor
+/// where enough members agree.
+///
+/// Each member votes for its floor cells with a weight of 1, or with its
+/// [`ConstraintReport::score`](crate::constraints::ConstraintReport::score)
+/// when constraints are set, so members that fail them count for less. A
+/// cell becomes floor when its share of the total weight reaches the
+/// threshold. Averaging smooths out clumps and specks that only some seeds
+/// produce; the result is not guaranteed to be connected.
+///
+/// ```rust
+/// use terrain_forge::algorithms::CellularAutomata;
+/// use terrain_forge::compose::Ensemble;
+/// use terrain_forge::{Algorithm, Grid};
+///
+/// let ensemble = Ensemble::new(CellularAutomata::default(), 5).with_threshold(0.6);
+/// let mut grid = Grid::new(60, 40);
+/// ensemble.generate(&mut grid, 3);
+/// assert!(grid.count(|t| t.is_floor()) > 0);
+/// ```
+pub struct Ensemble {
+    algorithm: Box<dyn Algorithm + Send + Sync>,
+    members: usize,
+    threshold: f64,
+    constraints: Option<ConstraintSet>,
+}
+
+impl Ensemble {
+    /// Blends `members` runs of `algorithm` by majority vote.
+    pub fn new<A: Algorithm + 'static>(algorithm: A, members: usize) -> Self {
+        Self {
+            algorithm: Box::new(algorithm),
+            members: members.max(1),
+            threshold: 0.5,
+            constraints: None,
+        }
+    }
+
+    /// Sets the share of votes a cell needs to become floor. Default: 0.5.
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Weights each member's vote by its score against `constraints`.
+    pub fn with_constraints(mut self, constraints: ConstraintSet) -> Self {
+        self.constraints = Some(constraints);
+        self
+    }
+
+    /// Generates the member grids and their vote weights, in member order.
+    pub fn members(&self, width: usize, height: usize, seed: u64) -> Vec<(Grid<Tile>, f64)> {
+        let streams = Rng::new(seed);
+        parallel::map(self.members, |i| {
+            let mut grid = Grid::new(width, height);
+            self.algorithm
+                .generate(&mut grid, streams.stream(i as u64).next_u64());
+            let weight = match &self.constraints {
+                Some(set) => set.evaluate(&ConstraintContext::new(&grid)).score() as f64,
+                None => 1.0,
+            };
+            (grid, weight)
+        })
+    }
+}
+
+impl Algorithm for Ensemble {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        let (w, h) = (grid.width(), grid.height());
+        let mut members = self.members(w, h, seed);
+        // If every member scored zero, fall back to an even vote.
+        if members.iter().all(|(_, weight)| *weight <= 0.0) {
+            members.iter_mut().for_each(|(_, weight)| *weight = 1.0);
+        }
+        let total: f64 = members.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        for y in 0..h {
+            for x in 0..w {
+                let votes: f64 = members
+                    .iter()
+                    .filter(|(member, _)| member[(x, y)].is_floor())
+                    .map(|(_, weight)| weight.max(0.0))
+                    .sum();
+                let floor = votes > 0.0 && votes >= self.threshold * total;
+                grid.set(
+                    x as i32,
+                    y as i32,
+                    if floor { Tile::Floor } else { Tile::Wall },
+                );
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "Ensemble"
+    }
+}
@@ -1,7 +1,8 @@
 //! Layered generation with blend modes
 
 use crate::grid::Cell;
-use crate::{Algorithm, Grid};
+use crate::parallel;
+use crate::{Algorithm, Grid, Rng};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -18,11 +19,93 @@ pub enum BlendMode {
     Mask,
 }
 
+struct Layer<C: Cell> {
+    name: String,
+    algorithm: Box<dyn Algorithm<C> + Send + Sync>,
+    mode: BlendMode,
+    seed: Option<u64>,
+    enabled: bool,
+}
+
+/// One layer's part in a [`LayeredGenerator::generate_history`] run.
+#[derive(Debug, Clone)]
+pub struct LayerRecord<C: Cell = crate::Tile> {
+    pub name: String,
+    pub mode: BlendMode,
+    pub seed: u64,
+    /// Passability of what the layer's algorithm generated on its own
+    /// (for `Replace`, the grid it generated into).
+    pub output: Grid<crate::Tile>,
+    /// The blended grid after this layer.
+    pub after: Grid<C>,
+}
+
+/// Per-layer grids from [`LayeredGenerator::generate_history`], in layer
+/// order; disabled layers are skipped.
+#[derive(Debug, Clone)]
+pub struct LayerHistory<C: Cell = crate::Tile> {
+    /// Passability of the grid before the first layer.
+    pub start: Grid<crate::Tile>,
+    pub layers: Vec<LayerRecord<C>>,
+}
+
+impl<C: Cell> LayerHistory<C> {
+    /// The record for the layer called `name`.
+    pub fn layer(&self, name: &str) -> Option<&LayerRecord<C>> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    /// Name of the last layer that changed the passability of `(x, y)`, or
+    /// `None` if no layer did (the cell kept its starting state).
+    pub fn source(&self, x: usize, y: usize) -> Option<&str> {
+        let mut source = None;
+        let mut before = self.start.get(x as i32, y as i32)?.is_floor();
+        for layer in &self.layers {
+            let now = layer.after[(x, y)].is_passable();
+            if now != before {
+                source = Some(layer.name.as_str());
+            }
+            before = now;
+        }
+        source
+    }
+}
+
 /// Layered generator that blends multiple algorithms.
 ///
 /// Generic over `C: Cell`, so it works with both [`Tile`](crate::Tile) and custom cell types.
+///
+/// Layers are named `layer0`, `layer1`, ... unless renamed with
+/// [`named`](Self::named), get a seed derived from the generation seed and
+/// their index unless given one with [`seeded`](Self::seeded), and can be
+/// switched off by name without shifting the other layers' seeds.
+/// [`generate_history`](Self::generate_history) keeps every layer's grid.
+///
+/// ```rust
+/// use terrain_forge::algorithms::{Bsp, DrunkardWalk};
+/// use terrain_forge::compose::LayeredGenerator;
+/// use terrain_forge::Grid;
+///
+/// let mut layered = LayeredGenerator::new()
+///     .base(Bsp::default())
+///     .named("rooms")
+///     .union(DrunkardWalk::default())
+///     .named("tunnels")
+///     .seeded(7);
+/// let mut grid = Grid::new(60, 40);
+/// let history = layered.generate_history(&mut grid, 1);
+/// assert_eq!(history.layers[1].seed, 7);
+/// if let Some((x, y, _)) = grid.iter().find(|(x, y, t)| {
+///     t.is_floor() && !history.layer("rooms").unwrap().after[(*x, *y)].is_floor()
+/// }) {
+///     assert_eq!(history.source(x, y), Some("tunnels"));
+/// }
+///
+/// layered.set_enabled("tunnels", false);
+/// assert_eq!(layered.generate_history(&mut grid, 1).layers.len(), 1);
+/// ```
 pub struct LayeredGenerator<C: Cell = crate::Tile> {
-    layers: Vec<(Box<dyn Algorithm<C> + Send + Sync>, BlendMode)>,
+    layers: Vec<Layer<C>>,
 }
 
 impl<C: Cell> LayeredGenerator<C> {
@@ -32,27 +115,23 @@ impl<C: Cell> LayeredGenerator<C> {
     }
 
     /// Sets the base layer (replaces).
-    pub fn base<A: Algorithm<C> + Send + Sync + 'static>(mut self, algo: A) -> Self {
-        self.layers.push((Box::new(algo), BlendMode::Replace));
-        self
+    pub fn base<A: Algorithm<C> + Send + Sync + 'static>(self, algo: A) -> Self {
+        self.add(algo, BlendMode::Replace)
     }
 
     /// Adds a union layer.
-    pub fn union<A: Algorithm<C> + Send + Sync + 'static>(mut self, algo: A) -> Self {
-        self.layers.push((Box::new(algo), BlendMode::Union));
-        self
+    pub fn union<A: Algorithm<C> + Send + Sync + 'static>(self, algo: A) -> Self {
+        self.add(algo, BlendMode::Union)
     }
 
     /// Adds an intersection layer.
-    pub fn intersect<A: Algorithm<C> + Send + Sync + 'static>(mut self, algo: A) -> Self {
-        self.layers.push((Box::new(algo), BlendMode::Intersect));
-        self
+    pub fn intersect<A: Algorithm<C> + Send + Sync + 'static>(self, algo: A) -> Self {
+        self.add(algo, BlendMode::Intersect)
     }
 
     /// Adds a difference layer.
-    pub fn difference<A: Algorithm<C> + Send + Sync + 'static>(mut self, algo: A) -> Self {
-        self.layers.push((Box::new(algo), BlendMode::Difference));
-        self
+    pub fn difference<A: Algorithm<C> + Send + Sync + 'static>(self, algo: A) -> Self {
+        self.add(algo, BlendMode::Difference)
     }
 
     /// Adds a layer with the specified blend mode.
@@ -61,9 +140,48 @@ impl<C: Cell> LayeredGenerator<C> {
         algo: A,
         mode: BlendMode,
     ) -> Self {
-        self.layers.push((Box::new(algo), mode));
+        self.layers.push(Layer {
+            name: format!("layer{}", self.layers.len()),
+            algorithm: Box::new(algo),
+            mode,
+            seed: None,
+            enabled: true,
+        });
+        self
+    }
+
+    /// Names the most recently added layer.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        if let Some(layer) = self.layers.last_mut() {
+            layer.name = name.into();
+        }
+        self
+    }
+
+    /// Gives the most recently added layer a fixed seed.
+    pub fn seeded(mut self, seed: u64) -> Self {
+        if let Some(layer) = self.layers.last_mut() {
+            layer.seed = Some(seed);
+        }
         self
     }
+
+    /// Switches the layer called `name` on or off; returns `false` if there
+    /// is no such layer.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let found = self.layers.iter_mut().find(|l| l.name == name);
+        found.map(|layer| layer.enabled = enabled).is_some()
+    }
+
+    /// Whether the layer called `name` exists and is enabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.layers.iter().any(|l| l.name == name && l.enabled)
+    }
+
+    /// Layer names in order.
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers.iter().map(|l| l.name.as_str()).collect()
+    }
 }
 
 impl<C: Cell> Default for LayeredGenerator<C> {
@@ -72,62 +190,87 @@ impl<C: Cell> Default for LayeredGenerator<C> {
     }
 }
 
-impl<C: Cell + 'static> Algorithm<C> for LayeredGenerator<C> {
-    fn generate(&self, grid: &mut Grid<C>, seed: u64) {
-        for (i, (algo, mode)) in self.layers.iter().enumerate() {
-            let layer_seed = seed.wrapping_add(i as u64 * 1000);
+impl<C: Cell + 'static> LayeredGenerator<C> {
+    /// Like [`generate`](Algorithm::generate), but returns each enabled
+    /// layer's own output and the blended grid after it.
+    pub fn generate_history(&self, grid: &mut Grid<C>, seed: u64) -> LayerHistory<C> {
+        let start = grid.to_tiles();
+        let mut layers = Vec::new();
+        self.run(grid, seed, |layer, seed, mask, after| {
+            layers.push(LayerRecord {
+                name: layer.name.clone(),
+                mode: layer.mode,
+                seed,
+                output: mask.cloned().unwrap_or_else(|| after.to_tiles()),
+                after: after.clone(),
+            });
+        });
+        LayerHistory { start, layers }
+    }
 
-            match mode {
-                BlendMode::Replace => {
-                    algo.generate(grid, layer_seed);
-                }
-                BlendMode::Union => {
-                    let mut layer = Grid::new(grid.width(), grid.height());
-                    algo.generate(&mut layer, layer_seed);
-                    for y in 0..grid.height() {
-                        for x in 0..grid.width() {
-                            if layer[(x, y)].is_passable() {
-                                grid[(x, y)].set_passable();
-                            }
-                        }
-                    }
-                }
-                BlendMode::Intersect => {
-                    let mut layer = Grid::new(grid.width(), grid.height());
-                    algo.generate(&mut layer, layer_seed);
-                    for y in 0..grid.height() {
-                        for x in 0..grid.width() {
-                            if !layer[(x, y)].is_passable() {
-                                grid.set(x as i32, y as i32, C::default());
-                            }
-                        }
-                    }
-                }
-                BlendMode::Difference => {
-                    let mut layer = Grid::new(grid.width(), grid.height());
-                    algo.generate(&mut layer, layer_seed);
-                    for y in 0..grid.height() {
-                        for x in 0..grid.width() {
-                            if layer[(x, y)].is_passable() {
-                                grid.set(x as i32, y as i32, C::default());
-                            }
+    fn run(
+        &self,
+        grid: &mut Grid<C>,
+        seed: u64,
+        mut record: impl FnMut(&Layer<C>, u64, Option<&Grid<crate::Tile>>, &Grid<C>),
+    ) {
+        let streams = Rng::new(seed);
+        let seeds: Vec<u64> = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                layer
+                    .seed
+                    .unwrap_or_else(|| streams.stream(i as u64).next_u64())
+            })
+            .collect();
+        let (w, h) = (grid.width(), grid.height());
+
+        // Blended layers only depend on their seed, so generate them up front.
+        let masks = parallel::map(self.layers.len(), |i| {
+            let layer = &self.layers[i];
+            if !layer.enabled || matches!(layer.mode, BlendMode::Replace) {
+                return None;
+            }
+            let mut output = Grid::<C>::new(w, h);
+            layer.algorithm.generate(&mut output, seeds[i]);
+            Some(output.to_tiles())
+        });
+
+        for (i, (layer, mask)) in self.layers.iter().zip(masks).enumerate() {
+            if !layer.enabled {
+                continue;
+            }
+            let Some(mask) = mask else {
+                layer.algorithm.generate(grid, seeds[i]);
+                record(layer, seeds[i], None, grid);
+                continue;
+            };
+            for y in 0..h {
+                for x in 0..w {
+                    let floor = mask[(x, y)].is_floor();
+                    match layer.mode {
+                        BlendMode::Union if floor => grid[(x, y)].set_passable(),
+                        BlendMode::Intersect | BlendMode::Mask if !floor => {
+                            grid.set(x as i32, y as i32, C::default());
                         }
-                    }
-                }
-                BlendMode::Mask => {
-                    let mut mask = Grid::new(grid.width(), grid.height());
-                    algo.generate(&mut mask, layer_seed);
-                    for y in 0..grid.height() {
-                        for x in 0..grid.width() {
-                            if !mask[(x, y)].is_passable() {
-                                grid.set(x as i32, y as i32, C::default());
-                            }
+                        BlendMode::Difference if floor => {
+                            grid.set(x as i32, y as i32, C::default());
                         }
+                        _ => {}
                     }
                 }
             }
+            record(layer, seeds[i], Some(&mask), grid);
         }
     }
+}
+
+impl<C: Cell + 'static> Algorithm<C> for LayeredGenerator<C> {
+    fn generate(&self, grid: &mut Grid<C>, seed: u64) {
+        self.run(grid, seed, |_, _, _, _| {});
+    }
 
     fn name(&self) -> &'static str {
         "LayeredGenerator"
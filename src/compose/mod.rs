@@ -1,9 +1,18 @@
 //! Composition system for chaining and layering algorithms.
 //!
-//! Use `Pipeline` for sequential algorithm chains and `LayeredGenerator` for blends.
+//! Use `Pipeline` for sequential algorithm chains, `LayeredGenerator` for blends,
+//! `Ensemble` to average one algorithm over several seeds,
+//! `MultiFloorGenerator` for stacked floors linked by stairs, and
+//! `Progressive` for coarse-to-fine generation over zoom levels.
 
+mod ensemble;
 mod layer;
+mod multi_floor;
 mod pipeline;
+mod progressive;
 
-pub use layer::{BlendMode, LayeredGenerator};
+pub use ensemble::Ensemble;
+pub use layer::{BlendMode, LayerHistory, LayerRecord, LayeredGenerator};
+pub use multi_floor::{Floor, MultiFloorConfig, MultiFloorGenerator, MultiFloorMap};
 pub use pipeline::Pipeline;
+pub use progressive::Progressive;
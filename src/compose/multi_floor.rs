@@ -0,0 +1,332 @@
+//! Multi-floor dungeons linked by stairs
+
+use crate::ops::{self, OpError, OpResult, Params};
+use crate::semantic::{Marker, VerticalConnectivity};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Configuration for [`MultiFloorGenerator`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultiFloorConfig {
+    /// Number of floors. Default: 3.
+    pub floors: usize,
+    /// Algorithm per depth; floors past the end reuse the last entry. Default: `["bsp"]`.
+    pub algorithms: Vec<String>,
+    /// Params for each entry of `algorithms` (missing entries use defaults).
+    pub params: Vec<Option<Params>>,
+    /// Stairs between each pair of adjacent floors. Default: 1.
+    pub stairs_per_floor: usize,
+    /// Floor cells required around a stair on both floors. Default: 1.
+    pub clearance: usize,
+    /// Minimum Chebyshev distance between stairs on the same floor. Default: 6.
+    pub min_stair_distance: usize,
+}
+
+impl Default for MultiFloorConfig {
+    fn default() -> Self {
+        Self {
+            floors: 3,
+            algorithms: vec!["bsp".to_string()],
+            params: Vec::new(),
+            stairs_per_floor: 1,
+            clearance: 1,
+            min_stair_distance: 6,
+        }
+    }
+}
+
+impl MultiFloorConfig {
+    /// Sets `floors`.
+    pub fn with_floors(mut self, floors: usize) -> Self {
+        self.floors = floors;
+        self
+    }
+
+    /// Sets the per-depth algorithm names.
+    pub fn with_algorithms<S: Into<String>>(
+        mut self,
+        algorithms: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.algorithms = algorithms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets `stairs_per_floor`.
+    pub fn with_stairs(mut self, stairs_per_floor: usize) -> Self {
+        self.stairs_per_floor = stairs_per_floor;
+        self
+    }
+
+    /// Sets `clearance`.
+    pub fn with_clearance(mut self, clearance: usize) -> Self {
+        self.clearance = clearance;
+        self
+    }
+
+    /// Sets `min_stair_distance`.
+    pub fn with_min_stair_distance(mut self, min_stair_distance: usize) -> Self {
+        self.min_stair_distance = min_stair_distance;
+        self
+    }
+}
+
+/// One generated floor.
+#[derive(Debug, Clone)]
+pub struct Floor {
+    /// Depth, 0 at the top.
+    pub depth: usize,
+    /// Algorithm that generated the floor.
+    pub algorithm: String,
+    pub grid: Grid<Tile>,
+    /// Stairs leading to `depth - 1`.
+    pub stairs_up: Vec<(u32, u32)>,
+    /// Stairs leading to `depth + 1`.
+    pub stairs_down: Vec<(u32, u32)>,
+}
+
+impl Floor {
+    /// `stairs_up` / `stairs_down` markers for semantic layers.
+    pub fn stair_markers(&self) -> Vec<Marker> {
+        let up = self
+            .stairs_up
+            .iter()
+            .map(|&(x, y)| Marker::with_tag(x, y, "stairs_up".to_string()));
+        let down = self
+            .stairs_down
+            .iter()
+            .map(|&(x, y)| Marker::with_tag(x, y, "stairs_down".to_string()));
+        up.chain(down).collect()
+    }
+}
+
+/// Floors generated by [`MultiFloorGenerator`].
+#[derive(Debug, Clone)]
+pub struct MultiFloorMap {
+    pub floors: Vec<Floor>,
+    /// Stair candidates and placements; `floor_accessibility` lists the
+    /// floors reachable from each floor through the placed stairs.
+    pub vertical: VerticalConnectivity,
+}
+
+impl MultiFloorMap {
+    /// Whether every floor is a single walkable region and each pair of
+    /// adjacent floors shares at least one stair.
+    pub fn is_connected(&self) -> bool {
+        self.floors
+            .iter()
+            .all(|f| f.grid.flood_regions().len() == 1)
+            && self
+                .floors
+                .windows(2)
+                .all(|pair| !pair[0].stairs_down.is_empty() && !pair[1].stairs_up.is_empty())
+    }
+}
+
+/// Generates `floors` stacked maps and links adjacent floors with stairs.
+///
+/// Each floor keeps only its largest region so every stair is reachable.
+/// Stairs sit where both floors are open (see
+/// [`VerticalConnectivity::analyze_stair_candidates`]); when no such cell
+/// exists, a short 4-connected corridor is carved on the lower floor so the
+/// link is always made.
+///
+/// ```rust
+/// use terrain_forge::compose::{MultiFloorConfig, MultiFloorGenerator};
+///
+/// let config = MultiFloorConfig::default().with_algorithms(["bsp", "cellular"]);
+/// let map = MultiFloorGenerator::new(config).generate(60, 40, 7).unwrap();
+/// assert_eq!(map.floors.len(), 3);
+/// assert!(map.is_connected());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultiFloorGenerator {
+    config: MultiFloorConfig,
+}
+
+impl MultiFloorGenerator {
+    pub fn new(config: MultiFloorConfig) -> Self {
+        Self { config }
+    }
+
+    /// Generates all floors; fails on unknown algorithm names or bad params,
+    /// or when a floor has no open cell to put a stair on.
+    pub fn generate(&self, width: usize, height: usize, seed: u64) -> OpResult<MultiFloorMap> {
+        let cfg = &self.config;
+        let streams = Rng::new(seed);
+        let mut floors = Vec::with_capacity(cfg.floors);
+        for depth in 0..cfg.floors {
+            let index = depth.min(cfg.algorithms.len().saturating_sub(1));
+            let name = cfg.algorithms.get(index).map_or("bsp", String::as_str);
+            let params = cfg.params.get(index).and_then(Option::as_ref);
+            let algorithm = ops::build_algorithm(name, params)?;
+
+            let mut grid = Grid::new(width, height);
+            algorithm.generate(&mut grid, streams.stream(depth as u64).next_u64());
+            keep_largest(&mut grid);
+            floors.push(Floor {
+                depth,
+                algorithm: name.to_string(),
+                grid,
+                stairs_up: Vec::new(),
+                stairs_down: Vec::new(),
+            });
+        }
+
+        let mut vertical = VerticalConnectivity::new();
+        let grids: Vec<Grid<Tile>> = floors.iter().map(|f| f.grid.clone()).collect();
+        vertical.analyze_stair_candidates(&grids, cfg.clearance);
+        let mut by_pair: HashMap<usize, Vec<(u32, u32)>> = HashMap::new();
+        for &(x, y, from, _) in &vertical.stair_candidates {
+            by_pair.entry(from as usize).or_default().push((x, y));
+        }
+
+        let mut rng = streams.stream(u64::MAX);
+        for upper in 0..cfg.floors.saturating_sub(1) {
+            let lower = upper + 1;
+            let mut candidates = by_pair.remove(&upper).unwrap_or_default();
+            if candidates.is_empty() {
+                // Clearance may be too strict; any shared floor cell works.
+                candidates = shared_floor(&floors[upper].grid, &floors[lower].grid);
+            }
+            rng.shuffle(&mut candidates);
+
+            let mut placed = 0;
+            for (x, y) in candidates {
+                if placed == cfg.stairs_per_floor.max(1) {
+                    break;
+                }
+                if self.crowded(&floors[upper], (x, y)) || self.crowded(&floors[lower], (x, y)) {
+                    continue;
+                }
+                self.link(&mut floors, &mut vertical, upper, (x, y));
+                placed += 1;
+            }
+
+            if placed == 0 {
+                let (above, below) = floors.split_at_mut(lower);
+                let (x, y) = carve_link(&above[upper].grid, &mut below[0].grid, &mut rng)
+                    .ok_or_else(|| {
+                        OpError::new(format!("floor {} has no open cell for a stair down", upper))
+                    })?;
+                self.link(&mut floors, &mut vertical, upper, (x, y));
+            }
+        }
+
+        vertical.floor_accessibility = accessibility(cfg.floors, &vertical.stairs);
+        Ok(MultiFloorMap { floors, vertical })
+    }
+
+    fn crowded(&self, floor: &Floor, (x, y): (u32, u32)) -> bool {
+        let min = self.config.min_stair_distance as u32;
+        floor
+            .stairs_up
+            .iter()
+            .chain(&floor.stairs_down)
+            .any(|&(sx, sy)| sx.abs_diff(x).max(sy.abs_diff(y)) < min.max(1))
+    }
+
+    fn link(
+        &self,
+        floors: &mut [Floor],
+        vertical: &mut VerticalConnectivity,
+        upper: usize,
+        (x, y): (u32, u32),
+    ) {
+        floors[upper].stairs_down.push((x, y));
+        floors[upper + 1].stairs_up.push((x, y));
+        vertical.stairs.push((x, y, upper as u32, upper as u32 + 1));
+    }
+}
+
+/// Walls off every region but the largest.
+fn keep_largest(grid: &mut Grid<Tile>) {
+    let mut regions = grid.flood_regions();
+    let Some(largest) = (0..regions.len()).max_by_key(|&i| regions[i].len()) else {
+        return;
+    };
+    regions.swap_remove(largest);
+    for (x, y) in regions.into_iter().flatten() {
+        grid.set(x as i32, y as i32, Tile::Wall);
+    }
+}
+
+/// Floors reachable from each floor by walking the stair graph. Every floor
+/// is a single region, so any stair on it is reachable from anywhere on it.
+fn accessibility(floors: usize, stairs: &[(u32, u32, u32, u32)]) -> BTreeMap<u32, Vec<u32>> {
+    let mut adjacent = vec![Vec::new(); floors];
+    for &(_, _, from, to) in stairs {
+        adjacent[from as usize].push(to as usize);
+        adjacent[to as usize].push(from as usize);
+    }
+    (0..floors)
+        .map(|start| {
+            let mut seen = vec![false; floors];
+            seen[start] = true;
+            let mut queue = VecDeque::from([start]);
+            while let Some(depth) = queue.pop_front() {
+                for &next in &adjacent[depth] {
+                    if !seen[next] {
+                        seen[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+            let reachable = (0..floors).filter(|&d| seen[d]).map(|d| d as u32);
+            (start as u32, reachable.collect())
+        })
+        .collect()
+}
+
+fn shared_floor(a: &Grid<Tile>, b: &Grid<Tile>) -> Vec<(u32, u32)> {
+    a.iter()
+        .filter(|&(x, y, t)| t.is_floor() && b[(x, y)].is_floor())
+        .map(|(x, y, _)| (x as u32, y as u32))
+        .collect()
+}
+
+/// Picks a floor cell of `upper` and carves `lower` from it along the
+/// shortest 4-connected path to the nearest floor cell of `lower`. Returns
+/// the stair position, or `None` if `upper` has no floor.
+fn carve_link(upper: &Grid<Tile>, lower: &mut Grid<Tile>, rng: &mut Rng) -> Option<(u32, u32)> {
+    let cells: Vec<(usize, usize)> = upper
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    let &start = rng.pick(&cells)?;
+
+    // Breadth-first search for the nearest open cell on the lower floor,
+    // remembering parents so the carved path is 4-connected.
+    let mut parent = BTreeMap::from([(start, start)]);
+    let mut queue = VecDeque::from([start]);
+    let mut cell = start;
+    while let Some(current) = queue.pop_front() {
+        cell = current;
+        if lower[current].is_floor() {
+            break;
+        }
+        for (nx, ny) in lower.neighbors_4(current.0, current.1) {
+            if let Entry::Vacant(e) = parent.entry((nx, ny)) {
+                e.insert(current);
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    // Without any open cell on `lower`, the walk ends somewhere arbitrary;
+    // only the stair cell itself is opened then.
+    if !lower[cell].is_floor() {
+        cell = start;
+    }
+    loop {
+        lower.set(cell.0 as i32, cell.1 as i32, Tile::Floor);
+        if cell == start {
+            break;
+        }
+        cell = parent[&cell];
+    }
+    Some((start.0 as u32, start.1 as u32))
+}
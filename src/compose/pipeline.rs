@@ -2,7 +2,7 @@
 //!
 //! This is the lightweight, algorithm-only pipeline (not the ops pipeline).
 
-use crate::{Algorithm, Cell, Grid};
+use crate::{Algorithm, Cell, Grid, Rng};
 
 /// Sequential algorithm pipeline.
 ///
@@ -34,8 +34,9 @@ impl<C: Cell> Pipeline<C> {
     }
 
     pub fn execute(&self, grid: &mut Grid<C>, seed: u64) {
+        let streams = Rng::new(seed);
         for (i, step) in self.steps.iter().enumerate() {
-            step.generate(grid, seed.wrapping_add(i as u64 * 1000));
+            step.generate(grid, streams.stream(i as u64).next_u64());
         }
     }
 }
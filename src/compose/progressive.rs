@@ -0,0 +1,174 @@
+//! Coarse-to-fine generation by repeated zooming
+
+use crate::{Algorithm, Grid, Rng, Tile};
+
+type Refiner = Box<dyn Fn(&mut Grid<Tile>, u64) + Send + Sync>;
+
+struct ZoomLevel {
+    factor: usize,
+    refiners: Vec<Refiner>,
+}
+
+/// Generates a small macro grid, then repeatedly upscales it and refines
+/// each zoom level, like zoomed world generation.
+///
+/// The base algorithm runs at the final size divided by the product of all
+/// zoom factors (rounded up). Each [`zoom`](Self::zoom) upscales the
+/// previous level; with fuzzy zooming (the default) cells near coarse-cell
+/// boundaries may take a neighbor's value, so edges don't stay blocky.
+/// [`refine`](Self::refine) steps then run on the upscaled grid. Every
+/// level draws its seeds from its own stream, so adding a zoom level
+/// leaves the coarser levels unchanged.
+///
+/// ```rust
+/// use terrain_forge::algorithms::CellularAutomata;
+/// use terrain_forge::compose::Progressive;
+/// use terrain_forge::{effects, Algorithm, Grid};
+///
+/// let lod = Progressive::new(CellularAutomata::default())
+///     .zoom(2)
+///     .refine_with(|grid, _| effects::open(grid, 1))
+///     .zoom(2);
+/// let levels = lod.levels(80, 60, 4);
+/// assert_eq!((levels[0].width(), levels[0].height()), (20, 15));
+/// assert_eq!((levels[2].width(), levels[2].height()), (80, 60));
+///
+/// let mut grid = Grid::new(80, 60);
+/// lod.generate(&mut grid, 4);
+/// assert_eq!(grid, levels[2]);
+/// ```
+pub struct Progressive {
+    base: Box<dyn Algorithm + Send + Sync>,
+    levels: Vec<ZoomLevel>,
+    fuzzy: bool,
+}
+
+impl Progressive {
+    /// Starts from `base`, run at the macro resolution.
+    pub fn new<A: Algorithm + 'static>(base: A) -> Self {
+        Self {
+            base: Box::new(base),
+            levels: vec![ZoomLevel {
+                factor: 1,
+                refiners: Vec::new(),
+            }],
+            fuzzy: true,
+        }
+    }
+
+    /// Adds a zoom level that upscales the previous one by `factor`.
+    pub fn zoom(mut self, factor: usize) -> Self {
+        self.levels.push(ZoomLevel {
+            factor: factor.max(1),
+            refiners: Vec::new(),
+        });
+        self
+    }
+
+    /// Runs `algorithm` on the latest level, after its upscale (or after
+    /// the base algorithm, before any zoom).
+    pub fn refine<A: Algorithm + 'static>(self, algorithm: A) -> Self {
+        self.refine_with(move |grid, seed| algorithm.generate(grid, seed))
+    }
+
+    /// Like [`refine`](Self::refine), with a closure taking the grid and
+    /// the step's seed, e.g. to apply an effect.
+    pub fn refine_with<F>(mut self, refiner: F) -> Self
+    where
+        F: Fn(&mut Grid<Tile>, u64) + Send + Sync + 'static,
+    {
+        if let Some(level) = self.levels.last_mut() {
+            level.refiners.push(Box::new(refiner));
+        }
+        self
+    }
+
+    /// Sets whether upscaling jitters coarse-cell boundaries. Default: true.
+    pub fn with_fuzzy(mut self, fuzzy: bool) -> Self {
+        self.fuzzy = fuzzy;
+        self
+    }
+
+    /// Grid size of each level, coarsest first, for a `width` x `height`
+    /// result.
+    pub fn sizes(&self, width: usize, height: usize) -> Vec<(usize, usize)> {
+        let mut sizes = Vec::with_capacity(self.levels.len());
+        let (mut w, mut h) = (width, height);
+        for level in self.levels.iter().rev() {
+            sizes.push((w.max(1), h.max(1)));
+            w = w.div_ceil(level.factor);
+            h = h.div_ceil(level.factor);
+        }
+        sizes.reverse();
+        sizes
+    }
+
+    /// Generates every level, coarsest first; the last is `width` x `height`.
+    pub fn levels(&self, width: usize, height: usize, seed: u64) -> Vec<Grid<Tile>> {
+        let streams = Rng::new(seed);
+        let mut grids: Vec<Grid<Tile>> = Vec::with_capacity(self.levels.len());
+        for (i, (level, (w, h))) in self
+            .levels
+            .iter()
+            .zip(self.sizes(width, height))
+            .enumerate()
+        {
+            let level_rng = streams.stream(i as u64);
+            let mut grid = match grids.last() {
+                Some(coarse) => upscale(coarse, w, h, self.fuzzy, &mut level_rng.stream(0)),
+                None => {
+                    let mut grid = Grid::new(w, h);
+                    self.base
+                        .generate(&mut grid, level_rng.stream(0).next_u64());
+                    grid
+                }
+            };
+            for (j, refiner) in level.refiners.iter().enumerate() {
+                refiner(&mut grid, level_rng.stream(j as u64 + 1).next_u64());
+            }
+            grids.push(grid);
+        }
+        grids
+    }
+}
+
+impl Algorithm for Progressive {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        if let Some(fine) = self.levels(grid.width(), grid.height(), seed).pop() {
+            *grid = fine;
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "Progressive"
+    }
+}
+
+/// Nearest-neighbor upscale; when `fuzzy`, each cell samples the coarse
+/// grid up to half a coarse cell away from its own position.
+fn upscale(
+    coarse: &Grid<Tile>,
+    width: usize,
+    height: usize,
+    fuzzy: bool,
+    rng: &mut Rng,
+) -> Grid<Tile> {
+    if !fuzzy {
+        return coarse.resized(width, height);
+    }
+    let (cw, ch) = (coarse.width(), coarse.height());
+    // Fine cells per coarse cell, at least 1.
+    let (fx, fy) = ((width / cw.max(1)).max(1), (height / ch.max(1)).max(1));
+    let mut grid = Grid::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let jx = rng.range(-(fx as i32 / 2), fx as i32 / 2 + 1);
+            let jy = rng.range(-(fy as i32 / 2), fy as i32 / 2 + 1);
+            let sx = (x as i32 + jx).clamp(0, width as i32 - 1) as usize;
+            let sy = (y as i32 + jy).clamp(0, height as i32 - 1) as usize;
+            let tile = coarse[(sx * cw / width, sy * ch / height)];
+            grid.set(x as i32, y as i32, tile);
+        }
+    }
+    grid
+}
@@ -0,0 +1,1041 @@
+//! Declarative generation configs in JSON, TOML (feature `toml`), or RON
+//! (feature `ron`).
+//!
+//! A [`Config`] describes a pipeline (algorithms, combine layers, and the
+//! other [`Step`](crate::pipeline::Step) kinds), post-processing effects,
+//! validation thresholds, and semantic requirements.
+//!
+//! ```rust
+//! use terrain_forge::config::Config;
+//!
+//! let config = Config::from_json(r#"{
+//!     "width": 60,
+//!     "height": 40,
+//!     "pipeline": [
+//!         "bsp",
+//!         { "op": "store", "key": "rooms" },
+//!         { "type": "cellular", "iterations": 3 },
+//!         { "op": "combine", "mode": "union", "source": { "saved": "rooms" } }
+//!     ],
+//!     "effects": ["remove_dead_ends"]
+//! }"#).unwrap();
+//! let grid = config.generate(42).unwrap();
+//! assert_eq!(grid.width(), 60);
+//!
+//! // CLI-style shorthand: `>` chains, `|` unions, `&` intersects.
+//! let layered = Config::from_shorthand("rooms | cellular(iterations=6) > erode(2)").unwrap();
+//! assert_eq!(layered.pipeline.len(), 3);
+//! ```
+
+use crate::constraints::{
+    BalancedExitsConstraint, ConnectivityConstraint, ConstraintContext, ConstraintSet,
+    DensityConstraint, SemanticRequirementsConstraint,
+};
+use crate::entities::EntityMapping;
+use crate::exits::MultiExitConfig;
+use crate::manifest::{Manifest, StepKind, StepRecord, Timer};
+use crate::ops::{self, OpError, OpResult, Params};
+use crate::pipeline::{ImageSource, Pipeline};
+use crate::semantic::{Marker, SemanticConfig, SemanticLayers, SemanticRequirements};
+use crate::spawns::SpawnTables;
+use crate::{Grid, Rng, SemanticExtractor, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Serialization format of a [`Config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    /// Requires feature `toml`.
+    Toml,
+    /// Requires feature `ron`.
+    Ron,
+}
+
+impl ConfigFormat {
+    /// Guesses the format from a file extension (`json`, `toml`, `ron`).
+    pub fn from_extension(path: impl AsRef<std::path::Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "ron" => Some(Self::Ron),
+            _ => None,
+        }
+    }
+}
+
+/// A complete generation config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "default_width")]
+    pub width: usize,
+    #[serde(default = "default_height")]
+    pub height: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Generation steps; an empty pipeline runs `bsp`.
+    #[serde(default)]
+    pub pipeline: Vec<PipelineStepSpec>,
+
+    /// Post-processing effects, applied after the pipeline.
+    #[serde(default)]
+    pub effects: Vec<EffectSpec>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate: Option<ValidationSpec>,
+    /// Semantic requirements; consumers retry generation until they pass.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirements: Option<RequirementsSpec>,
+    /// How [`generate_map`](crate::generate_map) retries attempts that
+    /// fail `validate` or `requirements`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetrySpec>,
+
+    /// Semantic extractor profile; without one the profile is guessed from
+    /// the primary algorithm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub semantic: Option<ProfileSpec>,
+
+    /// Markers added to extracted semantic layers.
+    #[serde(default)]
+    pub markers: Vec<MarkerSpec>,
+
+    /// Rules turning markers into entities for export; see
+    /// [`entities`](crate::entities).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entities: Option<EntityMapping>,
+
+    /// Weighted tables rolled at markers with the map seed; see
+    /// [`spawns`](crate::spawns).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawns: Option<SpawnTables>,
+
+    /// Balanced exits placed on different map edges, checked by
+    /// [`generate_map`](crate::generate_map); see
+    /// [`exits::place_multi`](crate::exits::place_multi). Needs a `Spawn`
+    /// or `PlayerStart` marker, extracted or from `markers`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exits: Option<MultiExitConfig>,
+
+    /// Sub-map specs keyed by marker tag or a marker's `child` name; see
+    /// [`world`](crate::world).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub children: BTreeMap<String, Config>,
+}
+
+fn default_width() -> usize {
+    80
+}
+fn default_height() -> usize {
+    60
+}
+fn default_weight() -> f32 {
+    1.0
+}
+fn default_threshold() -> u8 {
+    128
+}
+fn default_pattern_size() -> usize {
+    3
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            name: None,
+            width: default_width(),
+            height: default_height(),
+            seed: None,
+            pipeline: Vec::new(),
+            effects: Vec::new(),
+            validate: None,
+            requirements: None,
+            retry: None,
+            semantic: None,
+            markers: Vec::new(),
+            entities: None,
+            spawns: None,
+            exits: None,
+            children: BTreeMap::new(),
+        }
+    }
+}
+
+/// An algorithm name, or a table with `type` plus algorithm params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AlgorithmSpec {
+    Name(String),
+    WithParams {
+        #[serde(rename = "type")]
+        type_name: String,
+        #[serde(flatten)]
+        params: Params,
+    },
+}
+
+impl AlgorithmSpec {
+    pub fn name(&self) -> &str {
+        match self {
+            AlgorithmSpec::Name(name) => name.as_str(),
+            AlgorithmSpec::WithParams { type_name, .. } => type_name.as_str(),
+        }
+    }
+
+    pub fn params(&self) -> Option<&Params> {
+        match self {
+            AlgorithmSpec::Name(_) => None,
+            AlgorithmSpec::WithParams { params, .. } => Some(params),
+        }
+    }
+}
+
+/// A pipeline entry: a bare algorithm or an `op` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PipelineStepSpec {
+    Algorithm(AlgorithmSpec),
+    Op(PipelineOpSpec),
+}
+
+/// Pipeline operations, tagged by `op`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PipelineOpSpec {
+    /// Layers another algorithm or a stored grid onto the current grid.
+    Combine {
+        mode: String,
+        source: CombineSourceSpec,
+    },
+    Effect {
+        name: String,
+        #[serde(default, alias = "config", skip_serializing_if = "Option::is_none")]
+        params: Option<Params>,
+    },
+    /// Stores the current grid for later `combine` steps.
+    Store {
+        key: String,
+    },
+    /// Replaces the grid with a PNG (see [`Grid::from_image`]).
+    Image {
+        path: String,
+        #[serde(default = "default_threshold")]
+        threshold: u8,
+    },
+    /// Runs WFC with patterns learned from a PNG sample.
+    WfcSample {
+        path: String,
+        #[serde(default = "default_threshold")]
+        threshold: u8,
+        #[serde(default = "default_pattern_size")]
+        pattern_size: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        seed: Option<u64>,
+    },
+    Log {
+        message: String,
+    },
+}
+
+/// Source of a combine layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CombineSourceSpec {
+    /// A grid stored by an earlier `store` step.
+    Saved {
+        saved: String,
+    },
+    Algorithm(AlgorithmSpec),
+}
+
+/// An effect name, or a table with `name` plus `config` params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EffectSpec {
+    Name(String),
+    WithParams {
+        name: String,
+        #[serde(alias = "params")]
+        config: Params,
+    },
+}
+
+impl EffectSpec {
+    pub fn name(&self) -> &str {
+        match self {
+            EffectSpec::Name(name) => name.as_str(),
+            EffectSpec::WithParams { name, .. } => name.as_str(),
+        }
+    }
+
+    pub fn params(&self) -> Option<&Params> {
+        match self {
+            EffectSpec::Name(_) => None,
+            EffectSpec::WithParams { config, .. } => Some(config),
+        }
+    }
+
+    /// Whether the effect reads semantic markers.
+    pub fn needs_semantic(&self) -> bool {
+        matches!(self.name(), "connect_markers" | "clear_marker_area")
+    }
+}
+
+/// Validation thresholds checked after generation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connectivity: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub density: Option<(f64, f64)>,
+}
+
+/// Semantic requirements keyed by region kind and marker name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequirementsSpec {
+    #[serde(default)]
+    pub min_regions: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub max_regions: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub required_connections: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_walkable_area: Option<usize>,
+    #[serde(default)]
+    pub required_markers: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<usize>,
+}
+
+impl RequirementsSpec {
+    pub fn to_requirements(&self) -> SemanticRequirements {
+        let mut req = SemanticRequirements::none();
+        req.min_regions.extend(self.min_regions.clone());
+        req.max_regions.extend(self.max_regions.clone());
+        req.required_connections
+            .extend(self.required_connections.clone());
+        req.min_walkable_area = self.min_walkable_area;
+
+        for (marker, count) in &self.required_markers {
+            req.required_markers
+                .insert(ops::parse_marker_type(marker), *count);
+        }
+
+        req
+    }
+
+    /// `max_attempts`, defaulting to 10 (at least 1).
+    pub fn attempts(&self) -> usize {
+        self.max_attempts.unwrap_or(10).max(1)
+    }
+}
+
+/// Retry policy for [`generate_map`](crate::generate_map).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetrySpec {
+    /// Attempts in total, at least 1. Default: 10.
+    pub max_attempts: usize,
+    /// Added to the seed (wrapping) before each retry. Default: 1.
+    pub seed_step: u64,
+}
+
+impl Default for RetrySpec {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            seed_step: 1,
+        }
+    }
+}
+
+/// The config [`generate_map`](crate::generate_map) runs: a [`Config`]
+/// with its pipeline, effects, extraction profile, constraints, and retry
+/// policy.
+pub type MapConfig = Config;
+
+/// A semantic extractor profile: a preset name (`caves`, `rooms`, `mazes`,
+/// `default`), a weighted blend of profiles, or a full [`SemanticConfig`]
+/// table.
+///
+/// ```rust
+/// use terrain_forge::config::ProfileSpec;
+///
+/// let spec = ProfileSpec::from_json(r#"{ "blend": [
+///     { "profile": "caves", "weight": 3 },
+///     { "profile": { "marker_types": [["Shrine", 1.0]] } }
+/// ] }"#).unwrap();
+/// let profile = spec.resolve().unwrap();
+/// assert!(profile.marker_types.iter().any(|(kind, _)| kind == "Shrine"));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ProfileSpec {
+    Preset(String),
+    Blend { blend: Vec<WeightedProfile> },
+    Custom(Box<SemanticConfig>),
+}
+
+/// One entry of a [`ProfileSpec::Blend`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightedProfile {
+    pub profile: ProfileSpec,
+    /// Relative weight. Default: 1.0.
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+impl ProfileSpec {
+    /// Parses a profile in the given format.
+    pub fn parse(input: &str, format: ConfigFormat) -> OpResult<Self> {
+        match format {
+            ConfigFormat::Json => Self::from_json(input),
+            ConfigFormat::Toml => {
+                #[cfg(feature = "toml")]
+                return toml::from_str(input).map_err(|e| invalid("TOML", e));
+                #[cfg(not(feature = "toml"))]
+                Err(OpError::new("TOML configs require the `toml` feature"))
+            }
+            ConfigFormat::Ron => {
+                #[cfg(feature = "ron")]
+                return ron::from_str(input).map_err(|e| invalid("RON", e));
+                #[cfg(not(feature = "ron"))]
+                Err(OpError::new("RON configs require the `ron` feature"))
+            }
+        }
+    }
+
+    pub fn from_json(input: &str) -> OpResult<Self> {
+        serde_json::from_str(input).map_err(|e| invalid("JSON", e))
+    }
+
+    /// Loads a profile file, choosing the format by extension (default JSON).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> OpResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(
+            &content,
+            ConfigFormat::from_extension(path).unwrap_or(ConfigFormat::Json),
+        )
+    }
+
+    /// Resolves presets and blends into a single [`SemanticConfig`].
+    pub fn resolve(&self) -> OpResult<SemanticConfig> {
+        match self {
+            Self::Preset(name) => SemanticConfig::preset(name)
+                .ok_or_else(|| OpError::new(format!("Unknown extractor profile: {}", name))),
+            Self::Blend { blend } => {
+                let profiles = blend
+                    .iter()
+                    .map(|entry| Ok((entry.profile.resolve()?, entry.weight)))
+                    .collect::<OpResult<Vec<_>>>()?;
+                SemanticConfig::blend(&profiles)
+                    .ok_or_else(|| OpError::new("Profile blend needs a positive weight"))
+            }
+            Self::Custom(config) => Ok((**config).clone()),
+        }
+    }
+}
+
+/// A marker added after semantic extraction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkerSpec {
+    pub x: u32,
+    pub y: u32,
+    pub tag: String,
+    /// Name of the [`Config::children`] entry this marker links to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child: Option<String>,
+}
+
+impl Config {
+    /// Parses a config in the given format.
+    pub fn parse(input: &str, format: ConfigFormat) -> OpResult<Self> {
+        match format {
+            ConfigFormat::Json => Self::from_json(input),
+            ConfigFormat::Toml => Self::from_toml(input),
+            ConfigFormat::Ron => Self::from_ron(input),
+        }
+    }
+
+    pub fn from_json(input: &str) -> OpResult<Self> {
+        serde_json::from_str(input)
+            .map_err(|e| invalid("JSON", e))
+            .and_then(Self::checked)
+    }
+
+    pub fn from_toml(input: &str) -> OpResult<Self> {
+        #[cfg(feature = "toml")]
+        return toml::from_str(input)
+            .map_err(|e| invalid("TOML", e))
+            .and_then(Self::checked);
+        #[cfg(not(feature = "toml"))]
+        {
+            let _ = input;
+            Err(OpError::new("TOML configs require the `toml` feature"))
+        }
+    }
+
+    pub fn from_ron(input: &str) -> OpResult<Self> {
+        #[cfg(feature = "ron")]
+        return ron::from_str(input)
+            .map_err(|e| invalid("RON", e))
+            .and_then(Self::checked);
+        #[cfg(not(feature = "ron"))]
+        {
+            let _ = input;
+            Err(OpError::new("RON configs require the `ron` feature"))
+        }
+    }
+
+    /// Rejects a `semantic` profile that doesn't resolve.
+    fn checked(self) -> OpResult<Self> {
+        if let Some(spec) = &self.semantic {
+            spec.resolve()?;
+        }
+        Ok(self)
+    }
+
+    /// Loads a config file, choosing the format by extension (default JSON).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> OpResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(
+            &content,
+            ConfigFormat::from_extension(path).unwrap_or(ConfigFormat::Json),
+        )
+    }
+
+    /// Builds a config from CLI shorthand (see [`parse_shorthand`]).
+    pub fn from_shorthand(input: &str) -> OpResult<Self> {
+        Ok(Self {
+            pipeline: parse_shorthand(input)?,
+            ..Self::default()
+        })
+    }
+
+    /// Name of the first algorithm in the pipeline.
+    pub fn primary_algorithm(&self) -> Option<&str> {
+        self.pipeline.iter().find_map(|step| match step {
+            PipelineStepSpec::Algorithm(spec) => Some(spec.name()),
+            PipelineStepSpec::Op(_) => None,
+        })
+    }
+
+    /// Semantic extractor for the `semantic` profile, or matching the
+    /// primary algorithm when there is none. A profile that doesn't resolve
+    /// (rejected when parsing) falls back to the default.
+    pub fn extractor(&self) -> SemanticExtractor {
+        self.known_extractor().unwrap_or_default()
+    }
+
+    /// Like [`extractor`](Self::extractor), but picks a profile from
+    /// `grid`'s statistics ([`SemanticExtractor::auto`]) when neither the
+    /// `semantic` profile nor the primary algorithm decides one.
+    pub fn extractor_for(&self, grid: &Grid<Tile>) -> SemanticExtractor {
+        self.known_extractor()
+            .unwrap_or_else(|| SemanticExtractor::auto(grid))
+    }
+
+    fn known_extractor(&self) -> Option<SemanticExtractor> {
+        if let Some(spec) = &self.semantic {
+            return Some(SemanticExtractor::new(spec.resolve().unwrap_or_default()));
+        }
+        match self.primary_algorithm() {
+            Some("cellular") => Some(SemanticExtractor::for_caves()),
+            Some("bsp" | "rooms" | "room_accretion" | "rooms_on_mask") => {
+                Some(SemanticExtractor::for_rooms())
+            }
+            Some("maze") => Some(SemanticExtractor::for_mazes()),
+            _ => None,
+        }
+    }
+
+    /// Builds the pipeline, validating algorithm names and params.
+    pub fn build_pipeline(&self) -> OpResult<Pipeline> {
+        let mut pipeline = Pipeline::new();
+        for step in &self.pipeline {
+            match step {
+                PipelineStepSpec::Algorithm(spec) => {
+                    ops::build_algorithm(spec.name(), spec.params())?;
+                    pipeline.add_algorithm(spec.name(), None, spec.params().cloned());
+                }
+                PipelineStepSpec::Op(op) => match op {
+                    PipelineOpSpec::Combine { mode, source } => {
+                        let mode = ops::script::parse_combine_mode(mode)?;
+                        match source {
+                            CombineSourceSpec::Saved { saved } => {
+                                pipeline.add_combine_with_saved(mode, saved.clone());
+                            }
+                            CombineSourceSpec::Algorithm(spec) => {
+                                ops::build_algorithm(spec.name(), spec.params())?;
+                                pipeline.add_combine_with_algorithm(
+                                    mode,
+                                    spec.name(),
+                                    None,
+                                    spec.params().cloned(),
+                                );
+                            }
+                        }
+                    }
+                    PipelineOpSpec::Effect { name, params } => {
+                        pipeline.add_effect(name.clone(), params.clone());
+                    }
+                    PipelineOpSpec::Store { key } => {
+                        pipeline.store_grid(key.clone());
+                    }
+                    PipelineOpSpec::Image { path, threshold } => {
+                        pipeline.add_image(ImageSource::Path(path.clone()), *threshold);
+                    }
+                    PipelineOpSpec::WfcSample {
+                        path,
+                        threshold,
+                        pattern_size,
+                        seed,
+                    } => {
+                        pipeline.add_wfc_sample(
+                            ImageSource::Path(path.clone()),
+                            *threshold,
+                            *pattern_size,
+                            *seed,
+                        );
+                    }
+                    PipelineOpSpec::Log { message } => {
+                        pipeline.add_step(crate::pipeline::Step::Log {
+                            message: message.clone(),
+                        });
+                    }
+                },
+            }
+        }
+
+        if self.pipeline.is_empty() {
+            pipeline.add_algorithm("bsp", None, None);
+        }
+        Ok(pipeline)
+    }
+
+    /// `validate` thresholds and `requirements` as one constraint set.
+    pub fn constraints(&self) -> ConstraintSet {
+        let mut constraints = ConstraintSet::new();
+        if let Some(spec) = &self.validate {
+            if let Some(min) = spec.connectivity {
+                constraints.push(ConnectivityConstraint::new(min));
+            }
+            if let Some((min, max)) = spec.density {
+                constraints.push(DensityConstraint::new(min, max));
+            }
+        }
+        if let Some(spec) = &self.requirements {
+            constraints.push(SemanticRequirementsConstraint::new(spec.to_requirements()));
+        }
+        if let Some(exits) = &self.exits {
+            constraints.push(BalancedExitsConstraint::new(exits));
+        }
+        constraints
+    }
+
+    /// Attempts [`generate_map`](crate::generate_map) makes: `retry`'s
+    /// `max_attempts`, else the requirements' (see
+    /// [`RequirementsSpec::attempts`]), else 10.
+    pub fn attempts(&self) -> usize {
+        match (&self.retry, &self.requirements) {
+            (Some(retry), _) => retry.max_attempts.max(1),
+            (None, Some(requirements)) => requirements.attempts(),
+            (None, None) => RetrySpec::default().max_attempts,
+        }
+    }
+
+    /// Whether any effect reads semantic markers.
+    pub fn effects_need_semantic(&self) -> bool {
+        self.effects.iter().any(EffectSpec::needs_semantic)
+    }
+
+    /// Applies `effects` in order, stopping at the first error.
+    pub fn apply_effects(
+        &self,
+        grid: &mut Grid<Tile>,
+        semantic: Option<&SemanticLayers>,
+    ) -> OpResult<()> {
+        for effect in &self.effects {
+            ops::effect(effect.name(), grid, effect.params(), semantic)?;
+        }
+        Ok(())
+    }
+
+    /// Adds `markers` to extracted semantic layers.
+    pub fn apply_marker_overrides(&self, semantic: &mut SemanticLayers) {
+        for spec in &self.markers {
+            let mut marker = Marker::with_tag(spec.x, spec.y, spec.tag.clone());
+            if let Some(child) = &spec.child {
+                marker
+                    .metadata
+                    .insert(crate::world::CHILD_KEY.to_string(), child.clone());
+            }
+            semantic.markers.push(marker);
+        }
+    }
+
+    /// Runs the pipeline and effects on a fresh grid.
+    ///
+    /// Semantic layers are extracted (with the same seed) only when an
+    /// effect needs them.
+    pub fn generate(&self, seed: u64) -> OpResult<Grid<Tile>> {
+        self.generate_with_manifest(seed).map(|(grid, _)| grid)
+    }
+
+    /// Like [`generate`](Self::generate), also returning a [`Manifest`] of
+    /// the pipeline steps and effects run, with resolved params, seeds, and
+    /// timings. When `validate` is set, its connectivity and density checks
+    /// are recorded as constraint results; failures do not make this fail.
+    pub fn generate_with_manifest(&self, seed: u64) -> OpResult<(Grid<Tile>, Manifest)> {
+        let mut grid = Grid::new(self.width, self.height);
+        let mut manifest = self
+            .build_pipeline()?
+            .execute_seed(&mut grid, seed)?
+            .into_manifest();
+        let semantic = self.effects_need_semantic().then(|| {
+            self.extractor_for(&grid)
+                .extract(&grid, &mut Rng::new(seed))
+        });
+        for effect in &self.effects {
+            let timer = Timer::start();
+            ops::effect(effect.name(), &mut grid, effect.params(), semantic.as_ref())?;
+            let mut record = StepRecord::new(StepKind::Effect, effect.name())
+                .with_params(ops::resolve_effect_params(effect.name(), effect.params()));
+            record.micros = timer.micros();
+            manifest.steps.push(record);
+        }
+        if let Some(spec) = &self.validate {
+            let mut constraints = ConstraintSet::new();
+            if let Some(min) = spec.connectivity {
+                constraints.push(ConnectivityConstraint::new(min));
+            }
+            if let Some((min, max)) = spec.density {
+                constraints.push(DensityConstraint::new(min, max));
+            }
+            manifest.record_constraints(&constraints.evaluate(&ConstraintContext::new(&grid)));
+        }
+        Ok((grid, manifest))
+    }
+}
+
+/// Parses CLI shorthand into pipeline steps.
+///
+/// ```text
+/// chain := layer ('>' layer)*          run in order
+/// layer := term (('|' | '&') term)*    union / intersect with fresh layers
+/// term  := call | '(' chain ')'
+/// call  := name ['(' arg (',' arg)* ')']
+/// arg   := key '=' value | value       positional args follow the op's schema
+/// ```
+///
+/// Names that are effects (`erode(2)`, `remove_dead_ends`) become effect
+/// steps; everything else is an algorithm. Values are numbers, `true` /
+/// `false`, or (optionally quoted) strings. Parenthesized layers are built
+/// on a fresh grid and combined through a stored copy of the current one.
+///
+/// ```rust
+/// use terrain_forge::config::{parse_shorthand, Config};
+///
+/// let steps = parse_shorthand("rooms | (cellular(iterations=6) > erode(2))").unwrap();
+/// assert_eq!(steps.len(), 5);
+/// assert!(parse_shorthand("bsp > (cellular").is_err());
+///
+/// let config = Config::from_shorthand("rooms | drunkard > remove_dead_ends").unwrap();
+/// assert!(config.generate(7).is_ok());
+/// ```
+pub fn parse_shorthand(input: &str) -> OpResult<Vec<PipelineStepSpec>> {
+    let mut parser = ShorthandParser { input, pos: 0 };
+    let expr = parser.chain()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected input"));
+    }
+    let mut steps = Vec::new();
+    expr.compile(true, &mut steps, &mut 0)?;
+    Ok(steps)
+}
+
+enum ShorthandExpr {
+    Call {
+        name: String,
+        params: Option<Params>,
+        effect: bool,
+    },
+    Chain(Vec<ShorthandExpr>),
+    Layer(Box<ShorthandExpr>, Vec<(&'static str, ShorthandExpr)>),
+}
+
+impl ShorthandExpr {
+    /// Appends the steps for this expression. `fresh` expressions start
+    /// from a new grid rather than building on the current one.
+    fn compile(
+        self,
+        fresh: bool,
+        steps: &mut Vec<PipelineStepSpec>,
+        stores: &mut usize,
+    ) -> OpResult<()> {
+        match self {
+            ShorthandExpr::Call {
+                name,
+                params,
+                effect: true,
+            } => steps.push(PipelineStepSpec::Op(PipelineOpSpec::Effect {
+                name,
+                params,
+            })),
+            ShorthandExpr::Call { name, params, .. } => {
+                let spec = algorithm_spec(name, params);
+                steps.push(if fresh && !steps.is_empty() {
+                    PipelineStepSpec::Op(PipelineOpSpec::Combine {
+                        mode: "replace".to_string(),
+                        source: CombineSourceSpec::Algorithm(spec),
+                    })
+                } else {
+                    PipelineStepSpec::Algorithm(spec)
+                });
+            }
+            ShorthandExpr::Chain(items) => {
+                for (i, item) in items.into_iter().enumerate() {
+                    item.compile(fresh && i == 0, steps, stores)?;
+                }
+            }
+            ShorthandExpr::Layer(first, rest) => {
+                first.compile(fresh, steps, stores)?;
+                for (mode, operand) in rest {
+                    match operand {
+                        ShorthandExpr::Call {
+                            name, effect: true, ..
+                        } => {
+                            return Err(OpError::parse(
+                                "shorthand",
+                                format!("effect '{}' can't be layered with | or &", name),
+                            ));
+                        }
+                        ShorthandExpr::Call { name, params, .. } => {
+                            steps.push(PipelineStepSpec::Op(PipelineOpSpec::Combine {
+                                mode: mode.to_string(),
+                                source: CombineSourceSpec::Algorithm(algorithm_spec(name, params)),
+                            }));
+                        }
+                        group => {
+                            // Union and intersect are symmetric, so build the
+                            // group in place and layer the stored grid back on.
+                            *stores += 1;
+                            let key = format!("shorthand_{}", stores);
+                            steps.push(PipelineStepSpec::Op(PipelineOpSpec::Store {
+                                key: key.clone(),
+                            }));
+                            group.compile(true, steps, stores)?;
+                            steps.push(PipelineStepSpec::Op(PipelineOpSpec::Combine {
+                                mode: mode.to_string(),
+                                source: CombineSourceSpec::Saved { saved: key },
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn algorithm_spec(name: String, params: Option<Params>) -> AlgorithmSpec {
+    match params {
+        Some(params) => AlgorithmSpec::WithParams {
+            type_name: name,
+            params,
+        },
+        None => AlgorithmSpec::Name(name),
+    }
+}
+
+struct ShorthandParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl ShorthandParser<'_> {
+    /// Next non-space character, without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> OpResult<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn error(&self, message: &str) -> OpError {
+        OpError::parse(
+            "shorthand",
+            format!("{} at column {}", message, self.pos + 1),
+        )
+    }
+
+    fn chain(&mut self) -> OpResult<ShorthandExpr> {
+        let mut items = vec![self.layer()?];
+        while self.eat('>') {
+            items.push(self.layer()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            ShorthandExpr::Chain(items)
+        })
+    }
+
+    fn layer(&mut self) -> OpResult<ShorthandExpr> {
+        let first = self.term()?;
+        let mut rest = Vec::new();
+        loop {
+            let mode = if self.eat('|') {
+                "union"
+            } else if self.eat('&') {
+                "intersect"
+            } else {
+                break;
+            };
+            rest.push((mode, self.term()?));
+        }
+        Ok(if rest.is_empty() {
+            first
+        } else {
+            ShorthandExpr::Layer(Box::new(first), rest)
+        })
+    }
+
+    fn term(&mut self) -> OpResult<ShorthandExpr> {
+        if self.eat('(') {
+            let expr = self.chain()?;
+            self.expect(')')?;
+            return Ok(expr);
+        }
+        let name = self.ident();
+        if name.is_empty() {
+            return Err(self.error("expected a name"));
+        }
+        let effect = is_effect(&name);
+        let params = if self.eat('(') {
+            self.args(&name, effect)?
+        } else {
+            None
+        };
+        Ok(ShorthandExpr::Call {
+            name,
+            params,
+            effect,
+        })
+    }
+
+    fn ident(&mut self) -> String {
+        self.peek();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].to_string()
+    }
+
+    /// Parses call arguments after the opening parenthesis.
+    fn args(&mut self, name: &str, effect: bool) -> OpResult<Option<Params>> {
+        let mut params = Params::new();
+        if self.eat(')') {
+            return Ok(None);
+        }
+        let mut positional = 0;
+        loop {
+            let start = self.pos;
+            let key = self.ident();
+            let key = if !key.is_empty() && self.eat('=') {
+                key
+            } else {
+                self.pos = start;
+                let schema = if effect {
+                    ops::describe_effect(name)
+                } else {
+                    ops::describe_algorithm(name)
+                };
+                let param = schema.and_then(|s| s.params.get(positional).map(|p| p.name.clone()));
+                positional += 1;
+                param.ok_or_else(|| {
+                    self.error(&format!(
+                        "'{}' takes no positional argument {}; use key=value",
+                        name, positional
+                    ))
+                })?
+            };
+            let value = self.value()?;
+            if params.insert(key.clone(), value).is_some() {
+                return Err(self.error(&format!("'{}' is given twice", key)));
+            }
+            if !self.eat(',') {
+                self.expect(')')?;
+                return Ok(Some(params));
+            }
+        }
+    }
+
+    fn value(&mut self) -> OpResult<serde_json::Value> {
+        let quote = match self.peek() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => {
+                let rest = &self.input[self.pos..];
+                let len = rest.find([',', ')']).unwrap_or(rest.len());
+                let raw = rest[..len].trim();
+                if raw.is_empty() {
+                    return Err(self.error("expected a value"));
+                }
+                self.pos += len;
+                return Ok(match raw {
+                    "true" => true.into(),
+                    "false" => false.into(),
+                    _ => raw
+                        .parse::<i64>()
+                        .map(Into::into)
+                        .or_else(|_| raw.parse::<f64>().map(Into::into))
+                        .unwrap_or_else(|_| raw.into()),
+                });
+            }
+        };
+        let rest = &self.input[self.pos + 1..];
+        let Some(len) = rest.find(quote) else {
+            return Err(self.error("unterminated string"));
+        };
+        self.pos += len + 2;
+        Ok(rest[..len].into())
+    }
+}
+
+fn is_effect(name: &str) -> bool {
+    let algorithm = ops::ALGORITHM_NAMES.contains(&name)
+        || ops::registered_algorithms().iter().any(|a| a == name);
+    !algorithm
+        && (ops::EFFECT_NAMES.contains(&name)
+            || ops::registered_effects().iter().any(|e| e == name))
+}
+
+fn invalid(format: &str, e: impl std::fmt::Display) -> OpError {
+    OpError::parse(format!("{} config", format), e)
+}
@@ -1,8 +1,9 @@
 //! Constraint validation utilities and helpers.
 
-use crate::{pipeline, semantic};
+use crate::{parallel, pipeline, semantic};
 use crate::{Grid, Tile};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// Returns connectivity ratio (0.0–1.0): largest region / total floor.
 #[must_use]
@@ -90,7 +91,7 @@ pub struct ConstraintResult {
     /// Score from 0.0 (fail) to 1.0 (pass).
     pub score: f32,
     /// Additional details about the evaluation.
-    pub details: HashMap<String, String>,
+    pub details: BTreeMap<String, String>,
 }
 
 impl ConstraintResult {
@@ -99,7 +100,7 @@ impl ConstraintResult {
         Self {
             passed: true,
             score: 1.0,
-            details: HashMap::new(),
+            details: BTreeMap::new(),
         }
     }
 
@@ -108,7 +109,7 @@ impl ConstraintResult {
         Self {
             passed: false,
             score: 0.0,
-            details: HashMap::new(),
+            details: BTreeMap::new(),
         }
     }
 
@@ -149,6 +150,16 @@ pub struct ConstraintReport {
     pub results: Vec<ConstraintEvaluation>,
 }
 
+impl ConstraintReport {
+    /// Mean score of all results; 1.0 when there are none.
+    pub fn score(&self) -> f32 {
+        if self.results.is_empty() {
+            return 1.0;
+        }
+        self.results.iter().map(|r| r.result.score).sum::<f32>() / self.results.len() as f32
+    }
+}
+
 /// A set of constraints to evaluate together.
 #[derive(Default)]
 pub struct ConstraintSet {
@@ -172,9 +183,21 @@ impl ConstraintSet {
     pub fn evaluate(&self, ctx: &ConstraintContext) -> ConstraintReport {
         let mut results = Vec::new();
         let mut passed = true;
+        #[cfg(feature = "tracing")]
+        let _log = crate::logging::Timed::new(tracing::debug_span!(
+            "constraints",
+            count = self.constraints.len()
+        ));
 
         for constraint in &self.constraints {
             let result = constraint.evaluate(ctx);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                id = constraint.id(),
+                passed = result.passed,
+                score = result.score,
+                "constraint evaluated"
+            );
             if !result.passed {
                 passed = false;
             }
@@ -187,6 +210,19 @@ impl ConstraintSet {
 
         ConstraintReport { passed, results }
     }
+
+    /// Generates one grid per seed and evaluates each against the set.
+    ///
+    /// Runs in parallel with feature `parallel`; reports keep seed order.
+    pub fn evaluate_seeds<F>(&self, seeds: &[u64], generate: F) -> Vec<(u64, ConstraintReport)>
+    where
+        F: Fn(u64) -> Grid<Tile> + Sync + Send,
+    {
+        parallel::map(seeds.len(), |i| {
+            let grid = generate(seeds[i]);
+            (seeds[i], self.evaluate(&ConstraintContext::new(&grid)))
+        })
+    }
 }
 
 /// Constraint that validates semantic layer requirements.
@@ -258,7 +294,7 @@ impl Constraint for ConnectivityConstraint {
         ConstraintResult {
             passed,
             score,
-            details: HashMap::from([
+            details: BTreeMap::from([
                 ("ratio".to_string(), format!("{:.4}", ratio)),
                 ("min".to_string(), format!("{:.4}", self.min_ratio)),
             ]),
@@ -305,7 +341,7 @@ impl Constraint for DensityConstraint {
         ConstraintResult {
             passed,
             score,
-            details: HashMap::from([
+            details: BTreeMap::from([
                 ("density".to_string(), format!("{:.4}", density)),
                 ("min".to_string(), format!("{:.4}", self.min)),
                 ("max".to_string(), format!("{:.4}", self.max)),
@@ -371,3 +407,1171 @@ impl Constraint for PipelineConditionConstraint {
         }
     }
 }
+
+/// Marker tag of doors checked by [`validate_locks`].
+pub const DOOR_TAG: &str = "door";
+/// Marker tag of keys checked by [`validate_locks`].
+pub const KEY_TAG: &str = "key";
+/// Marker metadata naming the lock a door or key belongs to. Doors without
+/// it are unlocked.
+pub const LOCK_KEY: &str = "lock";
+
+/// A lock-and-key problem found by [`validate_locks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockIssue {
+    /// No spawn (or `PlayerStart`) marker on floor.
+    NoStart,
+    /// A locked door with no matching key.
+    MissingKey { lock: String, door: (u32, u32) },
+    /// Every key for the lock sits behind its own door (or another door
+    /// that can't be opened first).
+    KeyBehindDoor {
+        lock: String,
+        door: (u32, u32),
+        key: (u32, u32),
+    },
+    /// Floor reachable with all doors open but not by unlocking them in
+    /// order; `cell` is one cell of the cut-off area.
+    Unreachable { cell: (u32, u32), size: usize },
+}
+
+impl std::fmt::Display for LockIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoStart => write!(f, "no spawn marker to start from; add a spawn marker"),
+            Self::MissingKey { lock, door: (x, y) } => write!(
+                f,
+                "door '{}' at ({}, {}) has no key; add a '{}' marker with {}={}",
+                lock, x, y, KEY_TAG, LOCK_KEY, lock
+            ),
+            Self::KeyBehindDoor {
+                lock,
+                door: (dx, dy),
+                key: (kx, ky),
+            } => write!(
+                f,
+                "key '{}' at ({}, {}) can't be reached without passing its door at ({}, {}); move the key in front of the door",
+                lock, kx, ky, dx, dy
+            ),
+            Self::Unreachable { cell: (x, y), size } => write!(
+                f,
+                "{} cells around ({}, {}) can never be reached; check the keys of the doors leading there",
+                size, x, y
+            ),
+        }
+    }
+}
+
+/// Checks lock-and-key consistency of door and key markers.
+///
+/// Starting at the spawn marker, keys are collected and their doors opened
+/// until nothing changes. Each locked door must have its key reachable
+/// while that door stays shut, and no floor reachable with every door open
+/// may be left behind a door that never opens.
+pub fn validate_locks(grid: &Grid<Tile>, semantic: &semantic::SemanticLayers) -> Vec<LockIssue> {
+    let lock_of = |m: &semantic::Marker, tag: &str| {
+        (m.tag() == tag)
+            .then(|| m.metadata.get(LOCK_KEY).cloned())
+            .flatten()
+    };
+    let doors: Vec<(String, (u32, u32))> = semantic
+        .markers
+        .iter()
+        .filter_map(|m| Some((lock_of(m, DOOR_TAG)?, (m.x, m.y))))
+        .collect();
+    let keys: Vec<(String, (u32, u32))> = semantic
+        .markers
+        .iter()
+        .filter_map(|m| Some((lock_of(m, KEY_TAG)?, (m.x, m.y))))
+        .collect();
+    let Some(start) = semantic
+        .spawn()
+        .map(|m| (m.x as usize, m.y as usize))
+        .filter(|&(x, y)| grid.get(x as i32, y as i32).is_some_and(|t| t.is_floor()))
+    else {
+        return vec![LockIssue::NoStart];
+    };
+
+    let mut issues = Vec::new();
+    for (lock, door) in &doors {
+        let mut own = keys.iter().filter(|(k, _)| k == lock).peekable();
+        let Some(&(_, first)) = own.peek() else {
+            issues.push(LockIssue::MissingKey {
+                lock: lock.clone(),
+                door: *door,
+            });
+            continue;
+        };
+        let reached = unlock_reach(grid, start, &doors, &keys, Some(*door));
+        if !own.any(|(_, (x, y))| reached[*y as usize * grid.width() + *x as usize]) {
+            issues.push(LockIssue::KeyBehindDoor {
+                lock: lock.clone(),
+                door: *door,
+                key: *first,
+            });
+        }
+    }
+
+    let reached = unlock_reach(grid, start, &doors, &keys, None);
+    let open = unlock_reach(grid, start, &[], &[], None);
+    let w = grid.width();
+    let mut seen = vec![false; reached.len()];
+    for i in 0..reached.len() {
+        if !open[i] || reached[i] || seen[i] {
+            continue;
+        }
+        seen[i] = true;
+        let mut size = 0;
+        let mut stack = vec![i];
+        while let Some(j) = stack.pop() {
+            size += 1;
+            let (x, y) = ((j % w) as i32, (j / w) as i32);
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                if grid.get(x + dx, y + dy).is_some() {
+                    let k = (y + dy) as usize * w + (x + dx) as usize;
+                    if open[k] && !reached[k] && !seen[k] {
+                        seen[k] = true;
+                        stack.push(k);
+                    }
+                }
+            }
+        }
+        let cell = ((i % w) as u32, (i / w) as u32);
+        issues.push(LockIssue::Unreachable { cell, size });
+    }
+    issues
+}
+
+/// Floor reachable from `start` collecting keys and opening their doors as
+/// they are found; `sealed` stays shut regardless. Indexed `y * w + x`.
+fn unlock_reach(
+    grid: &Grid<Tile>,
+    start: (usize, usize),
+    doors: &[(String, (u32, u32))],
+    keys: &[(String, (u32, u32))],
+    sealed: Option<(u32, u32)>,
+) -> Vec<bool> {
+    let w = grid.width();
+    let mut held: Vec<&str> = Vec::new();
+    loop {
+        let closed: Vec<(u32, u32)> = doors
+            .iter()
+            .filter(|(lock, pos)| Some(*pos) == sealed || !held.contains(&lock.as_str()))
+            .map(|&(_, pos)| pos)
+            .collect();
+        let mut reached = vec![false; w * grid.height()];
+        let mut stack = vec![start];
+        reached[start.1 * w + start.0] = !closed.contains(&(start.0 as u32, start.1 as u32));
+        if !reached[start.1 * w + start.0] {
+            return reached;
+        }
+        while let Some((x, y)) = stack.pop() {
+            for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if !grid.get(nx, ny).is_some_and(|t| t.is_floor()) {
+                    continue;
+                }
+                let i = ny as usize * w + nx as usize;
+                if !reached[i] && !closed.contains(&(nx as u32, ny as u32)) {
+                    reached[i] = true;
+                    stack.push((nx as usize, ny as usize));
+                }
+            }
+        }
+        let before = held.len();
+        for (lock, (x, y)) in keys {
+            if reached[*y as usize * w + *x as usize] && !held.contains(&lock.as_str()) {
+                held.push(lock);
+            }
+        }
+        if held.len() == before {
+            return reached;
+        }
+    }
+}
+
+/// Constraint that fails on any [`validate_locks`] issue; details list
+/// each issue message.
+pub struct LockConstraint;
+
+impl Constraint for LockConstraint {
+    fn id(&self) -> &'static str {
+        "lock_and_key"
+    }
+
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Semantic
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> ConstraintResult {
+        let Some(semantic) = ctx.semantic else {
+            return ConstraintResult::fail().with_detail("semantic", "missing");
+        };
+        let issues = validate_locks(ctx.grid, semantic);
+        if issues.is_empty() {
+            return ConstraintResult::pass();
+        }
+        let mut result = ConstraintResult::fail().with_detail("issues", issues.len().to_string());
+        for (i, issue) in issues.iter().enumerate() {
+            result = result.with_detail(format!("issue_{}", i), issue.to_string());
+        }
+        result
+    }
+}
+
+/// Metadata key naming the player a spawn or resource marker belongs to.
+pub const PLAYER_KEY: &str = "player";
+/// Tag of resource markers weighed by [`spawn_imbalance`].
+pub const RESOURCE_TAG: &str = "resource";
+
+/// How unfairly spawn markers are placed.
+///
+/// Each spawn gets a signature: its sorted path lengths to the other
+/// spawns, then to every [`RESOURCE_TAG`] marker. Returns the largest
+/// difference between matching entries of any two signatures (0 is
+/// perfectly balanced), or `None` if some spawn can't reach a marker.
+pub fn spawn_imbalance(grid: &Grid<Tile>, semantic: &semantic::SemanticLayers) -> Option<usize> {
+    let spawns: Vec<(usize, usize)> = semantic
+        .markers
+        .iter()
+        .filter(|m| m.marker_type == semantic::MarkerType::Spawn)
+        .map(|m| (m.x as usize, m.y as usize))
+        .collect();
+    let resources: Vec<(usize, usize)> = semantic
+        .markers
+        .iter()
+        .filter(|m| m.tag() == RESOURCE_TAG)
+        .map(|m| (m.x as usize, m.y as usize))
+        .collect();
+
+    let mut signatures = Vec::with_capacity(spawns.len());
+    for (i, &spawn) in spawns.iter().enumerate() {
+        let dist = crate::effects::dijkstra_map(grid, &[spawn]);
+        let lookup = |&(x, y): &(usize, usize)| {
+            dist.get(y)
+                .and_then(|row| row.get(x))
+                .copied()
+                .filter(|&d| d != u32::MAX)
+        };
+        let mut to_spawns = Vec::new();
+        for (j, other) in spawns.iter().enumerate() {
+            if i != j {
+                to_spawns.push(lookup(other)?);
+            }
+        }
+        let mut to_resources = resources.iter().map(lookup).collect::<Option<Vec<_>>>()?;
+        to_spawns.sort_unstable();
+        to_resources.sort_unstable();
+        to_spawns.extend(to_resources);
+        signatures.push(to_spawns);
+    }
+
+    let Some(first) = signatures.first() else {
+        return Some(0);
+    };
+    let mut worst = 0;
+    for signature in &signatures[1..] {
+        for (a, b) in first.iter().zip(signature) {
+            worst = worst.max(a.abs_diff(*b) as usize);
+        }
+    }
+    Some(worst)
+}
+
+/// Constraint that fails when [`spawn_imbalance`] exceeds `max_imbalance`
+/// or a spawn is cut off; details give the `imbalance`.
+pub struct SpawnBalanceConstraint {
+    /// Largest allowed path-length difference, in cells.
+    pub max_imbalance: usize,
+}
+
+impl Constraint for SpawnBalanceConstraint {
+    fn id(&self) -> &'static str {
+        "spawn_balance"
+    }
+
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Semantic
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> ConstraintResult {
+        let Some(semantic) = ctx.semantic else {
+            return ConstraintResult::fail().with_detail("semantic", "missing");
+        };
+        match spawn_imbalance(ctx.grid, semantic) {
+            Some(imbalance) if imbalance <= self.max_imbalance => {
+                ConstraintResult::pass().with_detail("imbalance", imbalance.to_string())
+            }
+            Some(imbalance) => {
+                ConstraintResult::fail().with_detail("imbalance", imbalance.to_string())
+            }
+            None => ConstraintResult::fail().with_detail("imbalance", "unreachable"),
+        }
+    }
+}
+
+/// Constraint that fails unless the map has `count` exits on as many
+/// different edges with an [`exits::balance`](crate::exits::balance)
+/// spread within `tolerance`; details give `exits`, `edges`, and `spread`.
+pub struct BalancedExitsConstraint {
+    pub count: usize,
+    /// Largest allowed `(longest - shortest) / longest` path distance.
+    pub tolerance: f32,
+    /// Cells from an edge that count as on it.
+    pub edge_margin: usize,
+}
+
+impl BalancedExitsConstraint {
+    /// Checks what `config` asks [`place_multi`](crate::exits::place_multi)
+    /// for.
+    pub fn new(config: &crate::exits::MultiExitConfig) -> Self {
+        Self {
+            count: config.count.min(4),
+            tolerance: config.tolerance,
+            edge_margin: config.edge_margin,
+        }
+    }
+}
+
+impl Constraint for BalancedExitsConstraint {
+    fn id(&self) -> &'static str {
+        "balanced_exits"
+    }
+
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Semantic
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> ConstraintResult {
+        let Some(semantic) = ctx.semantic else {
+            return ConstraintResult::fail().with_detail("semantic", "missing");
+        };
+        let Some(balance) = crate::exits::balance(ctx.grid, semantic, self.edge_margin) else {
+            return ConstraintResult::fail().with_detail("spawn", "missing");
+        };
+        let passed = self.count == 0
+            || (balance.exits >= self.count
+                && balance.edges >= self.count
+                && balance.spread.is_some_and(|s| s <= self.tolerance));
+        let result = if passed {
+            ConstraintResult::pass()
+        } else {
+            ConstraintResult::fail()
+        };
+        result
+            .with_detail("exits", balance.exits.to_string())
+            .with_detail("edges", balance.edges.to_string())
+            .with_detail(
+                "spread",
+                balance
+                    .spread
+                    .map_or("unreachable".to_string(), |s| format!("{:.3}", s)),
+            )
+    }
+}
+
+/// Configuration for [`spawn_safety_issues`] and [`enforce_spawn_safety`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnSafetyConfig {
+    /// Radius of the clear area around each spawn. Default: 3.
+    pub radius: usize,
+    /// Marker tags that count as hazards, matched case-insensitively as
+    /// substrings. Encounter and boss markers always count. Default:
+    /// `enemy`, `trap`.
+    pub hazard_tags: Vec<String>,
+}
+
+impl Default for SpawnSafetyConfig {
+    fn default() -> Self {
+        Self {
+            radius: 3,
+            hazard_tags: vec!["enemy".to_string(), "trap".to_string()],
+        }
+    }
+}
+
+impl SpawnSafetyConfig {
+    /// Sets `radius`.
+    pub fn with_radius(mut self, radius: usize) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets `hazard_tags`.
+    pub fn with_hazard_tags(mut self, tags: Vec<String>) -> Self {
+        self.hazard_tags = tags;
+        self
+    }
+
+    fn is_hazard(&self, marker: &semantic::Marker) -> bool {
+        is_hazard(marker, &self.hazard_tags)
+    }
+}
+
+/// Encounter and boss markers, and custom markers whose tag contains one of
+/// `tags` (case-insensitively).
+fn is_hazard(marker: &semantic::Marker, tags: &[String]) -> bool {
+    match &marker.marker_type {
+        semantic::MarkerType::EncounterZone { .. } | semantic::MarkerType::BossRoom => true,
+        semantic::MarkerType::Custom(tag) => {
+            let tag = tag.to_ascii_lowercase();
+            tags.iter().any(|h| tag.contains(&h.to_ascii_lowercase()))
+        }
+        _ => false,
+    }
+}
+
+/// A spawn-safety problem found by [`spawn_safety_issues`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnSafetyIssue {
+    /// Cells of the safe area around `spawn` that aren't floor.
+    Blocked { spawn: (u32, u32), cells: usize },
+    /// A hazard marker inside the safe area around `spawn`.
+    Hazard {
+        spawn: (u32, u32),
+        marker: (u32, u32),
+        tag: String,
+    },
+}
+
+impl std::fmt::Display for SpawnSafetyIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blocked {
+                spawn: (x, y),
+                cells,
+            } => write!(
+                f,
+                "{} cells around the spawn at ({}, {}) aren't walkable; clear them or move the spawn",
+                cells, x, y
+            ),
+            Self::Hazard {
+                spawn: (sx, sy),
+                marker: (x, y),
+                tag,
+            } => write!(
+                f,
+                "'{}' at ({}, {}) is too close to the spawn at ({}, {}); move it away",
+                tag, x, y, sx, sy
+            ),
+        }
+    }
+}
+
+/// Cells that must stay clear around `spawn`: the disc of `radius`, kept
+/// off the outer border ring. Where the border cuts the disc off, the area
+/// grows away from it to the same number of cells (or the whole interior
+/// on tiny grids).
+pub fn spawn_safe_area(
+    grid: &Grid<Tile>,
+    spawn: (usize, usize),
+    radius: usize,
+) -> Vec<(usize, usize)> {
+    let r = radius as i64;
+    let target = (-r..=r)
+        .flat_map(|dy| (-r..=r).map(move |dx| dx * dx + dy * dy))
+        .filter(|&d| d <= r * r)
+        .count();
+    let (w, h) = (grid.width(), grid.height());
+    let mut cells: Vec<(i64, usize, usize)> = (1..h.saturating_sub(1))
+        .flat_map(|y| (1..w.saturating_sub(1)).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let (dx, dy) = (x as i64 - spawn.0 as i64, y as i64 - spawn.1 as i64);
+            (dx * dx + dy * dy, y, x)
+        })
+        .collect();
+    cells.sort_unstable();
+    cells.truncate(target);
+    cells.into_iter().map(|(_, y, x)| (x, y)).collect()
+}
+
+/// Checks that every spawn (or `PlayerStart`) marker has a walkable,
+/// hazard-free [`spawn_safe_area`].
+pub fn spawn_safety_issues(
+    grid: &Grid<Tile>,
+    semantic: &semantic::SemanticLayers,
+    config: &SpawnSafetyConfig,
+) -> Vec<SpawnSafetyIssue> {
+    let mut issues = Vec::new();
+    for spawn in spawn_positions(grid, semantic) {
+        let area = spawn_safe_area(grid, (spawn.0 as usize, spawn.1 as usize), config.radius);
+        let blocked = area.iter().filter(|&&c| !grid[c].is_floor()).count();
+        if blocked > 0 {
+            issues.push(SpawnSafetyIssue::Blocked {
+                spawn,
+                cells: blocked,
+            });
+        }
+        for m in &semantic.markers {
+            if config.is_hazard(m) && area.contains(&(m.x as usize, m.y as usize)) {
+                issues.push(SpawnSafetyIssue::Hazard {
+                    spawn,
+                    marker: (m.x, m.y),
+                    tag: m.tag(),
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// What [`enforce_spawn_safety`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpawnSafetyRepair {
+    /// Cells carved to floor.
+    pub carved: Vec<(usize, usize)>,
+    /// Hazard markers moved out of a safe area, as `(from, to)`.
+    pub moved: Vec<((u32, u32), (u32, u32))>,
+    /// Hazard markers dropped because no floor outside the safe areas was
+    /// reachable from them.
+    pub removed: usize,
+}
+
+/// Repairs every [`spawn_safety_issues`] issue: carves each spawn's
+/// [`spawn_safe_area`] to floor and moves hazard markers in it to the
+/// nearest floor cell outside all safe areas.
+///
+/// The outer border ring is never carved, so the map stays sealed.
+/// `semantic.masks.walkable` follows the carved cells.
+pub fn enforce_spawn_safety(
+    grid: &mut Grid<Tile>,
+    semantic: &mut semantic::SemanticLayers,
+    config: &SpawnSafetyConfig,
+) -> SpawnSafetyRepair {
+    let (w, h) = (grid.width(), grid.height());
+    let mut safe = vec![false; w * h];
+    for (sx, sy) in spawn_positions(grid, semantic) {
+        for (x, y) in spawn_safe_area(grid, (sx as usize, sy as usize), config.radius) {
+            safe[y * w + x] = true;
+        }
+    }
+
+    let mut repair = SpawnSafetyRepair::default();
+    let masks_fit = semantic.masks.width == w && semantic.masks.height == h;
+    for i in (0..w * h).filter(|&i| safe[i]) {
+        let (x, y) = (i % w, i / w);
+        if !grid[(x, y)].is_floor() {
+            grid.set(x as i32, y as i32, Tile::Floor);
+            if masks_fit {
+                semantic.masks.walkable[y][x] = true;
+            }
+            repair.carved.push((x, y));
+        }
+    }
+
+    let grid = &*grid;
+    semantic.markers.retain_mut(|m| {
+        let from = (m.x as usize, m.y as usize);
+        if !config.is_hazard(m) || from.0 >= w || from.1 >= h || !safe[from.1 * w + from.0] {
+            return true;
+        }
+        match nearest_unsafe_floor(grid, from, &safe) {
+            Some((x, y)) => {
+                repair.moved.push(((m.x, m.y), (x as u32, y as u32)));
+                m.x = x as u32;
+                m.y = y as u32;
+                true
+            }
+            None => {
+                repair.removed += 1;
+                false
+            }
+        }
+    });
+    repair
+}
+
+/// Positions of spawn markers on the grid.
+fn spawn_positions(grid: &Grid<Tile>, semantic: &semantic::SemanticLayers) -> Vec<(u32, u32)> {
+    semantic
+        .markers
+        .iter()
+        .filter(|m| m.is_spawn())
+        .filter(|m| (m.x as usize) < grid.width() && (m.y as usize) < grid.height())
+        .map(|m| (m.x, m.y))
+        .collect()
+}
+
+/// Closest floor cell outside `safe`, walking 4-connected floor from
+/// `start`.
+fn nearest_unsafe_floor(
+    grid: &Grid<Tile>,
+    start: (usize, usize),
+    safe: &[bool],
+) -> Option<(usize, usize)> {
+    let w = grid.width();
+    let mut seen = vec![false; safe.len()];
+    seen[start.1 * w + start.0] = true;
+    let mut queue = std::collections::VecDeque::from([start]);
+    while let Some((x, y)) = queue.pop_front() {
+        if !safe[y * w + x] && grid[(x, y)].is_floor() {
+            return Some((x, y));
+        }
+        for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if !grid.get(nx, ny).is_some_and(|t| t.is_floor()) {
+                continue;
+            }
+            let i = ny as usize * w + nx as usize;
+            if !seen[i] {
+                seen[i] = true;
+                queue.push_back((nx as usize, ny as usize));
+            }
+        }
+    }
+    None
+}
+
+/// Constraint that fails on any [`spawn_safety_issues`] issue; details
+/// list each issue message. [`enforce_spawn_safety`] repairs it.
+pub struct SpawnSafetyConstraint {
+    pub config: SpawnSafetyConfig,
+}
+
+impl Constraint for SpawnSafetyConstraint {
+    fn id(&self) -> &'static str {
+        "spawn_safety"
+    }
+
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Semantic
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> ConstraintResult {
+        let Some(semantic) = ctx.semantic else {
+            return ConstraintResult::fail().with_detail("semantic", "missing");
+        };
+        let issues = spawn_safety_issues(ctx.grid, semantic, &self.config);
+        if issues.is_empty() {
+            return ConstraintResult::pass();
+        }
+        let mut result = ConstraintResult::fail().with_detail("issues", issues.len().to_string());
+        for (i, issue) in issues.iter().enumerate() {
+            result = result.with_detail(format!("issue_{}", i), issue.to_string());
+        }
+        result
+    }
+}
+
+/// Configuration for [`danger_field`] and [`safest_route`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DangerConfig {
+    /// Walking distance at which a hazard's danger fades to zero. Default: 6.
+    pub radius: usize,
+    /// Marker tags that count as hazards, matched case-insensitively as
+    /// substrings. Encounter and boss markers always count. Default:
+    /// `enemy`, `trap`.
+    pub hazard_tags: Vec<String>,
+}
+
+impl Default for DangerConfig {
+    fn default() -> Self {
+        Self {
+            radius: 6,
+            hazard_tags: vec!["enemy".to_string(), "trap".to_string()],
+        }
+    }
+}
+
+impl DangerConfig {
+    /// Sets `radius`.
+    pub fn with_radius(mut self, radius: usize) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets `hazard_tags`.
+    pub fn with_hazard_tags(mut self, tags: Vec<String>) -> Self {
+        self.hazard_tags = tags;
+        self
+    }
+}
+
+/// Per-cell danger from [`danger_field`].
+#[derive(Debug, Clone)]
+pub struct DangerField {
+    /// Summed danger, indexed `[y][x]`; 0 on walls and out of reach.
+    pub danger: Vec<Vec<f32>>,
+}
+
+/// A route found by [`DangerField::safest_path`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafePath {
+    /// Cells from start to end, 4-connected.
+    pub path: Vec<(usize, usize)>,
+    /// Highest danger on the path.
+    pub max_danger: f32,
+    /// Danger summed over the path.
+    pub total_danger: f32,
+}
+
+impl SafePath {
+    /// Peak danger, then total danger, then length.
+    fn safer_than(&self, other: &SafePath) -> bool {
+        self.max_danger
+            .total_cmp(&other.max_danger)
+            .then(self.total_danger.total_cmp(&other.total_danger))
+            .then(self.path.len().cmp(&other.path.len()))
+            .is_lt()
+    }
+}
+
+impl DangerField {
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        self.danger
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Highest danger anywhere.
+    pub fn max(&self) -> f32 {
+        self.danger.iter().flatten().copied().fold(0.0, f32::max)
+    }
+
+    /// The safest floor path from `start` to `end`: the lowest possible
+    /// peak danger first, then the least total danger, then the fewest
+    /// steps. `None` if either end is not floor or they aren't connected.
+    pub fn safest_path(
+        &self,
+        grid: &Grid<Tile>,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Option<SafePath> {
+        let floor =
+            |(x, y): (usize, usize)| grid.get(x as i32, y as i32).is_some_and(|t| t.is_floor());
+        if !floor(start) || !floor(end) {
+            return None;
+        }
+        let danger = |(x, y): (usize, usize)| self.get(x, y);
+
+        // Lowest reachable peak (bottleneck search), then a least-danger
+        // search confined to cells at or under it.
+        let ((ceiling, _), _) = search(
+            grid,
+            start,
+            end,
+            |_| true,
+            |cost: &PathCost, cell| (cost.0.max(danger(cell)), 0),
+        )?;
+        let (cost, path) = search(
+            grid,
+            start,
+            end,
+            |cell| danger(cell) <= ceiling,
+            |cost: &PathCost, cell| (cost.0 + danger(cell), cost.1 + 1),
+        )?;
+        Some(SafePath {
+            path,
+            max_danger: ceiling,
+            total_danger: cost.0,
+        })
+    }
+}
+
+/// Danger (peak or sum) and step count along a path.
+type PathCost = (f32, u32);
+
+/// Dijkstra over 4-connected floor with a monotone cost `extend`; returns
+/// the cost at `end` and the path. Costs start at `start`'s own extension.
+fn search(
+    grid: &Grid<Tile>,
+    start: (usize, usize),
+    end: (usize, usize),
+    allowed: impl Fn((usize, usize)) -> bool,
+    extend: impl Fn(&PathCost, (usize, usize)) -> PathCost,
+) -> Option<(PathCost, Vec<(usize, usize)>)> {
+    use std::cmp::{Ordering, Reverse};
+    use std::collections::BinaryHeap;
+
+    #[derive(PartialEq)]
+    struct Key(PathCost, usize);
+    impl Eq for Key {}
+    impl Ord for Key {
+        fn cmp(&self, other: &Self) -> Ordering {
+            (self.0 .0.total_cmp(&other.0 .0))
+                .then(self.0 .1.cmp(&other.0 .1))
+                .then(self.1.cmp(&other.1))
+        }
+    }
+    impl PartialOrd for Key {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    let (w, h) = (grid.width(), grid.height());
+    let mut best: Vec<Option<PathCost>> = vec![None; w * h];
+    let mut parent = vec![usize::MAX; w * h];
+    let first = extend(&(0.0, 0), start);
+    best[start.1 * w + start.0] = Some(first);
+    let mut heap = BinaryHeap::from([Reverse(Key(first, start.1 * w + start.0))]);
+    while let Some(Reverse(Key(cost, i))) = heap.pop() {
+        if best[i].is_some_and(|b| Key(b, i) < Key(cost, i)) {
+            continue;
+        }
+        let (x, y) = (i % w, i / w);
+        if (x, y) == end {
+            let mut path = vec![end];
+            let mut at = i;
+            while parent[at] != usize::MAX {
+                at = parent[at];
+                path.push((at % w, at / w));
+            }
+            path.reverse();
+            return Some((cost, path));
+        }
+        for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if !grid.get(nx, ny).is_some_and(|t| t.is_floor()) {
+                continue;
+            }
+            let next = (nx as usize, ny as usize);
+            if !allowed(next) {
+                continue;
+            }
+            let j = next.1 * w + next.0;
+            let candidate = extend(&cost, next);
+            if best[j].is_none_or(|b| Key(candidate, j) < Key(b, j)) {
+                best[j] = Some(candidate);
+                parent[j] = i;
+                heap.push(Reverse(Key(candidate, j)));
+            }
+        }
+    }
+    None
+}
+
+/// Sums distance-decayed danger from every hazard marker.
+///
+/// Each hazard spreads over the floor it can walk to (walls block it),
+/// contributing `weight * (1 - d / (radius + 1))` at walking distance `d`.
+/// The weight is the difficulty for encounter zones (at least 1) and 1
+/// otherwise.
+pub fn danger_field(
+    grid: &Grid<Tile>,
+    semantic: &semantic::SemanticLayers,
+    config: &DangerConfig,
+) -> DangerField {
+    let (w, h) = (grid.width(), grid.height());
+    let mut danger = vec![vec![0.0f32; w]; h];
+    let reach = config.radius as f32 + 1.0;
+    let mut dist = vec![usize::MAX; w * h];
+    for marker in &semantic.markers {
+        let (mx, my) = (marker.x as usize, marker.y as usize);
+        if !is_hazard(marker, &config.hazard_tags)
+            || mx >= w
+            || my >= h
+            || !grid[(mx, my)].is_floor()
+        {
+            continue;
+        }
+        let weight = match marker.marker_type {
+            semantic::MarkerType::EncounterZone { difficulty } => difficulty.max(1) as f32,
+            _ => 1.0,
+        };
+        dist.fill(usize::MAX);
+        dist[my * w + mx] = 0;
+        let mut queue = std::collections::VecDeque::from([(mx, my)]);
+        while let Some((x, y)) = queue.pop_front() {
+            let d = dist[y * w + x];
+            danger[y][x] += weight * (1.0 - d as f32 / reach);
+            if d >= config.radius {
+                continue;
+            }
+            for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if !grid.get(nx, ny).is_some_and(|t| t.is_floor()) {
+                    continue;
+                }
+                let i = ny as usize * w + nx as usize;
+                if dist[i] == usize::MAX {
+                    dist[i] = d + 1;
+                    queue.push_back((nx as usize, ny as usize));
+                }
+            }
+        }
+    }
+    DangerField { danger }
+}
+
+/// The safest path from any spawn marker to any [`Exit`] (or
+/// `"Exit"`-tagged) marker under [`danger_field`], comparing routes by
+/// [`DangerField::safest_path`]'s order. `None` if there is no spawn, no
+/// exit, or no connecting floor.
+///
+/// [`Exit`]: semantic::MarkerType::Exit
+pub fn safest_route(
+    grid: &Grid<Tile>,
+    semantic: &semantic::SemanticLayers,
+    config: &DangerConfig,
+) -> Option<(DangerField, SafePath)> {
+    let field = danger_field(grid, semantic, config);
+    let exits: Vec<(usize, usize)> = semantic
+        .exits()
+        .map(|m| (m.x as usize, m.y as usize))
+        .collect();
+    let mut best: Option<SafePath> = None;
+    for (sx, sy) in spawn_positions(grid, semantic) {
+        for &exit in &exits {
+            let Some(path) = field.safest_path(grid, (sx as usize, sy as usize), exit) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|b| path.safer_than(b)) {
+                best = Some(path);
+            }
+        }
+    }
+    best.map(|path| (field, path))
+}
+
+/// Constraint that a stealth route exists: fails unless [`safest_route`]
+/// finds a spawn-to-exit path whose peak danger is at most `max_danger`.
+/// Details give the route's `max_danger`, `total_danger`, and `length`.
+pub struct StealthRouteConstraint {
+    pub config: DangerConfig,
+    /// Highest danger the route may pass through; 0 means untouched by any
+    /// hazard.
+    pub max_danger: f32,
+}
+
+impl Constraint for StealthRouteConstraint {
+    fn id(&self) -> &'static str {
+        "stealth_route"
+    }
+
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Semantic
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> ConstraintResult {
+        let Some(semantic) = ctx.semantic else {
+            return ConstraintResult::fail().with_detail("semantic", "missing");
+        };
+        let Some((_, route)) = safest_route(ctx.grid, semantic, &self.config) else {
+            return ConstraintResult::fail().with_detail("route", "none");
+        };
+        let result = if route.max_danger <= self.max_danger {
+            ConstraintResult::pass()
+        } else {
+            ConstraintResult::fail()
+        };
+        result
+            .with_detail("max_danger", format!("{:.2}", route.max_danger))
+            .with_detail("total_danger", format!("{:.2}", route.total_danger))
+            .with_detail("length", route.path.len().to_string())
+    }
+}
+
+/// Floor regions no spawn marker can walk to, or `None` if there is no
+/// spawn on the grid.
+pub fn unreachable_pockets(
+    grid: &Grid<Tile>,
+    semantic: &semantic::SemanticLayers,
+) -> Option<Vec<Vec<(usize, usize)>>> {
+    let spawns = spawn_positions(grid, semantic);
+    if spawns.is_empty() {
+        return None;
+    }
+    let spawns: Vec<(usize, usize)> = spawns
+        .into_iter()
+        .map(|(x, y)| (x as usize, y as usize))
+        .collect();
+    Some(
+        grid.flood_regions()
+            .into_iter()
+            .filter(|region| !spawns.iter().any(|s| region.contains(s)))
+            .collect(),
+    )
+}
+
+/// Fraction of floor in [`unreachable_pockets`]; `None` without a spawn.
+pub fn unreachable_fraction(grid: &Grid<Tile>, semantic: &semantic::SemanticLayers) -> Option<f32> {
+    let pockets = unreachable_pockets(grid, semantic)?;
+    Some(pocket_fraction(grid, &pockets))
+}
+
+fn pocket_fraction(grid: &Grid<Tile>, pockets: &[Vec<(usize, usize)>]) -> f32 {
+    let floor = grid.count(|t| t.is_floor());
+    let unreachable: usize = pockets.iter().map(Vec::len).sum();
+    if floor == 0 {
+        0.0
+    } else {
+        unreachable as f32 / floor as f32
+    }
+}
+
+/// How [`repair_pockets`] deals with unreachable floor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PocketFix {
+    /// Fill each pocket with wall, dropping markers inside it.
+    #[default]
+    Seal,
+    /// Carve the shortest tunnel from each pocket to reachable floor.
+    Connect,
+}
+
+/// What [`repair_pockets`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PocketRepair {
+    /// Floor cells walled up.
+    pub sealed: Vec<(usize, usize)>,
+    /// Wall cells carved to floor.
+    pub carved: Vec<(usize, usize)>,
+    /// Markers dropped from sealed pockets.
+    pub removed: usize,
+}
+
+/// Makes all floor reachable from the spawn markers, sealing or
+/// connecting each of the [`unreachable_pockets`]. Does nothing without a
+/// spawn.
+///
+/// Tunnels never cut through the outer border ring; a pocket that can't be
+/// reached without doing so is sealed instead. `semantic.masks.walkable`
+/// follows the changed cells.
+pub fn repair_pockets(
+    grid: &mut Grid<Tile>,
+    semantic: &mut semantic::SemanticLayers,
+    fix: PocketFix,
+) -> PocketRepair {
+    let mut repair = PocketRepair::default();
+    while let Some(pocket) = unreachable_pockets(grid, semantic).and_then(|p| p.into_iter().next())
+    {
+        let tunnel = match fix {
+            PocketFix::Seal => None,
+            PocketFix::Connect => tunnel_out(grid, &pocket, semantic),
+        };
+        match tunnel {
+            Some(tunnel) => {
+                for &(x, y) in &tunnel {
+                    grid.set(x as i32, y as i32, Tile::Floor);
+                }
+                repair.carved.extend(tunnel);
+            }
+            None => {
+                for &(x, y) in &pocket {
+                    grid.set(x as i32, y as i32, Tile::Wall);
+                }
+                let before = semantic.markers.len();
+                semantic
+                    .markers
+                    .retain(|m| !pocket.contains(&(m.x as usize, m.y as usize)));
+                repair.removed += before - semantic.markers.len();
+                repair.sealed.extend(pocket);
+            }
+        }
+    }
+
+    let (w, h) = (grid.width(), grid.height());
+    if semantic.masks.width == w && semantic.masks.height == h {
+        for &(x, y) in &repair.sealed {
+            semantic.masks.walkable[y][x] = false;
+        }
+        for &(x, y) in &repair.carved {
+            semantic.masks.walkable[y][x] = true;
+        }
+    }
+    repair
+}
+
+/// Fewest wall cells to carve, staying off the border ring, to join
+/// `pocket` to floor reachable from a spawn.
+fn tunnel_out(
+    grid: &Grid<Tile>,
+    pocket: &[(usize, usize)],
+    semantic: &semantic::SemanticLayers,
+) -> Option<Vec<(usize, usize)>> {
+    let (w, h) = (grid.width(), grid.height());
+    let mut reachable = vec![false; w * h];
+    for (sx, sy) in spawn_positions(grid, semantic) {
+        let region = grid.flood_fill(sx as usize, sy as usize);
+        for (x, y) in region {
+            reachable[y * w + x] = true;
+        }
+    }
+
+    // 0-1 BFS: walls cost 1, floor 0.
+    let mut cost = vec![usize::MAX; w * h];
+    let mut parent = vec![usize::MAX; w * h];
+    let mut queue = std::collections::VecDeque::new();
+    for &(x, y) in pocket {
+        cost[y * w + x] = 0;
+        queue.push_back((x, y));
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let i = y * w + x;
+        if reachable[i] {
+            let mut tunnel = Vec::new();
+            let mut at = i;
+            while at != usize::MAX {
+                let (ax, ay) = (at % w, at / w);
+                if !grid[(ax, ay)].is_floor() {
+                    tunnel.push((ax, ay));
+                }
+                at = parent[at];
+            }
+            tunnel.reverse();
+            return Some(tunnel);
+        }
+        for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 1 || ny < 1 || nx as usize + 1 >= w || ny as usize + 1 >= h {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            let step = usize::from(!grid[(nx, ny)].is_floor());
+            let j = ny * w + nx;
+            if cost[i] + step < cost[j] {
+                cost[j] = cost[i] + step;
+                parent[j] = i;
+                if step == 0 {
+                    queue.push_front((nx, ny));
+                } else {
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Constraint that fails when more than `max_unreachable` of the floor
+/// can't be walked to from a spawn marker (or there is no spawn); details
+/// give the `unreachable` fraction and `pockets` count.
+/// [`repair_pockets`] repairs it.
+pub struct ReachabilityConstraint {
+    /// Largest allowed unreachable fraction of floor, `0..=1`.
+    pub max_unreachable: f32,
+}
+
+impl Constraint for ReachabilityConstraint {
+    fn id(&self) -> &'static str {
+        "reachability"
+    }
+
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Semantic
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> ConstraintResult {
+        let Some(semantic) = ctx.semantic else {
+            return ConstraintResult::fail().with_detail("semantic", "missing");
+        };
+        let Some(pockets) = unreachable_pockets(ctx.grid, semantic) else {
+            return ConstraintResult::fail().with_detail("spawn", "missing");
+        };
+        let fraction = pocket_fraction(ctx.grid, &pockets);
+        let result = if fraction <= self.max_unreachable {
+            ConstraintResult::pass()
+        } else {
+            ConstraintResult::fail()
+        };
+        result
+            .with_detail("unreachable", format!("{:.3}", fraction))
+            .with_detail("pockets", pockets.len().to_string())
+    }
+}
@@ -0,0 +1,468 @@
+//! Room furnishing with small prefabs.
+//!
+//! [`Decorator`] finds rooms (open areas at least three cells wide, see
+//! [`find_rooms`]) and places furniture prefabs in them by rule:
+//! against a wall, in the open, facing the nearest door, or anywhere.
+//!
+//! Furniture prefabs use the normal [`Prefab`] legend: cells with a marker
+//! are furniture items named by the marker, plain floor cells (`.`) are
+//! clearance that must stay free (the space in front of a shelf, say), and
+//! anything else is ignored. Row 0 is the back of the piece; rotations
+//! turn it to face other directions. Door cells and their neighbors stay
+//! clear, and a placement is rejected if it would cut off part of the room.
+//!
+//! ```rust
+//! use terrain_forge::decoration::Decorator;
+//! use terrain_forge::{algorithms, Grid, Rng};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("bsp").unwrap().generate(&mut grid, 6);
+//! let decoration = Decorator::default().decorate(&grid, &mut Rng::new(6));
+//! for marker in &decoration.markers {
+//!     assert!(grid[(marker.x as usize, marker.y as usize)].is_floor());
+//! }
+//! ```
+
+use crate::algorithms::{Prefab, PrefabData, PrefabLegendEntry, PrefabTransform};
+use crate::semantic::{Marker, SemanticLayers};
+use crate::{Grid, Rng, Tile};
+use std::collections::HashSet;
+
+/// Name of the mask layer written by [`Decoration::apply`].
+pub const DECORATION_MASK: &str = "decoration";
+
+/// Where a furniture piece may go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Back row flush against a wall.
+    AgainstWall,
+    /// Not touching any wall.
+    Center,
+    /// Front turned toward the room's nearest door.
+    FacingDoor,
+    /// Any free floor.
+    Anywhere,
+}
+
+/// A furniture prefab and its placement rule.
+#[derive(Debug, Clone)]
+pub struct DecorRule {
+    pub prefab: Prefab,
+    pub placement: Placement,
+    /// Most copies per room. Default: 1.
+    pub max_per_room: usize,
+    /// Chance of attempting each copy. Default: 1.0.
+    pub chance: f64,
+}
+
+impl DecorRule {
+    pub fn new(prefab: Prefab, placement: Placement) -> Self {
+        Self {
+            prefab,
+            placement,
+            max_per_room: 1,
+            chance: 1.0,
+        }
+    }
+
+    /// Builds the prefab from a pattern and `(symbol, item)` pairs; `.` is
+    /// clearance.
+    pub fn furniture(
+        name: &str,
+        pattern: &[&str],
+        items: &[(char, &str)],
+        placement: Placement,
+    ) -> Self {
+        let legend = items
+            .iter()
+            .map(|&(symbol, item)| {
+                let entry = PrefabLegendEntry {
+                    tile: Some("floor".to_string()),
+                    marker: Some(item.to_string()),
+                    ..Default::default()
+                };
+                (symbol.to_string(), entry)
+            })
+            .collect();
+        let prefab = Prefab::from_data(PrefabData {
+            name: name.to_string(),
+            width: pattern.first().map_or(0, |row| row.len()),
+            height: pattern.len(),
+            pattern: pattern.iter().map(|row| row.to_string()).collect(),
+            weight: 1.0,
+            tags: vec!["furniture".to_string()],
+            legend: Some(legend),
+        });
+        Self::new(prefab, placement)
+    }
+
+    /// Sets `max_per_room`.
+    pub fn with_max_per_room(mut self, max_per_room: usize) -> Self {
+        self.max_per_room = max_per_room;
+        self
+    }
+
+    /// Sets `chance`.
+    pub fn with_chance(mut self, chance: f64) -> Self {
+        self.chance = chance;
+        self
+    }
+}
+
+/// Configuration for [`Decorator`].
+#[derive(Debug, Clone)]
+pub struct DecorConfig {
+    /// Rules, tried in order for each room.
+    pub rules: Vec<DecorRule>,
+    /// Rooms smaller than this stay empty. Default: 12.
+    pub min_room_area: usize,
+}
+
+impl Default for DecorConfig {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                DecorRule::furniture(
+                    "throne",
+                    &["T", "."],
+                    &[('T', "throne")],
+                    Placement::FacingDoor,
+                )
+                .with_chance(0.3),
+                DecorRule::furniture(
+                    "bookshelf",
+                    &["SS", ".."],
+                    &[('S', "bookshelf")],
+                    Placement::AgainstWall,
+                )
+                .with_max_per_room(2)
+                .with_chance(0.6),
+                DecorRule::furniture(
+                    "table",
+                    &["CTC"],
+                    &[('C', "chair"), ('T', "table")],
+                    Placement::Center,
+                )
+                .with_chance(0.7),
+                DecorRule::furniture("chest", &["X"], &[('X', "chest")], Placement::AgainstWall)
+                    .with_chance(0.5),
+                DecorRule::furniture("barrel", &["B"], &[('B', "barrel")], Placement::AgainstWall)
+                    .with_max_per_room(3)
+                    .with_chance(0.5),
+            ],
+            min_room_area: 12,
+        }
+    }
+}
+
+impl DecorConfig {
+    /// Replaces the rule list.
+    pub fn with_rules(mut self, rules: Vec<DecorRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+}
+
+/// A detected room.
+#[derive(Debug, Clone)]
+pub struct Room {
+    pub cells: Vec<(usize, usize)>,
+    /// Room cells next to floor outside the room.
+    pub doors: Vec<(usize, usize)>,
+}
+
+/// Furnishing result.
+#[derive(Debug, Clone)]
+pub struct Decoration {
+    /// Item name per cell, indexed `[y][x]`.
+    pub items: Vec<Vec<Option<String>>>,
+    /// One marker per item cell, tagged with the item name.
+    pub markers: Vec<Marker>,
+    pub rooms: Vec<Room>,
+}
+
+impl Decoration {
+    pub fn item(&self, x: usize, y: usize) -> Option<&str> {
+        self.items.get(y)?.get(x)?.as_deref()
+    }
+
+    /// Adds the markers and marks item cells in the [`DECORATION_MASK`]
+    /// layer and `no_spawn`.
+    pub fn apply(&self, semantic: &mut SemanticLayers) {
+        for marker in &self.markers {
+            let (x, y) = (marker.x as usize, marker.y as usize);
+            semantic.masks.set_named(DECORATION_MASK, x, y, true);
+            if let Some(cell) = semantic
+                .masks
+                .no_spawn
+                .get_mut(y)
+                .and_then(|r| r.get_mut(x))
+            {
+                *cell = true;
+            }
+        }
+        semantic.markers.extend(self.markers.iter().cloned());
+    }
+}
+
+/// Furnishes rooms.
+#[derive(Debug, Clone, Default)]
+pub struct Decorator {
+    config: DecorConfig,
+}
+
+/// A candidate placement: transformed prefab cells as absolute positions.
+struct Fit {
+    items: Vec<((usize, usize), String)>,
+    clearance: Vec<(usize, usize)>,
+    facing: &'static str,
+}
+
+impl Decorator {
+    pub fn new(config: DecorConfig) -> Self {
+        Self { config }
+    }
+
+    /// Finds rooms in `grid` and furnishes them.
+    pub fn decorate(&self, grid: &Grid<Tile>, rng: &mut Rng) -> Decoration {
+        let (w, h) = (grid.width(), grid.height());
+        let rooms = find_rooms(grid, self.config.min_room_area);
+        let mut items = vec![vec![None; w]; h];
+        let mut markers = Vec::new();
+
+        for (index, room) in rooms.iter().enumerate() {
+            let cells: HashSet<(usize, usize)> = room.cells.iter().copied().collect();
+            let mut reserved: HashSet<(usize, usize)> =
+                room.doors.iter().flat_map(|&(x, y)| around(x, y)).collect();
+            let mut occupied: HashSet<(usize, usize)> = HashSet::new();
+
+            for rule in &self.config.rules {
+                for _ in 0..rule.max_per_room {
+                    if !rng.chance(rule.chance) {
+                        continue;
+                    }
+                    let mut fits = candidates(grid, room, &cells, rule);
+                    rng.shuffle(&mut fits);
+                    let chosen = fits.into_iter().find(|fit| {
+                        let cells_free = fit
+                            .items
+                            .iter()
+                            .map(|(p, _)| p)
+                            .chain(&fit.clearance)
+                            .all(|p| !occupied.contains(p) && !reserved.contains(p));
+                        cells_free && stays_connected(room, &cells, &occupied, fit)
+                    });
+                    let Some(fit) = chosen else { break };
+                    for ((x, y), item) in &fit.items {
+                        occupied.insert((*x, *y));
+                        items[*y][*x] = Some(item.clone());
+                        markers.push(
+                            Marker::with_tag(*x as u32, *y as u32, item.clone())
+                                .with_metadata("prefab", rule.prefab.name.clone())
+                                .with_metadata("facing", fit.facing)
+                                .with_metadata("room", index.to_string()),
+                        );
+                    }
+                    reserved.extend(fit.clearance.iter().copied());
+                }
+            }
+        }
+
+        Decoration {
+            items,
+            markers,
+            rooms,
+        }
+    }
+}
+
+/// Rooms: connected areas of floor cells covered by a fully open 3x3 block,
+/// with at least `min_area` cells.
+pub fn find_rooms(grid: &Grid<Tile>, min_area: usize) -> Vec<Room> {
+    let (w, h) = (grid.width(), grid.height());
+    let floor = |x: i32, y: i32| grid.get(x, y).is_some_and(|t| t.is_floor());
+    let mut open = Grid::new(w, h);
+    for y in 1..h.saturating_sub(1) {
+        for x in 1..w.saturating_sub(1) {
+            let block = (-1..=1).all(|dy| (-1..=1).all(|dx| floor(x as i32 + dx, y as i32 + dy)));
+            if block {
+                for (nx, ny) in around(x, y) {
+                    open.set(nx as i32, ny as i32, Tile::Floor);
+                }
+            }
+        }
+    }
+
+    open.flood_regions()
+        .into_iter()
+        .filter(|cells| cells.len() >= min_area.max(1))
+        .map(|mut cells| {
+            cells.sort_by_key(|&(x, y)| (y, x));
+            let doors = cells
+                .iter()
+                .copied()
+                .filter(|&(x, y)| {
+                    grid.neighbors_4(x, y)
+                        .any(|(nx, ny)| grid[(nx, ny)].is_floor() && !open[(nx, ny)].is_floor())
+                })
+                .collect();
+            Room { cells, doors }
+        })
+        .collect()
+}
+
+/// Every valid position and rotation of `rule`'s prefab in `room`.
+fn candidates(
+    grid: &Grid<Tile>,
+    room: &Room,
+    cells: &HashSet<(usize, usize)>,
+    rule: &DecorRule,
+) -> Vec<Fit> {
+    let prefab = &rule.prefab;
+    let (min_x, min_y, max_x, max_y) = room
+        .cells
+        .iter()
+        .fold((usize::MAX, usize::MAX, 0, 0), |(a, b, c, d), &(x, y)| {
+            (a.min(x), b.min(y), c.max(x), d.max(y))
+        });
+    let wall = |x: i64, y: i64| {
+        x < 0 || y < 0 || !grid.get(x as i32, y as i32).is_some_and(|t| t.is_floor())
+    };
+    let center = room.cells.iter().fold((0.0, 0.0), |(sx, sy), &(x, y)| {
+        (sx + x as f64, sy + y as f64)
+    });
+    let center = (
+        center.0 / room.cells.len() as f64,
+        center.1 / room.cells.len() as f64,
+    );
+    let door = room.doors.iter().min_by(|a, b| {
+        let d =
+            |p: &&(usize, usize)| (p.0 as f64 - center.0).powi(2) + (p.1 as f64 - center.1).powi(2);
+        d(a).total_cmp(&d(b))
+    });
+
+    let mut fits = Vec::new();
+    for rotation in 0..4u8 {
+        let transform = PrefabTransform {
+            rotation,
+            ..Default::default()
+        };
+        // Back is -y before rotation; rotating clockwise turns (dx, dy) into (-dy, dx).
+        let back = (0..rotation).fold((0i64, -1i64), |(dx, dy), _| (-dy, dx));
+        let front = (-back.0, -back.1);
+        let facing = match front {
+            (0, 1) => "south",
+            (-1, 0) => "west",
+            (0, -1) => "north",
+            _ => "east",
+        };
+        if rule.placement == Placement::FacingDoor {
+            let Some(&(dx, dy)) = door else { continue };
+            let to_door = (dx as f64 - center.0, dy as f64 - center.1);
+            let best = [(0i64, 1i64), (-1, 0), (0, -1), (1, 0)]
+                .into_iter()
+                .max_by(|a, b| {
+                    let dot = |v: &(i64, i64)| v.0 as f64 * to_door.0 + v.1 as f64 * to_door.1;
+                    dot(a).total_cmp(&dot(b))
+                });
+            if best != Some(front) {
+                continue;
+            }
+        }
+
+        let cells_of = |ox: usize, oy: usize| {
+            let mut items = Vec::new();
+            let mut clearance = Vec::new();
+            let mut backs = Vec::new();
+            for py in 0..prefab.height {
+                for px in 0..prefab.width {
+                    let Some(cell) = prefab.cell(px, py) else {
+                        continue;
+                    };
+                    let (tx, ty) = transform.apply_to_point((px, py), prefab.width, prefab.height);
+                    let pos = (ox + tx, oy + ty);
+                    if let Some(item) = cell.markers.first() {
+                        items.push((pos, item.clone()));
+                        if py == 0 {
+                            backs.push(pos);
+                        }
+                    } else if cell.tile == Some(Tile::Floor) {
+                        clearance.push(pos);
+                    }
+                }
+            }
+            (items, clearance, backs)
+        };
+
+        for oy in min_y..=max_y {
+            for ox in min_x..=max_x {
+                let (items, clearance, backs) = cells_of(ox, oy);
+                if items.is_empty() {
+                    continue;
+                }
+                let inside = items
+                    .iter()
+                    .map(|(p, _)| p)
+                    .chain(&clearance)
+                    .all(|p| cells.contains(p));
+                if !inside {
+                    continue;
+                }
+                let ok = match rule.placement {
+                    Placement::AgainstWall => {
+                        !backs.is_empty()
+                            && backs
+                                .iter()
+                                .all(|&(x, y)| wall(x as i64 + back.0, y as i64 + back.1))
+                    }
+                    Placement::Center => items
+                        .iter()
+                        .map(|(p, _)| p)
+                        .chain(&clearance)
+                        .all(|&(x, y)| around(x, y).all(|(nx, ny)| !wall(nx as i64, ny as i64))),
+                    Placement::FacingDoor | Placement::Anywhere => true,
+                };
+                if ok {
+                    fits.push(Fit {
+                        items,
+                        clearance,
+                        facing,
+                    });
+                }
+            }
+        }
+    }
+    fits
+}
+
+/// Whether the room's free cells stay in one piece after placing `fit`.
+fn stays_connected(
+    room: &Room,
+    cells: &HashSet<(usize, usize)>,
+    occupied: &HashSet<(usize, usize)>,
+    fit: &Fit,
+) -> bool {
+    let blocked: HashSet<(usize, usize)> = fit.items.iter().map(|(p, _)| *p).collect();
+    let free =
+        |p: &(usize, usize)| cells.contains(p) && !occupied.contains(p) && !blocked.contains(p);
+    let (Some(min_x), Some(min_y)) = (
+        room.cells.iter().map(|p| p.0).min(),
+        room.cells.iter().map(|p| p.1).min(),
+    ) else {
+        return false;
+    };
+    let max_x = room.cells.iter().map(|p| p.0).max().unwrap_or(min_x);
+    let max_y = room.cells.iter().map(|p| p.1).max().unwrap_or(min_y);
+    // The free cells on a grid of the room's bounding box.
+    let mut area = Grid::new(max_x - min_x + 1, max_y - min_y + 1);
+    for p in room.cells.iter().filter(|p| free(p)) {
+        area.set((p.0 - min_x) as i32, (p.1 - min_y) as i32, Tile::Floor);
+    }
+    area.flood_regions().len() == 1
+}
+
+/// The 3x3 block around `(x, y)`, clipped at zero.
+fn around(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    (y.saturating_sub(1)..=y + 1)
+        .flat_map(move |ny| (x.saturating_sub(1)..=x + 1).map(move |nx| (nx, ny)))
+}
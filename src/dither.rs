@@ -0,0 +1,261 @@
+//! Dithered borders between adjacent biomes.
+//!
+//! Class grids such as [`bands::classify`](crate::bands::classify) output
+//! meet along hard contour lines. [`blend`] interleaves two classes across
+//! their shared boundary instead: cells within [`DitherConfig::width`] of
+//! the other class swap to it with a chance that falls from one half at the
+//! boundary to zero at the edge of the transition, and a threshold pattern
+//! decides which cells make the cut.
+//!
+//! The pattern comes from [`thresholds`]: a tiled blue-noise screen built
+//! by void-and-cluster from the seed, so swapped cells spread evenly
+//! without white-noise clumps or the grid artifacts of an ordered Bayer
+//! matrix. With `cluster` above 1 the screen orders whole
+//! `cluster x cluster` blocks at a time, giving green noise: clumps of
+//! cells scattered with blue-noise spacing. The screen uses only IEEE
+//! arithmetic (no `exp` or trig), so the same seed gives the same border
+//! on every platform.
+//!
+//! ```rust
+//! use terrain_forge::dither::{self, DitherConfig};
+//!
+//! // Grass (0) on the west half, rock (1) on the east.
+//! let classes: Vec<Vec<usize>> = (0..32)
+//!     .map(|_| (0..64).map(|x| usize::from(x >= 32)).collect())
+//!     .collect();
+//! let blended = dither::blend(&classes, &0, &1, &DitherConfig::default(), 7);
+//!
+//! // Some grass crosses into the rock and some rock into the grass...
+//! assert!(blended.iter().any(|row| row[33] == 0));
+//! assert!(blended.iter().any(|row| row[30] == 1));
+//! // ...but only within the transition width.
+//! assert!(blended.iter().all(|row| row[20] == 0 && row[44] == 1));
+//! assert_eq!(blended, dither::blend(&classes, &0, &1, &DitherConfig::default(), 7));
+//! ```
+
+use crate::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Configuration for [`blend`] and [`thresholds`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DitherConfig {
+    /// Cells on each side of the boundary that may swap; 0 keeps the hard
+    /// edge. Default: 3.
+    pub width: usize,
+    /// Side of the blocks the screen orders together: 1 gives blue noise,
+    /// larger values green noise. Default: 1.
+    pub cluster: usize,
+    /// Side of the tiled screen, in blocks. Larger tiles repeat less often
+    /// but cost quadratically more to build. Default: 32.
+    pub tile_size: usize,
+}
+
+impl Default for DitherConfig {
+    fn default() -> Self {
+        Self {
+            width: 3,
+            cluster: 1,
+            tile_size: 32,
+        }
+    }
+}
+
+impl DitherConfig {
+    /// Sets `width`.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets `cluster`.
+    pub fn with_cluster(mut self, cluster: usize) -> Self {
+        self.cluster = cluster;
+        self
+    }
+
+    /// Sets `tile_size`.
+    pub fn with_tile_size(mut self, size: usize) -> Self {
+        self.tile_size = size;
+        self
+    }
+}
+
+/// Copy of `classes` with cells of `a` and `b` near their shared boundary
+/// interleaved; other classes are untouched. Distance to the boundary is
+/// counted in 4-connected steps through the cell's own class, so the two
+/// only blend where they actually touch. Rows are indexed `[y][x]`.
+pub fn blend<T: Clone + PartialEq>(
+    classes: &[Vec<T>],
+    a: &T,
+    b: &T,
+    config: &DitherConfig,
+    seed: u64,
+) -> Vec<Vec<T>> {
+    let mut out = classes.to_vec();
+    let h = classes.len();
+    let w = classes.first().map_or(0, Vec::len);
+    if config.width == 0 || a == b || w == 0 {
+        return out;
+    }
+    let screen = thresholds(w, h, config, seed);
+    let to_b = boundary_distance(classes, b, a, config.width);
+    let to_a = boundary_distance(classes, a, b, config.width);
+    let width = config.width as f64;
+    for y in 0..h {
+        for x in 0..w {
+            let (distance, other) = match (to_b[y][x], to_a[y][x]) {
+                (Some(d), _) => (d, b),
+                (_, Some(d)) => (d, a),
+                _ => continue,
+            };
+            // Half the cells swap at the boundary, none past `width`.
+            let chance = 0.5 * (1.0 - (distance as f64 - 0.5) / width);
+            if screen[y][x] < chance {
+                out[y][x] = other.clone();
+            }
+        }
+    }
+    out
+}
+
+/// Dither screen of `width` x `height` thresholds in `[0, 1)`, indexed
+/// `[y][x]`. Thresholds are spread evenly: any fraction `p` of cells lies
+/// below `p`, arranged as blue noise (or green noise with `cluster > 1`).
+pub fn thresholds(width: usize, height: usize, config: &DitherConfig, seed: u64) -> Vec<Vec<f64>> {
+    let size = config.tile_size.max(2);
+    let cluster = config.cluster.max(1);
+    let ranks = void_and_cluster(size, seed);
+    let count = (size * size) as f64;
+    let fine = (cluster * cluster) as f64;
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    let block = ranks[(y / cluster) % size * size + (x / cluster) % size];
+                    // Within a block, the cell's own blue-noise rank breaks
+                    // ties, so blocks fill in one clump at a time.
+                    let cell = ranks[y % size * size + x % size] as f64 / count;
+                    let within = ((cell * fine) as usize).min(cluster * cluster - 1);
+                    (block as f64 + within as f64 / fine) / count
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Steps from each cell of `class` to the nearest `other` cell, walking
+/// through `class` only, up to `limit`.
+fn boundary_distance<T: PartialEq>(
+    classes: &[Vec<T>],
+    other: &T,
+    class: &T,
+    limit: usize,
+) -> Vec<Vec<Option<usize>>> {
+    let h = classes.len();
+    let w = classes.first().map_or(0, Vec::len);
+    let mut distance = vec![vec![None; w]; h];
+    let mut queue = VecDeque::new();
+    for (y, row) in classes.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if cell == other {
+                queue.push_back((x, y, 0));
+            }
+        }
+    }
+    while let Some((x, y, d)) = queue.pop_front() {
+        if d == limit {
+            continue;
+        }
+        for (dx, dy) in [(0i32, -1i32), (0, 1), (1, 0), (-1, 0)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || ny < 0 || nx as usize >= w || ny as usize >= h {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            if classes[ny][nx] == *class && distance[ny][nx].is_none() {
+                distance[ny][nx] = Some(d + 1);
+                queue.push_back((nx, ny, d + 1));
+            }
+        }
+    }
+    distance
+}
+
+/// Blue-noise rank (0..size²) of every cell of a toroidal `size` x `size`
+/// tile, row-major, using Ulichney's void-and-cluster method.
+fn void_and_cluster(size: usize, seed: u64) -> Vec<usize> {
+    let n = size * size;
+    // Rational falloff with a spread of about 1.5 cells; unlike a Gaussian
+    // it needs no `exp`, so energies match bit for bit across platforms.
+    let kernel: Vec<f64> = (0..n)
+        .map(|i| {
+            let wrap = |d: usize| d.min(size - d) as f64;
+            let (dx, dy) = (wrap(i % size), wrap(i / size));
+            1.0 / (1.0 + (dx * dx + dy * dy) / 2.25)
+        })
+        .collect();
+    let mut energy = vec![0.0; n];
+    let mut on = vec![false; n];
+    let toggle = |energy: &mut [f64], on: &mut [bool], cell: usize| {
+        on[cell] = !on[cell];
+        let sign = if on[cell] { 1.0 } else { -1.0 };
+        let (cx, cy) = (cell % size, cell / size);
+        for (i, e) in energy.iter_mut().enumerate() {
+            let dx = (i % size + size - cx) % size;
+            let dy = (i / size + size - cy) % size;
+            *e += sign * kernel[dy * size + dx];
+        }
+    };
+    // Tightest cluster among set cells, or largest void among unset ones;
+    // ties go to the lowest index.
+    let extreme = |energy: &[f64], on: &[bool], set: bool| -> usize {
+        let mut best: Option<usize> = None;
+        for i in (0..n).filter(|&i| on[i] == set) {
+            let better = match best {
+                None => true,
+                Some(j) if set => energy[i] > energy[j],
+                Some(j) => energy[i] < energy[j],
+            };
+            if better {
+                best = Some(i);
+            }
+        }
+        best.unwrap_or(0)
+    };
+
+    // A random tenth of the cells, relaxed until evenly spread.
+    let mut cells: Vec<usize> = (0..n).collect();
+    Rng::new(seed).shuffle(&mut cells);
+    let initial = (n / 10).max(1);
+    for &cell in &cells[..initial] {
+        toggle(&mut energy, &mut on, cell);
+    }
+    for _ in 0..n {
+        let cluster = extreme(&energy, &on, true);
+        toggle(&mut energy, &mut on, cluster);
+        let void = extreme(&energy, &on, false);
+        toggle(&mut energy, &mut on, void);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; n];
+    let (start_energy, start_on) = (energy.clone(), on.clone());
+    for rank in (0..initial).rev() {
+        let cluster = extreme(&energy, &on, true);
+        toggle(&mut energy, &mut on, cluster);
+        ranks[cluster] = rank;
+    }
+    // With a linear kernel the tightest cluster of unset cells is also the
+    // largest void, so one loop fills the rest of the tile.
+    let (mut energy, mut on) = (start_energy, start_on);
+    for rank in initial..n {
+        let void = extreme(&energy, &on, false);
+        toggle(&mut energy, &mut on, void);
+        ranks[void] = rank;
+    }
+    ranks
+}
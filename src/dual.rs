@@ -0,0 +1,334 @@
+//! Dual-grid (corner and edge) views of tile grids.
+//!
+//! Tile grids store one value per cell. Renderers that draw terrain on
+//! cell corners (dual-grid or marching-squares tilesets) or thin walls on
+//! cell edges need the same map in other shapes:
+//!
+//! - [`corner_masks`] gives each of the `(w + 1) x (h + 1)` cell corners a
+//!   4-bit mask of which surrounding cells are floor, the index into a
+//!   16-tile dual-grid tileset. [`from_corner_masks`] undoes it.
+//! - [`wall_edges`] lists every edge between a floor cell and a wall (or
+//!   the map edge) with its orientation and the side the floor is on, and
+//!   [`merge_edges`] joins them into straight runs.
+//! - [`EdgeWalls`] is a thin-wall map: all cells open, walls on edges.
+//!   [`EdgeWalls::to_tiles`] expands it to a `(2w + 1) x (2h + 1)` tile
+//!   grid with walls as full tiles.
+//!
+//! Corner `(x, y)` is the top-left corner of cell `(x, y)`, matching the
+//! border polylines in [`kingdoms`](crate::kingdoms).
+//!
+//! ```rust
+//! use terrain_forge::dual::{self, Orientation};
+//! use terrain_forge::{Grid, Tile};
+//!
+//! let mut grid = Grid::new(4, 3);
+//! grid.fill_rect(1, 1, 2, 1, Tile::Floor);
+//!
+//! let masks = dual::corner_masks(&grid);
+//! assert_eq!((masks.width(), masks.height()), (5, 4));
+//! assert_eq!(masks[(2, 1)], dual::BOTTOM_LEFT | dual::BOTTOM_RIGHT);
+//! assert_eq!(dual::from_corner_masks(&masks), grid);
+//!
+//! let runs = dual::merge_edges(&dual::wall_edges(&grid));
+//! assert_eq!(runs.len(), 4);
+//! assert_eq!(runs.iter().filter(|r| r.orientation == Orientation::Horizontal).count(), 2);
+//! ```
+
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Corner mask bit for the cell up and to the left of the corner.
+pub const TOP_LEFT: u8 = 1;
+/// Corner mask bit for the cell up and to the right of the corner.
+pub const TOP_RIGHT: u8 = 2;
+/// Corner mask bit for the cell down and to the left of the corner.
+pub const BOTTOM_LEFT: u8 = 4;
+/// Corner mask bit for the cell down and to the right of the corner.
+pub const BOTTOM_RIGHT: u8 = 8;
+
+/// Floor mask of the up-to-four cells around each corner, as a
+/// `(w + 1) x (h + 1)` grid. Cells outside the map count as wall.
+pub fn corner_masks(grid: &Grid<Tile>) -> Grid<u8> {
+    let (w, h) = (grid.width(), grid.height());
+    let floor = |x: usize, y: usize| {
+        x > 0
+            && y > 0
+            && grid
+                .get(x as i32 - 1, y as i32 - 1)
+                .is_some_and(|t| t.is_floor())
+    };
+    let mut masks = Grid::new(w + 1, h + 1);
+    for y in 0..=h {
+        for x in 0..=w {
+            let mut mask = 0;
+            for (bit, (cx, cy)) in [
+                (TOP_LEFT, (x, y)),
+                (TOP_RIGHT, (x + 1, y)),
+                (BOTTOM_LEFT, (x, y + 1)),
+                (BOTTOM_RIGHT, (x + 1, y + 1)),
+            ] {
+                if floor(cx, cy) {
+                    mask |= bit;
+                }
+            }
+            masks[(x, y)] = mask;
+        }
+    }
+    masks
+}
+
+/// Rebuilds the tile grid from [`corner_masks`] output: each cell reads
+/// the [`BOTTOM_RIGHT`] bit of its top-left corner.
+pub fn from_corner_masks(masks: &Grid<u8>) -> Grid<Tile> {
+    let (w, h) = (
+        masks.width().saturating_sub(1),
+        masks.height().saturating_sub(1),
+    );
+    let mut grid = Grid::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            if masks[(x, y)] & BOTTOM_RIGHT != 0 {
+                grid.set(x as i32, y as i32, Tile::Floor);
+            }
+        }
+    }
+    grid
+}
+
+/// Direction an edge runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    /// Along x, between a cell and the one above or below it.
+    Horizontal,
+    /// Along y, between a cell and the one left or right of it.
+    Vertical,
+}
+
+/// Which side of a wall edge the floor is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Facing {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Facing {
+    /// Lowercase name, as serialized.
+    pub fn name(self) -> &'static str {
+        match self {
+            Facing::North => "north",
+            Facing::South => "south",
+            Facing::East => "east",
+            Facing::West => "west",
+        }
+    }
+
+    /// Unit step `(dx, dy)` toward this side; north is up (negative y).
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Facing::North => (0, -1),
+            Facing::South => (0, 1),
+            Facing::East => (1, 0),
+            Facing::West => (-1, 0),
+        }
+    }
+}
+
+/// A cell edge with floor on one side and wall (or the map edge) on the
+/// other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WallEdge {
+    /// Corner the edge starts at; it ends one step right (horizontal) or
+    /// down (vertical).
+    pub corner: (usize, usize),
+    pub orientation: Orientation,
+    /// Side the floor is on, i.e. the way the wall faces.
+    pub facing: Facing,
+}
+
+impl WallEdge {
+    /// Corner the edge ends at.
+    pub fn end(&self) -> (usize, usize) {
+        match self.orientation {
+            Orientation::Horizontal => (self.corner.0 + 1, self.corner.1),
+            Orientation::Vertical => (self.corner.0, self.corner.1 + 1),
+        }
+    }
+
+    /// The floor cell the edge bounds.
+    pub fn floor_cell(&self) -> (usize, usize) {
+        let (x, y) = self.corner;
+        match self.facing {
+            Facing::South | Facing::East => (x, y),
+            Facing::North => (x, y - 1),
+            Facing::West => (x - 1, y),
+        }
+    }
+}
+
+/// Every floor/wall edge, horizontal edges first, each in row-major order
+/// of their corners.
+pub fn wall_edges(grid: &Grid<Tile>) -> Vec<WallEdge> {
+    let (w, h) = (grid.width(), grid.height());
+    let floor = |x: i64, y: i64| {
+        x >= 0 && y >= 0 && grid.get(x as i32, y as i32).is_some_and(|t| t.is_floor())
+    };
+    let mut edges = Vec::new();
+    for y in 0..=h {
+        for x in 0..w {
+            let (above, below) = (floor(x as i64, y as i64 - 1), floor(x as i64, y as i64));
+            if above != below {
+                edges.push(WallEdge {
+                    corner: (x, y),
+                    orientation: Orientation::Horizontal,
+                    facing: if below { Facing::South } else { Facing::North },
+                });
+            }
+        }
+    }
+    for y in 0..h {
+        for x in 0..=w {
+            let (left, right) = (floor(x as i64 - 1, y as i64), floor(x as i64, y as i64));
+            if left != right {
+                edges.push(WallEdge {
+                    corner: (x, y),
+                    orientation: Orientation::Vertical,
+                    facing: if right { Facing::East } else { Facing::West },
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// A straight run of wall edges facing the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WallRun {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub orientation: Orientation,
+    pub facing: Facing,
+}
+
+impl WallRun {
+    /// Length in cell edges.
+    pub fn length(&self) -> usize {
+        self.start.0.abs_diff(self.end.0) + self.start.1.abs_diff(self.end.1)
+    }
+}
+
+/// Joins consecutive collinear edges with the same facing into runs.
+pub fn merge_edges(edges: &[WallEdge]) -> Vec<WallRun> {
+    let mut sorted = edges.to_vec();
+    sorted.sort_by_key(|e| match e.orientation {
+        Orientation::Horizontal => (0, e.facing as u8, e.corner.1, e.corner.0),
+        Orientation::Vertical => (1, e.facing as u8, e.corner.0, e.corner.1),
+    });
+    let mut runs: Vec<WallRun> = Vec::new();
+    for edge in sorted {
+        match runs.last_mut() {
+            Some(run)
+                if run.orientation == edge.orientation
+                    && run.facing == edge.facing
+                    && run.end == edge.corner =>
+            {
+                run.end = edge.end();
+            }
+            _ => runs.push(WallRun {
+                start: edge.corner,
+                end: edge.end(),
+                orientation: edge.orientation,
+                facing: edge.facing,
+            }),
+        }
+    }
+    runs
+}
+
+/// A thin-wall map: every cell is open, and walls stand on cell edges.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeWalls {
+    pub width: usize,
+    pub height: usize,
+    /// Walls on horizontal edges, indexed `[y][x]` for the top edge of cell
+    /// `(x, y)`; `height + 1` rows of `width`.
+    pub horizontal: Vec<Vec<bool>>,
+    /// Walls on vertical edges, indexed `[y][x]` for the left edge of cell
+    /// `(x, y)`; `height` rows of `width + 1`.
+    pub vertical: Vec<Vec<bool>>,
+}
+
+impl EdgeWalls {
+    /// A `width` x `height` map walled only around its outside.
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut walls = Self {
+            width,
+            height,
+            horizontal: vec![vec![false; width]; height + 1],
+            vertical: vec![vec![false; width + 1]; height],
+        };
+        for x in 0..width {
+            walls.horizontal[0][x] = true;
+            walls.horizontal[height][x] = true;
+        }
+        for row in &mut walls.vertical {
+            row[0] = true;
+            row[width] = true;
+        }
+        walls
+    }
+
+    /// Thin walls on every [`wall_edges`] edge of `grid`. Wall cells
+    /// themselves become open cells walled in on their floor sides.
+    pub fn from_tiles(grid: &Grid<Tile>) -> Self {
+        let (w, h) = (grid.width(), grid.height());
+        let mut walls = Self {
+            width: w,
+            height: h,
+            horizontal: vec![vec![false; w]; h + 1],
+            vertical: vec![vec![false; w + 1]; h],
+        };
+        for edge in wall_edges(grid) {
+            let (x, y) = edge.corner;
+            match edge.orientation {
+                Orientation::Horizontal => walls.horizontal[y][x] = true,
+                Orientation::Vertical => walls.vertical[y][x] = true,
+            }
+        }
+        walls
+    }
+
+    /// Expands to a `(2w + 1) x (2h + 1)` tile grid: cell `(x, y)` becomes
+    /// floor at `(2x + 1, 2y + 1)`, open edges become floor between cells,
+    /// and walls and corners become wall tiles.
+    pub fn to_tiles(&self) -> Grid<Tile> {
+        let mut grid = Grid::new(2 * self.width + 1, 2 * self.height + 1);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (tx, ty) = (2 * x as i32 + 1, 2 * y as i32 + 1);
+                grid.set(tx, ty, Tile::Floor);
+                if !self.horizontal[y][x] {
+                    grid.set(tx, ty - 1, Tile::Floor);
+                }
+                if !self.vertical[y][x] {
+                    grid.set(tx - 1, ty, Tile::Floor);
+                }
+            }
+        }
+        // Open outer edges reach the border row and column.
+        for x in 0..self.width {
+            if !self.horizontal[self.height][x] {
+                grid.set(2 * x as i32 + 1, 2 * self.height as i32, Tile::Floor);
+            }
+        }
+        for y in 0..self.height {
+            if !self.vertical[y][self.width] {
+                grid.set(2 * self.width as i32, 2 * y as i32 + 1, Tile::Floor);
+            }
+        }
+        grid
+    }
+}
@@ -0,0 +1,284 @@
+//! Cave ecology: nests, dens, and other lairs.
+//!
+//! [`place`] finds cavern sub-features in each semantic region (dead-end
+//! alcoves, open chambers, and cells on the shore of a `water` mask) and
+//! puts the themed markers of each [`Habitat`] on them. Sites are checked
+//! against existing markers with the habitat's [`MarkerConstraints`], so
+//! lairs keep their exclusion radius and stay out of safe zones, and each
+//! marker records a population scaled to the size of its region.
+//!
+//! ```rust
+//! use terrain_forge::ecology::{self, EcologyConfig};
+//! use terrain_forge::semantic::meta;
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("cellular").unwrap().generate(&mut grid, 6);
+//! let mut semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(6));
+//!
+//! let placed = ecology::place(&grid, &mut semantic, &EcologyConfig::default(), &mut Rng::new(6));
+//! assert!(placed > 0);
+//! let lair = semantic.markers.iter().rev().find(|m| m.metadata.contains_key(meta::ECOLOGY_POPULATION));
+//! assert!(lair.is_some());
+//! ```
+
+use crate::effects::clearance;
+use crate::semantic::{
+    meta, Marker, MarkerConstraints, MarkerType, SemanticLayers, TerrainFeature,
+};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+
+const NEIGHBORS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Cavern sub-feature a [`Habitat`] occupies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaveFeature {
+    /// Floor cells walled on three sides: the tips of dead ends and pockets.
+    Alcove,
+    /// The most open cells of large regions, at least `chamber_clearance`
+    /// from any wall.
+    Chamber,
+    /// Dry floor cells next to the `water` mask.
+    Shore,
+}
+
+impl CaveFeature {
+    pub fn name(self) -> &'static str {
+        match self {
+            CaveFeature::Alcove => "alcove",
+            CaveFeature::Chamber => "chamber",
+            CaveFeature::Shore => "shore",
+        }
+    }
+}
+
+/// One kind of lair and where it goes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Habitat {
+    /// Marker tag, e.g. `"nest"`.
+    pub tag: String,
+    pub feature: CaveFeature,
+    /// Minimum distance to another marker with the same tag. Default: 12.0.
+    pub exclusion_radius: f32,
+    /// Minimum distance to any marker. Default: 3.0.
+    pub min_distance_any: f32,
+    /// Sites per region at most. Default: 1.
+    pub per_region: usize,
+    /// Population per floor cell of the region. Default: 0.02.
+    pub population_density: f64,
+    /// Population bounds. Default: 1..=8.
+    pub min_population: usize,
+    pub max_population: usize,
+}
+
+impl Habitat {
+    pub fn new(tag: impl Into<String>, feature: CaveFeature) -> Self {
+        Self {
+            tag: tag.into(),
+            feature,
+            exclusion_radius: 12.0,
+            min_distance_any: 3.0,
+            per_region: 1,
+            population_density: 0.02,
+            min_population: 1,
+            max_population: 8,
+        }
+    }
+
+    /// Sets `exclusion_radius`.
+    pub fn with_exclusion_radius(mut self, radius: f32) -> Self {
+        self.exclusion_radius = radius;
+        self
+    }
+
+    /// Sets `per_region`.
+    pub fn with_per_region(mut self, per_region: usize) -> Self {
+        self.per_region = per_region;
+        self
+    }
+
+    /// Sets `population_density` and the population bounds.
+    pub fn with_population(mut self, density: f64, min: usize, max: usize) -> Self {
+        self.population_density = density;
+        self.min_population = min;
+        self.max_population = max;
+        self
+    }
+
+    /// Placement rules: the exclusion radius against the same tag,
+    /// `min_distance_any` against everything, and no safe zone in the
+    /// region.
+    pub fn constraints(&self) -> MarkerConstraints {
+        MarkerConstraints {
+            min_distance_same: Some(self.exclusion_radius),
+            min_distance_any: Some(self.min_distance_any),
+            exclude_types: vec![MarkerType::SafeZone],
+            ..MarkerConstraints::none()
+        }
+    }
+
+    /// Population for a region of `area` floor cells.
+    pub fn population(&self, area: usize) -> usize {
+        let scaled = (area as f64 * self.population_density).round() as usize;
+        scaled.clamp(
+            self.min_population,
+            self.max_population.max(self.min_population),
+        )
+    }
+}
+
+/// Configuration for [`place`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EcologyConfig {
+    /// Placed in order, so earlier habitats get first pick of sites.
+    /// Default: `nest` in chambers, `den` in alcoves, `spawning_pool` on
+    /// shores.
+    pub habitats: Vec<Habitat>,
+    /// Wall distance (Chebyshev) a cell needs to count as a chamber.
+    /// Default: 3.
+    pub chamber_clearance: usize,
+    /// Regions with fewer cells get no lairs. Default: 20.
+    pub min_region: usize,
+}
+
+impl Default for EcologyConfig {
+    fn default() -> Self {
+        Self {
+            habitats: vec![
+                Habitat::new("nest", CaveFeature::Chamber),
+                Habitat::new("den", CaveFeature::Alcove).with_exclusion_radius(8.0),
+                Habitat::new("spawning_pool", CaveFeature::Shore),
+            ],
+            chamber_clearance: 3,
+            min_region: 20,
+        }
+    }
+}
+
+impl EcologyConfig {
+    /// Config with just these habitats.
+    pub fn new(habitats: Vec<Habitat>) -> Self {
+        Self {
+            habitats,
+            ..Self::default()
+        }
+    }
+
+    /// Sets `chamber_clearance`.
+    pub fn with_chamber_clearance(mut self, clearance: usize) -> Self {
+        self.chamber_clearance = clearance;
+        self
+    }
+
+    /// Sets `min_region`.
+    pub fn with_min_region(mut self, min_region: usize) -> Self {
+        self.min_region = min_region;
+        self
+    }
+}
+
+/// Cavern features of every floor cell.
+pub fn features(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    chamber_clearance: usize,
+) -> Vec<Vec<Vec<CaveFeature>>> {
+    let (w, h) = (grid.width(), grid.height());
+    let clear = clearance(grid);
+    let water = semantic.masks.named(TerrainFeature::Water.mask_name());
+    let is_water = |x: i32, y: i32| {
+        x >= 0
+            && y >= 0
+            && water
+                .and_then(|m| m.get(y as usize))
+                .and_then(|row| row.get(x as usize))
+                .copied()
+                .unwrap_or(false)
+    };
+    let mut out = vec![vec![Vec::new(); w]; h];
+    for (x, y, tile) in grid.iter() {
+        if !tile.is_floor() {
+            continue;
+        }
+        let (xi, yi) = (x as i32, y as i32);
+        let walls = NEIGHBORS
+            .iter()
+            .filter(|&&(dx, dy)| !grid.get(xi + dx, yi + dy).is_some_and(|t| t.is_floor()))
+            .count();
+        let cell = &mut out[y][x];
+        if walls == 3 {
+            cell.push(CaveFeature::Alcove);
+        }
+        if clear[y * w + x] >= chamber_clearance.max(1) {
+            cell.push(CaveFeature::Chamber);
+        }
+        if !is_water(xi, yi) && NEIGHBORS.iter().any(|&(dx, dy)| is_water(xi + dx, yi + dy)) {
+            cell.push(CaveFeature::Shore);
+        }
+    }
+    out
+}
+
+/// Places habitat markers region by region. Returns the number added.
+///
+/// Chambers take their most open cell first; alcoves and shores are tried
+/// in random order. Markers carry the region id, the feature under
+/// [`meta::ECOLOGY_FEATURE`], and the population under
+/// [`meta::ECOLOGY_POPULATION`].
+pub fn place(
+    grid: &Grid<Tile>,
+    semantic: &mut SemanticLayers,
+    config: &EcologyConfig,
+    rng: &mut Rng,
+) -> usize {
+    let w = grid.width();
+    let features = features(grid, semantic, config.chamber_clearance);
+    let clear = clearance(grid);
+    let regions: Vec<(u32, Vec<(u32, u32)>)> = semantic
+        .regions
+        .iter()
+        .filter(|r| r.cells.len() >= config.min_region)
+        .map(|r| (r.id, r.cells.clone()))
+        .collect();
+
+    let mut added = 0;
+    for habitat in &config.habitats {
+        let constraints = habitat.constraints();
+        for (id, cells) in &regions {
+            let mut sites: Vec<(usize, usize)> = cells
+                .iter()
+                .map(|&(x, y)| (x as usize, y as usize))
+                .filter(|&(x, y)| {
+                    features
+                        .get(y)
+                        .and_then(|row| row.get(x))
+                        .is_some_and(|f| f.contains(&habitat.feature))
+                })
+                .collect();
+            rng.shuffle(&mut sites);
+            if habitat.feature == CaveFeature::Chamber {
+                sites.sort_by_key(|&(x, y)| std::cmp::Reverse(clear[y * w + x]));
+            }
+            let population = habitat.population(cells.len());
+            let mut placed = 0;
+            for (x, y) in sites {
+                if placed >= habitat.per_region {
+                    break;
+                }
+                let marker = Marker::with_tag(x as u32, y as u32, habitat.tag.clone())
+                    .with_region(*id)
+                    .with_metadata(meta::ECOLOGY_FEATURE, habitat.feature.name())
+                    .with_metadata(meta::ECOLOGY_POPULATION, population.to_string());
+                if constraints.allows(&marker, &semantic.markers) {
+                    semantic.markers.push(marker);
+                    placed += 1;
+                }
+            }
+            added += placed;
+        }
+    }
+    added
+}
@@ -67,7 +67,8 @@ pub fn radial_blend(
 
     for y in 0..h {
         for x in 0..w {
-            let dist = ((x as f64 - cx).powi(2) + (y as f64 - cy).powi(2)).sqrt();
+            let (dx, dy) = (x as f64 - cx, y as f64 - cy);
+            let dist = (dx * dx + dy * dy).sqrt();
             let cell = if dist < inner_r {
                 base[(x, y)]
             } else if dist > outer_r {
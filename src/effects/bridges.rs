@@ -0,0 +1,199 @@
+//! Bridges over water and chasms
+
+use super::water::WaterTable;
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Name of the [`Masks`](crate::semantic::Masks) layer written by
+/// [`mark_bridges`].
+pub const BRIDGE_MASK: &str = "bridge";
+
+/// Configuration for [`place_bridges`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BridgeConfig {
+    /// Longest crossing, in cells. Default: 8.
+    pub max_length: usize,
+    /// Most bridges placed. Default: 4.
+    pub max_bridges: usize,
+    /// Wall cells count as chasm and may be crossed; otherwise only water
+    /// is. Default: true.
+    pub cross_walls: bool,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            max_length: 8,
+            max_bridges: 4,
+            cross_walls: true,
+        }
+    }
+}
+
+impl BridgeConfig {
+    /// Sets `max_length`.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Sets `max_bridges`.
+    pub fn with_max_bridges(mut self, max_bridges: usize) -> Self {
+        self.max_bridges = max_bridges;
+        self
+    }
+
+    /// Sets `cross_walls`.
+    pub fn with_cross_walls(mut self, cross_walls: bool) -> Self {
+        self.cross_walls = cross_walls;
+        self
+    }
+}
+
+/// A straight crossing placed by [`place_bridges`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bridge {
+    /// Bridge deck cells, from `start` to `end`.
+    pub cells: Vec<(usize, usize)>,
+    /// Land cell the bridge leaves from.
+    pub start: (usize, usize),
+    /// Land cell the bridge reaches.
+    pub end: (usize, usize),
+}
+
+impl Bridge {
+    /// Number of deck cells.
+    pub fn length(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// Joins separate landmasses with straight bridges.
+///
+/// Land is floor that is not flooded in `water`. Every horizontal or
+/// vertical run of water (and, with `cross_walls`, wall) cells up to
+/// `max_length` long between two landmasses is a candidate; the shortest
+/// candidates that join still-separate landmasses are built, up to
+/// `max_bridges`. Deck cells become floor and are drained from `water`.
+pub fn place_bridges(
+    grid: &mut Grid<Tile>,
+    mut water: Option<&mut WaterTable>,
+    config: &BridgeConfig,
+) -> Vec<Bridge> {
+    let (w, h) = (grid.width(), grid.height());
+    let wet = |water: &Option<&mut WaterTable>, x: usize, y: usize| {
+        water.as_ref().is_some_and(|t| t.is_water(x, y))
+    };
+    let land = |x: usize, y: usize| grid[(x, y)].is_floor() && !wet(&water, x, y);
+    let gap = |x: usize, y: usize| {
+        if grid[(x, y)].is_floor() {
+            wet(&water, x, y)
+        } else {
+            config.cross_walls
+        }
+    };
+
+    // Label landmasses (4-connected).
+    let mut labels = vec![usize::MAX; w * h];
+    let mut count = 0;
+    for (x, y) in (0..h).flat_map(|y| (0..w).map(move |x| (x, y))) {
+        if labels[y * w + x] != usize::MAX || !land(x, y) {
+            continue;
+        }
+        labels[y * w + x] = count;
+        let mut stack = vec![(x, y)];
+        while let Some((cx, cy)) = stack.pop() {
+            for (nx, ny) in [
+                (cx.wrapping_sub(1), cy),
+                (cx + 1, cy),
+                (cx, cy.wrapping_sub(1)),
+                (cx, cy + 1),
+            ] {
+                if nx < w && ny < h && labels[ny * w + nx] == usize::MAX && land(nx, ny) {
+                    labels[ny * w + nx] = count;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        count += 1;
+    }
+    if count <= 1 || config.max_bridges == 0 {
+        return Vec::new();
+    }
+
+    // Straight crossings, scanning right and down from every shore cell.
+    let mut candidates = Vec::new();
+    for y in 0..h {
+        for x in 0..w {
+            let from = labels[y * w + x];
+            if from == usize::MAX {
+                continue;
+            }
+            for (dx, dy) in [(1, 0), (0, 1)] {
+                let mut cells = Vec::new();
+                let (mut cx, mut cy) = (x + dx, y + dy);
+                while cx < w && cy < h && cells.len() <= config.max_length && gap(cx, cy) {
+                    cells.push((cx, cy));
+                    cx += dx;
+                    cy += dy;
+                }
+                if cells.is_empty() || cells.len() > config.max_length || cx >= w || cy >= h {
+                    continue;
+                }
+                let to = labels[cy * w + cx];
+                if to != usize::MAX && to != from {
+                    candidates.push((cells, (x, y), (cx, cy), from, to));
+                }
+            }
+        }
+    }
+    candidates.sort_by_key(|(cells, start, ..)| (cells.len(), start.1, start.0));
+
+    // Kruskal over landmasses: shortest crossings first.
+    let mut parent: Vec<usize> = (0..count).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut bridges = Vec::new();
+    for (cells, start, end, from, to) in candidates {
+        if bridges.len() >= config.max_bridges {
+            break;
+        }
+        let (a, b) = (root(&mut parent, from), root(&mut parent, to));
+        if a == b {
+            continue;
+        }
+        parent[a] = b;
+        bridges.push(Bridge { cells, start, end });
+    }
+
+    for bridge in &bridges {
+        for &(x, y) in &bridge.cells {
+            grid.set(x as i32, y as i32, Tile::Floor);
+            if let Some(table) = water.as_deref_mut() {
+                table.water[y][x] = false;
+            }
+        }
+    }
+    bridges
+}
+
+/// Writes the [`BRIDGE_MASK`] layer and marks deck cells walkable and
+/// `no_spawn`.
+pub fn mark_bridges(semantic: &mut SemanticLayers, bridges: &[Bridge]) {
+    let masks = &mut semantic.masks;
+    for &(x, y) in bridges.iter().flat_map(|b| &b.cells) {
+        if y >= masks.height || x >= masks.width {
+            continue;
+        }
+        masks.set_named(BRIDGE_MASK, x, y, true);
+        masks.walkable[y][x] = true;
+        masks.no_spawn[y][x] = true;
+    }
+}
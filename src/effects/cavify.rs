@@ -0,0 +1,169 @@
+//! Topology-preserving maze smoothing
+
+use super::morphology::{is_simple, ring};
+use crate::noise::{NoiseSource, Perlin};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Configuration for [`cavify`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CavifyConfig {
+    /// Base chance to open a wall cell beside a corridor on each widening
+    /// pass, scaled by noise so width varies along a corridor. Default: 0.35.
+    pub widen: f64,
+    /// Widening passes; each can push a corridor one cell wider.
+    /// Default: 2.
+    pub passes: usize,
+    /// Frequency of the widening noise. Default: 0.12.
+    pub noise_scale: f64,
+    /// Whether to open inner corners and shave outer ones. Default: true.
+    pub round_corners: bool,
+    /// Chambers grown around random corridor cells. Default: 3.
+    pub chambers: usize,
+    /// Largest chamber radius; each chamber takes a radius between half of
+    /// this and this. Default: 3.
+    pub chamber_radius: usize,
+}
+
+impl Default for CavifyConfig {
+    fn default() -> Self {
+        Self {
+            widen: 0.35,
+            passes: 2,
+            noise_scale: 0.12,
+            round_corners: true,
+            chambers: 3,
+            chamber_radius: 3,
+        }
+    }
+}
+
+impl CavifyConfig {
+    /// Sets `widen` and `passes`.
+    pub fn with_widen(mut self, widen: f64, passes: usize) -> Self {
+        self.widen = widen;
+        self.passes = passes;
+        self
+    }
+
+    /// Sets `round_corners`.
+    pub fn with_round_corners(mut self, round_corners: bool) -> Self {
+        self.round_corners = round_corners;
+        self
+    }
+
+    /// Sets `chambers` and `chamber_radius`.
+    pub fn with_chambers(mut self, chambers: usize, radius: usize) -> Self {
+        self.chambers = chambers;
+        self.chamber_radius = radius;
+        self
+    }
+}
+
+/// Smooths a maze into organic cave passages without changing its
+/// topology. Returns the number of cells changed.
+///
+/// Unlike running cellular automata over a maze, every change is a
+/// *simple* cell flip: a wall is opened only if it touches exactly one
+/// local floor component and opening it encloses no wall, and a floor cell
+/// is walled only if its floor neighbours stay joined without it. No
+/// region is split or merged and no loop is created, so a perfect maze
+/// stays perfect. Chambers grow first, then corridors widen, then corners
+/// round. The outermost ring of cells is never changed.
+pub fn cavify(grid: &mut Grid<Tile>, config: &CavifyConfig, seed: u64) -> usize {
+    let (w, h) = (grid.width(), grid.height());
+    if w < 3 || h < 3 {
+        return 0;
+    }
+    let mut rng = Rng::new(seed);
+    let mut changed = 0;
+
+    let floor: Vec<(usize, usize)> = grid
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    for _ in 0..config.chambers {
+        let Some(&(cx, cy)) = rng.pick(&floor) else {
+            break;
+        };
+        let max = config.chamber_radius.max(1);
+        let r = rng.range_usize_inclusive((max / 2).max(1), max) as i32;
+        // Nearest cells first, so the chamber grows outward from its centre.
+        let mut cells: Vec<(i32, i32)> = (-r..=r)
+            .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| dx * dx + dy * dy <= r * r)
+            .collect();
+        cells.sort_by_key(|&(dx, dy)| (dx * dx + dy * dy, dy, dx));
+        for (dx, dy) in cells {
+            changed += open(grid, cx as i32 + dx, cy as i32 + dy) as usize;
+        }
+    }
+
+    let noise = Perlin::new(rng.next_u64()).with_frequency(config.noise_scale);
+    for _ in 0..config.passes {
+        let snapshot: Vec<bool> = grid.cells().iter().map(Tile::is_floor).collect();
+        for y in 1..h - 1 {
+            for x in 1..w - 1 {
+                let idx = y * w + x;
+                let beside = snapshot[idx - 1]
+                    || snapshot[idx + 1]
+                    || snapshot[idx - w]
+                    || snapshot[idx + w];
+                if snapshot[idx] || !beside {
+                    continue;
+                }
+                let n = (noise.sample(x as f64, y as f64) + 1.0) / 2.0;
+                if rng.chance((config.widen * 2.0 * n).clamp(0.0, 1.0)) {
+                    changed += open(grid, x as i32, y as i32) as usize;
+                }
+            }
+        }
+    }
+
+    if config.round_corners {
+        for y in 1..h - 1 {
+            for x in 1..w - 1 {
+                let [north, _, east, _, south, _, west, _] = ring(grid, x, y);
+                if north == south || east == west {
+                    continue;
+                }
+                let (x, y) = (x as i32, y as i32);
+                changed += if grid[(x as usize, y as usize)].is_floor() {
+                    shave(grid, x, y)
+                } else {
+                    open(grid, x, y)
+                } as usize;
+            }
+        }
+    }
+    changed
+}
+
+/// Opens the wall at `(x, y)` if it is inside the border and simple.
+fn open(grid: &mut Grid<Tile>, x: i32, y: i32) -> bool {
+    let (w, h) = (grid.width() as i32, grid.height() as i32);
+    if x < 1 || y < 1 || x >= w - 1 || y >= h - 1 {
+        return false;
+    }
+    let (ux, uy) = (x as usize, y as usize);
+    if grid[(ux, uy)].is_floor() || !is_simple(&ring(grid, ux, uy)) {
+        return false;
+    }
+    grid.set(x, y, Tile::Floor);
+    true
+}
+
+/// Walls the floor cell at `(x, y)` if it is simple and not a dead-end
+/// tip, so passages never lose length.
+fn shave(grid: &mut Grid<Tile>, x: i32, y: i32) -> bool {
+    let (ux, uy) = (x as usize, y as usize);
+    let ring = ring(grid, ux, uy);
+    let open_sides = (0..8).step_by(2).filter(|&i| ring[i]).count();
+    if open_sides < 2 || !is_simple(&ring) {
+        return false;
+    }
+    grid.set(x, y, Tile::Wall);
+    true
+}
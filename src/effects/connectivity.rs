@@ -1,10 +1,13 @@
 //! Connectivity effects
 
+use super::morphology::{is_simple, ring};
 use crate::grid::{line_points, Cell};
 use crate::semantic::{MarkerType, SemanticLayers};
 use crate::spatial::{shortest_path, PathfindingConstraints};
 use crate::{Grid, Rng, Tile};
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
 use std::collections::VecDeque;
 
 /// Methods for connecting semantic markers
@@ -124,7 +127,7 @@ pub fn connect_regions_spanning(
     for y in 1..h - 1 {
         for x in 1..w - 1 {
             if !grid[(x, y)].is_floor() {
-                let adjacent_regions: HashSet<u32> =
+                let adjacent_regions: BTreeSet<u32> =
                     [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
                         .iter()
                         .filter_map(|&(nx, ny)| {
@@ -182,6 +185,312 @@ pub fn connect_regions_spanning(
     connections_made
 }
 
+/// Configuration for [`connect_regions_budgeted`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpanningConfig {
+    /// Chance per single-wall gap between two different regions to open it
+    /// as a loop once the tree is built. Default: 0.2.
+    pub extra_connection_chance: f64,
+    /// Most wall cells carved in total, loops included; `None` for no
+    /// limit. Default: None.
+    pub corridor_budget: Option<usize>,
+    /// Most wall cells carved for one connection; `None` for no limit.
+    /// Default: None.
+    pub max_corridor_length: Option<usize>,
+}
+
+impl Default for SpanningConfig {
+    fn default() -> Self {
+        Self {
+            extra_connection_chance: 0.2,
+            corridor_budget: None,
+            max_corridor_length: None,
+        }
+    }
+}
+
+impl SpanningConfig {
+    /// Sets `extra_connection_chance`.
+    pub fn with_extra_connection_chance(mut self, chance: f64) -> Self {
+        self.extra_connection_chance = chance;
+        self
+    }
+
+    /// Sets `corridor_budget`.
+    pub fn with_corridor_budget(mut self, budget: usize) -> Self {
+        self.corridor_budget = Some(budget);
+        self
+    }
+
+    /// Sets `max_corridor_length`.
+    pub fn with_max_corridor_length(mut self, length: usize) -> Self {
+        self.max_corridor_length = Some(length);
+        self
+    }
+}
+
+/// One connection carved by [`connect_regions_budgeted`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Corridor {
+    /// Labels (as from [`label_regions`]) of the regions joined: the one
+    /// the corridor left from, then the one it reached.
+    pub regions: (u32, u32),
+    /// Wall cells turned to floor, in order along the corridor.
+    pub cells: Vec<(usize, usize)>,
+    /// Opened after the spanning tree, closing a loop.
+    pub is_loop: bool,
+}
+
+impl Corridor {
+    /// Number of cells carved.
+    pub fn length(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// What [`connect_regions_budgeted`] carved.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanningReport {
+    pub corridors: Vec<Corridor>,
+    /// Wall cells carved in total.
+    pub carved: usize,
+    /// Labels of regions left unconnected because the budget or the
+    /// length limit ran out.
+    pub unconnected: Vec<u32>,
+}
+
+impl SpanningReport {
+    /// Every region was joined.
+    pub fn connected(&self) -> bool {
+        self.unconnected.is_empty()
+    }
+}
+
+/// Joins all floor regions with corridors, within a carving budget.
+///
+/// Unlike [`connect_regions_spanning`], which only opens single walls
+/// between touching regions, this grows a spanning tree from the largest
+/// region: each round finds the connection to a new region that carves the
+/// fewest walls, walking existing floor and earlier corridors for free, so
+/// corridors branch off each other instead of running side by side. The
+/// tree stops when the next connection would exceed `max_corridor_length`
+/// or what is left of `corridor_budget`; the remaining regions are listed
+/// in the report. Loops are then opened as in [`connect_regions_spanning`].
+pub fn connect_regions_budgeted(
+    grid: &mut Grid<Tile>,
+    config: &SpanningConfig,
+    rng: &mut Rng,
+) -> SpanningReport {
+    let (w, h) = (grid.width(), grid.height());
+    let (mut labels, region_count) = label_regions(grid);
+    let mut report = SpanningReport::default();
+    if region_count <= 1 {
+        return report;
+    }
+    let mut budget = config.corridor_budget.unwrap_or(usize::MAX);
+    let max_length = config.max_corridor_length.unwrap_or(usize::MAX);
+
+    let mut sizes = vec![0usize; region_count as usize + 1];
+    for &label in &labels {
+        sizes[label as usize] += 1;
+    }
+    let root = (1..=region_count).max_by_key(|&r| (sizes[r as usize], Reverse(r)));
+    let mut joined = vec![false; region_count as usize + 1];
+    joined[root.unwrap_or(1) as usize] = true;
+
+    let mut remaining = region_count as usize - 1;
+    while remaining > 0 {
+        let Some((path, reached)) = cheapest_connection(grid, &labels, &joined) else {
+            break;
+        };
+        // The path runs from the joined side (first cell) to `reached`.
+        let cells: Vec<(usize, usize)> = path
+            .iter()
+            .copied()
+            .filter(|&(x, y)| !grid[(x, y)].is_floor())
+            .collect();
+        if cells.len() > max_length || cells.len() > budget {
+            break;
+        }
+        let from = path
+            .first()
+            .map(|&(x, y)| labels[y * w + x])
+            .unwrap_or_default();
+        for &(x, y) in &cells {
+            grid.set(x as i32, y as i32, Tile::Floor);
+            labels[y * w + x] = from;
+        }
+        budget -= cells.len();
+        report.carved += cells.len();
+        joined[reached as usize] = true;
+        remaining -= 1;
+        report.corridors.push(Corridor {
+            regions: (from, reached),
+            cells,
+            is_loop: false,
+        });
+    }
+
+    let mut gaps = Vec::new();
+    for y in 1..h.saturating_sub(1) {
+        for x in 1..w.saturating_sub(1) {
+            if grid[(x, y)].is_floor() {
+                continue;
+            }
+            for (a, b) in [((x - 1, y), (x + 1, y)), ((x, y - 1), (x, y + 1))] {
+                let (la, lb) = (labels[a.1 * w + a.0], labels[b.1 * w + b.0]);
+                if grid[a].is_floor() && grid[b].is_floor() && la != lb {
+                    gaps.push((x, y, la.min(lb), la.max(lb)));
+                    break;
+                }
+            }
+        }
+    }
+    rng.shuffle(&mut gaps);
+    for (x, y, a, b) in gaps {
+        if budget == 0 {
+            break;
+        }
+        if joined[a as usize] && joined[b as usize] && rng.chance(config.extra_connection_chance) {
+            grid.set(x as i32, y as i32, Tile::Floor);
+            budget -= 1;
+            report.carved += 1;
+            report.corridors.push(Corridor {
+                regions: (a, b),
+                cells: vec![(x, y)],
+                is_loop: true,
+            });
+        }
+    }
+
+    report.unconnected = (1..=region_count)
+        .filter(|&r| !joined[r as usize])
+        .collect();
+    report
+}
+
+/// 0-1 BFS from every joined floor cell, where floor costs nothing and
+/// wall costs one, to the nearest cell of an unjoined region. Returns the
+/// path (joined end first) and the region reached.
+fn cheapest_connection(
+    grid: &Grid<Tile>,
+    labels: &[u32],
+    joined: &[bool],
+) -> Option<(Vec<(usize, usize)>, u32)> {
+    let (w, h) = (grid.width(), grid.height());
+    let mut cost = vec![usize::MAX; w * h];
+    let mut parent = vec![usize::MAX; w * h];
+    let mut queue = VecDeque::new();
+    for (i, &label) in labels.iter().enumerate() {
+        if label > 0 && joined[label as usize] && grid[(i % w, i / w)].is_floor() {
+            cost[i] = 0;
+            queue.push_back(i);
+        }
+    }
+    while let Some(i) = queue.pop_front() {
+        let (x, y) = (i % w, i / w);
+        let label = labels[i];
+        if label > 0 && !joined[label as usize] && grid[(x, y)].is_floor() {
+            let mut path = vec![(x, y)];
+            let mut at = i;
+            while parent[at] != usize::MAX {
+                at = parent[at];
+                path.push((at % w, at / w));
+            }
+            path.reverse();
+            return Some((path, label));
+        }
+        // Corridors stay off the outer ring.
+        let inner = |nx: usize, ny: usize| nx > 0 && ny > 0 && nx + 1 < w && ny + 1 < h;
+        for (dx, dy) in [(-1i32, 0), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || ny < 0 || nx as usize >= w || ny as usize >= h {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            let floor = grid[(nx, ny)].is_floor();
+            if !floor && !inner(nx, ny) {
+                continue;
+            }
+            let j = ny * w + nx;
+            let next = cost[i] + usize::from(!floor);
+            if next < cost[j] {
+                cost[j] = next;
+                parent[j] = i;
+                if floor {
+                    queue.push_front(j);
+                } else {
+                    queue.push_back(j);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Shrinks the walkable area to `target` floor cells by peeling the
+/// outermost floor away, layer by layer.
+///
+/// Each pass removes floor cells that touch a wall, farthest from the
+/// floor's centroid first, as long as the cell is a simple point (its floor
+/// neighbors stay connected and no loop closes), so no region is split or
+/// erased.
+/// Cells under markers in `semantic` are kept. Stops at `target` or when
+/// nothing more can go; returns the floor count reached.
+pub fn shrink_to_area(
+    grid: &mut Grid<Tile>,
+    target: usize,
+    semantic: Option<&SemanticLayers>,
+) -> usize {
+    let w = grid.width();
+    let mut floor = grid.count(|t| t.is_floor());
+    let mut keep = vec![false; w * grid.height()];
+    for marker in semantic.map(|s| s.markers.as_slice()).unwrap_or_default() {
+        if let Some(cell) = keep.get_mut(marker.y as usize * w + marker.x as usize) {
+            *cell = true;
+        }
+    }
+    let (sx, sy) = grid
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .fold((0.0, 0.0), |(sx, sy), (x, y, _)| {
+            (sx + x as f64, sy + y as f64)
+        });
+    let center = (sx / floor.max(1) as f64, sy / floor.max(1) as f64);
+    let reach = |x: usize, y: usize| (x as f64 - center.0).powi(2) + (y as f64 - center.1).powi(2);
+    let is_floor = |grid: &Grid<Tile>, x: i32, y: i32| grid.get(x, y).is_some_and(|t| t.is_floor());
+
+    while floor > target {
+        let mut edge: Vec<(usize, usize)> = grid
+            .iter()
+            .filter(|&(x, y, t)| {
+                t.is_floor()
+                    && !keep[y * w + x]
+                    && [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                        .iter()
+                        .any(|&(dx, dy)| !is_floor(grid, x as i32 + dx, y as i32 + dy))
+            })
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        edge.sort_by(|&a, &b| reach(b.0, b.1).total_cmp(&reach(a.0, a.1)).then(a.cmp(&b)));
+
+        let before = floor;
+        for (x, y) in edge {
+            if floor <= target {
+                break;
+            }
+            if is_simple(&ring(grid, x, y)) {
+                grid.set(x as i32, y as i32, Tile::Wall);
+                floor -= 1;
+            }
+        }
+        if floor == before {
+            break;
+        }
+    }
+    floor
+}
 /// Bridges small gaps between floor regions.
 pub fn bridge_gaps(grid: &mut Grid<Tile>, max_distance: usize) {
     let regions = grid.flood_regions();
@@ -254,16 +563,7 @@ pub fn remove_dead_ends(grid: &mut Grid<Tile>, iterations: usize) {
         let mut changed = false;
         for y in 1..h - 1 {
             for x in 1..w - 1 {
-                if !grid[(x, y)].is_floor() {
-                    continue;
-                }
-                let neighbors = [
-                    grid[(x - 1, y)].is_floor(),
-                    grid[(x + 1, y)].is_floor(),
-                    grid[(x, y - 1)].is_floor(),
-                    grid[(x, y + 1)].is_floor(),
-                ];
-                if neighbors.iter().filter(|&&b| b).count() <= 1 {
+                if is_dead_end(grid, x, y) {
                     grid.set(x as i32, y as i32, Tile::Wall);
                     changed = true;
                 }
@@ -275,6 +575,33 @@ pub fn remove_dead_ends(grid: &mut Grid<Tile>, iterations: usize) {
     }
 }
 
+/// Finds dead-end cells: interior floor cells with at most one orthogonal floor neighbor.
+pub fn find_dead_ends(grid: &Grid<Tile>) -> Vec<(usize, usize)> {
+    let (w, h) = (grid.width(), grid.height());
+    let mut dead_ends = Vec::new();
+    for y in 1..h.saturating_sub(1) {
+        for x in 1..w.saturating_sub(1) {
+            if is_dead_end(grid, x, y) {
+                dead_ends.push((x, y));
+            }
+        }
+    }
+    dead_ends
+}
+
+fn is_dead_end(grid: &Grid<Tile>, x: usize, y: usize) -> bool {
+    if !grid[(x, y)].is_floor() {
+        return false;
+    }
+    let neighbors = [
+        grid[(x - 1, y)].is_floor(),
+        grid[(x + 1, y)].is_floor(),
+        grid[(x, y - 1)].is_floor(),
+        grid[(x, y + 1)].is_floor(),
+    ];
+    neighbors.iter().filter(|&&b| b).count() <= 1
+}
+
 /// Finds chokepoint cells (removal would disconnect regions).
 pub fn find_chokepoints(grid: &Grid<Tile>) -> Vec<(usize, usize)> {
     let (w, h) = (grid.width(), grid.height());
@@ -3,21 +3,35 @@
 //! Most effects mutate the grid in place.
 
 mod blend;
+mod bridges;
+mod cavify;
 mod connectivity;
 mod filters;
 mod morphology;
+mod obstacles;
+mod roads;
 mod spatial;
 mod transform;
 mod warp;
+mod water;
 
 pub use blend::{gradient_blend, radial_blend, threshold};
+pub use bridges::{mark_bridges, place_bridges, Bridge, BridgeConfig, BRIDGE_MASK};
+pub use cavify::{cavify, CavifyConfig};
 pub use connectivity::{
-    bridge_gaps, carve_path, clear_rect, connect_markers, connect_regions_spanning,
-    find_chokepoints, label_regions, remove_dead_ends, MarkerConnectMethod,
+    bridge_gaps, carve_path, clear_rect, connect_markers, connect_regions_budgeted,
+    connect_regions_spanning, find_chokepoints, find_dead_ends, label_regions, remove_dead_ends,
+    shrink_to_area, Corridor, MarkerConnectMethod, SpanningConfig, SpanningReport,
 };
 pub use filters::{gaussian_blur, median_filter};
-pub use morphology::{close, dilate, erode, open};
+pub use morphology::{close, dilate, erode, open, thicken_walls};
+pub(crate) use obstacles::clearance;
+pub use obstacles::{place_obstacles, ObstacleConfig, ObstacleStyle};
+pub use roads::{
+    mark_road, route_road, Crossing, CrossingKind, Road, RoadConfig, FORD_MASK, ROAD_MASK,
+};
 #[allow(deprecated)]
 pub use spatial::{dijkstra_map, distance_transform};
 pub use transform::{invert, mirror, resize, rotate, scatter};
 pub use warp::{domain_warp, edge_detect};
+pub use water::{WaterConfig, WaterLevel, WaterTable, WATER_MASK};
@@ -1,16 +1,18 @@
 //! Morphological operations
 
+use crate::parallel;
 use crate::{Grid, Tile};
 
 /// Erodes floor tiles — removes isolated floors.
 pub fn erode(grid: &mut Grid<Tile>, iterations: usize) {
     let (w, h) = (grid.width(), grid.height());
     for _ in 0..iterations {
-        let snapshot: Vec<bool> = (0..w * h)
-            .map(|i| grid[(i % w, i / w)].is_floor())
-            .collect();
-        for y in 1..h - 1 {
-            for x in 1..w - 1 {
+        let snapshot: Vec<bool> = grid.cells().iter().map(Tile::is_floor).collect();
+        parallel::for_each_row(grid.cells_mut(), w, |y, row| {
+            if y == 0 || y + 1 >= h {
+                return;
+            }
+            for (x, tile) in row.iter_mut().enumerate().take(w - 1).skip(1) {
                 let idx = y * w + x;
                 if snapshot[idx] {
                     let has_wall = !snapshot[idx - 1]
@@ -18,11 +20,11 @@ pub fn erode(grid: &mut Grid<Tile>, iterations: usize) {
                         || !snapshot[idx - w]
                         || !snapshot[idx + w];
                     if has_wall {
-                        grid.set(x as i32, y as i32, Tile::Wall);
+                        *tile = Tile::Wall;
                     }
                 }
             }
-        }
+        });
     }
 }
 
@@ -30,11 +32,12 @@ pub fn erode(grid: &mut Grid<Tile>, iterations: usize) {
 pub fn dilate(grid: &mut Grid<Tile>, iterations: usize) {
     let (w, h) = (grid.width(), grid.height());
     for _ in 0..iterations {
-        let snapshot: Vec<bool> = (0..w * h)
-            .map(|i| grid[(i % w, i / w)].is_floor())
-            .collect();
-        for y in 1..h - 1 {
-            for x in 1..w - 1 {
+        let snapshot: Vec<bool> = grid.cells().iter().map(Tile::is_floor).collect();
+        parallel::for_each_row(grid.cells_mut(), w, |y, row| {
+            if y == 0 || y + 1 >= h {
+                return;
+            }
+            for (x, tile) in row.iter_mut().enumerate().take(w - 1).skip(1) {
                 let idx = y * w + x;
                 if !snapshot[idx] {
                     let has_floor = snapshot[idx - 1]
@@ -42,11 +45,11 @@ pub fn dilate(grid: &mut Grid<Tile>, iterations: usize) {
                         || snapshot[idx - w]
                         || snapshot[idx + w];
                     if has_floor {
-                        grid.set(x as i32, y as i32, Tile::Floor);
+                        *tile = Tile::Floor;
                     }
                 }
             }
-        }
+        });
     }
 }
 
@@ -61,3 +64,94 @@ pub fn close(grid: &mut Grid<Tile>, iterations: usize) {
     dilate(grid, iterations);
     erode(grid, iterations);
 }
+
+/// Thickens walls into floor like [`erode`], but never changes connectivity:
+/// a floor cell is only walled when it is a simple point (its floor
+/// neighbours stay joined without it and no loop closes), so passages
+/// narrow to one cell instead of sealing and no region disappears. Dead-end tips are kept, so repeated passes thin open areas
+/// toward a skeleton rather than a point. Returns the number of cells walled.
+pub fn thicken_walls(grid: &mut Grid<Tile>, iterations: usize) -> usize {
+    let (w, h) = (grid.width(), grid.height());
+    if w < 3 || h < 3 {
+        return 0;
+    }
+    let mut walled = 0;
+    for _ in 0..iterations {
+        let snapshot: Vec<bool> = grid.cells().iter().map(Tile::is_floor).collect();
+        let mut changed = false;
+        for y in 1..h - 1 {
+            for x in 1..w - 1 {
+                let idx = y * w + x;
+                let edge = !snapshot[idx - 1]
+                    || !snapshot[idx + 1]
+                    || !snapshot[idx - w]
+                    || !snapshot[idx + w];
+                if !snapshot[idx] || !edge {
+                    continue;
+                }
+                let ring = ring(grid, x, y);
+                // Dead-end tips stay so passages keep their length.
+                let open_sides = (0..8).step_by(2).filter(|&i| ring[i]).count();
+                if open_sides >= 2 && is_simple(&ring) {
+                    grid.set(x as i32, y as i32, Tile::Wall);
+                    walled += 1;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    walled
+}
+
+/// Clockwise ring from north; even indices are the 4-neighbours.
+const RING: [(i32, i32); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Floor flags of the ring around `(x, y)` in [`is_simple`] order; cells off
+/// the grid are wall.
+pub(crate) fn ring(grid: &Grid<Tile>, x: usize, y: usize) -> [bool; 8] {
+    RING.map(|(dx, dy)| {
+        grid.get(x as i32 + dx, y as i32 + dy)
+            .is_some_and(|t| t.is_floor())
+    })
+}
+
+/// Whether the centre of `ring` is a simple point, so flipping it between
+/// floor and wall keeps the local topology: its 4-adjacent floor forms one
+/// 4-connected run and the walls one 8-connected run. Walling a simple
+/// floor cell never splits a region or closes a loop, and opening a simple
+/// wall never joins regions or opens one.
+///
+/// `ring` is clockwise from north, as from [`ring`]; even indices are the
+/// 4-neighbours.
+pub(crate) fn is_simple(ring: &[bool; 8]) -> bool {
+    // Floor runs along the ring that touch a 4-neighbour.
+    let mut floor_runs = 0;
+    for i in 0..8 {
+        if ring[i] && !ring[(i + 7) % 8] {
+            floor_runs += (i..i + 8).take_while(|&j| ring[j % 8]).any(|j| j % 2 == 0) as usize;
+        }
+    }
+    if ring.iter().all(|&f| f) {
+        floor_runs = 1;
+    }
+    // Wall runs; a floor corner between two wall 4-neighbours doesn't part
+    // them, since walls connect diagonally.
+    let wall = |i: usize| !ring[i % 8] || (i % 2 == 1 && !ring[(i + 7) % 8] && !ring[(i + 1) % 8]);
+    let mut wall_runs = (0..8).filter(|&i| wall(i) && !wall(i + 7)).count();
+    if (0..8).all(wall) {
+        wall_runs = 1;
+    }
+    floor_runs == 1 && wall_runs == 1
+}
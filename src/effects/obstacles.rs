@@ -0,0 +1,177 @@
+//! Pillars and broken walls inside open areas
+
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Shape of the obstacles placed by [`place_obstacles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObstacleStyle {
+    /// Square pillars on a regular lattice.
+    #[default]
+    Pillars,
+    /// Short wall segments, each randomly horizontal or vertical.
+    BrokenWalls,
+}
+
+/// Configuration for [`place_obstacles`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObstacleConfig {
+    pub style: ObstacleStyle,
+    /// Distance between lattice points. Default: 5.
+    pub spacing: usize,
+    /// Pillar side, or wall segment thickness. Default: 1.
+    pub size: usize,
+    /// Wall segment length ([`BrokenWalls`](ObstacleStyle::BrokenWalls)).
+    /// Default: 4.
+    pub length: usize,
+    /// Chance each lattice point gets an obstacle, `0..=1`. Default: 0.8.
+    pub density: f64,
+    /// Floor cells kept clear between an obstacle and any wall or other
+    /// obstacle, so passages stay at least this wide. At least 1.
+    /// Default: 2.
+    pub min_corridor: usize,
+    /// Regions with fewer floor cells are left alone. Default: 80.
+    pub min_region: usize,
+}
+
+impl Default for ObstacleConfig {
+    fn default() -> Self {
+        Self {
+            style: ObstacleStyle::Pillars,
+            spacing: 5,
+            size: 1,
+            length: 4,
+            density: 0.8,
+            min_corridor: 2,
+            min_region: 80,
+        }
+    }
+}
+
+impl ObstacleConfig {
+    /// Sets `style`.
+    pub fn with_style(mut self, style: ObstacleStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets `spacing`.
+    pub fn with_spacing(mut self, spacing: usize) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets `density`.
+    pub fn with_density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets `min_corridor`.
+    pub fn with_min_corridor(mut self, min_corridor: usize) -> Self {
+        self.min_corridor = min_corridor;
+        self
+    }
+
+    /// Sets `min_region`.
+    pub fn with_min_region(mut self, min_region: usize) -> Self {
+        self.min_region = min_region;
+        self
+    }
+}
+
+/// Scatters pillars or broken wall segments inside large open regions to
+/// break sightlines. Returns the number of cells walled.
+///
+/// Obstacles sit on a lattice with a random offset. One is placed only if
+/// every cell of it is floor in a region of at least `min_region` cells
+/// and more than `min_corridor` cells (Chebyshev) from any wall, including
+/// earlier obstacles. Each obstacle is therefore ringed by open floor, so
+/// no region is split and no passage narrows below `min_corridor`.
+pub fn place_obstacles(grid: &mut Grid<Tile>, config: &ObstacleConfig, seed: u64) -> usize {
+    let (w, h) = (grid.width(), grid.height());
+    let spacing = config.spacing.max(1);
+    let size = config.size.max(1);
+    let gap = config.min_corridor.max(1);
+    let mut rng = Rng::new(seed);
+
+    let mut eligible = vec![false; w * h];
+    for region in grid.flood_regions() {
+        if region.len() >= config.min_region {
+            for (x, y) in region {
+                eligible[y * w + x] = true;
+            }
+        }
+    }
+
+    let (ox, oy) = (rng.range_usize(0, spacing), rng.range_usize(0, spacing));
+    let mut clear = clearance(grid);
+    let mut walled = 0;
+    for ay in (oy..h).step_by(spacing) {
+        for ax in (ox..w).step_by(spacing) {
+            if !rng.chance(config.density) {
+                continue;
+            }
+            let (sw, sh) = match config.style {
+                ObstacleStyle::Pillars => (size, size),
+                ObstacleStyle::BrokenWalls if rng.chance(0.5) => (config.length.max(1), size),
+                ObstacleStyle::BrokenWalls => (size, config.length.max(1)),
+            };
+            // Centre the shape on the lattice point.
+            let (x0, y0) = (ax.saturating_sub(sw / 2), ay.saturating_sub(sh / 2));
+            if x0 + sw > w || y0 + sh > h {
+                continue;
+            }
+            let fits = (y0..y0 + sh)
+                .all(|y| (x0..x0 + sw).all(|x| eligible[y * w + x] && clear[y * w + x] > gap));
+            if !fits {
+                continue;
+            }
+            for y in y0..y0 + sh {
+                for x in x0..x0 + sw {
+                    grid.set(x as i32, y as i32, Tile::Wall);
+                }
+            }
+            walled += sw * sh;
+            clear = clearance(grid);
+        }
+    }
+    walled
+}
+
+/// Chebyshev distance from each cell to the nearest wall or the grid edge
+/// (walls are 0, cells on the edge 1).
+pub(crate) fn clearance(grid: &Grid<Tile>) -> Vec<usize> {
+    let (w, h) = (grid.width(), grid.height());
+    let mut dist = vec![usize::MAX; w * h];
+    let mut queue = VecDeque::new();
+    for (x, y, tile) in grid.iter() {
+        if !tile.is_floor() {
+            dist[y * w + x] = 0;
+            queue.push_back((x, y));
+        } else if x == 0 || y == 0 || x + 1 == w || y + 1 == h {
+            dist[y * w + x] = 1;
+            queue.push_back((x, y));
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let d = dist[y * w + x];
+        for dy in -1i32..=1 {
+            for dx in -1i32..=1 {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx < 0 || ny < 0 || nx as usize >= w || ny as usize >= h {
+                    continue;
+                }
+                let i = ny as usize * w + nx as usize;
+                if dist[i] > d + 1 {
+                    dist[i] = d + 1;
+                    queue.push_back((nx as usize, ny as usize));
+                }
+            }
+        }
+    }
+    dist
+}
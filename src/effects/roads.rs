@@ -0,0 +1,344 @@
+//! Roads that cross water by fords and bridges
+
+use super::bridges::BRIDGE_MASK;
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Name of the [`Masks`](crate::semantic::Masks) layer holding road cells,
+/// written by [`mark_road`].
+pub const ROAD_MASK: &str = "road";
+
+/// Name of the [`Masks`](crate::semantic::Masks) layer holding ford cells,
+/// written by [`mark_road`].
+pub const FORD_MASK: &str = "ford";
+
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Configuration for [`route_road`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoadConfig {
+    /// Longest bridge, in deck cells. Default: 6.
+    pub max_span: usize,
+    /// Water cells at most this far (4-connected steps) from land are
+    /// shallow enough to ford, so rivers up to twice this wide can be
+    /// forded across. 0 disables fords. Default: 1.
+    pub ford_depth: usize,
+    /// Cost of each ford cell, relative to 1 per land cell. Default: 3.0.
+    pub ford_cost: f32,
+    /// Cost of each bridge deck cell, relative to 1 per land cell.
+    /// Default: 4.0.
+    pub bridge_cost: f32,
+}
+
+impl Default for RoadConfig {
+    fn default() -> Self {
+        Self {
+            max_span: 6,
+            ford_depth: 1,
+            ford_cost: 3.0,
+            bridge_cost: 4.0,
+        }
+    }
+}
+
+impl RoadConfig {
+    /// Sets `max_span`.
+    pub fn with_max_span(mut self, max_span: usize) -> Self {
+        self.max_span = max_span;
+        self
+    }
+
+    /// Sets `ford_depth`.
+    pub fn with_ford_depth(mut self, ford_depth: usize) -> Self {
+        self.ford_depth = ford_depth;
+        self
+    }
+
+    /// Sets `ford_cost` and `bridge_cost`.
+    pub fn with_costs(mut self, ford_cost: f32, bridge_cost: f32) -> Self {
+        self.ford_cost = ford_cost;
+        self.bridge_cost = bridge_cost;
+        self
+    }
+}
+
+/// How a road crosses water.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossingKind {
+    /// Wading through shallow water.
+    Ford,
+    /// A straight deck over water of any depth.
+    Bridge,
+}
+
+/// One water crossing on a [`Road`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Crossing {
+    pub kind: CrossingKind,
+    /// Water cells crossed, in road order.
+    pub cells: Vec<(usize, usize)>,
+    /// Land cell before the crossing.
+    pub start: (usize, usize),
+    /// Land cell after the crossing.
+    pub end: (usize, usize),
+}
+
+/// A road found by [`route_road`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Road {
+    /// Every cell from start to end, 4-connected, including crossings.
+    pub path: Vec<(usize, usize)>,
+    pub crossings: Vec<Crossing>,
+    /// Total cost under the [`RoadConfig`] weights.
+    pub cost: f32,
+}
+
+impl Road {
+    /// Crossings of one kind.
+    pub fn crossings_of(&self, kind: CrossingKind) -> impl Iterator<Item = &Crossing> + '_ {
+        self.crossings.iter().filter(move |c| c.kind == kind)
+    }
+
+    /// Builds the road's bridges: deck cells become floor and are drained
+    /// from `water`. Fords stay water.
+    pub fn build_bridges(&self, grid: &mut Grid<Tile>, water: &mut [Vec<bool>]) {
+        for &(x, y) in self
+            .crossings_of(CrossingKind::Bridge)
+            .flat_map(|c| &c.cells)
+        {
+            grid.set(x as i32, y as i32, Tile::Floor);
+            if let Some(cell) = water.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *cell = false;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Node {
+    cost: f32,
+    index: usize,
+}
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Routes the cheapest road from `start` to `end` over land, fording
+/// shallow water and bridging wider water.
+///
+/// Land is floor that is not set in `water` (indexed `[y][x]`); walls are
+/// never crossed. A road steps 4-connected between land cells, may wade
+/// through water cells within `ford_depth` of land, and may bridge a
+/// straight horizontal or vertical run of up to `max_span` water cells
+/// between two land cells. Returns `None` if either end is not land or
+/// no route exists.
+pub fn route_road(
+    grid: &Grid<Tile>,
+    water: &[Vec<bool>],
+    start: (usize, usize),
+    end: (usize, usize),
+    config: &RoadConfig,
+) -> Option<Road> {
+    let (w, h) = (grid.width(), grid.height());
+    let wet = |x: usize, y: usize| {
+        water
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(false)
+    };
+    let floor = |x: usize, y: usize| grid[(x, y)].is_floor();
+    let land = |x: usize, y: usize| floor(x, y) && !wet(x, y);
+    if start.0 >= w || start.1 >= h || end.0 >= w || end.1 >= h {
+        return None;
+    }
+    if !land(start.0, start.1) || !land(end.0, end.1) {
+        return None;
+    }
+    let step = |x: usize, y: usize, (dx, dy): (i32, i32)| {
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        (nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h)
+            .then_some((nx as usize, ny as usize))
+    };
+
+    // Distance from land through water, for ford depth.
+    let mut depth = vec![usize::MAX; w * h];
+    let mut queue = VecDeque::new();
+    for (x, y, tile) in grid.iter() {
+        if tile.is_floor() && !wet(x, y) {
+            depth[y * w + x] = 0;
+            queue.push_back((x, y));
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let d = depth[y * w + x];
+        for dir in DIRECTIONS {
+            if let Some((nx, ny)) = step(x, y, dir) {
+                if floor(nx, ny) && depth[ny * w + nx] == usize::MAX {
+                    depth[ny * w + nx] = d + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+    let shallow =
+        |x: usize, y: usize| floor(x, y) && wet(x, y) && depth[y * w + x] <= config.ford_depth;
+
+    // Dijkstra; a bridge is one edge from shore to shore.
+    let mut cost = vec![f32::INFINITY; w * h];
+    let mut parent = vec![usize::MAX; w * h];
+    let mut heap = BinaryHeap::new();
+    let index = |(x, y): (usize, usize)| y * w + x;
+    cost[index(start)] = 0.0;
+    heap.push(Node {
+        cost: 0.0,
+        index: index(start),
+    });
+    while let Some(Node {
+        cost: here,
+        index: i,
+    }) = heap.pop()
+    {
+        if here > cost[i] {
+            continue;
+        }
+        if i == index(end) {
+            break;
+        }
+        let (x, y) = (i % w, i / w);
+        let on_land = !wet(x, y);
+        let mut relax = |next: (usize, usize), added: f32| {
+            let j = index(next);
+            if here + added < cost[j] {
+                cost[j] = here + added;
+                parent[j] = i;
+                heap.push(Node {
+                    cost: cost[j],
+                    index: j,
+                });
+            }
+        };
+        for dir in DIRECTIONS {
+            let Some((nx, ny)) = step(x, y, dir) else {
+                continue;
+            };
+            if land(nx, ny) {
+                relax((nx, ny), 1.0);
+            } else if shallow(nx, ny) {
+                relax((nx, ny), config.ford_cost);
+            }
+            if !on_land || !wet(nx, ny) || !floor(nx, ny) {
+                continue;
+            }
+            let mut span = 0;
+            let mut at = Some((nx, ny));
+            while let Some((cx, cy)) = at {
+                if !floor(cx, cy) || span > config.max_span {
+                    break;
+                }
+                if !wet(cx, cy) {
+                    relax((cx, cy), span as f32 * config.bridge_cost + 1.0);
+                    break;
+                }
+                span += 1;
+                at = step(cx, cy, dir);
+            }
+        }
+    }
+    if cost[index(end)].is_infinite() {
+        return None;
+    }
+
+    // Rebuild the path, filling in bridge decks.
+    let mut hops = vec![end];
+    let mut i = index(end);
+    while parent[i] != usize::MAX {
+        i = parent[i];
+        hops.push((i % w, i / w));
+    }
+    hops.reverse();
+    let mut path = vec![start];
+    let mut crossings = Vec::new();
+    let mut ford: Option<Crossing> = None;
+    for pair in hops.windows(2) {
+        let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+        let span = ax.abs_diff(bx) + ay.abs_diff(by);
+        if span > 1 {
+            let (dx, dy) = (
+                (bx as i32 - ax as i32).signum(),
+                (by as i32 - ay as i32).signum(),
+            );
+            let cells: Vec<(usize, usize)> = (1..span as i32)
+                .map(|k| ((ax as i32 + dx * k) as usize, (ay as i32 + dy * k) as usize))
+                .collect();
+            path.extend(&cells);
+            crossings.push(Crossing {
+                kind: CrossingKind::Bridge,
+                cells,
+                start: (ax, ay),
+                end: (bx, by),
+            });
+        } else if wet(bx, by) {
+            ford.get_or_insert_with(|| Crossing {
+                kind: CrossingKind::Ford,
+                cells: Vec::new(),
+                start: (ax, ay),
+                end: (ax, ay),
+            })
+            .cells
+            .push((bx, by));
+        } else if let Some(mut crossing) = ford.take() {
+            crossing.end = (bx, by);
+            crossings.push(crossing);
+        }
+        path.push((bx, by));
+    }
+
+    Some(Road {
+        path,
+        crossings,
+        cost: cost[index(end)],
+    })
+}
+
+/// Writes the [`ROAD_MASK`] layer for the whole road, [`FORD_MASK`] for
+/// ford cells, and [`BRIDGE_MASK`] for bridge decks; bridge decks and
+/// fords become walkable.
+pub fn mark_road(semantic: &mut SemanticLayers, road: &Road) {
+    let masks = &mut semantic.masks;
+    for &(x, y) in &road.path {
+        masks.set_named(ROAD_MASK, x, y, true);
+    }
+    for crossing in &road.crossings {
+        let layer = match crossing.kind {
+            CrossingKind::Ford => FORD_MASK,
+            CrossingKind::Bridge => BRIDGE_MASK,
+        };
+        for &(x, y) in &crossing.cells {
+            if y >= masks.height || x >= masks.width {
+                continue;
+            }
+            masks.set_named(layer, x, y, true);
+            masks.walkable[y][x] = true;
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Cave water tables and underground lakes
+
+use crate::noise::{NoiseExt, NoiseSource, Perlin};
+use crate::semantic::SemanticLayers;
+use crate::spatial::{shortest_path, PathfindingConstraints};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Name of the [`Masks`](crate::semantic::Masks) layer written by
+/// [`WaterTable::apply`].
+pub const WATER_MASK: &str = "water";
+
+const NEIGHBORS: [(i32, i32); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+/// How water levels are chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaterLevel {
+    /// Each connected cavern floods its own lowest `fill` fraction.
+    #[default]
+    PerCavern,
+    /// One level for the whole map, at the `fill` quantile of all floor.
+    Global,
+}
+
+/// Configuration for [`WaterTable::generate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaterConfig {
+    pub level: WaterLevel,
+    /// Fraction of floor below the water line, `0..=1`. Default: 0.3.
+    pub fill: f64,
+    /// Frequency of the cave floor elevation noise. Default: 0.08.
+    pub frequency: f64,
+    /// Caverns smaller than this stay dry (per-cavern mode). Default: 20.
+    pub min_cavern: usize,
+}
+
+impl Default for WaterConfig {
+    fn default() -> Self {
+        Self {
+            level: WaterLevel::PerCavern,
+            fill: 0.3,
+            frequency: 0.08,
+            min_cavern: 20,
+        }
+    }
+}
+
+impl WaterConfig {
+    /// Sets `level`.
+    pub fn with_level(mut self, level: WaterLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets `fill`.
+    pub fn with_fill(mut self, fill: f64) -> Self {
+        self.fill = fill;
+        self
+    }
+}
+
+/// Water cells over a cave grid's floor.
+///
+/// Tiles stay `Floor`; water is a separate layer that [`apply`](Self::apply)
+/// writes into semantic masks.
+#[derive(Debug, Clone)]
+pub struct WaterTable {
+    /// Floor elevation in `[0, 1]`, indexed `[y][x]`.
+    pub elevation: Vec<Vec<f64>>,
+    /// Flooded cells, indexed `[y][x]`.
+    pub water: Vec<Vec<bool>>,
+    /// Water level of each flooded cavern (one entry in global mode).
+    pub levels: Vec<f64>,
+}
+
+impl WaterTable {
+    /// Floods the floor of `grid` below its cavern (or global) water level.
+    pub fn generate(grid: &Grid<Tile>, config: &WaterConfig, seed: u64) -> Self {
+        let (w, h) = (grid.width(), grid.height());
+        let noise = Perlin::new(seed).fbm(3, 2.0, 0.5);
+        let f = config.frequency;
+        let elevation: Vec<Vec<f64>> = (0..h)
+            .map(|y| {
+                (0..w)
+                    .map(|x| (0.5 + 0.5 * noise.sample(x as f64 * f, y as f64 * f)).clamp(0.0, 1.0))
+                    .collect()
+            })
+            .collect();
+
+        let caverns = match config.level {
+            WaterLevel::PerCavern => grid
+                .flood_regions()
+                .into_iter()
+                .filter(|cells| cells.len() >= config.min_cavern.max(1))
+                .collect(),
+            WaterLevel::Global => vec![grid
+                .iter()
+                .filter(|(_, _, t)| t.is_floor())
+                .map(|(x, y, _)| (x, y))
+                .collect::<Vec<_>>()],
+        };
+
+        let mut water = vec![vec![false; w]; h];
+        let mut levels = Vec::new();
+        for cells in caverns {
+            let mut heights: Vec<f64> = cells.iter().map(|&(x, y)| elevation[y][x]).collect();
+            if heights.is_empty() || config.fill <= 0.0 {
+                continue;
+            }
+            heights.sort_by(f64::total_cmp);
+            let index = (config.fill.min(1.0) * heights.len() as f64) as usize;
+            let level = heights[index.min(heights.len() - 1)];
+            for &(x, y) in &cells {
+                water[y][x] = elevation[y][x] < level;
+            }
+            levels.push(level);
+        }
+
+        Self {
+            elevation,
+            water,
+            levels,
+        }
+    }
+
+    pub fn is_water(&self, x: usize, y: usize) -> bool {
+        self.water
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Number of flooded cells.
+    pub fn count(&self) -> usize {
+        self.water.iter().flatten().filter(|&&w| w).count()
+    }
+
+    /// `grid` with water cells turned to walls, for dry pathfinding.
+    pub fn dry_grid(&self, grid: &Grid<Tile>) -> Grid<Tile> {
+        let mut dry = grid.clone();
+        for (y, row) in self.water.iter().enumerate() {
+            for (x, &wet) in row.iter().enumerate() {
+                if wet {
+                    dry.set(x as i32, y as i32, Tile::Wall);
+                }
+            }
+        }
+        dry
+    }
+
+    /// Makes sure a dry path joins `start` and `end`, draining the fewest
+    /// water cells needed (a ford) when there is none.
+    ///
+    /// Returns `false` if the points are not floor or not connected at all.
+    pub fn ensure_dry_path(
+        &mut self,
+        grid: &Grid<Tile>,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> bool {
+        let floor =
+            |(x, y): (usize, usize)| grid.get(x as i32, y as i32).is_some_and(|t| t.is_floor());
+        if !floor(start) || !floor(end) {
+            return false;
+        }
+        let constraints = PathfindingConstraints::default();
+        if shortest_path(&self.dry_grid(grid), start, end, &constraints).is_some() {
+            return true;
+        }
+
+        // 0-1 BFS over all floor: entering water costs 1, dry floor 0.
+        let (w, h) = (grid.width(), grid.height());
+        let mut cost = vec![usize::MAX; w * h];
+        let mut parent = vec![None; w * h];
+        let mut queue = VecDeque::from([start]);
+        cost[start.1 * w + start.0] = usize::from(self.is_water(start.0, start.1));
+        while let Some((x, y)) = queue.pop_front() {
+            let here = cost[y * w + x];
+            for (dx, dy) in NEIGHBORS {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx < 0 || ny < 0 || nx as usize >= w || ny as usize >= h {
+                    continue;
+                }
+                let next = (nx as usize, ny as usize);
+                if !floor(next) {
+                    continue;
+                }
+                let step = usize::from(self.is_water(next.0, next.1));
+                let i = next.1 * w + next.0;
+                if here + step < cost[i] {
+                    cost[i] = here + step;
+                    parent[i] = Some((x, y));
+                    if step == 0 {
+                        queue.push_front(next);
+                    } else {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        if cost[end.1 * w + end.0] == usize::MAX {
+            return false;
+        }
+
+        let mut current = Some(end);
+        while let Some((x, y)) = current {
+            self.water[y][x] = false;
+            current = parent[y * w + x];
+        }
+        true
+    }
+
+    /// Writes the [`WATER_MASK`] layer, clears `walkable`, and sets
+    /// `no_spawn` on water cells. Markers are left where they are.
+    pub fn apply(&self, semantic: &mut SemanticLayers) {
+        let masks = &mut semantic.masks;
+        for (y, row) in self.water.iter().enumerate() {
+            for (x, &wet) in row.iter().enumerate() {
+                if !wet || y >= masks.height || x >= masks.width {
+                    continue;
+                }
+                masks.set_named(WATER_MASK, x, y, true);
+                masks.walkable[y][x] = false;
+                masks.no_spawn[y][x] = true;
+            }
+        }
+    }
+}
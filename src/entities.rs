@@ -0,0 +1,245 @@
+//! Marker-to-entity mapping.
+//!
+//! Semantic [`Marker`]s say *where* something goes; games still need to
+//! decide *what*. An [`EntityMapping`] holds ordered [`EntityRule`]s that
+//! match markers by tag or category, metadata, and the tags of the region
+//! they sit in, and turns each match into an engine-agnostic
+//! [`EntityDescriptor`] (name, archetype, properties). Mappings are plain
+//! data, so they load from JSON next to a [`Config`](crate::config::Config),
+//! and [`MapBundle::with_entities`](crate::bundle::MapBundle::with_entities)
+//! stores the result with the map.
+//!
+//! String properties, names, and archetypes may reference marker metadata
+//! as `{key}`, e.g. `"loot_table_{loot.tier}"`.
+//!
+//! ```rust
+//! use terrain_forge::entities::{EntityMapping, EntityRule};
+//! use terrain_forge::semantic::{
+//!     ConnectivityGraph, Marker, MarkerType, Masks, Region, SemanticLayers,
+//! };
+//!
+//! let mut region = Region::new(1, "room");
+//! region.tags.push("crypt".to_string());
+//! let mut chest = Marker::new(3, 4, MarkerType::LootTier { tier: 2 });
+//! chest.region_id = Some(1);
+//! let semantic = SemanticLayers {
+//!     regions: vec![region],
+//!     markers: vec![chest],
+//!     masks: Masks::new(8, 8),
+//!     connectivity: ConnectivityGraph::new(),
+//! };
+//!
+//! let mapping = EntityMapping::new()
+//!     .with_rule(EntityRule::new("loot", "bone_chest", "container").with_region_tag("crypt"))
+//!     .with_rule(
+//!         EntityRule::new("loot", "chest", "container")
+//!             .with_property("table", "loot_{loot.tier}"),
+//!     );
+//! let entities = mapping.map(&semantic);
+//! assert_eq!(entities[0].name, "bone_chest");
+//! assert_eq!((entities[0].x, entities[0].y), (3, 4));
+//! ```
+
+use crate::error::{Error, Result};
+use crate::semantic::{Marker, Region, SemanticLayers};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Converts matching markers into one kind of entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityRule {
+    /// Marker tag (`"loot_tier_2"`, `"spawn"`, a custom tag), category
+    /// (`"loot"`, `"encounter"`), or `"*"` for any marker.
+    pub marker: String,
+    /// Metadata entries the marker must have, with these exact values.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Tags the marker's region must all have. Markers without a region
+    /// never match a rule that lists tags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub region_tags: Vec<String>,
+    /// Entity name; may reference metadata as `{key}`.
+    pub name: String,
+    /// Engine-side type or prefab; may reference metadata as `{key}`.
+    pub archetype: String,
+    /// Properties copied to the entity; string values may reference
+    /// metadata as `{key}`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, Value>,
+}
+
+impl EntityRule {
+    pub fn new(
+        marker: impl Into<String>,
+        name: impl Into<String>,
+        archetype: impl Into<String>,
+    ) -> Self {
+        Self {
+            marker: marker.into(),
+            metadata: BTreeMap::new(),
+            region_tags: Vec::new(),
+            name: name.into(),
+            archetype: archetype.into(),
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Requires a metadata entry.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Requires a region tag.
+    pub fn with_region_tag(mut self, tag: impl Into<String>) -> Self {
+        self.region_tags.push(tag.into());
+        self
+    }
+
+    /// Adds a property.
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// Whether `marker`, in `region`, matches this rule.
+    pub fn matches(&self, marker: &Marker, region: Option<&Region>) -> bool {
+        let kind = self.marker == "*"
+            || self.marker == marker.tag()
+            || self.marker == marker.marker_type.category();
+        kind && self
+            .metadata
+            .iter()
+            .all(|(k, v)| marker.metadata.get(k) == Some(v))
+            && self
+                .region_tags
+                .iter()
+                .all(|tag| region.is_some_and(|r| r.tags.contains(tag)))
+    }
+
+    fn apply(&self, marker: &Marker) -> EntityDescriptor {
+        let fill = |text: &str| substitute(text, &marker.metadata);
+        EntityDescriptor {
+            name: fill(&self.name),
+            archetype: fill(&self.archetype),
+            x: marker.x,
+            y: marker.y,
+            marker: marker.tag(),
+            region_id: marker.region_id,
+            properties: self
+                .properties
+                .iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        Value::String(s) => Value::String(fill(s)),
+                        other => other.clone(),
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An entity to spawn, independent of any engine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityDescriptor {
+    pub name: String,
+    pub archetype: String,
+    pub x: u32,
+    pub y: u32,
+    /// Tag of the marker it came from.
+    pub marker: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, Value>,
+}
+
+/// Ordered rules; the first rule matching a marker wins.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityMapping {
+    pub rules: Vec<EntityRule>,
+}
+
+impl EntityMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a rule, after (lower priority than) existing ones.
+    pub fn with_rule(mut self, rule: EntityRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Parses a mapping from JSON: `{ "rules": [ ... ] }`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::parse("entity mapping", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// The entity for one marker, or `None` if no rule matches.
+    pub fn map_marker(&self, marker: &Marker, region: Option<&Region>) -> Option<EntityDescriptor> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(marker, region))
+            .map(|rule| rule.apply(marker))
+    }
+
+    /// Entities for every marker some rule matches, in marker order.
+    pub fn map(&self, semantic: &SemanticLayers) -> Vec<EntityDescriptor> {
+        let regions: BTreeMap<u32, &Region> = semantic.regions.iter().map(|r| (r.id, r)).collect();
+        semantic
+            .markers
+            .iter()
+            .filter_map(|marker| {
+                let region = marker.region_id.and_then(|id| regions.get(&id).copied());
+                self.map_marker(marker, region)
+            })
+            .collect()
+    }
+
+    /// Markers no rule matches.
+    pub fn unmapped<'a>(&self, semantic: &'a SemanticLayers) -> Vec<&'a Marker> {
+        let regions: BTreeMap<u32, &Region> = semantic.regions.iter().map(|r| (r.id, r)).collect();
+        semantic
+            .markers
+            .iter()
+            .filter(|marker| {
+                let region = marker.region_id.and_then(|id| regions.get(&id).copied());
+                !self.rules.iter().any(|rule| rule.matches(marker, region))
+            })
+            .collect()
+    }
+}
+
+/// Replaces each `{key}` with `metadata[key]`; unknown keys are kept as is.
+fn substitute(text: &str, metadata: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}') {
+            Some(close) => {
+                let key = &after[..close];
+                match metadata.get(key) {
+                    Some(value) => out.push_str(value),
+                    None => out.push_str(&rest[open..open + close + 2]),
+                }
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push_str(&rest[open..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,131 @@
+//! Entrance markers where corridors meet rooms.
+//!
+//! Connectivity edges say which regions touch, not where. [`find`] locates
+//! every threshold cell: a floor cell outside any room (see
+//! [`find_rooms`]) with a room cell beside it, i.e. the last
+//! corridor cell before the room opens up. Each [`Entrance`] knows which
+//! room it opens into and which side that room is on, so games can put
+//! gates, fog triggers, or room-reveal logic there. Entrances are plain
+//! floor, separate from any door tiles or door markers.
+//!
+//! ```rust
+//! use terrain_forge::dual::Facing;
+//! use terrain_forge::entrances::{self, EntranceConfig};
+//! use terrain_forge::{Grid, Tile};
+//!
+//! let mut grid = Grid::new(20, 8);
+//! grid.fill_rect(1, 1, 5, 5, Tile::Floor);
+//! grid.fill_rect(6, 3, 6, 1, Tile::Floor);
+//! grid.fill_rect(12, 1, 5, 5, Tile::Floor);
+//!
+//! let found = entrances::find(&grid, &EntranceConfig::default());
+//! assert_eq!(found.len(), 2);
+//! assert_eq!((found[0].x, found[0].y, found[0].facing), (6, 3, Facing::West));
+//! assert_eq!((found[1].x, found[1].y, found[1].facing), (11, 3, Facing::East));
+//! ```
+
+use crate::decoration::find_rooms;
+use crate::dual::Facing;
+use crate::semantic::{meta, Marker, SemanticLayers};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Configuration for [`find`] and [`place`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntranceConfig {
+    /// Open areas smaller than this don't count as rooms. Default: 9.
+    pub min_room_area: usize,
+    /// Marker tag. Default: `"entrance"`.
+    pub tag: String,
+}
+
+impl Default for EntranceConfig {
+    fn default() -> Self {
+        Self {
+            min_room_area: 9,
+            tag: "entrance".to_string(),
+        }
+    }
+}
+
+impl EntranceConfig {
+    /// Sets `min_room_area`.
+    pub fn with_min_room_area(mut self, area: usize) -> Self {
+        self.min_room_area = area;
+        self
+    }
+
+    /// Sets `tag`.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = tag.into();
+        self
+    }
+}
+
+/// A threshold cell just outside a room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entrance {
+    pub x: usize,
+    pub y: usize,
+    /// Side the room is on; stepping this way enters it.
+    pub facing: Facing,
+    /// Index of the room in [`find_rooms`] order.
+    pub room: usize,
+}
+
+/// Every entrance, in row-major order. A cell between two rooms is an
+/// entrance to each; a cell touching one room on several sides counts
+/// once, facing the first of north, south, east, west.
+pub fn find(grid: &Grid<Tile>, config: &EntranceConfig) -> Vec<Entrance> {
+    let rooms = find_rooms(grid, config.min_room_area);
+    let room_of: HashMap<(usize, usize), usize> = rooms
+        .iter()
+        .enumerate()
+        .flat_map(|(i, room)| room.cells.iter().map(move |&cell| (cell, i)))
+        .collect();
+
+    let mut entrances = Vec::new();
+    for (x, y, tile) in grid.iter() {
+        if !tile.is_floor() || room_of.contains_key(&(x, y)) {
+            continue;
+        }
+        let start = entrances.len();
+        for facing in [Facing::North, Facing::South, Facing::East, Facing::West] {
+            let (dx, dy) = facing.offset();
+            let (nx, ny) = (x as i64 + dx as i64, y as i64 + dy as i64);
+            if nx < 0 || ny < 0 {
+                continue;
+            }
+            let Some(&room) = room_of.get(&(nx as usize, ny as usize)) else {
+                continue;
+            };
+            if !entrances[start..].iter().any(|e: &Entrance| e.room == room) {
+                entrances.push(Entrance { x, y, facing, room });
+            }
+        }
+    }
+    entrances
+}
+
+/// Adds a marker tagged `config.tag` per entrance, with
+/// [`meta::ENTRANCE_FACING`], [`meta::ENTRANCE_ROOM`], and the region
+/// holding the cell. Returns the number of markers added.
+pub fn place(grid: &Grid<Tile>, semantic: &mut SemanticLayers, config: &EntranceConfig) -> usize {
+    let region_of: HashMap<(u32, u32), u32> = semantic
+        .regions
+        .iter()
+        .flat_map(|r| r.cells.iter().map(move |&cell| (cell, r.id)))
+        .collect();
+    let entrances = find(grid, config);
+    for entrance in &entrances {
+        let (x, y) = (entrance.x as u32, entrance.y as u32);
+        let mut marker = Marker::with_tag(x, y, config.tag.clone())
+            .with_metadata(meta::ENTRANCE_FACING, entrance.facing.name())
+            .with_metadata(meta::ENTRANCE_ROOM, entrance.room.to_string());
+        marker.region_id = region_of.get(&(x, y)).copied();
+        semantic.markers.push(marker);
+    }
+    entrances.len()
+}
@@ -0,0 +1,96 @@
+//! Crate-wide error type.
+//!
+//! Ops, configs, scripts, prefab loading, and [`generate_with_requirements`]
+//! all return [`Error`], so callers can match on what went wrong instead of
+//! parsing messages.
+//!
+//! ```rust
+//! use terrain_forge::{ops, Error, Grid};
+//!
+//! let mut grid = Grid::new(20, 20);
+//! match ops::generate("no_such_algorithm", &mut grid, None, None) {
+//!     Err(Error::UnknownAlgorithm(name)) => assert_eq!(name, "no_such_algorithm"),
+//!     other => panic!("unexpected {:?}", other),
+//! }
+//! ```
+//!
+//! [`generate_with_requirements`]: crate::generate_with_requirements
+
+use crate::constraints::ConstraintReport;
+
+/// Result alias using [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by terrain-forge.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// No algorithm is registered under this name.
+    #[error("Unknown algorithm: {0}")]
+    UnknownAlgorithm(String),
+    /// No effect is registered under this name.
+    #[error("Unknown effect: {0}")]
+    UnknownEffect(String),
+    /// A param of `op` is unknown, missing, mistyped, or out of range.
+    #[error("{op}: {message}")]
+    InvalidParam {
+        op: String,
+        name: String,
+        message: String,
+    },
+    /// No attempt met the constraints; `report` is the last attempt's.
+    #[error("Failed to generate map meeting requirements after {attempts} attempts")]
+    ConstraintUnsatisfied {
+        attempts: usize,
+        report: ConstraintReport,
+    },
+    /// Reading or writing a file failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Input in `format` (JSON config, op script, image, ...) didn't parse.
+    #[error("Invalid {format}: {message}")]
+    Parse { format: String, message: String },
+    /// Op `index` of a script failed.
+    #[error("op {index}: {source}")]
+    Script { index: usize, source: Box<Error> },
+    /// `algorithm` ran past its wall-clock `budget` with no fallback; see
+    /// [`TimeBudgeted`](crate::bench::TimeBudgeted).
+    #[error("{algorithm} exceeded its {budget:?} budget")]
+    BudgetExceeded {
+        algorithm: String,
+        budget: std::time::Duration,
+    },
+    /// Anything else.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl Error {
+    /// An [`Error::Other`] with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self::Other(message.into())
+    }
+
+    /// An [`Error::InvalidParam`] for a missing required param.
+    pub fn missing_param(op: &str, name: &str) -> Self {
+        Self::InvalidParam {
+            op: op.to_string(),
+            name: name.to_string(),
+            message: format!("missing required param '{}'", name),
+        }
+    }
+
+    /// An [`Error::Parse`] for `format`.
+    pub fn parse(format: impl Into<String>, err: impl std::fmt::Display) -> Self {
+        Self::Parse {
+            format: format.into(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::parse("JSON", err)
+    }
+}
@@ -0,0 +1,675 @@
+//! Exit placement by path distance from the spawn.
+//!
+//! Rerolling seeds until a random exit lands far enough away wastes
+//! generations. [`find`] instead builds a Dijkstra map from the spawn
+//! marker and scores every reachable floor cell directly: the farthest
+//! one wins, or with [`ExitConfig::target_distance`] the one closest to
+//! the target. Candidates can be limited to regions of given kinds, and
+//! dead-end cells (see [`find_dead_ends`]) are skipped unless allowed.
+//! [`place`] writes the winner as the map's `Exit` marker.
+//!
+//! Wave and escape modes want several exits instead, spread over the map's
+//! edges and about equally far from the spawn. [`find_multi`] picks one
+//! cell near each of `count` different edges so the spawn-to-exit path
+//! lengths differ by at most [`MultiExitConfig::tolerance`] where the map
+//! allows it. [`place_multi`] guarantees that tolerance: it carves
+//! corridors toward edges the spawn can't reach and shortcuts toward edges
+//! that are too far, and gives up rather than place unbalanced exits.
+//! [`balance`] measures any map's exits, and [`BalancedExitsConstraint`]
+//! checks them.
+//!
+//! ```rust
+//! use terrain_forge::exits::{self, ExitConfig, MultiExitConfig};
+//! use terrain_forge::semantic::{Marker, MarkerType};
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("rooms").unwrap().generate(&mut grid, 3);
+//! let mut semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(3));
+//! let (x, y, _) = grid.iter().find(|(_, _, t)| t.is_floor()).unwrap();
+//! semantic.markers.push(Marker::new(x as u32, y as u32, MarkerType::Spawn));
+//!
+//! let far = exits::find(&grid, &semantic, &ExitConfig::default()).unwrap();
+//! let near = exits::find(&grid, &semantic, &ExitConfig::default().with_target(10.0)).unwrap();
+//! assert!(far.distance > near.distance);
+//! assert!((near.distance - 10.0).abs() < 1.0);
+//!
+//! let multi = exits::place_multi(&mut grid, &mut semantic, &MultiExitConfig::default()).unwrap();
+//! assert_eq!(multi.exits.len(), 2);
+//! assert_ne!(multi.exits[0].edge, multi.exits[1].edge);
+//! assert!(multi.balanced(0.15));
+//! let balance = exits::balance(&grid, &semantic, 2).unwrap();
+//! assert_eq!((balance.exits, balance.edges), (2, 2));
+//! assert!(balance.spread.unwrap() <= 0.15);
+//! ```
+//!
+//! [`BalancedExitsConstraint`]: crate::constraints::BalancedExitsConstraint
+
+use crate::dual::Facing;
+use crate::effects::find_dead_ends;
+use crate::semantic::{meta, Marker, MarkerType, SemanticLayers};
+use crate::spatial::{dijkstra_map, DijkstraMap, PathfindingConstraints};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Configuration for [`find`] and [`place`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExitConfig {
+    /// Path distance to aim for; `None` takes the farthest cell.
+    /// Default: `None`.
+    pub target_distance: Option<f32>,
+    /// Region kinds the exit may sit in; empty allows any floor.
+    /// Default: empty.
+    pub region_kinds: Vec<String>,
+    /// Allow dead-end cells. Default: false.
+    pub allow_dead_ends: bool,
+}
+
+impl ExitConfig {
+    /// Sets `target_distance`.
+    pub fn with_target(mut self, distance: f32) -> Self {
+        self.target_distance = Some(distance);
+        self
+    }
+
+    /// Adds to `region_kinds`.
+    pub fn with_region_kind(mut self, kind: impl Into<String>) -> Self {
+        self.region_kinds.push(kind.into());
+        self
+    }
+
+    /// Sets `allow_dead_ends`.
+    pub fn with_dead_ends(mut self, allow: bool) -> Self {
+        self.allow_dead_ends = allow;
+        self
+    }
+}
+
+/// The chosen exit cell.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExitPlacement {
+    pub x: usize,
+    pub y: usize,
+    /// Path distance from the spawn, with diagonal steps costing 1.414.
+    pub distance: f32,
+    /// Region holding the cell, if any.
+    pub region_id: Option<u32>,
+}
+
+/// Finds the best exit cell for the first `Spawn` (or `PlayerStart`)
+/// marker. Cells under other markers never qualify; ties go to the first
+/// cell in row-major order. Returns `None` without a spawn or without a
+/// reachable candidate.
+pub fn find(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    config: &ExitConfig,
+) -> Option<ExitPlacement> {
+    let start = spawn_cell(grid, semantic)?;
+    let region_of = region_index(semantic);
+    let dead_ends: HashSet<(usize, usize)> = if config.allow_dead_ends {
+        HashSet::new()
+    } else {
+        find_dead_ends(grid).into_iter().collect()
+    };
+    let occupied = occupied_cells(semantic);
+
+    let map = dijkstra_map(grid, &[start], &PathfindingConstraints::default());
+    let score = |distance: f32| match config.target_distance {
+        Some(target) => -(distance - target).abs(),
+        None => distance,
+    };
+    let mut best: Option<(f32, ExitPlacement)> = None;
+    for (x, y, tile) in grid.iter() {
+        let distance = map.get(x, y);
+        if !tile.is_floor()
+            || !distance.is_finite()
+            || (x, y) == start
+            || occupied.contains(&(x, y))
+            || dead_ends.contains(&(x, y))
+        {
+            continue;
+        }
+        let region = region_of.get(&(x as u32, y as u32));
+        if !config.region_kinds.is_empty()
+            && !region.is_some_and(|(_, kind)| config.region_kinds.iter().any(|k| k == kind))
+        {
+            continue;
+        }
+        if best.is_none_or(|(s, _)| score(distance) > s) {
+            let placement = ExitPlacement {
+                x,
+                y,
+                distance,
+                region_id: region.map(|&(id, _)| id),
+            };
+            best = Some((score(distance), placement));
+        }
+    }
+    best.map(|(_, placement)| placement)
+}
+
+/// Replaces any `Exit` markers in `semantic` with one at the cell from
+/// [`find`], recording [`meta::EXIT_DISTANCE`]. Leaves the markers alone
+/// and returns `None` when there is no candidate.
+pub fn place(
+    grid: &Grid<Tile>,
+    semantic: &mut SemanticLayers,
+    config: &ExitConfig,
+) -> Option<ExitPlacement> {
+    let placement = find(grid, semantic, config)?;
+    semantic.markers.retain(|m| !m.is_exit());
+    let mut marker = Marker::new(placement.x as u32, placement.y as u32, MarkerType::Exit)
+        .with_metadata(meta::EXIT_DISTANCE, format!("{:.1}", placement.distance));
+    marker.region_id = placement.region_id;
+    semantic.markers.push(marker);
+    Some(placement)
+}
+
+/// Configuration for [`find_multi`] and [`place_multi`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultiExitConfig {
+    /// Exits to place, each on a different edge; at most 4. Default: 2.
+    pub count: usize,
+    /// Largest allowed spread of the exits' path distances, as
+    /// `(longest - shortest) / longest`. Default: 0.15.
+    pub tolerance: f32,
+    /// Cells from an edge that count as on it. Default: 2.
+    pub edge_margin: usize,
+    /// Let [`place_multi`] carve corridors toward edges the spawn can't
+    /// reach and shortcuts toward exits too far to balance. Default: true.
+    pub repair: bool,
+}
+
+impl Default for MultiExitConfig {
+    fn default() -> Self {
+        Self {
+            count: 2,
+            tolerance: 0.15,
+            edge_margin: 2,
+            repair: true,
+        }
+    }
+}
+
+impl MultiExitConfig {
+    /// Sets `count`.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Sets `tolerance`.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets `edge_margin`.
+    pub fn with_edge_margin(mut self, margin: usize) -> Self {
+        self.edge_margin = margin;
+        self
+    }
+
+    /// Sets `repair`.
+    pub fn with_repair(mut self, repair: bool) -> Self {
+        self.repair = repair;
+        self
+    }
+}
+
+/// An exit chosen on one map edge.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EdgeExit {
+    pub edge: Facing,
+    pub placement: ExitPlacement,
+}
+
+/// Exits from [`find_multi`] or [`place_multi`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiExits {
+    /// One exit per edge, in [`Facing`] declaration order.
+    pub exits: Vec<EdgeExit>,
+    /// `(longest - shortest) / longest` over the exits' path distances.
+    pub spread: f32,
+    /// Wall cells carved into floor by repairs.
+    pub carved: usize,
+}
+
+impl MultiExits {
+    /// Whether `spread` is within `tolerance`.
+    pub fn balanced(&self, tolerance: f32) -> bool {
+        self.spread <= tolerance
+    }
+}
+
+/// Picks exits on `config.count` different edges for the first `Spawn`
+/// (or `PlayerStart`) marker. Among sets within `tolerance` the one whose
+/// nearest exit is farthest wins; if none is, the set with the smallest
+/// spread. Returns `None` without a spawn or if fewer edges are reachable.
+pub fn find_multi(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    config: &MultiExitConfig,
+) -> Option<MultiExits> {
+    let start = spawn_cell(grid, semantic)?;
+    let map = dijkstra_map(grid, &[start], &PathfindingConstraints::default());
+    select(grid, semantic, &map, start, config)
+}
+
+/// Replaces any `Exit` markers in `semantic` with the exits from
+/// [`find_multi`], recording [`meta::EXIT_DISTANCE`] and
+/// [`meta::EXIT_EDGE`], with a spread guaranteed within `tolerance`.
+///
+/// With `repair` on, edges the spawn can't reach are first joined by a
+/// straight corridor from the nearest reachable floor. Then, while the
+/// spread is over `tolerance`, a 4-connected shortcut is carved toward the
+/// farthest exit's edge so a cell there comes as near as the nearest exit,
+/// and the exits are picked again. Carved cells are not added to any region.
+///
+/// Returns `None`, leaving both the grid and the markers alone, when there
+/// is still no set of exits within `tolerance`; callers can reroll the
+/// seed, as [`BalancedExitsConstraint`] does for
+/// [`generate_map`](crate::generate_map).
+///
+/// [`BalancedExitsConstraint`]: crate::constraints::BalancedExitsConstraint
+pub fn place_multi(
+    grid: &mut Grid<Tile>,
+    semantic: &mut SemanticLayers,
+    config: &MultiExitConfig,
+) -> Option<MultiExits> {
+    let start = spawn_cell(grid, semantic)?;
+    let mut work = grid.clone();
+    let mut carved = 0;
+    let mut map = dijkstra_map(&work, &[start], &PathfindingConstraints::default());
+    if config.repair {
+        carved += reach_edges(&mut work, &mut map, start, config);
+    }
+
+    let mut multi = select(&work, semantic, &map, start, config)?;
+    // Each shortcut settles the farthest exit; the bound guards against
+    // shortcuts that also pull other exits closer.
+    let shortcuts = if config.repair { FACINGS.len() * 2 } else { 0 };
+    for _ in 0..shortcuts {
+        if multi.balanced(config.tolerance) {
+            break;
+        }
+        let Some(path) = shortcut(&work, &map, &multi, config.edge_margin) else {
+            break;
+        };
+        for (x, y) in path {
+            if !work[(x, y)].is_floor() {
+                work.set(x as i32, y as i32, Tile::Floor);
+                carved += 1;
+            }
+        }
+        map = dijkstra_map(&work, &[start], &PathfindingConstraints::default());
+        multi = select(&work, semantic, &map, start, config)?;
+    }
+    if !multi.balanced(config.tolerance) {
+        return None;
+    }
+
+    multi.carved = carved;
+    *grid = work;
+    semantic.markers.retain(|m| !m.is_exit());
+    for exit in &multi.exits {
+        let placement = exit.placement;
+        let mut marker = Marker::new(placement.x as u32, placement.y as u32, MarkerType::Exit)
+            .with_metadata(meta::EXIT_DISTANCE, format!("{:.1}", placement.distance))
+            .with_metadata(meta::EXIT_EDGE, exit.edge.name());
+        marker.region_id = placement.region_id;
+        semantic.markers.push(marker);
+    }
+    Some(multi)
+}
+
+/// Carves straight corridors toward edges the spawn can't reach until
+/// `config.count` edges are reachable, updating `map`. Returns the number
+/// of cells carved.
+fn reach_edges(
+    grid: &mut Grid<Tile>,
+    map: &mut DijkstraMap,
+    start: (usize, usize),
+    config: &MultiExitConfig,
+) -> usize {
+    let wanted = config.count.min(4);
+    let mut carved = 0;
+    // Each repair reaches one more edge; the bound guards against corner
+    // cells that land nearer another edge than the one carved toward.
+    for _ in 0..FACINGS.len() {
+        let reached: HashSet<Facing> = grid
+            .iter()
+            .filter(|&(x, y, _)| map.get(x, y).is_finite())
+            .filter_map(|(x, y, _)| edge_of(grid, x, y, config.edge_margin))
+            .collect();
+        if reached.len() >= wanted {
+            break;
+        }
+        // Join the missing edge closest to the reachable area, from the
+        // cell farthest from the corners.
+        let Some((gap, _, x, y, edge)) = FACINGS
+            .iter()
+            .filter(|edge| !reached.contains(edge))
+            .filter_map(|&edge| {
+                grid.iter()
+                    .filter(|&(x, y, _)| map.get(x, y).is_finite())
+                    .map(|(x, y, _)| {
+                        let (along, length) = match edge {
+                            Facing::North | Facing::South => (x, grid.width()),
+                            Facing::East | Facing::West => (y, grid.height()),
+                        };
+                        let corner = along.min(length - 1 - along);
+                        (edge_distance(grid, x, y, edge), Reverse(corner), x, y, edge)
+                    })
+                    .min_by_key(|&(gap, corner, ..)| (gap, corner))
+            })
+            .min_by_key(|&(gap, corner, ..)| (gap, corner))
+        else {
+            break;
+        };
+        // Stop as soon as the corridor is within the margin of the edge.
+        let (dx, dy) = edge.offset();
+        let (mut cx, mut cy) = (x as i32, y as i32);
+        for _ in config.edge_margin..gap {
+            cx += dx;
+            cy += dy;
+            if grid.get(cx, cy).is_some_and(|t| !t.is_floor()) {
+                grid.set(cx, cy, Tile::Floor);
+                carved += 1;
+            }
+        }
+        *map = dijkstra_map(grid, &[start], &PathfindingConstraints::default());
+    }
+    carved
+}
+
+/// A 4-connected corridor meant to bring the farthest exit's edge down to
+/// the nearest exit's path distance: an L from a reachable cell to a cell
+/// on that edge, picked so walking to the first and then straight to the
+/// second comes closest to that distance. `None` if no corridor would
+/// beat the farthest exit.
+fn shortcut(
+    grid: &Grid<Tile>,
+    map: &DijkstraMap,
+    multi: &MultiExits,
+    margin: usize,
+) -> Option<Vec<(usize, usize)>> {
+    let by_distance =
+        |a: &&EdgeExit, b: &&EdgeExit| a.placement.distance.total_cmp(&b.placement.distance);
+    let far = multi.exits.iter().max_by(by_distance)?;
+    let near = multi.exits.iter().min_by(by_distance)?.placement.distance;
+    let reachable: Vec<(usize, usize, f32)> = grid
+        .iter()
+        .filter(|&(x, y, _)| map.get(x, y).is_finite())
+        .map(|(x, y, _)| (x, y, map.get(x, y)))
+        .collect();
+    // Ties go to the first pair in row-major order.
+    let (_, from, to) = grid
+        .iter()
+        .filter(|&(x, y, _)| edge_of(grid, x, y, margin) == Some(far.edge))
+        .flat_map(|(tx, ty, _)| {
+            reachable.iter().map(move |&(x, y, distance)| {
+                let via = distance + (x.abs_diff(tx) + y.abs_diff(ty)) as f32;
+                (via, (x, y), (tx, ty))
+            })
+        })
+        .filter(|&(via, ..)| via + 1.0 < far.placement.distance)
+        .min_by(|a, b| (a.0 - near).abs().total_cmp(&(b.0 - near).abs()))?;
+
+    // Of the two L shapes, open the one crossing fewer walls.
+    let walls = |path: &[(usize, usize)]| path.iter().filter(|&&c| !grid[c].is_floor()).count();
+    let across = l_path(from, to, true);
+    let down = l_path(from, to, false);
+    Some(if walls(&down) < walls(&across) {
+        down
+    } else {
+        across
+    })
+}
+
+/// Cells from `from` to `to` inclusive, moving along x first when
+/// `x_first`, then along the other axis.
+fn l_path(from: (usize, usize), to: (usize, usize), x_first: bool) -> Vec<(usize, usize)> {
+    let corner = if x_first {
+        (to.0, from.1)
+    } else {
+        (from.0, to.1)
+    };
+    let mut path = vec![from];
+    for target in [corner, to] {
+        let mut cell = *path.last().unwrap_or(&from);
+        while cell != target {
+            cell.0 = step_toward(cell.0, target.0);
+            cell.1 = step_toward(cell.1, target.1);
+            path.push(cell);
+        }
+    }
+    path
+}
+
+fn step_toward(from: usize, to: usize) -> usize {
+    match from.cmp(&to) {
+        std::cmp::Ordering::Less => from + 1,
+        std::cmp::Ordering::Greater => from - 1,
+        std::cmp::Ordering::Equal => from,
+    }
+}
+
+/// Balance of a map's `Exit` markers, from [`balance`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitBalance {
+    /// `Exit` (or `"Exit"`-tagged) markers.
+    pub exits: usize,
+    /// Distinct edges those exits sit on.
+    pub edges: usize,
+    /// `(longest - shortest) / longest` over their path distances from the
+    /// spawn; `None` if an exit is unreachable or there are none.
+    pub spread: Option<f32>,
+}
+
+/// Measures the `Exit` markers against the first `Spawn` (or
+/// `PlayerStart`) marker, counting a cell as on an edge within
+/// `edge_margin` cells of it. Returns `None` without a spawn.
+pub fn balance(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    edge_margin: usize,
+) -> Option<ExitBalance> {
+    let start = spawn_cell(grid, semantic)?;
+    let map = dijkstra_map(grid, &[start], &PathfindingConstraints::default());
+    let exits: Vec<(usize, usize)> = semantic
+        .exits()
+        .map(|m| (m.x as usize, m.y as usize))
+        .collect();
+    let edges: HashSet<Facing> = exits
+        .iter()
+        .filter_map(|&(x, y)| edge_of(grid, x, y, edge_margin))
+        .collect();
+    let distances: Option<Vec<f32>> = exits
+        .iter()
+        .map(|&(x, y)| Some(map.get(x, y)).filter(|d| d.is_finite()))
+        .collect();
+    let spread = distances.filter(|d| !d.is_empty()).map(|d| spread_of(&d));
+    Some(ExitBalance {
+        exits: exits.len(),
+        edges: edges.len(),
+        spread,
+    })
+}
+
+/// Path distance and cell.
+type Candidate = (f32, usize, usize);
+/// Whether the spread is out of tolerance, then a value to minimize.
+type Score = (bool, f32);
+
+const FACINGS: [Facing; 4] = [Facing::North, Facing::South, Facing::East, Facing::West];
+
+/// The first spawn marker's cell, if it is floor.
+fn spawn_cell(grid: &Grid<Tile>, semantic: &SemanticLayers) -> Option<(usize, usize)> {
+    let spawn = semantic.spawn()?;
+    let start = (spawn.x as usize, spawn.y as usize);
+    grid.get(start.0 as i32, start.1 as i32)
+        .is_some_and(|t| t.is_floor())
+        .then_some(start)
+}
+
+fn region_index(semantic: &SemanticLayers) -> HashMap<(u32, u32), (u32, &str)> {
+    semantic
+        .regions
+        .iter()
+        .flat_map(|r| {
+            r.cells
+                .iter()
+                .map(move |&cell| (cell, (r.id, r.kind.as_str())))
+        })
+        .collect()
+}
+
+/// Cells under markers other than exits.
+fn occupied_cells(semantic: &SemanticLayers) -> HashSet<(usize, usize)> {
+    semantic
+        .markers
+        .iter()
+        .filter(|m| !m.is_exit())
+        .map(|m| (m.x as usize, m.y as usize))
+        .collect()
+}
+
+fn edge_distance(grid: &Grid<Tile>, x: usize, y: usize, edge: Facing) -> usize {
+    match edge {
+        Facing::North => y,
+        Facing::South => grid.height() - 1 - y,
+        Facing::East => grid.width() - 1 - x,
+        Facing::West => x,
+    }
+}
+
+/// The nearest edge within `margin` cells; ties go north, south, east, west.
+fn edge_of(grid: &Grid<Tile>, x: usize, y: usize, margin: usize) -> Option<Facing> {
+    FACINGS
+        .into_iter()
+        .map(|edge| (edge_distance(grid, x, y, edge), edge))
+        .filter(|&(d, _)| d <= margin)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, edge)| edge)
+}
+
+fn spread_of(distances: &[f32]) -> f32 {
+    let longest = distances.iter().copied().fold(0.0, f32::max);
+    let shortest = distances.iter().copied().fold(f32::INFINITY, f32::min);
+    if longest > 0.0 {
+        (longest - shortest) / longest
+    } else {
+        0.0
+    }
+}
+
+fn select(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    map: &DijkstraMap,
+    start: (usize, usize),
+    config: &MultiExitConfig,
+) -> Option<MultiExits> {
+    let wanted = config.count.min(4);
+    if wanted == 0 {
+        return Some(MultiExits {
+            exits: Vec::new(),
+            spread: 0.0,
+            carved: 0,
+        });
+    }
+    let region_of = region_index(semantic);
+    let occupied = occupied_cells(semantic);
+    // Candidates per edge, nearest first; ties stay in row-major order.
+    let mut candidates: Vec<(Facing, Vec<Candidate>)> =
+        FACINGS.iter().map(|&edge| (edge, Vec::new())).collect();
+    for (x, y, tile) in grid.iter() {
+        let distance = map.get(x, y);
+        if !tile.is_floor()
+            || !distance.is_finite()
+            || (x, y) == start
+            || occupied.contains(&(x, y))
+        {
+            continue;
+        }
+        if let Some(edge) = edge_of(grid, x, y, config.edge_margin) {
+            let i = FACINGS.iter().position(|&e| e == edge).unwrap();
+            candidates[i].1.push((distance, x, y));
+        }
+    }
+    candidates.retain(|(_, cells)| !cells.is_empty());
+    if candidates.len() < wanted {
+        return None;
+    }
+    for (_, cells) in &mut candidates {
+        cells.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+
+    // Anchor on each candidate as the nearest exit and pair it with the
+    // nearest cell at least as far on each other edge; this visits the
+    // best set for every possible shortest distance.
+    // Score and picks, as (edge, candidate) indices.
+    let mut best: Option<(Score, Vec<(usize, usize)>)> = None;
+    for (anchor_edge, (_, cells)) in candidates.iter().enumerate() {
+        for (anchor, &(shortest, ..)) in cells.iter().enumerate() {
+            let mut others: Vec<(f32, usize, usize)> = candidates
+                .iter()
+                .enumerate()
+                .filter(|&(edge, _)| edge != anchor_edge)
+                .filter_map(|(edge, (_, cells))| {
+                    let i = cells.partition_point(|c| c.0 < shortest);
+                    cells.get(i).map(|c| (c.0, edge, i))
+                })
+                .collect();
+            if others.len() + 1 < wanted {
+                continue;
+            }
+            others.sort_by(|a, b| a.0.total_cmp(&b.0));
+            others.truncate(wanted - 1);
+            let longest = others.last().map_or(shortest, |o| o.0);
+            let spread = spread_of(&[shortest, longest]);
+            // Lower is better: balanced sets by nearest exit, farthest
+            // first, then unbalanced ones by spread.
+            let score = if spread <= config.tolerance {
+                (false, -shortest)
+            } else {
+                (true, spread)
+            };
+            if best.as_ref().is_none_or(|(s, _)| score < *s) {
+                let mut picks = vec![(anchor_edge, anchor)];
+                picks.extend(others.iter().map(|&(_, edge, i)| (edge, i)));
+                best = Some((score, picks));
+            }
+        }
+    }
+
+    let (_, mut picks) = best?;
+    picks.sort_unstable();
+    let exits: Vec<EdgeExit> = picks
+        .into_iter()
+        .map(|(edge, i)| {
+            let (distance, x, y) = candidates[edge].1[i];
+            EdgeExit {
+                edge: candidates[edge].0,
+                placement: ExitPlacement {
+                    x,
+                    y,
+                    distance,
+                    region_id: region_of.get(&(x as u32, y as u32)).map(|&(id, _)| id),
+                },
+            }
+        })
+        .collect();
+    let distances: Vec<f32> = exits.iter().map(|e| e.placement.distance).collect();
+    Some(MultiExits {
+        spread: spread_of(&distances),
+        exits,
+        carved: 0,
+    })
+}
@@ -0,0 +1,338 @@
+//! Packed collision bitmaps for engine physics and pathing layers.
+//!
+//! A [`CollisionMask`] stores one bit per cell, row-major, each row padded
+//! to `stride` bytes. By default a set bit means walkable; set
+//! [`CollisionConfig::solid`] to mark blocked cells instead. A 100x100 map
+//! packs into 1300 bytes where a JSON boolean array takes ~55 KB.
+//!
+//! [`to_bytes`] writes the mask behind a 20-byte header, optionally
+//! run-length encoded:
+//!
+//! | bytes  | content                                          |
+//! |--------|--------------------------------------------------|
+//! | 0..4   | magic `TFCL`                                     |
+//! | 4      | [`FORMAT_VERSION`]                               |
+//! | 5      | flags: `1` MSB-first, `2` RLE, `4` solid         |
+//! | 6..8   | reserved, `0`                                    |
+//! | 8..12  | width, little-endian `u32`                       |
+//! | 12..16 | height, little-endian `u32`                      |
+//! | 16..20 | stride in bytes, little-endian `u32`             |
+//!
+//! RLE payloads are alternating run lengths of clear and set bits over the
+//! cells in row-major order (padding excluded), starting with clear bits,
+//! each as an unsigned LEB128 varint.
+//!
+//! ```rust
+//! use terrain_forge::export::{collision, CollisionConfig, CollisionMask};
+//! use terrain_forge::{ops, Grid};
+//!
+//! let mut grid = Grid::new(100, 100);
+//! ops::generate("bsp", &mut grid, Some(3), None).unwrap();
+//!
+//! let mask = CollisionMask::from_grid(&grid, &CollisionConfig::default());
+//! assert_eq!(mask.stride, 13);
+//! assert_eq!(mask.bits.len(), 1300);
+//!
+//! let bytes = collision::to_bytes(&grid, &CollisionConfig::default().with_rle(true));
+//! let back = CollisionMask::from_bytes(&bytes).unwrap();
+//! assert_eq!(back, mask);
+//! ```
+
+use crate::error::{Error, Result};
+use crate::{Cell, Grid};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Leading bytes of an encoded mask.
+pub const MAGIC: [u8; 4] = *b"TFCL";
+/// Current header version.
+pub const FORMAT_VERSION: u8 = 1;
+/// Header length in bytes.
+pub const HEADER_LEN: usize = 20;
+
+const FLAG_MSB: u8 = 1;
+const FLAG_RLE: u8 = 2;
+const FLAG_SOLID: u8 = 4;
+
+/// Bit order within each byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BitOrder {
+    /// Cell `x` is bit `x % 8` (Unity `BitArray`, most C bitsets).
+    #[default]
+    Lsb0,
+    /// Cell `x` is bit `7 - x % 8` (PBM, most image formats).
+    Msb0,
+}
+
+/// Configuration for collision export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollisionConfig {
+    /// Row stride is rounded up to a multiple of this many bytes.
+    /// Default: 1.
+    pub row_align: usize,
+    /// Default: [`BitOrder::Lsb0`].
+    pub bit_order: BitOrder,
+    /// Set bits mark blocked cells rather than walkable ones.
+    /// Default: false.
+    pub solid: bool,
+    /// Run-length encode the payload in [`to_bytes`]. Default: false.
+    pub rle: bool,
+}
+
+impl Default for CollisionConfig {
+    fn default() -> Self {
+        Self {
+            row_align: 1,
+            bit_order: BitOrder::Lsb0,
+            solid: false,
+            rle: false,
+        }
+    }
+}
+
+impl CollisionConfig {
+    /// Sets `row_align`.
+    pub fn with_row_align(mut self, bytes: usize) -> Self {
+        self.row_align = bytes;
+        self
+    }
+
+    /// Sets `bit_order`.
+    pub fn with_bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Sets `solid`.
+    pub fn with_solid(mut self, solid: bool) -> Self {
+        self.solid = solid;
+        self
+    }
+
+    /// Sets `rle`.
+    pub fn with_rle(mut self, rle: bool) -> Self {
+        self.rle = rle;
+        self
+    }
+}
+
+/// One bit per cell, row-major.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollisionMask {
+    pub width: usize,
+    pub height: usize,
+    /// Bytes per row, padding included.
+    pub stride: usize,
+    pub bit_order: BitOrder,
+    /// Set bits mark blocked cells.
+    pub solid: bool,
+    /// `stride * height` bytes.
+    pub bits: Vec<u8>,
+}
+
+impl CollisionMask {
+    /// An all-clear mask.
+    pub fn new(width: usize, height: usize, config: &CollisionConfig) -> Self {
+        let align = config.row_align.max(1);
+        let stride = width.div_ceil(8).div_ceil(align) * align;
+        Self {
+            width,
+            height,
+            stride,
+            bit_order: config.bit_order,
+            solid: config.solid,
+            bits: vec![0; stride * height],
+        }
+    }
+
+    /// Packs the passability of `grid`.
+    pub fn from_grid<C: Cell>(grid: &Grid<C>, config: &CollisionConfig) -> Self {
+        let mut mask = Self::new(grid.width(), grid.height(), config);
+        for (x, y, cell) in grid.iter() {
+            if cell.is_passable() != mask.solid {
+                mask.set(x, y, true);
+            }
+        }
+        mask
+    }
+
+    fn locate(&self, x: usize, y: usize) -> (usize, u8) {
+        let bit = match self.bit_order {
+            BitOrder::Lsb0 => x % 8,
+            BitOrder::Msb0 => 7 - x % 8,
+        };
+        (y * self.stride + x / 8, 1 << bit)
+    }
+
+    /// Raw bit at `(x, y)`; `false` out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let (byte, bit) = self.locate(x, y);
+        self.bits[byte] & bit != 0
+    }
+
+    /// Sets the raw bit at `(x, y)`; ignored out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let (byte, bit) = self.locate(x, y);
+        if value {
+            self.bits[byte] |= bit;
+        } else {
+            self.bits[byte] &= !bit;
+        }
+    }
+
+    /// Whether `(x, y)` is walkable, honoring `solid`; `false` out of bounds.
+    pub fn is_walkable(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.get(x, y) != self.solid
+    }
+
+    /// Alternating clear/set run lengths over cells, starting with clear.
+    pub fn runs(&self) -> Vec<usize> {
+        let mut runs = Vec::new();
+        let mut current = false;
+        let mut length = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) != current {
+                    runs.push(length);
+                    current = !current;
+                    length = 0;
+                }
+                length += 1;
+            }
+        }
+        runs.push(length);
+        runs
+    }
+
+    /// Encodes the mask with its header, RLE-compressed when `rle` is set.
+    pub fn to_bytes(&self, rle: bool) -> Vec<u8> {
+        let mut flags = 0;
+        if self.bit_order == BitOrder::Msb0 {
+            flags |= FLAG_MSB;
+        }
+        if rle {
+            flags |= FLAG_RLE;
+        }
+        if self.solid {
+            flags |= FLAG_SOLID;
+        }
+        let mut out = Vec::with_capacity(HEADER_LEN + self.bits.len());
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&[FORMAT_VERSION, flags, 0, 0]);
+        for n in [self.width, self.height, self.stride] {
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        if rle {
+            for run in self.runs() {
+                write_varint(&mut out, run as u64);
+            }
+        } else {
+            out.extend_from_slice(&self.bits);
+        }
+        out
+    }
+
+    /// Decodes bytes written by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let invalid = |message: &str| Error::parse("collision mask", message);
+        if bytes.len() < HEADER_LEN || bytes[..4] != MAGIC {
+            return Err(invalid("missing TFCL header"));
+        }
+        if bytes[4] > FORMAT_VERSION {
+            return Err(invalid(&format!("unsupported version {}", bytes[4])));
+        }
+        let flags = bytes[5];
+        let read_u32 = |at: usize| {
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]) as usize
+        };
+        let (width, height, stride) = (read_u32(8), read_u32(12), read_u32(16));
+        if stride < width.div_ceil(8) {
+            return Err(invalid("stride is shorter than a row"));
+        }
+        let mut mask = Self {
+            width,
+            height,
+            stride,
+            bit_order: if flags & FLAG_MSB != 0 {
+                BitOrder::Msb0
+            } else {
+                BitOrder::Lsb0
+            },
+            solid: flags & FLAG_SOLID != 0,
+            bits: Vec::new(),
+        };
+        let payload = &bytes[HEADER_LEN..];
+        if flags & FLAG_RLE == 0 {
+            if payload.len() != stride * height {
+                return Err(invalid("payload size does not match stride and height"));
+            }
+            mask.bits = payload.to_vec();
+            return Ok(mask);
+        }
+
+        mask.bits = vec![0; stride * height];
+        let (mut cell, mut value, mut at) = (0, false, 0);
+        while at < payload.len() {
+            let run = read_varint(payload, &mut at)
+                .ok_or_else(|| invalid("truncated run length"))? as usize;
+            if run > width * height - cell {
+                return Err(invalid("runs exceed the map size"));
+            }
+            if value {
+                for i in cell..cell + run {
+                    mask.set(i % width, i / width, true);
+                }
+            }
+            cell += run;
+            value = !value;
+        }
+        if cell != width * height {
+            return Err(invalid("runs do not cover the map"));
+        }
+        Ok(mask)
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], at: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*at)?;
+        *at += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Packs `grid` and encodes it with its header (see the module docs).
+pub fn to_bytes<C: Cell>(grid: &Grid<C>, config: &CollisionConfig) -> Vec<u8> {
+    CollisionMask::from_grid(grid, config).to_bytes(config.rle)
+}
+
+/// Writes [`to_bytes`] output to `path`.
+pub fn save<C: Cell, P: AsRef<Path>>(
+    path: P,
+    grid: &Grid<C>,
+    config: &CollisionConfig,
+) -> Result<()> {
+    std::fs::write(path, to_bytes(grid, config))?;
+    Ok(())
+}
@@ -0,0 +1,261 @@
+//! Godot 4 scene export (feature `godot`).
+//!
+//! Writes a `.tscn` scene with an embedded `TileSet` and a `TileMap` node.
+//! Impassable cells use atlas tiles that carry a full-cell collision polygon,
+//! and markers become `Marker2D` nodes with their data as metadata.
+//!
+//! ```rust
+//! use terrain_forge::{algorithms, Grid, SemanticExtractor, Rng};
+//! use terrain_forge::export::{godot, GodotConfig};
+//!
+//! let mut grid = Grid::new(40, 30);
+//! algorithms::get("bsp").unwrap().generate(&mut grid, 7);
+//! let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(7));
+//!
+//! let scene = godot::to_tscn(&grid, Some(&semantic), &GodotConfig::default());
+//! assert!(scene.starts_with("[gd_scene"));
+//! ```
+
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Configuration for Godot scene export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GodotConfig {
+    /// Tile size in pixels. Default: 16.
+    pub tile_size: u32,
+    /// Atlas texture resource path. Default: "res://terrain.png".
+    pub texture_path: String,
+    /// Atlas coordinates of the floor tile. Default: (0, 0).
+    pub floor_atlas: (i32, i32),
+    /// Atlas coordinates of the wall tile (with collision). Default: (1, 0).
+    pub wall_atlas: (i32, i32),
+    /// Atlas coordinates used for floor cells that are not walkable per the
+    /// semantic walkable mask (with collision). Default: (2, 0).
+    pub blocked_floor_atlas: (i32, i32),
+    /// Physics collision layer bitmask. Default: 1.
+    pub collision_layer: u32,
+    /// Name of the scene root node. Default: "Level".
+    pub root_name: String,
+    /// Emit `Marker2D` nodes for markers. Default: true.
+    pub include_markers: bool,
+}
+
+impl Default for GodotConfig {
+    fn default() -> Self {
+        Self {
+            tile_size: 16,
+            texture_path: "res://terrain.png".to_string(),
+            floor_atlas: (0, 0),
+            wall_atlas: (1, 0),
+            blocked_floor_atlas: (2, 0),
+            collision_layer: 1,
+            root_name: "Level".to_string(),
+            include_markers: true,
+        }
+    }
+}
+
+impl GodotConfig {
+    /// Sets `tile_size`.
+    pub fn with_tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    /// Sets `texture_path`.
+    pub fn with_texture_path(mut self, texture_path: impl Into<String>) -> Self {
+        self.texture_path = texture_path.into();
+        self
+    }
+
+    /// Sets `root_name`.
+    pub fn with_root_name(mut self, root_name: impl Into<String>) -> Self {
+        self.root_name = root_name.into();
+        self
+    }
+}
+
+/// Renders the grid as a Godot 4 `.tscn` scene.
+///
+/// With semantic layers, collision follows the walkable mask (so prefab or
+/// effect edits to the mask are respected); otherwise walls collide.
+pub fn to_tscn(
+    grid: &Grid<Tile>,
+    semantic: Option<&SemanticLayers>,
+    config: &GodotConfig,
+) -> String {
+    let (w, h) = (grid.width(), grid.height());
+    let walkable = semantic
+        .map(|s| &s.masks)
+        .filter(|m| m.width == w && m.height == h && m.walkable.len() == h);
+    let half = config.tile_size as f64 / 2.0;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "[gd_scene load_steps=4 format=3]");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        r#"[ext_resource type="Texture2D" path="{}" id="1_texture"]"#,
+        escape(&config.texture_path)
+    );
+    let _ = writeln!(out);
+
+    // Tile set: floor without collision, wall and blocked floor with a full-cell polygon.
+    let polygon = format!(
+        "PackedVector2Array({h0}, {h0}, {h1}, {h0}, {h1}, {h1}, {h0}, {h1})",
+        h0 = -half,
+        h1 = half
+    );
+    let _ = writeln!(
+        out,
+        r#"[sub_resource type="TileSetAtlasSource" id="TileSetAtlasSource_terrain"]"#
+    );
+    let _ = writeln!(out, r#"texture = ExtResource("1_texture")"#);
+    let _ = writeln!(
+        out,
+        "texture_region_size = Vector2i({}, {})",
+        config.tile_size, config.tile_size
+    );
+    let (fx, fy) = config.floor_atlas;
+    let _ = writeln!(out, "{}:{}/0 = 0", fx, fy);
+    for (x, y) in [config.wall_atlas, config.blocked_floor_atlas] {
+        let _ = writeln!(out, "{}:{}/0 = 0", x, y);
+        let _ = writeln!(
+            out,
+            "{}:{}/0/physics_layer_0/polygon_0/points = {}",
+            x, y, polygon
+        );
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, r#"[sub_resource type="TileSet" id="TileSet_terrain"]"#);
+    let _ = writeln!(
+        out,
+        "tile_size = Vector2i({}, {})",
+        config.tile_size, config.tile_size
+    );
+    let _ = writeln!(
+        out,
+        "physics_layer_0/collision_layer = {}",
+        config.collision_layer
+    );
+    let _ = writeln!(
+        out,
+        r#"sources/0 = SubResource("TileSetAtlasSource_terrain")"#
+    );
+    let _ = writeln!(out);
+
+    let _ = writeln!(
+        out,
+        r#"[node name="{}" type="Node2D"]"#,
+        node_name(&config.root_name)
+    );
+    let _ = writeln!(out);
+
+    // TileMap cell triplets: packed coords, source | atlas_x << 16, atlas_y | alternative << 16.
+    let mut tile_data = Vec::with_capacity(w * h * 3);
+    for (x, y, tile) in grid.iter() {
+        let (ax, ay) = if !tile.is_floor() {
+            config.wall_atlas
+        } else if walkable.is_some_and(|m| !m.walkable[y].get(x).copied().unwrap_or(true)) {
+            config.blocked_floor_atlas
+        } else {
+            config.floor_atlas
+        };
+        tile_data.push((((y as u32) << 16) | (x as u32 & 0xFFFF)) as i32);
+        tile_data.push(ax << 16);
+        tile_data.push(ay);
+    }
+    let tile_data: Vec<String> = tile_data.iter().map(|v| v.to_string()).collect();
+
+    let _ = writeln!(out, r#"[node name="TileMap" type="TileMap" parent="."]"#);
+    let _ = writeln!(out, r#"tile_set = SubResource("TileSet_terrain")"#);
+    let _ = writeln!(out, "format = 2");
+    let _ = writeln!(out, r#"layer_0/name = "terrain""#);
+    let _ = writeln!(
+        out,
+        "layer_0/tile_data = PackedInt32Array({})",
+        tile_data.join(", ")
+    );
+
+    if let Some(semantic) = semantic.filter(|_| config.include_markers) {
+        let _ = writeln!(out);
+        let _ = writeln!(out, r#"[node name="Markers" type="Node2D" parent="."]"#);
+        for (i, marker) in semantic.markers.iter().enumerate() {
+            let _ = writeln!(out);
+            let _ = writeln!(
+                out,
+                r#"[node name="{}_{}" type="Marker2D" parent="Markers"]"#,
+                node_name(&marker.tag()),
+                i
+            );
+            let _ = writeln!(
+                out,
+                "position = Vector2({}, {})",
+                marker.x as f64 * config.tile_size as f64 + half,
+                marker.y as f64 * config.tile_size as f64 + half
+            );
+            let _ = writeln!(out, r#"metadata/tag = "{}""#, escape(&marker.tag()));
+            let _ = writeln!(
+                out,
+                r#"metadata/category = "{}""#,
+                marker.marker_type.category()
+            );
+            let _ = writeln!(out, "metadata/weight = {:?}", marker.weight as f64);
+            if let Some(region_id) = marker.region_id {
+                let _ = writeln!(out, "metadata/region_id = {}", region_id);
+            }
+            let mut metadata: Vec<_> = marker.metadata.iter().collect();
+            metadata.sort();
+            for (key, value) in metadata {
+                let _ = writeln!(out, r#"metadata/{} = "{}""#, identifier(key), escape(value));
+            }
+        }
+    }
+
+    out
+}
+
+/// Writes the scene to `path` (conventionally `.tscn`).
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    grid: &Grid<Tile>,
+    semantic: Option<&SemanticLayers>,
+    config: &GodotConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, to_tscn(grid, semantic, config))?;
+    Ok(())
+}
+
+/// Strips characters Godot forbids in node names.
+fn node_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !matches!(c, '.' | ':' | '@' | '/' | '"' | '%'))
+        .collect();
+    if cleaned.is_empty() {
+        "Node".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Maps a metadata key to a valid property identifier.
+fn identifier(key: &str) -> String {
+    let mut id: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) {
+        id.insert(0, '_');
+    }
+    id
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,190 @@
+//! 16-bit heightmap export for terrain tools.
+//!
+//! Value grids are rows of `f64` (`values[y][x]`, as produced by
+//! [`DiamondSquare::heightmap`](crate::algorithms::DiamondSquare::heightmap)).
+//! Values are normalized to the full `u16` range before encoding.
+//!
+//! - 16-bit grayscale PNG (World Machine, Gaea, Unreal landscape import)
+//! - headerless 16-bit RAW (Unity terrain, Unreal `.r16`)
+//! - 32-bit float EXR, unnormalized (feature `exr`)
+//!
+//! Unity expects square RAW heightmaps of size `2^n + 1`; Unreal landscapes
+//! prefer sizes like 505, 1009, or 2017.
+//!
+//! ```rust
+//! use terrain_forge::algorithms::DiamondSquare;
+//! use terrain_forge::export::{heightmap, HeightmapConfig};
+//!
+//! let values = DiamondSquare::default().heightmap(129, 129, 42);
+//! let raw = heightmap::to_raw16(&values, &HeightmapConfig::default());
+//! assert_eq!(raw.len(), 129 * 129 * 2);
+//! let png = heightmap::to_png16(&values, &HeightmapConfig::default()).unwrap();
+//! assert!(png.starts_with(b"\x89PNG"));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Byte order for 16-bit RAW output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteOrder {
+    /// Little-endian (Unity "Windows", Unreal `.r16`).
+    #[default]
+    Little,
+    /// Big-endian (Unity "Mac").
+    Big,
+}
+
+/// Configuration for heightmap export.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeightmapConfig {
+    /// Value range mapped to `0..=65535`. Default: the grid's min/max.
+    pub range: Option<(f64, f64)>,
+    /// Byte order for RAW output. Default: little-endian.
+    pub byte_order: ByteOrder,
+    /// Flip rows so the first row is the southern edge. Default: false.
+    pub flip_y: bool,
+}
+
+impl HeightmapConfig {
+    /// Sets `range`.
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Sets `byte_order`.
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// Sets `flip_y`.
+    pub fn with_flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+}
+
+/// Returns `(width, height)` of a value grid (width of the first row).
+pub fn dimensions(values: &[Vec<f64>]) -> (usize, usize) {
+    (values.first().map_or(0, |r| r.len()), values.len())
+}
+
+/// Normalizes values to row-major `u16` samples.
+///
+/// Non-finite values and short rows map to the bottom of the range.
+pub fn to_u16(values: &[Vec<f64>], config: &HeightmapConfig) -> Vec<u16> {
+    let (w, h) = dimensions(values);
+    let (lo, hi) = config.range.unwrap_or_else(|| value_range(values));
+    let span = hi - lo;
+    let mut out = Vec::with_capacity(w * h);
+    for y in 0..h {
+        let row = &values[if config.flip_y { h - 1 - y } else { y }];
+        for x in 0..w {
+            let v = row.get(x).copied().filter(|v| v.is_finite()).unwrap_or(lo);
+            let t = if span > 0.0 { (v - lo) / span } else { 0.0 };
+            out.push((t.clamp(0.0, 1.0) * u16::MAX as f64).round() as u16);
+        }
+    }
+    out
+}
+
+/// Encodes a headerless 16-bit RAW heightmap.
+pub fn to_raw16(values: &[Vec<f64>], config: &HeightmapConfig) -> Vec<u8> {
+    to_u16(values, config)
+        .into_iter()
+        .flat_map(|v| match config.byte_order {
+            ByteOrder::Little => v.to_le_bytes(),
+            ByteOrder::Big => v.to_be_bytes(),
+        })
+        .collect()
+}
+
+/// Encodes a 16-bit grayscale PNG.
+pub fn to_png16(
+    values: &[Vec<f64>],
+    config: &HeightmapConfig,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (w, h) = dimensions(values);
+    // PNG stores 16-bit samples big-endian regardless of `byte_order`.
+    let data: Vec<u8> = to_u16(values, config)
+        .into_iter()
+        .flat_map(u16::to_be_bytes)
+        .collect();
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, w as u32, h as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(out)
+}
+
+/// Writes a 16-bit grayscale PNG to `path`.
+pub fn save_png16<P: AsRef<Path>>(
+    path: P,
+    values: &[Vec<f64>],
+    config: &HeightmapConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, to_png16(values, config)?)?;
+    Ok(())
+}
+
+/// Writes a headerless 16-bit RAW heightmap to `path`.
+pub fn save_raw16<P: AsRef<Path>>(
+    path: P,
+    values: &[Vec<f64>],
+    config: &HeightmapConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, to_raw16(values, config))?;
+    Ok(())
+}
+
+/// Encodes a single-channel (`Y`) 32-bit float EXR with the raw values.
+///
+/// Only `flip_y` applies; values are not normalized.
+#[cfg(feature = "exr")]
+pub fn to_exr(
+    values: &[Vec<f64>],
+    config: &HeightmapConfig,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    use exr::prelude::*;
+
+    let (w, h) = dimensions(values);
+    let samples: Vec<f32> = (0..h)
+        .flat_map(|y| {
+            let row = &values[if config.flip_y { h - 1 - y } else { y }];
+            (0..w).map(move |x| row.get(x).copied().unwrap_or(0.0) as f32)
+        })
+        .collect();
+    let channels = AnyChannels::sort(vec![AnyChannel::new("Y", FlatSamples::F32(samples))].into());
+    let image = Image::from_channels((w, h), channels);
+    let mut out = std::io::Cursor::new(Vec::new());
+    image.write().to_buffered(&mut out)?;
+    Ok(out.into_inner())
+}
+
+/// Writes a 32-bit float EXR to `path`.
+#[cfg(feature = "exr")]
+pub fn save_exr<P: AsRef<Path>>(
+    path: P,
+    values: &[Vec<f64>],
+    config: &HeightmapConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, to_exr(values, config)?)?;
+    Ok(())
+}
+
+fn value_range(values: &[Vec<f64>]) -> (f64, f64) {
+    values
+        .iter()
+        .flatten()
+        .filter(|v| v.is_finite())
+        .fold(None, |acc: Option<(f64, f64)>, &v| match acc {
+            Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+            None => Some((v, v)),
+        })
+        .unwrap_or((0.0, 1.0))
+}
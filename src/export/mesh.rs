@@ -0,0 +1,386 @@
+//! Triangle mesh export (OBJ, glTF 2.0).
+//!
+//! Two builders produce a [`Mesh`] in Y-up world space, with grid `x` along
+//! +X and grid `y` along +Z:
+//!
+//! - [`Mesh::from_heightmap`] triangulates a value grid, optionally decimated
+//!   by sampling every `step`-th row/column.
+//! - [`Mesh::from_tiles`] extrudes a tile dungeon: floor quads at height 0,
+//!   walls facing into the floor, and caps on walls bordering floors.
+//!
+//! ```rust
+//! use terrain_forge::algorithms::DiamondSquare;
+//! use terrain_forge::export::{mesh, Mesh, MeshConfig};
+//!
+//! let values = DiamondSquare::default().heightmap(65, 65, 42);
+//! let terrain = Mesh::from_heightmap(&values, &MeshConfig::default().with_step(4));
+//! assert_eq!(terrain.vertex_count(), 17 * 17);
+//! let obj = mesh::to_obj(&terrain);
+//! assert!(obj.contains("\nf "));
+//! ```
+
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Configuration for mesh building.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshConfig {
+    /// World size of one grid cell. Default: 1.0.
+    pub cell_size: f32,
+    /// Multiplier for heightmap values. Default: 10.0.
+    pub height_scale: f32,
+    /// Heightmap sampling stride (1 = full resolution). Default: 1.
+    pub step: usize,
+    /// Wall height for tile extrusion. Default: 2.0.
+    pub wall_height: f32,
+    /// Cap wall tops that border floors. Default: true.
+    pub wall_caps: bool,
+}
+
+impl Default for MeshConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 1.0,
+            height_scale: 10.0,
+            step: 1,
+            wall_height: 2.0,
+            wall_caps: true,
+        }
+    }
+}
+
+impl MeshConfig {
+    /// Sets `cell_size`.
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Sets `height_scale`.
+    pub fn with_height_scale(mut self, height_scale: f32) -> Self {
+        self.height_scale = height_scale;
+        self
+    }
+
+    /// Sets `step`.
+    pub fn with_step(mut self, step: usize) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Sets `wall_height`.
+    pub fn with_wall_height(mut self, wall_height: f32) -> Self {
+        self.wall_height = wall_height;
+        self
+    }
+
+    /// Sets `wall_caps`.
+    pub fn with_wall_caps(mut self, wall_caps: bool) -> Self {
+        self.wall_caps = wall_caps;
+        self
+    }
+}
+
+/// An indexed triangle mesh with per-vertex normals.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// Counter-clockwise triangles (front faces).
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Triangulates a heightmap (`values[y][x]`).
+    ///
+    /// With `step > 1` only every `step`-th sample is used; the last row and
+    /// column are always kept so the mesh covers the full extent.
+    pub fn from_heightmap(values: &[Vec<f64>], config: &MeshConfig) -> Self {
+        let h = values.len();
+        let w = values.first().map_or(0, |r| r.len());
+        let mut mesh = Mesh::default();
+        if w < 2 || h < 2 {
+            return mesh;
+        }
+
+        let xs = samples(w, config.step);
+        let zs = samples(h, config.step);
+        let height = |x: usize, y: usize| {
+            values[y].get(x).copied().unwrap_or(0.0) as f32 * config.height_scale
+        };
+
+        for &y in &zs {
+            for &x in &xs {
+                // Central differences in world units for the normal.
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(w - 1));
+                let (y0, y1) = (y.saturating_sub(1), (y + 1).min(h - 1));
+                let dx = (height(x1, y) - height(x0, y)) / ((x1 - x0) as f32 * config.cell_size);
+                let dz = (height(x, y1) - height(x, y0)) / ((y1 - y0) as f32 * config.cell_size);
+                mesh.positions.push([
+                    x as f32 * config.cell_size,
+                    height(x, y),
+                    y as f32 * config.cell_size,
+                ]);
+                mesh.normals.push(normalize([-dx, 1.0, -dz]));
+            }
+        }
+
+        let cols = xs.len() as u32;
+        for row in 0..zs.len() as u32 - 1 {
+            for col in 0..cols - 1 {
+                let a = row * cols + col;
+                let (b, c, d) = (a + 1, a + cols, a + cols + 1);
+                mesh.indices.extend_from_slice(&[a, c, b, b, c, d]);
+            }
+        }
+        mesh
+    }
+
+    /// Extrudes a tile grid into floor, wall, and wall-cap geometry.
+    pub fn from_tiles(grid: &Grid<Tile>, config: &MeshConfig) -> Self {
+        let s = config.cell_size;
+        let top = config.wall_height;
+        let mut mesh = Mesh::default();
+        let is_floor = |x: i32, y: i32| grid.get(x, y).is_some_and(|t| t.is_floor());
+
+        for (x, y, tile) in grid.iter() {
+            let (xi, yi) = (x as i32, y as i32);
+            let (x0, x1) = (x as f32 * s, (x + 1) as f32 * s);
+            let (z0, z1) = (y as f32 * s, (y + 1) as f32 * s);
+
+            if tile.is_floor() {
+                mesh.push_quad(
+                    [[x0, 0.0, z0], [x1, 0.0, z0], [x1, 0.0, z1], [x0, 0.0, z1]],
+                    [0.0, 1.0, 0.0],
+                );
+                // Walls on each side that borders a wall (or the map edge).
+                let sides = [
+                    (0, -1, [[x0, 0.0, z0], [x1, 0.0, z0]], [0.0, 0.0, 1.0]),
+                    (0, 1, [[x0, 0.0, z1], [x1, 0.0, z1]], [0.0, 0.0, -1.0]),
+                    (-1, 0, [[x0, 0.0, z0], [x0, 0.0, z1]], [1.0, 0.0, 0.0]),
+                    (1, 0, [[x1, 0.0, z0], [x1, 0.0, z1]], [-1.0, 0.0, 0.0]),
+                ];
+                for (dx, dy, [p, q], normal) in sides {
+                    if !is_floor(xi + dx, yi + dy) {
+                        mesh.push_quad([p, q, [q[0], top, q[2]], [p[0], top, p[2]]], normal);
+                    }
+                }
+            } else if config.wall_caps
+                && (-1..=1).any(|dy| (-1..=1).any(|dx| is_floor(xi + dx, yi + dy)))
+            {
+                mesh.push_quad(
+                    [[x0, top, z0], [x1, top, z0], [x1, top, z1], [x0, top, z1]],
+                    [0.0, 1.0, 0.0],
+                );
+            }
+        }
+        mesh
+    }
+
+    /// Appends a flat quad, winding it counter-clockwise around `normal`.
+    fn push_quad(&mut self, corners: [[f32; 3]; 4], normal: [f32; 3]) {
+        let base = self.positions.len() as u32;
+        self.positions.extend_from_slice(&corners);
+        self.normals.extend_from_slice(&[normal; 4]);
+        let face = cross(sub(corners[1], corners[0]), sub(corners[2], corners[0]));
+        if dot(face, normal) >= 0.0 {
+            self.indices
+                .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        } else {
+            self.indices
+                .extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+        }
+    }
+
+    fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for p in &self.positions {
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+        (min, max)
+    }
+}
+
+/// Renders the mesh as Wavefront OBJ text.
+pub fn to_obj(mesh: &Mesh) -> String {
+    let mut out = String::from("# terrain-forge mesh\n");
+    for [x, y, z] in &mesh.positions {
+        let _ = writeln!(out, "v {} {} {}", x, y, z);
+    }
+    for [x, y, z] in &mesh.normals {
+        let _ = writeln!(out, "vn {} {} {}", x, y, z);
+    }
+    for tri in mesh.indices.chunks_exact(3) {
+        let (a, b, c) = (tri[0] + 1, tri[1] + 1, tri[2] + 1);
+        let _ = writeln!(out, "f {a}//{a} {b}//{b} {c}//{c}");
+    }
+    out
+}
+
+/// Renders the mesh as a self-contained glTF 2.0 JSON document (`.gltf`)
+/// with the buffer embedded as a base64 data URI.
+pub fn to_gltf(mesh: &Mesh) -> String {
+    let buffer = buffer_bytes(mesh);
+    let uri = format!("data:application/octet-stream;base64,{}", base64(&buffer));
+    gltf_json(mesh, buffer.len(), Some(&uri)).to_string()
+}
+
+/// Encodes the mesh as binary glTF 2.0 (`.glb`).
+pub fn to_glb(mesh: &Mesh) -> Vec<u8> {
+    let mut buffer = buffer_bytes(mesh);
+    let mut json = gltf_json(mesh, buffer.len(), None).to_string().into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+    let total = 12 + 8 + json.len() + 8 + buffer.len();
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(b"glTF");
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(total as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(&json);
+    out.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"BIN\0");
+    out.extend_from_slice(&buffer);
+    out
+}
+
+/// Writes the mesh to `path`, choosing the format from the extension
+/// (`.obj`, `.gltf`, or `.glb`).
+pub fn save<P: AsRef<Path>>(path: P, mesh: &Mesh) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("obj") => std::fs::write(path, to_obj(mesh))?,
+        Some("gltf") => std::fs::write(path, to_gltf(mesh))?,
+        Some("glb") => std::fs::write(path, to_glb(mesh))?,
+        _ => {
+            return Err(format!(
+                "Unsupported mesh extension for '{}' (use .obj, .gltf, or .glb)",
+                path.display()
+            )
+            .into())
+        }
+    }
+    Ok(())
+}
+
+/// Positions, then normals, then indices, all little-endian.
+fn buffer_bytes(mesh: &Mesh) -> Vec<u8> {
+    let mut out = Vec::with_capacity(mesh.positions.len() * 24 + mesh.indices.len() * 4);
+    for v in mesh.positions.iter().chain(&mesh.normals).flatten() {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    for i in &mesh.indices {
+        out.extend_from_slice(&i.to_le_bytes());
+    }
+    out
+}
+
+fn gltf_json(mesh: &Mesh, buffer_len: usize, uri: Option<&str>) -> serde_json::Value {
+    let vertex_bytes = mesh.positions.len() * 12;
+    let index_bytes = mesh.indices.len() * 4;
+    let (min, max) = mesh.bounds();
+    let mut buffer = serde_json::json!({ "byteLength": buffer_len });
+    if let Some(uri) = uri {
+        buffer["uri"] = uri.into();
+    }
+    serde_json::json!({
+        "asset": { "version": "2.0", "generator": "terrain-forge" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0, "name": "terrain" }],
+        "meshes": [{
+            "primitives": [{
+                "attributes": { "POSITION": 0, "NORMAL": 1 },
+                "indices": 2,
+                "mode": 4
+            }]
+        }],
+        "buffers": [buffer],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": vertex_bytes, "target": 34962 },
+            { "buffer": 0, "byteOffset": vertex_bytes, "byteLength": vertex_bytes, "target": 34962 },
+            { "buffer": 0, "byteOffset": vertex_bytes * 2, "byteLength": index_bytes, "target": 34963 }
+        ],
+        "accessors": [
+            {
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": mesh.positions.len(),
+                "type": "VEC3",
+                "min": min,
+                "max": max
+            },
+            { "bufferView": 1, "componentType": 5126, "count": mesh.normals.len(), "type": "VEC3" },
+            { "bufferView": 2, "componentType": 5125, "count": mesh.indices.len(), "type": "SCALAR" }
+        ]
+    })
+}
+
+/// Sample positions `0, step, 2*step, ..., len - 1`.
+fn samples(len: usize, step: usize) -> Vec<usize> {
+    let mut out: Vec<usize> = (0..len).step_by(step.max(1)).collect();
+    if out.last() != Some(&(len - 1)) {
+        out.push(len - 1);
+    }
+    out
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = dot(v, v).sqrt();
+    if len > 0.0 {
+        [v[0] / len, v[1] / len, v[2] / len]
+    } else {
+        [0.0, 1.0, 0.0]
+    }
+}
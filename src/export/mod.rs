@@ -0,0 +1,33 @@
+//! Exporters for external map editors and engines.
+//!
+//! - [`tiled`] - Tiled JSON (`.tmj`) and XML (`.tmx`) maps
+//! - [`collision`] - packed walkability bitmaps, optionally RLE-compressed
+//! - `godot` - Godot 4 `.tscn` scenes (feature `godot`)
+//! - [`heightmap`] - 16-bit PNG/RAW and float EXR (feature `exr`) heightmaps
+//! - [`mesh`] - OBJ and glTF meshes from heightmaps or extruded tile grids
+//! - [`svg`] - vector maps with wall outlines, regions, connectivity, and markers
+
+pub mod collision;
+#[cfg(feature = "godot")]
+pub mod godot;
+pub mod heightmap;
+pub mod mesh;
+pub mod svg;
+pub mod tiled;
+
+pub use collision::{BitOrder, CollisionConfig, CollisionMask};
+#[cfg(feature = "godot")]
+pub use godot::GodotConfig;
+pub use heightmap::{ByteOrder, HeightmapConfig};
+pub use mesh::{Mesh, MeshConfig};
+pub use svg::SvgConfig;
+pub use tiled::{TiledConfig, TiledTileset};
+
+/// Escapes text for XML attribute values and character data.
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,387 @@
+//! SVG vector rendering.
+//!
+//! Floors are filled from wall outlines traced with marching squares, so
+//! the output scales cleanly for print. Optional layers from
+//! [`SemanticLayers`] add region fills, connectivity edges between region
+//! centroids, and labeled markers, each in its own `<g>` group for easy
+//! editing.
+//!
+//! ```rust
+//! use terrain_forge::{algorithms, Grid, SemanticExtractor, Rng};
+//! use terrain_forge::export::{svg, SvgConfig};
+//!
+//! let mut grid = Grid::new(40, 30);
+//! algorithms::get("cellular").unwrap().generate(&mut grid, 7);
+//! let semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(7));
+//!
+//! let doc = svg::to_svg(&grid, Some(&semantic), &SvgConfig::default());
+//! assert!(doc.starts_with("<svg"));
+//! assert!(doc.contains("id=\"outlines\""));
+//! ```
+
+use super::xml_escape;
+use crate::semantic::SemanticLayers;
+use crate::tileset::{TileId, TileSet};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Fill colors cycled through by region kind and marker tag.
+const PALETTE: [&str; 8] = [
+    "#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#edc948", "#b07aa1", "#ff9da7",
+];
+
+/// Configuration for SVG rendering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SvgConfig {
+    /// Size of one cell in SVG units. Default: 8.
+    pub cell_size: f32,
+    /// Background (wall) fill. Default: "#303030".
+    pub wall_color: String,
+    /// Floor fill. Default: "#e6e6e6".
+    pub floor_color: String,
+    /// Wall outline stroke. Default: "#111111".
+    pub outline_color: String,
+    /// Wall outline stroke width in SVG units. Default: 1.5.
+    pub outline_width: f32,
+    /// Draw translucent region fills. Default: true.
+    pub include_regions: bool,
+    /// Region fill opacity. Default: 0.35.
+    pub region_opacity: f32,
+    /// Draw connectivity edges between region centroids. Default: true.
+    pub include_connectivity: bool,
+    /// Draw markers. Default: true.
+    pub include_markers: bool,
+    /// Label markers with their tag. Default: true.
+    pub label_markers: bool,
+}
+
+impl Default for SvgConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 8.0,
+            wall_color: "#303030".to_string(),
+            floor_color: "#e6e6e6".to_string(),
+            outline_color: "#111111".to_string(),
+            outline_width: 1.5,
+            include_regions: true,
+            region_opacity: 0.35,
+            include_connectivity: true,
+            include_markers: true,
+            label_markers: true,
+        }
+    }
+}
+
+impl SvgConfig {
+    /// Sets `cell_size`.
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Sets the wall and floor fills.
+    pub fn with_colors(mut self, wall: impl Into<String>, floor: impl Into<String>) -> Self {
+        self.wall_color = wall.into();
+        self.floor_color = floor.into();
+        self
+    }
+
+    /// Takes the wall and floor fills from a tile set.
+    pub fn with_tileset(self, tileset: &TileSet) -> Self {
+        let hex = |id| {
+            let [r, g, b] = tileset.get(id).map_or([0, 0, 0], |t| t.color);
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        };
+        let (wall, floor) = (hex(TileId::WALL), hex(TileId::FLOOR));
+        self.with_colors(wall, floor)
+    }
+
+    /// Sets the outline stroke color and width.
+    pub fn with_outline(mut self, color: impl Into<String>, width: f32) -> Self {
+        self.outline_color = color.into();
+        self.outline_width = width;
+        self
+    }
+
+    /// Enables or disables the semantic layers.
+    pub fn with_layers(mut self, regions: bool, connectivity: bool, markers: bool) -> Self {
+        self.include_regions = regions;
+        self.include_connectivity = connectivity;
+        self.include_markers = markers;
+        self
+    }
+}
+
+/// Closed floor/wall boundaries traced with marching squares over cell
+/// centers. Points are in cell units; each contour's last point connects
+/// back to its first.
+pub fn outlines(grid: &Grid<Tile>) -> Vec<Vec<(f32, f32)>> {
+    // Work in half-cell units so every edge midpoint is an integer point.
+    let floor = |x: i32, y: i32| grid.get(x, y).is_some_and(|t| t.is_floor());
+    let mut adjacency: HashMap<(i32, i32), Vec<(i32, i32)>> = HashMap::new();
+    let mut link = |a: (i32, i32), b: (i32, i32)| {
+        adjacency.entry(a).or_default().push(b);
+        adjacency.entry(b).or_default().push(a);
+    };
+
+    for y in -1..grid.height() as i32 {
+        for x in -1..grid.width() as i32 {
+            let case = (floor(x, y) as u8) << 3
+                | (floor(x + 1, y) as u8) << 2
+                | (floor(x + 1, y + 1) as u8) << 1
+                | floor(x, y + 1) as u8;
+            let top = (2 * x + 2, 2 * y + 1);
+            let right = (2 * x + 3, 2 * y + 2);
+            let bottom = (2 * x + 2, 2 * y + 3);
+            let left = (2 * x + 1, 2 * y + 2);
+            match case {
+                1 | 14 => link(left, bottom),
+                2 | 13 => link(bottom, right),
+                3 | 12 => link(left, right),
+                4 | 11 => link(top, right),
+                6 | 9 => link(top, bottom),
+                7 | 8 => link(left, top),
+                // Saddles: keep diagonal floors apart.
+                5 => {
+                    link(top, right);
+                    link(left, bottom);
+                }
+                10 => {
+                    link(left, top);
+                    link(bottom, right);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut starts: Vec<(i32, i32)> = adjacency.keys().copied().collect();
+    starts.sort_unstable();
+    let mut visited: HashSet<(i32, i32)> = HashSet::new();
+    let mut contours = Vec::new();
+    for start in starts {
+        if !visited.insert(start) {
+            continue;
+        }
+        let mut contour = vec![start];
+        let mut current = start;
+        while let Some(&next) = adjacency[&current].iter().find(|p| !visited.contains(*p)) {
+            visited.insert(next);
+            contour.push(next);
+            current = next;
+        }
+        // Drop points on straight runs.
+        let n = contour.len();
+        let corners = (0..n).filter(|&i| {
+            let (a, b, c) = (contour[(i + n - 1) % n], contour[i], contour[(i + 1) % n]);
+            (b.0 - a.0) * (c.1 - b.1) != (b.1 - a.1) * (c.0 - b.0)
+        });
+        contours.push(
+            corners
+                .map(|i| (contour[i].0 as f32 / 2.0, contour[i].1 as f32 / 2.0))
+                .collect(),
+        );
+    }
+    contours
+}
+
+/// Renders the grid (and optional semantic layers) as an SVG document.
+pub fn to_svg(grid: &Grid<Tile>, semantic: Option<&SemanticLayers>, config: &SvgConfig) -> String {
+    let s = config.cell_size;
+    let (w, h) = (grid.width() as f32 * s, grid.height() as f32 * s);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+    );
+    let _ = writeln!(
+        out,
+        r#" <rect id="walls" width="{w}" height="{h}" fill="{}"/>"#,
+        xml_escape(&config.wall_color)
+    );
+
+    let mut path = String::new();
+    for contour in outlines(grid) {
+        for (i, (x, y)) in contour.iter().enumerate() {
+            let _ = write!(
+                path,
+                "{}{} {} ",
+                if i == 0 { 'M' } else { 'L' },
+                x * s,
+                y * s
+            );
+        }
+        path.push_str("Z ");
+    }
+    let path = path.trim_end();
+    let _ = writeln!(
+        out,
+        r#" <path id="floors" d="{path}" fill="{}" fill-rule="evenodd"/>"#,
+        xml_escape(&config.floor_color)
+    );
+
+    if let Some(semantic) = semantic {
+        if config.include_regions {
+            write_regions(&mut out, semantic, config);
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        r#" <path id="outlines" d="{path}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round"/>"#,
+        xml_escape(&config.outline_color),
+        config.outline_width
+    );
+
+    if let Some(semantic) = semantic {
+        if config.include_connectivity {
+            write_connectivity(&mut out, semantic, s);
+        }
+        if config.include_markers {
+            write_markers(&mut out, semantic, config);
+        }
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Writes the SVG to `path`.
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    grid: &Grid<Tile>,
+    semantic: Option<&SemanticLayers>,
+    config: &SvgConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, to_svg(grid, semantic, config))?;
+    Ok(())
+}
+
+fn write_regions(out: &mut String, semantic: &SemanticLayers, config: &SvgConfig) {
+    let s = config.cell_size;
+    let mut kinds: Vec<&str> = Vec::new();
+    let _ = writeln!(
+        out,
+        r#" <g id="regions" opacity="{}">"#,
+        config.region_opacity
+    );
+    for region in &semantic.regions {
+        let color = palette_for(&mut kinds, &region.kind);
+        // One rectangle per horizontal run of cells.
+        let mut cells = region.cells.clone();
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        let mut d = String::new();
+        let mut i = 0;
+        while i < cells.len() {
+            let (x0, y) = cells[i];
+            let mut run = 1;
+            while i + run < cells.len() && cells[i + run] == (x0 + run as u32, y) {
+                run += 1;
+            }
+            let _ = write!(
+                d,
+                "M{} {}h{}v{}h-{}Z",
+                x0 as f32 * s,
+                y as f32 * s,
+                run as f32 * s,
+                s,
+                run as f32 * s
+            );
+            i += run;
+        }
+        let _ = writeln!(
+            out,
+            r#"  <path d="{d}" fill="{color}" data-region="{}" data-kind="{}"><title>{} #{}</title></path>"#,
+            region.id,
+            xml_escape(&region.kind),
+            xml_escape(&region.kind),
+            region.id
+        );
+    }
+    let _ = writeln!(out, " </g>");
+}
+
+fn write_connectivity(out: &mut String, semantic: &SemanticLayers, s: f32) {
+    let centroids: HashMap<u32, (f32, f32)> = semantic
+        .regions
+        .iter()
+        .filter(|r| !r.cells.is_empty())
+        .map(|r| {
+            let n = r.cells.len() as f32;
+            let (sx, sy) = r.cells.iter().fold((0.0, 0.0), |(sx, sy), &(x, y)| {
+                (sx + x as f32, sy + y as f32)
+            });
+            (r.id, ((sx / n + 0.5) * s, (sy / n + 0.5) * s))
+        })
+        .collect();
+
+    let _ = writeln!(
+        out,
+        r##" <g id="connectivity" stroke="#2b6cb0" stroke-width="{}" fill="#2b6cb0">"##,
+        s * 0.25
+    );
+    for &(a, b) in &semantic.connectivity.edges {
+        if let (Some(&(x1, y1)), Some(&(x2, y2))) = (centroids.get(&a), centroids.get(&b)) {
+            let _ = writeln!(
+                out,
+                r#"  <line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" data-from="{a}" data-to="{b}"/>"#
+            );
+        }
+    }
+    let mut ids: Vec<_> = centroids.iter().collect();
+    ids.sort_unstable_by_key(|(id, _)| **id);
+    for (id, (x, y)) in ids {
+        let _ = writeln!(
+            out,
+            r#"  <circle cx="{x}" cy="{y}" r="{}" stroke="none" data-region="{id}"/>"#,
+            s * 0.5
+        );
+    }
+    let _ = writeln!(out, " </g>");
+}
+
+fn write_markers(out: &mut String, semantic: &SemanticLayers, config: &SvgConfig) {
+    let s = config.cell_size;
+    let mut tags: Vec<&str> = Vec::new();
+    let tag_names: Vec<String> = semantic.markers.iter().map(|m| m.tag()).collect();
+    let _ = writeln!(
+        out,
+        r#" <g id="markers" font-family="sans-serif" font-size="{}">"#,
+        s * 1.2
+    );
+    for (marker, tag) in semantic.markers.iter().zip(&tag_names) {
+        let color = palette_for(&mut tags, tag);
+        let (cx, cy) = ((marker.x as f32 + 0.5) * s, (marker.y as f32 + 0.5) * s);
+        let _ = writeln!(
+            out,
+            r##"  <circle cx="{cx}" cy="{cy}" r="{}" fill="{color}" stroke="#000" stroke-width="{}"><title>{}</title></circle>"##,
+            s * 0.45,
+            s * 0.1,
+            xml_escape(tag)
+        );
+        if config.label_markers {
+            let _ = writeln!(
+                out,
+                r##"  <text x="{}" y="{}" fill="#000" stroke="#fff" stroke-width="{}" paint-order="stroke">{}</text>"##,
+                cx + s * 0.7,
+                cy - s * 0.3,
+                s * 0.25,
+                xml_escape(tag)
+            );
+        }
+    }
+    let _ = writeln!(out, " </g>");
+}
+
+/// Stable palette color for `key`, by first appearance.
+fn palette_for<'a>(seen: &mut Vec<&'a str>, key: &'a str) -> &'static str {
+    let index = seen.iter().position(|k| *k == key).unwrap_or_else(|| {
+        seen.push(key);
+        seen.len() - 1
+    });
+    PALETTE[index % PALETTE.len()]
+}
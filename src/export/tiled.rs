@@ -0,0 +1,537 @@
+//! Tiled map export.
+//!
+//! Writes a grid as a Tiled tile layer, with optional `markers` and `regions`
+//! object groups built from [`SemanticLayers`].
+//!
+//! ```rust
+//! use terrain_forge::{algorithms, Grid, SemanticExtractor, Rng};
+//! use terrain_forge::export::{tiled, TiledConfig};
+//!
+//! let mut grid = Grid::new(40, 30);
+//! algorithms::get("bsp").unwrap().generate(&mut grid, 7);
+//! let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(7));
+//!
+//! let tmj = tiled::to_tmj(&grid, Some(&semantic), &TiledConfig::default());
+//! assert!(tmj.contains("\"tilelayer\""));
+//! ```
+
+use super::xml_escape;
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Tileset referenced by the exported map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TiledTileset {
+    /// GID of the first tile in the set. Default: 1.
+    pub first_gid: u32,
+    /// Tileset name. Default: "terrain".
+    pub name: String,
+    /// External tileset file (`.tsx`/`.tsj`). When set, the image fields are ignored.
+    pub source: Option<String>,
+    /// Tileset image path. Default: "terrain.png".
+    pub image: String,
+    /// Image width in pixels. Default: 32.
+    pub image_width: u32,
+    /// Image height in pixels. Default: 16.
+    pub image_height: u32,
+    /// Tiles per row. Default: 2.
+    pub columns: u32,
+    /// Total tiles in the set. Default: 2.
+    pub tile_count: u32,
+}
+
+impl Default for TiledTileset {
+    fn default() -> Self {
+        Self {
+            first_gid: 1,
+            name: "terrain".to_string(),
+            source: None,
+            image: "terrain.png".to_string(),
+            image_width: 32,
+            image_height: 16,
+            columns: 2,
+            tile_count: 2,
+        }
+    }
+}
+
+/// Configuration for Tiled export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TiledConfig {
+    /// Tile width in pixels. Default: 16.
+    pub tile_width: u32,
+    /// Tile height in pixels. Default: 16.
+    pub tile_height: u32,
+    /// Tileset the GIDs refer to.
+    pub tileset: TiledTileset,
+    /// GID written for floor tiles. Default: 1.
+    pub floor_gid: u32,
+    /// GID written for wall tiles (0 leaves the cell empty). Default: 2.
+    pub wall_gid: u32,
+    /// Floor GID overrides by region kind (e.g. "Corridor" -> 3).
+    pub region_floor_gids: HashMap<String, u32>,
+    /// Emit a `markers` object group. Default: true.
+    pub include_markers: bool,
+    /// Emit a `regions` object group. Default: true.
+    pub include_regions: bool,
+}
+
+impl Default for TiledConfig {
+    fn default() -> Self {
+        Self {
+            tile_width: 16,
+            tile_height: 16,
+            tileset: TiledTileset::default(),
+            floor_gid: 1,
+            wall_gid: 2,
+            region_floor_gids: HashMap::new(),
+            include_markers: true,
+            include_regions: true,
+        }
+    }
+}
+
+impl TiledConfig {
+    /// Sets `tile_width` and `tile_height`.
+    pub fn with_tile_size(mut self, width: u32, height: u32) -> Self {
+        self.tile_width = width;
+        self.tile_height = height;
+        self
+    }
+
+    /// Sets `tileset`.
+    pub fn with_tileset(mut self, tileset: TiledTileset) -> Self {
+        self.tileset = tileset;
+        self
+    }
+
+    /// Sets the floor and wall GIDs.
+    pub fn with_gids(mut self, floor_gid: u32, wall_gid: u32) -> Self {
+        self.floor_gid = floor_gid;
+        self.wall_gid = wall_gid;
+        self
+    }
+
+    /// Adds a floor GID override for a region kind.
+    pub fn with_region_floor_gid(mut self, kind: impl Into<String>, gid: u32) -> Self {
+        self.region_floor_gids.insert(kind.into(), gid);
+        self
+    }
+}
+
+/// A custom property on a Tiled object.
+enum Property {
+    String(String),
+    Int(i64),
+    Float(f64),
+}
+
+impl Property {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Property::String(_) => "string",
+            Property::Int(_) => "int",
+            Property::Float(_) => "float",
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Property::String(s) => json!(s),
+            Property::Int(i) => json!(i),
+            Property::Float(f) => json!(f),
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        match self {
+            Property::String(s) => xml_escape(s),
+            Property::Int(i) => i.to_string(),
+            Property::Float(f) => f.to_string(),
+        }
+    }
+}
+
+struct Object {
+    id: u32,
+    name: String,
+    class: String,
+    x: f64,
+    y: f64,
+    /// `None` for point objects.
+    size: Option<(f64, f64)>,
+    properties: Vec<(String, Property)>,
+}
+
+struct ObjectGroup {
+    name: &'static str,
+    objects: Vec<Object>,
+}
+
+/// Format-independent map contents.
+struct TiledMap {
+    data: Vec<u32>,
+    groups: Vec<ObjectGroup>,
+    next_object_id: u32,
+}
+
+impl TiledMap {
+    fn build(grid: &Grid<Tile>, semantic: Option<&SemanticLayers>, config: &TiledConfig) -> Self {
+        let (w, h) = (grid.width(), grid.height());
+        let mut data: Vec<u32> = grid
+            .iter()
+            .map(|(_, _, t)| {
+                if t.is_floor() {
+                    config.floor_gid
+                } else {
+                    config.wall_gid
+                }
+            })
+            .collect();
+
+        let mut groups = Vec::new();
+        let mut next_id = 1;
+        let (tw, th) = (config.tile_width as f64, config.tile_height as f64);
+
+        if let Some(semantic) = semantic {
+            for region in &semantic.regions {
+                if let Some(&gid) = config.region_floor_gids.get(&region.kind) {
+                    for &(x, y) in &region.cells {
+                        let (x, y) = (x as usize, y as usize);
+                        if x < w && y < h && grid[(x, y)].is_floor() {
+                            data[y * w + x] = gid;
+                        }
+                    }
+                }
+            }
+
+            if config.include_regions {
+                let mut objects = Vec::new();
+                for region in &semantic.regions {
+                    let Some((min_x, min_y, max_x, max_y)) = bounds(&region.cells) else {
+                        continue;
+                    };
+                    let mut properties = vec![
+                        ("region_id".to_string(), Property::Int(region.id as i64)),
+                        (
+                            "cells".to_string(),
+                            Property::Int(region.cells.len() as i64),
+                        ),
+                    ];
+                    if !region.tags.is_empty() {
+                        properties
+                            .push(("tags".to_string(), Property::String(region.tags.join(","))));
+                    }
+                    objects.push(Object {
+                        id: next_id,
+                        name: region.kind.clone(),
+                        class: "region".to_string(),
+                        x: min_x as f64 * tw,
+                        y: min_y as f64 * th,
+                        size: Some((
+                            (max_x - min_x + 1) as f64 * tw,
+                            (max_y - min_y + 1) as f64 * th,
+                        )),
+                        properties,
+                    });
+                    next_id += 1;
+                }
+                groups.push(ObjectGroup {
+                    name: "regions",
+                    objects,
+                });
+            }
+
+            if config.include_markers {
+                let mut objects = Vec::new();
+                for marker in &semantic.markers {
+                    let mut properties = vec![
+                        (
+                            "category".to_string(),
+                            Property::String(marker.marker_type.category().to_string()),
+                        ),
+                        ("weight".to_string(), Property::Float(marker.weight as f64)),
+                    ];
+                    if let Some(region_id) = marker.region_id {
+                        properties.push(("region_id".to_string(), Property::Int(region_id as i64)));
+                    }
+                    let mut metadata: Vec<_> = marker.metadata.iter().collect();
+                    metadata.sort();
+                    for (key, value) in metadata {
+                        properties.push((key.clone(), Property::String(value.clone())));
+                    }
+                    objects.push(Object {
+                        id: next_id,
+                        name: marker.tag(),
+                        class: "marker".to_string(),
+                        x: (marker.x as f64 + 0.5) * tw,
+                        y: (marker.y as f64 + 0.5) * th,
+                        size: None,
+                        properties,
+                    });
+                    next_id += 1;
+                }
+                groups.push(ObjectGroup {
+                    name: "markers",
+                    objects,
+                });
+            }
+        }
+
+        Self {
+            data,
+            groups,
+            next_object_id: next_id,
+        }
+    }
+}
+
+/// Renders the map as Tiled JSON (`.tmj`).
+pub fn to_tmj(
+    grid: &Grid<Tile>,
+    semantic: Option<&SemanticLayers>,
+    config: &TiledConfig,
+) -> String {
+    let map = TiledMap::build(grid, semantic, config);
+    let (w, h) = (grid.width(), grid.height());
+
+    let mut layers = vec![json!({
+        "id": 1,
+        "name": "terrain",
+        "type": "tilelayer",
+        "x": 0,
+        "y": 0,
+        "width": w,
+        "height": h,
+        "opacity": 1,
+        "visible": true,
+        "data": map.data,
+    })];
+    for (i, group) in map.groups.iter().enumerate() {
+        let objects: Vec<Value> = group
+            .objects
+            .iter()
+            .map(|o| {
+                let (width, height) = o.size.unwrap_or((0.0, 0.0));
+                let mut object = json!({
+                    "id": o.id,
+                    "name": o.name,
+                    "type": o.class,
+                    "x": o.x,
+                    "y": o.y,
+                    "width": width,
+                    "height": height,
+                    "rotation": 0,
+                    "visible": true,
+                    "properties": o.properties.iter().map(|(name, value)| json!({
+                        "name": name,
+                        "type": value.type_name(),
+                        "value": value.to_json(),
+                    })).collect::<Vec<_>>(),
+                });
+                if o.size.is_none() {
+                    object["point"] = json!(true);
+                }
+                object
+            })
+            .collect();
+        layers.push(json!({
+            "id": i + 2,
+            "name": group.name,
+            "type": "objectgroup",
+            "draworder": "topdown",
+            "x": 0,
+            "y": 0,
+            "opacity": 1,
+            "visible": true,
+            "objects": objects,
+        }));
+    }
+
+    let tileset = &config.tileset;
+    let tileset = match &tileset.source {
+        Some(source) => json!({ "firstgid": tileset.first_gid, "source": source }),
+        None => json!({
+            "firstgid": tileset.first_gid,
+            "name": tileset.name,
+            "image": tileset.image,
+            "imagewidth": tileset.image_width,
+            "imageheight": tileset.image_height,
+            "tilewidth": config.tile_width,
+            "tileheight": config.tile_height,
+            "tilecount": tileset.tile_count,
+            "columns": tileset.columns,
+            "margin": 0,
+            "spacing": 0,
+        }),
+    };
+
+    let doc = json!({
+        "type": "map",
+        "version": "1.10",
+        "orientation": "orthogonal",
+        "renderorder": "right-down",
+        "infinite": false,
+        "width": w,
+        "height": h,
+        "tilewidth": config.tile_width,
+        "tileheight": config.tile_height,
+        "nextlayerid": layers.len() + 1,
+        "nextobjectid": map.next_object_id,
+        "layers": layers,
+        "tilesets": [tileset],
+    });
+    serde_json::to_string_pretty(&doc).unwrap_or_default()
+}
+
+/// Renders the map as Tiled XML (`.tmx`) with CSV-encoded tile data.
+pub fn to_tmx(
+    grid: &Grid<Tile>,
+    semantic: Option<&SemanticLayers>,
+    config: &TiledConfig,
+) -> String {
+    let map = TiledMap::build(grid, semantic, config);
+    let (w, h) = (grid.width(), grid.height());
+    let mut out = String::new();
+
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        out,
+        r#"<map version="1.10" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{}" tileheight="{}" infinite="0" nextlayerid="{}" nextobjectid="{}">"#,
+        w,
+        h,
+        config.tile_width,
+        config.tile_height,
+        map.groups.len() + 2,
+        map.next_object_id
+    );
+
+    let tileset = &config.tileset;
+    match &tileset.source {
+        Some(source) => {
+            let _ = writeln!(
+                out,
+                r#" <tileset firstgid="{}" source="{}"/>"#,
+                tileset.first_gid,
+                xml_escape(source)
+            );
+        }
+        None => {
+            let _ = writeln!(
+                out,
+                r#" <tileset firstgid="{}" name="{}" tilewidth="{}" tileheight="{}" tilecount="{}" columns="{}">"#,
+                tileset.first_gid,
+                xml_escape(&tileset.name),
+                config.tile_width,
+                config.tile_height,
+                tileset.tile_count,
+                tileset.columns
+            );
+            let _ = writeln!(
+                out,
+                r#"  <image source="{}" width="{}" height="{}"/>"#,
+                xml_escape(&tileset.image),
+                tileset.image_width,
+                tileset.image_height
+            );
+            let _ = writeln!(out, " </tileset>");
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        r#" <layer id="1" name="terrain" width="{}" height="{}">"#,
+        w, h
+    );
+    let _ = writeln!(out, r#"  <data encoding="csv">"#);
+    for (y, row) in map.data.chunks(w.max(1)).enumerate() {
+        let line: Vec<String> = row.iter().map(|gid| gid.to_string()).collect();
+        let sep = if y + 1 < h { "," } else { "" };
+        let _ = writeln!(out, "{}{}", line.join(","), sep);
+    }
+    let _ = writeln!(out, "  </data>");
+    let _ = writeln!(out, " </layer>");
+
+    for (i, group) in map.groups.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            r#" <objectgroup id="{}" name="{}">"#,
+            i + 2,
+            group.name
+        );
+        for o in &group.objects {
+            let size = match o.size {
+                Some((width, height)) => format!(r#" width="{}" height="{}""#, width, height),
+                None => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                r#"  <object id="{}" name="{}" type="{}" x="{}" y="{}"{}>"#,
+                o.id,
+                xml_escape(&o.name),
+                xml_escape(&o.class),
+                o.x,
+                o.y,
+                size
+            );
+            if !o.properties.is_empty() {
+                let _ = writeln!(out, "   <properties>");
+                for (name, value) in &o.properties {
+                    let _ = writeln!(
+                        out,
+                        r#"    <property name="{}" type="{}" value="{}"/>"#,
+                        xml_escape(name),
+                        value.type_name(),
+                        value.to_xml()
+                    );
+                }
+                let _ = writeln!(out, "   </properties>");
+            }
+            if o.size.is_none() {
+                let _ = writeln!(out, "   <point/>");
+            }
+            let _ = writeln!(out, "  </object>");
+        }
+        let _ = writeln!(out, " </objectgroup>");
+    }
+
+    let _ = writeln!(out, "</map>");
+    out
+}
+
+/// Writes the map to `path`, as TMX if the extension is `.tmx` and TMJ otherwise.
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    grid: &Grid<Tile>,
+    semantic: Option<&SemanticLayers>,
+    config: &TiledConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let is_tmx = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tmx"));
+    let content = if is_tmx {
+        to_tmx(grid, semantic, config)
+    } else {
+        to_tmj(grid, semantic, config)
+    };
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn bounds(cells: &[(u32, u32)]) -> Option<(u32, u32, u32, u32)> {
+    let first = cells.first()?;
+    Some(cells.iter().fold(
+        (first.0, first.1, first.0, first.1),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    ))
+}
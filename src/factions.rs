@@ -0,0 +1,321 @@
+//! Faction territories.
+//!
+//! [`assign`] splits the walkable area into one territory per [`Faction`].
+//! Seeds are spread out by walking distance, then territories grow outward
+//! together, always expanding the faction furthest below its weighted share,
+//! so areas stay balanced and borders follow corridors rather than straight
+//! Voronoi lines. Caverns no seed can reach go whole to the faction with the
+//! nearest territory.
+//!
+//! [`Territories::apply`] tags semantic regions with their majority faction,
+//! writes a `faction_<name>` mask per faction, and places each faction's
+//! encounter markers along its borders.
+//!
+//! ```rust
+//! use terrain_forge::factions::{self, Faction, FactionConfig};
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("bsp").unwrap().generate(&mut grid, 8);
+//! let mut semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(8));
+//!
+//! let config = FactionConfig::new(vec![
+//!     Faction::new("goblins", "goblin_patrol"),
+//!     Faction::new("undead", "skeleton_guard"),
+//! ]);
+//! let territories = factions::assign(&grid, &config, &mut Rng::new(8));
+//! territories.apply(&mut semantic, &mut Rng::new(8));
+//! assert!(semantic.regions.iter().all(|r| r.tags.iter().any(|t| t.starts_with("faction:"))));
+//! ```
+
+use crate::semantic::{Marker, SemanticLayers};
+use crate::spatial::{dijkstra_map, PathfindingConstraints};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+const NEIGHBORS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// A faction and the encounters it spawns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Faction {
+    pub name: String,
+    /// Tag of encounter markers placed on this faction's borders.
+    pub encounter: String,
+    /// Relative territory size. Default: 1.0.
+    pub weight: f64,
+}
+
+impl Faction {
+    pub fn new(name: impl Into<String>, encounter: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            encounter: encounter.into(),
+            weight: 1.0,
+        }
+    }
+
+    /// Sets `weight`.
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// Configuration for [`assign`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FactionConfig {
+    pub factions: Vec<Faction>,
+    /// Encounter markers per faction per neighboring faction. Default: 2.
+    pub encounters_per_border: usize,
+    /// Minimum Chebyshev distance between encounter markers. Default: 6.
+    pub encounter_spacing: usize,
+}
+
+impl Default for FactionConfig {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl FactionConfig {
+    pub fn new(factions: Vec<Faction>) -> Self {
+        Self {
+            factions,
+            encounters_per_border: 2,
+            encounter_spacing: 6,
+        }
+    }
+
+    /// Sets `encounters_per_border`.
+    pub fn with_encounters_per_border(mut self, count: usize) -> Self {
+        self.encounters_per_border = count;
+        self
+    }
+
+    /// Sets `encounter_spacing`.
+    pub fn with_encounter_spacing(mut self, spacing: usize) -> Self {
+        self.encounter_spacing = spacing;
+        self
+    }
+}
+
+/// Territory ownership for every walkable cell.
+#[derive(Debug, Clone)]
+pub struct Territories {
+    pub config: FactionConfig,
+    /// Owning faction index, indexed `[y][x]`; `None` on walls.
+    pub owner: Vec<Vec<Option<usize>>>,
+    /// Seed cell of each faction.
+    pub seeds: Vec<(usize, usize)>,
+}
+
+impl Territories {
+    pub fn owner(&self, x: usize, y: usize) -> Option<usize> {
+        self.owner
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .flatten()
+    }
+
+    /// Cells owned by each faction.
+    pub fn areas(&self) -> Vec<usize> {
+        let mut areas = vec![0; self.config.factions.len()];
+        for &owner in self.owner.iter().flatten().flatten() {
+            areas[owner] += 1;
+        }
+        areas
+    }
+
+    /// Border cells as `(x, y, owner, neighbor)`: cells next to another
+    /// faction's territory.
+    pub fn borders(&self) -> Vec<(usize, usize, usize, usize)> {
+        let mut borders = Vec::new();
+        for (y, row) in self.owner.iter().enumerate() {
+            for (x, &owner) in row.iter().enumerate() {
+                let Some(owner) = owner else { continue };
+                let rival = NEIGHBORS.iter().find_map(|&(dx, dy)| {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || ny < 0 {
+                        return None;
+                    }
+                    self.owner(nx as usize, ny as usize)
+                        .filter(|&other| other != owner)
+                });
+                if let Some(rival) = rival {
+                    borders.push((x, y, owner, rival));
+                }
+            }
+        }
+        borders
+    }
+
+    /// Tags regions, writes faction masks, and adds border encounter
+    /// markers. Returns the number of markers added.
+    pub fn apply(&self, semantic: &mut SemanticLayers, rng: &mut Rng) -> usize {
+        let factions = &self.config.factions;
+        for region in &mut semantic.regions {
+            let mut counts = vec![0usize; factions.len()];
+            for &(x, y) in &region.cells {
+                if let Some(owner) = self.owner(x as usize, y as usize) {
+                    counts[owner] += 1;
+                }
+            }
+            region.tags.retain(|t| !t.starts_with("faction:"));
+            let majority = (0..counts.len()).max_by_key(|&i| (counts[i], std::cmp::Reverse(i)));
+            if let Some(i) = majority.filter(|&i| counts[i] > 0) {
+                region.tags.push(format!("faction:{}", factions[i].name));
+            }
+        }
+
+        for (y, row) in self.owner.iter().enumerate() {
+            for (x, &owner) in row.iter().enumerate() {
+                if let Some(owner) = owner {
+                    let name = format!("faction_{}", factions[owner].name);
+                    semantic.masks.set_named(&name, x, y, true);
+                }
+            }
+        }
+
+        let mut by_pair: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+        for (x, y, owner, rival) in self.borders() {
+            by_pair.entry((owner, rival)).or_default().push((x, y));
+        }
+        let spacing = self.config.encounter_spacing.max(1) as u32;
+        let mut added = 0;
+        for ((owner, rival), mut cells) in by_pair {
+            rng.shuffle(&mut cells);
+            let mut placed = 0;
+            for (x, y) in cells {
+                if placed == self.config.encounters_per_border {
+                    break;
+                }
+                let (x, y) = (x as u32, y as u32);
+                let crowded = semantic
+                    .markers
+                    .iter()
+                    .any(|m| m.x.abs_diff(x).max(m.y.abs_diff(y)) < spacing);
+                if crowded {
+                    continue;
+                }
+                let mut marker = Marker::with_tag(x, y, factions[owner].encounter.clone());
+                marker
+                    .metadata
+                    .insert("faction".to_string(), factions[owner].name.clone());
+                marker
+                    .metadata
+                    .insert("rival".to_string(), factions[rival].name.clone());
+                semantic.markers.push(marker);
+                placed += 1;
+            }
+            added += placed;
+        }
+        added
+    }
+}
+
+/// Partitions the floor of `grid` among `config.factions`.
+pub fn assign(grid: &Grid<Tile>, config: &FactionConfig, rng: &mut Rng) -> Territories {
+    let (w, h) = (grid.width(), grid.height());
+    let mut owner = vec![vec![None; w]; h];
+    let count = config.factions.len();
+    let floor: Vec<(usize, usize)> = grid
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    if count == 0 || floor.is_empty() {
+        return Territories {
+            config: config.clone(),
+            owner,
+            seeds: Vec::new(),
+        };
+    }
+
+    // Farthest-point seeds within the largest cavern.
+    let cavern = grid
+        .flood_regions()
+        .into_iter()
+        .max_by_key(Vec::len)
+        .unwrap_or_default();
+    let constraints = PathfindingConstraints::cardinal();
+    let mut seeds = vec![*rng.pick(&cavern).unwrap_or(&floor[0])];
+    while seeds.len() < count {
+        let nearest = dijkstra_map(grid, &seeds, &constraints);
+        let steps = |x: usize, y: usize| {
+            let d = nearest.get(x, y);
+            if d.is_finite() {
+                d as usize
+            } else {
+                0
+            }
+        };
+        let next = cavern
+            .iter()
+            .copied()
+            .filter(|c| !seeds.contains(c))
+            .max_by_key(|&(x, y)| steps(x, y));
+        let Some(next) = next else { break };
+        seeds.push(next);
+    }
+
+    // Grow all territories together, favoring the one furthest below its share.
+    let mut frontier: Vec<VecDeque<(usize, usize)>> = vec![VecDeque::new(); count];
+    let mut areas = vec![0usize; count];
+    for (i, &(x, y)) in seeds.iter().enumerate() {
+        owner[y][x] = Some(i);
+        frontier[i].push_back((x, y));
+        areas[i] += 1;
+    }
+    loop {
+        let next = (0..count)
+            .filter(|&i| !frontier[i].is_empty())
+            .min_by(|&a, &b| {
+                let share = |i: usize| areas[i] as f64 / config.factions[i].weight.max(1e-6);
+                share(a).total_cmp(&share(b))
+            });
+        let Some(i) = next else { break };
+        let Some((x, y)) = frontier[i].pop_front() else {
+            continue;
+        };
+        for (dx, dy) in NEIGHBORS {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if !grid.get(nx, ny).is_some_and(|t| t.is_floor()) {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            if owner[ny][nx].is_none() {
+                owner[ny][nx] = Some(i);
+                areas[i] += 1;
+                frontier[i].push_back((nx, ny));
+            }
+        }
+    }
+
+    // Unreached caverns go to the nearest territory.
+    let claimed: Vec<(usize, usize, usize)> = floor
+        .iter()
+        .filter_map(|&(x, y)| owner[y][x].map(|o| (x, y, o)))
+        .collect();
+    for cells in grid.flood_regions() {
+        let (cx, cy) = cells[0];
+        if owner[cy][cx].is_some() {
+            continue;
+        }
+        let nearest = claimed
+            .iter()
+            .min_by_key(|&&(x, y, _)| x.abs_diff(cx).pow(2) + y.abs_diff(cy).pow(2))
+            .map_or(0, |&(_, _, o)| o);
+        for (x, y) in cells {
+            owner[y][x] = Some(nearest);
+        }
+    }
+
+    Territories {
+        config: config.clone(),
+        owner,
+        seeds,
+    }
+}
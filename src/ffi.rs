@@ -0,0 +1,394 @@
+//! C API (feature `ffi`).
+//!
+//! A small, stable C surface for engines that can't link Rust directly. Build
+//! a shared library with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib` and include
+//! `include/terrain_forge.h`.
+//!
+//! All handles are opaque and must be released with [`tf_grid_free`]. Functions
+//! returning `int32_t` use `0` for success and `-1` for failure; the failure
+//! message is available from [`tf_last_error`] on the same thread.
+//!
+//! No panic ever unwinds into the caller: every entry point catches it and
+//! reports it like any other failure (`-1`, null, or `0` for the getters),
+//! with a `panicked: ...` message from [`tf_last_error`]. A handle that was
+//! being modified when the panic hit may hold a partially generated grid,
+//! but is still valid to read and free.
+
+use crate::ops::{self, OpScript, Params};
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Rng, Tile};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Opaque grid handle with optional semantic layers.
+pub struct TfGrid {
+    grid: Grid<Tile>,
+    semantic: Option<SemanticLayers>,
+    /// Cached NUL-terminated marker tags, rebuilt whenever semantics change.
+    tags: Vec<CString>,
+}
+
+impl TfGrid {
+    fn set_semantic(&mut self, semantic: Option<SemanticLayers>) {
+        self.tags = semantic
+            .iter()
+            .flat_map(|s| s.markers.iter())
+            .map(|m| CString::new(m.tag().replace('\0', "")).unwrap_or_default())
+            .collect();
+        self.semantic = semantic;
+    }
+
+    /// Detaches the semantic layers before the grid is modified, so a
+    /// panic mid-modification can't leave stale layers behind.
+    fn take_semantic(&mut self) -> Option<SemanticLayers> {
+        let semantic = self.semantic.take();
+        self.set_semantic(None);
+        semantic
+    }
+}
+
+/// A semantic marker as seen from C.
+///
+/// `tag` points into the grid handle and stays valid until the grid's
+/// semantics change or the grid is freed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TfMarker {
+    pub x: u32,
+    pub y: u32,
+    pub weight: f32,
+    /// Region id, or `-1` if the marker has none.
+    pub region_id: i64,
+    pub tag: *const c_char,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: impl Into<String>) -> i32 {
+    let message = CString::new(message.into().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+    -1
+}
+
+/// Runs an entry point body, turning a panic into `fallback` plus an error
+/// message instead of unwinding across the C ABI.
+fn guard<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown payload".to_string());
+        set_error(format!("panicked: {message}"));
+        fallback
+    })
+}
+
+unsafe fn read_str<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{} is null", what));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", what))
+}
+
+unsafe fn read_params(ptr: *const c_char) -> Result<Option<Params>, String> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    let json = read_str(ptr, "params")?;
+    if json.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(json)
+        .map(Some)
+        .map_err(|e| format!("Invalid params JSON: {}", e))
+}
+
+/// Returns the message of the last error on this thread, or null.
+///
+/// The pointer is valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn tf_last_error() -> *const c_char {
+    guard(ptr::null(), || {
+        LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+    })
+}
+
+/// Returns the library version as a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn tf_version() -> *const c_char {
+    guard(ptr::null(), || {
+        concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+    })
+}
+
+/// Creates an all-wall grid. Free with [`tf_grid_free`].
+#[no_mangle]
+pub extern "C" fn tf_grid_new(width: u32, height: u32) -> *mut TfGrid {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(TfGrid {
+            grid: Grid::new(width as usize, height as usize),
+            semantic: None,
+            tags: Vec::new(),
+        }))
+    })
+}
+
+/// Releases a grid handle. Null is ignored.
+///
+/// # Safety
+/// `grid` must be null or a handle returned by this library that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn tf_grid_free(grid: *mut TfGrid) {
+    guard((), || {
+        if !grid.is_null() {
+            drop(Box::from_raw(grid));
+        }
+    })
+}
+
+/// Grid width, or 0 for null.
+///
+/// # Safety
+/// `grid` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tf_grid_width(grid: *const TfGrid) -> u32 {
+    guard(0, || grid.as_ref().map_or(0, |g| g.grid.width() as u32))
+}
+
+/// Grid height, or 0 for null.
+///
+/// # Safety
+/// `grid` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tf_grid_height(grid: *const TfGrid) -> u32 {
+    guard(0, || grid.as_ref().map_or(0, |g| g.grid.height() as u32))
+}
+
+/// Runs a named algorithm on the grid. `params_json` may be null.
+///
+/// Clears any previously extracted semantic layers.
+///
+/// # Safety
+/// `grid` must be a valid handle; `name` and `params_json` must be null or
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn tf_generate(
+    grid: *mut TfGrid,
+    name: *const c_char,
+    params_json: *const c_char,
+    seed: u64,
+) -> i32 {
+    guard(-1, || {
+        let Some(handle) = grid.as_mut() else {
+            return set_error("grid is null");
+        };
+        let semantic = handle.take_semantic();
+        let result = read_str(name, "name").and_then(|name| {
+            let params = read_params(params_json)?;
+            ops::generate(name, &mut handle.grid, Some(seed), params.as_ref())
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => 0,
+            Err(e) => {
+                handle.set_semantic(semantic);
+                set_error(e)
+            }
+        }
+    })
+}
+
+/// Applies a named effect. `params_json` may be null.
+///
+/// Effects see the current semantic layers, which are then cleared since
+/// they no longer describe the modified grid; extract again if needed.
+///
+/// # Safety
+/// Same as [`tf_generate`].
+#[no_mangle]
+pub unsafe extern "C" fn tf_effect(
+    grid: *mut TfGrid,
+    name: *const c_char,
+    params_json: *const c_char,
+) -> i32 {
+    guard(-1, || {
+        let Some(handle) = grid.as_mut() else {
+            return set_error("grid is null");
+        };
+        let semantic = handle.take_semantic();
+        let result = read_str(name, "name").and_then(|name| {
+            let params = read_params(params_json)?;
+            ops::effect(name, &mut handle.grid, params.as_ref(), semantic.as_ref())
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => 0,
+            Err(e) => {
+                handle.set_semantic(semantic);
+                set_error(e)
+            }
+        }
+    })
+}
+
+/// Extracts semantic layers with a profile (`default`, `caves`, `rooms`,
+/// `mazes`; null means `default`).
+///
+/// # Safety
+/// `grid` must be a valid handle; `profile` must be null or a NUL-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn tf_extract_semantic(
+    grid: *mut TfGrid,
+    profile: *const c_char,
+    seed: u64,
+) -> i32 {
+    guard(-1, || {
+        let Some(handle) = grid.as_mut() else {
+            return set_error("grid is null");
+        };
+        let profile = if profile.is_null() {
+            "default"
+        } else {
+            match read_str(profile, "profile") {
+                Ok(p) => p,
+                Err(e) => return set_error(e),
+            }
+        };
+        let extractor = match ops::script::extractor_for_profile(profile) {
+            Ok(extractor) => extractor,
+            Err(e) => return set_error(e.to_string()),
+        };
+        let semantic = extractor.extract(&handle.grid, &mut Rng::new(seed));
+        handle.set_semantic(Some(semantic));
+        0
+    })
+}
+
+/// Runs a JSON op script (see [`OpScript`]) and returns a new grid handle,
+/// or null on failure.
+///
+/// # Safety
+/// `script_json` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tf_run_script(script_json: *const c_char) -> *mut TfGrid {
+    guard(ptr::null_mut(), || {
+        let result = read_str(script_json, "script").and_then(|json| {
+            OpScript::from_json(json)
+                .and_then(|script| script.run())
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(output) => {
+                let mut handle = TfGrid {
+                    grid: output.grid,
+                    semantic: None,
+                    tags: Vec::new(),
+                };
+                handle.set_semantic(output.semantic);
+                Box::into_raw(Box::new(handle))
+            }
+            Err(e) => {
+                set_error(e);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Returns `1` for floor, `0` for wall, `-1` if out of bounds or null.
+///
+/// # Safety
+/// `grid` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tf_grid_get(grid: *const TfGrid, x: i32, y: i32) -> i32 {
+    guard(-1, || match grid.as_ref().and_then(|g| g.grid.get(x, y)) {
+        Some(tile) => tile.is_floor() as i32,
+        None => -1,
+    })
+}
+
+/// Copies tiles row-major into `out` (`1` floor, `0` wall).
+///
+/// Returns the number of cells written, at most `len`.
+///
+/// # Safety
+/// `grid` must be a valid handle and `out` must point to at least `len`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tf_grid_copy_tiles(
+    grid: *const TfGrid,
+    out: *mut u8,
+    len: usize,
+) -> usize {
+    guard(0, || {
+        let Some(handle) = grid.as_ref() else {
+            set_error("grid is null");
+            return 0;
+        };
+        if out.is_null() {
+            set_error("out is null");
+            return 0;
+        }
+        let out = std::slice::from_raw_parts_mut(out, len);
+        let mut written = 0;
+        for ((_, _, tile), slot) in handle.grid.iter().zip(out.iter_mut()) {
+            *slot = tile.is_floor() as u8;
+            written += 1;
+        }
+        written
+    })
+}
+
+/// Number of markers in the grid's semantic layers (0 if none extracted).
+///
+/// # Safety
+/// `grid` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tf_marker_count(grid: *const TfGrid) -> usize {
+    guard(0, || {
+        grid.as_ref()
+            .and_then(|g| g.semantic.as_ref())
+            .map_or(0, |s| s.markers.len())
+    })
+}
+
+/// Reads marker `index` into `out`.
+///
+/// # Safety
+/// `grid` must be a valid handle and `out` a valid, writable `TfMarker`.
+#[no_mangle]
+pub unsafe extern "C" fn tf_marker_get(
+    grid: *const TfGrid,
+    index: usize,
+    out: *mut TfMarker,
+) -> i32 {
+    guard(-1, || {
+        let Some(handle) = grid.as_ref() else {
+            return set_error("grid is null");
+        };
+        let Some(out) = out.as_mut() else {
+            return set_error("out is null");
+        };
+        let Some(marker) = handle.semantic.as_ref().and_then(|s| s.markers.get(index)) else {
+            return set_error(format!("marker index {} out of range", index));
+        };
+        *out = TfMarker {
+            x: marker.x,
+            y: marker.y,
+            weight: marker.weight,
+            region_id: marker.region_id.map_or(-1, i64::from),
+            tag: handle.tags[index].as_ptr(),
+        };
+        0
+    })
+}
@@ -0,0 +1,235 @@
+// Bit-packed tile kernels: one bit per cell (1 = floor), 32 cells per word.
+// Each invocation produces one output word, so writes never race.
+
+struct Params {
+    width: u32,
+    height: u32,
+    words_per_row: u32,
+    a: u32,
+    b: u32,
+    c: u32,
+    _pad0: u32,
+    _pad1: u32,
+    // perlin: frequency, out_min, range_span, threshold
+    f0: vec4<f32>,
+    // perlin: fill_min, fill_max, lacunarity, persistence
+    f1: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> p: Params;
+@group(0) @binding(1) var<storage, read> src: array<u32>;
+@group(0) @binding(2) var<storage, read_write> dst: array<u32>;
+@group(0) @binding(3) var<storage, read> perm: array<u32>;
+
+fn cell(x: i32, y: i32) -> u32 {
+    if (x < 0 || y < 0 || x >= i32(p.width) || y >= i32(p.height)) {
+        return 0u;
+    }
+    let word = src[u32(y) * p.words_per_row + u32(x) / 32u];
+    return (word >> (u32(x) % 32u)) & 1u;
+}
+
+fn is_interior(x: u32, y: u32) -> bool {
+    return x > 0u && y > 0u && x + 1u < p.width && y + 1u < p.height;
+}
+
+fn set_bit(word: u32, bit: u32, value: bool) -> u32 {
+    if (value) {
+        return word | (1u << bit);
+    }
+    return word & ~(1u << bit);
+}
+
+// a = birth limit, b = death limit
+@compute @workgroup_size(64)
+fn cellular_step(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let wx = gid.x;
+    let y = gid.y;
+    if (wx >= p.words_per_row || y >= p.height) {
+        return;
+    }
+    let idx = y * p.words_per_row + wx;
+    var out = src[idx];
+    for (var bit = 0u; bit < 32u; bit++) {
+        let x = wx * 32u + bit;
+        if (x >= p.width) {
+            break;
+        }
+        if (!is_interior(x, y)) {
+            continue;
+        }
+        let xi = i32(x);
+        let yi = i32(y);
+        var n = 0u;
+        for (var dy = -1; dy <= 1; dy++) {
+            for (var dx = -1; dx <= 1; dx++) {
+                if (dx != 0 || dy != 0) {
+                    n += cell(xi + dx, yi + dy);
+                }
+            }
+        }
+        let floor = cell(xi, yi) == 1u;
+        out = set_bit(out, bit, select(n >= p.a, n >= p.b, floor));
+    }
+    dst[idx] = out;
+}
+
+fn cross_count(x: i32, y: i32) -> u32 {
+    return cell(x - 1, y) + cell(x + 1, y) + cell(x, y - 1) + cell(x, y + 1);
+}
+
+@compute @workgroup_size(64)
+fn erode_step(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let wx = gid.x;
+    let y = gid.y;
+    if (wx >= p.words_per_row || y >= p.height) {
+        return;
+    }
+    let idx = y * p.words_per_row + wx;
+    var out = src[idx];
+    for (var bit = 0u; bit < 32u; bit++) {
+        let x = wx * 32u + bit;
+        if (x >= p.width) {
+            break;
+        }
+        if (is_interior(x, y) && cell(i32(x), i32(y)) == 1u && cross_count(i32(x), i32(y)) < 4u) {
+            out = set_bit(out, bit, false);
+        }
+    }
+    dst[idx] = out;
+}
+
+@compute @workgroup_size(64)
+fn dilate_step(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let wx = gid.x;
+    let y = gid.y;
+    if (wx >= p.words_per_row || y >= p.height) {
+        return;
+    }
+    let idx = y * p.words_per_row + wx;
+    var out = src[idx];
+    for (var bit = 0u; bit < 32u; bit++) {
+        let x = wx * 32u + bit;
+        if (x >= p.width) {
+            break;
+        }
+        if (is_interior(x, y) && cell(i32(x), i32(y)) == 0u && cross_count(i32(x), i32(y)) > 0u) {
+            out = set_bit(out, bit, true);
+        }
+    }
+    dst[idx] = out;
+}
+
+// a = radius; samples clamp at the low edge like the CPU filter.
+@compute @workgroup_size(64)
+fn blur(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let wx = gid.x;
+    let y = gid.y;
+    if (wx >= p.words_per_row || y >= p.height) {
+        return;
+    }
+    let r = i32(p.a);
+    let kernel = (2u * p.a + 1u) * (2u * p.a + 1u);
+    let idx = y * p.words_per_row + wx;
+    var out = src[idx];
+    for (var bit = 0u; bit < 32u; bit++) {
+        let x = wx * 32u + bit;
+        if (x >= p.width) {
+            break;
+        }
+        if (!is_interior(x, y)) {
+            continue;
+        }
+        var sum = 0u;
+        for (var dy = 0; dy <= 2 * r; dy++) {
+            for (var dx = 0; dx <= 2 * r; dx++) {
+                let nx = max(i32(x) + dx - r, 0);
+                let ny = max(i32(y) + dy - r, 0);
+                sum += cell(nx, ny);
+            }
+        }
+        out = set_bit(out, bit, sum * 2u >= kernel);
+    }
+    dst[idx] = out;
+}
+
+fn fade(t: f32) -> f32 {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+fn gradient(hash: u32, x: f32, y: f32) -> f32 {
+    let h = hash & 7u;
+    var u = select(y, x, h < 4u);
+    var v = select(x, y, h < 4u);
+    if ((h & 1u) != 0u) {
+        u = -u;
+    }
+    if ((h & 2u) != 0u) {
+        v = -v;
+    }
+    return u + v;
+}
+
+fn perlin(px: f32, py: f32) -> f32 {
+    let fx = floor(px);
+    let fy = floor(py);
+    let xi = u32(i32(fx) & 255);
+    let yi = u32(i32(fy) & 255);
+    let xf = px - fx;
+    let yf = py - fy;
+    let u = fade(xf);
+    let v = fade(yf);
+
+    let aa = perm[xi + perm[yi]];
+    let ab = perm[xi + perm[yi + 1u]];
+    let ba = perm[xi + 1u + perm[yi]];
+    let bb = perm[xi + 1u + perm[yi + 1u]];
+
+    let nx0 = mix(gradient(aa, xf, yf), gradient(ba, xf - 1.0, yf), u);
+    let nx1 = mix(gradient(ab, xf, yf - 1.0), gradient(bb, xf - 1.0, yf - 1.0), u);
+    return mix(nx0, nx1, v);
+}
+
+// a = octaves, b = 1 when a fill range is set
+@compute @workgroup_size(64)
+fn perlin_fill(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let wx = gid.x;
+    let y = gid.y;
+    if (wx >= p.words_per_row || y >= p.height) {
+        return;
+    }
+    let idx = y * p.words_per_row + wx;
+    var out = 0u;
+    for (var bit = 0u; bit < 32u; bit++) {
+        let x = wx * 32u + bit;
+        if (x >= p.width) {
+            break;
+        }
+        if (!is_interior(x, y)) {
+            continue;
+        }
+        var raw = 0.0;
+        if (p.a > 1u) {
+            var total = 0.0;
+            var freq = 1.0;
+            var amp = 1.0;
+            var max_value = 0.0;
+            for (var o = 0u; o < p.a; o++) {
+                total += perlin(f32(x) * freq * p.f0.x, f32(y) * freq * p.f0.x) * amp;
+                max_value += amp;
+                freq *= p.f1.z;
+                amp *= p.f1.w;
+            }
+            raw = total / max_value;
+        } else {
+            raw = perlin(f32(x) * p.f0.x, f32(y) * p.f0.x);
+        }
+        let value = p.f0.y + (raw + 1.0) * 0.5 * p.f0.z;
+        var fill = value >= p.f0.w;
+        if (p.b == 1u) {
+            fill = value >= p.f1.x && value <= p.f1.y;
+        }
+        out = set_bit(out, bit, fill);
+    }
+    dst[idx] = out;
+}
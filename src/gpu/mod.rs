@@ -0,0 +1,524 @@
+//! wgpu compute backend for very large grids (feature `gpu`).
+//!
+//! [`GpuBackend`] runs cellular automata iterations, erosion/dilation, the
+//! majority blur, and Perlin noise fill as compute shaders over bit-packed
+//! grids. The free functions in this module use the shared backend when an
+//! adapter is available and the grid has at least [`GPU_MIN_CELLS`] cells,
+//! and otherwise run the CPU implementation.
+//!
+//! Cellular, morphology, and blur kernels are integer-exact, so results match
+//! the CPU path bit for bit. Noise fill samples in `f32` on the GPU and may
+//! differ from the CPU (`f64`) near the threshold.
+//!
+//! ```rust,no_run
+//! use terrain_forge::algorithms::CellularConfig;
+//! use terrain_forge::{gpu, Grid, Tile};
+//!
+//! let mut grid: Grid<Tile> = Grid::new(4096, 4096);
+//! gpu::cellular(&mut grid, &CellularConfig::default(), 42);
+//! gpu::erode(&mut grid, 1);
+//! ```
+
+use crate::algorithms::{self, CellularAutomata, CellularConfig, NoiseFill, NoiseFillConfig};
+use crate::noise::Perlin;
+use crate::{effects, Algorithm, Grid, Tile};
+use std::sync::{mpsc, OnceLock};
+use wgpu::util::DeviceExt;
+
+/// Grids smaller than this run on the CPU; upload and readback dominate below it.
+pub const GPU_MIN_CELLS: usize = 512 * 512;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Error from GPU setup or dispatch.
+#[derive(Debug, Clone)]
+pub struct GpuError {
+    message: String,
+}
+
+impl GpuError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for GpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+struct Kernels {
+    cellular: wgpu::ComputePipeline,
+    erode: wgpu::ComputePipeline,
+    dilate: wgpu::ComputePipeline,
+    blur: wgpu::ComputePipeline,
+    perlin: wgpu::ComputePipeline,
+}
+
+/// Compute device with the tile kernels compiled.
+pub struct GpuBackend {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    kernels: Kernels,
+    adapter_name: String,
+}
+
+/// Uniform block mirrored by `Params` in `kernels.wgsl`.
+#[derive(Default, Clone, Copy)]
+struct Params {
+    a: u32,
+    b: u32,
+    f0: [f32; 4],
+    f1: [f32; 4],
+}
+
+impl Params {
+    fn to_words(self, width: u32, height: u32, words_per_row: u32) -> [u32; 16] {
+        let mut out = [0u32; 16];
+        out[..6].copy_from_slice(&[width, height, words_per_row, self.a, self.b, 0]);
+        for (i, v) in self.f0.iter().chain(self.f1.iter()).enumerate() {
+            out[8 + i] = v.to_bits();
+        }
+        out
+    }
+}
+
+impl GpuBackend {
+    /// Requests an adapter and device and compiles the kernels.
+    pub fn new() -> Result<Self, GpuError> {
+        pollster::block_on(Self::new_async())
+    }
+
+    async fn new_async() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| GpuError::new(format!("no GPU adapter: {}", e)))?;
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("terrain-forge"),
+                required_limits: wgpu::Limits::downlevel_defaults(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| GpuError::new(format!("device request failed: {}", e)))?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("terrain-forge kernels"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("kernels.wgsl").into()),
+        });
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("terrain-forge bindings"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, false),
+                storage(3, true),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("terrain-forge pipeline layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: Some(entry),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let kernels = Kernels {
+            cellular: pipeline("cellular_step"),
+            erode: pipeline("erode_step"),
+            dilate: pipeline("dilate_step"),
+            blur: pipeline("blur"),
+            perlin: pipeline("perlin_fill"),
+        };
+
+        Ok(Self {
+            adapter_name: adapter.get_info().name,
+            device,
+            queue,
+            layout,
+            kernels,
+        })
+    }
+
+    /// Process-wide backend, created on first use; `None` when no adapter is available.
+    pub fn shared() -> Option<&'static GpuBackend> {
+        static SHARED: OnceLock<Option<GpuBackend>> = OnceLock::new();
+        SHARED.get_or_init(|| GpuBackend::new().ok()).as_ref()
+    }
+
+    /// Name reported by the adapter.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// Runs `iterations` automata passes; matches [`CellularAutomata`].
+    pub fn cellular_steps(
+        &self,
+        grid: &mut Grid<Tile>,
+        iterations: usize,
+        birth_limit: usize,
+        death_limit: usize,
+    ) -> Result<(), GpuError> {
+        let params = Params {
+            a: birth_limit.min(u32::MAX as usize) as u32,
+            b: death_limit.min(u32::MAX as usize) as u32,
+            ..Default::default()
+        };
+        self.apply(&self.kernels.cellular, grid, params, iterations, None)
+    }
+
+    /// Matches [`effects::erode`].
+    pub fn erode(&self, grid: &mut Grid<Tile>, iterations: usize) -> Result<(), GpuError> {
+        self.apply(
+            &self.kernels.erode,
+            grid,
+            Params::default(),
+            iterations,
+            None,
+        )
+    }
+
+    /// Matches [`effects::dilate`].
+    pub fn dilate(&self, grid: &mut Grid<Tile>, iterations: usize) -> Result<(), GpuError> {
+        self.apply(
+            &self.kernels.dilate,
+            grid,
+            Params::default(),
+            iterations,
+            None,
+        )
+    }
+
+    /// Matches [`effects::gaussian_blur`].
+    pub fn gaussian_blur(&self, grid: &mut Grid<Tile>, radius: usize) -> Result<(), GpuError> {
+        let radius = u32::try_from(radius).map_err(|_| GpuError::new("blur radius too large"))?;
+        let params = Params {
+            a: radius,
+            ..Default::default()
+        };
+        self.apply(&self.kernels.blur, grid, params, 1, None)
+    }
+
+    /// Perlin noise fill in `f32`; other noise types return an error.
+    pub fn noise_fill(
+        &self,
+        grid: &mut Grid<Tile>,
+        config: &NoiseFillConfig,
+        seed: u64,
+    ) -> Result<(), GpuError> {
+        if !matches!(config.noise, algorithms::NoiseType::Perlin) {
+            return Err(GpuError::new(format!(
+                "{:?} noise has no GPU kernel",
+                config.noise
+            )));
+        }
+        let scale = if config.scale > 0.0 {
+            config.scale
+        } else {
+            1.0
+        };
+        let (mut out_min, mut out_max) = config.output_range;
+        if out_min > out_max {
+            std::mem::swap(&mut out_min, &mut out_max);
+        }
+        let (fill_min, fill_max) = config
+            .fill_range
+            .map(|(a, b)| if a <= b { (a, b) } else { (b, a) })
+            .unwrap_or_default();
+        let params = Params {
+            a: config.octaves,
+            b: config.fill_range.is_some() as u32,
+            f0: [
+                (config.frequency / scale) as f32,
+                out_min as f32,
+                (out_max - out_min) as f32,
+                config.threshold as f32,
+            ],
+            f1: [
+                fill_min as f32,
+                fill_max as f32,
+                config.lacunarity as f32,
+                config.persistence as f32,
+            ],
+        };
+        let perm: Vec<u32> = Perlin::new(seed)
+            .permutation()
+            .iter()
+            .map(|&v| v as u32)
+            .collect();
+        self.apply(&self.kernels.perlin, grid, params, 1, Some(&perm))
+    }
+
+    /// Dispatches `passes` ping-pong passes and writes the result back to `grid`.
+    /// `grid` is only modified when the whole dispatch succeeds.
+    fn apply(
+        &self,
+        kernel: &wgpu::ComputePipeline,
+        grid: &mut Grid<Tile>,
+        params: Params,
+        passes: usize,
+        perm: Option<&[u32]>,
+    ) -> Result<(), GpuError> {
+        let (w, h) = (grid.width(), grid.height());
+        if passes == 0 || w == 0 || h == 0 {
+            return Ok(());
+        }
+        let words_per_row = w.div_ceil(32);
+        let limits = self.device.limits();
+        let bytes = (words_per_row * h * 4) as u64;
+        let groups_x = (words_per_row as u32).div_ceil(WORKGROUP_SIZE);
+        if bytes > limits.max_storage_buffer_binding_size as u64
+            || h > limits.max_compute_workgroups_per_dimension as usize
+            || groups_x > limits.max_compute_workgroups_per_dimension
+        {
+            return Err(GpuError::new(format!(
+                "{}x{} grid exceeds device limits",
+                w, h
+            )));
+        }
+
+        let uniform = params.to_words(w as u32, h as u32, words_per_row as u32);
+        let uniform = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: bytemuck::cast_slice(&uniform),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let usage = wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_SRC
+            | wgpu::BufferUsages::COPY_DST;
+        let front = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("cells a"),
+                contents: bytemuck::cast_slice(&pack(grid, words_per_row)),
+                usage,
+            });
+        let back = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("cells b"),
+            size: bytes,
+            usage,
+            mapped_at_creation: false,
+        });
+        let perm = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("perm"),
+                contents: bytemuck::cast_slice(perm.unwrap_or(&[0])),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let bind = |src: &wgpu::Buffer, dst: &wgpu::Buffer| {
+            self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: src.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: dst.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: perm.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+        let forward = bind(&front, &back);
+        let backward = bind(&back, &front);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(kernel);
+            for i in 0..passes {
+                let group = if i.is_multiple_of(2) {
+                    &forward
+                } else {
+                    &backward
+                };
+                pass.set_bind_group(0, group, &[]);
+                pass.dispatch_workgroups(groups_x, h as u32, 1);
+            }
+        }
+        let result = if passes.is_multiple_of(2) {
+            &front
+        } else {
+            &back
+        };
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: bytes,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        encoder.copy_buffer_to_buffer(result, 0, &staging, 0, bytes);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let (tx, rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |r| {
+            let _ = tx.send(r);
+        });
+        self.device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|e| GpuError::new(format!("device poll failed: {}", e)))?;
+        rx.recv()
+            .map_err(|_| GpuError::new("readback was dropped"))?
+            .map_err(|e| GpuError::new(format!("readback failed: {}", e)))?;
+        let words: Vec<u32> = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+
+        unpack(grid, &words, words_per_row);
+        Ok(())
+    }
+}
+
+fn pack(grid: &Grid<Tile>, words_per_row: usize) -> Vec<u32> {
+    let w = grid.width();
+    let mut words = vec![0u32; words_per_row * grid.height()];
+    for (i, tile) in grid.cells().iter().enumerate() {
+        if tile.is_floor() {
+            let (x, y) = (i % w, i / w);
+            words[y * words_per_row + x / 32] |= 1 << (x % 32);
+        }
+    }
+    words
+}
+
+fn unpack(grid: &mut Grid<Tile>, words: &[u32], words_per_row: usize) {
+    let w = grid.width();
+    for (i, tile) in grid.cells_mut().iter_mut().enumerate() {
+        let (x, y) = (i % w, i / w);
+        let floor = words[y * words_per_row + x / 32] >> (x % 32) & 1 == 1;
+        *tile = if floor { Tile::Floor } else { Tile::Wall };
+    }
+}
+
+fn backend_for(grid: &Grid<Tile>) -> Option<&'static GpuBackend> {
+    if grid.width() * grid.height() < GPU_MIN_CELLS {
+        return None;
+    }
+    GpuBackend::shared()
+}
+
+/// Same output as [`CellularAutomata`]; iterations run on the GPU when available.
+pub fn cellular(grid: &mut Grid<Tile>, config: &CellularConfig, seed: u64) {
+    CellularAutomata::new(config.clone()).seed_floors(grid, seed);
+    cellular_steps(
+        grid,
+        config.iterations,
+        config.birth_limit,
+        config.death_limit,
+    );
+    if config.connect {
+        algorithms::connect_caves(grid, config.min_cave_size);
+    }
+}
+
+/// Automata passes over interior cells, on the GPU when available.
+pub fn cellular_steps(
+    grid: &mut Grid<Tile>,
+    iterations: usize,
+    birth_limit: usize,
+    death_limit: usize,
+) {
+    if let Some(gpu) = backend_for(grid) {
+        if gpu
+            .cellular_steps(grid, iterations, birth_limit, death_limit)
+            .is_ok()
+        {
+            return;
+        }
+    }
+    for _ in 0..iterations {
+        algorithms::cellular_step(grid, birth_limit, death_limit);
+    }
+}
+
+/// [`effects::erode`], on the GPU when available.
+pub fn erode(grid: &mut Grid<Tile>, iterations: usize) {
+    if let Some(gpu) = backend_for(grid) {
+        if gpu.erode(grid, iterations).is_ok() {
+            return;
+        }
+    }
+    effects::erode(grid, iterations);
+}
+
+/// [`effects::dilate`], on the GPU when available.
+pub fn dilate(grid: &mut Grid<Tile>, iterations: usize) {
+    if let Some(gpu) = backend_for(grid) {
+        if gpu.dilate(grid, iterations).is_ok() {
+            return;
+        }
+    }
+    effects::dilate(grid, iterations);
+}
+
+/// [`effects::gaussian_blur`], on the GPU when available.
+pub fn gaussian_blur(grid: &mut Grid<Tile>, radius: usize) {
+    if let Some(gpu) = backend_for(grid) {
+        if gpu.gaussian_blur(grid, radius).is_ok() {
+            return;
+        }
+    }
+    effects::gaussian_blur(grid, radius);
+}
+
+/// [`NoiseFill`], on the GPU for Perlin noise when available.
+pub fn noise_fill(grid: &mut Grid<Tile>, config: &NoiseFillConfig, seed: u64) {
+    if let Some(gpu) = backend_for(grid) {
+        if gpu.noise_fill(grid, config, seed).is_ok() {
+            return;
+        }
+    }
+    NoiseFill::new(config.clone()).generate(grid, seed);
+}
@@ -28,6 +28,10 @@ pub trait Cell: Clone + Default {
     fn is_passable(&self) -> bool;
     /// Marks this cell as passable. Default implementation is a no-op.
     fn set_passable(&mut self) {}
+    /// Marks this cell as impassable. Default implementation resets to `Self::default()`.
+    fn set_impassable(&mut self) {
+        *self = Self::default();
+    }
 }
 
 /// Basic tile type for dungeon/terrain generation.
@@ -58,6 +62,21 @@ impl Cell for Tile {
     fn set_passable(&mut self) {
         *self = Tile::Floor;
     }
+    fn set_impassable(&mut self) {
+        *self = Tile::Wall;
+    }
+}
+
+/// Label maps, such as territory ids; 0 is impassable (unlabeled).
+impl Cell for u8 {
+    fn is_passable(&self) -> bool {
+        *self != 0
+    }
+    fn set_passable(&mut self) {
+        if *self == 0 {
+            *self = 1;
+        }
+    }
 }
 
 /// 2D grid of cells.
@@ -75,13 +94,40 @@ impl Cell for Tile {
 /// grid.set(5, 5, Tile::Floor);
 /// assert_eq!(grid.count(|t| t.is_floor()), 1);
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Grid<C: Cell = Tile> {
     width: usize,
     height: usize,
     cells: Vec<C>,
 }
 
+impl<'de, C: Cell + Deserialize<'de>> Deserialize<'de> for Grid<C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Grid")]
+        struct Raw<C> {
+            width: usize,
+            height: usize,
+            cells: Vec<C>,
+        }
+
+        let raw = Raw::<C>::deserialize(deserializer)?;
+        if raw.width.checked_mul(raw.height) != Some(raw.cells.len()) {
+            return Err(serde::de::Error::custom(format!(
+                "grid is {}x{} but has {} cells",
+                raw.width,
+                raw.height,
+                raw.cells.len()
+            )));
+        }
+        Ok(Self {
+            width: raw.width,
+            height: raw.height,
+            cells: raw.cells,
+        })
+    }
+}
+
 impl<C: Cell> Grid<C> {
     /// Creates a new grid filled with `C::default()`.
     #[must_use]
@@ -165,6 +211,59 @@ impl<C: Cell> Grid<C> {
         self.cells.iter().filter(|c| predicate(c)).count()
     }
 
+    /// Row-major cells.
+    pub(crate) fn cells(&self) -> &[C] {
+        &self.cells
+    }
+
+    /// Row-major cells, mutable.
+    pub(crate) fn cells_mut(&mut self) -> &mut [C] {
+        &mut self.cells
+    }
+
+    /// Returns a [`Tile`] grid mirroring this grid's passability.
+    #[must_use]
+    pub fn to_tiles(&self) -> Grid<Tile> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self
+                .cells
+                .iter()
+                .map(|c| {
+                    if c.is_passable() {
+                        Tile::Floor
+                    } else {
+                        Tile::Wall
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes passability from a [`Tile`] grid of the same size.
+    ///
+    /// Only cells whose passability differs are touched, via
+    /// [`Cell::set_passable`] / [`Cell::set_impassable`], so extra per-cell
+    /// data survives. Cells outside the overlap are left unchanged.
+    pub fn apply_tiles(&mut self, tiles: &Grid<Tile>) {
+        let w = self.width.min(tiles.width);
+        let h = self.height.min(tiles.height);
+        for y in 0..h {
+            for x in 0..w {
+                let cell = &mut self.cells[y * self.width + x];
+                match (
+                    cell.is_passable(),
+                    tiles.cells[y * tiles.width + x].is_floor(),
+                ) {
+                    (false, true) => cell.set_passable(),
+                    (true, false) => cell.set_impassable(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
     /// Iterates over all cells as `(x, y, &cell)`.
     pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &C)> {
         self.cells
@@ -173,6 +272,27 @@ impl<C: Cell> Grid<C> {
             .map(move |(i, c)| (i % self.width, i / self.width, c))
     }
 
+    /// Returns a copy resampled to `width` x `height` (nearest neighbor).
+    #[must_use]
+    pub fn resized(&self, width: usize, height: usize) -> Grid<C> {
+        if self.cells.is_empty() {
+            return Grid::new(width, height);
+        }
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let sy = y * self.height / height.max(1);
+            for x in 0..width {
+                let sx = x * self.width / width.max(1);
+                cells.push(self.cells[sy * self.width + sx].clone());
+            }
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
     /// BFS from `(sx, sy)`, returns all connected passable cells.
     pub fn flood_fill(&self, sx: usize, sy: usize) -> Vec<(usize, usize)> {
         let (w, h) = (self.width, self.height);
@@ -245,40 +365,63 @@ impl<C: Cell> Grid<C> {
 
     /// 4-directional neighbors within bounds.
     pub fn neighbors_4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
-        let (w, h) = (self.width, self.height);
-        let mut n = Vec::with_capacity(4);
-        if x > 0 {
-            n.push((x - 1, y));
-        }
-        if x + 1 < w {
-            n.push((x + 1, y));
-        }
-        if y > 0 {
-            n.push((x, y - 1));
-        }
-        if y + 1 < h {
-            n.push((x, y + 1));
-        }
-        n.into_iter()
+        neighbors_4_within(x, y, self.width, self.height)
     }
 
     /// 8-directional neighbors within bounds.
     pub fn neighbors_8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
-        let (w, h) = (self.width, self.height);
-        let mut n = Vec::with_capacity(8);
-        for dy in -1i32..=1 {
-            for dx in -1i32..=1 {
-                if dx == 0 && dy == 0 {
-                    continue;
-                }
-                let nx = x as i32 + dx;
-                let ny = y as i32 + dy;
-                if nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h {
-                    n.push((nx as usize, ny as usize));
-                }
+        neighbors_8_within(x, y, self.width, self.height)
+    }
+}
+
+impl Grid<Tile> {
+    /// Loads a PNG as a tile grid: pixels with luminance `>= threshold`
+    /// (0-255) become floor, darker or transparent pixels become wall.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_image<P: AsRef<std::path::Path>>(
+        path: P,
+        threshold: u8,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_image_bytes(&std::fs::read(path)?, threshold)
+    }
+
+    /// Decodes PNG bytes as a tile grid (see [`Grid::from_image`]).
+    pub fn from_image_bytes(
+        bytes: &[u8],
+        threshold: u8,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        let channels = info.color_type.samples();
+        let (w, h) = (info.width as usize, info.height as usize);
+
+        let mut grid = Grid::new(w, h);
+        for (i, px) in buf[..info.buffer_size()]
+            .chunks_exact(channels)
+            .take(w * h)
+            .enumerate()
+        {
+            let (luma, alpha) = match px {
+                [l] => (*l as u32, 255),
+                [l, a] => (*l as u32, *a),
+                [r, g, b] => (
+                    (*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114) / 1000,
+                    255,
+                ),
+                [r, g, b, a, ..] => (
+                    (*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114) / 1000,
+                    *a,
+                ),
+                [] => (0, 0),
+            };
+            if alpha >= 128 && luma >= threshold as u32 {
+                grid.cells[i] = Tile::Floor;
             }
         }
-        n.into_iter()
+        Ok(grid)
     }
 }
 
@@ -328,6 +471,54 @@ impl fmt::Display for Grid<Tile> {
     }
 }
 
+/// 4-directional neighbors of `(x, y)` inside a `width` x `height` area,
+/// for flat buffers (height fields, masks) that aren't a [`Grid`].
+pub(crate) fn neighbors_4_within(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    let mut n = Vec::with_capacity(4);
+    if x > 0 {
+        n.push((x - 1, y));
+    }
+    if x + 1 < width {
+        n.push((x + 1, y));
+    }
+    if y > 0 {
+        n.push((x, y - 1));
+    }
+    if y + 1 < height {
+        n.push((x, y + 1));
+    }
+    n.into_iter()
+}
+
+/// 8-directional neighbors of `(x, y)` inside a `width` x `height` area,
+/// row by row.
+pub(crate) fn neighbors_8_within(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    let mut n = Vec::with_capacity(8);
+    for dy in -1i32..=1 {
+        for dx in -1i32..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            if nx >= 0 && ny >= 0 && (nx as usize) < width && (ny as usize) < height {
+                n.push((nx as usize, ny as usize));
+            }
+        }
+    }
+    n.into_iter()
+}
+
 /// Bresenham-style line from `start` to `end` (inclusive).
 pub fn line_points(start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
     let (mut x, mut y) = (start.0 as i32, start.1 as i32);
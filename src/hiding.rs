@@ -0,0 +1,355 @@
+//! Hiddenness scores for treasure and secrets.
+//!
+//! Loot dropped on random floor cells mostly lands in open corridors the
+//! player walks through anyway. [`score`] rates every floor cell from 0
+//! (in plain sight) to 1 (tucked away) by blending three terms:
+//!
+//! - **Occlusion**: how few floor cells can see it within
+//!   [`HidingConfig::sight_radius`], by line of sight through floor.
+//! - **Detour**: how far off the critical path it lies, the shortest route
+//!   from the spawn to the exit (or to the farthest reachable cell, as in
+//!   [`pacing`](crate::pacing)).
+//! - **Dead end**: how close it is to the tip of a dead end (see
+//!   [`find_dead_ends`]).
+//!
+//! The result is a value grid (rows of `f64`, like the
+//! [`climate`](crate::climate) layers), so it also works as a
+//! [`theme`](crate::theme) channel. [`pick`] takes the best spaced-out
+//! cells from it and [`place`] turns them into `Treasure` markers;
+//! [`PacingConfig::hide_loot`](crate::pacing::PacingConfig::hide_loot)
+//! steers paced loot the same way, and [`mask`] marks hiding spots for
+//! secret-room passes.
+//!
+//! ```rust
+//! use terrain_forge::hiding::{self, HidingConfig};
+//! use terrain_forge::semantic::{Marker, MarkerType};
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("cellular").unwrap().generate(&mut grid, 5);
+//! let mut semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(5));
+//! let (x, y, _) = grid.iter().find(|(_, _, t)| t.is_floor()).unwrap();
+//! semantic.markers.push(Marker::new(x as u32, y as u32, MarkerType::Spawn));
+//!
+//! let config = HidingConfig::default();
+//! let scores = hiding::score(&grid, &semantic, &config);
+//! assert_eq!(scores.len(), 60);
+//! let placed = hiding::place(&grid, &mut semantic, &config, 3);
+//! assert_eq!(placed.len(), 3);
+//! let average = scores.iter().flatten().sum::<f64>() / grid.count(|t| t.is_floor()) as f64;
+//! assert!(placed.iter().all(|&(x, y)| scores[y][x] > average));
+//! ```
+
+use crate::effects::find_dead_ends;
+use crate::pacing::path_end;
+use crate::semantic::{meta, Marker, MarkerType, SemanticLayers};
+use crate::spatial::{dijkstra_map, PathfindingConstraints};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Configuration for [`score`] and [`place`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HidingConfig {
+    /// How far a cell can be seen from, in cells. Default: 8.
+    pub sight_radius: usize,
+    /// Distance off the critical path at which the detour term is full,
+    /// in path steps. Default: 10.0.
+    pub path_falloff: f64,
+    /// Steps from a dead-end tip over which the dead-end term fades out.
+    /// Default: 6.
+    pub dead_end_reach: usize,
+    /// Weight of the occlusion term. Default: 0.4.
+    pub occlusion_weight: f64,
+    /// Weight of the detour term; dropped when there is no spawn.
+    /// Default: 0.3.
+    pub detour_weight: f64,
+    /// Weight of the dead-end term. Default: 0.3.
+    pub dead_end_weight: f64,
+    /// Smallest distance between cells from [`pick`], and between placed
+    /// treasure and other markers. Default: 6.0.
+    pub spacing: f32,
+}
+
+impl Default for HidingConfig {
+    fn default() -> Self {
+        Self {
+            sight_radius: 8,
+            path_falloff: 10.0,
+            dead_end_reach: 6,
+            occlusion_weight: 0.4,
+            detour_weight: 0.3,
+            dead_end_weight: 0.3,
+            spacing: 6.0,
+        }
+    }
+}
+
+impl HidingConfig {
+    /// Sets `sight_radius`.
+    pub fn with_sight_radius(mut self, radius: usize) -> Self {
+        self.sight_radius = radius;
+        self
+    }
+
+    /// Sets `path_falloff`.
+    pub fn with_path_falloff(mut self, falloff: f64) -> Self {
+        self.path_falloff = falloff;
+        self
+    }
+
+    /// Sets `dead_end_reach`.
+    pub fn with_dead_end_reach(mut self, reach: usize) -> Self {
+        self.dead_end_reach = reach;
+        self
+    }
+
+    /// Sets the occlusion, detour, and dead-end weights.
+    pub fn with_weights(mut self, occlusion: f64, detour: f64, dead_end: f64) -> Self {
+        self.occlusion_weight = occlusion;
+        self.detour_weight = detour;
+        self.dead_end_weight = dead_end;
+        self
+    }
+
+    /// Sets `spacing`.
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+}
+
+/// Hiddenness of every cell in `0..=1`, indexed `[y][x]`; walls score 0.
+///
+/// The critical path starts at the first `Spawn` (or `PlayerStart`)
+/// marker and ends at the first `Exit` marker if reachable. Cells the
+/// spawn can't reach get the full detour term. Weights are normalized over
+/// the terms in use.
+pub fn score(grid: &Grid<Tile>, semantic: &SemanticLayers, config: &HidingConfig) -> Vec<Vec<f64>> {
+    let (w, h) = (grid.width(), grid.height());
+    let occlusion = occlusion(grid, config.sight_radius);
+    let dead_end = dead_end_depth(grid, config.dead_end_reach);
+    let detour = detour(grid, semantic, config.path_falloff);
+    let detour_weight = if detour.is_some() {
+        config.detour_weight
+    } else {
+        0.0
+    };
+    let total = config.occlusion_weight + detour_weight + config.dead_end_weight;
+
+    let mut scores = vec![vec![0.0; w]; h];
+    if total <= 0.0 {
+        return scores;
+    }
+    for (x, y, tile) in grid.iter() {
+        if !tile.is_floor() {
+            continue;
+        }
+        let i = y * w + x;
+        let detour = detour.as_ref().map_or(0.0, |d| d[i]);
+        scores[y][x] = (config.occlusion_weight * occlusion[i]
+            + detour_weight * detour
+            + config.dead_end_weight * dead_end[i])
+            / total;
+    }
+    scores
+}
+
+/// Up to `count` floor cells in descending score order, each at least
+/// `spacing` from the ones before; ties go to the first cell in row-major
+/// order. Cells scoring 0 are never picked.
+pub fn pick(scores: &[Vec<f64>], count: usize, spacing: f32) -> Vec<(usize, usize)> {
+    let mut cells: Vec<(f64, usize, usize)> = scores
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &s)| (s, x, y)))
+        .filter(|&(s, ..)| s > 0.0)
+        .collect();
+    cells.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut picked: Vec<(usize, usize)> = Vec::new();
+    for (_, x, y) in cells {
+        if picked.len() == count {
+            break;
+        }
+        if picked.iter().all(|&p| far_enough(p, (x, y), spacing)) {
+            picked.push((x, y));
+        }
+    }
+    picked
+}
+
+/// Adds up to `count` `Treasure` markers at the most hidden cells, at
+/// least `spacing` from each other and from existing markers, recording
+/// [`meta::HIDING_SCORE`]. Returns their cells.
+pub fn place(
+    grid: &Grid<Tile>,
+    semantic: &mut SemanticLayers,
+    config: &HidingConfig,
+    count: usize,
+) -> Vec<(usize, usize)> {
+    let mut scores = score(grid, semantic, config);
+    let taken: Vec<(usize, usize)> = semantic
+        .markers
+        .iter()
+        .map(|m| (m.x as usize, m.y as usize))
+        .collect();
+    for (y, row) in scores.iter_mut().enumerate() {
+        for (x, s) in row.iter_mut().enumerate() {
+            if !taken.iter().all(|&p| far_enough(p, (x, y), config.spacing)) {
+                *s = 0.0;
+            }
+        }
+    }
+    let region_of: HashMap<(u32, u32), u32> = semantic
+        .regions
+        .iter()
+        .flat_map(|r| r.cells.iter().map(move |&cell| (cell, r.id)))
+        .collect();
+    let picked = pick(&scores, count, config.spacing);
+    for &(x, y) in &picked {
+        let mut marker = Marker::new(x as u32, y as u32, MarkerType::Treasure)
+            .with_metadata(meta::HIDING_SCORE, format!("{:.2}", scores[y][x]));
+        marker.region_id = region_of.get(&(x as u32, y as u32)).copied();
+        semantic.markers.push(marker);
+    }
+    picked
+}
+
+/// Cells scoring at least `threshold`, indexed `[y][x]`, e.g. to store as
+/// a named [`Masks`](crate::semantic::Masks) layer.
+pub fn mask(scores: &[Vec<f64>], threshold: f64) -> Vec<Vec<bool>> {
+    scores
+        .iter()
+        .map(|row| row.iter().map(|&s| s > 0.0 && s >= threshold).collect())
+        .collect()
+}
+
+fn far_enough(a: (usize, usize), b: (usize, usize), spacing: f32) -> bool {
+    let dx = a.0 as f32 - b.0 as f32;
+    let dy = a.1 as f32 - b.1 as f32;
+    dx * dx + dy * dy >= spacing * spacing
+}
+
+/// Per cell, one minus the share of the best-seen cell's viewers that see
+/// it, counting floor within `radius` with a clear Bresenham line.
+fn occlusion(grid: &Grid<Tile>, radius: usize) -> Vec<f64> {
+    let (w, h) = (grid.width(), grid.height());
+    let r = radius as i32;
+    let floor = |x: i32, y: i32| grid.get(x, y).is_some_and(|t| t.is_floor());
+    let mut seen = vec![0usize; w * h];
+    for (x, y, tile) in grid.iter() {
+        if !tile.is_floor() {
+            continue;
+        }
+        let (x, y) = (x as i32, y as i32);
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if (dx, dy) != (0, 0)
+                    && dx * dx + dy * dy <= r * r
+                    && floor(x + dx, y + dy)
+                    && clear_line(&floor, (x, y), (x + dx, y + dy))
+                {
+                    seen[y as usize * w + x as usize] += 1;
+                }
+            }
+        }
+    }
+    let most = seen.iter().copied().max().unwrap_or(0).max(1) as f64;
+    seen.iter().map(|&s| 1.0 - s as f64 / most).collect()
+}
+
+/// Whether every cell strictly between `from` and `to` is floor.
+fn clear_line(floor: &impl Fn(i32, i32) -> bool, from: (i32, i32), to: (i32, i32)) -> bool {
+    let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+    let (sx, sy) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let (mut x, mut y, mut err) = (from.0, from.1, dx + dy);
+    loop {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        if (x, y) == to {
+            return true;
+        }
+        if !floor(x, y) {
+            return false;
+        }
+    }
+}
+
+/// Per cell, `1` at a dead-end tip fading to `0` at `reach` floor steps
+/// away.
+fn dead_end_depth(grid: &Grid<Tile>, reach: usize) -> Vec<f64> {
+    let (w, h) = (grid.width(), grid.height());
+    let mut steps = vec![usize::MAX; w * h];
+    let mut queue = VecDeque::new();
+    for (x, y) in find_dead_ends(grid) {
+        steps[y * w + x] = 0;
+        queue.push_back((x, y));
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let next = steps[y * w + x] + 1;
+        if next >= reach {
+            continue;
+        }
+        for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if grid.get(nx, ny).is_some_and(|t| t.is_floor()) {
+                let j = ny as usize * w + nx as usize;
+                if steps[j] > next {
+                    steps[j] = next;
+                    queue.push_back((nx as usize, ny as usize));
+                }
+            }
+        }
+    }
+    steps
+        .into_iter()
+        .map(|s| match s {
+            usize::MAX => 0.0,
+            s => 1.0 - s as f64 / reach.max(1) as f64,
+        })
+        .collect()
+}
+
+/// Per cell, how far off the critical path it is relative to `falloff`,
+/// or `None` without a spawn on floor.
+fn detour(grid: &Grid<Tile>, semantic: &SemanticLayers, falloff: f64) -> Option<Vec<f64>> {
+    let spawn = semantic.spawn()?;
+    let start = (spawn.x as usize, spawn.y as usize);
+    if !grid
+        .get(start.0 as i32, start.1 as i32)
+        .is_some_and(|t| t.is_floor())
+    {
+        return None;
+    }
+    let constraints = PathfindingConstraints::cardinal();
+    let from_spawn = dijkstra_map(grid, &[start], &constraints);
+    let (w, h) = (grid.width(), grid.height());
+    let end = path_end(grid, semantic, &from_spawn)?;
+    let from_end = dijkstra_map(grid, &[end], &constraints);
+    let length = from_spawn.get(end.0, end.1);
+
+    // Going out to a cell and back adds about twice its distance from the
+    // path.
+    let scale = 2.0 * falloff.max(f64::EPSILON);
+    Some(
+        (0..w * h)
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                let extra = (from_spawn.get(x, y) + from_end.get(x, y) - length) as f64;
+                if extra.is_finite() {
+                    (extra / scale).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                }
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,444 @@
+//! Incremental regeneration of dirty regions.
+//!
+//! Editors mark edited cells on a [`DirtyTracker`], then call
+//! [`regenerate_region`] for each dirty rectangle. The pipeline runs on a
+//! patch covering the rectangle plus a blend margin; the rectangle is copied
+//! from the patch and the margin is dithered between patch and existing map,
+//! favouring the patch near the rectangle. Cells beyond the margin are never
+//! touched. [`SemanticExtractor::update`](crate::SemanticExtractor::update)
+//! then refreshes semantics for the returned area.
+//!
+//! To re-roll one bad room, [`regenerate_semantic_region`] does all of this
+//! for a semantic region's own cells, reconnects the result to the
+//! surrounding floor, and patches the semantic layers.
+//!
+//! ```rust
+//! use terrain_forge::incremental::{self, DirtyTracker};
+//! use terrain_forge::pipeline::Pipeline;
+//! use terrain_forge::{Grid, Rng, SemanticExtractor};
+//!
+//! let mut pipeline = Pipeline::new();
+//! pipeline.add_algorithm("cellular", None, None);
+//!
+//! let mut grid = Grid::new(120, 80);
+//! pipeline.execute_seed(&mut grid, 1).unwrap();
+//! let extractor = SemanticExtractor::for_caves();
+//! let mut layers = extractor.extract(&grid, &mut Rng::new(1));
+//!
+//! let mut dirty = DirtyTracker::new();
+//! dirty.mark_rect(incremental::DirtyRect::new(40, 30, 16, 12));
+//! for rect in dirty.drain() {
+//!     let update = incremental::regenerate_region(&pipeline, &mut grid, rect, 4, 2).unwrap();
+//!     extractor.update(&mut layers, &grid, update.rect, &mut Rng::new(2));
+//! }
+//! ```
+
+use crate::ops::OpError;
+use crate::pipeline::{Pipeline, PipelineContext};
+use crate::rng;
+use crate::semantic::SemanticLayers;
+use crate::{Algorithm, Grid, Rng, SemanticExtractor, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Axis-aligned cell rectangle; `x`/`y` is the top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DirtyRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl DirtyRect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Single-cell rectangle.
+    pub fn cell(x: usize, y: usize) -> Self {
+        Self::new(x, y, 1, 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    /// Smallest rectangle covering both.
+    pub fn union(&self, other: &DirtyRect) -> DirtyRect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        DirtyRect::new(x, y, right - x, bottom - y)
+    }
+
+    /// True when the rectangles overlap or share an edge.
+    pub fn touches(&self, other: &DirtyRect) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    /// Grows the rectangle by `margin` on every side.
+    pub fn expanded(&self, margin: usize) -> DirtyRect {
+        let x = self.x.saturating_sub(margin);
+        let y = self.y.saturating_sub(margin);
+        DirtyRect::new(
+            x,
+            y,
+            self.x + self.width + margin - x,
+            self.y + self.height + margin - y,
+        )
+    }
+
+    /// Clips the rectangle to a `width` x `height` grid.
+    pub fn clamped(&self, width: usize, height: usize) -> DirtyRect {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        DirtyRect::new(
+            x,
+            y,
+            (self.x + self.width).min(width) - x,
+            (self.y + self.height).min(height) - y,
+        )
+    }
+
+    /// Chebyshev distance from a cell to the rectangle (0 inside).
+    fn distance(&self, x: usize, y: usize) -> usize {
+        let dx = if x < self.x {
+            self.x - x
+        } else {
+            x.saturating_sub(self.x + self.width - 1)
+        };
+        let dy = if y < self.y {
+            self.y - y
+        } else {
+            y.saturating_sub(self.y + self.height - 1)
+        };
+        dx.max(dy)
+    }
+}
+
+/// Collects edited cells into merged dirty rectangles.
+#[derive(Debug, Clone, Default)]
+pub struct DirtyTracker {
+    rects: Vec<DirtyRect>,
+    chunk_size: Option<usize>,
+}
+
+impl DirtyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snaps marks to `chunk_size` x `chunk_size` chunks.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = (chunk_size > 0).then_some(chunk_size);
+        self
+    }
+
+    pub fn mark(&mut self, x: usize, y: usize) {
+        self.mark_rect(DirtyRect::cell(x, y));
+    }
+
+    /// Adds a rectangle, merging it with any rectangle it touches.
+    pub fn mark_rect(&mut self, rect: DirtyRect) {
+        if rect.is_empty() {
+            return;
+        }
+        let mut rect = match self.chunk_size {
+            Some(size) => {
+                let x = rect.x / size * size;
+                let y = rect.y / size * size;
+                let right = (rect.x + rect.width).div_ceil(size) * size;
+                let bottom = (rect.y + rect.height).div_ceil(size) * size;
+                DirtyRect::new(x, y, right - x, bottom - y)
+            }
+            None => rect,
+        };
+        while let Some(i) = self.rects.iter().position(|r| r.touches(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(i));
+        }
+        self.rects.push(rect);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn rects(&self) -> &[DirtyRect] {
+        &self.rects
+    }
+
+    /// Bounding rectangle of everything marked.
+    pub fn bounds(&self) -> Option<DirtyRect> {
+        self.rects.iter().copied().reduce(|a, b| a.union(&b))
+    }
+
+    /// Returns the dirty rectangles (top-to-bottom, left-to-right) and clears the tracker.
+    pub fn drain(&mut self) -> Vec<DirtyRect> {
+        let mut rects = std::mem::take(&mut self.rects);
+        rects.sort_by_key(|r| (r.y, r.x));
+        rects
+    }
+}
+
+/// Result of a region regeneration.
+#[derive(Debug, Clone)]
+pub struct RegionUpdate {
+    /// Every cell that may have changed (dirty rectangle plus margin, clipped to the grid).
+    pub rect: DirtyRect,
+    /// Context from running the pipeline on the patch.
+    pub context: PipelineContext,
+}
+
+/// Re-runs `pipeline` for `rect` and blends it into `grid` over `margin` cells.
+///
+/// Steps see a patch-sized grid, so saved grids and image steps are patch
+/// sized too. The same `seed`, rectangle, and margin always give the same result.
+pub fn regenerate_region(
+    pipeline: &Pipeline,
+    grid: &mut Grid<Tile>,
+    rect: DirtyRect,
+    margin: usize,
+    seed: u64,
+) -> Result<RegionUpdate, OpError> {
+    let mut context = PipelineContext::new();
+    let rect = regenerate_region_with(grid, rect, margin, seed, |patch| {
+        pipeline.execute(patch, &mut context, &mut Rng::new(seed))
+    })?;
+    Ok(RegionUpdate { rect, context })
+}
+
+/// Like [`regenerate_region`], with a caller-supplied patch generator.
+///
+/// `generate` receives a blank patch covering the rectangle plus margin and a
+/// one-cell apron, so generator border walls never land in the map. Returns
+/// the area written.
+pub fn regenerate_region_with<F>(
+    grid: &mut Grid<Tile>,
+    rect: DirtyRect,
+    margin: usize,
+    seed: u64,
+    generate: F,
+) -> Result<DirtyRect, OpError>
+where
+    F: FnOnce(&mut Grid<Tile>) -> Result<(), OpError>,
+{
+    let (w, h) = (grid.width(), grid.height());
+    let rect = rect.clamped(w, h);
+    if rect.is_empty() {
+        return Ok(rect);
+    }
+    let area = rect.expanded(margin).clamped(w, h);
+
+    let mut patch = Grid::new(area.width + 2, area.height + 2);
+    generate(&mut patch)?;
+
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            let distance = rect.distance(x, y);
+            let take_patch = distance == 0
+                || rng::cell_unit(seed, x, y) * (margin + 1) as f64 >= distance as f64;
+            if take_patch {
+                let cell = patch[(x - area.x + 1, y - area.y + 1)];
+                grid.set(x as i32, y as i32, cell);
+            }
+        }
+    }
+    Ok(area)
+}
+
+/// Result of [`regenerate_semantic_region`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionReroll {
+    /// Bounds of every cell that may have changed.
+    pub rect: DirtyRect,
+    /// Wall cells carved to reconnect the new floor to its surroundings.
+    pub tunnels: Vec<(usize, usize)>,
+}
+
+/// Re-rolls one semantic region: runs `algorithm` over the region's cells,
+/// blends it over a `margin`-cell border, reconnects the result to the
+/// floor around it, and patches `layers` with
+/// [`SemanticExtractor::update`].
+///
+/// Cells of the region take the new tiles; border cells are dithered
+/// between new and old, favouring the new tiles near the region. Floor
+/// that ends up cut off inside the rewritten area, or outside floor that
+/// the region used to join together, is reconnected with the shortest
+/// tunnels through the rewritten area. Cells farther out are never
+/// touched. Region ids in `layers` are renumbered by the update.
+///
+/// Fails if `layers` has no region `region_id`.
+pub fn regenerate_semantic_region(
+    grid: &mut Grid<Tile>,
+    layers: &mut SemanticLayers,
+    extractor: &SemanticExtractor,
+    region_id: u32,
+    algorithm: &dyn Algorithm,
+    margin: usize,
+    seed: u64,
+) -> Result<RegionReroll, OpError> {
+    let (w, h) = (grid.width(), grid.height());
+    let region = layers
+        .regions
+        .iter()
+        .find(|r| r.id == region_id)
+        .ok_or_else(|| OpError::InvalidParam {
+            op: "regenerate_semantic_region".to_string(),
+            name: "region_id".to_string(),
+            message: format!("no region {}", region_id),
+        })?;
+
+    // Chebyshev distance from the region, up to `margin`.
+    let mut distance = vec![usize::MAX; w * h];
+    let mut queue = VecDeque::new();
+    for &(x, y) in &region.cells {
+        let (x, y) = (x as usize, y as usize);
+        if x < w && y < h && distance[y * w + x] == usize::MAX {
+            distance[y * w + x] = 0;
+            queue.push_back((x, y));
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let d = distance[y * w + x];
+        if d == margin {
+            continue;
+        }
+        for (nx, ny) in grid.neighbors_8(x, y) {
+            if distance[ny * w + nx] == usize::MAX {
+                distance[ny * w + nx] = d + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    let area: Vec<(usize, usize)> = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .filter(|&(x, y)| distance[y * w + x] != usize::MAX)
+        .collect();
+    let Some(rect) = area
+        .iter()
+        .map(|&(x, y)| DirtyRect::cell(x, y))
+        .reduce(|a, b| a.union(&b))
+    else {
+        return Ok(RegionReroll {
+            rect: DirtyRect::new(0, 0, 0, 0),
+            tunnels: Vec::new(),
+        });
+    };
+
+    let before = grid.clone();
+    let mut patch = Grid::new(rect.width + 2, rect.height + 2);
+    algorithm.generate(&mut patch, seed);
+    for &(x, y) in &area {
+        let d = distance[y * w + x];
+        if d == 0 || rng::cell_unit(seed, x, y) * (margin + 1) as f64 >= d as f64 {
+            grid.set(x as i32, y as i32, patch[(x - rect.x + 1, y - rect.y + 1)]);
+        }
+    }
+
+    let in_area = |x: usize, y: usize| {
+        distance[y * w + x] != usize::MAX && x > 0 && y > 0 && x + 1 < w && y + 1 < h
+    };
+    let (old_labels, old_count) = crate::effects::label_regions(&before);
+    let mut tunnels = Vec::new();
+    // Each pass joins one pair; bounded by the number of components.
+    for _ in 0..area.len() {
+        let (labels, count) = crate::effects::label_regions(grid);
+        let mut outside_of = vec![false; count as usize + 1];
+        let mut old_owner: Vec<Option<u32>> = vec![None; old_count as usize + 1];
+        let mut split = None;
+        for y in 0..h {
+            for x in 0..w {
+                let (i, label) = (y * w + x, labels[y * w + x]);
+                if label == 0 || distance[i] != usize::MAX {
+                    continue;
+                }
+                outside_of[label as usize] = true;
+                // Outside floor that was one component must stay one.
+                let old = old_labels[i] as usize;
+                match old_owner[old] {
+                    Some(other) if other != label => split = split.or(Some((label, other))),
+                    Some(_) => {}
+                    None => old_owner[old] = Some(label),
+                }
+            }
+        }
+        let island = (1..=count).find(|&l| !outside_of[l as usize]);
+        let (from, to) = match (island, split) {
+            (Some(island), _) => (island, None),
+            (None, Some((a, b))) => (a, Some(b)),
+            (None, None) => break,
+        };
+        let Some(path) = tunnel(grid, &labels, from, to, &in_area) else {
+            break;
+        };
+        for &(x, y) in &path {
+            grid.set(x as i32, y as i32, Tile::Floor);
+        }
+        tunnels.extend(path);
+    }
+
+    extractor.update(layers, grid, rect, &mut Rng::new(seed));
+    Ok(RegionReroll { rect, tunnels })
+}
+
+/// Shortest run of non-`from` cells inside the area leading from component
+/// `from` to component `to` (any other component when `None`).
+fn tunnel(
+    grid: &Grid<Tile>,
+    labels: &[u32],
+    from: u32,
+    to: Option<u32>,
+    in_area: &dyn Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    let (w, h) = (grid.width(), grid.height());
+    let is_goal = |label: u32| label != 0 && label != from && to.is_none_or(|t| t == label);
+    let mut parent = vec![usize::MAX; w * h];
+    let mut queue: VecDeque<usize> = (0..w * h).filter(|&i| labels[i] == from).collect();
+    for &i in &queue {
+        parent[i] = i;
+    }
+    while let Some(i) = queue.pop_front() {
+        for (nx, ny) in grid.neighbors_4(i % w, i / w) {
+            let n = ny * w + nx;
+            if parent[n] != usize::MAX {
+                continue;
+            }
+            if is_goal(labels[n]) {
+                let mut path = Vec::new();
+                let mut c = i;
+                while labels[c] != from {
+                    path.push((c % w, c / w));
+                    c = parent[c];
+                }
+                return Some(path);
+            }
+            if in_area(nx, ny) {
+                parent[n] = i;
+                queue.push_back(n);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,324 @@
+//! Kingdom borders grown over an overworld.
+//!
+//! [`generate`] spreads seed points over the land of a map and grows one
+//! territory from each at once, Dijkstra-style: every cell goes to the
+//! kingdom that reaches it most cheaply. Step costs are perturbed by noise,
+//! so borders wander instead of following the straight bisectors of a
+//! Voronoi diagram, and can be raised by climbing, so mountain ridges tend
+//! to become borders.
+//!
+//! The result is a [`Grid<u8>`] label map (0 for unclaimed cells, kingdom
+//! `i` as `i + 1`) plus each shared border as polylines along cell edges,
+//! ready to draw on a political map.
+//!
+//! ```rust
+//! use terrain_forge::kingdoms::{self, KingdomConfig};
+//! use terrain_forge::{Grid, Tile};
+//!
+//! let mut land = Grid::new(80, 60);
+//! land.fill_rect(1, 1, 78, 58, Tile::Floor);
+//! let map = kingdoms::generate(&land, None, &KingdomConfig::default().with_count(4), 3);
+//! assert_eq!(map.seeds.len(), 4);
+//! assert_eq!(map.label(40, 30).map(|l| l > 0), Some(true));
+//! assert!(!map.borders.is_empty());
+//! ```
+
+use crate::noise::{NoiseSource, Perlin};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+const NEIGHBORS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Configuration for [`generate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KingdomConfig {
+    /// Number of kingdoms, at most 255. Default: 6.
+    pub count: usize,
+    /// Random candidates per seed; the one farthest from earlier seeds
+    /// wins, so more candidates spread kingdoms more evenly. Default: 16.
+    pub candidates: usize,
+    /// Frequency of the cost noise; lower values give broader wiggles.
+    /// Default: 0.08.
+    pub noise_scale: f64,
+    /// How much noise can raise a step's cost: each step costs between 1
+    /// and `1 + noise_strength`. Default: 2.0.
+    pub noise_strength: f64,
+    /// Extra cost per unit of height difference when a heightmap is given.
+    /// Default: 20.0.
+    pub height_cost: f64,
+}
+
+impl Default for KingdomConfig {
+    fn default() -> Self {
+        Self {
+            count: 6,
+            candidates: 16,
+            noise_scale: 0.08,
+            noise_strength: 2.0,
+            height_cost: 20.0,
+        }
+    }
+}
+
+impl KingdomConfig {
+    /// Sets `count`.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Sets `noise_scale` and `noise_strength`.
+    pub fn with_noise(mut self, scale: f64, strength: f64) -> Self {
+        self.noise_scale = scale;
+        self.noise_strength = strength;
+        self
+    }
+
+    /// Sets `height_cost`.
+    pub fn with_height_cost(mut self, height_cost: f64) -> Self {
+        self.height_cost = height_cost;
+        self
+    }
+}
+
+/// The frontier between two kingdoms.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Border {
+    /// Labels of the two kingdoms, lower first.
+    pub kingdoms: (u8, u8),
+    /// Polylines through cell corners (`(x, y)` is the top-left corner of
+    /// cell `(x, y)`). A closed loop repeats its first point at the end.
+    pub lines: Vec<Vec<(usize, usize)>>,
+}
+
+impl Border {
+    /// Total length in cell edges.
+    pub fn length(&self) -> usize {
+        self.lines
+            .iter()
+            .flat_map(|line| line.windows(2))
+            .map(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1))
+            .sum()
+    }
+}
+
+/// Territory labels and borders from [`generate`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Kingdoms {
+    /// Kingdom `i` is labeled `i + 1`; 0 marks cells no kingdom claimed.
+    pub labels: Grid<u8>,
+    /// Seed cell of each kingdom, by index.
+    pub seeds: Vec<(usize, usize)>,
+    /// One entry per pair of neighboring kingdoms, ordered by labels.
+    pub borders: Vec<Border>,
+}
+
+impl Kingdoms {
+    /// Label at `(x, y)`, or `None` outside the map.
+    pub fn label(&self, x: usize, y: usize) -> Option<u8> {
+        self.labels.get(x as i32, y as i32).copied()
+    }
+
+    /// Cells held by each kingdom, by index.
+    pub fn areas(&self) -> Vec<usize> {
+        let mut areas = vec![0; self.seeds.len()];
+        for (_, _, &label) in self.labels.iter() {
+            if label > 0 {
+                areas[label as usize - 1] += 1;
+            }
+        }
+        areas
+    }
+
+    /// Border between kingdoms labeled `a` and `b`, in either order.
+    pub fn border(&self, a: u8, b: u8) -> Option<&Border> {
+        let key = (a.min(b), a.max(b));
+        self.borders.iter().find(|border| border.kingdoms == key)
+    }
+}
+
+/// Grows `config.count` kingdoms over the floor of `land`.
+///
+/// `heights`, indexed `[y][x]`, makes climbing cost
+/// `height_cost * |Δh|` per step. Floor cells no seed can reach stay 0.
+pub fn generate(
+    land: &Grid<Tile>,
+    heights: Option<&[Vec<f64>]>,
+    config: &KingdomConfig,
+    seed: u64,
+) -> Kingdoms {
+    let (w, h) = (land.width(), land.height());
+    let mut rng = Rng::new(seed);
+    let mut labels: Grid<u8> = Grid::new(w, h);
+    let floor: Vec<(usize, usize)> = land
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .map(|(x, y, _)| (x, y))
+        .collect();
+
+    let mut seeds: Vec<(usize, usize)> = Vec::new();
+    for _ in 0..config.count.min(u8::MAX as usize) {
+        let candidates = (0..config.candidates.max(1)).filter_map(|_| rng.pick(&floor).copied());
+        let best = candidates
+            .filter(|c| !seeds.contains(c))
+            .max_by_key(|&(x, y)| {
+                seeds
+                    .iter()
+                    .map(|&(sx, sy)| sx.abs_diff(x).pow(2) + sy.abs_diff(y).pow(2))
+                    .min()
+                    .unwrap_or(0)
+            });
+        match best {
+            Some(cell) => seeds.push(cell),
+            None => break,
+        }
+    }
+
+    let noise = Perlin::new(rng.next_u64()).with_frequency(config.noise_scale);
+    let height = |x: usize, y: usize| {
+        heights
+            .and_then(|hs| hs.get(y))
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(0.0)
+    };
+    let step_cost = |from: (usize, usize), to: (usize, usize)| {
+        let n = (noise.sample(to.0 as f64, to.1 as f64) + 1.0) / 2.0;
+        let climb = (height(to.0, to.1) - height(from.0, from.1)).abs();
+        1.0 + config.noise_strength.max(0.0) * n.clamp(0.0, 1.0)
+            + config.height_cost.max(0.0) * climb
+    };
+
+    // Costs are kept as fixed-point integers so the heap orders them
+    // exactly and ties break on the cell, keeping growth deterministic.
+    let mut best = vec![u64::MAX; w * h];
+    let mut heap = BinaryHeap::new();
+    for (i, &(x, y)) in seeds.iter().enumerate() {
+        best[y * w + x] = 0;
+        labels[(x, y)] = i as u8 + 1;
+        heap.push(Reverse((0u64, y, x, i as u8 + 1)));
+    }
+    while let Some(Reverse((cost, y, x, label))) = heap.pop() {
+        if cost > best[y * w + x] || labels[(x, y)] != label {
+            continue;
+        }
+        for (dx, dy) in NEIGHBORS {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if !land.get(nx, ny).is_some_and(|t| t.is_floor()) {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            let next = cost + (step_cost((x, y), (nx, ny)) * 1024.0) as u64;
+            if next < best[ny * w + nx] {
+                best[ny * w + nx] = next;
+                labels[(nx, ny)] = label;
+                heap.push(Reverse((next, ny, nx, label)));
+            }
+        }
+    }
+
+    let borders = trace_borders(&labels);
+    Kingdoms {
+        labels,
+        seeds,
+        borders,
+    }
+}
+
+type Corner = (usize, usize);
+
+/// Chains the cell edges between differently labeled kingdoms into
+/// polylines, one [`Border`] per pair.
+fn trace_borders(labels: &Grid<u8>) -> Vec<Border> {
+    let (w, h) = (labels.width(), labels.height());
+    let mut edges: BTreeMap<(u8, u8), Vec<(Corner, Corner)>> = BTreeMap::new();
+    for y in 0..h {
+        for x in 0..w {
+            let a = labels[(x, y)];
+            if x + 1 < w {
+                let b = labels[(x + 1, y)];
+                if a != b && a > 0 && b > 0 {
+                    edges
+                        .entry((a.min(b), a.max(b)))
+                        .or_default()
+                        .push(((x + 1, y), (x + 1, y + 1)));
+                }
+            }
+            if y + 1 < h {
+                let b = labels[(x, y + 1)];
+                if a != b && a > 0 && b > 0 {
+                    edges
+                        .entry((a.min(b), a.max(b)))
+                        .or_default()
+                        .push(((x, y + 1), (x + 1, y + 1)));
+                }
+            }
+        }
+    }
+
+    edges
+        .into_iter()
+        .map(|(kingdoms, segments)| Border {
+            kingdoms,
+            lines: chain(&segments),
+        })
+        .collect()
+}
+
+/// Joins unit segments into maximal polylines, starting from endpoints and
+/// junctions so open lines aren't split, and drops points on straight runs.
+fn chain(segments: &[(Corner, Corner)]) -> Vec<Vec<Corner>> {
+    let mut adjacency: BTreeMap<Corner, Vec<usize>> = BTreeMap::new();
+    for (i, &(a, b)) in segments.iter().enumerate() {
+        adjacency.entry(a).or_default().push(i);
+        adjacency.entry(b).or_default().push(i);
+    }
+    let mut used = vec![false; segments.len()];
+    let mut lines = Vec::new();
+    let starts: Vec<Corner> = adjacency
+        .iter()
+        .filter(|(_, edges)| edges.len() != 2)
+        .map(|(&corner, _)| corner)
+        .chain(segments.iter().map(|&(a, _)| a))
+        .collect();
+    for start in starts {
+        while let Some(&first) = adjacency[&start].iter().find(|&&i| !used[i]) {
+            let mut line = vec![start];
+            let mut at = start;
+            let mut next = Some(first);
+            while let Some(i) = next {
+                used[i] = true;
+                let (a, b) = segments[i];
+                at = if a == at { b } else { a };
+                line.push(at);
+                // Continue only through plain path corners.
+                next = match adjacency[&at].as_slice() {
+                    [p, q] => [*p, *q].into_iter().find(|&j| !used[j]),
+                    _ => None,
+                };
+            }
+            lines.push(simplify(line));
+        }
+    }
+    lines
+}
+
+fn simplify(line: Vec<Corner>) -> Vec<Corner> {
+    let mut out: Vec<Corner> = Vec::with_capacity(line.len());
+    for p in line {
+        if let [.., a, b] = out[..] {
+            let (d1, d2) = (
+                (b.0 as i64 - a.0 as i64, b.1 as i64 - a.1 as i64),
+                (p.0 as i64 - b.0 as i64, p.1 as i64 - b.1 as i64),
+            );
+            if d1.0 * d2.1 == d1.1 * d2.0 {
+                out.pop();
+            }
+        }
+        out.push(p);
+    }
+    out
+}
@@ -24,6 +24,7 @@
 //!     min_room_size: 6,
 //!     max_depth: 5,
 //!     room_padding: 1,
+//!     ..Default::default()
 //! });
 //! bsp.generate(&mut grid, 12345);
 //! ```
@@ -49,7 +50,7 @@
 //!
 //! ## Algorithms
 //!
-//! 15 generation algorithms available via [`algorithms::get`]:
+//! 16 generation algorithms available via [`algorithms::get`]:
 //! - `bsp` - Binary Space Partitioning for structured rooms
 //! - `cellular` - Cellular automata for organic caves
 //! - `drunkard` - Random walk for winding corridors
@@ -65,6 +66,7 @@
 //! - `agent` - Multi-agent carving
 //! - `glass_seam` - Region connector
 //! - `room_accretion` - Brogue-style organic dungeons
+//! - `rooms_on_mask` - Rooms placed only inside a mask
 //!
 //! ## Composition
 //!
@@ -91,6 +93,7 @@
 
 mod algorithm;
 mod grid;
+mod parallel;
 mod rng;
 mod semantic_extractor;
 mod semantic_visualization;
@@ -99,20 +102,79 @@ mod semantic_visualization;
 mod semantic_tests;
 
 pub mod algorithms;
+pub mod ambience;
 pub mod analysis;
+pub mod arena;
+pub mod bands;
+pub mod bench;
+#[cfg(feature = "bevy")]
+pub mod bevy;
+#[cfg(any(feature = "bincode", feature = "msgpack"))]
+pub mod binary;
+pub mod bundle;
+pub mod canyons;
+pub mod climate;
 pub mod compose;
+pub mod config;
 pub mod constraints;
+pub mod decoration;
+pub mod dither;
+pub mod dual;
+pub mod ecology;
 pub mod effects;
+pub mod entities;
+pub mod entrances;
+pub mod error;
+pub mod exits;
+pub mod export;
+pub mod factions;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod hiding;
+pub mod incremental;
+pub mod kingdoms;
+#[cfg(feature = "tracing")]
+mod logging;
+pub mod manifest;
 pub mod noise;
 pub mod ops;
+pub mod pacing;
 pub mod pipeline;
+#[cfg(feature = "profile")]
+pub mod profile;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod quests;
+pub mod recipes;
+pub mod render;
+#[cfg(feature = "rng-trace")]
+pub mod rng_trace;
+pub mod search;
 pub mod semantic;
+pub mod settlements;
 pub mod spatial;
+pub mod spawns;
+pub mod stream;
+pub mod terrain_dungeon;
+pub mod testing;
+pub mod theme;
+pub mod tileset;
+pub mod traps;
+pub mod validate;
+pub mod vegetation;
+pub mod veins;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod world;
 
-pub use algorithm::Algorithm;
+pub use algorithm::{Algorithm, CellAdapter, Masked};
+pub use config::MapConfig;
+pub use error::Error;
 pub use grid::{line_points, Cell, Grid, Tile};
 pub use ops::{CombineMode, Params};
-pub use rng::Rng;
+pub use rng::{Rng, Seed};
 pub use semantic::{ConnectivityGraph, Marker, Masks, Region, SemanticConfig, SemanticLayers};
 pub use semantic_extractor::{extract_semantics, extract_semantics_default, SemanticExtractor};
 pub use semantic_visualization::{
@@ -136,7 +198,9 @@ pub use semantic_visualization::{
 ///
 /// # Returns
 /// * `Ok((grid, semantic))` - Successfully generated map meeting requirements
-/// * `Err(String)` - Failed to meet requirements after max attempts
+/// * `Err(Error::UnknownAlgorithm)` - No algorithm named `algorithm_name`
+/// * `Err(Error::ConstraintUnsatisfied)` - Failed to meet requirements after
+///   max attempts; carries the last attempt's constraint report
 ///
 /// # Example
 /// ```rust
@@ -155,39 +219,121 @@ pub fn generate_with_requirements(
     requirements: semantic::SemanticRequirements,
     max_attempts: Option<usize>,
     base_seed: u64,
-) -> Result<(Grid<Tile>, semantic::SemanticLayers), String> {
+) -> error::Result<(Grid<Tile>, semantic::SemanticLayers)> {
     let max_attempts = max_attempts.unwrap_or(10);
+    let algo = algorithms::get(algorithm_name)
+        .ok_or_else(|| Error::UnknownAlgorithm(algorithm_name.to_string()))?;
+    let extractor = match algorithm_name {
+        "cellular" => Some(SemanticExtractor::for_caves()),
+        "bsp" | "rooms" | "room_accretion" | "rooms_on_mask" => {
+            Some(SemanticExtractor::for_rooms())
+        }
+        "maze" => Some(SemanticExtractor::for_mazes()),
+        _ => None,
+    };
 
-    for attempt in 0..max_attempts {
+    // Attempts may run in parallel; the lowest passing attempt wins either way.
+    let attempt = |attempt: usize| {
         let seed = base_seed.wrapping_add(attempt as u64);
         let mut grid = Grid::new(width, height);
-        let mut rng = Rng::new(seed);
+        algo.generate(&mut grid, seed);
+        let semantic = match &extractor {
+            Some(extractor) => extractor.extract(&grid, &mut Rng::new(seed)),
+            None => SemanticExtractor::auto(&grid).extract(&grid, &mut Rng::new(seed)),
+        };
+        (grid, semantic)
+    };
+    let found = parallel::find_map_first(max_attempts, |i| {
+        let (grid, semantic) = attempt(i);
+        requirements.validate(&semantic).then_some((grid, semantic))
+    });
+    if let Some(result) = found {
+        return Ok(result);
+    }
 
-        // Generate using specified algorithm
-        if let Some(algo) = algorithms::get(algorithm_name) {
-            algo.generate(&mut grid, seed);
-        } else {
-            return Err(format!("Unknown algorithm: {}", algorithm_name));
+    let mut constraints = constraints::ConstraintSet::new();
+    constraints.push(constraints::SemanticRequirementsConstraint::new(
+        requirements,
+    ));
+    let report = match max_attempts.checked_sub(1) {
+        Some(last) => {
+            let (grid, semantic) = attempt(last);
+            let mut ctx = constraints::ConstraintContext::new(&grid);
+            ctx.semantic = Some(&semantic);
+            constraints.evaluate(&ctx)
         }
+        None => constraints::ConstraintReport {
+            passed: false,
+            results: Vec::new(),
+        },
+    };
+    Err(Error::ConstraintUnsatisfied {
+        attempts: max_attempts,
+        report,
+    })
+}
 
-        // Extract semantic layers
-        let extractor = match algorithm_name {
-            "cellular" => SemanticExtractor::for_caves(),
-            "bsp" | "rooms" | "room_accretion" => SemanticExtractor::for_rooms(),
-            "maze" => SemanticExtractor::for_mazes(),
-            _ => SemanticExtractor::default(),
-        };
-
-        let semantic = extractor.extract(&grid, &mut rng);
+/// Runs the whole flow a [`MapConfig`] describes and bundles the result.
+///
+/// Each attempt runs the pipeline and effects, extracts semantic layers
+/// with the config's profile, adds its marker overrides, and maps its
+/// entities, as [`MapBundle::generate`](bundle::MapBundle::generate) does;
+/// the attempt must then pass the `validate` thresholds and
+/// `requirements` ([`Config::constraints`](config::Config::constraints)).
+/// The first attempt uses `config.seed` (or 0) and each retry adds the
+/// retry policy's `seed_step`, up to [`Config::attempts`](config::Config::attempts)
+/// attempts. The bundle records the passing attempt's seed, so it
+/// [`reproduces`](bundle::MapBundle::reproduces), and the attempt count as
+/// `attempts` metadata.
+///
+/// # Errors
+/// * Any error building the pipeline or running an op
+/// * [`Error::ConstraintUnsatisfied`] when no attempt passes, with the last
+///   attempt's report
+///
+/// # Example
+/// ```rust
+/// use terrain_forge::{generate_map, MapConfig};
+///
+/// let config = MapConfig::from_json(r#"{
+///     "width": 60,
+///     "height": 40,
+///     "seed": 7,
+///     "pipeline": ["bsp"],
+///     "validate": { "connectivity": 0.5 },
+///     "requirements": { "required_markers": { "PlayerStart": 1 } },
+///     "retry": { "max_attempts": 20 }
+/// }"#).unwrap();
+/// let bundle = generate_map(&config).unwrap();
+/// assert!(bundle.semantic.is_some());
+/// assert!(bundle.reproduces().unwrap());
+/// ```
+pub fn generate_map(config: &MapConfig) -> error::Result<bundle::MapBundle> {
+    config.build_pipeline()?;
+    let constraints = config.constraints();
+    let attempts = config.attempts();
+    let step = config.retry.as_ref().map_or(1, |retry| retry.seed_step);
+    let base_seed = config.seed.unwrap_or(0);
 
-        // Validate requirements
-        if requirements.validate(&semantic) {
-            return Ok((grid, semantic));
-        }
+    // Attempts may run in parallel; the lowest passing attempt (or the
+    // first error) wins either way.
+    let attempt = |attempt: usize| -> error::Result<_> {
+        let seed = base_seed.wrapping_add(step.wrapping_mul(attempt as u64));
+        let bundle = bundle::MapBundle::generate(config, seed)?;
+        let mut ctx = constraints::ConstraintContext::new(&bundle.grid);
+        ctx.semantic = bundle.semantic.as_ref();
+        let report = constraints.evaluate(&ctx);
+        Ok((bundle, report))
+    };
+    let found = parallel::find_map_first(attempts, |i| match attempt(i) {
+        Ok((bundle, report)) => report.passed.then_some(Ok((i, bundle))),
+        Err(err) => Some(Err(err)),
+    });
+    if let Some(result) = found {
+        let (i, bundle) = result?;
+        return Ok(bundle.with_metadata("attempts", i + 1));
     }
 
-    Err(format!(
-        "Failed to generate map meeting requirements after {} attempts",
-        max_attempts
-    ))
+    let (_, report) = attempt(attempts - 1)?;
+    Err(Error::ConstraintUnsatisfied { attempts, report })
 }
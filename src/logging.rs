@@ -0,0 +1,40 @@
+//! `tracing` instrumentation (feature `tracing`).
+//!
+//! Algorithms, effects, and pipeline steps run inside `debug` spans
+//! carrying their name, seed, and parameters; each span ends with a
+//! `finished` event giving its duration in microseconds. Install any
+//! `tracing` subscriber to collect them.
+
+use crate::ops::Params;
+use std::time::Instant;
+use tracing::span::EnteredSpan;
+
+/// Enters `span` and emits a `finished` event with the elapsed time when
+/// dropped.
+pub(crate) struct Timed {
+    _span: EnteredSpan,
+    start: Instant,
+}
+
+impl Timed {
+    pub(crate) fn new(span: tracing::Span) -> Self {
+        Self {
+            _span: span.entered(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timed {
+    fn drop(&mut self) {
+        let micros = self.start.elapsed().as_micros() as u64;
+        tracing::debug!(micros, "finished");
+    }
+}
+
+/// Parameters as compact JSON, `{}` when there are none.
+pub(crate) fn params(params: Option<&Params>) -> String {
+    params
+        .and_then(|p| serde_json::to_string(p).ok())
+        .unwrap_or_else(|| "{}".to_string())
+}
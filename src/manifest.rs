@@ -0,0 +1,201 @@
+//! Machine-readable provenance for generated maps.
+//!
+//! A [`Manifest`] lists every step a [`Pipeline`](crate::pipeline::Pipeline)
+//! or [`Config`](crate::config::Config) ran, in order: the params it
+//! actually ran with (schema defaults and presets resolved), the seed each
+//! seeded step used, how long it took, and the constraint results checked
+//! afterwards. Pipelines record into
+//! [`PipelineContext::manifest`](crate::pipeline::PipelineContext::manifest);
+//! [`Config::generate_with_manifest`](crate::config::Config::generate_with_manifest)
+//! returns one alongside the grid.
+//!
+//! ```rust
+//! use terrain_forge::manifest::StepKind;
+//! use terrain_forge::pipeline::Pipeline;
+//! use terrain_forge::Grid;
+//!
+//! let mut pipe = Pipeline::new();
+//! pipe.add_algorithm("cellular", None, None).add_effect("erode", None);
+//! let mut grid = Grid::new(60, 40);
+//! let context = pipe.execute_seed(&mut grid, 9).unwrap();
+//!
+//! let manifest = context.manifest();
+//! assert_eq!(manifest.seed, Some(9));
+//! assert_eq!(manifest.steps[0].kind, StepKind::Algorithm);
+//! assert!(manifest.steps[0].seed.is_some());
+//! assert!(manifest.steps[0].params.contains_key("iterations"));
+//! ```
+
+use crate::constraints::ConstraintReport;
+use crate::ops::{OpError, OpResult, Params};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a [`StepRecord`] ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    Algorithm,
+    Effect,
+    Combine,
+    /// A conditional; its branch's steps follow one level deeper.
+    If,
+    StoreGrid,
+    SetParameter,
+    Log,
+    Image,
+    WfcSample,
+}
+
+/// One executed step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub kind: StepKind,
+    /// Algorithm or effect name, otherwise the step kind.
+    pub name: String,
+    /// Params the step ran with, defaults included. Empty for steps
+    /// without params.
+    #[serde(default, skip_serializing_if = "Params::is_empty")]
+    pub params: Params,
+    /// Seed the step used, for seeded steps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Nesting level: 0 at the top, one more inside each `if` branch.
+    #[serde(default)]
+    pub depth: usize,
+    /// Step-specific note: the branch taken, combine mode, grid key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Wall-clock time, including nested steps. Always 0 on
+    /// `wasm32-unknown-unknown`.
+    pub micros: u64,
+}
+
+impl StepRecord {
+    pub fn new(kind: StepKind, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            params: Params::new(),
+            seed: None,
+            depth: 0,
+            detail: None,
+            micros: 0,
+        }
+    }
+
+    /// Sets `params`.
+    pub fn with_params(mut self, params: Params) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Sets `seed`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets `detail`.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// One constraint checked after generation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConstraintRecord {
+    pub id: String,
+    pub passed: bool,
+    pub score: f32,
+    #[serde(default)]
+    pub details: BTreeMap<String, String>,
+}
+
+/// Provenance of one generated map.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Seed the run started from, if it started from one.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub width: usize,
+    pub height: usize,
+    /// Steps in execution order; nested steps follow their `if`.
+    #[serde(default)]
+    pub steps: Vec<StepRecord>,
+    #[serde(default)]
+    pub constraints: Vec<ConstraintRecord>,
+}
+
+impl Manifest {
+    pub fn new(width: usize, height: usize, seed: Option<u64>) -> Self {
+        Self {
+            seed,
+            width,
+            height,
+            ..Self::default()
+        }
+    }
+
+    /// First step named `name`.
+    pub fn step(&self, name: &str) -> Option<&StepRecord> {
+        self.steps.iter().find(|s| s.name == name)
+    }
+
+    /// Total wall-clock time of the top-level steps.
+    pub fn micros(&self) -> u64 {
+        self.steps
+            .iter()
+            .filter(|s| s.depth == 0)
+            .map(|s| s.micros)
+            .sum()
+    }
+
+    /// Whether every recorded constraint passed; true when none were.
+    pub fn passed(&self) -> bool {
+        self.constraints.iter().all(|c| c.passed)
+    }
+
+    /// Appends the results of a constraint evaluation.
+    pub fn record_constraints(&mut self, report: &ConstraintReport) {
+        self.constraints
+            .extend(report.results.iter().map(|e| ConstraintRecord {
+                id: e.id.clone(),
+                passed: e.result.passed,
+                score: e.result.score,
+                details: e.result.details.clone(),
+            }));
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn from_json(json: &str) -> OpResult<Self> {
+        serde_json::from_str(json).map_err(|e| OpError::parse("manifest", e))
+    }
+}
+
+/// Step timer; `Instant` panics on `wasm32-unknown-unknown`, so there it
+/// always reads 0.
+pub(crate) struct Timer {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    start: std::time::Instant,
+}
+
+impl Timer {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn micros(&self) -> u64 {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        return self.start.elapsed().as_micros() as u64;
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        0
+    }
+}
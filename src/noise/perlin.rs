@@ -32,6 +32,11 @@ impl Perlin {
         self
     }
 
+    #[cfg(feature = "gpu")]
+    pub(crate) fn permutation(&self) -> &[u8; 512] {
+        &self.perm
+    }
+
     fn gradient(hash: u8, x: f64, y: f64) -> f64 {
         let h = hash & 7;
         let u = if h < 4 { x } else { y };
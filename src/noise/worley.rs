@@ -46,7 +46,7 @@ impl NoiseSource for Worley {
                 let cy = yi + dy;
                 let px = cx as f64 + self.hash(cx, cy, 0);
                 let py = cy as f64 + self.hash(cx, cy, 1);
-                let dist = (x - px).powi(2) + (y - py).powi(2);
+                let dist = (x - px) * (x - px) + (y - py) * (y - py);
                 min_dist = min_dist.min(dist);
             }
         }
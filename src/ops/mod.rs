@@ -1,6 +1,8 @@
 //! Unified ops facade: algorithms, effects, and grid combine.
 //!
 //! Use this module for name-based execution with optional JSON params.
+//! Downstream crates can add their own names with [`register_algorithm`] and
+//! [`register_effect`].
 //!
 //! ```rust
 //! use terrain_forge::{Grid, ops};
@@ -22,32 +24,236 @@ use crate::effects;
 use crate::noise;
 use crate::semantic::{marker_positions, MarkerType, SemanticLayers};
 use crate::{Algorithm, Grid, Tile};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock, RwLock};
 
-pub type Params = HashMap<String, serde_json::Value>;
+mod schema;
+pub(crate) mod script;
+
+pub use schema::{OpKind, OpSchema, ParamSchema, ParamType, ALGORITHM_NAMES, EFFECT_NAMES};
+pub use script::{run_script, OpScript, ScriptLogEntry, ScriptOp, ScriptOutput};
+
+pub type Params = BTreeMap<String, serde_json::Value>;
 pub type OpResult<T> = Result<T, OpError>;
 
-#[derive(Debug, Clone)]
-/// Error type for ops operations.
-pub struct OpError {
-    message: String,
+/// Factory that builds an algorithm from optional params (see [`register_algorithm`]).
+pub type AlgorithmFactory =
+    dyn Fn(Option<&Params>) -> OpResult<Box<dyn Algorithm<Tile> + Send + Sync>> + Send + Sync;
+
+/// Handler that applies an effect to a grid (see [`register_effect`]).
+pub type EffectHandler =
+    dyn Fn(&mut Grid<Tile>, Option<&Params>, Option<&SemanticLayers>) -> OpResult<()> + Send + Sync;
+
+#[derive(Default)]
+struct Registry {
+    algorithms: HashMap<String, Arc<AlgorithmFactory>>,
+    effects: HashMap<String, Arc<EffectHandler>>,
+    schemas: HashMap<(OpKind, String), OpSchema>,
+}
+
+fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Registers a named algorithm so [`generate`], [`build_algorithm`], pipelines, and
+/// configs can use it by name.
+///
+/// Registered names are looked up before the built-in algorithms, so registering
+/// a built-in name overrides it. Registering the same name twice replaces the
+/// earlier factory.
+///
+/// # Examples
+///
+/// ```
+/// use terrain_forge::{ops, Grid};
+/// use terrain_forge::algorithms::{Bsp, BspConfig};
+///
+/// ops::register_algorithm("tiny_bsp", |_params| {
+///     Ok(Box::new(Bsp::new(BspConfig {
+///         min_room_size: 3,
+///         max_depth: 3,
+///         room_padding: 1,
+///         ..Default::default()
+///     })))
+/// });
+///
+/// let mut grid = Grid::new(40, 30);
+/// ops::generate("tiny_bsp", &mut grid, Some(7), None).unwrap();
+/// assert!(grid.count(|t| t.is_floor()) > 0);
+/// ```
+pub fn register_algorithm<F>(name: impl Into<String>, factory: F)
+where
+    F: Fn(Option<&Params>) -> OpResult<Box<dyn Algorithm<Tile> + Send + Sync>>
+        + Send
+        + Sync
+        + 'static,
+{
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    registry.algorithms.insert(name.into(), Arc::new(factory));
+}
+
+/// Registers a named effect so [`effect`] and pipelines can apply it by name.
+///
+/// Registered names are looked up before the built-in effects.
+pub fn register_effect<F>(name: impl Into<String>, handler: F)
+where
+    F: Fn(&mut Grid<Tile>, Option<&Params>, Option<&SemanticLayers>) -> OpResult<()>
+        + Send
+        + Sync
+        + 'static,
+{
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    registry.effects.insert(name.into(), Arc::new(handler));
+}
+
+/// Names of user-registered algorithms, sorted.
+pub fn registered_algorithms() -> Vec<String> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    let mut names: Vec<String> = registry.algorithms.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Names of user-registered effects, sorted.
+pub fn registered_effects() -> Vec<String> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    let mut names: Vec<String> = registry.effects.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Registers a parameter schema for a user-registered op, enabling [`describe`]
+/// and param validation for it. Replaces any earlier schema with the same kind and name.
+pub fn register_schema(schema: OpSchema) {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    registry
+        .schemas
+        .insert((schema.kind, schema.name.clone()), schema);
 }
 
-impl OpError {
-    pub fn new(message: impl Into<String>) -> Self {
-        Self {
-            message: message.into(),
+/// Describes the parameters of a named algorithm or effect.
+///
+/// Algorithms are looked up before effects; use [`describe_algorithm`] or
+/// [`describe_effect`] to disambiguate.
+pub fn describe(name: &str) -> Option<OpSchema> {
+    describe_algorithm(name).or_else(|| describe_effect(name))
+}
+
+/// Describes the parameters of a named algorithm (aliases accepted).
+pub fn describe_algorithm(name: &str) -> Option<OpSchema> {
+    let name = name.trim();
+    registered_schema(OpKind::Algorithm, name).or_else(|| {
+        if registered_algorithm(name).is_some() {
+            None
+        } else {
+            schema::builtin_algorithm_schema(name)
         }
+    })
+}
+
+/// Describes the parameters of a named effect.
+pub fn describe_effect(name: &str) -> Option<OpSchema> {
+    let name = name.trim();
+    registered_schema(OpKind::Effect, name).or_else(|| {
+        if registered_effect(name).is_some() {
+            None
+        } else {
+            schema::builtin_effect_schema(name)
+        }
+    })
+}
+
+/// Validates params for a named algorithm against its schema.
+///
+/// Ops without a schema (user-registered ops that did not call
+/// [`register_schema`]) accept any params.
+pub fn validate_algorithm_params(name: &str, params: &Params) -> OpResult<()> {
+    match describe_algorithm(name) {
+        Some(schema) => schema.validate(params),
+        None => Ok(()),
+    }
+}
+
+/// Validates params for a named effect against its schema.
+pub fn validate_effect_params(name: &str, params: &Params) -> OpResult<()> {
+    match describe_effect(name) {
+        Some(schema) => schema.validate(params),
+        None => Ok(()),
     }
 }
 
-impl std::fmt::Display for OpError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.message.fmt(f)
+/// Params a named algorithm actually runs with: schema defaults, then any
+/// `"preset"` values, then the explicit params.
+pub fn resolve_algorithm_params(name: &str, params: Option<&Params>) -> OpResult<Params> {
+    let mut resolved = describe_algorithm(name)
+        .map(|s| s.defaults())
+        .unwrap_or_default();
+    if let Some(params) = params {
+        resolved.extend(resolve_preset(name, params)?);
     }
+    Ok(resolved)
+}
+
+/// Params a named effect actually runs with: schema defaults, then the
+/// explicit params.
+pub fn resolve_effect_params(name: &str, params: Option<&Params>) -> Params {
+    let mut resolved = describe_effect(name)
+        .map(|s| s.defaults())
+        .unwrap_or_default();
+    resolved.extend(
+        params
+            .into_iter()
+            .flatten()
+            .map(|(k, v)| (k.clone(), v.clone())),
+    );
+    resolved
 }
 
-impl std::error::Error for OpError {}
+/// Expands a `"preset"` param into the preset's values, with explicit params on top.
+fn resolve_preset(name: &str, params: &Params) -> OpResult<Params> {
+    let Some(value) = params.get("preset") else {
+        return Ok(params.clone());
+    };
+    let preset = value.as_str().ok_or_else(|| OpError::InvalidParam {
+        op: name.to_string(),
+        name: "preset".to_string(),
+        message: "param 'preset' must be a string".to_string(),
+    })?;
+    let mut resolved = preset_params(name, preset).ok_or_else(|| OpError::InvalidParam {
+        op: name.to_string(),
+        name: "preset".to_string(),
+        message: format!(
+            "unknown preset '{}' (available: {})",
+            preset,
+            presets(name).join(", ")
+        ),
+    })?;
+    for (key, value) in params {
+        if key != "preset" {
+            resolved.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(resolved)
+}
+
+fn registered_schema(kind: OpKind, name: &str) -> Option<OpSchema> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    registry.schemas.get(&(kind, name.to_string())).cloned()
+}
+
+fn registered_algorithm(name: &str) -> Option<Arc<AlgorithmFactory>> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    registry.algorithms.get(name).cloned()
+}
+
+fn registered_effect(name: &str) -> Option<Arc<EffectHandler>> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    registry.effects.get(name).cloned()
+}
+
+/// Error type for ops operations; the crate-wide [`Error`](crate::Error).
+pub type OpError = crate::Error;
 
 /// Generate using a named algorithm with optional seed and params.
 /// Generates terrain by algorithm name with optional seed and params.
@@ -67,11 +273,47 @@ pub fn generate(
     seed: Option<u64>,
     params: Option<&Params>,
 ) -> OpResult<()> {
+    #[cfg(feature = "profile")]
+    let _span = crate::profile::Span::new(crate::profile::ProfileKind::Algorithm, name.trim());
+    #[cfg(feature = "rng-trace")]
+    let _trace = crate::rng_trace::Span::new(crate::rng_trace::TraceKind::Algorithm, name.trim());
+    #[cfg(feature = "tracing")]
+    let _log = crate::logging::Timed::new(tracing::debug_span!(
+        "algorithm",
+        name = name.trim(),
+        seed = seed.unwrap_or(0),
+        params = %crate::logging::params(params),
+    ));
     let algo = build_algorithm(name, params)?;
     algo.generate(grid, seed.unwrap_or(0));
     Ok(())
 }
 
+/// Like [`generate`], calling `frame` with intermediate states
+/// (see [`Algorithm::generate_frames`]).
+pub fn generate_frames(
+    name: &str,
+    grid: &mut Grid<Tile>,
+    seed: Option<u64>,
+    params: Option<&Params>,
+    frame: &mut dyn FnMut(&Grid<Tile>),
+) -> OpResult<()> {
+    #[cfg(feature = "profile")]
+    let _span = crate::profile::Span::new(crate::profile::ProfileKind::Algorithm, name.trim());
+    #[cfg(feature = "rng-trace")]
+    let _trace = crate::rng_trace::Span::new(crate::rng_trace::TraceKind::Algorithm, name.trim());
+    #[cfg(feature = "tracing")]
+    let _log = crate::logging::Timed::new(tracing::debug_span!(
+        "algorithm",
+        name = name.trim(),
+        seed = seed.unwrap_or(0),
+        params = %crate::logging::params(params),
+    ));
+    let algo = build_algorithm(name, params)?;
+    algo.generate_frames(grid, seed.unwrap_or(0), frame);
+    Ok(())
+}
+
 /// Generate using a named algorithm with optional semantic output.
 /// Generates terrain and extracts semantic layers.
 pub fn generate_with_semantic(
@@ -82,7 +324,21 @@ pub fn generate_with_semantic(
     semantic: Option<&mut SemanticLayers>,
 ) -> OpResult<()> {
     let name = name.trim();
-    if name == "prefab" {
+    #[cfg(feature = "profile")]
+    let _span = crate::profile::Span::new(crate::profile::ProfileKind::Algorithm, name);
+    #[cfg(feature = "rng-trace")]
+    let _trace = crate::rng_trace::Span::new(crate::rng_trace::TraceKind::Algorithm, name);
+    #[cfg(feature = "tracing")]
+    let _log = crate::logging::Timed::new(tracing::debug_span!(
+        "algorithm",
+        name,
+        seed = seed.unwrap_or(0),
+        params = %crate::logging::params(params),
+    ));
+    if name == "prefab" && registered_algorithm(name).is_none() {
+        if let Some(params) = params {
+            validate_algorithm_params(name, params)?;
+        }
         let (config, library) = build_prefab_config(params)?;
         let placer = PrefabPlacer::new(config, library);
         if let Some(semantic) = semantic {
@@ -98,13 +354,26 @@ pub fn generate_with_semantic(
     Ok(())
 }
 
-/// Build an algorithm instance from a name + optional params.
 /// Builds an algorithm instance from a name and optional params.
+///
+/// A `"preset"` param applies a named preset from
+/// [`algorithms::presets`](crate::algorithms::presets); other params override it.
 pub fn build_algorithm(
     name: &str,
     params: Option<&Params>,
 ) -> OpResult<Box<dyn Algorithm<Tile> + Send + Sync>> {
     let name = name.trim();
+    if let Some(factory) = registered_algorithm(name) {
+        if let Some(params) = params {
+            validate_algorithm_params(name, params)?;
+        }
+        return factory(params);
+    }
+    let resolved = params.map(|p| resolve_preset(name, p)).transpose()?;
+    let params = resolved.as_ref();
+    if let Some(params) = params {
+        validate_algorithm_params(name, params)?;
+    }
     match name {
         "bsp" => {
             let mut config = BspConfig::default();
@@ -118,6 +387,10 @@ pub fn build_algorithm(
                 if let Some(v) = get_usize(params, "room_padding") {
                     config.room_padding = v;
                 }
+                if let Some(v) = params.get("room_shapes") {
+                    config.room_shapes = serde_json::from_value(v.clone())
+                        .map_err(|e| OpError::new(format!("Invalid room_shapes: {}", e)))?;
+                }
             }
             Ok(Box::new(Bsp::new(config)))
         }
@@ -136,6 +409,12 @@ pub fn build_algorithm(
                 if let Some(v) = get_usize(params, "death_limit") {
                     config.death_limit = v;
                 }
+                if let Some(v) = get_bool(params, "connect") {
+                    config.connect = v;
+                }
+                if let Some(v) = get_usize(params, "min_cave_size") {
+                    config.min_cave_size = v;
+                }
             }
             Ok(Box::new(CellularAutomata::new(config)))
         }
@@ -148,6 +427,9 @@ pub fn build_algorithm(
                 if let Some(v) = get_usize(params, "max_iterations") {
                     config.max_iterations = v;
                 }
+                if let Some(v) = get_usize(params, "max_duration_ms") {
+                    config.max_duration_ms = Some(v as u64);
+                }
             }
             Ok(Box::new(DrunkardWalk::new(config)))
         }
@@ -199,6 +481,9 @@ pub fn build_algorithm(
                 if let Some(v) = get_usize(params, "max_walk_steps") {
                     config.max_walk_steps = v;
                 }
+                if let Some(v) = get_usize(params, "max_duration_ms") {
+                    config.max_duration_ms = Some(v as u64);
+                }
             }
             Ok(Box::new(Dla::new(config)))
         }
@@ -214,6 +499,9 @@ pub fn build_algorithm(
                 if let Some(v) = get_bool(params, "enable_backtracking") {
                     config.enable_backtracking = v;
                 }
+                if let Some(v) = get_usize(params, "max_duration_ms") {
+                    config.max_duration_ms = Some(v as u64);
+                }
             }
             Ok(Box::new(Wfc::new(config)))
         }
@@ -253,6 +541,9 @@ pub fn build_algorithm(
                 if let Some(v) = get_f64(params, "turn_chance") {
                     config.turn_chance = v;
                 }
+                if let Some(v) = get_usize(params, "max_duration_ms") {
+                    config.max_duration_ms = Some(v as u64);
+                }
             }
             Ok(Box::new(AgentBased::new(config)))
         }
@@ -341,12 +632,37 @@ pub fn build_algorithm(
             }
             Ok(Box::new(RoomAccretion::new(config)))
         }
+        "rooms_on_mask" => {
+            let mut config = RoomsOnMaskConfig::default();
+            if let Some(params) = params {
+                if let Some(v) = get_usize(params, "max_rooms") {
+                    config.max_rooms = v;
+                }
+                if let Some(v) = get_usize(params, "min_room_size") {
+                    config.min_room_size = v;
+                }
+                if let Some(v) = get_usize(params, "max_room_size") {
+                    config.max_room_size = v;
+                }
+                if let Some(v) = get_usize(params, "min_spacing") {
+                    config.min_spacing = v;
+                }
+                if let Some(v) = get_usize(params, "attempts") {
+                    config.attempts = v;
+                }
+                if let Some(v) = get_bool(params, "on_floor") {
+                    config.on_floor = v;
+                }
+            }
+            Ok(Box::new(RoomsOnMask::new(config)))
+        }
         "prefab" => {
             let (config, library) = build_prefab_config(params)?;
             Ok(Box::new(PrefabPlacer::new(config, library)))
         }
-        _ => crate::algorithms::get(name)
-            .ok_or_else(|| OpError::new(format!("Unknown algorithm: {}", name))),
+        _ => {
+            crate::algorithms::get(name).ok_or_else(|| OpError::UnknownAlgorithm(name.to_string()))
+        }
     }
 }
 
@@ -359,6 +675,22 @@ pub fn effect(
     semantic: Option<&SemanticLayers>,
 ) -> OpResult<()> {
     let name = name.trim();
+    #[cfg(feature = "profile")]
+    let _span = crate::profile::Span::new(crate::profile::ProfileKind::Effect, name);
+    #[cfg(feature = "rng-trace")]
+    let _trace = crate::rng_trace::Span::new(crate::rng_trace::TraceKind::Effect, name);
+    #[cfg(feature = "tracing")]
+    let _log = crate::logging::Timed::new(tracing::debug_span!(
+        "effect",
+        name,
+        params = %crate::logging::params(params),
+    ));
+    if let Some(params) = params {
+        validate_effect_params(name, params)?;
+    }
+    if let Some(handler) = registered_effect(name) {
+        return handler(grid, params, semantic);
+    }
     match name {
         "erode" => {
             let iterations = params.and_then(|p| get_usize(p, "iterations")).unwrap_or(1);
@@ -380,6 +712,68 @@ pub fn effect(
             effects::close(grid, iterations);
             Ok(())
         }
+        "thicken_walls" => {
+            let iterations = params.and_then(|p| get_usize(p, "iterations")).unwrap_or(1);
+            effects::thicken_walls(grid, iterations);
+            Ok(())
+        }
+        "obstacles" => {
+            let style = match params.and_then(|p| get_str(p, "style")) {
+                Some("broken_walls") => effects::ObstacleStyle::BrokenWalls,
+                _ => effects::ObstacleStyle::Pillars,
+            };
+            let defaults = effects::ObstacleConfig::default();
+            let config = defaults
+                .clone()
+                .with_style(style)
+                .with_spacing(
+                    params
+                        .and_then(|p| get_usize(p, "spacing"))
+                        .unwrap_or(defaults.spacing),
+                )
+                .with_density(
+                    params
+                        .and_then(|p| get_f64(p, "density"))
+                        .unwrap_or(defaults.density),
+                )
+                .with_min_corridor(
+                    params
+                        .and_then(|p| get_usize(p, "min_corridor"))
+                        .unwrap_or(defaults.min_corridor),
+                );
+            let seed = params.and_then(|p| get_u64(p, "seed")).unwrap_or(42);
+            effects::place_obstacles(grid, &config, seed);
+            Ok(())
+        }
+        "cavify" => {
+            let defaults = effects::CavifyConfig::default();
+            let config = defaults
+                .clone()
+                .with_widen(
+                    params
+                        .and_then(|p| get_f64(p, "widen"))
+                        .unwrap_or(defaults.widen),
+                    params
+                        .and_then(|p| get_usize(p, "passes"))
+                        .unwrap_or(defaults.passes),
+                )
+                .with_round_corners(
+                    params
+                        .and_then(|p| get_bool(p, "round_corners"))
+                        .unwrap_or(defaults.round_corners),
+                )
+                .with_chambers(
+                    params
+                        .and_then(|p| get_usize(p, "chambers"))
+                        .unwrap_or(defaults.chambers),
+                    params
+                        .and_then(|p| get_usize(p, "chamber_radius"))
+                        .unwrap_or(defaults.chamber_radius),
+                );
+            let seed = params.and_then(|p| get_u64(p, "seed")).unwrap_or(42);
+            effects::cavify(grid, &config, seed);
+            Ok(())
+        }
         "bridge_gaps" => {
             let max_distance = params
                 .and_then(|p| get_usize(p, "max_distance"))
@@ -387,18 +781,46 @@ pub fn effect(
             effects::bridge_gaps(grid, max_distance);
             Ok(())
         }
+        "bridges" => {
+            let max_length = params.and_then(|p| get_usize(p, "max_length")).unwrap_or(8);
+            let max_bridges = params
+                .and_then(|p| get_usize(p, "max_bridges"))
+                .unwrap_or(4);
+            let config = effects::BridgeConfig::default()
+                .with_max_length(max_length)
+                .with_max_bridges(max_bridges);
+            effects::place_bridges(grid, None, &config);
+            Ok(())
+        }
         "remove_dead_ends" => {
             let iterations = params.and_then(|p| get_usize(p, "iterations")).unwrap_or(3);
             effects::remove_dead_ends(grid, iterations);
             Ok(())
         }
+        "cap_dead_ends" => {
+            let (config, library) = build_prefab_config(params)?;
+            let seed = params.and_then(|p| get_u64(p, "seed")).unwrap_or(42);
+            PrefabPlacer::new(config, library).cap_dead_ends(grid, seed);
+            Ok(())
+        }
         "connect_regions_spanning" => {
             let chance = params
                 .and_then(|p| get_f64(p, "extra_connection_chance"))
                 .unwrap_or(0.2);
             let seed = params.and_then(|p| get_u64(p, "seed")).unwrap_or(42);
             let mut rng = crate::Rng::new(seed);
-            effects::connect_regions_spanning(grid, chance, &mut rng);
+            let budget = params.and_then(|p| get_usize(p, "corridor_budget"));
+            let max_length = params.and_then(|p| get_usize(p, "max_corridor_length"));
+            if budget.is_some() || max_length.is_some() {
+                let config = effects::SpanningConfig {
+                    extra_connection_chance: chance,
+                    corridor_budget: budget,
+                    max_corridor_length: max_length,
+                };
+                effects::connect_regions_budgeted(grid, &config, &mut rng);
+            } else {
+                effects::connect_regions_spanning(grid, chance, &mut rng);
+            }
             Ok(())
         }
         "mirror" => {
@@ -444,10 +866,10 @@ pub fn effect(
         }
         "clear_rect" => {
             let Some(params) = params else {
-                return Err(OpError::new("clear_rect requires params"));
+                return Err(OpError::missing_param(name, "center"));
             };
             let center = parse_point(params.get("center"))
-                .ok_or_else(|| OpError::new("clear_rect requires center: [x, y]"))?;
+                .ok_or_else(|| OpError::missing_param(name, "center"))?;
             let width = get_usize(params, "width").unwrap_or(3);
             let height = get_usize(params, "height").unwrap_or(3);
             effects::clear_rect(grid, center, width, height);
@@ -458,7 +880,7 @@ pub fn effect(
                 return Err(OpError::new("clear_marker_area requires semantic layers"));
             };
             let Some(params) = params else {
-                return Err(OpError::new("clear_marker_area requires params"));
+                return Err(OpError::missing_param(name, "marker"));
             };
             let marker_name = get_str(params, "marker").unwrap_or("spawn");
             let marker_type = parse_marker_type(marker_name);
@@ -481,7 +903,7 @@ pub fn effect(
                 return Err(OpError::new("connect_markers requires semantic layers"));
             };
             let Some(params) = params else {
-                return Err(OpError::new("connect_markers requires params"));
+                return Err(OpError::missing_param(name, "from"));
             };
             let from = get_str(params, "from").unwrap_or("spawn");
             let to = get_str(params, "to").unwrap_or("exit");
@@ -508,24 +930,31 @@ pub fn effect(
             }
             Ok(())
         }
+        "shrink_to_area" => {
+            let target = params
+                .and_then(|p| get_usize(p, "target"))
+                .ok_or_else(|| OpError::missing_param(name, "target"))?;
+            effects::shrink_to_area(grid, target, semantic);
+            Ok(())
+        }
         "invert" => {
             effects::invert(grid);
             Ok(())
         }
         "resize" => {
             let Some(params) = params else {
-                return Err(OpError::new("resize requires params"));
+                return Err(OpError::missing_param(name, "width"));
             };
             let width =
-                get_usize(params, "width").ok_or_else(|| OpError::new("resize requires width"))?;
+                get_usize(params, "width").ok_or_else(|| OpError::missing_param(name, "width"))?;
             let height = get_usize(params, "height")
-                .ok_or_else(|| OpError::new("resize requires height"))?;
+                .ok_or_else(|| OpError::missing_param(name, "height"))?;
             let pad = parse_tile(params.get("pad").or_else(|| params.get("pad_value")))
                 .unwrap_or(Tile::Wall);
             effects::resize(grid, width, height, pad);
             Ok(())
         }
-        _ => Err(OpError::new(format!("Unknown effect: {}", name))),
+        _ => Err(OpError::UnknownEffect(name.to_string())),
     }
 }
 
@@ -752,46 +1181,48 @@ fn parse_prefabs(val: &serde_json::Value) -> Vec<Prefab> {
     prefabs
 }
 
+/// Loads prefab libraries named by `library_paths`, `library_dir`, and `library_path`.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_prefab_files(params: &Params, library: &mut PrefabLibrary) -> OpResult<()> {
+    if let Some(paths_val) = params.get("library_paths") {
+        let paths = parse_string_list(paths_val);
+        if !paths.is_empty() {
+            library.extend_from(PrefabLibrary::load_from_paths(paths)?);
+        }
+    }
+    if let Some(dir) = get_str(params, "library_dir") {
+        library.extend_from(PrefabLibrary::load_from_dir(dir)?);
+    }
+    if let Some(path) = get_str(params, "library_path") {
+        library.extend_from(PrefabLibrary::load_from_json(path)?);
+    }
+    Ok(())
+}
+
+/// Filesystem prefab loading is unavailable on wasm; inline `prefabs` still work.
+#[cfg(target_arch = "wasm32")]
+fn load_prefab_files(params: &Params, _library: &mut PrefabLibrary) -> OpResult<()> {
+    match ["library_paths", "library_dir", "library_path"]
+        .into_iter()
+        .find(|key| params.contains_key(*key))
+    {
+        Some(key) => Err(OpError::InvalidParam {
+            op: "prefab".to_string(),
+            name: key.to_string(),
+            message: format!(
+                "'{}' is not supported on wasm32; pass inline 'prefabs' instead",
+                key
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
 fn build_prefab_config(params: Option<&Params>) -> OpResult<(PrefabConfig, PrefabLibrary)> {
     let mut config = PrefabConfig::default();
     let mut library = PrefabLibrary::new();
     if let Some(params) = params {
-        if let Some(paths_val) = params.get("library_paths") {
-            let paths = parse_string_list(paths_val);
-            if !paths.is_empty() {
-                match PrefabLibrary::load_from_paths(paths) {
-                    Ok(loaded) => library.extend_from(loaded),
-                    Err(err) => {
-                        return Err(OpError::new(format!(
-                            "Failed to load prefab library paths: {}",
-                            err
-                        )))
-                    }
-                }
-            }
-        }
-        if let Some(dir) = get_str(params, "library_dir") {
-            match PrefabLibrary::load_from_dir(dir) {
-                Ok(loaded) => library.extend_from(loaded),
-                Err(err) => {
-                    return Err(OpError::new(format!(
-                        "Failed to load prefab library dir '{}': {}",
-                        dir, err
-                    )))
-                }
-            }
-        }
-        if let Some(path) = get_str(params, "library_path") {
-            match PrefabLibrary::load_from_json(path) {
-                Ok(loaded) => library.extend_from(loaded),
-                Err(err) => {
-                    return Err(OpError::new(format!(
-                        "Failed to load prefab library '{}': {}",
-                        path, err
-                    )))
-                }
-            }
-        }
+        load_prefab_files(params, &mut library)?;
         if let Some(prefabs_val) = params.get("prefabs") {
             for prefab in parse_prefabs(prefabs_val) {
                 library.add_prefab(prefab);
@@ -894,7 +1325,7 @@ fn parse_tile(value: Option<&serde_json::Value>) -> Option<Tile> {
     }
 }
 
-fn parse_marker_type(name: &str) -> MarkerType {
+pub(crate) fn parse_marker_type(name: &str) -> MarkerType {
     let trimmed = name.trim();
     let lower = trimmed.to_ascii_lowercase();
     match lower.as_str() {
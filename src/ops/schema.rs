@@ -0,0 +1,612 @@
+//! Parameter schemas for ops: introspection and validation of [`Params`].
+//!
+//! ```rust
+//! use terrain_forge::ops;
+//! use terrain_forge::ops::Params;
+//! use serde_json::json;
+//!
+//! let schema = ops::describe("bsp").unwrap();
+//! assert!(schema.param("min_room_size").is_some());
+//!
+//! let mut params = Params::new();
+//! params.insert("min_rom_size".to_string(), json!(6));
+//! let err = schema.validate(&params).unwrap_err();
+//! assert!(err.to_string().contains("did you mean 'min_room_size'"));
+//! ```
+
+use super::{
+    parse_point, parse_range, parse_tile, value_to_bool, value_to_f64, value_to_u64, OpError,
+    OpResult, Params,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Whether an op generates a grid or post-processes one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum OpKind {
+    Algorithm,
+    Effect,
+}
+
+/// Accepted value type for a parameter.
+///
+/// Numeric and boolean types also accept their string forms (`"3"`, `"true"`),
+/// matching how ops parse params.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ParamType {
+    /// Non-negative integer.
+    Integer,
+    /// Floating point number.
+    Float,
+    /// Boolean.
+    Bool,
+    /// Free-form string.
+    String,
+    /// One of a fixed set of (case-insensitive) strings.
+    Choice(Vec<String>),
+    /// `[x, y]` pair.
+    Point,
+    /// Array of `[x, y]` pairs.
+    PointList,
+    /// `[min, max]` or `{ "min": .., "max": .. }`.
+    Range,
+    /// Tile name (`"floor"`/`"wall"`), boolean, or 0/1.
+    Tile,
+    /// A string or an array of strings.
+    StringList,
+    /// Arbitrary JSON, validated by the op itself.
+    Json,
+}
+
+impl std::fmt::Display for ParamType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamType::Integer => write!(f, "integer"),
+            ParamType::Float => write!(f, "number"),
+            ParamType::Bool => write!(f, "bool"),
+            ParamType::String => write!(f, "string"),
+            ParamType::Choice(options) => write!(f, "one of [{}]", options.join(", ")),
+            ParamType::Point => write!(f, "point [x, y]"),
+            ParamType::PointList => write!(f, "list of points"),
+            ParamType::Range => write!(f, "range [min, max]"),
+            ParamType::Tile => write!(f, "tile"),
+            ParamType::StringList => write!(f, "string or list of strings"),
+            ParamType::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Schema for a single parameter.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamSchema {
+    pub name: String,
+    pub ty: ParamType,
+    pub default: Option<Value>,
+    /// Inclusive numeric bounds for `Integer`/`Float` params.
+    pub range: Option<(f64, f64)>,
+    pub required: bool,
+    pub description: String,
+}
+
+impl ParamSchema {
+    /// Creates an optional parameter with no default or range.
+    pub fn new(name: impl Into<String>, ty: ParamType) -> Self {
+        Self {
+            name: name.into(),
+            ty,
+            default: None,
+            range: None,
+            required: false,
+            description: String::new(),
+        }
+    }
+
+    pub fn with_default(mut self, default: impl Into<Value>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Checks a single value against this parameter's type and range.
+    pub fn validate_value(&self, value: &Value) -> Result<(), String> {
+        let type_ok = match &self.ty {
+            ParamType::Integer => value_to_u64(value).is_some(),
+            ParamType::Float => value_to_f64(value).is_some(),
+            ParamType::Bool => value_to_bool(value).is_some(),
+            ParamType::String => value.is_string(),
+            ParamType::Choice(options) => value.as_str().is_some_and(|s| {
+                let s = s.trim().to_ascii_lowercase();
+                options.contains(&s)
+            }),
+            ParamType::Point => parse_point(Some(value)).is_some(),
+            ParamType::PointList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(|v| parse_point(Some(v)).is_some())),
+            ParamType::Range => parse_range(Some(value)).is_some(),
+            ParamType::Tile => parse_tile(Some(value)).is_some(),
+            ParamType::StringList => {
+                value.is_string()
+                    || value
+                        .as_array()
+                        .is_some_and(|items| items.iter().all(Value::is_string))
+            }
+            ParamType::Json => true,
+        };
+        if !type_ok {
+            return Err(format!(
+                "param '{}' expects {}, got {}",
+                self.name, self.ty, value
+            ));
+        }
+        if let (Some((min, max)), Some(v)) = (self.range, value_to_f64(value)) {
+            if matches!(self.ty, ParamType::Integer | ParamType::Float) && (v < min || v > max) {
+                return Err(format!(
+                    "param '{}' = {} is out of range [{}, {}]",
+                    self.name, v, min, max
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Schema describing an op and its accepted parameters.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpSchema {
+    pub name: String,
+    pub kind: OpKind,
+    pub description: String,
+    pub params: Vec<ParamSchema>,
+}
+
+impl OpSchema {
+    /// Creates an empty schema.
+    pub fn new(name: impl Into<String>, kind: OpKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            description: String::new(),
+            params: Vec::new(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_param(mut self, param: ParamSchema) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// Looks up a parameter by name.
+    pub fn param(&self, name: &str) -> Option<&ParamSchema> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    /// Returns a `Params` map populated with every declared default.
+    pub fn defaults(&self) -> Params {
+        self.params
+            .iter()
+            .filter_map(|p| p.default.clone().map(|d| (p.name.clone(), d)))
+            .collect()
+    }
+
+    /// Validates params: rejects unknown names, wrong types, out-of-range values,
+    /// and missing required params.
+    pub fn validate(&self, params: &Params) -> OpResult<()> {
+        let mut keys: Vec<&String> = params.keys().collect();
+        keys.sort();
+        for key in keys {
+            let Some(param) = self.param(key) else {
+                let mut message = format!("unknown param '{}'", key);
+                if let Some(suggestion) = self.closest_param(key) {
+                    message.push_str(&format!(" (did you mean '{}'?)", suggestion));
+                }
+                return Err(OpError::InvalidParam {
+                    op: self.name.clone(),
+                    name: key.clone(),
+                    message,
+                });
+            };
+            param
+                .validate_value(&params[key])
+                .map_err(|message| OpError::InvalidParam {
+                    op: self.name.clone(),
+                    name: key.clone(),
+                    message,
+                })?;
+        }
+        if let Some(missing) = self
+            .params
+            .iter()
+            .find(|p| p.required && !params.contains_key(&p.name))
+        {
+            return Err(OpError::missing_param(&self.name, &missing.name));
+        }
+        Ok(())
+    }
+
+    fn closest_param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .map(|p| (edit_distance(key, &p.name), p.name.as_str()))
+            .filter(|(d, name)| *d <= 3.max(name.len() / 3))
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, name)| name)
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j + 1])
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Names of the built-in algorithms that have schemas.
+pub const ALGORITHM_NAMES: &[&str] = &[
+    "bsp",
+    "cellular",
+    "drunkard",
+    "maze",
+    "rooms",
+    "voronoi",
+    "dla",
+    "wfc",
+    "percolation",
+    "diamond_square",
+    "agent",
+    "fractal",
+    "noise_fill",
+    "glass_seam",
+    "room_accretion",
+    "rooms_on_mask",
+    "prefab",
+];
+
+/// Names of the built-in effects that have schemas.
+pub const EFFECT_NAMES: &[&str] = &[
+    "erode",
+    "dilate",
+    "open",
+    "close",
+    "thicken_walls",
+    "obstacles",
+    "cavify",
+    "bridge_gaps",
+    "bridges",
+    "remove_dead_ends",
+    "cap_dead_ends",
+    "connect_regions_spanning",
+    "mirror",
+    "rotate",
+    "scatter",
+    "gaussian_blur",
+    "median_filter",
+    "domain_warp",
+    "clear_rect",
+    "clear_marker_area",
+    "connect_markers",
+    "shrink_to_area",
+    "invert",
+    "resize",
+];
+
+fn int(name: &str, default: u64) -> ParamSchema {
+    ParamSchema::new(name, ParamType::Integer).with_default(default)
+}
+
+fn float(name: &str, default: f64) -> ParamSchema {
+    ParamSchema::new(name, ParamType::Float).with_default(default)
+}
+
+fn chance(name: &str, default: f64) -> ParamSchema {
+    float(name, default).with_range(0.0, 1.0)
+}
+
+fn max_duration() -> ParamSchema {
+    ParamSchema::new("max_duration_ms", ParamType::Integer)
+        .with_description("Soft time limit; generation stops early and keeps its partial result")
+}
+
+fn boolean(name: &str, default: bool) -> ParamSchema {
+    ParamSchema::new(name, ParamType::Bool).with_default(default)
+}
+
+fn choice(name: &str, options: &[&str], default: &str) -> ParamSchema {
+    ParamSchema::new(
+        name,
+        ParamType::Choice(options.iter().map(|s| s.to_string()).collect()),
+    )
+    .with_default(default)
+}
+
+fn seed() -> ParamSchema {
+    int("seed", 42).with_description("Seed for the effect's RNG")
+}
+
+fn prefab_params(schema: OpSchema) -> OpSchema {
+    schema
+        .with_param(
+            ParamSchema::new("library_paths", ParamType::StringList)
+                .with_description("Prefab library JSON files to load"),
+        )
+        .with_param(
+            ParamSchema::new("library_dir", ParamType::String)
+                .with_description("Directory of prefab library JSON files"),
+        )
+        .with_param(
+            ParamSchema::new("library_path", ParamType::String)
+                .with_description("Single prefab library JSON file"),
+        )
+        .with_param(
+            ParamSchema::new("prefabs", ParamType::Json)
+                .with_description("Inline prefab definitions"),
+        )
+        .with_param(
+            ParamSchema::new("tags", ParamType::StringList)
+                .with_description("Only use prefabs with any of these tags"),
+        )
+        .with_param(choice(
+            "placement_mode",
+            &[
+                "overwrite",
+                "merge",
+                "paint_floor",
+                "paintfloor",
+                "floor",
+                "paint_wall",
+                "paintwall",
+                "wall",
+            ],
+            "overwrite",
+        ))
+        .with_param(int("max_prefabs", 3))
+        .with_param(int("min_spacing", 5))
+        .with_param(boolean("allow_rotation", true))
+        .with_param(boolean("allow_mirroring", false))
+        .with_param(boolean("weighted_selection", true))
+}
+
+/// Returns the schema of a built-in algorithm (aliases accepted).
+pub(crate) fn builtin_algorithm_schema(name: &str) -> Option<OpSchema> {
+    let schema = |name: &str, description: &str| {
+        OpSchema::new(name, OpKind::Algorithm).with_description(description)
+    };
+    let schema = match name {
+        "bsp" => schema("bsp", "Binary space partitioning rooms")
+            .with_param(int("min_room_size", 5).with_range(1.0, f64::MAX))
+            .with_param(int("max_depth", 4))
+            .with_param(int("room_padding", 1))
+            .with_param(
+                ParamSchema::new("room_shapes", ParamType::Json)
+                    .with_description("Weighted leaf room shapes, e.g. [[\"l_shape\", 2.0]]"),
+            ),
+        "cellular" | "cellular_automata" => schema("cellular", "Cellular automata caves")
+            .with_param(chance("initial_floor_chance", 0.45))
+            .with_param(int("iterations", 4))
+            .with_param(int("birth_limit", 5).with_range(0.0, 8.0))
+            .with_param(int("death_limit", 4).with_range(0.0, 8.0))
+            .with_param(boolean("connect", false))
+            .with_param(int("min_cave_size", 12)),
+        "drunkard" => schema("drunkard", "Random walk corridors")
+            .with_param(chance("floor_percent", 0.4))
+            .with_param(int("max_iterations", 50000))
+            .with_param(max_duration()),
+        "maze" => schema("maze", "Perfect maze")
+            .with_param(int("corridor_width", 1).with_range(1.0, f64::MAX)),
+        "rooms" | "simple_rooms" => schema("rooms", "Simple rectangular rooms")
+            .with_param(int("max_rooms", 10))
+            .with_param(int("min_room_size", 4))
+            .with_param(int("max_room_size", 10))
+            .with_param(int("min_spacing", 1)),
+        "voronoi" => schema("voronoi", "Voronoi regions")
+            .with_param(int("num_points", 15))
+            .with_param(chance("floor_chance", 0.5)),
+        "dla" => schema("dla", "Diffusion-limited aggregation")
+            .with_param(int("num_particles", 500))
+            .with_param(int("max_walk_steps", 1000))
+            .with_param(max_duration()),
+        "wfc" | "wave_function_collapse" => schema("wfc", "Wave function collapse")
+            .with_param(chance("floor_weight", 0.4))
+            .with_param(int("pattern_size", 3).with_range(1.0, f64::MAX))
+            .with_param(boolean("enable_backtracking", true))
+            .with_param(max_duration()),
+        "percolation" => schema("percolation", "Connected cluster generation")
+            .with_param(chance("fill_probability", 0.45))
+            .with_param(boolean("keep_largest", true)),
+        "diamond_square" => schema("diamond_square", "Diamond-square heightmap terrain")
+            .with_param(float("roughness", 0.6))
+            .with_param(float("threshold", 0.4)),
+        "agent" => schema("agent", "Multi-agent carving")
+            .with_param(int("num_agents", 5))
+            .with_param(int("steps_per_agent", 200))
+            .with_param(chance("turn_chance", 0.3))
+            .with_param(max_duration()),
+        "fractal" => schema("fractal", "Fractal set terrain")
+            .with_param(choice(
+                "fractal_type",
+                &["mandelbrot", "julia"],
+                "mandelbrot",
+            ))
+            .with_param(int("max_iterations", 100)),
+        "noise_fill" | "noise" => schema("noise_fill", "Thresholded noise fill")
+            .with_param(choice(
+                "noise",
+                &["perlin", "simplex", "value", "worley", "cellular"],
+                "perlin",
+            ))
+            .with_param(float("frequency", 0.08))
+            .with_param(float("scale", 1.0))
+            .with_param(float("size", 1.0).with_description("Alias for `scale`"))
+            .with_param(ParamSchema::new("range", ParamType::Range).with_default(json!([0.0, 1.0])))
+            .with_param(
+                ParamSchema::new("value_range", ParamType::Range)
+                    .with_description("Alias for `range`"),
+            )
+            .with_param(
+                ParamSchema::new("output_range", ParamType::Range)
+                    .with_description("Alias for `range`"),
+            )
+            .with_param(ParamSchema::new("fill_range", ParamType::Range))
+            .with_param(float("threshold", 0.0))
+            .with_param(int("octaves", 1).with_range(0.0, u32::MAX as f64))
+            .with_param(float("lacunarity", 2.0))
+            .with_param(float("persistence", 0.5)),
+        "glass_seam" | "gsb" => schema("glass_seam", "Region connector")
+            .with_param(chance("coverage_threshold", 0.75))
+            .with_param(ParamSchema::new("required_points", ParamType::PointList))
+            .with_param(int("carve_radius", 0))
+            .with_param(boolean("use_mst_terminals", true)),
+        "room_accretion" | "accretion" => schema("room_accretion", "Brogue-style organic dungeons")
+            .with_param(
+                ParamSchema::new("templates", ParamType::Json)
+                    .with_description("Room templates (Rectangle/Circle/Blob)"),
+            )
+            .with_param(int("max_rooms", 15))
+            .with_param(chance("loop_chance", 0.1)),
+        "rooms_on_mask" => schema("rooms_on_mask", "Rooms placed only inside a mask")
+            .with_param(int("max_rooms", 12))
+            .with_param(int("min_room_size", 4))
+            .with_param(int("max_room_size", 10))
+            .with_param(int("min_spacing", 1))
+            .with_param(int("attempts", 8))
+            .with_param(boolean("on_floor", false)),
+        "prefab" => prefab_params(schema("prefab", "Prefab placement")),
+        _ => return None,
+    };
+    let presets = crate::algorithms::presets(name);
+    if presets.is_empty() {
+        return Some(schema);
+    }
+    Some(
+        schema.with_param(
+            ParamSchema::new(
+                "preset",
+                ParamType::Choice(presets.iter().map(|s| s.to_string()).collect()),
+            )
+            .with_description("Named parameter preset; other params override it"),
+        ),
+    )
+}
+
+/// Returns the schema of a built-in effect.
+pub(crate) fn builtin_effect_schema(name: &str) -> Option<OpSchema> {
+    let schema = |name: &str, description: &str| {
+        OpSchema::new(name, OpKind::Effect).with_description(description)
+    };
+    let schema = match name {
+        "erode" | "dilate" | "open" | "close" => {
+            schema(name, "Morphological operation").with_param(int("iterations", 1))
+        }
+        "thicken_walls" => schema(name, "Grow walls into floor without disconnecting regions")
+            .with_param(int("iterations", 1)),
+        "obstacles" => schema(name, "Pillars or broken walls inside large open areas")
+            .with_param(choice("style", &["pillars", "broken_walls"], "pillars"))
+            .with_param(int("spacing", 5))
+            .with_param(chance("density", 0.8))
+            .with_param(int("min_corridor", 2))
+            .with_param(seed()),
+        "cavify" => schema(name, "Smooth a maze into caves without changing its topology")
+            .with_param(chance("widen", 0.35))
+            .with_param(int("passes", 2))
+            .with_param(boolean("round_corners", true))
+            .with_param(int("chambers", 3))
+            .with_param(int("chamber_radius", 3))
+            .with_param(seed()),
+        "bridge_gaps" => schema(name, "Connect nearby regions").with_param(int("max_distance", 5)),
+        "bridges" => schema(name, "Straight bridges across chasms between regions")
+            .with_param(int("max_length", 8))
+            .with_param(int("max_bridges", 4)),
+        "remove_dead_ends" => {
+            schema(name, "Remove dead-end corridors").with_param(int("iterations", 3))
+        }
+        "cap_dead_ends" => {
+            prefab_params(schema(name, "Cap dead-end corridors with prefabs")).with_param(seed())
+        }
+        "connect_regions_spanning" => schema(name, "Spanning-tree region connection")
+            .with_param(chance("extra_connection_chance", 0.2))
+            .with_param(
+                ParamSchema::new("corridor_budget", ParamType::Integer).with_description(
+                    "Most wall cells carved in total; routes corridors between distant regions",
+                ),
+            )
+            .with_param(
+                ParamSchema::new("max_corridor_length", ParamType::Integer).with_description(
+                    "Most wall cells carved per connection; routes corridors between distant regions",
+                ),
+            )
+            .with_param(seed()),
+        "mirror" => schema(name, "Mirror the grid")
+            .with_param(boolean("horizontal", true))
+            .with_param(boolean("vertical", false)),
+        "rotate" => schema(name, "Rotate the grid").with_param(int("degrees", 90)),
+        "scatter" => schema(name, "Scatter random floor")
+            .with_param(chance("density", 0.12))
+            .with_param(seed()),
+        "gaussian_blur" | "median_filter" => {
+            schema(name, "Smoothing filter").with_param(int("radius", 1))
+        }
+        "domain_warp" => schema(name, "Noise-based domain warp")
+            .with_param(float("amplitude", 2.0))
+            .with_param(float("frequency", 0.08))
+            .with_param(seed()),
+        "clear_rect" => schema(name, "Clear a rectangle to floor")
+            .with_param(ParamSchema::new("center", ParamType::Point).required())
+            .with_param(int("width", 3))
+            .with_param(int("height", 3)),
+        "clear_marker_area" => schema(name, "Clear areas around markers")
+            .with_param(ParamSchema::new("marker", ParamType::String).with_default("spawn"))
+            .with_param(int("width", 5))
+            .with_param(int("height", 5)),
+        "connect_markers" => schema(name, "Carve a path between markers")
+            .with_param(ParamSchema::new("from", ParamType::String).with_default("spawn"))
+            .with_param(ParamSchema::new("to", ParamType::String).with_default("exit"))
+            .with_param(choice("method", &["line", "path"], "line"))
+            .with_param(int("radius", 0)),
+        "shrink_to_area" => schema(name, "Peel outer floor down to a target area")
+            .with_param(
+                ParamSchema::new("target", ParamType::Integer)
+                    .required()
+                    .with_description("Floor cells to keep"),
+            ),
+        "invert" => schema(name, "Swap floor and wall"),
+        "resize" => schema(name, "Resize the grid")
+            .with_param(ParamSchema::new("width", ParamType::Integer).required())
+            .with_param(ParamSchema::new("height", ParamType::Integer).required())
+            .with_param(ParamSchema::new("pad", ParamType::Tile).with_default("wall"))
+            .with_param(
+                ParamSchema::new("pad_value", ParamType::Tile).with_description("Alias for `pad`"),
+            ),
+        _ => return None,
+    };
+    Some(schema)
+}
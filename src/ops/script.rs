@@ -0,0 +1,207 @@
+//! Batch execution of op invocations described as data.
+//!
+//! ```rust
+//! use terrain_forge::ops;
+//!
+//! let output = ops::run_script(r#"{
+//!     "width": 60,
+//!     "height": 40,
+//!     "seed": 7,
+//!     "ops": [
+//!         { "op": "generate", "name": "cellular", "params": { "iterations": 5 } },
+//!         { "op": "effect", "name": "erode" },
+//!         { "op": "combine", "mode": "union", "name": "rooms" },
+//!         { "op": "extract", "profile": "caves" }
+//!     ]
+//! }"#).unwrap();
+//!
+//! assert_eq!(output.log.len(), 4);
+//! assert!(output.semantic.is_some());
+//! ```
+
+use super::{
+    combine, effect, generate, generate_with_semantic, CombineMode, OpError, OpResult, Params,
+};
+use crate::semantic::{SemanticConfig, SemanticLayers};
+use crate::{Grid, Rng, SemanticExtractor, Tile};
+use serde::{Deserialize, Serialize};
+
+/// An ordered list of op invocations on a single grid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpScript {
+    pub width: usize,
+    pub height: usize,
+    /// Seed for ops that don't specify one. Default: 0.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub ops: Vec<ScriptOp>,
+}
+
+/// A single scripted op invocation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ScriptOp {
+    /// Run a named algorithm on the grid.
+    Generate {
+        name: String,
+        #[serde(default)]
+        seed: Option<u64>,
+        #[serde(default)]
+        params: Option<Params>,
+    },
+    /// Apply a named effect to the grid (receives the latest extracted semantics).
+    Effect {
+        name: String,
+        #[serde(default)]
+        params: Option<Params>,
+    },
+    /// Generate a named algorithm into a scratch grid and combine it into the grid.
+    Combine {
+        mode: String,
+        name: String,
+        #[serde(default)]
+        seed: Option<u64>,
+        #[serde(default)]
+        params: Option<Params>,
+    },
+    /// Extract semantic layers with a named extractor profile.
+    Extract {
+        #[serde(default)]
+        profile: Option<String>,
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+}
+
+/// Structured log entry for one executed op.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptLogEntry {
+    /// Position of the op in the script.
+    pub index: usize,
+    /// Op kind (`generate`, `effect`, `combine`, `extract`).
+    pub op: String,
+    /// Algorithm, effect, or profile name.
+    pub name: String,
+    /// Seed used, for seeded ops.
+    pub seed: Option<u64>,
+    /// Floor tile count after the op.
+    pub floor_count: usize,
+}
+
+/// Result of running an [`OpScript`].
+#[derive(Debug, Clone)]
+pub struct ScriptOutput {
+    pub grid: Grid<Tile>,
+    /// Semantics from the most recent `extract` op, if any.
+    pub semantic: Option<SemanticLayers>,
+    pub log: Vec<ScriptLogEntry>,
+}
+
+impl OpScript {
+    /// Parses a script from JSON.
+    pub fn from_json(json: &str) -> OpResult<Self> {
+        serde_json::from_str(json).map_err(|e| OpError::parse("op script", e))
+    }
+
+    /// Executes the script on a fresh grid.
+    ///
+    /// Fails on the first op that errors; the error names the op index.
+    pub fn run(&self) -> OpResult<ScriptOutput> {
+        let mut grid = Grid::new(self.width, self.height);
+        let mut semantic: Option<SemanticLayers> = None;
+        let mut rng = Rng::new(self.seed.unwrap_or(0));
+        let mut log = Vec::with_capacity(self.ops.len());
+
+        for (index, op) in self.ops.iter().enumerate() {
+            let at = |e: OpError| OpError::Script {
+                index,
+                source: Box::new(e),
+            };
+            let (kind, name, seed) = match op {
+                ScriptOp::Generate { name, seed, params } => {
+                    let seed = seed.unwrap_or_else(|| rng.next_u64());
+                    if let Some(layers) = semantic.as_mut() {
+                        generate_with_semantic(
+                            name,
+                            &mut grid,
+                            Some(seed),
+                            params.as_ref(),
+                            Some(layers),
+                        )
+                        .map_err(at)?;
+                    } else {
+                        generate(name, &mut grid, Some(seed), params.as_ref()).map_err(at)?;
+                    }
+                    ("generate", name.clone(), Some(seed))
+                }
+                ScriptOp::Effect { name, params } => {
+                    effect(name, &mut grid, params.as_ref(), semantic.as_ref()).map_err(at)?;
+                    ("effect", name.clone(), None)
+                }
+                ScriptOp::Combine {
+                    mode,
+                    name,
+                    seed,
+                    params,
+                } => {
+                    let mode = parse_combine_mode(mode).map_err(at)?;
+                    let seed = seed.unwrap_or_else(|| rng.next_u64());
+                    let mut other = Grid::new(grid.width(), grid.height());
+                    generate(name, &mut other, Some(seed), params.as_ref()).map_err(at)?;
+                    combine(mode, &mut grid, &other).map_err(at)?;
+                    ("combine", name.clone(), Some(seed))
+                }
+                ScriptOp::Extract { profile, seed } => {
+                    let profile = profile.as_deref().unwrap_or("default");
+                    let extractor = extractor_for_profile(profile).map_err(at)?;
+                    let seed = seed.unwrap_or_else(|| rng.next_u64());
+                    semantic = Some(extractor.extract(&grid, &mut Rng::new(seed)));
+                    ("extract", profile.to_string(), Some(seed))
+                }
+            };
+            log.push(ScriptLogEntry {
+                index,
+                op: kind.to_string(),
+                name,
+                seed,
+                floor_count: grid.count(|t| t.is_floor()),
+            });
+        }
+
+        Ok(ScriptOutput {
+            grid,
+            semantic,
+            log,
+        })
+    }
+}
+
+/// Parses and runs an op script from JSON.
+///
+/// See [`OpScript`] for the format: `width`, `height`, optional `seed`, and an
+/// `ops` array of `generate` / `effect` / `combine` / `extract` entries.
+pub fn run_script(json: &str) -> OpResult<ScriptOutput> {
+    OpScript::from_json(json)?.run()
+}
+
+pub(crate) fn parse_combine_mode(mode: &str) -> OpResult<CombineMode> {
+    match mode.trim().to_ascii_lowercase().as_str() {
+        "replace" => Ok(CombineMode::Replace),
+        "union" | "|" => Ok(CombineMode::Union),
+        "intersect" | "&" => Ok(CombineMode::Intersect),
+        "difference" | "-" => Ok(CombineMode::Difference),
+        "mask" => Ok(CombineMode::Mask),
+        other => Err(OpError::new(format!("Unknown combine mode: {}", other))),
+    }
+}
+
+pub(crate) fn extractor_for_profile(profile: &str) -> OpResult<SemanticExtractor> {
+    SemanticConfig::preset(profile)
+        .map(SemanticExtractor::new)
+        .ok_or_else(|| {
+            OpError::new(format!(
+                "Unknown extractor profile: {}",
+                profile.trim().to_ascii_lowercase()
+            ))
+        })
+}
@@ -0,0 +1,398 @@
+//! Difficulty pacing along the critical path.
+//!
+//! [`plan`] walks the shortest route from the spawn marker to the exit (or
+//! to the farthest reachable cell), cuts it into equal segments, and gives
+//! each a target tension from a [`CurveShape`], by default a sawtooth of
+//! rising waves that peaks at the boss. [`PacingPlan::place`] then adds
+//! encounters whose count and difficulty follow the curve, loot in the
+//! lulls after each peak, and a boss room at the end.
+//! [`PacingPlan::measure`] reads the tension existing encounters give each
+//! segment, to compare a hand-placed map against the target.
+//!
+//! ```rust
+//! use terrain_forge::pacing::{self, PacingConfig};
+//! use terrain_forge::semantic::{Marker, MarkerType};
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("cellular").unwrap().generate(&mut grid, 5);
+//! let mut semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(5));
+//! let (x, y, _) = grid.iter().find(|(_, _, t)| t.is_floor()).unwrap();
+//! semantic.markers.push(Marker::new(x as u32, y as u32, MarkerType::Spawn));
+//!
+//! let config = PacingConfig::default();
+//! let plan = pacing::plan(&grid, &semantic, &config).unwrap();
+//! let placed = plan.place(&grid, &mut semantic, &config, &mut Rng::new(5));
+//! assert!(placed > 0);
+//! let measured = plan.measure(&grid, &semantic, &config);
+//! assert_eq!(measured.len(), plan.segments.len());
+//! ```
+
+use crate::hiding::{self, HidingConfig};
+use crate::semantic::{meta, Marker, MarkerConstraints, MarkerType, SemanticLayers};
+use crate::spatial::{dijkstra_map, shortest_path, DijkstraMap, PathfindingConstraints};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Target tension over the length of the critical path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveShape {
+    /// Steady climb from 0 to 1.
+    Rising,
+    /// `waves` build-ups, each starting low and peaking higher than the
+    /// last; the final peak, at the end of the path, is 1.
+    Sawtooth { waves: usize },
+    /// Tension at evenly spaced points along the path, interpolated
+    /// linearly.
+    Custom { points: Vec<f64> },
+}
+
+impl Default for CurveShape {
+    fn default() -> Self {
+        CurveShape::Sawtooth { waves: 3 }
+    }
+}
+
+impl CurveShape {
+    /// Tension at `t`, from 0 (spawn) to 1 (end), clamped to 0..=1.
+    pub fn tension(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        let value = match self {
+            CurveShape::Rising => t,
+            CurveShape::Sawtooth { waves } => {
+                let waves = (*waves).max(1) as f64;
+                let wave = (t * waves).floor().min(waves - 1.0);
+                let local = t * waves - wave;
+                (wave + 1.0) / waves * (0.3 + 0.7 * local)
+            }
+            CurveShape::Custom { points } => match points.len() {
+                0 => 0.0,
+                1 => points[0],
+                n => {
+                    let at = t * (n - 1) as f64;
+                    let i = (at.floor() as usize).min(n - 2);
+                    let f = at - i as f64;
+                    points[i] * (1.0 - f) + points[i + 1] * f
+                }
+            },
+        };
+        value.clamp(0.0, 1.0)
+    }
+}
+
+/// Configuration for [`plan`] and [`PacingPlan::place`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PacingConfig {
+    /// Default: a sawtooth with 3 waves.
+    pub shape: CurveShape,
+    /// Segments the critical path is cut into. Default: 8.
+    pub segments: usize,
+    /// Encounters in a segment at tension 1. Default: 3.
+    pub max_encounters: usize,
+    /// Encounter difficulty at tension 1; tension 0 gives 1. Default: 5.
+    pub max_difficulty: u8,
+    /// Loot tier after a peak of tension 1. Default: 3.
+    pub max_loot_tier: u8,
+    /// Put a boss room near the end of the path. Default: true.
+    pub boss: bool,
+    /// Markers go on floor within this many steps of their segment's path
+    /// cells. Default: 3.
+    pub spread: usize,
+    /// Minimum distance between placed markers and any other marker.
+    /// Default: 2.0.
+    pub spacing: f32,
+    /// Put loot on the most hidden cells of its zone (see
+    /// [`hiding::score`]) instead of random ones. Default: false.
+    pub hide_loot: bool,
+}
+
+impl Default for PacingConfig {
+    fn default() -> Self {
+        Self {
+            shape: CurveShape::default(),
+            segments: 8,
+            max_encounters: 3,
+            max_difficulty: 5,
+            max_loot_tier: 3,
+            boss: true,
+            spread: 3,
+            spacing: 2.0,
+            hide_loot: false,
+        }
+    }
+}
+
+impl PacingConfig {
+    /// Sets `shape`.
+    pub fn with_shape(mut self, shape: CurveShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets `segments`.
+    pub fn with_segments(mut self, segments: usize) -> Self {
+        self.segments = segments;
+        self
+    }
+
+    /// Sets `max_encounters`.
+    pub fn with_max_encounters(mut self, max_encounters: usize) -> Self {
+        self.max_encounters = max_encounters;
+        self
+    }
+
+    /// Sets `boss`.
+    pub fn with_boss(mut self, boss: bool) -> Self {
+        self.boss = boss;
+        self
+    }
+
+    /// Sets `hide_loot`.
+    pub fn with_hide_loot(mut self, hide_loot: bool) -> Self {
+        self.hide_loot = hide_loot;
+        self
+    }
+}
+
+/// A stretch of the critical path and its target tension.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Path cells, in walking order.
+    pub cells: Vec<(usize, usize)>,
+    /// Target tension, 0 to 1.
+    pub tension: f64,
+}
+
+/// The critical path and its intended pacing, from [`plan`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PacingPlan {
+    /// Cells from the spawn to the end, inclusive.
+    pub path: Vec<(usize, usize)>,
+    pub segments: Vec<Segment>,
+}
+
+/// Finds the critical path and assigns each segment its target tension.
+///
+/// The path starts at the first `Spawn` (or `PlayerStart`) marker and ends
+/// at the first reachable `Exit`, or at the reachable floor cell farthest from the
+/// spawn. Returns `None` without a spawn on floor or when the path is
+/// shorter than `config.segments`.
+pub fn plan(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    config: &PacingConfig,
+) -> Option<PacingPlan> {
+    let spawn = semantic.spawn()?;
+    let start = (spawn.x as usize, spawn.y as usize);
+    if !grid
+        .get(start.0 as i32, start.1 as i32)
+        .is_some_and(|t| t.is_floor())
+    {
+        return None;
+    }
+
+    let constraints = PathfindingConstraints::cardinal();
+    let from_spawn = dijkstra_map(grid, &[start], &constraints);
+    let end = path_end(grid, semantic, &from_spawn)?;
+    let path = shortest_path(grid, start, end, &constraints)?;
+
+    let count = config.segments.max(1);
+    if path.len() < count {
+        return None;
+    }
+    let segments = (0..count)
+        .map(|i| {
+            let (lo, hi) = (i * path.len() / count, (i + 1) * path.len() / count);
+            let t = if count == 1 {
+                1.0
+            } else {
+                i as f64 / (count - 1) as f64
+            };
+            Segment {
+                cells: path[lo..hi].to_vec(),
+                tension: config.shape.tension(t),
+            }
+        })
+        .collect();
+    Some(PacingPlan { path, segments })
+}
+
+/// End of the critical path: the first `Exit` reachable on `from_spawn`,
+/// or else the reachable cell farthest from the spawn.
+pub(crate) fn path_end(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    from_spawn: &DijkstraMap,
+) -> Option<(usize, usize)> {
+    let reachable = |x: usize, y: usize| from_spawn.get(x, y).is_finite();
+    semantic
+        .exits()
+        .map(|m| (m.x as usize, m.y as usize))
+        .find(|&(x, y)| x < grid.width() && y < grid.height() && reachable(x, y))
+        .or_else(|| {
+            grid.iter()
+                .filter(|&(x, y, _)| reachable(x, y))
+                .max_by(|a, b| {
+                    from_spawn
+                        .get(a.0, a.1)
+                        .total_cmp(&from_spawn.get(b.0, b.1))
+                })
+                .map(|(x, y, _)| (x, y))
+        })
+}
+
+impl PacingPlan {
+    /// Adds encounters, loot, and the boss room to `semantic` following
+    /// the target curve. Returns the number of markers added.
+    ///
+    /// A segment at tension `τ` gets `round(τ * max_encounters)` encounters
+    /// of difficulty `1 + round(τ * (max_difficulty - 1))`. A segment
+    /// calmer than the one before gets loot whose tier follows the previous
+    /// segment's tension, on the zone's most hidden free cell with
+    /// `hide_loot`. Markers carry [`meta::PACING_SEGMENT`] and
+    /// [`meta::PACING_TENSION`].
+    pub fn place(
+        &self,
+        grid: &Grid<Tile>,
+        semantic: &mut SemanticLayers,
+        config: &PacingConfig,
+        rng: &mut Rng,
+    ) -> usize {
+        let w = grid.width();
+        let zones = self.zones(grid, config.spread);
+        let mut sites: Vec<Vec<(usize, usize)>> = vec![Vec::new(); self.segments.len()];
+        for (i, zone) in zones.iter().enumerate() {
+            if let Some(segment) = *zone {
+                sites[segment].push((i % w, i / w));
+            }
+        }
+        let mut region_of = vec![None; zones.len()];
+        for region in &semantic.regions {
+            for &(x, y) in &region.cells {
+                if let Some(cell) = region_of.get_mut(y as usize * w + x as usize) {
+                    *cell = Some(region.id);
+                }
+            }
+        }
+        let constraints = MarkerConstraints {
+            min_distance_any: Some(config.spacing),
+            ..MarkerConstraints::none()
+        };
+        let level = |tension: f64, max: u8| 1 + (tension * (max.max(1) - 1) as f64).round() as u8;
+        let hidden = config
+            .hide_loot
+            .then(|| hiding::score(grid, semantic, &HidingConfig::default()));
+
+        let mut added = 0;
+        let last = self.segments.len().saturating_sub(1);
+        for (i, segment) in self.segments.iter().enumerate() {
+            let mut wanted = Vec::new();
+            if config.boss && i == last {
+                wanted.push(MarkerType::BossRoom);
+            }
+            let encounters = (segment.tension * config.max_encounters as f64).round() as usize;
+            let difficulty = level(segment.tension, config.max_difficulty);
+            wanted.extend((0..encounters).map(|_| MarkerType::EncounterZone { difficulty }));
+            if let Some(previous) = i.checked_sub(1).map(|p| &self.segments[p]) {
+                if segment.tension < previous.tension {
+                    let tier = level(previous.tension, config.max_loot_tier);
+                    wanted.push(MarkerType::LootTier { tier });
+                }
+            }
+
+            let mut candidates = sites[i].clone();
+            rng.shuffle(&mut candidates);
+            if config.boss && i == last {
+                // The boss waits at the far end of the path.
+                let end = segment.cells.last().copied().unwrap_or_default();
+                candidates.sort_by_key(|&(x, y)| x.abs_diff(end.0) + y.abs_diff(end.1));
+            }
+            // Hidden loot searches the zone from its best hiding spot down
+            // instead of in shuffled order.
+            let mut hiding_spots = sites[i].clone();
+            if let Some(scores) = &hidden {
+                hiding_spots.sort_by(|a, b| scores[b.1][b.0].total_cmp(&scores[a.1][a.0]));
+            }
+            let mut candidates = candidates.into_iter();
+            for marker_type in wanted {
+                let hide = hidden.is_some() && matches!(marker_type, MarkerType::LootTier { .. });
+                let build = |(x, y): (usize, usize)| {
+                    let mut marker = Marker::new(x as u32, y as u32, marker_type.clone())
+                        .with_metadata(meta::PACING_SEGMENT, i.to_string())
+                        .with_metadata(meta::PACING_TENSION, format!("{:.2}", segment.tension));
+                    marker.region_id = region_of[y * w + x];
+                    constraints
+                        .allows(&marker, &semantic.markers)
+                        .then_some(marker)
+                };
+                let placed = if hide {
+                    hiding_spots.iter().copied().find_map(build)
+                } else {
+                    candidates.by_ref().find_map(build)
+                };
+                if let Some(marker) = placed {
+                    semantic.markers.push(marker);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    /// Tension existing markers give each segment: encounter difficulty
+    /// (a boss room counts as `max_difficulty`) summed over the segment's
+    /// zone, scaled so the most intense segment reads 1.
+    pub fn measure(
+        &self,
+        grid: &Grid<Tile>,
+        semantic: &SemanticLayers,
+        config: &PacingConfig,
+    ) -> Vec<f64> {
+        let w = grid.width();
+        let zones = self.zones(grid, config.spread);
+        let mut totals = vec![0.0; self.segments.len()];
+        for marker in &semantic.markers {
+            let weight = match marker.marker_type {
+                MarkerType::EncounterZone { difficulty } => difficulty as f64,
+                MarkerType::BossRoom => config.max_difficulty as f64,
+                _ => continue,
+            };
+            let zone = zones
+                .get(marker.y as usize * w + marker.x as usize)
+                .copied()
+                .flatten();
+            if let Some(segment) = zone {
+                totals[segment] += weight;
+            }
+        }
+        let peak = totals.iter().copied().fold(0.0, f64::max);
+        if peak > 0.0 {
+            for total in &mut totals {
+                *total /= peak;
+            }
+        }
+        totals
+    }
+
+    /// Segment of the nearest path cell within `spread` steps, per cell;
+    /// ties go to the earlier segment.
+    fn zones(&self, grid: &Grid<Tile>, spread: usize) -> Vec<Option<usize>> {
+        let w = grid.width();
+        let constraints = PathfindingConstraints::cardinal();
+        let mut zone = vec![None; w * grid.height()];
+        let mut depth = vec![f32::INFINITY; zone.len()];
+        for (i, segment) in self.segments.iter().enumerate() {
+            let map = dijkstra_map(grid, &segment.cells, &constraints);
+            for (x, y, _) in grid.iter() {
+                let d = map.get(x, y);
+                if d <= spread as f32 && d < depth[y * w + x] {
+                    depth[y * w + x] = d;
+                    zone[y * w + x] = Some(i);
+                }
+            }
+        }
+        zone
+    }
+}
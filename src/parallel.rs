@@ -0,0 +1,53 @@
+//! Data-parallel helpers for hot paths.
+//!
+//! With feature `parallel` these run on rayon's global pool; otherwise they
+//! are plain loops. Work is split so results never depend on scheduling, so
+//! seeded output is identical either way.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Calls `f(y, row)` for each `width`-long row of `cells`.
+pub(crate) fn for_each_row<T, F>(cells: &mut [T], width: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync + Send,
+{
+    if width == 0 {
+        return;
+    }
+    #[cfg(feature = "parallel")]
+    cells
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+    #[cfg(not(feature = "parallel"))]
+    cells
+        .chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+}
+
+/// Maps `0..n` through `f`, preserving order.
+pub(crate) fn map<T, F>(n: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    return (0..n).into_par_iter().map(f).collect();
+    #[cfg(not(feature = "parallel"))]
+    (0..n).map(f).collect()
+}
+
+/// Returns the first (lowest-index) `Some` from `f` over `0..n`.
+pub(crate) fn find_map_first<T, F>(n: usize, f: F) -> Option<T>
+where
+    T: Send,
+    F: Fn(usize) -> Option<T> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    return (0..n).into_par_iter().find_map_first(f);
+    #[cfg(not(feature = "parallel"))]
+    (0..n).find_map(f)
+}
@@ -17,10 +17,42 @@
 //!
 //! pipe.execute_seed(&mut grid, 12345).unwrap();
 //! ```
+//!
+//! Step params may reference pipeline parameters as `${name}`. A value that
+//! is exactly one reference takes the parameter's type (`"${iterations}"`
+//! with `iterations = "4"` becomes the number 4); references inside longer
+//! strings are spliced in as text. Parameters come from
+//! [`Pipeline::set_parameter`] defaults, `SetParameter` steps, and the
+//! [`PipelineContext`]:
+//!
+//! ```rust
+//! use terrain_forge::{Grid, pipeline::Pipeline};
+//! use terrain_forge::ops::Params;
+//! use serde_json::json;
+//!
+//! let mut params = Params::new();
+//! params.insert("iterations".to_string(), json!("${erosion}"));
+//!
+//! let mut pipe = Pipeline::new();
+//! pipe.set_parameter("erosion", "3")
+//!     .add_algorithm("cellular", None, None)
+//!     .add_effect("erode", Some(params));
+//!
+//! let mut grid = Grid::new(60, 40);
+//! let context = pipe.execute_seed(&mut grid, 7).unwrap();
+//! assert_eq!(context.manifest().steps[1].params["iterations"], json!(3));
+//! ```
 
+use crate::algorithms::{Wfc, WfcConfig, WfcPatternExtractor};
+use crate::manifest::{Manifest, StepKind, StepRecord, Timer};
 use crate::ops::{self, CombineMode, OpError, Params};
 use crate::{Algorithm, Grid, Rng, Tile};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+/// Receives `(step_label, grid)` from [`Pipeline::execute_frames`].
+type FrameSink<'f> = dyn FnMut(&str, &Grid<Tile>) + 'f;
 
 /// Unified pipeline steps (name + optional params).
 #[derive(Debug, Clone)]
@@ -53,6 +85,57 @@ pub enum Step {
     Log {
         message: String,
     },
+    /// Replace the grid with an image, resampled to the grid's size.
+    Image {
+        source: ImageSource,
+        threshold: u8,
+    },
+    /// Run WFC with patterns extracted from an image sample.
+    WfcSample {
+        source: ImageSource,
+        threshold: u8,
+        pattern_size: usize,
+        seed: Option<u64>,
+    },
+}
+
+impl Step {
+    /// Algorithm or effect name, otherwise the step kind.
+    pub fn label(&self) -> &str {
+        match self {
+            Step::Algorithm { name, .. } | Step::Effect { name, .. } => name,
+            Step::Combine { .. } => "combine",
+            Step::If { .. } => "if",
+            Step::StoreGrid { .. } => "store_grid",
+            Step::SetParameter { .. } => "set_parameter",
+            Step::Log { .. } => "log",
+            Step::Image { .. } => "image",
+            Step::WfcSample { .. } => "wfc_sample",
+        }
+    }
+}
+
+/// PNG input for image steps (see [`Grid::from_image`]).
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    Path(String),
+    Bytes(Vec<u8>),
+}
+
+impl ImageSource {
+    /// Decodes the image with the given luminance threshold.
+    pub fn load(&self, threshold: u8) -> Result<Grid<Tile>, OpError> {
+        let loaded = match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            ImageSource::Path(path) => Grid::from_image(path, threshold),
+            #[cfg(target_arch = "wasm32")]
+            ImageSource::Path(_) => {
+                Err("image paths are not supported on wasm32; use ImageSource::Bytes".into())
+            }
+            ImageSource::Bytes(bytes) => Grid::from_image_bytes(bytes, threshold),
+        };
+        loaded.map_err(|e| OpError::parse("image", e))
+    }
 }
 
 /// Source for combine steps.
@@ -71,11 +154,20 @@ pub enum CombineSource {
 #[derive(Debug, Clone, Default)]
 pub struct Pipeline {
     steps: Vec<Step>,
+    /// Parameter defaults, copied into the context unless already set there
+    parameters: BTreeMap<String, String>,
 }
 
 impl Pipeline {
     pub fn new() -> Self {
-        Self { steps: Vec::new() }
+        Self::default()
+    }
+
+    /// Sets a default for `${key}` references in step params. Values
+    /// already in the context when the pipeline runs take precedence.
+    pub fn set_parameter(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.parameters.insert(key.into(), value.into());
+        self
     }
 
     pub fn add_step(&mut self, step: Step) -> &mut Self {
@@ -162,63 +254,277 @@ impl Pipeline {
         self
     }
 
+    pub fn add_image(&mut self, source: ImageSource, threshold: u8) -> &mut Self {
+        self.steps.push(Step::Image { source, threshold });
+        self
+    }
+
+    pub fn add_wfc_sample(
+        &mut self,
+        source: ImageSource,
+        threshold: u8,
+        pattern_size: usize,
+        seed: Option<u64>,
+    ) -> &mut Self {
+        self.steps.push(Step::WfcSample {
+            source,
+            threshold,
+            pattern_size,
+            seed,
+        });
+        self
+    }
+
     pub fn execute(
         &self,
         grid: &mut Grid<Tile>,
         context: &mut PipelineContext,
         rng: &mut Rng,
     ) -> Result<(), OpError> {
+        context.manifest.width = grid.width();
+        context.manifest.height = grid.height();
+        self.apply_parameters(context);
+        #[cfg(feature = "tracing")]
+        let _log = crate::logging::Timed::new(tracing::info_span!(
+            "pipeline",
+            seed = ?context.manifest.seed,
+            width = grid.width(),
+            height = grid.height(),
+            steps = self.steps.len(),
+        ));
         for step in &self.steps {
-            Self::execute_step(step, grid, context, rng)?;
+            Self::execute_step(step, grid, context, rng, None, 0)?;
         }
         Ok(())
     }
 
+    /// Like [`execute_seed`](Self::execute_seed), calling `frame(step_label, grid)`
+    /// after every step and for intermediate algorithm states
+    /// (see [`Algorithm::generate_frames`]).
+    pub fn execute_frames(
+        &self,
+        grid: &mut Grid<Tile>,
+        seed: u64,
+        frame: &mut dyn FnMut(&str, &Grid<Tile>),
+    ) -> Result<PipelineContext, OpError> {
+        let mut context = PipelineContext::new();
+        context.manifest = Manifest::new(grid.width(), grid.height(), Some(seed));
+        let mut rng = Rng::new(seed);
+        self.apply_parameters(&mut context);
+        #[cfg(feature = "tracing")]
+        let _log = crate::logging::Timed::new(tracing::info_span!(
+            "pipeline",
+            seed,
+            width = grid.width(),
+            height = grid.height(),
+            steps = self.steps.len(),
+        ));
+        for step in &self.steps {
+            Self::execute_step(step, grid, &mut context, &mut rng, Some(&mut *frame), 0)?;
+        }
+        Ok(context)
+    }
+
     pub fn execute_seed(
         &self,
         grid: &mut Grid<Tile>,
         seed: u64,
     ) -> Result<PipelineContext, OpError> {
         let mut context = PipelineContext::new();
+        context.manifest.seed = Some(seed);
         let mut rng = Rng::new(seed);
         self.execute(grid, &mut context, &mut rng)?;
         Ok(context)
     }
 
-    fn execute_step(
+    /// Like [`execute_seed`](Self::execute_seed), calling `checkpoint`
+    /// after every top-level step with everything needed to
+    /// [`resume`](Self::resume) from there. Save the latest one to survive
+    /// preemption.
+    pub fn execute_checkpointed(
+        &self,
+        grid: &mut Grid<Tile>,
+        seed: u64,
+        checkpoint: &mut dyn FnMut(&PipelineCheckpoint),
+    ) -> Result<PipelineContext, OpError> {
+        let mut context = PipelineContext::new();
+        context.manifest = Manifest::new(grid.width(), grid.height(), Some(seed));
+        self.apply_parameters(&mut context);
+        self.run_from(0, grid, context, Rng::new(seed), checkpoint)
+    }
+
+    /// Continues a run of this pipeline from `from`, writing the result to
+    /// `grid` (which is resized to the checkpoint's grid). The output and
+    /// manifest match an uninterrupted run with the same seed, timings
+    /// aside. Fails if `from` was taken from a pipeline with a different
+    /// number of steps.
+    pub fn resume(
+        &self,
+        from: PipelineCheckpoint,
+        grid: &mut Grid<Tile>,
+        checkpoint: &mut dyn FnMut(&PipelineCheckpoint),
+    ) -> Result<PipelineContext, OpError> {
+        if from.total_steps != self.steps.len() || from.next_step > self.steps.len() {
+            return Err(OpError::new(format!(
+                "Checkpoint is for a pipeline of {} steps, not {}",
+                from.total_steps,
+                self.steps.len()
+            )));
+        }
+        *grid = from.grid;
+        self.run_from(from.next_step, grid, from.context, from.rng, checkpoint)
+    }
+
+    fn run_from(
+        &self,
+        start: usize,
+        grid: &mut Grid<Tile>,
+        mut context: PipelineContext,
+        mut rng: Rng,
+        checkpoint: &mut dyn FnMut(&PipelineCheckpoint),
+    ) -> Result<PipelineContext, OpError> {
+        #[cfg(feature = "tracing")]
+        let _log = crate::logging::Timed::new(tracing::info_span!(
+            "pipeline",
+            seed = ?context.manifest.seed,
+            width = grid.width(),
+            height = grid.height(),
+            steps = self.steps.len(),
+            start,
+        ));
+        for (i, step) in self.steps.iter().enumerate().skip(start) {
+            Self::execute_step(step, grid, &mut context, &mut rng, None, 0)?;
+            checkpoint(&PipelineCheckpoint {
+                next_step: i + 1,
+                total_steps: self.steps.len(),
+                grid: grid.clone(),
+                context: context.clone(),
+                rng: rng.clone(),
+            });
+        }
+        Ok(context)
+    }
+
+    fn apply_parameters(&self, context: &mut PipelineContext) {
+        for (key, value) in &self.parameters {
+            context
+                .parameters
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    /// Runs one step and times its manifest record (pushed by `run_step`).
+    fn execute_step<'f>(
         step: &Step,
         grid: &mut Grid<Tile>,
         context: &mut PipelineContext,
         rng: &mut Rng,
+        frame: Option<&mut FrameSink<'f>>,
+        depth: usize,
     ) -> Result<(), OpError> {
+        let timer = Timer::start();
+        let at = context.manifest.steps.len();
+        let result = Self::run_step(step, grid, context, rng, frame, depth);
+        #[cfg(feature = "tracing")]
+        if let Err(err) = &result {
+            tracing::warn!(step = step.label(), error = %err, "pipeline step failed");
+        }
+        result?;
+        if let Some(record) = context.manifest.steps.get_mut(at) {
+            record.micros = timer.micros();
+        }
+        Ok(())
+    }
+
+    fn run_step<'f>(
+        step: &Step,
+        grid: &mut Grid<Tile>,
+        context: &mut PipelineContext,
+        rng: &mut Rng,
+        mut frame: Option<&mut FrameSink<'f>>,
+        depth: usize,
+    ) -> Result<(), OpError> {
+        let record = |context: &mut PipelineContext, mut record: StepRecord| {
+            record.depth = depth;
+            context.manifest.steps.push(record);
+        };
+        #[cfg(feature = "profile")]
+        let _span =
+            crate::profile::Span::new(crate::profile::ProfileKind::PipelineStep, step.label());
+        #[cfg(feature = "rng-trace")]
+        let _trace =
+            crate::rng_trace::Span::new(crate::rng_trace::TraceKind::PipelineStep, step.label());
+        #[cfg(feature = "tracing")]
+        let _log = crate::logging::Timed::new(tracing::debug_span!(
+            "pipeline_step",
+            step = step.label(),
+            depth
+        ));
         match step {
             Step::Algorithm { name, seed, params } => {
+                let params = context.substitute_params(name, params.as_ref())?;
                 let use_seed = seed.unwrap_or_else(|| rng.next_u64());
-                ops::generate(name, grid, Some(use_seed), params.as_ref())?;
+                match frame {
+                    Some(frame) => ops::generate_frames(
+                        name,
+                        grid,
+                        Some(use_seed),
+                        params.as_ref(),
+                        &mut |g| frame(name, g),
+                    )?,
+                    None => ops::generate(name, grid, Some(use_seed), params.as_ref())?,
+                }
                 context.log_execution(format!("Algorithm: {} (seed: {})", name, use_seed));
-                Ok(())
+                let params = ops::resolve_algorithm_params(name, params.as_ref())?;
+                record(
+                    context,
+                    StepRecord::new(StepKind::Algorithm, name.as_str())
+                        .with_params(params)
+                        .with_seed(use_seed),
+                );
+                return Ok(());
             }
             Step::Effect { name, params } => {
+                let params = context.substitute_params(name, params.as_ref())?;
                 ops::effect(name, grid, params.as_ref(), None)?;
                 context.log_execution(format!("Effect: {}", name));
-                Ok(())
+                let params = ops::resolve_effect_params(name, params.as_ref());
+                record(
+                    context,
+                    StepRecord::new(StepKind::Effect, name.as_str()).with_params(params),
+                );
+                Ok::<(), OpError>(())
             }
             Step::Combine { mode, source } => {
+                let mut entry = StepRecord::new(StepKind::Combine, "combine");
                 let other = match source {
-                    CombineSource::Grid(other) => other.clone(),
+                    CombineSource::Grid(other) => {
+                        entry = entry.with_detail(format!("{:?}", mode));
+                        other.clone()
+                    }
                     CombineSource::Algorithm { name, seed, params } => {
+                        let params = context.substitute_params(name, params.as_ref())?;
                         let mut temp = Grid::new(grid.width(), grid.height());
                         let use_seed = seed.unwrap_or_else(|| rng.next_u64());
                         ops::generate(name, &mut temp, Some(use_seed), params.as_ref())?;
+                        entry = StepRecord::new(StepKind::Combine, name.as_str())
+                            .with_params(ops::resolve_algorithm_params(name, params.as_ref())?)
+                            .with_seed(use_seed)
+                            .with_detail(format!("{:?}", mode));
                         temp
                     }
-                    CombineSource::Saved(key) => context
-                        .get_grid(key)
-                        .ok_or_else(|| OpError::new(format!("Unknown saved grid: {}", key)))?
-                        .clone(),
+                    CombineSource::Saved(key) => {
+                        entry = entry.with_detail(format!("{:?} saved '{}'", mode, key));
+                        context
+                            .get_grid(key)
+                            .ok_or_else(|| OpError::new(format!("Unknown saved grid: {}", key)))?
+                            .clone()
+                    }
                 };
                 ops::combine(*mode, grid, &other)?;
                 context.log_execution(format!("Combine: {:?}", mode));
+                record(context, entry);
                 Ok(())
             }
             Step::If {
@@ -226,38 +532,143 @@ impl Pipeline {
                 then_steps,
                 else_steps,
             } => {
-                let branch = if condition.evaluate(grid, context) {
-                    then_steps
+                let (label, branch) = if condition.evaluate(grid, context) {
+                    ("then", then_steps)
                 } else {
-                    else_steps
+                    ("else", else_steps)
                 };
+                record(
+                    context,
+                    StepRecord::new(StepKind::If, "if").with_detail(label),
+                );
+                let mut branch_rng = rng.fork(label);
                 for step in branch {
-                    Self::execute_step(step, grid, context, rng)?;
+                    Self::execute_step(
+                        step,
+                        grid,
+                        context,
+                        &mut branch_rng,
+                        frame.as_deref_mut(),
+                        depth + 1,
+                    )?;
                 }
-                Ok(())
+                return Ok(());
             }
             Step::StoreGrid { key } => {
                 context.store_grid(key.clone(), grid.clone());
+                record(
+                    context,
+                    StepRecord::new(StepKind::StoreGrid, "store_grid").with_detail(key.as_str()),
+                );
                 Ok(())
             }
             Step::SetParameter { key, value } => {
                 context.set_parameter(key.clone(), value.clone());
+                record(
+                    context,
+                    StepRecord::new(StepKind::SetParameter, "set_parameter")
+                        .with_detail(format!("{} = {}", key, value)),
+                );
                 Ok(())
             }
             Step::Log { message } => {
                 context.log_execution(message.clone());
+                record(
+                    context,
+                    StepRecord::new(StepKind::Log, "log").with_detail(message.as_str()),
+                );
+                Ok(())
+            }
+            Step::Image { source, threshold } => {
+                let image = source.load(*threshold)?;
+                *grid = image.resized(grid.width(), grid.height());
+                context.log_execution(format!(
+                    "Image: {}x{} (threshold: {})",
+                    image.width(),
+                    image.height(),
+                    threshold
+                ));
+                record(
+                    context,
+                    StepRecord::new(StepKind::Image, "image").with_detail(format!(
+                        "{}x{}",
+                        image.width(),
+                        image.height()
+                    )),
+                );
                 Ok(())
             }
+            Step::WfcSample {
+                source,
+                threshold,
+                pattern_size,
+                seed,
+            } => {
+                let sample = source.load(*threshold)?;
+                let patterns = WfcPatternExtractor::extract_patterns(&sample, *pattern_size);
+                if patterns.is_empty() {
+                    return Err(OpError::new(format!(
+                        "Image sample {}x{} is smaller than pattern size {}",
+                        sample.width(),
+                        sample.height(),
+                        pattern_size
+                    )));
+                }
+                let use_seed = seed.unwrap_or_else(|| rng.next_u64());
+                let count = patterns.len();
+                let wfc = Wfc::new(WfcConfig::default().with_pattern_size(*pattern_size));
+                match frame {
+                    Some(frame) => {
+                        wfc.generate_with_patterns_frames(grid, patterns, use_seed, &mut |g| {
+                            frame("wfc_sample", g)
+                        })
+                    }
+                    None => wfc.generate_with_patterns(grid, patterns, use_seed),
+                }
+                context.log_execution(format!(
+                    "WfcSample: {} patterns (seed: {})",
+                    count, use_seed
+                ));
+                record(
+                    context,
+                    StepRecord::new(StepKind::WfcSample, "wfc_sample")
+                        .with_seed(use_seed)
+                        .with_detail(format!("{} patterns", count)),
+                );
+                return Ok(());
+            }
+        }?;
+        if let Some(frame) = frame {
+            frame(step.label(), grid);
         }
+        Ok(())
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<OpError>> = const { RefCell::new(None) };
+}
+
+impl Pipeline {
+    /// Takes the error of the last failed [`Algorithm::generate`] call on
+    /// this thread, leaving `None`.
+    pub fn take_last_error() -> Option<OpError> {
+        LAST_ERROR.with(|e| e.borrow_mut().take())
     }
 }
 
 impl Algorithm<Tile> for Pipeline {
+    /// Runs [`execute_seed`](Self::execute_seed), which this trait can't
+    /// return errors from. A failure leaves the grid as far as the pipeline
+    /// got, emits an `error` event with the `tracing` feature, and is kept
+    /// for [`Pipeline::take_last_error`] on the calling thread, replacing
+    /// any earlier one. It goes unnoticed only when nobody takes it and
+    /// `tracing` is off (or no subscriber listens).
     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
         if let Err(err) = self.execute_seed(grid, seed) {
-            if cfg!(debug_assertions) {
-                eprintln!("Pipeline execution failed: {}", err);
-            }
+            #[cfg(feature = "tracing")]
+            tracing::error!(seed, error = %err, "pipeline execution failed");
+            LAST_ERROR.with(|e| *e.borrow_mut() = Some(err));
         }
     }
 
@@ -266,6 +677,40 @@ impl Algorithm<Tile> for Pipeline {
     }
 }
 
+/// A pipeline run paused between top-level steps; see
+/// [`Pipeline::execute_checkpointed`].
+///
+/// Steps themselves aren't serialized, so a checkpoint resumes only with
+/// the pipeline that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineCheckpoint {
+    /// Index of the next step to run.
+    pub next_step: usize,
+    /// Steps in the pipeline that produced it.
+    pub total_steps: usize,
+    /// The grid after the steps run so far.
+    pub grid: Grid<Tile>,
+    /// Parameters, stored grids, log, and manifest so far.
+    pub context: PipelineContext,
+    rng: Rng,
+}
+
+impl PipelineCheckpoint {
+    /// Whether every step has run.
+    pub fn is_finished(&self) -> bool {
+        self.next_step >= self.total_steps
+    }
+
+    /// Parses a checkpoint saved with [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> Result<Self, OpError> {
+        serde_json::from_str(json).map_err(|e| OpError::parse("pipeline checkpoint", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
 /// Conditions that can be evaluated during pipeline execution
 #[derive(Debug, Clone)]
 pub enum PipelineCondition {
@@ -349,26 +794,30 @@ impl PipelineCondition {
 }
 
 /// Context for passing data between pipeline stages
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipelineContext {
     /// Key-value parameters passed between stages
-    parameters: HashMap<String, String>,
+    parameters: BTreeMap<String, String>,
     /// Stage execution history
     execution_log: Vec<String>,
     /// Current iteration count for loops
     iteration_count: usize,
     /// Named grids for combine steps
     grids: HashMap<String, Grid<Tile>>,
+    /// Structured record of executed steps
+    #[serde(default)]
+    manifest: Manifest,
 }
 
 impl PipelineContext {
     /// Create new empty context
     pub fn new() -> Self {
         Self {
-            parameters: HashMap::new(),
+            parameters: BTreeMap::new(),
             execution_log: Vec::new(),
             iteration_count: 0,
             grids: HashMap::new(),
+            manifest: Manifest::default(),
         }
     }
 
@@ -382,6 +831,32 @@ impl PipelineContext {
         self.parameters.get(key)
     }
 
+    /// Replaces `${name}` references in `op`'s params with context
+    /// parameters (see the [module docs](self)). Fails on a reference to a
+    /// parameter that isn't set.
+    pub fn substitute_params(
+        &self,
+        op: &str,
+        params: Option<&Params>,
+    ) -> Result<Option<Params>, OpError> {
+        params
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(key, value)| {
+                        substitute_value(value, &self.parameters)
+                            .map(|v| (key.clone(), v))
+                            .map_err(|missing| OpError::InvalidParam {
+                                op: op.to_string(),
+                                name: key.clone(),
+                                message: format!("unknown pipeline parameter '{}'", missing),
+                            })
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
     /// Log stage execution
     pub fn log_execution(&mut self, stage: impl Into<String>) {
         self.execution_log.push(stage.into());
@@ -411,6 +886,21 @@ impl PipelineContext {
     pub fn get_grid(&self, key: &str) -> Option<&Grid<Tile>> {
         self.grids.get(key)
     }
+
+    /// Steps executed so far, with resolved params, seeds, and timings.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Mutable manifest, e.g. to record constraint results.
+    pub fn manifest_mut(&mut self) -> &mut Manifest {
+        &mut self.manifest
+    }
+
+    /// Consumes the context, returning its manifest.
+    pub fn into_manifest(self) -> Manifest {
+        self.manifest
+    }
 }
 
 impl Default for PipelineContext {
@@ -427,7 +917,7 @@ pub struct StageResult {
     /// Optional message about execution
     pub message: Option<String>,
     /// Parameters to pass to next stage
-    pub output_parameters: HashMap<String, String>,
+    pub output_parameters: BTreeMap<String, String>,
 }
 
 impl StageResult {
@@ -436,7 +926,7 @@ impl StageResult {
         Self {
             success: true,
             message: None,
-            output_parameters: HashMap::new(),
+            output_parameters: BTreeMap::new(),
         }
     }
 
@@ -445,7 +935,7 @@ impl StageResult {
         Self {
             success: true,
             message: Some(message.into()),
-            output_parameters: HashMap::new(),
+            output_parameters: BTreeMap::new(),
         }
     }
 
@@ -454,7 +944,7 @@ impl StageResult {
         Self {
             success: false,
             message: Some(message.into()),
-            output_parameters: HashMap::new(),
+            output_parameters: BTreeMap::new(),
         }
     }
 
@@ -469,14 +959,14 @@ impl StageResult {
 #[derive(Debug, Clone)]
 pub struct ParameterMap {
     /// Parameters from different branches
-    branch_parameters: HashMap<String, HashMap<String, String>>,
+    branch_parameters: BTreeMap<String, BTreeMap<String, String>>,
 }
 
 impl ParameterMap {
     /// Create new parameter map
     pub fn new() -> Self {
         Self {
-            branch_parameters: HashMap::new(),
+            branch_parameters: BTreeMap::new(),
         }
     }
 
@@ -484,20 +974,20 @@ impl ParameterMap {
     pub fn add_branch(
         &mut self,
         branch_name: impl Into<String>,
-        parameters: HashMap<String, String>,
+        parameters: BTreeMap<String, String>,
     ) {
         self.branch_parameters
             .insert(branch_name.into(), parameters);
     }
 
     /// Get parameters from specific branch
-    pub fn get_branch(&self, branch_name: &str) -> Option<&HashMap<String, String>> {
+    pub fn get_branch(&self, branch_name: &str) -> Option<&BTreeMap<String, String>> {
         self.branch_parameters.get(branch_name)
     }
 
-    /// Merge all branch parameters (later branches override earlier ones)
-    pub fn merge_all(&self) -> HashMap<String, String> {
-        let mut merged = HashMap::new();
+    /// Merge all branch parameters (branches apply in name order; later names override earlier ones)
+    pub fn merge_all(&self) -> BTreeMap<String, String> {
+        let mut merged = BTreeMap::new();
         for params in self.branch_parameters.values() {
             merged.extend(params.clone());
         }
@@ -519,7 +1009,7 @@ pub enum PipelineOperation {
     /// Apply effect with parameters
     Effect {
         name: String,
-        parameters: HashMap<String, String>,
+        parameters: BTreeMap<String, String>,
     },
     /// Set context parameter
     SetParameter { key: String, value: String },
@@ -636,9 +1126,17 @@ impl ConditionalPipeline {
         match operation {
             PipelineOperation::Algorithm { name, seed } => {
                 let use_seed = seed.unwrap_or(12345);
+                let timer = Timer::start();
                 match ops::generate(name, grid, Some(use_seed), None) {
                     Ok(()) => {
                         context.log_execution(format!("Algorithm: {} (seed: {})", name, use_seed));
+                        let mut record = StepRecord::new(StepKind::Algorithm, name.as_str())
+                            .with_params(
+                                ops::resolve_algorithm_params(name, None).unwrap_or_default(),
+                            )
+                            .with_seed(use_seed);
+                        record.micros = timer.micros();
+                        context.manifest.steps.push(record);
                         StageResult::success()
                             .with_parameter("last_algorithm", name.clone())
                             .with_parameter("last_seed", use_seed.to_string())
@@ -647,10 +1145,19 @@ impl ConditionalPipeline {
                 }
             }
             PipelineOperation::Effect { name, parameters } => {
-                let params = params_from_strings(parameters);
+                let params =
+                    match context.substitute_params(name, Some(&params_from_strings(parameters))) {
+                        Ok(params) => params.unwrap_or_default(),
+                        Err(err) => return StageResult::failure(err.to_string()),
+                    };
+                let timer = Timer::start();
                 match ops::effect(name, grid, Some(&params), None) {
                     Ok(()) => {
                         context.log_execution(format!("Effect: {}", name));
+                        let mut record = StepRecord::new(StepKind::Effect, name.as_str())
+                            .with_params(ops::resolve_effect_params(name, Some(&params)));
+                        record.micros = timer.micros();
+                        context.manifest.steps.push(record);
                         StageResult::success().with_parameter("last_effect", name.clone())
                     }
                     Err(err) => StageResult::failure(err.to_string()),
@@ -702,13 +1209,82 @@ impl ConditionalOperation {
     }
 }
 
-fn params_from_strings(parameters: &HashMap<String, String>) -> Params {
+fn params_from_strings(parameters: &BTreeMap<String, String>) -> Params {
     parameters
         .iter()
-        .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+        .map(|(k, v)| (k.clone(), typed_value(v)))
         .collect()
 }
 
+/// Parses a parameter string as a bool or number where it is one, so
+/// substituted params keep the type ops expect.
+fn typed_value(text: &str) -> serde_json::Value {
+    let trimmed = text.trim();
+    if let Ok(b) = trimmed.parse::<bool>() {
+        return b.into();
+    }
+    if let Ok(n) = trimmed.parse::<i64>() {
+        return n.into();
+    }
+    if let Ok(n) = trimmed.parse::<u64>() {
+        return n.into();
+    }
+    match trimmed.parse::<f64>() {
+        Ok(n) if n.is_finite() => n.into(),
+        _ => serde_json::Value::String(text.to_string()),
+    }
+}
+
+/// Substitutes `${name}` references in `value`, recursing into arrays and
+/// objects. Returns the first unknown name on failure.
+fn substitute_value(
+    value: &serde_json::Value,
+    vars: &BTreeMap<String, String>,
+) -> Result<serde_json::Value, String> {
+    use serde_json::Value;
+    match value {
+        Value::String(text) => {
+            if let Some(name) = text
+                .strip_prefix("${")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .filter(|name| !name.contains('}'))
+            {
+                return vars
+                    .get(name)
+                    .map(|v| typed_value(v))
+                    .ok_or_else(|| name.to_string());
+            }
+            let mut out = String::with_capacity(text.len());
+            let mut rest = text.as_str();
+            while let Some(open) = rest.find("${") {
+                out.push_str(&rest[..open]);
+                let after = &rest[open + 2..];
+                let Some(close) = after.find('}') else {
+                    out.push_str(&rest[open..]);
+                    rest = "";
+                    break;
+                };
+                let name = &after[..close];
+                out.push_str(vars.get(name).ok_or_else(|| name.to_string())?);
+                rest = &after[close + 1..];
+            }
+            out.push_str(rest);
+            Ok(Value::String(out))
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|v| substitute_value(v, vars))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| substitute_value(v, vars).map(|v| (k.clone(), v)))
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
 /// Template for reusable pipeline configurations
 #[derive(Debug, Clone)]
 pub struct PipelineTemplate {
@@ -717,7 +1293,7 @@ pub struct PipelineTemplate {
     /// Template description
     pub description: String,
     /// Template parameters with default values
-    pub parameters: HashMap<String, String>,
+    pub parameters: BTreeMap<String, String>,
     /// Pipeline operations (can use parameter placeholders)
     pub operations: Vec<ConditionalOperation>,
 }
@@ -728,7 +1304,7 @@ impl PipelineTemplate {
         Self {
             name: name.into(),
             description: description.into(),
-            parameters: HashMap::new(),
+            parameters: BTreeMap::new(),
             operations: Vec::new(),
         }
     }
@@ -752,7 +1328,7 @@ impl PipelineTemplate {
     /// Instantiate template with custom parameters
     pub fn instantiate(
         &self,
-        custom_params: Option<HashMap<String, String>>,
+        custom_params: Option<BTreeMap<String, String>>,
     ) -> ConditionalPipeline {
         let mut pipeline = ConditionalPipeline::new();
 
@@ -775,7 +1351,7 @@ impl PipelineTemplate {
     fn substitute_parameters(
         &self,
         operation: &ConditionalOperation,
-        params: &HashMap<String, String>,
+        params: &BTreeMap<String, String>,
     ) -> ConditionalOperation {
         let substituted_op = match &operation.operation {
             PipelineOperation::Algorithm { name, seed } => {
@@ -787,7 +1363,7 @@ impl PipelineTemplate {
             }
             PipelineOperation::Effect { name, parameters } => {
                 let sub_name = self.substitute_string(name, params);
-                let mut sub_params = HashMap::new();
+                let mut sub_params = BTreeMap::new();
                 for (k, v) in parameters {
                     sub_params.insert(k.clone(), self.substitute_string(v, params));
                 }
@@ -825,12 +1401,13 @@ impl PipelineTemplate {
         }
     }
 
-    /// Substitute parameter placeholders in a string
-    fn substitute_string(&self, input: &str, params: &HashMap<String, String>) -> String {
+    /// Substitute `${key}` and `{key}` placeholders in a string; unknown
+    /// keys are left for the context to resolve at execution
+    fn substitute_string(&self, input: &str, params: &BTreeMap<String, String>) -> String {
         let mut result = input.to_string();
         for (key, value) in params {
-            let placeholder = format!("{{{}}}", key);
-            result = result.replace(&placeholder, value);
+            result = result.replace(&format!("${{{}}}", key), value);
+            result = result.replace(&format!("{{{}}}", key), value);
         }
         result
     }
@@ -0,0 +1,217 @@
+//! Timing and allocation instrumentation (feature `profile`).
+//!
+//! [`capture`] runs a closure and returns a [`ProfileReport`] with one
+//! [`ProfileEntry`] per algorithm, effect, and pipeline step executed on the
+//! calling thread. Outside `capture` nothing is recorded.
+//!
+//! Allocation counts need [`CountingAllocator`] installed as the global
+//! allocator; otherwise they read zero. Counts are process-wide, so
+//! allocations from other threads during a span are included.
+//!
+//! ```rust
+//! use terrain_forge::{ops, profile, Grid};
+//!
+//! let (_, report) = profile::capture(|| {
+//!     let mut grid = Grid::new(80, 60);
+//!     ops::generate("cellular", &mut grid, Some(1), None).unwrap();
+//!     ops::effect("erode", &mut grid, None, None).unwrap();
+//! });
+//! assert_eq!(report.entries.len(), 2);
+//! assert_eq!(report.entries[0].label, "cellular");
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// What a profiled span measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProfileKind {
+    Algorithm,
+    Effect,
+    PipelineStep,
+}
+
+/// One completed span.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileEntry {
+    pub kind: ProfileKind,
+    pub label: String,
+    /// Nesting depth; a pipeline step running an algorithm is depth 0, the algorithm depth 1.
+    pub depth: usize,
+    pub duration: Duration,
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+/// Spans recorded by [`capture`], in start order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileReport {
+    pub entries: Vec<ProfileEntry>,
+}
+
+impl ProfileReport {
+    /// Total time of top-level spans.
+    pub fn total(&self) -> Duration {
+        self.entries
+            .iter()
+            .filter(|e| e.depth == 0)
+            .map(|e| e.duration)
+            .sum()
+    }
+
+    /// Total time of spans of one kind.
+    pub fn total_for(&self, kind: ProfileKind) -> Duration {
+        self.entries
+            .iter()
+            .filter(|e| e.kind == kind)
+            .map(|e| e.duration)
+            .sum()
+    }
+
+    /// `(kind, label, count, total duration)` per distinct span, slowest first.
+    pub fn summary(&self) -> Vec<(ProfileKind, String, usize, Duration)> {
+        let mut out: Vec<(ProfileKind, String, usize, Duration)> = Vec::new();
+        for entry in &self.entries {
+            match out
+                .iter_mut()
+                .find(|(k, l, _, _)| *k == entry.kind && *l == entry.label)
+            {
+                Some(row) => {
+                    row.2 += 1;
+                    row.3 += entry.duration;
+                }
+                None => out.push((entry.kind, entry.label.clone(), 1, entry.duration)),
+            }
+        }
+        out.sort_by_key(|row| std::cmp::Reverse(row.3));
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+struct Recorder {
+    entries: Vec<ProfileEntry>,
+    depth: usize,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, recording spans on this thread, and returns its result with the report.
+/// Nested calls report to the innermost `capture`.
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, ProfileReport) {
+    let previous = RECORDER.with(|r| {
+        r.borrow_mut().replace(Recorder {
+            entries: Vec::new(),
+            depth: 0,
+        })
+    });
+    let result = f();
+    let recorder = RECORDER.with(|r| std::mem::replace(&mut *r.borrow_mut(), previous));
+    let entries = recorder.map(|r| r.entries).unwrap_or_default();
+    (result, ProfileReport { entries })
+}
+
+/// Open span; records its entry when dropped.
+pub(crate) struct Span {
+    slot: Option<usize>,
+    start: Instant,
+    allocations: u64,
+    bytes: u64,
+}
+
+impl Span {
+    pub(crate) fn new(kind: ProfileKind, label: impl Into<String>) -> Self {
+        let slot = RECORDER.with(|r| {
+            let mut r = r.borrow_mut();
+            let recorder = r.as_mut()?;
+            recorder.entries.push(ProfileEntry {
+                kind,
+                label: label.into(),
+                depth: recorder.depth,
+                duration: Duration::ZERO,
+                allocations: 0,
+                allocated_bytes: 0,
+            });
+            recorder.depth += 1;
+            Some(recorder.entries.len() - 1)
+        });
+        Self {
+            slot,
+            start: Instant::now(),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(slot) = self.slot else {
+            return;
+        };
+        let duration = self.start.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - self.allocations;
+        let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - self.bytes;
+        RECORDER.with(|r| {
+            if let Some(recorder) = r.borrow_mut().as_mut() {
+                recorder.depth = recorder.depth.saturating_sub(1);
+                if let Some(entry) = recorder.entries.get_mut(slot) {
+                    entry.duration = duration;
+                    entry.allocations = allocations;
+                    entry.allocated_bytes = bytes;
+                }
+            }
+        });
+    }
+}
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Process-wide allocation count and bytes so far.
+pub(crate) fn allocation_totals() -> (u64, u64) {
+    (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+/// System allocator wrapper that counts allocations for [`ProfileEntry`].
+///
+/// ```rust,ignore
+/// #[global_allocator]
+/// static ALLOC: terrain_forge::profile::CountingAllocator = terrain_forge::profile::CountingAllocator;
+/// ```
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
@@ -0,0 +1,260 @@
+//! Python bindings (feature `python`).
+//!
+//! Build an extension module with maturin (see `pyproject.toml`):
+//!
+//! ```text
+//! maturin develop --release
+//! ```
+//!
+//! ```python
+//! import terrain_forge as tf
+//!
+//! grid = tf.generate("cellular", 80, 60, seed=42, params={"iterations": 5})
+//! grid.effect("erode")
+//! semantic = grid.extract_semantic("caves", seed=42)   # dict
+//! tiles = grid.tiles()                                  # numpy uint8, shape (60, 80)
+//! height = tf.noise_heightmap("perlin", 80, 60, seed=7, octaves=4)  # numpy float64
+//! ```
+//!
+//! Params and scripts are plain dicts (anything `json.dumps` accepts).
+
+use crate::noise::{NoiseExt, NoiseSource, Perlin, Simplex, Value, Worley};
+use crate::ops::{self, OpScript, Params};
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Rng, Tile};
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+
+/// A tile grid with optional semantic layers, exposed to Python as `Grid`.
+#[pyclass(name = "Grid", module = "terrain_forge")]
+#[derive(Clone)]
+pub struct PyGrid {
+    grid: Grid<Tile>,
+    semantic: Option<SemanticLayers>,
+}
+
+#[pymethods]
+impl PyGrid {
+    #[new]
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            grid: Grid::new(width, height),
+            semantic: None,
+        }
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    /// `True` for floor, `False` for wall, `None` if out of bounds.
+    fn get(&self, x: i32, y: i32) -> Option<bool> {
+        self.grid.get(x, y).map(|t| t.is_floor())
+    }
+
+    fn set(&mut self, x: i32, y: i32, floor: bool) -> bool {
+        self.grid
+            .set(x, y, if floor { Tile::Floor } else { Tile::Wall })
+    }
+
+    fn floor_count(&self) -> usize {
+        self.grid.count(|t| t.is_floor())
+    }
+
+    /// Runs a named algorithm on the grid.
+    #[pyo3(signature = (name, seed=None, params=None))]
+    fn generate(
+        &mut self,
+        name: &str,
+        seed: Option<u64>,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let params = to_params(params)?;
+        ops::generate(name, &mut self.grid, seed, params.as_ref()).map_err(value_error)
+    }
+
+    /// Applies a named effect, using extracted semantics when present.
+    #[pyo3(signature = (name, params=None))]
+    fn effect(&mut self, name: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let params = to_params(params)?;
+        ops::effect(
+            name,
+            &mut self.grid,
+            params.as_ref(),
+            self.semantic.as_ref(),
+        )
+        .map_err(value_error)
+    }
+
+    /// Combines another grid into this one (`union`, `intersect`, `difference`, ...).
+    fn combine(&mut self, mode: &str, other: PyRef<'_, PyGrid>) -> PyResult<()> {
+        let mode = ops::script::parse_combine_mode(mode).map_err(value_error)?;
+        ops::combine(mode, &mut self.grid, &other.grid).map_err(value_error)
+    }
+
+    /// Extracts semantic layers and returns them as a dict.
+    #[pyo3(signature = (profile="default", seed=0))]
+    fn extract_semantic<'py>(
+        &mut self,
+        py: Python<'py>,
+        profile: &str,
+        seed: u64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let extractor = ops::script::extractor_for_profile(profile).map_err(value_error)?;
+        self.semantic = Some(extractor.extract(&self.grid, &mut Rng::new(seed)));
+        self.semantic(py)
+            .map(|s| s.expect("semantic layers just extracted"))
+    }
+
+    /// The last extracted semantic layers as a dict, or `None`.
+    fn semantic<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.semantic
+            .as_ref()
+            .map(|s| {
+                let json = serde_json::to_string(s).map_err(value_error)?;
+                py.import("json")?.call_method1("loads", (json,))
+            })
+            .transpose()
+    }
+
+    /// Tiles as a `(height, width)` numpy `uint8` array, 1 for floor.
+    fn tiles<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u8>> {
+        let (w, h) = (self.grid.width(), self.grid.height());
+        let cells = self
+            .grid
+            .iter()
+            .map(|(_, _, t)| t.is_floor() as u8)
+            .collect();
+        Array2::from_shape_vec((h, w), cells)
+            .expect("grid cell count matches shape")
+            .into_pyarray(py)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Grid(width={}, height={}, floors={})",
+            self.grid.width(),
+            self.grid.height(),
+            self.floor_count()
+        )
+    }
+}
+
+/// Generates a new grid with a named algorithm.
+#[pyfunction]
+#[pyo3(signature = (name, width, height, seed=None, params=None))]
+fn generate(
+    name: &str,
+    width: usize,
+    height: usize,
+    seed: Option<u64>,
+    params: Option<&Bound<'_, PyAny>>,
+) -> PyResult<PyGrid> {
+    let mut grid = PyGrid::new(width, height);
+    grid.generate(name, seed, params)?;
+    Ok(grid)
+}
+
+/// Runs an op script (JSON string or dict) and returns the resulting grid.
+#[pyfunction]
+fn run_script(script: &Bound<'_, PyAny>) -> PyResult<PyGrid> {
+    let json = to_json(script)?;
+    let output = OpScript::from_json(&json)
+        .and_then(|script| script.run())
+        .map_err(value_error)?;
+    Ok(PyGrid {
+        grid: output.grid,
+        semantic: output.semantic,
+    })
+}
+
+/// Lists built-in algorithm names.
+#[pyfunction]
+fn algorithms() -> Vec<&'static str> {
+    crate::algorithms::list().to_vec()
+}
+
+/// Samples a noise field as a `(height, width)` numpy `float64` array in
+/// roughly `[-1, 1]`.
+#[pyfunction]
+#[pyo3(signature = (kind, width, height, seed=0, frequency=0.05, octaves=1))]
+fn noise_heightmap<'py>(
+    py: Python<'py>,
+    kind: &str,
+    width: usize,
+    height: usize,
+    seed: u64,
+    frequency: f64,
+    octaves: u32,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    fn sample<N: NoiseSource>(noise: N, w: usize, h: usize, f: f64, octaves: u32) -> Vec<f64> {
+        let noise = noise.fbm(octaves.max(1), 2.0, 0.5);
+        (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .map(|(x, y)| noise.sample(x as f64 * f, y as f64 * f))
+            .collect()
+    }
+    let values = match kind.trim().to_ascii_lowercase().as_str() {
+        "perlin" => sample(Perlin::new(seed), width, height, frequency, octaves),
+        "simplex" => sample(Simplex::new(seed), width, height, frequency, octaves),
+        "value" => sample(Value::new(seed), width, height, frequency, octaves),
+        "worley" => sample(Worley::new(seed), width, height, frequency, octaves),
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown noise kind: {}",
+                other
+            )))
+        }
+    };
+    Ok(Array2::from_shape_vec((height, width), values)
+        .expect("sample count matches shape")
+        .into_pyarray(py))
+}
+
+/// The `terrain_forge` Python module.
+#[pymodule]
+pub fn terrain_forge(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGrid>()?;
+    m.add_function(wrap_pyfunction!(generate, m)?)?;
+    m.add_function(wrap_pyfunction!(run_script, m)?)?;
+    m.add_function(wrap_pyfunction!(algorithms, m)?)?;
+    m.add_function(wrap_pyfunction!(noise_heightmap, m)?)?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
+
+fn value_error(e: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Serializes a Python object (or passes through a string) as JSON.
+fn to_json(obj: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(s) = obj.cast::<PyString>() {
+        return Ok(s.to_str()?.to_string());
+    }
+    obj.py()
+        .import("json")?
+        .call_method1("dumps", (obj,))?
+        .extract()
+}
+
+fn to_params(params: Option<&Bound<'_, PyAny>>) -> PyResult<Option<Params>> {
+    match params.filter(|p| !p.is_none()) {
+        None => Ok(None),
+        Some(obj) => {
+            let json = to_json(obj)?;
+            serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| PyValueError::new_err(format!("Invalid params: {}", e)))
+        }
+    }
+}
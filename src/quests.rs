@@ -0,0 +1,235 @@
+//! Quest scaffolds derived from map structure.
+//!
+//! [`generate`] turns semantic layers into a quest skeleton: it starts at
+//! the spawn marker, ranks every other marker by walking distance, and picks
+//! one objective from each successive distance band, so steps get further
+//! away (and harder) as the chain goes on. Each step's kind follows its
+//! marker: loot becomes a fetch, enemies and encounters a kill, anything
+//! else a reach. The result is plain serializable data, and
+//! [`Quest::apply`] adds the matching `QuestStart`/`QuestObjective`/
+//! `QuestEnd` markers.
+//!
+//! ```rust
+//! use terrain_forge::quests::{self, QuestConfig};
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("bsp").unwrap().generate(&mut grid, 2);
+//! let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(2));
+//!
+//! if let Some(quest) = quests::generate(&grid, &semantic, &QuestConfig::default(), &mut Rng::new(2)) {
+//!     let json = serde_json::to_string(&quest).unwrap();
+//!     assert!(json.contains("steps"));
+//! }
+//! ```
+
+use crate::semantic::{meta, Marker, MarkerType, SemanticLayers};
+use crate::spatial::{dijkstra_map, PathfindingConstraints};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+
+/// What a quest step asks of the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    /// Pick up the item at the marker.
+    Fetch,
+    /// Defeat whatever guards the marker.
+    Kill,
+    /// Get to the marker.
+    Reach,
+}
+
+impl StepKind {
+    /// Step kind suggested by a marker's type or tag.
+    pub fn for_marker(marker: &Marker) -> Self {
+        match &marker.marker_type {
+            MarkerType::LootTier { .. } | MarkerType::Treasure => Self::Fetch,
+            MarkerType::EncounterZone { .. } | MarkerType::BossRoom => Self::Kill,
+            MarkerType::Custom(tag) => {
+                let tag = tag.to_ascii_lowercase();
+                let has = |words: &[&str]| words.iter().any(|w| tag.contains(w));
+                if has(&["treasure", "loot", "chest", "item"]) {
+                    Self::Fetch
+                } else if has(&["enemy", "boss", "encounter", "guard", "patrol", "ambush"]) {
+                    Self::Kill
+                } else {
+                    Self::Reach
+                }
+            }
+            _ => Self::Reach,
+        }
+    }
+}
+
+/// One objective in a quest chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestStep {
+    pub kind: StepKind,
+    /// Position of the referenced marker.
+    pub x: u32,
+    pub y: u32,
+    /// Tag of the referenced marker.
+    pub marker: String,
+    /// Region containing the marker, if known.
+    pub region: Option<u32>,
+    /// Walking distance from the quest start.
+    pub distance: usize,
+    /// 1 for the first step, rising by one per step.
+    pub difficulty: u8,
+}
+
+/// A quest skeleton.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quest {
+    pub start: (u32, u32),
+    pub steps: Vec<QuestStep>,
+}
+
+impl Quest {
+    /// Adds a `QuestStart` marker at the start, a `QuestObjective` per step
+    /// (priority = step number), and a `QuestEnd` on the last step.
+    ///
+    /// Objectives carry the step's number, kind, target tag, and difficulty
+    /// under the `quest.*` [`meta`] keys.
+    pub fn apply(&self, semantic: &mut SemanticLayers) {
+        let (sx, sy) = self.start;
+        semantic
+            .markers
+            .push(Marker::new(sx, sy, MarkerType::QuestStart));
+        for (i, step) in self.steps.iter().enumerate() {
+            let priority = (i + 1).min(u8::MAX as usize) as u8;
+            let mut marker = Marker::new(step.x, step.y, MarkerType::QuestObjective { priority })
+                .with_metadata(meta::QUEST_STEP, (i + 1).to_string())
+                .with_metadata(
+                    meta::QUEST_KIND,
+                    format!("{:?}", step.kind).to_ascii_lowercase(),
+                )
+                .with_metadata(meta::QUEST_TARGET, step.marker.clone())
+                .with_metadata(meta::QUEST_DIFFICULTY, step.difficulty.to_string());
+            if let Some(region) = step.region {
+                marker = marker.with_region(region);
+            }
+            semantic.markers.push(marker);
+        }
+        if let Some(last) = self.steps.last() {
+            semantic
+                .markers
+                .push(Marker::new(last.x, last.y, MarkerType::QuestEnd));
+        }
+    }
+}
+
+/// Configuration for [`generate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuestConfig {
+    /// Number of steps. Default: 3.
+    pub steps: usize,
+    /// Objectives closer than this to the start are skipped. Default: 5.
+    pub min_distance: usize,
+}
+
+impl Default for QuestConfig {
+    fn default() -> Self {
+        Self {
+            steps: 3,
+            min_distance: 5,
+        }
+    }
+}
+
+impl QuestConfig {
+    /// Sets `steps`.
+    pub fn with_steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Sets `min_distance`.
+    pub fn with_min_distance(mut self, min_distance: usize) -> Self {
+        self.min_distance = min_distance;
+        self
+    }
+}
+
+/// Derives a quest from `semantic`'s markers.
+///
+/// Starts at the first `Spawn`/`PlayerStart` marker (or `QuestStart`, or
+/// the first marker). Returns `None` when no marker is reachable from the
+/// start; chains may be shorter than `config.steps` on sparse maps.
+pub fn generate(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    config: &QuestConfig,
+    rng: &mut Rng,
+) -> Option<Quest> {
+    let start = semantic
+        .spawn()
+        .or_else(|| {
+            semantic
+                .markers
+                .iter()
+                .find(|m| m.marker_type == MarkerType::QuestStart)
+        })
+        .or_else(|| semantic.markers.first())?;
+    let origin = (start.x, start.y);
+    if !grid
+        .get(start.x as i32, start.y as i32)
+        .is_some_and(|t| t.is_floor())
+    {
+        return None;
+    }
+    let distance = dijkstra_map(
+        grid,
+        &[(start.x as usize, start.y as usize)],
+        &PathfindingConstraints::cardinal(),
+    );
+
+    let mut candidates: Vec<(&Marker, usize)> = semantic
+        .markers
+        .iter()
+        .filter(|m| (m.x, m.y) != origin && m.marker_type.category() != "quest")
+        .filter(|m| grid.in_bounds(m.x as i32, m.y as i32))
+        .filter_map(|m| {
+            let d = distance.get(m.x as usize, m.y as usize);
+            let d = d.is_finite().then_some(d as usize)?;
+            (d >= config.min_distance).then_some((m, d))
+        })
+        .collect();
+    if candidates.is_empty() || config.steps == 0 {
+        return None;
+    }
+    candidates.sort_by_key(|&(m, d)| (d, m.x, m.y));
+
+    // One pick per distance band, nearest band first.
+    let bands = config.steps.min(candidates.len());
+    let mut steps = Vec::with_capacity(bands);
+    for band in 0..bands {
+        let lo = band * candidates.len() / bands;
+        let hi = ((band + 1) * candidates.len() / bands).max(lo + 1);
+        let &(marker, d) = rng.pick(&candidates[lo..hi])?;
+        steps.push(QuestStep {
+            kind: StepKind::for_marker(marker),
+            x: marker.x,
+            y: marker.y,
+            marker: marker.tag(),
+            region: marker.region_id.or_else(|| region_of(semantic, marker)),
+            distance: d,
+            difficulty: (band + 1).min(u8::MAX as usize) as u8,
+        });
+    }
+
+    Some(Quest {
+        start: origin,
+        steps,
+    })
+}
+
+fn region_of(semantic: &SemanticLayers, marker: &Marker) -> Option<u32> {
+    semantic
+        .regions
+        .iter()
+        .find(|r| r.cells.contains(&(marker.x, marker.y)))
+        .map(|r| r.id)
+}
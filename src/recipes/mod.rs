@@ -0,0 +1,131 @@
+//! Complete generation recipes shipped as data.
+//!
+//! Each recipe is a full [`Config`] embedded in the crate as JSON: a
+//! pipeline, post-processing effects, a semantic extractor profile, and
+//! the validation thresholds and requirements [`generate_map`] retries
+//! against. They make tested starting points to copy and tweak, and
+//! [`source`] returns the JSON itself for exactly that.
+//!
+//! | Name | Map |
+//! |------|-----|
+//! | `cave_network` | Open caverns joined by winding tunnels |
+//! | `classic_dungeon` | BSP rooms and corridors without dead ends |
+//! | `island_overworld` | Archipelago with bridges between the islands |
+//! | `fortress_siege` | Mirrored fortress with balanced exits on two edges |
+//!
+//! ```rust
+//! use terrain_forge::constraints::ConstraintContext;
+//! use terrain_forge::{recipes, Grid, Rng};
+//!
+//! let recipe = recipes::get("classic_dungeon").unwrap();
+//! let mut grid = Grid::new(recipe.config.width, recipe.config.height);
+//! recipe.pipeline().unwrap().execute_seed(&mut grid, 7).unwrap();
+//! let semantic = recipe.extractor().extract(&grid, &mut Rng::new(7));
+//! let mut ctx = ConstraintContext::new(&grid);
+//! ctx.semantic = Some(&semantic);
+//! assert!(recipe.constraints().evaluate(&ctx).passed);
+//!
+//! // Or let `generate_map` do all of that, retrying seeds until it passes.
+//! let bundle = recipe.generate(7).unwrap();
+//! assert!(bundle.semantic.is_some());
+//! ```
+
+use crate::bundle::MapBundle;
+use crate::config::Config;
+use crate::constraints::ConstraintSet;
+use crate::error::{Error, Result};
+use crate::ops::OpResult;
+use crate::pipeline::Pipeline;
+use crate::{generate_map, SemanticExtractor};
+
+/// Recipe names, descriptions, and JSON sources.
+const RECIPES: [(&str, &str, &str); 4] = [
+    (
+        "cave_network",
+        "Open caverns joined by winding tunnels into one network",
+        include_str!("cave_network.json"),
+    ),
+    (
+        "classic_dungeon",
+        "BSP rooms and corridors with dead ends pruned",
+        include_str!("classic_dungeon.json"),
+    ),
+    (
+        "island_overworld",
+        "Archipelago of noise islands linked by bridges",
+        include_str!("island_overworld.json"),
+    ),
+    (
+        "fortress_siege",
+        "Mirrored fortress halls with balanced exits on two map edges",
+        include_str!("fortress_siege.json"),
+    ),
+];
+
+/// A named, ready-to-run [`Config`].
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    /// Name passed to [`get`].
+    pub name: &'static str,
+    /// One-line summary of the map it makes.
+    pub description: &'static str,
+    /// The parsed recipe; adjust it freely before generating.
+    pub config: Config,
+}
+
+impl Recipe {
+    /// Builds the recipe's pipeline, with its effects appended as steps.
+    pub fn pipeline(&self) -> OpResult<Pipeline> {
+        let mut pipeline = self.config.build_pipeline()?;
+        for effect in &self.config.effects {
+            pipeline.add_effect(effect.name(), effect.params().cloned());
+        }
+        Ok(pipeline)
+    }
+
+    /// Semantic extractor for the recipe's profile.
+    pub fn extractor(&self) -> SemanticExtractor {
+        self.config.extractor()
+    }
+
+    /// Validation thresholds, requirements, and exit balance as one set.
+    pub fn constraints(&self) -> ConstraintSet {
+        self.config.constraints()
+    }
+
+    /// Runs [`generate_map`] starting from `seed`, retrying until the
+    /// constraints pass.
+    pub fn generate(&self, seed: u64) -> Result<MapBundle> {
+        let mut config = self.config.clone();
+        config.seed = Some(seed);
+        generate_map(&config)
+    }
+}
+
+/// Names of the built-in recipes.
+pub fn list() -> impl ExactSizeIterator<Item = &'static str> {
+    RECIPES.iter().map(|(name, _, _)| *name)
+}
+
+/// The recipe called `name`, parsed. Fails for unknown names and for
+/// recipe JSON that doesn't parse.
+pub fn get(name: &str) -> Result<Recipe> {
+    let (name, description, json) = RECIPES
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .ok_or_else(|| Error::new(format!("Unknown recipe: {}", name)))?;
+    Ok(Recipe {
+        name,
+        description,
+        config: Config::from_json(json)?,
+    })
+}
+
+/// JSON source of the recipe called `name`, for copying into a config
+/// file.
+pub fn source(name: &str) -> Option<&'static str> {
+    RECIPES
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, _, json)| *json)
+}
@@ -0,0 +1,510 @@
+//! Text and ANSI terminal rendering.
+//!
+//! [`TextRender`] draws a tile grid as `#`/`.` with optional semantic
+//! overlays: region coloring and marker glyphs. A [`Legend`] picks the
+//! glyph, and optionally the color, for each marker tag and region kind;
+//! [`render_text_with_semantic`] adds its key below the map for log
+//! output. [`Heatmap`] shades value
+//! grids such as distance fields and heightmaps. Both implement
+//! [`Display`](std::fmt::Display), so they work with `print!` and
+//! `to_string`; ANSI escapes are only written with `with_color(true)`.
+//!
+//! ```rust
+//! use terrain_forge::render::TextRender;
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(40, 20);
+//! algorithms::get("cellular").unwrap().generate(&mut grid, 3);
+//! let semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(3));
+//!
+//! let plain = TextRender::new(&grid).to_string();
+//! assert_eq!(plain, grid.to_string());
+//! let colored = TextRender::new(&grid).with_semantic(&semantic).with_color(true);
+//! println!("{}", colored);
+//!
+//! use terrain_forge::render::{self, Legend, LegendEntry};
+//! let legend = Legend::default()
+//!     .with_marker("loot_slot", LegendEntry::new("%").with_color([255, 215, 0]))
+//!     .with_region("cave", LegendEntry::new(","));
+//! println!("{}", render::render_text_with_semantic(&grid, &semantic, &legend));
+//! ```
+
+use crate::semantic::SemanticLayers;
+use crate::spatial::{DijkstraMap, DistanceTransform};
+use crate::tileset::{TileId, TileSet};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+const RESET: &str = "\x1b[0m";
+const WALL_STYLE: &str = "\x1b[90m";
+const FLOOR_STYLE: &str = "\x1b[37m";
+const MARKER_STYLE: &str = "\x1b[1;93m";
+/// Foreground colors cycled through by region index.
+const REGION_STYLES: [&str; 6] = [
+    "\x1b[34m", "\x1b[32m", "\x1b[35m", "\x1b[36m", "\x1b[31m", "\x1b[33m",
+];
+/// Plain-text shading ramp, low to high.
+const RAMP: &[u8] = b" .:-=+*#%@";
+/// 256-color palette indexes from blue (low) through green to red (high).
+const HEAT: [u8; 16] = [
+    17, 19, 21, 27, 33, 39, 45, 50, 48, 46, 118, 190, 226, 214, 208, 196,
+];
+
+/// Marker tags in [`Legend::default`], with their glyphs.
+const DEFAULT_MARKERS: [(&str, &str); 7] = [
+    ("loot_slot", "$"),
+    ("boss_spawn", "B"),
+    ("light_anchor", "*"),
+    ("terminal_a", "A"),
+    ("terminal_b", "B"),
+    ("stairs_up", "<"),
+    ("stairs_down", ">"),
+];
+
+/// Text glyph for a marker tag in the default [`Legend`].
+pub fn marker_glyph(tag: &str) -> char {
+    DEFAULT_MARKERS
+        .iter()
+        .find(|(t, _)| *t == tag)
+        .and_then(|(_, glyph)| glyph.chars().next())
+        .unwrap_or('?')
+}
+
+/// Glyph and optional color for one legend item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegendEntry {
+    /// Text drawn in the cell; an emoji or any other short string.
+    pub glyph: String,
+    /// 24-bit foreground color, used in color mode only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<[u8; 3]>,
+}
+
+impl LegendEntry {
+    /// Uncolored entry drawn as `glyph`.
+    pub fn new(glyph: impl Into<String>) -> Self {
+        Self {
+            glyph: glyph.into(),
+            color: None,
+        }
+    }
+
+    /// Sets `color`.
+    pub fn with_color(mut self, color: [u8; 3]) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    fn style(&self) -> Option<String> {
+        self.color
+            .map(|[r, g, b]| format!("\x1b[38;2;{};{};{}m", r, g, b))
+    }
+}
+
+/// Glyphs for markers, region kinds, and tiles in [`TextRender`].
+///
+/// Marker entries are keyed by tag; a tag with no entry of its own falls
+/// back to the entry for the tag minus a trailing `_<number>`, so
+/// `"loot_tier"` covers `"loot_tier_1"`, `"loot_tier_2"`, and so on.
+/// Region entries replace the floor glyph inside regions of that kind.
+/// Terminals draw most emoji two columns wide, so an emoji legend should
+/// give the wall and floor emoji too to keep columns aligned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Legend {
+    /// Entries by marker tag. Default: the tags in [`marker_glyph`].
+    pub markers: BTreeMap<String, LegendEntry>,
+    /// Entries by region kind. Default: none.
+    pub regions: BTreeMap<String, LegendEntry>,
+    /// Entry for markers with no matching tag. Default: `?`.
+    pub unknown: LegendEntry,
+    /// Wall entry, overriding the render's wall character. Default: none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wall: Option<LegendEntry>,
+    /// Floor entry, overriding the render's floor character. Default: none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor: Option<LegendEntry>,
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        DEFAULT_MARKERS
+            .iter()
+            .fold(Self::empty(), |legend, &(tag, glyph)| {
+                legend.with_marker(tag, LegendEntry::new(glyph))
+            })
+    }
+}
+
+impl Legend {
+    /// Legend with no marker or region entries; every marker draws as `?`.
+    pub fn empty() -> Self {
+        Self {
+            markers: BTreeMap::new(),
+            regions: BTreeMap::new(),
+            unknown: LegendEntry::new("?"),
+            wall: None,
+            floor: None,
+        }
+    }
+
+    /// Sets the entry for marker `tag`.
+    pub fn with_marker(mut self, tag: impl Into<String>, entry: LegendEntry) -> Self {
+        self.markers.insert(tag.into(), entry);
+        self
+    }
+
+    /// Sets the entry for regions of `kind`.
+    pub fn with_region(mut self, kind: impl Into<String>, entry: LegendEntry) -> Self {
+        self.regions.insert(kind.into(), entry);
+        self
+    }
+
+    /// Sets `unknown`.
+    pub fn with_unknown(mut self, entry: LegendEntry) -> Self {
+        self.unknown = entry;
+        self
+    }
+
+    /// Sets the wall and floor entries.
+    pub fn with_tiles(mut self, wall: LegendEntry, floor: LegendEntry) -> Self {
+        self.wall = Some(wall);
+        self.floor = Some(floor);
+        self
+    }
+
+    /// Entry for marker `tag`, falling back to its untiered tag and then
+    /// to `unknown`.
+    pub fn marker(&self, tag: &str) -> &LegendEntry {
+        self.markers
+            .get(tag)
+            .or_else(|| {
+                let (base, suffix) = tag.rsplit_once('_')?;
+                let numeric = !suffix.is_empty() && suffix.bytes().all(|b| b.is_ascii_digit());
+                numeric.then(|| self.markers.get(base)).flatten()
+            })
+            .unwrap_or(&self.unknown)
+    }
+
+    /// Entry for regions of `kind`, if any.
+    pub fn region(&self, kind: &str) -> Option<&LegendEntry> {
+        self.regions.get(kind)
+    }
+
+    /// Key for the markers and region kinds present in `semantic`, one
+    /// `glyph name` line each: marker tags first, then region kinds with
+    /// an entry, both sorted.
+    pub fn key(&self, semantic: &SemanticLayers) -> String {
+        let tags: BTreeSet<String> = semantic.markers.iter().map(|m| m.tag()).collect();
+        let kinds: BTreeSet<&str> = semantic
+            .regions
+            .iter()
+            .map(|r| r.kind.as_str())
+            .filter(|kind| self.regions.contains_key(*kind))
+            .collect();
+        let mut out = String::new();
+        for tag in &tags {
+            out.push_str(&format!("{} {}\n", self.marker(tag).glyph, tag));
+        }
+        for kind in kinds {
+            out.push_str(&format!("{} {}\n", self.regions[kind].glyph, kind));
+        }
+        out
+    }
+}
+
+/// Renders `grid` with `semantic` overlays and `legend` glyphs, followed
+/// by a blank line and the legend [`key`](Legend::key) when any markers
+/// or keyed regions are present. Plain text, newline-terminated; suited
+/// to log files.
+pub fn render_text_with_semantic(
+    grid: &Grid<Tile>,
+    semantic: &SemanticLayers,
+    legend: &Legend,
+) -> String {
+    let mut out = TextRender::new(grid)
+        .with_semantic(semantic)
+        .with_legend(legend)
+        .to_string();
+    out.push('\n');
+    let key = legend.key(semantic);
+    if !key.is_empty() {
+        out.push('\n');
+        out.push_str(&key);
+    }
+    out
+}
+
+/// Displays a tile grid, optionally with semantic overlays and ANSI color.
+#[derive(Debug, Clone, Copy)]
+pub struct TextRender<'a> {
+    grid: &'a Grid<Tile>,
+    semantic: Option<&'a SemanticLayers>,
+    color: bool,
+    regions: bool,
+    markers: bool,
+    wall_char: char,
+    floor_char: char,
+    legend: Option<&'a Legend>,
+}
+
+impl<'a> TextRender<'a> {
+    /// Plain `#`/`.` rendering of `grid`.
+    pub fn new(grid: &'a Grid<Tile>) -> Self {
+        Self {
+            grid,
+            semantic: None,
+            color: false,
+            regions: true,
+            markers: true,
+            wall_char: '#',
+            floor_char: '.',
+            legend: None,
+        }
+    }
+
+    /// Overlays regions (color mode only) and marker glyphs.
+    pub fn with_semantic(mut self, semantic: &'a SemanticLayers) -> Self {
+        self.semantic = Some(semantic);
+        self
+    }
+
+    /// Emits ANSI color escapes.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Enables or disables the region and marker overlays.
+    pub fn with_overlays(mut self, regions: bool, markers: bool) -> Self {
+        self.regions = regions;
+        self.markers = markers;
+        self
+    }
+
+    /// Sets the wall and floor characters.
+    pub fn with_chars(mut self, wall: char, floor: char) -> Self {
+        self.wall_char = wall;
+        self.floor_char = floor;
+        self
+    }
+
+    /// Draws markers, regions, and tiles with `legend` instead of the
+    /// default [`Legend`].
+    pub fn with_legend(mut self, legend: &'a Legend) -> Self {
+        self.legend = Some(legend);
+        self
+    }
+
+    /// Takes the wall and floor characters from a tile set.
+    pub fn with_tileset(self, tileset: &TileSet) -> Self {
+        let glyph = |id, fallback| tileset.get(id).map_or(fallback, |t| t.glyph);
+        self.with_chars(glyph(TileId::WALL, '#'), glyph(TileId::FLOOR, '.'))
+    }
+}
+
+impl fmt::Display for TextRender<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let default;
+        let legend = match self.legend {
+            Some(legend) => legend,
+            None => {
+                default = Legend::default();
+                &default
+            }
+        };
+        let (w, h) = (self.grid.width(), self.grid.height());
+        let mut region_of: Vec<Option<usize>> = Vec::new();
+        let mut glyphs: Vec<Option<&LegendEntry>> = Vec::new();
+        // Glyph override and style for each region.
+        let mut region_looks: Vec<(Option<&str>, Cow<str>)> = Vec::new();
+        if let Some(semantic) = self.semantic {
+            if self.regions && (self.color || !legend.regions.is_empty()) {
+                region_of = vec![None; w * h];
+                for (i, region) in semantic.regions.iter().enumerate() {
+                    for &(x, y) in &region.cells {
+                        if let Some(slot) = region_of.get_mut(y as usize * w + x as usize) {
+                            *slot = Some(i);
+                        }
+                    }
+                }
+                region_looks = semantic
+                    .regions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, r)| {
+                        let cycled = REGION_STYLES[i % REGION_STYLES.len()];
+                        match legend.region(&r.kind) {
+                            Some(entry) => (
+                                Some(entry.glyph.as_str()),
+                                entry.style().map_or(Cow::Borrowed(cycled), Cow::Owned),
+                            ),
+                            None => (None, Cow::Borrowed(cycled)),
+                        }
+                    })
+                    .collect();
+            }
+            if self.markers {
+                glyphs = vec![None; w * h];
+                for marker in &semantic.markers {
+                    let (x, y) = (marker.x as usize, marker.y as usize);
+                    if x < w && y < h {
+                        glyphs[y * w + x] = Some(legend.marker(&marker.tag()));
+                    }
+                }
+            }
+        }
+        let tile = |entry: &Option<LegendEntry>, fallback: char, style: &str| match entry {
+            Some(entry) => (entry.glyph.clone(), entry.style().unwrap_or(style.into())),
+            None => (fallback.to_string(), style.to_string()),
+        };
+        let wall = tile(&legend.wall, self.wall_char, WALL_STYLE);
+        let floor = tile(&legend.floor, self.floor_char, FLOOR_STYLE);
+
+        for y in 0..h {
+            let mut style: Cow<str> = Cow::Borrowed("");
+            for x in 0..w {
+                let i = y * w + x;
+                let (glyph, next_style): (&str, Cow<str>) = match glyphs.get(i).copied().flatten() {
+                    Some(entry) => (
+                        &entry.glyph,
+                        entry
+                            .style()
+                            .map_or(Cow::Borrowed(MARKER_STYLE), Cow::Owned),
+                    ),
+                    None if self.grid[(x, y)].is_floor() => {
+                        match region_of.get(i).copied().flatten() {
+                            Some(r) => {
+                                let (glyph, style) = &region_looks[r];
+                                (glyph.unwrap_or(&floor.0), Cow::Borrowed(style.as_ref()))
+                            }
+                            None => (&floor.0, Cow::Borrowed(&floor.1)),
+                        }
+                    }
+                    None => (&wall.0, Cow::Borrowed(&wall.1)),
+                };
+                if self.color && next_style != style {
+                    f.write_str(&next_style)?;
+                    style = next_style;
+                }
+                f.write_str(glyph)?;
+            }
+            if self.color {
+                f.write_str(RESET)?;
+            }
+            if y + 1 < h {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Displays a grid of values shaded from low to high.
+///
+/// Non-finite values (such as unreachable cells in a [`DijkstraMap`]) are
+/// drawn blank.
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    values: Vec<f64>,
+    width: usize,
+    height: usize,
+    color: bool,
+}
+
+impl Heatmap {
+    /// Samples `value(x, y)` for every cell.
+    pub fn from_fn(width: usize, height: usize, value: impl Fn(usize, usize) -> f64) -> Self {
+        let values = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| value(x, y))
+            .collect();
+        Self {
+            values,
+            width,
+            height,
+            color: false,
+        }
+    }
+
+    /// Row-major values, as returned by heightmap generators.
+    pub fn from_rows(rows: &[Vec<f64>]) -> Self {
+        let width = rows.first().map_or(0, Vec::len);
+        Self::from_fn(width, rows.len(), |x, y| {
+            rows[y].get(x).copied().unwrap_or(f64::NAN)
+        })
+    }
+
+    /// Emits ANSI 256-color escapes instead of the ASCII ramp.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Finite `(min, max)`, or `None` if no value is finite.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.values
+            .iter()
+            .filter(|v| v.is_finite())
+            .fold(None, |acc, &v| match acc {
+                None => Some((v, v)),
+                Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+            })
+    }
+}
+
+impl From<&DistanceTransform> for Heatmap {
+    fn from(field: &DistanceTransform) -> Self {
+        Self::from_fn(field.width(), field.height(), |x, y| field.get(x, y) as f64)
+    }
+}
+
+impl From<&DijkstraMap> for Heatmap {
+    fn from(map: &DijkstraMap) -> Self {
+        Self::from_fn(map.width(), map.height(), |x, y| map.get(x, y) as f64)
+    }
+}
+
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (lo, hi) = self.range().unwrap_or((0.0, 0.0));
+        let span = if hi > lo { hi - lo } else { 1.0 };
+        for y in 0..self.height {
+            let mut last = None;
+            for x in 0..self.width {
+                let v = self.values[y * self.width + x];
+                if !v.is_finite() {
+                    if self.color && last.is_some() {
+                        f.write_str(RESET)?;
+                        last = None;
+                    }
+                    f.write_str(" ")?;
+                    continue;
+                }
+                let t = (v - lo) / span;
+                if self.color {
+                    let index =
+                        HEAT[((t * (HEAT.len() - 1) as f64).round() as usize).min(HEAT.len() - 1)];
+                    if last != Some(index) {
+                        write!(f, "\x1b[38;5;{}m", index)?;
+                        last = Some(index);
+                    }
+                    f.write_str("█")?;
+                } else {
+                    let i = ((t * (RAMP.len() - 1) as f64).round() as usize).min(RAMP.len() - 1);
+                    write!(f, "{}", RAMP[i] as char)?;
+                }
+            }
+            if self.color {
+                f.write_str(RESET)?;
+            }
+            if y + 1 < self.height {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
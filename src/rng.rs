@@ -2,42 +2,140 @@
 
 use rand::{Rng as RandRng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 /// Seeded RNG wrapper for deterministic generation.
 ///
 /// All terrain generation uses this RNG so that identical seeds produce
-/// identical output across runs and platforms.
-#[derive(Debug, Clone)]
+/// identical output across runs and platforms. Integer ranges are sampled
+/// with fixed-width types so 32- and 64-bit targets agree.
+///
+/// Serializes its full position (seed, stream, and word offset), so a
+/// restored generator continues with exactly the draws the original would
+/// have made; checkpoints rely on this.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RngState", into = "RngState")]
 pub struct Rng {
     inner: ChaCha8Rng,
 }
 
+/// Serialized form of [`Rng`].
+#[derive(Serialize, Deserialize)]
+struct RngState {
+    seed: [u8; 32],
+    stream: u64,
+    word_pos: u128,
+}
+
+impl From<Rng> for RngState {
+    fn from(rng: Rng) -> Self {
+        Self {
+            seed: rng.inner.get_seed(),
+            stream: rng.inner.get_stream(),
+            word_pos: rng.inner.get_word_pos(),
+        }
+    }
+}
+
+impl From<RngState> for Rng {
+    fn from(state: RngState) -> Self {
+        let mut inner = ChaCha8Rng::from_seed(state.seed);
+        inner.set_stream(state.stream);
+        inner.set_word_pos(state.word_pos);
+        Self { inner }
+    }
+}
+
 impl Rng {
     /// Creates a new RNG from the given seed.
     pub fn new(seed: u64) -> Self {
+        #[cfg(feature = "rng-trace")]
+        crate::rng_trace::record_generator();
         Self {
             inner: ChaCha8Rng::seed_from_u64(seed),
         }
     }
 
+    /// Returns an independent child generator for the given label.
+    ///
+    /// Advances this generator by exactly one draw regardless of how much the
+    /// child is used, so work done on a fork never shifts later draws here.
+    pub fn fork(&mut self, label: &str) -> Rng {
+        let seed = splitmix64(self.next_u64() ^ fnv1a(label.as_bytes()));
+        Rng::new(seed)
+    }
+
+    /// Returns the `n`th independent stream derived from this generator's seed.
+    ///
+    /// Does not advance this generator. Streams depend only on the seed and
+    /// `n`, so indexed work (chunks, layers) can run in any order or in
+    /// parallel with identical results.
+    pub fn stream(&self, n: u64) -> Rng {
+        let mut inner = ChaCha8Rng::from_seed(self.inner.get_seed());
+        let id = splitmix64(self.inner.get_stream() ^ splitmix64(n.wrapping_add(1)));
+        inner.set_stream(id);
+        #[cfg(feature = "rng-trace")]
+        crate::rng_trace::record_generator();
+        Self { inner }
+    }
+
     /// Returns a random `i32` in `[min, max)`.
     pub fn range(&mut self, min: i32, max: i32) -> i32 {
-        self.inner.gen_range(min..max)
+        let value = self.inner.gen_range(min..max);
+        self.note(value as u64);
+        value
+    }
+
+    /// Returns a random `i32` in `[min, max]`.
+    pub fn range_inclusive(&mut self, min: i32, max: i32) -> i32 {
+        let value = self.inner.gen_range(min..=max);
+        self.note(value as u64);
+        value
     }
 
     /// Returns a random `usize` in `[min, max)`.
     pub fn range_usize(&mut self, min: usize, max: usize) -> usize {
-        self.inner.gen_range(min..max)
+        // Sample as u64 so 32-bit targets draw the same values as 64-bit ones.
+        let value = self.inner.gen_range(min as u64..max as u64);
+        self.note(value);
+        value as usize
+    }
+
+    /// Returns a random `usize` in `[min, max]`.
+    pub fn range_usize_inclusive(&mut self, min: usize, max: usize) -> usize {
+        let value = self.inner.gen_range(min as u64..=max as u64);
+        self.note(value);
+        value as usize
+    }
+
+    /// Returns a random `f64` in `[min, max)`.
+    pub fn range_f64(&mut self, min: f64, max: f64) -> f64 {
+        min + self.random() * (max - min)
     }
 
     /// Returns a random `f64` in `[0.0, 1.0)`.
     pub fn random(&mut self) -> f64 {
-        self.inner.gen()
+        let value: f64 = self.inner.gen();
+        self.note(value.to_bits());
+        value
     }
 
     /// Returns a random `u64`.
     pub fn next_u64(&mut self) -> u64 {
-        self.inner.gen()
+        let value = self.inner.gen();
+        self.note(value);
+        value
+    }
+
+    /// Records a draw when feature `rng-trace` is on.
+    #[inline]
+    fn note(&self, _value: u64) {
+        #[cfg(feature = "rng-trace")]
+        crate::rng_trace::record_draw(
+            self.inner.get_stream(),
+            self.inner.get_word_pos() as u64,
+            _value,
+        );
     }
 
     /// Returns `true` with the given probability (0.0–1.0).
@@ -54,6 +152,75 @@ impl Rng {
         }
     }
 
+    /// Picks an index with probability proportional to its weight.
+    ///
+    /// Negative and NaN weights count as zero. Returns `None` if the slice is
+    /// empty or no weight is positive.
+    pub fn weighted_index(&mut self, weights: &[f64]) -> Option<usize> {
+        let weight = |w: f64| if w > 0.0 { w } else { 0.0 };
+        let total: f64 = weights.iter().map(|&w| weight(w)).sum();
+        if total <= 0.0 || !total.is_finite() {
+            return None;
+        }
+
+        let mut target = self.random() * total;
+        let mut last = None;
+        for (i, &w) in weights.iter().enumerate() {
+            let w = weight(w);
+            if w == 0.0 {
+                continue;
+            }
+            if target < w {
+                return Some(i);
+            }
+            target -= w;
+            last = Some(i);
+        }
+        last
+    }
+
+    /// Picks an item from `(item, weight)` pairs with probability proportional
+    /// to its weight. See [`Rng::weighted_index`].
+    pub fn pick_weighted<'a, T>(&mut self, items: &'a [(T, f64)]) -> Option<&'a T> {
+        let weights: Vec<f64> = items.iter().map(|(_, w)| *w).collect();
+        self.weighted_index(&weights).map(|i| &items[i].0)
+    }
+
+    /// Samples a normal distribution (Box-Muller).
+    ///
+    /// Uses `ln`/`cos` from the platform math library, so results may differ
+    /// in the last bits across platforms; built-in generators don't use it.
+    pub fn normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        // 1 - random() is in (0, 1], keeping ln() finite.
+        let u1 = 1.0 - self.random();
+        let u2 = self.random();
+        let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+        mean + z * std_dev
+    }
+
+    /// Samples a Poisson distribution with the given mean.
+    ///
+    /// Uses Knuth's method for small `lambda` and a rounded normal
+    /// approximation above 30. Non-positive `lambda` returns 0. Like
+    /// [`Rng::normal`], this relies on platform math functions.
+    pub fn poisson(&mut self, lambda: f64) -> u64 {
+        if lambda <= 0.0 || !lambda.is_finite() {
+            return 0;
+        }
+        if lambda > 30.0 {
+            return self.normal(lambda, lambda.sqrt()).round().max(0.0) as u64;
+        }
+
+        let limit = (-lambda).exp();
+        let mut k = 0;
+        let mut p = self.random();
+        while p > limit {
+            k += 1;
+            p *= self.random();
+        }
+        k
+    }
+
     /// Shuffles the slice in place (Fisher-Yates).
     pub fn shuffle<T>(&mut self, slice: &mut [T]) {
         for i in (1..slice.len()).rev() {
@@ -62,3 +229,149 @@ impl Rng {
         }
     }
 }
+
+/// A shareable generation seed.
+///
+/// Parses from plain numbers (`"42"`), share codes (`"1A2B-..."`, see
+/// [`share_code`](Self::share_code)), or any other text, which is hashed
+/// stably: `"salty-badger-42".parse::<Seed>()` is the same seed on every
+/// platform and release. Text is trimmed and lowercased first.
+///
+/// ```rust
+/// use terrain_forge::Seed;
+///
+/// let seed: Seed = "Salty-Badger-42".parse().unwrap();
+/// assert_eq!(seed, Seed::from_phrase("salty-badger-42"));
+/// assert_eq!(seed.to_string().parse::<Seed>().unwrap(), seed);
+/// assert_eq!("42".parse::<Seed>().unwrap().value(), 42);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Seed(pub u64);
+
+/// Crockford base32: no I, L, O, or U.
+const CODE_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+impl Seed {
+    /// Hashes a phrase into a seed.
+    pub fn from_phrase(phrase: &str) -> Self {
+        Self(splitmix64(fnv1a(phrase.trim().to_lowercase().as_bytes())))
+    }
+
+    /// Decodes a [`share_code`](Self::share_code). Case, dashes, and
+    /// spaces are ignored, and `I`/`L`/`O` read as `1`/`1`/`0`. Returns
+    /// `None` on bad characters, length, or checksum.
+    pub fn from_share_code(code: &str) -> Option<Self> {
+        let digits: Vec<u8> = code
+            .chars()
+            .filter(|c| !matches!(c, '-' | ' '))
+            .map(|c| match c.to_ascii_uppercase() {
+                'I' | 'L' => Some(1),
+                'O' => Some(0),
+                c => CODE_ALPHABET
+                    .iter()
+                    .position(|&a| a as char == c)
+                    .map(|i| i as u8),
+            })
+            .collect::<Option<_>>()?;
+        let (&check, data) = digits.split_last()?;
+        if data.len() != 13 || data[0] > 0xF {
+            return None;
+        }
+        let value = data.iter().fold(0u64, |v, &d| v << 5 | d as u64);
+        (code_check(value) == check).then_some(Self(value))
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// A generator seeded with this seed.
+    pub fn rng(self) -> Rng {
+        Rng::new(self.0)
+    }
+
+    /// Human-friendly code for the seed: 13 base32 digits plus a check
+    /// digit, grouped as `XXXX-XXXX-XXXX-XX`. Also what `Display` prints.
+    pub fn share_code(self) -> String {
+        let mut digits: Vec<u8> = (0..13)
+            .rev()
+            .map(|i| CODE_ALPHABET[(self.0 >> (i * 5) & 31) as usize])
+            .collect();
+        digits.push(CODE_ALPHABET[code_check(self.0) as usize]);
+        digits
+            .chunks(4)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Stable fingerprint of this seed together with a config, as 16 hex
+    /// digits. Two players with the same fingerprint generate the same map.
+    ///
+    /// The config is hashed through its JSON form with keys sorted, so
+    /// field order and map iteration order don't matter.
+    pub fn fingerprint<T: Serialize + ?Sized>(self, config: &T) -> String {
+        let json = serde_json::to_value(config)
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+        let hash = splitmix64(self.0 ^ fnv1a(json.as_bytes()));
+        format!("{:016x}", hash)
+    }
+}
+
+impl From<u64> for Seed {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Seed> for u64 {
+    fn from(seed: Seed) -> Self {
+        seed.0
+    }
+}
+
+impl std::fmt::Display for Seed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.share_code())
+    }
+}
+
+impl std::str::FromStr for Seed {
+    type Err = std::convert::Infallible;
+
+    /// Numbers parse as-is, valid share codes decode, anything else is
+    /// hashed with [`Seed::from_phrase`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(value) = s.parse::<u64>() {
+            return Ok(Self(value));
+        }
+        Ok(Self::from_share_code(s).unwrap_or_else(|| Self::from_phrase(s)))
+    }
+}
+
+/// Share code check digit: the value mod 31 (base32 digit `0..=30`).
+fn code_check(value: u64) -> u8 {
+    (value % 31) as u8
+}
+
+/// Stateless per-cell value in `[0, 1)`, for position-local randomness.
+pub(crate) fn cell_unit(seed: u64, x: usize, y: usize) -> f64 {
+    let h = splitmix64(seed ^ splitmix64((x as u64) << 32 | y as u64));
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
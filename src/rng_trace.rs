@@ -0,0 +1,292 @@
+//! RNG draw recording for determinism debugging (feature `rng-trace`).
+//!
+//! [`capture`] runs a closure and returns an [`RngTrace`] with one
+//! [`TraceSpan`] per algorithm, effect, and pipeline step executed on the
+//! calling thread, listing every [`Rng`](crate::Rng) draw made inside it
+//! with the generator's stream position. [`RngTrace::diff`] compares two
+//! traces and reports the first span and draw where they part ways, which
+//! turns "same seed, different map" into "the 312th draw of `cellular`".
+//! [`replay`] does both in one call against a saved trace.
+//!
+//! Draws on other threads (feature `parallel`) are not recorded.
+//!
+//! ```rust
+//! use terrain_forge::{ops, rng_trace, Grid};
+//!
+//! let run = |seed: u64| {
+//!     rng_trace::capture(|| {
+//!         let mut grid = Grid::new(40, 30);
+//!         ops::generate("cellular", &mut grid, Some(7), None).unwrap();
+//!         ops::generate("drunkard", &mut grid, Some(seed), None).unwrap();
+//!     })
+//!     .1
+//! };
+//!
+//! let baseline = run(1);
+//! assert!(baseline.diff(&run(1)).is_none());
+//! let divergence = baseline.diff(&run(2)).unwrap();
+//! assert_eq!(divergence.label, "drunkard");
+//! println!("{}", divergence);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+
+/// What a trace span covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TraceKind {
+    Algorithm,
+    Effect,
+    PipelineStep,
+    /// Draws made outside any algorithm, effect, or step.
+    Other,
+}
+
+/// One RNG draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RngDraw {
+    /// Stream id of the generator that drew.
+    pub stream: u64,
+    /// Generator word position after the draw.
+    pub position: u64,
+    /// Raw bits of the returned value (floats as `to_bits`).
+    pub value: u64,
+}
+
+/// Draws made directly inside one algorithm, effect, or step; draws in
+/// nested spans are recorded there instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceSpan {
+    pub kind: TraceKind,
+    pub label: String,
+    /// Nesting depth; a pipeline step running an algorithm is depth 0, the algorithm depth 1.
+    pub depth: usize,
+    /// Generators created (`Rng::new`, `stream`, `fork`).
+    pub generators: u64,
+    pub draws: Vec<RngDraw>,
+}
+
+/// Spans recorded by [`capture`], in start order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RngTrace {
+    pub spans: Vec<TraceSpan>,
+}
+
+impl RngTrace {
+    /// Total draws across all spans.
+    pub fn total_draws(&self) -> usize {
+        self.spans.iter().map(|s| s.draws.len()).sum()
+    }
+
+    /// `(label, draws)` per span.
+    pub fn counts(&self) -> Vec<(&str, usize)> {
+        self.spans
+            .iter()
+            .map(|s| (s.label.as_str(), s.draws.len()))
+            .collect()
+    }
+
+    /// First point where `other` stops matching this trace, or `None` if
+    /// both made the same draws in the same spans.
+    pub fn diff(&self, other: &RngTrace) -> Option<Divergence> {
+        for (index, (a, b)) in self.spans.iter().zip(&other.spans).enumerate() {
+            if a.kind != b.kind || a.label != b.label {
+                return Some(Divergence {
+                    span: index,
+                    label: a.label.clone(),
+                    draw: None,
+                    reason: format!("span is '{}' in the other run", b.label),
+                });
+            }
+            let differs = a.draws.iter().zip(&b.draws).position(|(x, y)| x != y);
+            let (draw, reason) = match differs {
+                Some(draw) => {
+                    let (x, y) = (a.draws[draw], b.draws[draw]);
+                    let reason = if x.stream != y.stream || x.position != y.position {
+                        format!(
+                            "draw came from stream {} at position {}, was stream {} at {}",
+                            y.stream, y.position, x.stream, x.position
+                        )
+                    } else {
+                        format!("drew {:#x}, was {:#x}", y.value, x.value)
+                    };
+                    (Some(draw), reason)
+                }
+                None if a.draws.len() != b.draws.len() => (
+                    Some(a.draws.len().min(b.draws.len())),
+                    format!("made {} draws, was {}", b.draws.len(), a.draws.len()),
+                ),
+                None if a.generators != b.generators => (
+                    None,
+                    format!("created {} generators, was {}", b.generators, a.generators),
+                ),
+                None => continue,
+            };
+            return Some(Divergence {
+                span: index,
+                label: a.label.clone(),
+                draw,
+                reason,
+            });
+        }
+        let (a, b) = (self.spans.len(), other.spans.len());
+        (a != b).then(|| Divergence {
+            span: a.min(b),
+            label: self
+                .spans
+                .get(b)
+                .or_else(|| other.spans.get(a))
+                .map(|s| s.label.clone())
+                .unwrap_or_default(),
+            draw: None,
+            reason: format!("other run has {} spans, was {}", b, a),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_json(json: &str) -> crate::error::Result<Self> {
+        serde_json::from_str(json).map_err(|e| crate::Error::parse("RNG trace", e))
+    }
+}
+
+/// Where two traces diverge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Divergence {
+    /// Index into [`RngTrace::spans`].
+    pub span: usize,
+    pub label: String,
+    /// Index of the first differing draw in the span, if the spans match.
+    pub draw: Option<usize>,
+    pub reason: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "span {} ('{}')", self.span, self.label)?;
+        if let Some(draw) = self.draw {
+            write!(f, ", draw {}", draw)?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+struct Recorder {
+    spans: Vec<TraceSpan>,
+    /// Indices of open spans, innermost last.
+    open: Vec<usize>,
+}
+
+impl Recorder {
+    /// Span that receives draws right now, opening an `Other` span when
+    /// nothing else is open.
+    fn current(&mut self) -> &mut TraceSpan {
+        let index = match self.open.last() {
+            Some(&index) => index,
+            None => {
+                let reuse = self
+                    .spans
+                    .last()
+                    .is_some_and(|s| s.kind == TraceKind::Other && s.depth == 0);
+                if !reuse {
+                    self.spans.push(TraceSpan {
+                        kind: TraceKind::Other,
+                        label: "outside".to_string(),
+                        depth: 0,
+                        generators: 0,
+                        draws: Vec::new(),
+                    });
+                }
+                self.spans.len() - 1
+            }
+        };
+        &mut self.spans[index]
+    }
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, recording RNG draws on this thread, and returns its result
+/// with the trace. Nested calls report to the innermost `capture`.
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, RngTrace) {
+    let previous = RECORDER.with(|r| {
+        r.borrow_mut().replace(Recorder {
+            spans: Vec::new(),
+            open: Vec::new(),
+        })
+    });
+    let result = f();
+    let recorder = RECORDER.with(|r| std::mem::replace(&mut *r.borrow_mut(), previous));
+    let spans = recorder.map(|r| r.spans).unwrap_or_default();
+    (result, RngTrace { spans })
+}
+
+/// Runs `f` under [`capture`] and diffs its trace against `expected`.
+pub fn replay<R>(expected: &RngTrace, f: impl FnOnce() -> R) -> (R, Option<Divergence>) {
+    let (result, trace) = capture(f);
+    (result, expected.diff(&trace))
+}
+
+pub(crate) fn record_draw(stream: u64, position: u64, value: u64) {
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            recorder.current().draws.push(RngDraw {
+                stream,
+                position,
+                value,
+            });
+        }
+    });
+}
+
+pub(crate) fn record_generator() {
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            recorder.current().generators += 1;
+        }
+    });
+}
+
+/// Open span; closes when dropped.
+pub(crate) struct Span {
+    open: bool,
+}
+
+impl Span {
+    pub(crate) fn new(kind: TraceKind, label: impl Into<String>) -> Self {
+        let open = RECORDER.with(|r| {
+            let mut r = r.borrow_mut();
+            let Some(recorder) = r.as_mut() else {
+                return false;
+            };
+            recorder.spans.push(TraceSpan {
+                kind,
+                label: label.into(),
+                depth: recorder.open.len(),
+                generators: 0,
+                draws: Vec::new(),
+            });
+            recorder.open.push(recorder.spans.len() - 1);
+            true
+        });
+        Self { open }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if !self.open {
+            return;
+        }
+        RECORDER.with(|r| {
+            if let Some(recorder) = r.borrow_mut().as_mut() {
+                recorder.open.pop();
+            }
+        });
+    }
+}
@@ -0,0 +1,130 @@
+//! Seed search: generate many candidates, keep the best.
+//!
+//! [`sweep`] generates one map per seed (in parallel with feature
+//! `parallel`), scores each with a [`Scorer`], and returns the top `k`.
+//! Only scores are held during the sweep; the winners are regenerated at the
+//! end, so `generator` must be deterministic per seed.
+//!
+//! ```rust
+//! use terrain_forge::constraints::{ConnectivityConstraint, ConstraintSet};
+//! use terrain_forge::{algorithms, search, Grid};
+//!
+//! let mut constraints = ConstraintSet::new();
+//! constraints.push(ConnectivityConstraint::new(0.8));
+//!
+//! let best = search::sweep(
+//!     |seed| {
+//!         let mut grid = Grid::new(60, 40);
+//!         algorithms::get("cellular").unwrap().generate(&mut grid, seed);
+//!         grid
+//!     },
+//!     &constraints,
+//!     0..50,
+//!     5,
+//! );
+//! assert_eq!(best.len(), 5);
+//! ```
+
+use crate::constraints::{ConstraintContext, ConstraintReport, ConstraintSet};
+use crate::{parallel, Grid, Tile};
+
+/// Score for one candidate map.
+#[derive(Debug, Clone)]
+pub struct Score {
+    /// Higher is better.
+    pub value: f64,
+    /// Passing candidates always rank above failing ones.
+    pub passed: bool,
+    /// Constraint report, when the scorer evaluates constraints.
+    pub report: Option<ConstraintReport>,
+}
+
+impl Score {
+    pub fn new(value: f64) -> Self {
+        Self {
+            value,
+            passed: true,
+            report: None,
+        }
+    }
+}
+
+/// Scores generated maps for [`sweep`].
+pub trait Scorer: Sync {
+    fn score(&self, grid: &Grid<Tile>) -> Score;
+}
+
+/// Mean constraint score; passes when every constraint passes.
+impl Scorer for ConstraintSet {
+    fn score(&self, grid: &Grid<Tile>) -> Score {
+        let report = self.evaluate(&ConstraintContext::new(grid));
+        let value = if report.results.is_empty() {
+            1.0
+        } else {
+            report
+                .results
+                .iter()
+                .map(|r| r.result.score as f64)
+                .sum::<f64>()
+                / report.results.len() as f64
+        };
+        Score {
+            value,
+            passed: report.passed,
+            report: Some(report),
+        }
+    }
+}
+
+/// Any metric closure; the candidate always passes.
+impl<F> Scorer for F
+where
+    F: Fn(&Grid<Tile>) -> f64 + Sync,
+{
+    fn score(&self, grid: &Grid<Tile>) -> Score {
+        Score::new(self(grid))
+    }
+}
+
+/// A kept map with its seed and score.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub seed: u64,
+    pub grid: Grid<Tile>,
+    pub score: Score,
+}
+
+/// Generates a map for every seed and returns the `top_k` best, best first.
+///
+/// Ranking: passing before failing, then higher score, then lower seed.
+/// Scores compare with [`f64::total_cmp`], so the ranking stays a total
+/// order (and the top-K deterministic) even when a scorer returns NaN.
+pub fn sweep<G, S>(
+    generator: G,
+    scorer: &S,
+    seeds: impl IntoIterator<Item = u64>,
+    top_k: usize,
+) -> Vec<Candidate>
+where
+    G: Fn(u64) -> Grid<Tile> + Sync + Send,
+    S: Scorer + ?Sized,
+{
+    let seeds: Vec<u64> = seeds.into_iter().collect();
+    let mut scored = parallel::map(seeds.len(), |i| {
+        (seeds[i], scorer.score(&generator(seeds[i])))
+    });
+    scored.sort_by(|(seed_a, a), (seed_b, b)| {
+        b.passed
+            .cmp(&a.passed)
+            .then_with(|| b.value.total_cmp(&a.value))
+            .then_with(|| seed_a.cmp(seed_b))
+    });
+    scored.truncate(top_k);
+
+    let grids = parallel::map(scored.len(), |i| generator(scored[i].0));
+    scored
+        .into_iter()
+        .zip(grids)
+        .map(|((seed, score), grid)| Candidate { seed, grid, score })
+        .collect()
+}
@@ -14,12 +14,24 @@
 //! let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut rng);
 //! println!("Regions: {}", semantic.regions.len());
 //! ```
-
-use crate::{Grid, Tile};
-use std::collections::HashMap;
+//!
+//! ## Marker metadata
+//!
+//! [`Marker::metadata`] keys are namespaced as `namespace.name`: built-in
+//! passes write under the namespaces in [`meta`] (`loot`, `encounter`,
+//! `quest`), and tools should pick a namespace of their own (say
+//! `mygame.faction`) so their keys never collide. Values are strings;
+//! numbers are written in decimal and points as `x,y`, which
+//! [`Marker::get_i64`], [`Marker::get_f64`], and [`Marker::get_point`]
+//! read back.
+
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Configuration for semantic layer generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SemanticConfig {
     /// Size thresholds for region classification
     pub size_thresholds: Vec<(usize, String)>,
@@ -35,10 +47,13 @@ pub struct SemanticConfig {
     pub region_analysis: RegionAnalysisConfig,
     /// Marker placement strategy
     pub marker_placement: MarkerPlacementConfig,
+    /// Rebalances marker density across regions after placement; see
+    /// [`balance_markers`]. Default: None.
+    pub marker_balance: Option<MarkerBalanceConfig>,
 }
 
 /// Type of connectivity analysis to perform
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ConnectivityType {
     /// 4-connected (orthogonal neighbors only)
     FourConnected,
@@ -47,7 +62,8 @@ pub enum ConnectivityType {
 }
 
 /// Configuration for advanced region analysis
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RegionAnalysisConfig {
     /// Enable shape analysis (aspect ratio, compactness)
     pub analyze_shape: bool,
@@ -55,10 +71,18 @@ pub struct RegionAnalysisConfig {
     pub analyze_connectivity_patterns: bool,
     /// Minimum region size for detailed analysis
     pub min_analysis_size: usize,
+    /// Cover each region with rectangles ([`Region::rects`]). Default: false.
+    pub decompose_rects: bool,
+    /// Water regions at least this elongated are `River`, others `Lake`
+    /// (see [`SemanticExtractor::extract_terrain`](crate::SemanticExtractor::extract_terrain)).
+    /// Elongation is area over the square of twice the deepest cell's
+    /// distance from land. Default: 4.0.
+    pub river_elongation: f32,
 }
 
 /// Configuration for marker placement strategies
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MarkerPlacementConfig {
     /// Placement strategy for markers
     pub strategy: PlacementStrategy,
@@ -69,7 +93,7 @@ pub struct MarkerPlacementConfig {
 }
 
 /// Marker placement strategies
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PlacementStrategy {
     /// Random placement within region
     Random,
@@ -105,12 +129,15 @@ impl SemanticConfig {
                 analyze_shape: true, // Cave shape matters
                 analyze_connectivity_patterns: true,
                 min_analysis_size: 15,
+                decompose_rects: false,
+                river_elongation: 4.0,
             },
             marker_placement: MarkerPlacementConfig {
                 strategy: PlacementStrategy::Random,
                 min_marker_distance: 5,
                 avoid_walls: true,
             },
+            marker_balance: None,
         }
     }
 
@@ -137,12 +164,15 @@ impl SemanticConfig {
                 analyze_shape: true, // Room rectangularity matters
                 analyze_connectivity_patterns: false,
                 min_analysis_size: 8,
+                decompose_rects: true,
+                river_elongation: 4.0,
             },
             marker_placement: MarkerPlacementConfig {
                 strategy: PlacementStrategy::Center, // Furniture in room centers
                 min_marker_distance: 4,
                 avoid_walls: true,
             },
+            marker_balance: None,
         }
     }
 
@@ -167,12 +197,15 @@ impl SemanticConfig {
                 analyze_shape: false,
                 analyze_connectivity_patterns: true, // Junction analysis important
                 min_analysis_size: 5,
+                decompose_rects: false,
+                river_elongation: 4.0,
             },
             marker_placement: MarkerPlacementConfig {
                 strategy: PlacementStrategy::Corners, // Traps in corners
                 min_marker_distance: 8,
                 avoid_walls: false, // Maze walls are part of structure
             },
+            marker_balance: None,
         }
     }
 }
@@ -195,31 +228,424 @@ impl Default for SemanticConfig {
             max_markers_per_region: 3,
             marker_scaling_factor: 100.0,
             connectivity_type: ConnectivityType::FourConnected,
-            region_analysis: RegionAnalysisConfig {
-                analyze_shape: false,
-                analyze_connectivity_patterns: false,
-                min_analysis_size: 10,
-            },
-            marker_placement: MarkerPlacementConfig {
-                strategy: PlacementStrategy::Random,
-                min_marker_distance: 3,
-                avoid_walls: true,
-            },
+            region_analysis: RegionAnalysisConfig::default(),
+            marker_placement: MarkerPlacementConfig::default(),
+            marker_balance: None,
+        }
+    }
+}
+
+impl SemanticConfig {
+    /// Sets `size_thresholds`.
+    pub fn with_size_thresholds(mut self, size_thresholds: Vec<(usize, String)>) -> Self {
+        self.size_thresholds = size_thresholds;
+        self
+    }
+
+    /// Sets `marker_types`.
+    pub fn with_marker_types(mut self, marker_types: Vec<(String, f32)>) -> Self {
+        self.marker_types = marker_types;
+        self
+    }
+
+    /// Sets `max_markers_per_region`.
+    pub fn with_max_markers_per_region(mut self, max_markers_per_region: usize) -> Self {
+        self.max_markers_per_region = max_markers_per_region;
+        self
+    }
+
+    /// Sets `marker_scaling_factor`.
+    pub fn with_marker_scaling_factor(mut self, marker_scaling_factor: f32) -> Self {
+        self.marker_scaling_factor = marker_scaling_factor;
+        self
+    }
+
+    /// Sets `connectivity_type`.
+    pub fn with_connectivity_type(mut self, connectivity_type: ConnectivityType) -> Self {
+        self.connectivity_type = connectivity_type;
+        self
+    }
+
+    /// Sets `region_analysis`.
+    pub fn with_region_analysis(mut self, region_analysis: RegionAnalysisConfig) -> Self {
+        self.region_analysis = region_analysis;
+        self
+    }
+
+    /// Sets `marker_placement`.
+    pub fn with_marker_placement(mut self, marker_placement: MarkerPlacementConfig) -> Self {
+        self.marker_placement = marker_placement;
+        self
+    }
+
+    /// Sets `marker_balance`.
+    pub fn with_marker_balance(mut self, marker_balance: MarkerBalanceConfig) -> Self {
+        self.marker_balance = Some(marker_balance);
+        self
+    }
+
+    /// Adds a size threshold, replacing any with the same `min_size` and
+    /// keeping thresholds sorted largest first.
+    pub fn with_threshold(mut self, min_size: usize, kind: impl Into<String>) -> Self {
+        self.size_thresholds.retain(|(size, _)| *size != min_size);
+        let at = self
+            .size_thresholds
+            .iter()
+            .position(|(size, _)| *size < min_size)
+            .unwrap_or(self.size_thresholds.len());
+        self.size_thresholds.insert(at, (min_size, kind.into()));
+        self
+    }
+
+    /// Adds a marker type, replacing the weight of an existing one.
+    pub fn with_marker(mut self, kind: impl Into<String>, weight: f32) -> Self {
+        let kind = kind.into();
+        match self.marker_types.iter_mut().find(|(name, _)| *name == kind) {
+            Some(entry) => entry.1 = weight,
+            None => self.marker_types.push((kind, weight)),
+        }
+        self
+    }
+
+    /// Removes a marker type.
+    pub fn without_marker(mut self, kind: &str) -> Self {
+        self.marker_types.retain(|(name, _)| name != kind);
+        self
+    }
+
+    /// Built-in profile by name: `default`, `caves`, `rooms`, or `mazes`
+    /// (singular forms and any case accepted).
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "default" => Some(Self::default()),
+            "caves" | "cave" => Some(Self::cave_system()),
+            "rooms" | "room" => Some(Self::room_system()),
+            "mazes" | "maze" => Some(Self::maze_system()),
+            _ => None,
+        }
+    }
+
+    /// Picks a profile for `grid` from its [`MapStats`].
+    ///
+    /// Corridor-dominated, closed-in maps lean toward the maze profile;
+    /// the rest split between caves and rooms by how jagged their walls
+    /// are. A profile with at least 80% of the weight is used as is,
+    /// otherwise the candidates are blended. Grids without floor get the
+    /// default profile.
+    pub fn auto(grid: &Grid<Tile>) -> Self {
+        let stats = MapStats::measure(grid);
+        if stats.floor == 0 {
+            return Self::default();
+        }
+        let maze = ((stats.corridor_ratio - 0.3) / 0.3).clamp(0.0, 1.0) * (1.0 - stats.openness);
+        let organic = ((stats.corner_ratio - 0.2) / 0.3).clamp(0.0, 1.0);
+        let weighted = [
+            (Self::maze_system(), maze),
+            (Self::cave_system(), (1.0 - maze) * organic),
+            (Self::room_system(), (1.0 - maze) * (1.0 - organic)),
+        ];
+        match weighted.iter().find(|(_, w)| *w >= 0.8) {
+            Some((config, _)) => config.clone(),
+            None => Self::blend(&weighted).unwrap_or_default(),
+        }
+    }
+
+    /// Blends weighted profiles into one.
+    ///
+    /// Marker weights are averaged by profile weight (a marker missing from
+    /// a profile counts as weight 0 there), as are the numeric settings.
+    /// Size thresholds, connectivity, and the analysis and placement flags
+    /// come from the heaviest profile. Returns `None` when no profile has a
+    /// positive weight.
+    pub fn blend(profiles: &[(SemanticConfig, f32)]) -> Option<Self> {
+        let profiles: Vec<_> = profiles.iter().filter(|(_, w)| *w > 0.0).collect();
+        let total: f32 = profiles.iter().map(|(_, w)| w).sum();
+        let (dominant, _) = profiles
+            .iter()
+            .copied()
+            .reduce(|best, p| if p.1 > best.1 { p } else { best })?;
+        let mean = |f: &dyn Fn(&SemanticConfig) -> f32| {
+            profiles.iter().map(|(c, w)| f(c) * w).sum::<f32>() / total
+        };
+
+        let mut blended = dominant.clone();
+        blended.marker_types.clear();
+        for (config, _) in &profiles {
+            for (kind, _) in &config.marker_types {
+                if !blended.marker_types.iter().any(|(k, _)| k == kind) {
+                    let weight = mean(&|c| {
+                        c.marker_types
+                            .iter()
+                            .find(|(k, _)| k == kind)
+                            .map_or(0.0, |(_, w)| *w)
+                    });
+                    blended.marker_types.push((kind.clone(), weight));
+                }
+            }
+        }
+        blended.max_markers_per_region =
+            mean(&|c| c.max_markers_per_region as f32).round() as usize;
+        blended.marker_scaling_factor = mean(&|c| c.marker_scaling_factor);
+        blended.region_analysis.min_analysis_size =
+            mean(&|c| c.region_analysis.min_analysis_size as f32).round() as usize;
+        blended.marker_placement.min_marker_distance =
+            mean(&|c| c.marker_placement.min_marker_distance as f32).round() as usize;
+        Some(blended)
+    }
+}
+
+impl Default for RegionAnalysisConfig {
+    fn default() -> Self {
+        Self {
+            analyze_shape: false,
+            analyze_connectivity_patterns: false,
+            min_analysis_size: 10,
+            decompose_rects: false,
+            river_elongation: 4.0,
+        }
+    }
+}
+
+impl RegionAnalysisConfig {
+    /// Sets `analyze_shape`.
+    pub fn with_analyze_shape(mut self, analyze_shape: bool) -> Self {
+        self.analyze_shape = analyze_shape;
+        self
+    }
+
+    /// Sets `analyze_connectivity_patterns`.
+    pub fn with_analyze_connectivity_patterns(
+        mut self,
+        analyze_connectivity_patterns: bool,
+    ) -> Self {
+        self.analyze_connectivity_patterns = analyze_connectivity_patterns;
+        self
+    }
+
+    /// Sets `min_analysis_size`.
+    pub fn with_min_analysis_size(mut self, min_analysis_size: usize) -> Self {
+        self.min_analysis_size = min_analysis_size;
+        self
+    }
+
+    /// Sets `decompose_rects`.
+    pub fn with_decompose_rects(mut self, decompose_rects: bool) -> Self {
+        self.decompose_rects = decompose_rects;
+        self
+    }
+
+    /// Sets `river_elongation`.
+    pub fn with_river_elongation(mut self, river_elongation: f32) -> Self {
+        self.river_elongation = river_elongation;
+        self
+    }
+}
+
+impl Default for MarkerPlacementConfig {
+    fn default() -> Self {
+        Self {
+            strategy: PlacementStrategy::Random,
+            min_marker_distance: 3,
+            avoid_walls: true,
+        }
+    }
+}
+
+impl MarkerPlacementConfig {
+    /// Sets `strategy`.
+    pub fn with_strategy(mut self, strategy: PlacementStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets `min_marker_distance`.
+    pub fn with_min_marker_distance(mut self, min_marker_distance: usize) -> Self {
+        self.min_marker_distance = min_marker_distance;
+        self
+    }
+
+    /// Sets `avoid_walls`.
+    pub fn with_avoid_walls(mut self, avoid_walls: bool) -> Self {
+        self.avoid_walls = avoid_walls;
+        self
+    }
+}
+
+/// Per-region marker density band for [`balance_markers`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkerBalanceConfig {
+    /// Fewest markers per 100 cells a region should hold, rounded up.
+    /// Default: 1.0.
+    pub min_density: f32,
+    /// Most markers per 100 cells a region should hold, rounded down but
+    /// never below the minimum. Default: 4.0.
+    pub max_density: f32,
+    /// Regions smaller than this neither give nor receive markers.
+    /// Default: 16.
+    pub min_region_area: usize,
+    /// Marker tags or categories that never move, e.g. `"spawn"` or
+    /// `"PlayerStart"`; they still count toward their region's density.
+    /// Default: spawn, quest, PlayerStart, and Exit markers.
+    pub pinned: Vec<String>,
+}
+
+impl Default for MarkerBalanceConfig {
+    fn default() -> Self {
+        Self {
+            min_density: 1.0,
+            max_density: 4.0,
+            min_region_area: 16,
+            pinned: ["spawn", "quest", "PlayerStart", "Exit"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl MarkerBalanceConfig {
+    /// Sets `min_density` and `max_density`.
+    pub fn with_density(mut self, min: f32, max: f32) -> Self {
+        self.min_density = min;
+        self.max_density = max;
+        self
+    }
+
+    /// Sets `min_region_area`.
+    pub fn with_min_region_area(mut self, min_region_area: usize) -> Self {
+        self.min_region_area = min_region_area;
+        self
+    }
+
+    /// Adds a pinned marker tag or category.
+    pub fn with_pinned(mut self, kind: impl Into<String>) -> Self {
+        self.pinned.push(kind.into());
+        self
+    }
+
+    /// Inclusive marker count band for a region of `area` cells.
+    pub fn band(&self, area: usize) -> (usize, usize) {
+        let min = (self.min_density.max(0.0) * area as f32 / 100.0).ceil() as usize;
+        let max = (self.max_density.max(0.0) * area as f32 / 100.0).floor() as usize;
+        (min, max.max(min))
+    }
+
+    fn is_pinned(&self, marker: &Marker) -> bool {
+        let (tag, category) = (marker.tag(), marker.marker_type.category());
+        self.pinned.iter().any(|p| *p == tag || p == category)
+    }
+}
+
+/// Layout statistics used to pick an extractor profile for grids of unknown
+/// origin ([`SemanticConfig::auto`]).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MapStats {
+    /// Floor cells.
+    pub floor: usize,
+    /// Share of floor cells walled in on two opposite sides (one-wide passages).
+    pub corridor_ratio: f32,
+    /// Share of floor cells whose eight neighbours are all floor.
+    pub openness: f32,
+    /// Share of wall-edge 2x2 blocks that are corners rather than straight
+    /// edges; high for organic caves, low for rectangular rooms.
+    pub corner_ratio: f32,
+    /// Floor regions.
+    pub regions: usize,
+    /// Share of floor in the largest region.
+    pub largest_region: f32,
+}
+
+impl MapStats {
+    /// Measures `grid`.
+    pub fn measure(grid: &Grid<Tile>) -> Self {
+        let (w, h) = (grid.width(), grid.height());
+        let floor_at = |x: i32, y: i32| grid.get(x, y).is_some_and(|t| t.is_floor());
+        let (mut floor, mut corridor, mut open) = (0, 0, 0);
+        for (x, y, tile) in grid.iter() {
+            if !tile.is_floor() {
+                continue;
+            }
+            floor += 1;
+            let (x, y) = (x as i32, y as i32);
+            let horizontal = !floor_at(x - 1, y) && !floor_at(x + 1, y);
+            let vertical = !floor_at(x, y - 1) && !floor_at(x, y + 1);
+            if horizontal || vertical {
+                corridor += 1;
+            }
+            if (-1..=1).all(|dy| (-1..=1).all(|dx| floor_at(x + dx, y + dy))) {
+                open += 1;
+            }
+        }
+
+        let (mut corners, mut edges) = (0usize, 0usize);
+        for y in 0..h.saturating_sub(1) as i32 {
+            for x in 0..w.saturating_sub(1) as i32 {
+                let block = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)];
+                match block.iter().filter(|&&(bx, by)| floor_at(bx, by)).count() {
+                    1 | 3 => corners += 1,
+                    2 => edges += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        let regions = grid.flood_regions();
+        let largest = regions.iter().map(Vec::len).max().unwrap_or(0);
+        let share = |n: usize, of: usize| if of == 0 { 0.0 } else { n as f32 / of as f32 };
+        Self {
+            floor,
+            corridor_ratio: share(corridor, floor),
+            openness: share(open, floor),
+            corner_ratio: share(corners, corners + edges),
+            regions: regions.len(),
+            largest_region: share(largest, floor),
         }
     }
 }
 
 /// A distinct region within the generated map
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Region {
     pub id: u32,
     pub kind: String,
     pub cells: Vec<(u32, u32)>,
     pub tags: Vec<String>,
+    /// Non-overlapping rectangles covering `cells`, largest first (see
+    /// [`Region::cover_rects`]). Empty unless
+    /// [`RegionAnalysisConfig::decompose_rects`] is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rects: Vec<RegionRect>,
+}
+
+/// An axis-aligned rectangle of cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RegionRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RegionRect {
+    pub fn area(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+
+    /// Center in cell coordinates (`x + width / 2`, as floats).
+    pub fn center(&self) -> (f32, f32) {
+        (
+            self.x as f32 + self.width as f32 / 2.0,
+            self.y as f32 + self.height as f32 / 2.0,
+        )
+    }
 }
 
 /// Hierarchical marker types for different gameplay elements
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MarkerType {
     /// Basic spawn points
     Spawn,
@@ -266,27 +692,82 @@ impl MarkerType {
     }
 }
 
+/// Namespaced [`Marker::metadata`] keys written by built-in passes.
+pub mod meta {
+    /// Tier of a [`LootTier`](super::MarkerType::LootTier) marker.
+    pub const LOOT_TIER: &str = "loot.tier";
+    /// Difficulty of an [`EncounterZone`](super::MarkerType::EncounterZone)
+    /// marker.
+    pub const ENCOUNTER_DIFFICULTY: &str = "encounter.difficulty";
+    /// Priority of a [`QuestObjective`](super::MarkerType::QuestObjective)
+    /// marker.
+    pub const QUEST_PRIORITY: &str = "quest.priority";
+    /// 1-based position of a quest step in its chain.
+    pub const QUEST_STEP: &str = "quest.step";
+    /// What a quest step asks: `fetch`, `kill`, or `reach`.
+    pub const QUEST_KIND: &str = "quest.kind";
+    /// Tag of the marker a quest step points at.
+    pub const QUEST_TARGET: &str = "quest.target";
+    /// Difficulty of a quest step, rising along the chain.
+    pub const QUEST_DIFFICULTY: &str = "quest.difficulty";
+    /// Cavern feature an ecology marker sits in: `alcove`, `chamber`, or
+    /// `shore`.
+    pub const ECOLOGY_FEATURE: &str = "ecology.feature";
+    /// Creatures living at an ecology marker, scaled to its region's size.
+    pub const ECOLOGY_POPULATION: &str = "ecology.population";
+    /// Critical-path segment a pacing marker belongs to, from 0.
+    pub const PACING_SEGMENT: &str = "pacing.segment";
+    /// Intended tension (0 to 1) of that segment.
+    pub const PACING_TENSION: &str = "pacing.tension";
+    /// Side of an entrance cell its room lies on (`north`, `south`,
+    /// `east`, `west`).
+    pub const ENTRANCE_FACING: &str = "entrance.facing";
+    /// Index of the room an entrance opens into.
+    pub const ENTRANCE_ROOM: &str = "entrance.room";
+    /// Path distance from the spawn to a placed exit.
+    pub const EXIT_DISTANCE: &str = "exit.distance";
+    /// Map edge a balanced exit sits on (`north`, `south`, `east`, `west`).
+    pub const EXIT_EDGE: &str = "exit.edge";
+    /// Hiddenness score of a cell holding placed treasure.
+    pub const HIDING_SCORE: &str = "hiding.score";
+}
+
 /// A spawn marker for entity placement
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Marker {
     pub x: u32,
     pub y: u32,
     pub marker_type: MarkerType,
     pub weight: f32,
     pub region_id: Option<u32>,
-    pub metadata: HashMap<String, String>,
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl Marker {
     /// Create a new marker with the given type
+    ///
+    /// Tiered types also record their level under the matching [`meta`]
+    /// key.
     pub fn new(x: u32, y: u32, marker_type: MarkerType) -> Self {
+        let mut metadata = BTreeMap::new();
+        let level = match marker_type {
+            MarkerType::LootTier { tier } => Some((meta::LOOT_TIER, tier)),
+            MarkerType::EncounterZone { difficulty } => {
+                Some((meta::ENCOUNTER_DIFFICULTY, difficulty))
+            }
+            MarkerType::QuestObjective { priority } => Some((meta::QUEST_PRIORITY, priority)),
+            _ => None,
+        };
+        if let Some((key, level)) = level {
+            metadata.insert(key.to_string(), level.to_string());
+        }
         Self {
             x,
             y,
             marker_type,
             weight: 1.0,
             region_id: None,
-            metadata: HashMap::new(),
+            metadata,
         }
     }
 
@@ -361,26 +842,231 @@ impl MarkerConstraints {
             require_nearby: Vec::new(),
         }
     }
+
+    /// Whether `candidate` may join `existing` markers. Distances are
+    /// Euclidean; `exclude_types` only applies to markers in the same
+    /// region, so it never rejects a candidate without a region.
+    pub fn allows(&self, candidate: &Marker, existing: &[Marker]) -> bool {
+        let distance = |m: &Marker| {
+            let (dx, dy) = (
+                m.x as f32 - candidate.x as f32,
+                m.y as f32 - candidate.y as f32,
+            );
+            (dx * dx + dy * dy).sqrt()
+        };
+        let near = |kind: &MarkerType, d: f32| {
+            existing
+                .iter()
+                .any(|m| &m.marker_type == kind && distance(m) <= d)
+        };
+        self.min_distance_same.is_none_or(|d| {
+            existing
+                .iter()
+                .filter(|m| m.marker_type == candidate.marker_type)
+                .all(|m| distance(m) >= d)
+        }) && self
+            .min_distance_any
+            .is_none_or(|d| existing.iter().all(|m| distance(m) >= d))
+            && self
+                .max_distance_from
+                .iter()
+                .all(|(kind, d)| near(kind, *d))
+            && self.require_nearby.iter().all(|(kind, d)| near(kind, *d))
+            && candidate.region_id.is_none_or(|region| {
+                !existing.iter().any(|m| {
+                    m.region_id == Some(region) && self.exclude_types.contains(&m.marker_type)
+                })
+            })
+    }
+}
+
+/// Moves markers between regions so each region's marker count falls in
+/// its [`MarkerBalanceConfig::band`]; returns how many markers moved.
+///
+/// Regions below their band are filled first, lowest density first, from
+/// the densest regions that can spare a marker; markers still over a
+/// band then move to the sparsest regions with room. A moved marker keeps
+/// its type and metadata, takes a free cell of its new region where
+/// `constraints` allow it (see [`MarkerConstraints::allows`]), and its
+/// `region_id` is updated. Markers are never created or removed, so a map
+/// with too few markers overall stays short.
+pub fn balance_markers(
+    semantic: &mut SemanticLayers,
+    config: &MarkerBalanceConfig,
+    constraints: &MarkerConstraints,
+    rng: &mut Rng,
+) -> usize {
+    balance_in(
+        &semantic.regions,
+        &mut semantic.markers,
+        config,
+        constraints,
+        rng,
+    )
+}
+
+/// [`balance_markers`] over a subset of regions; markers elsewhere stay.
+pub(crate) fn balance_in(
+    regions: &[Region],
+    markers: &mut [Marker],
+    config: &MarkerBalanceConfig,
+    constraints: &MarkerConstraints,
+    rng: &mut Rng,
+) -> usize {
+    let regions: BTreeMap<u32, &Region> = regions
+        .iter()
+        .filter(|r| r.area() >= config.min_region_area.max(1))
+        .map(|r| (r.id, r))
+        .collect();
+    let mut counts: BTreeMap<u32, usize> = regions.keys().map(|&id| (id, 0)).collect();
+    for m in markers.iter() {
+        if let Some(count) = m.region_id.and_then(|id| counts.get_mut(&id)) {
+            *count += 1;
+        }
+    }
+    let band = |id: u32| config.band(regions[&id].area());
+    let density = |id: u32, count: usize| count as f64 / regions[&id].area() as f64;
+    let mut blocked: HashSet<u32> = HashSet::new();
+    let mut moved = 0;
+
+    loop {
+        let sparsest = |counts: &BTreeMap<u32, usize>, room: &dyn Fn(u32, usize) -> bool| {
+            counts
+                .iter()
+                .filter(|&(&id, &n)| !blocked.contains(&id) && room(id, n))
+                .min_by(|a, b| density(*a.0, *a.1).total_cmp(&density(*b.0, *b.1)))
+                .map(|(&id, _)| id)
+        };
+        let densest = |counts: &BTreeMap<u32, usize>, spare: &dyn Fn(u32, usize) -> bool| {
+            counts
+                .iter()
+                .filter(|&(&id, &n)| {
+                    spare(id, n)
+                        && markers
+                            .iter()
+                            .any(|m| m.region_id == Some(id) && !config.is_pinned(m))
+                })
+                .max_by(|a, b| density(*a.0, *a.1).total_cmp(&density(*b.0, *b.1)))
+                .map(|(&id, _)| id)
+        };
+        // Deficits first, from any region above its minimum; then excess,
+        // into any region below its maximum.
+        let pair = sparsest(&counts, &|id, n| n < band(id).0)
+            .zip(densest(&counts, &|id, n| n > band(id).0))
+            .or_else(|| {
+                let donor = densest(&counts, &|id, n| n > band(id).1)?;
+                let to = sparsest(&counts, &|id, n| id != donor && n < band(id).1)?;
+                Some((to, donor))
+            });
+        let Some((to, from)) = pair else {
+            break;
+        };
+
+        let mut movable: Vec<usize> = (0..markers.len())
+            .filter(|&i| markers[i].region_id == Some(from) && !config.is_pinned(&markers[i]))
+            .collect();
+        rng.shuffle(&mut movable);
+        let mut cells = regions[&to].cells.clone();
+        rng.shuffle(&mut cells);
+        let occupied: HashSet<(u32, u32)> = markers.iter().map(|m| (m.x, m.y)).collect();
+
+        let found = movable.iter().find_map(|&i| {
+            let others: Vec<Marker> = markers
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, m)| m.clone())
+                .collect();
+            cells
+                .iter()
+                .filter(|cell| !occupied.contains(cell))
+                .find(|&&(x, y)| {
+                    let mut candidate = markers[i].clone();
+                    (candidate.x, candidate.y, candidate.region_id) = (x, y, Some(to));
+                    constraints.allows(&candidate, &others)
+                })
+                .map(|&cell| (i, cell))
+        });
+        match found {
+            Some((i, (x, y))) => {
+                let marker = &mut markers[i];
+                (marker.x, marker.y, marker.region_id) = (x, y, Some(to));
+                *counts.get_mut(&from).unwrap() -= 1;
+                *counts.get_mut(&to).unwrap() += 1;
+                moved += 1;
+            }
+            None => {
+                blocked.insert(to);
+            }
+        }
+    }
+    moved
+}
+
+/// Non-floor terrain the extractor treats as its own regions; see
+/// [`SemanticExtractor::extract_terrain`](crate::SemanticExtractor::extract_terrain).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TerrainFeature {
+    /// Lakes and rivers; swimmable.
+    Water,
+    Lava,
+    Chasm,
+}
+
+impl TerrainFeature {
+    /// Feature named by a tile name: names containing `water`, `lake`,
+    /// `river`, or `sea` are water; `lava` or `magma` lava; `chasm`,
+    /// `pit`, or `abyss` a chasm.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| name.contains(w));
+        if has(&["water", "lake", "river", "sea"]) {
+            Some(TerrainFeature::Water)
+        } else if has(&["lava", "magma"]) {
+            Some(TerrainFeature::Lava)
+        } else if has(&["chasm", "pit", "abyss"]) {
+            Some(TerrainFeature::Chasm)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the [`Masks`] layer holding this feature's cells.
+    pub fn mask_name(self) -> &'static str {
+        match self {
+            TerrainFeature::Water => "water",
+            TerrainFeature::Lava => "lava",
+            TerrainFeature::Chasm => "chasm",
+        }
+    }
 }
 
 /// Spatial masks for gameplay logic
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Masks {
     pub walkable: Vec<Vec<bool>>,
     pub no_spawn: Vec<Vec<bool>>,
+    /// User-defined mask layers keyed by name (e.g. from prefab legends).
+    pub named: BTreeMap<String, Vec<Vec<bool>>>,
     pub width: usize,
     pub height: usize,
 }
 
 /// Region connectivity information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectivityGraph {
     pub regions: Vec<u32>,
+    /// Pairs of adjacent regions that can be walked between.
     pub edges: Vec<(u32, u32)>,
+    /// Pairs of adjacent regions where crossing means entering water
+    /// (land to water, or water to water).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub swim_edges: Vec<(u32, u32)>,
 }
 
 /// Complete semantic information for a generated map
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemanticLayers {
     pub regions: Vec<Region>,
     pub markers: Vec<Marker>,
@@ -388,6 +1074,18 @@ pub struct SemanticLayers {
     pub connectivity: ConnectivityGraph,
 }
 
+impl SemanticLayers {
+    /// The first `Spawn` (or `PlayerStart`) marker.
+    pub fn spawn(&self) -> Option<&Marker> {
+        self.markers.iter().find(|m| m.is_spawn())
+    }
+
+    /// `Exit` (or `"Exit"`-tagged) markers, in order.
+    pub fn exits(&self) -> impl Iterator<Item = &Marker> {
+        self.markers.iter().filter(|m| m.is_exit())
+    }
+}
+
 /// Collect positions for markers of a given type.
 pub fn marker_positions(layers: &SemanticLayers, marker_type: &MarkerType) -> Vec<(usize, usize)> {
     layers
@@ -405,6 +1103,7 @@ impl Region {
             kind: kind.into(),
             cells: Vec::new(),
             tags: Vec::new(),
+            rects: Vec::new(),
         }
     }
 
@@ -419,6 +1118,74 @@ impl Region {
     pub fn area(&self) -> usize {
         self.cells.len()
     }
+
+    /// Covers the cells with non-overlapping rectangles, greedily taking the
+    /// largest remaining one each time, so a rectangular room is one rect
+    /// and an L-shaped room two.
+    pub fn cover_rects(&self) -> Vec<RegionRect> {
+        let Some(&(x0, y0)) = self.cells.first() else {
+            return Vec::new();
+        };
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (x0, y0, x0, y0);
+        for &(x, y) in &self.cells {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        let (w, h) = ((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize);
+        let mut open = vec![false; w * h];
+        for &(x, y) in &self.cells {
+            open[(y - min_y) as usize * w + (x - min_x) as usize] = true;
+        }
+        let mut remaining = open.iter().filter(|&&c| c).count();
+
+        let mut rects = Vec::new();
+        while remaining > 0 {
+            let (rx, ry, rw, rh) = largest_rect(&open, w, h);
+            for y in ry..ry + rh {
+                open[y * w + rx..y * w + rx + rw].fill(false);
+            }
+            remaining -= rw * rh;
+            rects.push(RegionRect {
+                x: min_x + rx as u32,
+                y: min_y + ry as u32,
+                width: rw as u32,
+                height: rh as u32,
+            });
+        }
+        rects
+    }
+}
+
+/// Largest all-open rectangle `(x, y, width, height)`, by the histogram
+/// method; the first found wins ties.
+fn largest_rect(open: &[bool], w: usize, h: usize) -> (usize, usize, usize, usize) {
+    let mut heights = vec![0usize; w];
+    let mut best = (0, 0, 0, 0);
+    let mut stack: Vec<usize> = Vec::with_capacity(w);
+    for y in 0..h {
+        for (x, height) in heights.iter_mut().enumerate() {
+            *height = if open[y * w + x] { *height + 1 } else { 0 };
+        }
+        stack.clear();
+        for x in 0..=w {
+            let current = if x < w { heights[x] } else { 0 };
+            while let Some(&top) = stack.last() {
+                if heights[top] < current {
+                    break;
+                }
+                stack.pop();
+                let left = stack.last().map_or(0, |&i| i + 1);
+                let (rw, rh) = (x - left, heights[top]);
+                if rw * rh > best.2 * best.3 {
+                    best = (left, y + 1 - rh, rw, rh);
+                }
+            }
+            stack.push(x);
+        }
+    }
+    best
 }
 
 impl Marker {
@@ -436,6 +1203,45 @@ impl Marker {
         self.metadata.insert(key.into(), value.into());
         self
     }
+
+    /// Stores `point` under `key` as `x,y`.
+    pub fn with_point(self, key: impl Into<String>, point: (i64, i64)) -> Self {
+        self.with_metadata(key, format!("{},{}", point.0, point.1))
+    }
+
+    /// Metadata value under `key` as an integer.
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.metadata.get(key)?.trim().parse().ok()
+    }
+
+    /// Metadata value under `key` as a float.
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.metadata.get(key)?.trim().parse().ok()
+    }
+
+    /// Metadata value under `key` as an `x,y` point.
+    pub fn get_point(&self, key: &str) -> Option<(i64, i64)> {
+        let (x, y) = self.metadata.get(key)?.split_once(',')?;
+        Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+    }
+
+    /// Whether this is a `Spawn` or `PlayerStart` marker.
+    pub fn is_spawn(&self) -> bool {
+        match &self.marker_type {
+            MarkerType::Spawn => true,
+            MarkerType::Custom(tag) => tag == "PlayerStart",
+            _ => false,
+        }
+    }
+
+    /// Whether this is an `Exit` (or `"Exit"`-tagged) marker.
+    pub fn is_exit(&self) -> bool {
+        match &self.marker_type {
+            MarkerType::Exit => true,
+            MarkerType::Custom(tag) => tag == "Exit",
+            _ => false,
+        }
+    }
 }
 
 /// Requirements for semantic-driven generation
@@ -511,11 +1317,30 @@ impl Masks {
         Self {
             walkable: vec![vec![false; width]; height],
             no_spawn: vec![vec![false; width]; height],
+            named: BTreeMap::new(),
             width,
             height,
         }
     }
 
+    /// Returns the named mask layer, if any cell has been set on it.
+    pub fn named(&self, name: &str) -> Option<&Vec<Vec<bool>>> {
+        self.named.get(name)
+    }
+
+    /// Sets a cell in the named mask layer, creating the layer on first use.
+    pub fn set_named(&mut self, name: &str, x: usize, y: usize, value: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let (width, height) = (self.width, self.height);
+        let layer = self
+            .named
+            .entry(name.to_string())
+            .or_insert_with(|| vec![vec![false; width]; height]);
+        layer[y][x] = value;
+    }
+
     pub fn from_tiles(tiles: &Grid<Tile>) -> Self {
         let mut masks = Self::new(tiles.width(), tiles.height());
 
@@ -535,6 +1360,7 @@ impl ConnectivityGraph {
         Self {
             regions: Vec::new(),
             edges: Vec::new(),
+            swim_edges: Vec::new(),
         }
     }
 
@@ -552,6 +1378,16 @@ impl ConnectivityGraph {
             self.edges.push((from, to));
         }
     }
+
+    /// Adds a [`swim_edges`](Self::swim_edges) entry.
+    pub fn add_swim_edge(&mut self, from: u32, to: u32) {
+        self.add_region(from);
+        self.add_region(to);
+
+        if !self.swim_edges.contains(&(from, to)) && !self.swim_edges.contains(&(to, from)) {
+            self.swim_edges.push((from, to));
+        }
+    }
 }
 
 /// Vertical connectivity analysis for multi-floor support
@@ -562,7 +1398,7 @@ pub struct VerticalConnectivity {
     /// Confirmed stair placements
     pub stairs: Vec<(u32, u32, u32, u32)>,
     /// Regions accessible from each floor
-    pub floor_accessibility: HashMap<u32, Vec<u32>>,
+    pub floor_accessibility: BTreeMap<u32, Vec<u32>>,
 }
 
 impl VerticalConnectivity {
@@ -571,7 +1407,7 @@ impl VerticalConnectivity {
         Self {
             stair_candidates: Vec::new(),
             stairs: Vec::new(),
-            floor_accessibility: HashMap::new(),
+            floor_accessibility: BTreeMap::new(),
         }
     }
 
@@ -647,7 +1483,7 @@ impl VerticalConnectivity {
         self.stairs.clear();
 
         // Group candidates by floor pair
-        let mut floor_candidates: HashMap<(u32, u32), Vec<(u32, u32)>> = HashMap::new();
+        let mut floor_candidates: BTreeMap<(u32, u32), Vec<(u32, u32)>> = BTreeMap::new();
         for &(x, y, from_floor, to_floor) in &self.stair_candidates {
             floor_candidates
                 .entry((from_floor, to_floor))
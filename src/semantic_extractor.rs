@@ -4,11 +4,14 @@
 //! from the generation algorithms. This allows semantic analysis of maps from
 //! any source - TerrainForge algorithms, pipelines, or external systems.
 
+use crate::incremental::DirtyRect;
 use crate::semantic::{
-    ConnectivityGraph, Marker, MarkerType, Masks, Region, SemanticConfig, SemanticLayers,
+    self, ConnectivityGraph, ConnectivityType, Marker, MarkerConstraints, MarkerType, Masks,
+    Region, SemanticConfig, SemanticLayers, TerrainFeature,
 };
+use crate::tileset::{TileCell, TileSet};
 use crate::{Grid, Rng, Tile};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 /// Standalone semantic extractor that analyzes any grid
 pub struct SemanticExtractor {
@@ -36,6 +39,18 @@ impl SemanticExtractor {
         Self::new(SemanticConfig::maze_system())
     }
 
+    /// Create extractor with a profile chosen from `grid`'s layout
+    /// statistics, for pipelines and imported maps (see
+    /// [`SemanticConfig::auto`]).
+    pub fn auto(grid: &Grid<Tile>) -> Self {
+        Self::new(SemanticConfig::auto(grid))
+    }
+
+    /// The configuration this extractor uses.
+    pub fn config(&self) -> &SemanticConfig {
+        &self.config
+    }
+
     /// Extract semantic layers from any grid
     pub fn extract(&self, grid: &Grid<Tile>, rng: &mut Rng) -> SemanticLayers {
         // 1. Extract regions using flood fill
@@ -45,7 +60,8 @@ impl SemanticExtractor {
         self.classify_regions(&mut regions);
 
         // 3. Generate markers based on configuration
-        let markers = self.generate_markers(&regions, rng);
+        let mut markers = self.generate_markers(&regions, rng);
+        self.balance_markers(&regions, &mut markers, rng);
 
         // 4. Create spatial masks
         let masks = Masks::from_tiles(grid);
@@ -61,6 +77,227 @@ impl SemanticExtractor {
         }
     }
 
+    /// Extract semantic layers from a grid with water, lava, and chasm
+    /// cells.
+    ///
+    /// `features` (indexed `[y][x]`) marks feature cells, which are never
+    /// land whatever `grid` holds there. Land is extracted and classified
+    /// as in [`extract`](Self::extract), except that land next to water
+    /// (4-connected) forms separate `Shore` regions. Each body of water
+    /// becomes a `Lake` or `River` region (see
+    /// [`river_elongation`](crate::semantic::RegionAnalysisConfig::river_elongation)),
+    /// and lava and chasms become `Lava` and `Chasm` regions. Markers go on
+    /// land and shore only.
+    ///
+    /// Connectivity `edges` join adjacent land and shore regions;
+    /// `swim_edges` join water regions to their land, shore, and water
+    /// neighbours. Lava and chasm regions get no edges. Feature cells are
+    /// not walkable and are set in the feature's named mask.
+    pub fn extract_terrain(
+        &self,
+        grid: &Grid<Tile>,
+        features: &[Vec<Option<TerrainFeature>>],
+        rng: &mut Rng,
+    ) -> SemanticLayers {
+        let (w, h) = (grid.width(), grid.height());
+        let feature =
+            |x: usize, y: usize| features.get(y).and_then(|r| r.get(x)).copied().flatten();
+        let is_land = |x: usize, y: usize| grid[(x, y)].is_floor() && feature(x, y).is_none();
+
+        let mut land = Grid::new(w, h);
+        let mut shore = Grid::new(w, h);
+        for y in 0..h {
+            for x in 0..w {
+                if !is_land(x, y) {
+                    continue;
+                }
+                let wet = [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().any(|&(dx, dy)| {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    nx >= 0
+                        && ny >= 0
+                        && feature(nx as usize, ny as usize) == Some(TerrainFeature::Water)
+                });
+                let target = if wet { &mut shore } else { &mut land };
+                target.set(x as i32, y as i32, Tile::Floor);
+            }
+        }
+
+        let mut regions = self.extract_regions(&land);
+        self.classify_regions(&mut regions);
+        let mut kinds: Vec<Option<TerrainFeature>> = vec![None; regions.len()];
+        for mut region in self.extract_regions(&shore) {
+            region.kind = "Shore".to_string();
+            regions.push(region);
+            kinds.push(None);
+        }
+        let walkers = regions.len();
+        for kind in [
+            TerrainFeature::Water,
+            TerrainFeature::Lava,
+            TerrainFeature::Chasm,
+        ] {
+            let mut cells = Grid::new(w, h);
+            for y in 0..h {
+                for x in 0..w {
+                    if feature(x, y) == Some(kind) {
+                        cells.set(x as i32, y as i32, Tile::Floor);
+                    }
+                }
+            }
+            for mut region in self.extract_regions(&cells) {
+                region.kind = match kind {
+                    TerrainFeature::Water if self.is_river(&region, w, h) => "River",
+                    TerrainFeature::Water => "Lake",
+                    TerrainFeature::Lava => "Lava",
+                    TerrainFeature::Chasm => "Chasm",
+                }
+                .to_string();
+                regions.push(region);
+                kinds.push(Some(kind));
+            }
+        }
+        for (i, region) in regions.iter_mut().enumerate() {
+            region.id = i as u32 + 1;
+        }
+
+        let mut markers = self.generate_markers(&regions[..walkers], rng);
+        self.balance_markers(&regions[..walkers], &mut markers, rng);
+
+        let mut masks = Masks::from_tiles(grid);
+        for y in 0..h {
+            for x in 0..w {
+                if let Some(kind) = feature(x, y) {
+                    masks.walkable[y][x] = false;
+                    masks.set_named(kind.mask_name(), x, y, true);
+                }
+            }
+        }
+
+        let mut owner = vec![0u32; w * h];
+        for region in &regions {
+            for &(x, y) in &region.cells {
+                owner[y as usize * w + x as usize] = region.id;
+            }
+        }
+        let mut connectivity = ConnectivityGraph::new();
+        for (region, kind) in regions.iter().zip(&kinds) {
+            connectivity.add_region(region.id);
+            if matches!(kind, Some(TerrainFeature::Lava | TerrainFeature::Chasm)) {
+                continue;
+            }
+            for &(x, y) in &region.cells {
+                for (dx, dy) in self.neighbor_offsets() {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || ny < 0 || nx as usize >= w || ny as usize >= h {
+                        continue;
+                    }
+                    let other = owner[ny as usize * w + nx as usize];
+                    if other == 0 || other == region.id {
+                        continue;
+                    }
+                    match (kind, kinds[other as usize - 1]) {
+                        (None, None) => connectivity.add_edge(region.id, other),
+                        (Some(TerrainFeature::Water), None | Some(TerrainFeature::Water))
+                        | (None, Some(TerrainFeature::Water)) => {
+                            connectivity.add_swim_edge(region.id, other)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        SemanticLayers {
+            regions,
+            markers,
+            masks,
+            connectivity,
+        }
+    }
+
+    /// [`extract_terrain`](Self::extract_terrain) for a [`TileCell`] grid:
+    /// passable cells are floor, and each tile's feature comes from its
+    /// name in `tileset` (see [`TerrainFeature::from_name`]).
+    pub fn extract_tiles(
+        &self,
+        grid: &Grid<TileCell>,
+        tileset: &TileSet,
+        rng: &mut Rng,
+    ) -> SemanticLayers {
+        let features: Vec<Vec<Option<TerrainFeature>>> = (0..grid.height())
+            .map(|y| {
+                (0..grid.width())
+                    .map(|x| {
+                        tileset
+                            .get(grid[(x, y)].id())
+                            .and_then(|def| TerrainFeature::from_name(&def.name))
+                    })
+                    .collect()
+            })
+            .collect();
+        self.extract_terrain(&grid.to_tiles(), &features, rng)
+    }
+
+    /// Whether a water region is elongated enough to be a river.
+    fn is_river(&self, region: &Region, w: usize, h: usize) -> bool {
+        let mut depth = vec![usize::MAX; w * h];
+        let inside: std::collections::HashSet<(u32, u32)> = region.cells.iter().copied().collect();
+        let mut queue = VecDeque::new();
+        for &(x, y) in &region.cells {
+            let edge = [(-1i64, 0i64), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .any(|&(dx, dy)| {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    nx >= 0
+                        && ny >= 0
+                        && (nx as usize) < w
+                        && (ny as usize) < h
+                        && !inside.contains(&(nx as u32, ny as u32))
+                });
+            if edge {
+                depth[y as usize * w + x as usize] = 1;
+                queue.push_back((x, y));
+            }
+        }
+        let mut deepest = 0;
+        while let Some((x, y)) = queue.pop_front() {
+            let d = depth[y as usize * w + x as usize];
+            deepest = deepest.max(d);
+            for (dx, dy) in [(-1i64, 0i64), (1, 0), (0, -1), (0, 1)] {
+                let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                if nx < 0 || ny < 0 || !inside.contains(&(nx as u32, ny as u32)) {
+                    continue;
+                }
+                let i = ny as usize * w + nx as usize;
+                if depth[i] == usize::MAX {
+                    depth[i] = d + 1;
+                    queue.push_back((nx as u32, ny as u32));
+                }
+            }
+        }
+        if deepest == 0 {
+            return false;
+        }
+        let elongation = region.cells.len() as f32 / ((2 * deepest) as f32).powi(2);
+        elongation >= self.config.region_analysis.river_elongation
+    }
+
+    fn neighbor_offsets(&self) -> &'static [(i32, i32)] {
+        match self.config.connectivity_type {
+            ConnectivityType::FourConnected => &[(0, 1), (1, 0), (0, -1), (-1, 0)],
+            ConnectivityType::EightConnected => &[
+                (0, 1),
+                (1, 0),
+                (0, -1),
+                (-1, 0),
+                (1, 1),
+                (1, -1),
+                (-1, 1),
+                (-1, -1),
+            ],
+        }
+    }
+
     /// Extract regions using flood fill algorithm
     fn extract_regions(&self, grid: &Grid<Tile>) -> Vec<Region> {
         let (labels, count) = crate::effects::label_regions(grid);
@@ -79,6 +316,9 @@ impl SemanticExtractor {
             }
 
             if !region.cells.is_empty() {
+                if self.config.region_analysis.decompose_rects {
+                    region.rects = region.cover_rects();
+                }
                 regions.push(region);
             }
         }
@@ -107,30 +347,132 @@ impl SemanticExtractor {
         let mut markers = Vec::new();
 
         for region in regions {
-            let marker_count = (self.config.max_markers_per_region as f32
-                * (region.cells.len() as f32 / self.config.marker_scaling_factor).min(1.0))
-                as usize;
-
-            for _ in 0..marker_count {
-                if let Some((marker_type, weight)) = rng.pick(&self.config.marker_types) {
-                    if rng.random() < (*weight as f64) {
-                        if let Some(position) = self.find_marker_position(region, &markers, rng) {
-                            markers.push(
-                                Marker::new(
-                                    position.0,
-                                    position.1,
-                                    MarkerType::Custom(marker_type.clone()),
-                                )
-                                .with_region(region.id)
-                                .with_weight(*weight),
-                            );
-                        }
+            let marker_count = self.marker_count(region);
+            self.place_markers(region, marker_count, None, &mut markers, rng);
+        }
+
+        markers
+    }
+
+    /// Runs the configured density balancing, keeping the placement
+    /// spacing for moved markers
+    fn balance_markers(&self, regions: &[Region], markers: &mut [Marker], rng: &mut Rng) {
+        if let Some(balance) = &self.config.marker_balance {
+            let constraints = MarkerConstraints {
+                min_distance_any: Some(self.config.marker_placement.min_marker_distance as f32),
+                ..MarkerConstraints::none()
+            };
+            semantic::balance_in(regions, markers, balance, &constraints, rng);
+        }
+    }
+
+    /// Target marker count for a region of this size
+    fn marker_count(&self, region: &Region) -> usize {
+        (self.config.max_markers_per_region as f32
+            * (region.cells.len() as f32 / self.config.marker_scaling_factor).min(1.0))
+            as usize
+    }
+
+    /// Roll `attempts` markers for a region, optionally only on cells inside `within`
+    fn place_markers(
+        &self,
+        region: &Region,
+        attempts: usize,
+        within: Option<DirtyRect>,
+        markers: &mut Vec<Marker>,
+        rng: &mut Rng,
+    ) {
+        for _ in 0..attempts {
+            if let Some((marker_type, weight)) = rng.pick(&self.config.marker_types) {
+                if rng.random() < (*weight as f64) {
+                    if let Some(position) = self.find_marker_position(region, markers, within, rng)
+                    {
+                        markers.push(
+                            Marker::new(
+                                position.0,
+                                position.1,
+                                MarkerType::Custom(marker_type.clone()),
+                            )
+                            .with_region(region.id)
+                            .with_weight(*weight),
+                        );
                     }
                 }
             }
         }
+    }
 
-        markers
+    /// Update layers after `grid` changed inside `rect`.
+    ///
+    /// Masks are refreshed inside `rect`; regions and connectivity are rebuilt
+    /// (an edit can merge or split regions anywhere). Markers outside `rect`
+    /// that still sit on floor are kept and re-assigned to their new region;
+    /// markers inside `rect` are re-rolled on cells inside `rect`.
+    pub fn update(
+        &self,
+        layers: &mut SemanticLayers,
+        grid: &Grid<Tile>,
+        rect: DirtyRect,
+        rng: &mut Rng,
+    ) {
+        if layers.masks.width != grid.width() || layers.masks.height != grid.height() {
+            *layers = self.extract(grid, rng);
+            return;
+        }
+        let rect = rect.clamped(grid.width(), grid.height());
+
+        for (y, row) in layers
+            .masks
+            .walkable
+            .iter_mut()
+            .enumerate()
+            .skip(rect.y)
+            .take(rect.height)
+        {
+            for (x, cell) in row.iter_mut().enumerate().skip(rect.x).take(rect.width) {
+                *cell = grid[(x, y)].is_floor();
+            }
+        }
+
+        let mut regions = self.extract_regions(grid);
+        self.classify_regions(&mut regions);
+        let (labels, _) = crate::effects::label_regions(grid);
+        let width = grid.width();
+
+        let mut markers: Vec<Marker> = std::mem::take(&mut layers.markers)
+            .into_iter()
+            .filter(|m| !rect.contains(m.x as usize, m.y as usize))
+            .filter_map(|mut m| {
+                let (x, y) = (m.x as usize, m.y as usize);
+                if x >= width || y >= grid.height() || !grid[(x, y)].is_floor() {
+                    return None;
+                }
+                if m.region_id.is_some() {
+                    m.region_id = Some(labels[y * width + x]);
+                }
+                Some(m)
+            })
+            .collect();
+
+        for region in &regions {
+            if !region
+                .cells
+                .iter()
+                .any(|&(x, y)| rect.contains(x as usize, y as usize))
+            {
+                continue;
+            }
+            let kept = markers
+                .iter()
+                .filter(|m| m.region_id == Some(region.id))
+                .count();
+            let attempts = self.marker_count(region).saturating_sub(kept);
+            self.place_markers(region, attempts, Some(rect), &mut markers, rng);
+        }
+
+        layers.connectivity = self.build_connectivity(grid, &regions);
+        layers.regions = regions;
+        layers.markers = markers;
     }
 
     /// Find appropriate position for marker based on placement strategy
@@ -138,6 +480,7 @@ impl SemanticExtractor {
         &self,
         region: &Region,
         existing_markers: &[Marker],
+        within: Option<DirtyRect>,
         rng: &mut Rng,
     ) -> Option<(u32, u32)> {
         use crate::semantic::PlacementStrategy;
@@ -158,6 +501,7 @@ impl SemanticExtractor {
         // Filter candidates based on distance constraints
         let valid_candidates: Vec<_> = candidates
             .into_iter()
+            .filter(|&(x, y)| within.is_none_or(|r| r.contains(x as usize, y as usize)))
             .filter(|&pos| self.is_valid_marker_position(pos, existing_markers))
             .collect();
 
@@ -5,10 +5,12 @@
 
 use crate::semantic::{ConnectivityGraph, Masks, SemanticLayers};
 use crate::{Grid, Tile};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// Visualization configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VisualizationConfig {
     /// Characters to use for different region types
     pub region_chars: HashMap<String, char>,
@@ -49,6 +51,44 @@ impl Default for VisualizationConfig {
     }
 }
 
+impl VisualizationConfig {
+    /// Sets `region_chars`.
+    pub fn with_region_chars(mut self, region_chars: HashMap<String, char>) -> Self {
+        self.region_chars = region_chars;
+        self
+    }
+
+    /// Sets `default_region_char`.
+    pub fn with_default_region_char(mut self, default_region_char: char) -> Self {
+        self.default_region_char = default_region_char;
+        self
+    }
+
+    /// Sets `wall_char`.
+    pub fn with_wall_char(mut self, wall_char: char) -> Self {
+        self.wall_char = wall_char;
+        self
+    }
+
+    /// Sets `floor_char`.
+    pub fn with_floor_char(mut self, floor_char: char) -> Self {
+        self.floor_char = floor_char;
+        self
+    }
+
+    /// Sets `show_region_ids`.
+    pub fn with_show_region_ids(mut self, show_region_ids: bool) -> Self {
+        self.show_region_ids = show_region_ids;
+        self
+    }
+
+    /// Sets `show_connectivity`.
+    pub fn with_show_connectivity(mut self, show_connectivity: bool) -> Self {
+        self.show_connectivity = show_connectivity;
+        self
+    }
+}
+
 /// Visualize regions overlaid on the grid
 pub fn visualize_regions(
     grid: &Grid<Tile>,
@@ -140,7 +180,7 @@ pub fn visualize_connectivity_graph(connectivity: &ConnectivityGraph) -> String
     output.push_str(&format!("Edges: {:?}\n", connectivity.edges));
 
     // Create adjacency representation
-    let mut adjacencies: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut adjacencies: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
     for &region_id in &connectivity.regions {
         adjacencies.insert(region_id, Vec::new());
     }
@@ -184,7 +224,7 @@ pub fn visualize_semantic_layers(grid: &Grid<Tile>, semantic: &SemanticLayers) -
     ));
 
     // Region breakdown
-    let mut region_counts = HashMap::new();
+    let mut region_counts = BTreeMap::new();
     for region in &semantic.regions {
         *region_counts.entry(&region.kind).or_insert(0) += 1;
     }
@@ -195,7 +235,7 @@ pub fn visualize_semantic_layers(grid: &Grid<Tile>, semantic: &SemanticLayers) -
     output.push('\n');
 
     // Marker breakdown
-    let mut marker_counts = HashMap::new();
+    let mut marker_counts = BTreeMap::new();
     for marker in &semantic.markers {
         *marker_counts.entry(marker.tag()).or_insert(0) += 1;
     }
@@ -208,8 +248,8 @@ pub fn visualize_semantic_layers(grid: &Grid<Tile>, semantic: &SemanticLayers) -
     // Region visualization
     output.push_str("=== REGION MAP ===\n");
     output.push_str("Legend: ");
-    for (kind, &char) in &config.region_chars {
-        if region_counts.contains_key(kind) {
+    for kind in region_counts.keys() {
+        if let Some(char) = config.region_chars.get(*kind) {
             output.push_str(&format!("{}={} ", char, kind));
         }
     }
@@ -0,0 +1,410 @@
+//! Settlements and the road network between them.
+//!
+//! [`generate`] places cities, towns, and villages on the land of an
+//! overworld and links them with a road hierarchy built on
+//! [`effects::route_road`]: highways join the cities into a spanning
+//! network, each town takes a road to its nearest reachable city or town,
+//! and each village a trail to its nearest reachable settlement. Each class
+//! has its own [`RoadConfig`], so highways bridge wide rivers while trails
+//! only ford.
+//!
+//! [`SettlementNetwork::apply`] builds the bridges and writes settlement
+//! markers and road masks into semantic layers.
+//!
+//! ```rust
+//! use terrain_forge::settlements::{self, SettlementConfig, SettlementTier};
+//! use terrain_forge::{Grid, Tile};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! grid.fill_rect(1, 1, 78, 58, Tile::Floor);
+//! let water = vec![vec![false; 80]; 60];
+//! let config = SettlementConfig::default().with_counts(2, 3, 5);
+//! let network = settlements::generate(&grid, &water, &config, 5);
+//! assert_eq!(network.of_tier(SettlementTier::City).count(), 2);
+//! assert!(network.unconnected().is_empty());
+//! ```
+
+use crate::effects::{self, mark_road, Road, RoadConfig};
+use crate::semantic::{Marker, SemanticLayers};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Name of the [`Masks`](crate::semantic::Masks) layer holding highway
+/// cells, written by [`SettlementNetwork::apply`].
+pub const HIGHWAY_MASK: &str = "highway";
+
+/// Name of the [`Masks`](crate::semantic::Masks) layer holding trail cells,
+/// written by [`SettlementNetwork::apply`].
+pub const TRAIL_MASK: &str = "trail";
+
+/// Marker metadata key holding a settlement's index in
+/// [`SettlementNetwork::settlements`].
+pub const SETTLEMENT_KEY: &str = "settlement";
+
+/// Size class of a settlement; also its marker tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementTier {
+    City,
+    Town,
+    Village,
+}
+
+impl SettlementTier {
+    pub fn tag(self) -> &'static str {
+        match self {
+            SettlementTier::City => "city",
+            SettlementTier::Town => "town",
+            SettlementTier::Village => "village",
+        }
+    }
+}
+
+/// Class of a link in the road hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoadClass {
+    /// Between cities.
+    Highway,
+    /// From a town to a city or town.
+    Road,
+    /// From a village to any settlement.
+    Trail,
+}
+
+/// Configuration for [`generate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettlementConfig {
+    /// Default: 3.
+    pub cities: usize,
+    /// Default: 5.
+    pub towns: usize,
+    /// Default: 10.
+    pub villages: usize,
+    /// Minimum Euclidean distance between settlements. Default: 10.0.
+    pub min_spacing: f64,
+    /// Random land cells scored per settlement; the best wins. Default: 32.
+    pub candidates: usize,
+    /// Cities prefer sites at most this many cells from water; 0 disables
+    /// the preference. Default: 6.
+    pub water_affinity: usize,
+    /// Nearest settlements tried, in distance order, when routing a town's
+    /// road or a village's trail. Default: 3.
+    pub attempts: usize,
+    /// Default: long bridges (span 10), no fords.
+    pub highway: RoadConfig,
+    /// Default: [`RoadConfig::default`].
+    pub road: RoadConfig,
+    /// Default: fords only (depth 2), no bridges.
+    pub trail: RoadConfig,
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self {
+            cities: 3,
+            towns: 5,
+            villages: 10,
+            min_spacing: 10.0,
+            candidates: 32,
+            water_affinity: 6,
+            attempts: 3,
+            highway: RoadConfig::default().with_max_span(10).with_ford_depth(0),
+            road: RoadConfig::default(),
+            trail: RoadConfig::default().with_max_span(0).with_ford_depth(2),
+        }
+    }
+}
+
+impl SettlementConfig {
+    /// Sets `cities`, `towns`, and `villages`.
+    pub fn with_counts(mut self, cities: usize, towns: usize, villages: usize) -> Self {
+        self.cities = cities;
+        self.towns = towns;
+        self.villages = villages;
+        self
+    }
+
+    /// Sets `min_spacing`.
+    pub fn with_min_spacing(mut self, spacing: f64) -> Self {
+        self.min_spacing = spacing;
+        self
+    }
+
+    /// Sets `water_affinity`.
+    pub fn with_water_affinity(mut self, cells: usize) -> Self {
+        self.water_affinity = cells;
+        self
+    }
+
+    /// Sets the [`RoadConfig`] of one road class.
+    pub fn with_road_config(mut self, class: RoadClass, config: RoadConfig) -> Self {
+        match class {
+            RoadClass::Highway => self.highway = config,
+            RoadClass::Road => self.road = config,
+            RoadClass::Trail => self.trail = config,
+        }
+        self
+    }
+
+    fn road_config(&self, class: RoadClass) -> &RoadConfig {
+        match class {
+            RoadClass::Highway => &self.highway,
+            RoadClass::Road => &self.road,
+            RoadClass::Trail => &self.trail,
+        }
+    }
+}
+
+/// A placed settlement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settlement {
+    pub tier: SettlementTier,
+    pub x: usize,
+    pub y: usize,
+}
+
+/// A routed road between two settlements.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoadLink {
+    pub class: RoadClass,
+    /// Index into [`SettlementNetwork::settlements`].
+    pub from: usize,
+    pub to: usize,
+    pub road: Road,
+}
+
+/// Result of [`generate`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettlementNetwork {
+    /// Cities first, then towns, then villages.
+    pub settlements: Vec<Settlement>,
+    pub links: Vec<RoadLink>,
+}
+
+impl SettlementNetwork {
+    pub fn of_tier(&self, tier: SettlementTier) -> impl Iterator<Item = &Settlement> + '_ {
+        self.settlements.iter().filter(move |s| s.tier == tier)
+    }
+
+    pub fn links_of(&self, class: RoadClass) -> impl Iterator<Item = &RoadLink> + '_ {
+        self.links.iter().filter(move |l| l.class == class)
+    }
+
+    /// Indices of settlements no road reaches.
+    pub fn unconnected(&self) -> Vec<usize> {
+        (0..self.settlements.len())
+            .filter(|&i| !self.links.iter().any(|l| l.from == i || l.to == i))
+            .collect()
+    }
+
+    /// Builds every link's bridges (see [`Road::build_bridges`]), marks
+    /// each road with [`mark_road`] plus [`HIGHWAY_MASK`] or
+    /// [`TRAIL_MASK`] by class, and adds a marker per settlement tagged
+    /// with its tier and carrying [`SETTLEMENT_KEY`].
+    pub fn apply(
+        &self,
+        grid: &mut Grid<Tile>,
+        water: &mut [Vec<bool>],
+        semantic: &mut SemanticLayers,
+    ) {
+        for link in &self.links {
+            link.road.build_bridges(grid, water);
+            mark_road(semantic, &link.road);
+            let mask = match link.class {
+                RoadClass::Highway => HIGHWAY_MASK,
+                RoadClass::Road => continue,
+                RoadClass::Trail => TRAIL_MASK,
+            };
+            for &(x, y) in &link.road.path {
+                semantic.masks.set_named(mask, x, y, true);
+            }
+        }
+        for (i, s) in self.settlements.iter().enumerate() {
+            semantic.markers.push(
+                Marker::with_tag(s.x as u32, s.y as u32, s.tier.tag().to_string())
+                    .with_metadata(SETTLEMENT_KEY, i.to_string()),
+            );
+        }
+    }
+}
+
+/// Places settlements on land and routes the road hierarchy between them.
+///
+/// Land is floor not set in `water` (indexed `[y][x]`), as for
+/// [`effects::route_road`]. Each settlement takes the best of `candidates`
+/// random land cells at least `min_spacing` from earlier ones, scoring
+/// distance from them and, for cities, closeness to water; tiers that run
+/// out of room get fewer settlements. Highways are added shortest first
+/// between cities not yet joined, so they form a spanning tree of each
+/// reachable group. Roads and trails that cannot be routed are left out;
+/// see [`SettlementNetwork::unconnected`].
+pub fn generate(
+    grid: &Grid<Tile>,
+    water: &[Vec<bool>],
+    config: &SettlementConfig,
+    seed: u64,
+) -> SettlementNetwork {
+    let settlements = place(grid, water, config, &mut Rng::new(seed));
+    let mut links = Vec::new();
+    let route = |from: usize, to: usize, class: RoadClass| {
+        let (a, b) = (&settlements[from], &settlements[to]);
+        effects::route_road(
+            grid,
+            water,
+            (a.x, a.y),
+            (b.x, b.y),
+            config.road_config(class),
+        )
+        .map(|road| RoadLink {
+            class,
+            from,
+            to,
+            road,
+        })
+    };
+
+    // Highways: Kruskal over city pairs, skipping pairs that cannot be routed.
+    let cities: Vec<usize> = (0..settlements.len())
+        .filter(|&i| settlements[i].tier == SettlementTier::City)
+        .collect();
+    let mut pairs: Vec<(f64, usize, usize)> = cities
+        .iter()
+        .enumerate()
+        .flat_map(|(n, &a)| cities[n + 1..].iter().map(move |&b| (a, b)))
+        .map(|(a, b)| (distance(&settlements[a], &settlements[b]), a, b))
+        .collect();
+    pairs.sort_by(|p, q| p.0.total_cmp(&q.0));
+    let mut group: Vec<usize> = (0..settlements.len()).collect();
+    for (_, a, b) in pairs {
+        let (ga, gb) = (find(&mut group, a), find(&mut group, b));
+        if ga == gb {
+            continue;
+        }
+        if let Some(link) = route(a, b, RoadClass::Highway) {
+            group[ga] = gb;
+            links.push(link);
+        }
+    }
+
+    // Roads and trails to the nearest reachable settlement up the hierarchy.
+    for (i, s) in settlements.iter().enumerate() {
+        let class = match s.tier {
+            SettlementTier::City => continue,
+            SettlementTier::Town => RoadClass::Road,
+            SettlementTier::Village => RoadClass::Trail,
+        };
+        let mut targets: Vec<usize> = (0..settlements.len())
+            .filter(|&j| j != i)
+            .filter(|&j| class == RoadClass::Trail || settlements[j].tier < s.tier || j < i)
+            .collect();
+        targets.sort_by(|&a, &b| {
+            distance(s, &settlements[a]).total_cmp(&distance(s, &settlements[b]))
+        });
+        if let Some(link) = targets
+            .into_iter()
+            .take(config.attempts.max(1))
+            .find_map(|j| route(i, j, class))
+        {
+            links.push(link);
+        }
+    }
+
+    SettlementNetwork { settlements, links }
+}
+
+fn place(
+    grid: &Grid<Tile>,
+    water: &[Vec<bool>],
+    config: &SettlementConfig,
+    rng: &mut Rng,
+) -> Vec<Settlement> {
+    let (w, h) = (grid.width(), grid.height());
+    let wet = |x: usize, y: usize| water.get(y).and_then(|r| r.get(x)).copied() == Some(true);
+    let land: Vec<(usize, usize)> = grid
+        .iter()
+        .filter(|&(x, y, t)| t.is_floor() && !wet(x, y))
+        .map(|(x, y, _)| (x, y))
+        .collect();
+
+    // 4-connected distance to water, for city sites.
+    let mut shore = vec![usize::MAX; w * h];
+    let mut queue: VecDeque<(usize, usize)> = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .filter(|&(x, y)| wet(x, y))
+        .collect();
+    for &(x, y) in &queue {
+        shore[y * w + x] = 0;
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let d = shore[y * w + x];
+        for (nx, ny) in [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ] {
+            if nx < w && ny < h && shore[ny * w + nx] == usize::MAX {
+                shore[ny * w + nx] = d + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    let mut placed: Vec<Settlement> = Vec::new();
+    let tiers = [
+        (SettlementTier::City, config.cities),
+        (SettlementTier::Town, config.towns),
+        (SettlementTier::Village, config.villages),
+    ];
+    for (tier, count) in tiers {
+        for _ in 0..count {
+            if land.is_empty() {
+                break;
+            }
+            let mut best: Option<(f64, Settlement)> = None;
+            for _ in 0..config.candidates.max(1) {
+                let (x, y) = land[rng.range_usize(0, land.len())];
+                let site = Settlement { tier, x, y };
+                let spread = placed
+                    .iter()
+                    .map(|p| distance(p, &site))
+                    .fold(f64::INFINITY, f64::min);
+                if spread < config.min_spacing {
+                    continue;
+                }
+                let near_water = tier == SettlementTier::City
+                    && config.water_affinity > 0
+                    && shore[y * w + x] <= config.water_affinity;
+                let score = spread.min(config.min_spacing * 3.0)
+                    + if near_water {
+                        config.min_spacing * 2.0
+                    } else {
+                        0.0
+                    };
+                if best.is_none_or(|(b, _)| score > b) {
+                    best = Some((score, site));
+                }
+            }
+            if let Some((_, site)) = best {
+                placed.push(site);
+            }
+        }
+    }
+    placed
+}
+
+fn distance(a: &Settlement, b: &Settlement) -> f64 {
+    let (dx, dy) = (a.x as f64 - b.x as f64, a.y as f64 - b.y as f64);
+    (dx * dx + dy * dy).sqrt()
+}
+
+fn find(group: &mut [usize], mut i: usize) -> usize {
+    while group[i] != i {
+        group[i] = group[group[i]];
+        i = group[i];
+    }
+    i
+}
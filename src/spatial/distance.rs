@@ -1,6 +1,7 @@
 //! Distance transform algorithms for spatial analysis
 
-use crate::{Cell, Grid};
+use crate::parallel;
+use crate::{Cell, Grid, Tile};
 use std::collections::VecDeque;
 
 /// Distance metrics for spatial calculations
@@ -49,6 +50,10 @@ impl DistanceTransform {
 
 /// Generate distance field from passable cells
 pub fn distance_field<C: Cell>(grid: &Grid<C>, metric: DistanceMetric) -> DistanceTransform {
+    if metric == DistanceMetric::Manhattan {
+        return manhattan_field(&grid.to_tiles());
+    }
+
     let mut transform = DistanceTransform::new(grid.width(), grid.height());
     let mut queue = VecDeque::new();
 
@@ -94,6 +99,54 @@ pub fn distance_field<C: Cell>(grid: &Grid<C>, metric: DistanceMetric) -> Distan
     transform
 }
 
+/// Exact Manhattan distances via separable row and column sweeps.
+fn manhattan_field(grid: &Grid<Tile>) -> DistanceTransform {
+    let (w, h) = (grid.width(), grid.height());
+    let mut transform = DistanceTransform::new(w, h);
+
+    // Distance to the nearest passable cell in the same row.
+    parallel::for_each_row(&mut transform.distances, w, |y, row| {
+        let mut d = f32::INFINITY;
+        for (x, out) in row.iter_mut().enumerate() {
+            d = if grid[(x, y)].is_floor() {
+                0.0
+            } else {
+                d + 1.0
+            };
+            *out = d;
+        }
+        d = f32::INFINITY;
+        for (x, out) in row.iter_mut().enumerate().rev() {
+            d = if grid[(x, y)].is_floor() {
+                0.0
+            } else {
+                d + 1.0
+            };
+            *out = out.min(d);
+        }
+    });
+
+    // Combine rows per column.
+    let rows = &transform.distances;
+    let columns = parallel::map(w, |x| {
+        let mut column: Vec<f32> = (0..h).map(|y| rows[y * w + x]).collect();
+        for y in 1..h {
+            column[y] = column[y].min(column[y - 1] + 1.0);
+        }
+        for y in (1..h).rev() {
+            column[y - 1] = column[y - 1].min(column[y] + 1.0);
+        }
+        column
+    });
+    for (x, column) in columns.into_iter().enumerate() {
+        for (y, d) in column.into_iter().enumerate() {
+            transform.distances[y * w + x] = d;
+        }
+    }
+
+    transform
+}
+
 fn neighbors(metric: DistanceMetric) -> &'static [(i32, i32)] {
     match metric {
         DistanceMetric::Manhattan => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
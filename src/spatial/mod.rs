@@ -1,14 +1,17 @@
 //! Spatial analysis algorithms.
 //!
-//! Includes distance fields, Dijkstra maps, flow fields, and morphology helpers.
+//! Includes distance fields, Dijkstra maps, flow fields, morphology helpers,
+//! and heightmap slope and aspect.
 
 pub mod distance;
 pub mod morphology;
 pub mod pathfinding;
+pub mod terrain;
 
 pub use distance::{distance_field, DistanceMetric, DistanceTransform};
 pub use morphology::{morphological_transform, MorphologyOp, StructuringElement};
 pub use pathfinding::{
-    dijkstra_map, flow_field_from_dijkstra, shortest_path, DijkstraMap, FlowField,
-    PathfindingConstraints,
+    dijkstra_map, dijkstra_map_with, flow_field_from_dijkstra, shortest_path, DijkstraMap,
+    FlowField, PathfindingConstraints,
 };
+pub use terrain::{aspect, block_steep, fill_depressions, slope, Basin, Depressions};
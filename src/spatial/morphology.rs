@@ -1,5 +1,6 @@
 //! Morphological operations for shape analysis
 
+use crate::parallel;
 use crate::{Cell, Grid, Tile};
 
 /// Morphological operations
@@ -102,99 +103,52 @@ pub fn morphological_transform<C: Cell>(
     op: MorphologyOp,
     element: &StructuringElement,
 ) -> Grid<Tile> {
+    let tiles = grid.to_tiles();
     match op {
-        MorphologyOp::Erosion => erosion(grid, element),
-        MorphologyOp::Dilation => dilation(grid, element),
-        MorphologyOp::Opening => {
-            let eroded = erosion(grid, element);
-            dilation_tile(&eroded, element)
-        }
-        MorphologyOp::Closing => {
-            let dilated = dilation(grid, element);
-            erosion_tile(&dilated, element)
-        }
+        MorphologyOp::Erosion => erosion(&tiles, element),
+        MorphologyOp::Dilation => dilation(&tiles, element),
+        MorphologyOp::Opening => dilation(&erosion(&tiles, element), element),
+        MorphologyOp::Closing => erosion(&dilation(&tiles, element), element),
     }
 }
 
-fn erosion<C: Cell>(grid: &Grid<C>, element: &StructuringElement) -> Grid<Tile> {
+fn erosion(grid: &Grid<Tile>, element: &StructuringElement) -> Grid<Tile> {
     let mut result = Grid::new(grid.width(), grid.height());
-
-    for y in 0..grid.height() {
-        for x in 0..grid.width() {
-            let mut all_match = true;
-
-            for ey in 0..element.height() {
-                for ex in 0..element.width() {
-                    if !element.get(ex, ey) {
-                        continue;
-                    }
-
-                    let gx = x as i32 + ex as i32 - element.center_x as i32;
-                    let gy = y as i32 + ey as i32 - element.center_y as i32;
-
-                    if let Some(cell) = grid.get(gx, gy) {
-                        if !cell.is_passable() {
-                            all_match = false;
-                            break;
-                        }
-                    } else {
-                        all_match = false;
-                        break;
-                    }
-                }
-                if !all_match {
-                    break;
-                }
-            }
-
-            let tile = if all_match { Tile::Floor } else { Tile::Wall };
-            result.set(x as i32, y as i32, tile);
+    parallel::for_each_row(result.cells_mut(), grid.width(), |y, row| {
+        for (x, tile) in row.iter_mut().enumerate() {
+            let all_match = element_cells(element, x, y)
+                .all(|(gx, gy)| grid.get(gx, gy).is_some_and(Tile::is_floor));
+            *tile = if all_match { Tile::Floor } else { Tile::Wall };
         }
-    }
-
+    });
     result
 }
 
-fn dilation<C: Cell>(grid: &Grid<C>, element: &StructuringElement) -> Grid<Tile> {
+fn dilation(grid: &Grid<Tile>, element: &StructuringElement) -> Grid<Tile> {
     let mut result = Grid::new(grid.width(), grid.height());
-
-    for y in 0..grid.height() {
-        for x in 0..grid.width() {
-            let mut any_match = false;
-
-            for ey in 0..element.height() {
-                for ex in 0..element.width() {
-                    if !element.get(ex, ey) {
-                        continue;
-                    }
-
-                    let gx = x as i32 + ex as i32 - element.center_x as i32;
-                    let gy = y as i32 + ey as i32 - element.center_y as i32;
-
-                    if let Some(cell) = grid.get(gx, gy) {
-                        if cell.is_passable() {
-                            any_match = true;
-                            break;
-                        }
-                    }
-                }
-                if any_match {
-                    break;
-                }
-            }
-
-            let tile = if any_match { Tile::Floor } else { Tile::Wall };
-            result.set(x as i32, y as i32, tile);
+    parallel::for_each_row(result.cells_mut(), grid.width(), |y, row| {
+        for (x, tile) in row.iter_mut().enumerate() {
+            let any_match = element_cells(element, x, y)
+                .any(|(gx, gy)| grid.get(gx, gy).is_some_and(Tile::is_floor));
+            *tile = if any_match { Tile::Floor } else { Tile::Wall };
         }
-    }
-
+    });
     result
 }
 
-fn erosion_tile(grid: &Grid<Tile>, element: &StructuringElement) -> Grid<Tile> {
-    erosion(grid, element)
-}
-
-fn dilation_tile(grid: &Grid<Tile>, element: &StructuringElement) -> Grid<Tile> {
-    dilation(grid, element)
+/// Grid coordinates covered by `element` centered on `(x, y)`.
+fn element_cells(
+    element: &StructuringElement,
+    x: usize,
+    y: usize,
+) -> impl Iterator<Item = (i32, i32)> + '_ {
+    (0..element.height())
+        .flat_map(move |ey| (0..element.width()).map(move |ex| (ex, ey)))
+        .filter(|&(ex, ey)| element.get(ex, ey))
+        .map(move |(ex, ey)| {
+            (
+                x as i32 + ex as i32 - element.center_x as i32,
+                y as i32 + ey as i32 - element.center_y as i32,
+            )
+        })
 }
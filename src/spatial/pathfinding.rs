@@ -2,12 +2,14 @@
 
 use crate::{Cell, Grid};
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BTreeMap, BinaryHeap};
 
 /// Dijkstra map for multi-goal pathfinding
 #[derive(Debug, Clone)]
 pub struct DijkstraMap {
     costs: Vec<f32>,
+    /// Per-cell entry costs of a weighted map; `None` for uniform maps.
+    entry_costs: Option<Vec<f32>>,
     width: usize,
     height: usize,
 }
@@ -16,11 +18,25 @@ impl DijkstraMap {
     pub fn new(width: usize, height: usize) -> Self {
         Self {
             costs: vec![f32::INFINITY; width * height],
+            entry_costs: None,
             width,
             height,
         }
     }
 
+    /// Cost multiplier for stepping onto `(x, y)`: 1.0 on uniform maps,
+    /// infinite where a weighted map found the cell impassable.
+    pub fn entry_cost(&self, x: usize, y: usize) -> f32 {
+        self.entry_costs
+            .as_ref()
+            .map_or(1.0, |costs| costs[y * self.width + x])
+    }
+
+    /// Whether the map was built with per-cell costs.
+    pub fn is_weighted(&self) -> bool {
+        self.entry_costs.is_some()
+    }
+
     pub fn get(&self, x: usize, y: usize) -> f32 {
         self.costs[y * self.width + x]
     }
@@ -73,13 +89,58 @@ impl FlowField {
 /// Pathfinding constraints
 #[derive(Debug, Clone)]
 pub struct PathfindingConstraints {
-    pub movement_cost: HashMap<(i32, i32), f32>,
+    /// Step offsets and their costs; ties between equally cheap steps go
+    /// to the first offset in key order.
+    pub movement_cost: BTreeMap<(i32, i32), f32>,
     pub blocked_cells: Vec<(usize, usize)>,
+    /// Cost multiplier for stepping onto each cell, indexed `[y][x]` (as
+    /// from [`TileSet::cost_map`](crate::tileset::TileSet::cost_map)).
+    /// Infinite, NaN, or negative entries are impassable; cells outside the
+    /// rows cost 1.0. Default: `None` (uniform).
+    pub cell_costs: Option<Vec<Vec<f32>>>,
+}
+
+impl PathfindingConstraints {
+    /// 4-directional movement at cost 1.0 per step, so map costs are
+    /// walking distances in steps.
+    pub fn cardinal() -> Self {
+        Self {
+            movement_cost: BTreeMap::from([
+                ((-1, 0), 1.0),
+                ((1, 0), 1.0),
+                ((0, -1), 1.0),
+                ((0, 1), 1.0),
+            ]),
+            ..Self::default()
+        }
+    }
+
+    /// Sets `cell_costs`.
+    pub fn with_cell_costs(mut self, costs: Vec<Vec<f32>>) -> Self {
+        self.cell_costs = Some(costs);
+        self
+    }
+
+    /// Adds to `blocked_cells`.
+    pub fn with_blocked_cells(mut self, cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        self.blocked_cells.extend(cells);
+        self
+    }
+
+    /// Entry cost of `(x, y)` from `cell_costs`, or `None` if impassable.
+    pub fn cell_cost(&self, x: usize, y: usize) -> Option<f32> {
+        let cost = self
+            .cell_costs
+            .as_ref()
+            .and_then(|rows| rows.get(y)?.get(x).copied())
+            .unwrap_or(1.0);
+        (cost.is_finite() && cost >= 0.0).then_some(cost)
+    }
 }
 
 impl Default for PathfindingConstraints {
     fn default() -> Self {
-        let mut movement_cost = HashMap::new();
+        let mut movement_cost = BTreeMap::new();
         // Standard 8-directional movement
         movement_cost.insert((-1, 0), 1.0);
         movement_cost.insert((1, 0), 1.0);
@@ -93,6 +154,7 @@ impl Default for PathfindingConstraints {
         Self {
             movement_cost,
             blocked_cells: Vec::new(),
+            cell_costs: None,
         }
     }
 }
@@ -122,14 +184,82 @@ impl PartialOrd for Node {
 }
 
 /// Generate Dijkstra map from multiple goals
+///
+/// Uses [`PathfindingConstraints::cell_costs`] when set; see
+/// [`dijkstra_map_with`] for computed costs.
 pub fn dijkstra_map<C: Cell>(
     grid: &Grid<C>,
     goals: &[(usize, usize)],
     constraints: &PathfindingConstraints,
+) -> DijkstraMap {
+    if constraints.cell_costs.is_some() {
+        dijkstra_map_with(grid, goals, constraints, |x, y, _| {
+            constraints.cell_cost(x, y)
+        })
+    } else {
+        build_dijkstra_map(
+            grid,
+            goals,
+            constraints,
+            None::<fn(usize, usize, &C) -> Option<f32>>,
+        )
+    }
+}
+
+/// Weighted Dijkstra map: `cost(x, y, cell)` is the multiplier for
+/// stepping onto a cell (scaling the movement cost), or `None` to block it.
+///
+/// Walls and `blocked_cells` stay impassable; `cost` replaces
+/// `cell_costs` (call [`PathfindingConstraints::cell_cost`] inside it to
+/// combine both). The map remembers the entry costs so
+/// [`flow_field_from_dijkstra`] and [`shortest_path`] follow the cheapest
+/// route rather than the shortest.
+///
+/// ```rust
+/// use terrain_forge::spatial::{dijkstra_map_with, flow_field_from_dijkstra, PathfindingConstraints};
+/// use terrain_forge::{Grid, Tile};
+///
+/// let mut grid = Grid::new(7, 3);
+/// grid.fill_rect(0, 0, 7, 3, Tile::Floor);
+/// // Row 1 is a swamp; rows 0 and 2 are roads.
+/// let constraints = PathfindingConstraints::default();
+/// let map = dijkstra_map_with(&grid, &[(6, 1)], &constraints, |_, y, _| {
+///     Some(if y == 1 { 5.0 } else { 1.0 })
+/// });
+/// assert!(map.get(0, 1) > map.get(0, 0));
+/// assert_ne!(flow_field_from_dijkstra(&map).get_direction(0, 1).1, 0);
+/// ```
+pub fn dijkstra_map_with<C: Cell>(
+    grid: &Grid<C>,
+    goals: &[(usize, usize)],
+    constraints: &PathfindingConstraints,
+    cost: impl Fn(usize, usize, &C) -> Option<f32>,
+) -> DijkstraMap {
+    build_dijkstra_map(grid, goals, constraints, Some(cost))
+}
+
+fn build_dijkstra_map<C: Cell>(
+    grid: &Grid<C>,
+    goals: &[(usize, usize)],
+    constraints: &PathfindingConstraints,
+    cost_fn: Option<impl Fn(usize, usize, &C) -> Option<f32>>,
 ) -> DijkstraMap {
     let mut map = DijkstraMap::new(grid.width(), grid.height());
     let mut heap = BinaryHeap::new();
 
+    // Entry cost per cell; walls and blocked cells are infinite.
+    if let Some(cost_fn) = &cost_fn {
+        let mut entry = vec![f32::INFINITY; grid.width() * grid.height()];
+        for (x, y, cell) in grid.iter() {
+            if cell.is_passable() && !constraints.blocked_cells.contains(&(x, y)) {
+                if let Some(c) = cost_fn(x, y, cell).filter(|c| c.is_finite() && *c >= 0.0) {
+                    entry[y * grid.width() + x] = c;
+                }
+            }
+        }
+        map.entry_costs = Some(entry);
+    }
+
     // Initialize goals with cost 0
     for &(x, y) in goals {
         map.set(x, y, 0.0);
@@ -140,6 +270,8 @@ pub fn dijkstra_map<C: Cell>(
         if cost > map.get(x, y) {
             continue;
         }
+        // Moving from a neighbor onto (x, y) pays (x, y)'s entry cost.
+        let entry_cost = map.entry_cost(x, y);
 
         for (&(dx, dy), &move_cost) in &constraints.movement_cost {
             let nx = x as i32 + dx;
@@ -159,7 +291,11 @@ pub fn dijkstra_map<C: Cell>(
                     }
                 }
 
-                let new_cost = cost + move_cost;
+                if !map.entry_cost(nx, ny).is_finite() {
+                    continue;
+                }
+
+                let new_cost = cost + move_cost * entry_cost;
                 if new_cost < map.get(nx, ny) {
                     map.set(nx, ny, new_cost);
                     heap.push(Node {
@@ -223,7 +359,11 @@ pub fn shortest_path<C: Cell>(
         let (x, y) = current;
         let current_cost = dijkstra.get(x, y);
         let mut best = None;
-        let mut best_cost = current_cost;
+        let mut best_cost = if dijkstra.is_weighted() {
+            f32::INFINITY
+        } else {
+            current_cost
+        };
 
         for &(dx, dy) in constraints.movement_cost.keys() {
             let nx = x as i32 + dx;
@@ -241,7 +381,15 @@ pub fn shortest_path<C: Cell>(
                 continue;
             }
 
-            let cost = dijkstra.get(npos.0, npos.1);
+            let mut cost = dijkstra.get(npos.0, npos.1);
+            if dijkstra.is_weighted() {
+                // Total cost through the neighbor; only moves that make
+                // progress toward the goal count.
+                if cost >= current_cost {
+                    continue;
+                }
+                cost += constraints.movement_cost[&(dx, dy)] * dijkstra.entry_cost(npos.0, npos.1);
+            }
             if cost < best_cost {
                 best_cost = cost;
                 best = Some(npos);
@@ -262,6 +410,10 @@ pub fn shortest_path<C: Cell>(
 }
 
 /// Generate flow field from Dijkstra map
+///
+/// On weighted maps (see [`dijkstra_map_with`]) each cell points at the
+/// neighbor with the cheapest total cost: its distance plus the step onto
+/// it, with diagonal steps costing `sqrt(2)`.
 pub fn flow_field_from_dijkstra(dijkstra: &DijkstraMap) -> FlowField {
     let mut flow = FlowField::new(dijkstra.width(), dijkstra.height());
 
@@ -273,7 +425,11 @@ pub fn flow_field_from_dijkstra(dijkstra: &DijkstraMap) -> FlowField {
             }
 
             let mut best_dir = (0, 0);
-            let mut best_cost = current_cost;
+            let mut best_cost = if dijkstra.is_weighted() {
+                f32::INFINITY
+            } else {
+                current_cost
+            };
 
             for dx in -1..=1 {
                 for dy in -1..=1 {
@@ -289,7 +445,19 @@ pub fn flow_field_from_dijkstra(dijkstra: &DijkstraMap) -> FlowField {
                         && (nx as usize) < dijkstra.width()
                         && (ny as usize) < dijkstra.height()
                     {
-                        let neighbor_cost = dijkstra.get(nx as usize, ny as usize);
+                        let (nx, ny) = (nx as usize, ny as usize);
+                        let mut neighbor_cost = dijkstra.get(nx, ny);
+                        if dijkstra.is_weighted() {
+                            if neighbor_cost >= current_cost {
+                                continue;
+                            }
+                            let step = if dx != 0 && dy != 0 {
+                                std::f32::consts::SQRT_2
+                            } else {
+                                1.0
+                            };
+                            neighbor_cost += step * dijkstra.entry_cost(nx, ny);
+                        }
                         if neighbor_cost < best_cost {
                             best_cost = neighbor_cost;
                             best_dir = (dx, dy);
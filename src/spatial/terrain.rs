@@ -0,0 +1,218 @@
+//! Slope, aspect, and basins of heightmaps.
+//!
+//! Heightmaps are rows of `f64` indexed `[y][x]`, like
+//! [`DiamondSquare::heightmap`](crate::algorithms::DiamondSquare::heightmap).
+//! Gradients use central differences (one-sided on the edges), in height
+//! units per cell. [`fill_depressions`] finds the basins that trap water,
+//! for lake placement and for draining rivers without dead ends.
+
+use crate::grid::neighbors_8_within;
+use crate::{Grid, Tile};
+
+/// Aspect of cells with no gradient.
+pub const FLAT: f64 = -1.0;
+
+/// Height gradient `(d/dx, d/dy)` at `(x, y)`.
+fn gradient(heights: &[Vec<f64>], x: usize, y: usize) -> (f64, f64) {
+    let (w, h) = (heights[y].len(), heights.len());
+    let diff = |lo: usize, hi: usize, at: &dyn Fn(usize) -> f64| {
+        if hi > lo {
+            (at(hi) - at(lo)) / (hi - lo) as f64
+        } else {
+            0.0
+        }
+    };
+    let gx = diff(x.saturating_sub(1), (x + 1).min(w - 1), &|i| heights[y][i]);
+    let gy = diff(y.saturating_sub(1), (y + 1).min(h - 1), &|i| heights[i][x]);
+    (gx, gy)
+}
+
+/// Steepness of each cell: gradient magnitude in height per cell.
+pub fn slope(heights: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    (0..heights.len())
+        .map(|y| {
+            (0..heights[y].len())
+                .map(|x| {
+                    let (gx, gy) = gradient(heights, x, y);
+                    gx.hypot(gy)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Downhill direction of each cell as a compass bearing in degrees:
+/// 0 faces north (toward row 0), 90 east, clockwise. Level cells are
+/// [`FLAT`].
+pub fn aspect(heights: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    (0..heights.len())
+        .map(|y| {
+            (0..heights[y].len())
+                .map(|x| {
+                    let (gx, gy) = gradient(heights, x, y);
+                    if gx == 0.0 && gy == 0.0 {
+                        return FLAT;
+                    }
+                    // Downhill is against the gradient; north is -y.
+                    let bearing = (-gx).atan2(gy).to_degrees();
+                    bearing.rem_euclid(360.0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Walls every cell of `grid` whose `slopes` entry exceeds `max_slope`;
+/// returns how many floor cells were blocked.
+pub fn block_steep(grid: &mut Grid<Tile>, slopes: &[Vec<f64>], max_slope: f64) -> usize {
+    let mut blocked = 0;
+    for (y, row) in slopes.iter().enumerate().take(grid.height()) {
+        for (x, &s) in row.iter().enumerate().take(grid.width()) {
+            if s > max_slope && grid[(x, y)].is_floor() {
+                grid.set(x as i32, y as i32, Tile::Wall);
+                blocked += 1;
+            }
+        }
+    }
+    blocked
+}
+
+/// A closed depression that holds water until it reaches its spill point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Basin {
+    /// 1-based; matches [`Depressions::basin_ids`].
+    pub id: u32,
+    /// Cells below the fill level.
+    pub cells: Vec<(usize, usize)>,
+    /// Lowest rim cell, where the filled basin overflows.
+    pub spill: (usize, usize),
+    /// Water surface height of the filled basin.
+    pub fill_level: f64,
+    /// Fill level minus the lowest cell.
+    pub depth: f64,
+}
+
+/// Result of [`fill_depressions`].
+#[derive(Debug, Clone)]
+pub struct Depressions {
+    /// Heights with every basin filled to its fill level, so water can
+    /// drain to the map edge from anywhere (across level lakes).
+    pub filled: Vec<Vec<f64>>,
+    /// Basin id of each cell, `0` outside basins.
+    pub basin_ids: Vec<Vec<u32>>,
+    /// Basins from deepest to shallowest.
+    pub basins: Vec<Basin>,
+}
+
+/// Finds basins with a priority flood from the map edge (8-connected).
+///
+/// Every cell is raised to the lowest level at which water could leave the
+/// map from it; cells that rise form basins, grouped by connectivity.
+pub fn fill_depressions(heights: &[Vec<f64>]) -> Depressions {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    #[derive(PartialEq)]
+    struct Level(f64);
+    impl Eq for Level {}
+    impl PartialOrd for Level {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Level {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.total_cmp(&other.0)
+        }
+    }
+
+    let h = heights.len();
+    let w = heights.first().map_or(0, |row| row.len());
+    let mut filled = heights.to_vec();
+    let mut done = vec![false; w * h];
+    let mut queue = BinaryHeap::new();
+    for y in 0..h {
+        for x in 0..w {
+            if x == 0 || y == 0 || x + 1 == w || y + 1 == h {
+                done[y * w + x] = true;
+                queue.push(Reverse((Level(heights[y][x]), y, x)));
+            }
+        }
+    }
+    while let Some(Reverse((Level(level), y, x))) = queue.pop() {
+        for (nx, ny) in neighbors_8_within(x, y, w, h) {
+            if !done[ny * w + nx] {
+                done[ny * w + nx] = true;
+                filled[ny][nx] = heights[ny][nx].max(level);
+                queue.push(Reverse((Level(filled[ny][nx]), ny, nx)));
+            }
+        }
+    }
+
+    let flooded = |x: usize, y: usize| filled[y][x] > heights[y][x];
+    let mut basin_ids = vec![vec![0u32; w]; h];
+    let mut basins = Vec::new();
+    for y in 0..h {
+        for x in 0..w {
+            if !flooded(x, y) || basin_ids[y][x] != 0 {
+                continue;
+            }
+            let id = basins.len() as u32 + 1;
+            basin_ids[y][x] = id;
+            let mut cells = Vec::new();
+            let mut stack = vec![(x, y)];
+            while let Some((cx, cy)) = stack.pop() {
+                cells.push((cx, cy));
+                for (nx, ny) in neighbors_8_within(cx, cy, w, h) {
+                    if flooded(nx, ny) && basin_ids[ny][nx] == 0 {
+                        basin_ids[ny][nx] = id;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            cells.sort_unstable_by_key(|&(x, y)| (y, x));
+            let fill_level = cells
+                .iter()
+                .map(|&(x, y)| filled[y][x])
+                .fold(f64::MIN, f64::max);
+            let bottom = cells
+                .iter()
+                .map(|&(x, y)| heights[y][x])
+                .fold(f64::MAX, f64::min);
+            let spill = cells
+                .iter()
+                .flat_map(|&(x, y)| neighbors_8_within(x, y, w, h))
+                .filter(|&(nx, ny)| basin_ids[ny][nx] != id)
+                .min_by(|&(ax, ay), &(bx, by)| {
+                    heights[ay][ax]
+                        .total_cmp(&heights[by][bx])
+                        .then((ay, ax).cmp(&(by, bx)))
+                })
+                .expect("basins never touch the map edge");
+            basins.push(Basin {
+                id,
+                cells,
+                spill,
+                fill_level,
+                depth: fill_level - bottom,
+            });
+        }
+    }
+
+    // Renumber deepest first.
+    basins.sort_by(|a, b| b.depth.total_cmp(&a.depth).then(a.id.cmp(&b.id)));
+    let mut renumber = vec![0u32; basins.len() + 1];
+    for (i, basin) in basins.iter_mut().enumerate() {
+        renumber[basin.id as usize] = i as u32 + 1;
+        basin.id = i as u32 + 1;
+    }
+    for id in basin_ids.iter_mut().flatten() {
+        *id = renumber[*id as usize];
+    }
+
+    Depressions {
+        filled,
+        basin_ids,
+        basins,
+    }
+}
@@ -0,0 +1,243 @@
+//! Weighted spawn tables sampled at markers.
+//!
+//! Where [`entities`](crate::entities) maps each marker to one fixed
+//! entity, a [`SpawnTables`] set rolls concrete content: each
+//! [`SpawnTable`] matches markers by tag or category, metadata, and
+//! difficulty or tier, and lists weighted [`SpawnEntry`]s with count
+//! ranges. [`SpawnTables::sample`] draws from the first matching table for
+//! every marker with the map seed and returns a serializable [`Spawn`]
+//! list. Each marker draws from its own RNG stream, so a marker's spawns
+//! depend only on the seed and its index, never on the other markers.
+//!
+//! ```rust
+//! use terrain_forge::semantic::{Marker, MarkerType};
+//! use terrain_forge::spawns::{SpawnEntry, SpawnTable, SpawnTables};
+//!
+//! let markers = vec![
+//!     Marker::new(3, 4, MarkerType::EncounterZone { difficulty: 1 }),
+//!     Marker::new(9, 2, MarkerType::EncounterZone { difficulty: 4 }),
+//! ];
+//! let tables = SpawnTables::new()
+//!     .with_table(
+//!         SpawnTable::new("encounter")
+//!             .with_levels(3, 5)
+//!             .with_entry(SpawnEntry::new("ogre", 1.0)),
+//!     )
+//!     .with_table(
+//!         SpawnTable::new("encounter")
+//!             .with_entry(SpawnEntry::new("rat", 3.0).with_count(2, 4))
+//!             .with_entry(SpawnEntry::new("goblin", 1.0)),
+//!     );
+//!
+//! let spawns = tables.sample(&markers, 42);
+//! assert_eq!(spawns, tables.sample(&markers, 42));
+//! assert!(spawns.iter().any(|s| s.name == "ogre" && (s.x, s.y) == (9, 2)));
+//! ```
+
+use crate::error::{Error, Result};
+use crate::semantic::{meta, Marker};
+use crate::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One weighted option in a [`SpawnTable`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnEntry {
+    /// What to spawn: a creature, item, or loot table id.
+    pub name: String,
+    /// Relative chance among the table's entries. Default: 1.0.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Fewest spawned when picked. Default: 1.
+    #[serde(default = "default_count")]
+    pub min_count: usize,
+    /// Most spawned when picked. Default: 1.
+    #[serde(default = "default_count")]
+    pub max_count: usize,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_count() -> usize {
+    1
+}
+
+impl SpawnEntry {
+    pub fn new(name: impl Into<String>, weight: f64) -> Self {
+        Self {
+            name: name.into(),
+            weight,
+            min_count: 1,
+            max_count: 1,
+        }
+    }
+
+    /// Sets `min_count` and `max_count`.
+    pub fn with_count(mut self, min: usize, max: usize) -> Self {
+        self.min_count = min;
+        self.max_count = max;
+        self
+    }
+}
+
+/// Weighted entries for the markers it matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnTable {
+    /// Marker tag, category (`"loot"`, `"encounter"`), or `"*"` for any
+    /// marker, as in [`EntityRule::marker`](crate::entities::EntityRule::marker).
+    pub marker: String,
+    /// Metadata entries the marker must have, with these exact values.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Inclusive range the marker's level must fall in; see [`level`].
+    /// Markers without a level never match a table with a range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub levels: Option<(i64, i64)>,
+    /// Picks per marker. Default: 1.
+    #[serde(default = "default_count")]
+    pub rolls: usize,
+    pub entries: Vec<SpawnEntry>,
+}
+
+impl SpawnTable {
+    pub fn new(marker: impl Into<String>) -> Self {
+        Self {
+            marker: marker.into(),
+            metadata: BTreeMap::new(),
+            levels: None,
+            rolls: 1,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Requires a metadata entry.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets `levels`.
+    pub fn with_levels(mut self, min: i64, max: i64) -> Self {
+        self.levels = Some((min, max));
+        self
+    }
+
+    /// Sets `rolls`.
+    pub fn with_rolls(mut self, rolls: usize) -> Self {
+        self.rolls = rolls;
+        self
+    }
+
+    /// Adds an entry.
+    pub fn with_entry(mut self, entry: SpawnEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Whether `marker` matches this table.
+    pub fn matches(&self, marker: &Marker) -> bool {
+        let kind = self.marker == "*"
+            || self.marker == marker.tag()
+            || self.marker == marker.marker_type.category();
+        kind && self
+            .metadata
+            .iter()
+            .all(|(k, v)| marker.metadata.get(k) == Some(v))
+            && self
+                .levels
+                .is_none_or(|(min, max)| level(marker).is_some_and(|l| (min..=max).contains(&l)))
+    }
+
+    /// Rolls `rolls` entries for the marker at `index` in `markers`.
+    fn roll(&self, index: usize, marker: &Marker, rng: &mut Rng) -> Vec<Spawn> {
+        let weights: Vec<f64> = self.entries.iter().map(|e| e.weight).collect();
+        (0..self.rolls)
+            .filter_map(|_| {
+                let entry = &self.entries[rng.weighted_index(&weights)?];
+                let (min, max) = (entry.min_count, entry.max_count.max(entry.min_count));
+                let count = rng.range_usize_inclusive(min, max);
+                (count > 0).then(|| Spawn {
+                    name: entry.name.clone(),
+                    count,
+                    x: marker.x,
+                    y: marker.y,
+                    marker: index,
+                    region_id: marker.region_id,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A concrete spawn at a marker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spawn {
+    pub name: String,
+    pub count: usize,
+    pub x: u32,
+    pub y: u32,
+    /// Index of the marker it was rolled for.
+    pub marker: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region_id: Option<u32>,
+}
+
+/// Ordered spawn tables; the first table matching a marker is used.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpawnTables {
+    pub tables: Vec<SpawnTable>,
+}
+
+impl SpawnTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a table, after (lower priority than) existing ones.
+    pub fn with_table(mut self, table: SpawnTable) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    /// Parses tables from JSON: `{ "tables": [ ... ] }`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::parse("spawn tables", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// The table used for `marker`, if any.
+    pub fn table_for(&self, marker: &Marker) -> Option<&SpawnTable> {
+        self.tables.iter().find(|table| table.matches(marker))
+    }
+
+    /// Spawns for every marker some table matches, in marker order.
+    ///
+    /// Marker `i` rolls with stream `i` of `Rng::new(seed)`. Picks that
+    /// roll a count of 0 are left out.
+    pub fn sample(&self, markers: &[Marker], seed: u64) -> Vec<Spawn> {
+        let streams = Rng::new(seed);
+        markers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, marker)| Some((i, marker, self.table_for(marker)?)))
+            .flat_map(|(i, marker, table)| table.roll(i, marker, &mut streams.stream(i as u64)))
+            .collect()
+    }
+}
+
+/// A marker's difficulty or tier: its `encounter.difficulty`, `loot.tier`,
+/// or `quest.priority` metadata, in that order.
+pub fn level(marker: &Marker) -> Option<i64> {
+    [
+        meta::ENCOUNTER_DIFFICULTY,
+        meta::LOOT_TIER,
+        meta::QUEST_PRIORITY,
+    ]
+    .iter()
+    .find_map(|key| marker.get_i64(key))
+}
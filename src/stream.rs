@@ -0,0 +1,157 @@
+//! Streaming chunk generation for maps too large to hold in memory.
+//!
+//! A [`ChunkGenerator`] can fill any rectangle of a map on its own, so
+//! [`chunks`] and [`rows`] yield finished pieces one at a time and memory
+//! stays O(chunk size). [`write_png`] streams a whole map to an 8-bit
+//! grayscale PNG band by band.
+//!
+//! Implemented by [`NoiseFill`](crate::algorithms::NoiseFill) and
+//! [`Voronoi`](crate::algorithms::Voronoi), whose chunks match a full
+//! [`Algorithm::generate`](crate::Algorithm::generate) exactly, and by
+//! [`Percolation`](crate::algorithms::Percolation), which seeds each cell
+//! from its position and skips the global largest-region pass.
+//!
+//! ```rust
+//! use terrain_forge::algorithms::NoiseFill;
+//! use terrain_forge::stream;
+//!
+//! let noise = NoiseFill::default();
+//! let mut floors = 0;
+//! for chunk in stream::chunks(&noise, 2000, 2000, 256, 7) {
+//!     floors += chunk.grid.count(|t| t.is_floor());
+//! }
+//! assert!(floors > 0);
+//! ```
+
+use crate::{Grid, Tile};
+
+/// Generator that can produce any rectangle of a map independently.
+pub trait ChunkGenerator: Sync {
+    /// Fills `chunk` with the cells starting at `origin` of a
+    /// `map_width` x `map_height` map generated from `seed`.
+    fn generate_chunk(
+        &self,
+        chunk: &mut Grid<Tile>,
+        origin: (usize, usize),
+        map_size: (usize, usize),
+        seed: u64,
+    );
+}
+
+/// A generated piece of a streamed map.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    /// Map x of the chunk's left column.
+    pub x: usize,
+    /// Map y of the chunk's top row.
+    pub y: usize,
+    pub grid: Grid<Tile>,
+}
+
+/// Iterator over the chunks of a map, row-major.
+pub struct ChunkStream<'a, G: ?Sized> {
+    generator: &'a G,
+    map_size: (usize, usize),
+    chunk_size: (usize, usize),
+    seed: u64,
+    next: (usize, usize),
+}
+
+impl<G: ChunkGenerator + ?Sized> Iterator for ChunkStream<'_, G> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        let (w, h) = self.map_size;
+        let (x, y) = self.next;
+        if y >= h || w == 0 {
+            return None;
+        }
+        let cw = self.chunk_size.0.min(w - x);
+        let ch = self.chunk_size.1.min(h - y);
+        let mut grid = Grid::new(cw, ch);
+        self.generator
+            .generate_chunk(&mut grid, (x, y), self.map_size, self.seed);
+
+        self.next = if x + cw >= w {
+            (0, y + ch)
+        } else {
+            (x + cw, y)
+        };
+        Some(Chunk { x, y, grid })
+    }
+}
+
+/// Streams square `chunk_size` chunks of a `width` x `height` map.
+pub fn chunks<G: ChunkGenerator + ?Sized>(
+    generator: &G,
+    width: usize,
+    height: usize,
+    chunk_size: usize,
+    seed: u64,
+) -> ChunkStream<'_, G> {
+    ChunkStream {
+        generator,
+        map_size: (width, height),
+        chunk_size: (chunk_size.max(1), chunk_size.max(1)),
+        seed,
+        next: (0, 0),
+    }
+}
+
+/// Streams a map as `band_height`-row horizontal bands spanning the full width.
+pub fn rows<G: ChunkGenerator + ?Sized>(
+    generator: &G,
+    width: usize,
+    height: usize,
+    band_height: usize,
+    seed: u64,
+) -> ChunkStream<'_, G> {
+    ChunkStream {
+        generator,
+        map_size: (width, height),
+        chunk_size: (width.max(1), band_height.max(1)),
+        seed,
+        next: (0, 0),
+    }
+}
+
+/// Streams a map to an 8-bit grayscale PNG (floor white, wall black),
+/// holding one band of `band_height` rows at a time.
+pub fn write_png<G: ChunkGenerator + ?Sized, W: std::io::Write>(
+    generator: &G,
+    width: usize,
+    height: usize,
+    band_height: usize,
+    seed: u64,
+    out: W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer()?;
+    for band in rows(generator, width, height, band_height, seed) {
+        let bytes: Vec<u8> = band
+            .grid
+            .iter()
+            .map(|(_, _, t)| if t.is_floor() { 255 } else { 0 })
+            .collect();
+        std::io::Write::write_all(&mut stream, &bytes)?;
+    }
+    stream.finish()?;
+    Ok(())
+}
+
+/// Streams a map to a PNG file; see [`write_png`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_png<G: ChunkGenerator + ?Sized, P: AsRef<std::path::Path>>(
+    path: P,
+    generator: &G,
+    width: usize,
+    height: usize,
+    band_height: usize,
+    seed: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_png(generator, width, height, band_height, seed, file)
+}
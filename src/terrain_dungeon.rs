@@ -0,0 +1,315 @@
+//! Dungeons draped over a heightmap.
+//!
+//! [`TerrainDungeonGenerator`] wraps any dungeon algorithm and fits its
+//! layout to terrain (rows of `f64`, as produced by
+//! [`DiamondSquare::heightmap`](crate::algorithms::DiamondSquare::heightmap)):
+//!
+//! - **Rooms** (open areas at least 3x3 across in the base layout) move up
+//!   to `snap_radius` cells to the flattest nearby ground; rooms that can't
+//!   find ground within `max_room_relief` are dropped.
+//! - **Cliffs** (slope of `cliff_slope` or more) are always wall.
+//! - **Corridors** are re-routed between rooms as a spanning tree of
+//!   cheapest paths, where each step costs more the steeper the cell, so
+//!   they wind along low-slope ground and never cross a cliff.
+//!
+//! Slope is the largest height change to a 4-neighbour. The output carries
+//! the tiles and an elevation channel with each room floor levelled to its
+//! mean height, ready for [`theme`](crate::theme) rules or the heightmap
+//! exporters.
+//!
+//! ```rust
+//! use terrain_forge::algorithms::{Bsp, DiamondSquare};
+//! use terrain_forge::terrain_dungeon::{TerrainDungeonConfig, TerrainDungeonGenerator};
+//!
+//! let heights = DiamondSquare::default().heightmap(80, 60, 7);
+//! let dungeon = TerrainDungeonGenerator::new(Bsp::default(), TerrainDungeonConfig::default())
+//!     .generate(&heights, 7);
+//! assert_eq!(dungeon.elevation.len(), 60);
+//! for (x, y, tile) in dungeon.grid.iter() {
+//!     assert!(!(dungeon.cliffs[y][x] && tile.is_floor()));
+//! }
+//! ```
+
+use crate::grid::{neighbors_4_within, neighbors_8_within};
+use crate::spatial::{shortest_path, PathfindingConstraints};
+use crate::{Algorithm, Grid, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Configuration for [`TerrainDungeonGenerator`]. Defaults suit the
+/// fairly rough output of `DiamondSquare::heightmap`; lower the thresholds
+/// for smoother terrain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrainDungeonConfig {
+    /// Slope (height change per cell) at which a cell becomes a cliff.
+    /// Default: 0.15.
+    pub cliff_slope: f64,
+    /// Largest height range a room may span. Default: 0.3.
+    pub max_room_relief: f64,
+    /// How far a room may move to find flatter ground. Default: 6.
+    pub snap_radius: usize,
+    /// Extra corridor cost per step, per unit of slope. Default: 20.0.
+    pub slope_cost: f64,
+}
+
+impl Default for TerrainDungeonConfig {
+    fn default() -> Self {
+        Self {
+            cliff_slope: 0.15,
+            max_room_relief: 0.3,
+            snap_radius: 6,
+            slope_cost: 20.0,
+        }
+    }
+}
+
+impl TerrainDungeonConfig {
+    /// Sets `cliff_slope`.
+    pub fn with_cliff_slope(mut self, slope: f64) -> Self {
+        self.cliff_slope = slope;
+        self
+    }
+
+    /// Sets `max_room_relief`.
+    pub fn with_max_room_relief(mut self, relief: f64) -> Self {
+        self.max_room_relief = relief;
+        self
+    }
+
+    /// Sets `snap_radius`.
+    pub fn with_snap_radius(mut self, radius: usize) -> Self {
+        self.snap_radius = radius;
+        self
+    }
+
+    /// Sets `slope_cost`.
+    pub fn with_slope_cost(mut self, cost: f64) -> Self {
+        self.slope_cost = cost;
+        self
+    }
+}
+
+/// A dungeon fitted to terrain.
+#[derive(Debug, Clone)]
+pub struct TerrainDungeon {
+    pub grid: Grid<Tile>,
+    /// Heights as rows like the input; room floors are levelled to their
+    /// mean height.
+    pub elevation: Vec<Vec<f64>>,
+    /// Cliff cells, indexed `[y][x]`; all of them are wall.
+    pub cliffs: Vec<Vec<bool>>,
+    /// Cells of each placed room.
+    pub rooms: Vec<Vec<(usize, usize)>>,
+}
+
+/// Fits a dungeon algorithm's layout to a heightmap.
+pub struct TerrainDungeonGenerator {
+    algorithm: Box<dyn Algorithm + Send + Sync>,
+    config: TerrainDungeonConfig,
+}
+
+impl TerrainDungeonGenerator {
+    pub fn new<A: Algorithm + 'static>(algorithm: A, config: TerrainDungeonConfig) -> Self {
+        Self {
+            algorithm: Box::new(algorithm),
+            config,
+        }
+    }
+
+    /// Generates a dungeon the size of `heights`.
+    ///
+    /// Rooms that only cliffs separate stay unconnected.
+    pub fn generate(&self, heights: &[Vec<f64>], seed: u64) -> TerrainDungeon {
+        let cfg = &self.config;
+        let h = heights.len();
+        let w = heights.first().map_or(0, |row| row.len());
+        let height = |x: usize, y: usize| heights[y][x];
+        let slope: Vec<f64> = (0..w * h)
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                neighbors_4_within(x, y, w, h)
+                    .map(|(nx, ny)| (height(nx, ny) - height(x, y)).abs())
+                    .fold(0.0, f64::max)
+            })
+            .collect();
+        let cliff: Vec<bool> = slope.iter().map(|&s| s >= cfg.cliff_slope).collect();
+
+        let mut base = Grid::new(w, h);
+        self.algorithm.generate(&mut base, seed);
+
+        // Biggest rooms pick their ground first; `claimed` keeps a wall
+        // between rooms.
+        let mut footprints = room_footprints(&base);
+        footprints.sort_by_key(|cells| std::cmp::Reverse(cells.len()));
+        let mut claimed = vec![false; w * h];
+        let mut rooms: Vec<Vec<(usize, usize)>> = Vec::new();
+        let radius = cfg.snap_radius as i64;
+        for footprint in footprints {
+            let mut best_score = (f64::MAX, i64::MAX);
+            let mut best = None;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let moved: Option<Vec<(usize, usize)>> = footprint
+                        .iter()
+                        .map(|&(x, y)| {
+                            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                            let inside = nx > 0 && ny > 0 && nx < w as i64 - 1 && ny < h as i64 - 1;
+                            let (nx, ny) = (nx as usize, ny as usize);
+                            (inside && !cliff[ny * w + nx] && !claimed[ny * w + nx])
+                                .then_some((nx, ny))
+                        })
+                        .collect();
+                    let Some(moved) = moved else { continue };
+                    let (lo, hi) = moved
+                        .iter()
+                        .fold((f64::MAX, f64::MIN), |(lo, hi), &(x, y)| {
+                            (lo.min(height(x, y)), hi.max(height(x, y)))
+                        });
+                    let relief = hi - lo;
+                    let shift = dx * dx + dy * dy;
+                    if relief <= cfg.max_room_relief && (relief, shift) < best_score {
+                        best_score = (relief, shift);
+                        best = Some(moved);
+                    }
+                }
+            }
+            let Some(cells) = best else { continue };
+            for &(x, y) in &cells {
+                for (nx, ny) in neighbors_8_within(x, y, w, h).chain([(x, y)]) {
+                    claimed[ny * w + nx] = true;
+                }
+            }
+            rooms.push(cells);
+        }
+
+        let mut grid = Grid::new(w, h);
+        let mut elevation = heights.to_vec();
+        for cells in &rooms {
+            let mean = cells.iter().map(|&(x, y)| height(x, y)).sum::<f64>() / cells.len() as f64;
+            for &(x, y) in cells {
+                grid.set(x as i32, y as i32, Tile::Floor);
+                elevation[y][x] = mean;
+            }
+        }
+
+        // Kruskal over room pairs by distance, routing each candidate edge
+        // on the slope-weighted terrain.
+        let anchors: Vec<(usize, usize)> = rooms.iter().map(|cells| anchor(cells)).collect();
+        let mut pairs: Vec<(usize, usize, usize)> = (0..anchors.len())
+            .flat_map(|a| (a + 1..anchors.len()).map(move |b| (a, b)))
+            .map(|(a, b)| {
+                let ((ax, ay), (bx, by)) = (anchors[a], anchors[b]);
+                (ax.abs_diff(bx).pow(2) + ay.abs_diff(by).pow(2), a, b)
+            })
+            .collect();
+        pairs.sort_unstable();
+        let costs: Vec<Vec<f32>> = (0..h)
+            .map(|y| {
+                (0..w)
+                    .map(|x| {
+                        let border = x == 0 || y == 0 || x + 1 == w || y + 1 == h;
+                        if border || cliff[y * w + x] {
+                            f32::INFINITY
+                        } else {
+                            (1.0 + cfg.slope_cost * slope[y * w + x]) as f32
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let constraints = PathfindingConstraints::cardinal().with_cell_costs(costs);
+        let open: Grid<Tile> = {
+            let mut open = Grid::new(w, h);
+            open.fill(Tile::Floor);
+            open
+        };
+        let mut parent: Vec<usize> = (0..rooms.len()).collect();
+        for (_, a, b) in pairs {
+            let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+            if ra == rb {
+                continue;
+            }
+            let Some(path) = shortest_path(&open, anchors[a], anchors[b], &constraints) else {
+                continue;
+            };
+            for (x, y) in path {
+                grid.set(x as i32, y as i32, Tile::Floor);
+            }
+            parent[ra] = rb;
+        }
+
+        TerrainDungeon {
+            grid,
+            elevation,
+            cliffs: cliff.chunks(w.max(1)).map(|row| row.to_vec()).collect(),
+            rooms,
+        }
+    }
+}
+
+/// Open areas of `grid`: floor cells within one step of a cell whose 3x3
+/// neighbourhood is all floor, grouped 8-connected.
+fn room_footprints(grid: &Grid<Tile>) -> Vec<Vec<(usize, usize)>> {
+    let (w, h) = (grid.width(), grid.height());
+    let open = |x: usize, y: usize| {
+        (-1..=1).all(|dy| {
+            (-1..=1).all(|dx| {
+                grid.get(x as i32 + dx, y as i32 + dy)
+                    .is_some_and(|t| t.is_floor())
+            })
+        })
+    };
+    let mut in_room = vec![false; w * h];
+    for y in 0..h {
+        for x in 0..w {
+            if open(x, y) {
+                for (nx, ny) in neighbors_8_within(x, y, w, h).chain([(x, y)]) {
+                    in_room[ny * w + nx] = true;
+                }
+            }
+        }
+    }
+
+    let mut seen = vec![false; w * h];
+    let mut rooms = Vec::new();
+    for start in 0..w * h {
+        if !in_room[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut cells = Vec::new();
+        let mut stack = vec![(start % w, start / w)];
+        while let Some((x, y)) = stack.pop() {
+            cells.push((x, y));
+            for (nx, ny) in neighbors_8_within(x, y, w, h) {
+                let i = ny * w + nx;
+                if in_room[i] && !seen[i] {
+                    seen[i] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        rooms.push(cells);
+    }
+    rooms
+}
+
+/// Room cell closest to the room's centroid.
+fn anchor(cells: &[(usize, usize)]) -> (usize, usize) {
+    let n = cells.len() as f64;
+    let cx = cells.iter().map(|&(x, _)| x as f64).sum::<f64>() / n;
+    let cy = cells.iter().map(|&(_, y)| y as f64).sum::<f64>() / n;
+    let distance = |&(x, y): &(usize, usize)| (x as f64 - cx).hypot(y as f64 - cy);
+    *cells
+        .iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .expect("rooms are never empty")
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    if parent[i] != i {
+        parent[i] = find(parent, parent[i]);
+    }
+    parent[i]
+}
@@ -0,0 +1,305 @@
+//! Property checks for [`Algorithm`] implementations.
+//!
+//! [`check`] runs an algorithm over a spread of grid sizes (down to 1x1)
+//! and seeds and reports every [`Violation`] of the properties the built-in
+//! algorithms keep:
+//!
+//! | property        | holds when                                          |
+//! |-----------------|-----------------------------------------------------|
+//! | `no_panic`      | `generate` returns for every size and seed          |
+//! | `bounds`        | the grid keeps its size and (optionally) a wall border |
+//! | `floor`         | at least one floor cell on grids of `min_floor_size`+ |
+//! | `deterministic` | the same size and seed give the same grid           |
+//!
+//! Use it from your own tests to catch the edge cases built-ins already
+//! handle:
+//!
+//! ```rust
+//! use terrain_forge::testing::{self, PropertyConfig};
+//! use terrain_forge::{Algorithm, Grid, Rng, Tile};
+//!
+//! struct Scatter;
+//!
+//! impl Algorithm for Scatter {
+//!     fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+//!         let mut rng = Rng::new(seed);
+//!         let (w, h) = (grid.width() as i32, grid.height() as i32);
+//!         for y in 1..h - 1 {
+//!             for x in 1..w - 1 {
+//!                 if rng.chance(0.5) {
+//!                     grid.set(x, y, Tile::Floor);
+//!                 }
+//!             }
+//!         }
+//!     }
+//!
+//!     fn name(&self) -> &'static str {
+//!         "Scatter"
+//!     }
+//! }
+//!
+//! let report = testing::check(&Scatter, &PropertyConfig::default());
+//! assert!(report.passed(), "{}", report);
+//! ```
+
+use crate::{Algorithm, Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// A property [`check`] verifies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Property {
+    NoPanic,
+    Bounds,
+    Floor,
+    Deterministic,
+}
+
+impl Property {
+    pub fn name(self) -> &'static str {
+        match self {
+            Property::NoPanic => "no_panic",
+            Property::Bounds => "bounds",
+            Property::Floor => "floor",
+            Property::Deterministic => "deterministic",
+        }
+    }
+}
+
+/// Sizes, seeds, and expectations for [`check`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PropertyConfig {
+    /// Grid sizes to generate. Default: 1x1, 2x2, 3x3, 5x4, 4x17, 16x9,
+    /// 40x30, 80x60, and 128x24.
+    pub sizes: Vec<(usize, usize)>,
+    /// Seeds to run at every size. Default: 0, 1, 42, 12345, and `u64::MAX`.
+    pub seeds: Vec<u64>,
+    /// The outer ring of cells must stay wall. Default: true.
+    pub wall_border: bool,
+    /// Grids at least this wide and tall must get some floor. Default: 16.
+    pub min_floor_size: usize,
+}
+
+impl Default for PropertyConfig {
+    fn default() -> Self {
+        Self {
+            sizes: vec![
+                (1, 1),
+                (2, 2),
+                (3, 3),
+                (5, 4),
+                (4, 17),
+                (16, 9),
+                (40, 30),
+                (80, 60),
+                (128, 24),
+            ],
+            seeds: vec![0, 1, 42, 12345, u64::MAX],
+            wall_border: true,
+            min_floor_size: 16,
+        }
+    }
+}
+
+impl PropertyConfig {
+    /// Sets `sizes`.
+    pub fn with_sizes(mut self, sizes: Vec<(usize, usize)>) -> Self {
+        self.sizes = sizes;
+        self
+    }
+
+    /// Sets `seeds`.
+    pub fn with_seeds(mut self, seeds: Vec<u64>) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    /// Sets `wall_border`.
+    pub fn with_wall_border(mut self, wall_border: bool) -> Self {
+        self.wall_border = wall_border;
+        self
+    }
+
+    /// Sets `min_floor_size`.
+    pub fn with_min_floor_size(mut self, min_floor_size: usize) -> Self {
+        self.min_floor_size = min_floor_size;
+        self
+    }
+}
+
+/// One failed property, with the run that broke it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    pub property: Property,
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}x{} seed {}: {}",
+            self.property.name(),
+            self.width,
+            self.height,
+            self.seed,
+            self.message
+        )
+    }
+}
+
+/// Everything [`check`] found.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PropertyReport {
+    /// [`Algorithm::name`] of the checked algorithm.
+    pub algorithm: String,
+    /// Size and seed combinations run.
+    pub runs: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl PropertyReport {
+    /// No violations.
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Violations of one property.
+    pub fn violations_of(&self, property: Property) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(move |v| v.property == property)
+    }
+}
+
+impl fmt::Display for PropertyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} runs, {} violations",
+            self.algorithm,
+            self.runs,
+            self.violations.len()
+        )?;
+        for violation in &self.violations {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `algorithm` at every size and seed of `config`, on blank grids,
+/// and checks each property.
+///
+/// Panics are caught and reported as [`Property::NoPanic`] violations (the
+/// panic hook still prints them); the other properties are skipped for
+/// that run.
+pub fn check<A: Algorithm<Tile> + ?Sized>(
+    algorithm: &A,
+    config: &PropertyConfig,
+) -> PropertyReport {
+    let mut report = PropertyReport {
+        algorithm: algorithm.name().to_string(),
+        ..PropertyReport::default()
+    };
+    for &(width, height) in &config.sizes {
+        for &seed in &config.seeds {
+            report.runs += 1;
+            let mut violation = |property: Property, message: String| {
+                report.violations.push(Violation {
+                    property,
+                    width,
+                    height,
+                    seed,
+                    message,
+                })
+            };
+            let first = match run(algorithm, width, height, seed) {
+                Ok(grid) => grid,
+                Err(message) => {
+                    violation(Property::NoPanic, message);
+                    continue;
+                }
+            };
+
+            if (first.width(), first.height()) != (width, height) {
+                violation(
+                    Property::Bounds,
+                    format!("grid resized to {}x{}", first.width(), first.height()),
+                );
+            } else if config.wall_border {
+                if let Some((x, y)) = border_floor(&first) {
+                    violation(
+                        Property::Bounds,
+                        format!("floor on the border at ({x}, {y})"),
+                    );
+                }
+            }
+
+            if width >= config.min_floor_size
+                && height >= config.min_floor_size
+                && first.count(|t| t.is_floor()) == 0
+            {
+                violation(Property::Floor, "no floor cells".to_string());
+            }
+
+            match run(algorithm, width, height, seed) {
+                Ok(second) if second == first => {}
+                Ok(second) => {
+                    let differing = first
+                        .iter()
+                        .zip(second.iter())
+                        .filter(|((_, _, a), (_, _, b))| a != b)
+                        .count();
+                    violation(
+                        Property::Deterministic,
+                        format!("second run differs in {differing} cells"),
+                    );
+                }
+                Err(message) => violation(Property::Deterministic, format!("second run {message}")),
+            }
+        }
+    }
+    report
+}
+
+/// Checks `algorithm` with the default [`PropertyConfig`] and panics with
+/// the report if any property fails.
+pub fn assert_properties<A: Algorithm<Tile> + ?Sized>(algorithm: &A) {
+    let report = check(algorithm, &PropertyConfig::default());
+    assert!(report.passed(), "{}", report);
+}
+
+fn run<A: Algorithm<Tile> + ?Sized>(
+    algorithm: &A,
+    width: usize,
+    height: usize,
+    seed: u64,
+) -> Result<Grid<Tile>, String> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut grid = Grid::new(width, height);
+        algorithm.generate(&mut grid, seed);
+        grid
+    }))
+    .map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown payload".to_string());
+        format!("panicked: {message}")
+    })
+}
+
+fn border_floor(grid: &Grid<Tile>) -> Option<(usize, usize)> {
+    let (w, h) = (grid.width(), grid.height());
+    grid.iter()
+        .find(|&(x, y, t)| t.is_floor() && (x == 0 || y == 0 || x + 1 == w || y + 1 == h))
+        .map(|(x, y, _)| (x, y))
+}
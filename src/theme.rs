@@ -0,0 +1,236 @@
+//! Tile theming by depth and value-grid channels.
+//!
+//! [`assign`] picks a [`Theme`] for every cell: the first theme whose
+//! rules all hold there. Rules test named channels, value grids as rows of
+//! `f64` like the [`climate`](crate::climate) output, plus the built-in
+//! [`DEPTH`] channel (row over height, `0` at the top).
+//!
+//! The resulting [`ThemeMap`] is a theme id layer. [`ThemeMap::paint`]
+//! remaps floor and wall to each theme's variant tiles from a [`TileSet`],
+//! and [`ThemeMap::apply`] tags semantic regions with their majority theme
+//! (`theme:<name>`) and writes a `theme_<name>` mask per theme.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use terrain_forge::theme::{self, Theme, ThemeConfig, DEPTH};
+//! use terrain_forge::tileset::{TileDef, TileId, TileSet};
+//! use terrain_forge::{algorithms, Grid};
+//!
+//! let mut grid = Grid::new(60, 40);
+//! algorithms::get("cellular").unwrap().generate(&mut grid, 5);
+//! let temperature: Vec<Vec<f64>> = (0..40).map(|_| (0..60).map(|x| x as f64).collect()).collect();
+//!
+//! let config = ThemeConfig::new(vec![
+//!     Theme::new("ice").with_rule("temperature", f64::MIN, 10.0).with_wall("ice_wall"),
+//!     Theme::new("lava").with_rule(DEPTH, 0.75, 1.0).with_floor("lava"),
+//! ]);
+//! let channels = HashMap::from([("temperature".to_string(), temperature)]);
+//! let themes = theme::assign(&grid, &channels, &config);
+//! assert_eq!(themes.theme_at(0, 0), Some("ice"));
+//! assert_eq!(themes.theme_at(59, 39), Some("lava"));
+//!
+//! let tileset = TileSet::default()
+//!     .with_tile(TileDef::new(TileId(2), "ice_wall"))
+//!     .with_tile(TileDef::new(TileId(3), "lava").with_passable(true).with_cost(5.0));
+//! let tiles = themes.paint(&grid, &tileset);
+//! assert_eq!(tiles[(0, 0)].id(), TileId(2));
+//! ```
+
+use crate::semantic::SemanticLayers;
+use crate::tileset::{TileCell, TileSet};
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Built-in channel: row over height, `0` at the top and `1` at the bottom.
+/// A channel of the same name passed to [`assign`] takes precedence.
+pub const DEPTH: &str = "depth";
+
+/// One channel range a theme requires.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeRule {
+    pub channel: String,
+    /// Inclusive lower bound.
+    pub min: f64,
+    /// Inclusive upper bound.
+    pub max: f64,
+}
+
+/// A themed variant and where it applies.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    /// All must hold; a theme without rules matches everywhere.
+    #[serde(default)]
+    pub rules: Vec<ThemeRule>,
+    /// Tile name floor cells become, if any.
+    #[serde(default)]
+    pub floor: Option<String>,
+    /// Tile name wall cells become, if any.
+    #[serde(default)]
+    pub wall: Option<String>,
+}
+
+impl Theme {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            rules: Vec::new(),
+            floor: None,
+            wall: None,
+        }
+    }
+
+    /// Requires `channel` to lie in `min..=max`.
+    pub fn with_rule(mut self, channel: impl Into<String>, min: f64, max: f64) -> Self {
+        self.rules.push(ThemeRule {
+            channel: channel.into(),
+            min,
+            max,
+        });
+        self
+    }
+
+    /// Sets the floor variant tile.
+    pub fn with_floor(mut self, tile: impl Into<String>) -> Self {
+        self.floor = Some(tile.into());
+        self
+    }
+
+    /// Sets the wall variant tile.
+    pub fn with_wall(mut self, tile: impl Into<String>) -> Self {
+        self.wall = Some(tile.into());
+        self
+    }
+}
+
+/// Configuration for [`assign`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Themes in priority order; the first match wins.
+    pub themes: Vec<Theme>,
+}
+
+impl ThemeConfig {
+    pub fn new(themes: Vec<Theme>) -> Self {
+        Self { themes }
+    }
+}
+
+/// Theme id per cell, from [`assign`].
+#[derive(Debug, Clone)]
+pub struct ThemeMap {
+    /// Index into `themes` per cell, indexed `[y][x]`; `None` is unthemed.
+    pub ids: Vec<Vec<Option<usize>>>,
+    pub themes: Vec<Theme>,
+}
+
+impl ThemeMap {
+    /// Theme index at `(x, y)`.
+    pub fn id_at(&self, x: usize, y: usize) -> Option<usize> {
+        *self.ids.get(y)?.get(x)?
+    }
+
+    /// Theme name at `(x, y)`.
+    pub fn theme_at(&self, x: usize, y: usize) -> Option<&str> {
+        self.id_at(x, y).map(|i| self.themes[i].name.as_str())
+    }
+
+    /// Number of cells per theme, by index.
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.themes.len()];
+        for id in self.ids.iter().flatten().flatten() {
+            counts[*id] += 1;
+        }
+        counts
+    }
+
+    /// `grid` as tiles of `tileset`, with themed cells swapped for their
+    /// theme's floor or wall variant. Variants missing from `tileset` keep
+    /// the plain tile.
+    pub fn paint(&self, grid: &Grid<Tile>, tileset: &TileSet) -> Grid<TileCell> {
+        let variant = |name: &Option<String>| {
+            name.as_deref()
+                .and_then(|n| tileset.by_name(n))
+                .map(|t| tileset.cell(t.id))
+        };
+        let variants: Vec<_> = self
+            .themes
+            .iter()
+            .map(|t| (variant(&t.floor), variant(&t.wall)))
+            .collect();
+        let mut tiles = tileset.from_tiles(grid);
+        for (x, y, tile) in grid.iter() {
+            let Some(id) = self.id_at(x, y) else {
+                continue;
+            };
+            let (floor, wall) = variants[id];
+            if let Some(cell) = if tile.is_floor() { floor } else { wall } {
+                tiles.set(x as i32, y as i32, cell);
+            }
+        }
+        tiles
+    }
+
+    /// Tags each region `theme:<name>` with its majority theme over its
+    /// cells and writes a `theme_<name>` mask per theme.
+    pub fn apply(&self, semantic: &mut SemanticLayers) {
+        for region in &mut semantic.regions {
+            let mut counts = vec![0usize; self.themes.len()];
+            for &(x, y) in &region.cells {
+                if let Some(id) = self.id_at(x as usize, y as usize) {
+                    counts[id] += 1;
+                }
+            }
+            region.tags.retain(|t| !t.starts_with("theme:"));
+            let majority = (0..counts.len()).max_by_key(|&i| (counts[i], std::cmp::Reverse(i)));
+            if let Some(i) = majority.filter(|&i| counts[i] > 0) {
+                region.tags.push(format!("theme:{}", self.themes[i].name));
+            }
+        }
+
+        for (y, row) in self.ids.iter().enumerate() {
+            for (x, &id) in row.iter().enumerate() {
+                if let Some(id) = id {
+                    let name = format!("theme_{}", self.themes[id].name);
+                    semantic.masks.set_named(&name, x, y, true);
+                }
+            }
+        }
+    }
+}
+
+/// Assigns each cell of `grid` the first theme in `config` whose rules
+/// hold there. Rules on channels missing from `channels` (or cells outside
+/// them) never hold.
+pub fn assign(
+    grid: &Grid<Tile>,
+    channels: &HashMap<String, Vec<Vec<f64>>>,
+    config: &ThemeConfig,
+) -> ThemeMap {
+    let (w, h) = (grid.width(), grid.height());
+    let value = |channel: &str, x: usize, y: usize| match channels.get(channel) {
+        Some(rows) => rows.get(y).and_then(|row| row.get(x)).copied(),
+        None if channel == DEPTH => Some(y as f64 / (h.max(2) - 1) as f64),
+        None => None,
+    };
+    let ids = (0..h)
+        .map(|y| {
+            (0..w)
+                .map(|x| {
+                    config.themes.iter().position(|theme| {
+                        theme.rules.iter().all(|rule| {
+                            value(&rule.channel, x, y)
+                                .is_some_and(|v| v >= rule.min && v <= rule.max)
+                        })
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    ThemeMap {
+        ids,
+        themes: config.themes.clone(),
+    }
+}
@@ -0,0 +1,311 @@
+//! User-defined tile palettes.
+//!
+//! A [`TileSet`] maps tile ids to a name, passability, opacity, movement
+//! cost, color, and text glyph. Grids of [`TileCell`] carry those ids and
+//! work with every algorithm through the [`Cell`] trait (wrap `Tile`
+//! algorithms in [`CellAdapter`](crate::CellAdapter)): algorithms only open
+//! and close cells, writing the reserved [`TileId::FLOOR`] and
+//! [`TileId::WALL`] ids, and you paint richer tiles (water, lava, doors)
+//! afterward. Renderers and exporters read colors and glyphs from the set.
+//!
+//! The classic [`Tile`] enum keeps working; [`TileSet::from_tiles`] and
+//! [`Grid::to_tiles`] convert between the two.
+//!
+//! ```rust
+//! use terrain_forge::tileset::{TileCell, TileDef, TileId, TileSet};
+//! use terrain_forge::{algorithms::Bsp, CellAdapter, Algorithm, Grid};
+//!
+//! let water = TileId(2);
+//! let tileset = TileSet::default().with_tile(
+//!     TileDef::new(water, "water").with_passable(true).with_cost(3.0).with_color([40, 90, 200]).with_glyph('~'),
+//! );
+//!
+//! let mut grid: Grid<TileCell> = Grid::new(40, 30);
+//! CellAdapter::new(Bsp::default()).generate(&mut grid, 1);
+//! let (x, y, _) = grid.iter().find(|(_, _, c)| c.id() == TileId::FLOOR).unwrap();
+//! grid.set(x as i32, y as i32, tileset.cell(water));
+//!
+//! assert!(tileset.render_text(&grid).contains('~'));
+//! assert_eq!(tileset.cost_map(&grid)[y][x], 3.0);
+//! ```
+
+use crate::error::{Error, Result};
+use crate::{Cell, Grid, Tile};
+use serde::{Deserialize, Serialize};
+
+/// A tile id in a [`TileSet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TileId(pub u16);
+
+impl TileId {
+    /// Written by algorithms when closing a cell.
+    pub const WALL: TileId = TileId(0);
+    /// Written by algorithms when opening a cell.
+    pub const FLOOR: TileId = TileId(1);
+}
+
+/// One tile type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileDef {
+    pub id: TileId,
+    pub name: String,
+    /// Default: false.
+    #[serde(default)]
+    pub passable: bool,
+    /// Blocks sight. Default: false.
+    #[serde(default)]
+    pub opaque: bool,
+    /// Movement cost when passable. Default: 1.0.
+    #[serde(default = "default_cost")]
+    pub cost: f32,
+    /// RGB color. Default: magenta.
+    #[serde(default = "default_color")]
+    pub color: [u8; 3],
+    /// Text glyph. Default: `'?'`.
+    #[serde(default = "default_glyph")]
+    pub glyph: char,
+}
+
+fn default_cost() -> f32 {
+    1.0
+}
+
+fn default_color() -> [u8; 3] {
+    [255, 0, 255]
+}
+
+fn default_glyph() -> char {
+    '?'
+}
+
+impl TileDef {
+    pub fn new(id: TileId, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            passable: false,
+            opaque: false,
+            cost: default_cost(),
+            color: default_color(),
+            glyph: default_glyph(),
+        }
+    }
+
+    /// Sets `passable`.
+    pub fn with_passable(mut self, passable: bool) -> Self {
+        self.passable = passable;
+        self
+    }
+
+    /// Sets `opaque`.
+    pub fn with_opaque(mut self, opaque: bool) -> Self {
+        self.opaque = opaque;
+        self
+    }
+
+    /// Sets `cost`.
+    pub fn with_cost(mut self, cost: f32) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Sets `color`.
+    pub fn with_color(mut self, color: [u8; 3]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets `glyph`.
+    pub fn with_glyph(mut self, glyph: char) -> Self {
+        self.glyph = glyph;
+        self
+    }
+}
+
+/// A grid cell holding a tile id and its cached passability.
+///
+/// Build cells with [`TileSet::cell`] so passability matches the set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TileCell {
+    id: TileId,
+    passable: bool,
+}
+
+impl TileCell {
+    pub const WALL: TileCell = TileCell {
+        id: TileId::WALL,
+        passable: false,
+    };
+    pub const FLOOR: TileCell = TileCell {
+        id: TileId::FLOOR,
+        passable: true,
+    };
+
+    pub fn id(&self) -> TileId {
+        self.id
+    }
+}
+
+impl Default for TileCell {
+    fn default() -> Self {
+        Self::WALL
+    }
+}
+
+impl Cell for TileCell {
+    fn is_passable(&self) -> bool {
+        self.passable
+    }
+
+    fn set_passable(&mut self) {
+        *self = Self::FLOOR;
+    }
+
+    fn set_impassable(&mut self) {
+        *self = Self::WALL;
+    }
+}
+
+/// A palette of tile types.
+///
+/// Always defines [`TileId::WALL`] and [`TileId::FLOOR`]; they can be
+/// restyled with [`with_tile`](Self::with_tile) but keep their passability.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<TileDef>", into = "Vec<TileDef>")]
+pub struct TileSet {
+    tiles: Vec<TileDef>,
+}
+
+impl Default for TileSet {
+    fn default() -> Self {
+        Self {
+            tiles: vec![
+                TileDef::new(TileId::WALL, "wall")
+                    .with_opaque(true)
+                    .with_color([48, 48, 48])
+                    .with_glyph('#'),
+                TileDef::new(TileId::FLOOR, "floor")
+                    .with_passable(true)
+                    .with_color([230, 230, 230])
+                    .with_glyph('.'),
+            ],
+        }
+    }
+}
+
+impl From<Vec<TileDef>> for TileSet {
+    fn from(tiles: Vec<TileDef>) -> Self {
+        tiles
+            .into_iter()
+            .fold(Self::default(), |set, tile| set.with_tile(tile))
+    }
+}
+
+impl From<TileSet> for Vec<TileDef> {
+    fn from(set: TileSet) -> Self {
+        set.tiles
+    }
+}
+
+impl TileSet {
+    /// Adds `tile`, replacing any tile with the same id.
+    pub fn with_tile(mut self, mut tile: TileDef) -> Self {
+        if tile.id == TileId::WALL || tile.id == TileId::FLOOR {
+            tile.passable = tile.id == TileId::FLOOR;
+        }
+        match self.tiles.iter_mut().find(|t| t.id == tile.id) {
+            Some(existing) => *existing = tile,
+            None => self.tiles.push(tile),
+        }
+        self
+    }
+
+    pub fn tiles(&self) -> &[TileDef] {
+        &self.tiles
+    }
+
+    pub fn get(&self, id: TileId) -> Option<&TileDef> {
+        self.tiles.iter().find(|t| t.id == id)
+    }
+
+    /// The tile named `name`.
+    pub fn by_name(&self, name: &str) -> Option<&TileDef> {
+        self.tiles.iter().find(|t| t.name == name)
+    }
+
+    /// A cell for `id`; unknown ids are impassable.
+    pub fn cell(&self, id: TileId) -> TileCell {
+        TileCell {
+            id,
+            passable: self.get(id).is_some_and(|t| t.passable),
+        }
+    }
+
+    /// Converts a classic grid: floor to [`TileId::FLOOR`], wall to
+    /// [`TileId::WALL`].
+    pub fn from_tiles(&self, grid: &Grid<Tile>) -> Grid<TileCell> {
+        let mut cells = Grid::new(grid.width(), grid.height());
+        for (x, y, tile) in grid.iter() {
+            let id = if tile.is_floor() {
+                TileId::FLOOR
+            } else {
+                TileId::WALL
+            };
+            cells.set(x as i32, y as i32, self.cell(id));
+        }
+        cells
+    }
+
+    /// Whether `id` blocks sight; unknown ids do.
+    pub fn is_opaque(&self, id: TileId) -> bool {
+        self.get(id).is_none_or(|t| t.opaque)
+    }
+
+    /// Movement cost per cell, indexed `[y][x]`; impassable cells are
+    /// infinite.
+    pub fn cost_map(&self, grid: &Grid<TileCell>) -> Vec<Vec<f32>> {
+        let mut costs = vec![vec![f32::INFINITY; grid.width()]; grid.height()];
+        for (x, y, cell) in grid.iter() {
+            if let Some(tile) = self.get(cell.id).filter(|t| t.passable) {
+                costs[y][x] = tile.cost;
+            }
+        }
+        costs
+    }
+
+    /// One glyph per cell, rows separated by newlines.
+    pub fn render_text(&self, grid: &Grid<TileCell>) -> String {
+        let mut out = String::with_capacity((grid.width() + 1) * grid.height());
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let glyph = self.get(grid[(x, y)].id).map_or('?', |t| t.glyph);
+                out.push(glyph);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Tight-packed RGB bytes, one pixel per cell, row-major.
+    pub fn to_rgb(&self, grid: &Grid<TileCell>) -> Vec<u8> {
+        grid.iter()
+            .flat_map(|(_, _, cell)| self.get(cell.id).map_or(default_color(), |t| t.color))
+            .collect()
+    }
+
+    /// An RGB PNG with one pixel per cell.
+    pub fn to_png(&self, grid: &Grid<TileCell>) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, grid.width() as u32, grid.height() as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let png_error = |e: png::EncodingError| Error::new(format!("PNG encoding failed: {}", e));
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer
+            .write_image_data(&self.to_rgb(grid))
+            .map_err(png_error)?;
+        writer.finish().map_err(png_error)?;
+        Ok(out)
+    }
+}
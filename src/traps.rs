@@ -0,0 +1,261 @@
+//! Trap placement along high-traffic corridors.
+//!
+//! [`TrapPlacer`] routes shortest paths from a spawn point to every goal,
+//! counts how many routes cross each cell ([`traffic`]), and puts trap
+//! markers on the busiest corridor cells, subject to a density cap, a
+//! minimum walking distance from spawn, and spacing between traps. With
+//! `avoidable` set, traps never sit on a cell every route must cross.
+//!
+//! ```rust
+//! use terrain_forge::traps::{TrapConfig, TrapPlacer};
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("bsp").unwrap().generate(&mut grid, 4);
+//! let mut semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(4));
+//!
+//! let placer = TrapPlacer::new(TrapConfig::default().with_max_traps(4));
+//! let placed = placer.place_semantic(&grid, &mut semantic, &mut Rng::new(4));
+//! assert!(placed <= 4);
+//! ```
+
+use crate::semantic::{Marker, SemanticLayers};
+use crate::spatial::{dijkstra_map, PathfindingConstraints};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Configuration for [`TrapPlacer`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrapConfig {
+    /// Upper bound on traps. Default: 8.
+    pub max_traps: usize,
+    /// Traps per trafficked candidate cell, `0..=1`. Default: 0.05.
+    pub density: f64,
+    /// Minimum walking distance from spawn. Default: 10.
+    pub min_spawn_distance: usize,
+    /// Minimum Chebyshev distance between traps. Default: 5.
+    pub min_spacing: usize,
+    /// Keep every goal reachable from spawn without crossing a trap.
+    /// Default: false.
+    pub avoidable: bool,
+    /// Only place on corridor cells (walls on both sides). Default: true.
+    pub corridors_only: bool,
+    /// Marker tag. Default: `"trap"`.
+    pub tag: String,
+}
+
+impl Default for TrapConfig {
+    fn default() -> Self {
+        Self {
+            max_traps: 8,
+            density: 0.05,
+            min_spawn_distance: 10,
+            min_spacing: 5,
+            avoidable: false,
+            corridors_only: true,
+            tag: "trap".to_string(),
+        }
+    }
+}
+
+impl TrapConfig {
+    /// Sets `max_traps`.
+    pub fn with_max_traps(mut self, max_traps: usize) -> Self {
+        self.max_traps = max_traps;
+        self
+    }
+
+    /// Sets `density`.
+    pub fn with_density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets `min_spawn_distance`.
+    pub fn with_min_spawn_distance(mut self, distance: usize) -> Self {
+        self.min_spawn_distance = distance;
+        self
+    }
+
+    /// Sets `min_spacing`.
+    pub fn with_min_spacing(mut self, spacing: usize) -> Self {
+        self.min_spacing = spacing;
+        self
+    }
+
+    /// Sets `avoidable`.
+    pub fn with_avoidable(mut self, avoidable: bool) -> Self {
+        self.avoidable = avoidable;
+        self
+    }
+
+    /// Sets `corridors_only`.
+    pub fn with_corridors_only(mut self, corridors_only: bool) -> Self {
+        self.corridors_only = corridors_only;
+        self
+    }
+}
+
+/// Places trap markers on busy corridors.
+#[derive(Debug, Clone, Default)]
+pub struct TrapPlacer {
+    config: TrapConfig,
+}
+
+impl TrapPlacer {
+    pub fn new(config: TrapConfig) -> Self {
+        Self { config }
+    }
+
+    /// Trap markers for routes from `spawn` to `goals`.
+    ///
+    /// `rng` breaks ties between equally busy cells.
+    pub fn place(
+        &self,
+        grid: &Grid<Tile>,
+        spawn: (usize, usize),
+        goals: &[(usize, usize)],
+        rng: &mut Rng,
+    ) -> Vec<Marker> {
+        let cfg = &self.config;
+        if !grid
+            .get(spawn.0 as i32, spawn.1 as i32)
+            .is_some_and(|t| t.is_floor())
+        {
+            return Vec::new();
+        }
+        let cardinal = PathfindingConstraints::cardinal();
+        let distance = dijkstra_map(grid, &[spawn], &cardinal);
+        let traffic = traffic(grid, spawn, goals);
+        let min_distance = cfg.min_spawn_distance.max(1) as f32;
+
+        let mut candidates: Vec<(usize, usize)> = grid
+            .iter()
+            .filter(|&(x, y, t)| {
+                t.is_floor()
+                    && traffic[y][x] > 0
+                    && !goals.contains(&(x, y))
+                    && distance.get(x, y) >= min_distance
+                    && distance.get(x, y).is_finite()
+                    && (!cfg.corridors_only || is_corridor(grid, x, y))
+            })
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        rng.shuffle(&mut candidates);
+        candidates.sort_by_key(|&(x, y)| std::cmp::Reverse(traffic[y][x]));
+
+        let limit = cfg
+            .max_traps
+            .min((candidates.len() as f64 * cfg.density).ceil() as usize);
+        let mut traps: Vec<(usize, usize)> = Vec::new();
+        for (x, y) in candidates {
+            if traps.len() >= limit {
+                break;
+            }
+            let spaced = traps
+                .iter()
+                .all(|&(tx, ty)| tx.abs_diff(x).max(ty.abs_diff(y)) >= cfg.min_spacing.max(1));
+            if !spaced {
+                continue;
+            }
+            if cfg.avoidable {
+                let mut blocked = traps.clone();
+                blocked.push((x, y));
+                let reach = dijkstra_map(
+                    grid,
+                    &[spawn],
+                    &cardinal.clone().with_blocked_cells(blocked),
+                );
+                if goals.iter().any(|&(gx, gy)| !reach.get(gx, gy).is_finite()) {
+                    continue;
+                }
+            }
+            traps.push((x, y));
+        }
+
+        traps
+            .into_iter()
+            .map(|(x, y)| {
+                let mut marker = Marker::with_tag(x as u32, y as u32, cfg.tag.clone());
+                marker
+                    .metadata
+                    .insert("traffic".to_string(), traffic[y][x].to_string());
+                marker
+            })
+            .collect()
+    }
+
+    /// Places traps using the layers' markers: the first `spawn` or
+    /// `PlayerStart` marker is the spawn and every other marker is a goal.
+    /// Returns the number of traps added.
+    pub fn place_semantic(
+        &self,
+        grid: &Grid<Tile>,
+        semantic: &mut SemanticLayers,
+        rng: &mut Rng,
+    ) -> usize {
+        let Some(spawn) = semantic.spawn() else {
+            return 0;
+        };
+        let spawn = (spawn.x as usize, spawn.y as usize);
+        let goals: Vec<(usize, usize)> = semantic
+            .markers
+            .iter()
+            .filter(|m| !m.is_spawn())
+            .map(|m| (m.x as usize, m.y as usize))
+            .collect();
+        let traps = self.place(grid, spawn, &goals, rng);
+        let count = traps.len();
+        semantic.markers.extend(traps);
+        count
+    }
+}
+
+/// How many shortest routes from `spawn` to `goals` cross each cell,
+/// indexed `[y][x]`. Unreachable goals contribute nothing.
+pub fn traffic(
+    grid: &Grid<Tile>,
+    spawn: (usize, usize),
+    goals: &[(usize, usize)],
+) -> Vec<Vec<u32>> {
+    let (w, h) = (grid.width(), grid.height());
+    let mut counts = vec![vec![0u32; w]; h];
+    if !grid
+        .get(spawn.0 as i32, spawn.1 as i32)
+        .is_some_and(|t| t.is_floor())
+    {
+        return counts;
+    }
+    let distance = dijkstra_map(grid, &[spawn], &PathfindingConstraints::cardinal());
+    for &goal in goals {
+        if goal.0 >= w || goal.1 >= h {
+            continue;
+        }
+        let mut d = distance.get(goal.0, goal.1);
+        if !d.is_finite() {
+            continue;
+        }
+        let mut current = goal;
+        counts[current.1][current.0] += 1;
+        while d > 0.0 {
+            let previous = grid
+                .neighbors_4(current.0, current.1)
+                .find(|&(nx, ny)| distance.get(nx, ny) == d - 1.0);
+            let Some(previous) = previous else { break };
+            current = previous;
+            d -= 1.0;
+            counts[current.1][current.0] += 1;
+        }
+    }
+    counts
+}
+
+/// Whether `(x, y)` is floor with walls on both sides along one axis.
+fn is_corridor(grid: &Grid<Tile>, x: usize, y: usize) -> bool {
+    let wall = |dx: i32, dy: i32| {
+        grid.get(x as i32 + dx, y as i32 + dy)
+            .is_none_or(|t| t.is_wall())
+    };
+    (wall(-1, 0) && wall(1, 0)) || (wall(0, -1) && wall(0, 1))
+}
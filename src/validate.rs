@@ -0,0 +1,361 @@
+//! One-call map validation.
+//!
+//! [`full_report`] runs every structural check the crate knows about and
+//! collects the results as [`Finding`]s with a [`Severity`]:
+//!
+//! | check           | looks at                                            |
+//! |-----------------|-----------------------------------------------------|
+//! | `connectivity`  | largest floor region / all floor                    |
+//! | `border`        | floor on the outer edge                             |
+//! | `pockets`       | floor regions cut off from the main one             |
+//! | `markers`       | missing spawn; markers off-map, on walls, stacked   |
+//! | `regions`       | empty or tiny semantic regions                      |
+//! | `doors`         | lock-and-key issues from [`validate_locks`]         |
+//!
+//! A [`ValidationProfile`] sets thresholds and how strict each check is;
+//! [`ValidationReport::passed`] is the single gate: no `Error` findings.
+//!
+//! ```rust
+//! use terrain_forge::validate::{full_report, ValidationProfile};
+//! use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("bsp").unwrap().generate(&mut grid, 12);
+//! let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(12));
+//!
+//! let report = full_report(&grid, Some(&semantic), &ValidationProfile::default());
+//! for finding in &report.findings {
+//!     println!("{}", finding);
+//! }
+//! assert!(report.passed());
+//! ```
+//!
+//! [`validate_locks`]: crate::constraints::validate_locks
+
+use crate::constraints::{validate_border, validate_connectivity, validate_locks, DOOR_TAG};
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Tile};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    /// Fails the report.
+    Error,
+}
+
+/// One problem (or note) found by a check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Check name, e.g. `"pockets"`.
+    pub check: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Cell the finding points at, if any.
+    pub position: Option<(u32, u32)>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:?}] {}: {}", self.severity, self.check, self.message)?;
+        if let Some((x, y)) = self.position {
+            write!(f, " at ({}, {})", x, y)?;
+        }
+        Ok(())
+    }
+}
+
+/// Thresholds and strictness for [`full_report`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationProfile {
+    /// Minimum largest-region share of floor. Default: 0.9.
+    pub min_connectivity: f32,
+    /// Floor on the outer edge is an error rather than a warning.
+    /// Default: true.
+    pub require_border: bool,
+    /// Severity of cut-off floor pockets without markers. Default: Warning.
+    pub pocket_severity: Severity,
+    /// Semantic regions smaller than this are flagged. Default: 4.
+    pub min_region_area: usize,
+    /// A missing spawn marker is an error rather than a warning.
+    /// Default: false.
+    pub require_spawn: bool,
+}
+
+impl Default for ValidationProfile {
+    fn default() -> Self {
+        Self {
+            min_connectivity: 0.9,
+            require_border: true,
+            pocket_severity: Severity::Warning,
+            min_region_area: 4,
+            require_spawn: false,
+        }
+    }
+}
+
+impl ValidationProfile {
+    /// Ship-ready maps: full connectivity, no pockets, a spawn required.
+    pub fn strict() -> Self {
+        Self {
+            min_connectivity: 1.0,
+            pocket_severity: Severity::Error,
+            require_spawn: true,
+            ..Self::default()
+        }
+    }
+
+    /// Organic maps (caves): looser connectivity, open borders allowed.
+    pub fn lenient() -> Self {
+        Self {
+            min_connectivity: 0.6,
+            require_border: false,
+            pocket_severity: Severity::Info,
+            min_region_area: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Sets `min_connectivity`.
+    pub fn with_min_connectivity(mut self, min: f32) -> Self {
+        self.min_connectivity = min;
+        self
+    }
+
+    /// Sets `require_spawn`.
+    pub fn with_require_spawn(mut self, require: bool) -> Self {
+        self.require_spawn = require;
+        self
+    }
+}
+
+/// Everything [`full_report`] found.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Checks that ran, in order.
+    pub checks: Vec<String>,
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    /// `true` when no finding is an [`Severity::Error`].
+    pub fn passed(&self) -> bool {
+        self.worst() < Some(Severity::Error)
+    }
+
+    /// Highest severity found, if any.
+    pub fn worst(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+
+    /// Number of findings with `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    /// Findings from `check`.
+    pub fn findings_for<'a>(&'a self, check: &'a str) -> impl Iterator<Item = &'a Finding> + 'a {
+        self.findings.iter().filter(move |f| f.check == check)
+    }
+
+    fn push(
+        &mut self,
+        check: &str,
+        severity: Severity,
+        message: String,
+        position: Option<(u32, u32)>,
+    ) {
+        self.findings.push(Finding {
+            check: check.to_string(),
+            severity,
+            message,
+            position,
+        });
+    }
+}
+
+/// Runs every check on `grid` (and `semantic`, when given; semantic checks
+/// are skipped otherwise).
+pub fn full_report(
+    grid: &Grid<Tile>,
+    semantic: Option<&SemanticLayers>,
+    profile: &ValidationProfile,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let (w, h) = (grid.width(), grid.height());
+
+    // Floor regions, largest first; `region_of` maps cells to their index.
+    let mut regions = grid.flood_regions();
+    regions.sort_by_key(|r| std::cmp::Reverse(r.len()));
+    let mut region_of = vec![None; w * h];
+    for (i, cells) in regions.iter().enumerate() {
+        for &(x, y) in cells {
+            region_of[y * w + x] = Some(i);
+        }
+    }
+
+    report.checks.push("connectivity".to_string());
+    if regions.is_empty() {
+        report.push(
+            "connectivity",
+            Severity::Error,
+            "map has no floor".to_string(),
+            None,
+        );
+    } else {
+        let ratio = validate_connectivity(grid);
+        if ratio < profile.min_connectivity {
+            report.push(
+                "connectivity",
+                Severity::Error,
+                format!(
+                    "largest region holds {:.1}% of floor (minimum {:.1}%)",
+                    ratio * 100.0,
+                    profile.min_connectivity * 100.0
+                ),
+                None,
+            );
+        }
+    }
+
+    report.checks.push("border".to_string());
+    if w > 0 && h > 0 && !validate_border(grid) {
+        let open = grid
+            .iter()
+            .find(|&(x, y, t)| t.is_floor() && (x == 0 || y == 0 || x == w - 1 || y == h - 1))
+            .map(|(x, y, _)| (x as u32, y as u32));
+        let severity = if profile.require_border {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        report.push(
+            "border",
+            severity,
+            "floor touches the map edge".to_string(),
+            open,
+        );
+    }
+
+    report.checks.push("pockets".to_string());
+    let marked: Vec<usize> = semantic
+        .map(|s| {
+            s.markers
+                .iter()
+                .filter_map(|m| cell_index(m.x, m.y, w, h).and_then(|i| region_of[i]))
+                .collect()
+        })
+        .unwrap_or_default();
+    for (i, cells) in regions.iter().enumerate().skip(1) {
+        let (x, y) = cells[0];
+        let (severity, note) = if marked.contains(&i) {
+            (Severity::Error, " holding markers")
+        } else {
+            (profile.pocket_severity, "")
+        };
+        report.push(
+            "pockets",
+            severity,
+            format!(
+                "{}-cell pocket{} is unreachable from the main area",
+                cells.len(),
+                note
+            ),
+            Some((x as u32, y as u32)),
+        );
+    }
+
+    let Some(semantic) = semantic else {
+        return report;
+    };
+
+    report.checks.push("markers".to_string());
+    if semantic.spawn().is_none() {
+        let severity = if profile.require_spawn {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        report.push("markers", severity, "no spawn marker".to_string(), None);
+    }
+    let mut stacked: HashMap<(u32, u32), usize> = HashMap::new();
+    for marker in &semantic.markers {
+        let position = Some((marker.x, marker.y));
+        let Some(i) = cell_index(marker.x, marker.y, w, h) else {
+            report.push(
+                "markers",
+                Severity::Error,
+                format!("marker '{}' is outside the {}x{} map", marker.tag(), w, h),
+                position,
+            );
+            continue;
+        };
+        if region_of[i].is_none() {
+            report.push(
+                "markers",
+                Severity::Error,
+                format!("marker '{}' is on a wall", marker.tag()),
+                position,
+            );
+        }
+        *stacked.entry((marker.x, marker.y)).or_default() += 1;
+    }
+    let mut stacked: Vec<_> = stacked.into_iter().filter(|&(_, n)| n > 1).collect();
+    stacked.sort();
+    for ((x, y), n) in stacked {
+        report.push(
+            "markers",
+            Severity::Warning,
+            format!("{} markers share one cell", n),
+            Some((x, y)),
+        );
+    }
+
+    report.checks.push("regions".to_string());
+    for region in &semantic.regions {
+        let position = region.cells.first().copied();
+        if region.cells.is_empty() {
+            report.push(
+                "regions",
+                Severity::Error,
+                format!("region {} ({}) has no cells", region.id, region.kind),
+                None,
+            );
+        } else if region.cells.len() < profile.min_region_area {
+            report.push(
+                "regions",
+                Severity::Warning,
+                format!(
+                    "region {} ({}) has only {} cells",
+                    region.id,
+                    region.kind,
+                    region.cells.len()
+                ),
+                position,
+            );
+        }
+    }
+
+    if semantic.markers.iter().any(|m| m.tag() == DOOR_TAG) {
+        report.checks.push("doors".to_string());
+        for issue in validate_locks(grid, semantic) {
+            report.push("doors", Severity::Error, issue.to_string(), None);
+        }
+    }
+
+    report
+}
+
+fn cell_index(x: u32, y: u32, w: usize, h: usize) -> Option<usize> {
+    let (x, y) = (x as usize, y as usize);
+    (x < w && y < h).then_some(y * w + x)
+}
@@ -0,0 +1,339 @@
+//! Vegetation distribution for overworld maps.
+//!
+//! [`distribute`] scores every land cell against a table of [`Species`]
+//! (moisture, elevation, and slope ranges), modulates the result with noise
+//! so forests form patches, and clears space around structures and roads.
+//! The output is a density grid in `[0, 1]` plus scatter markers tagged with
+//! the species name.
+//!
+//! Moisture usually comes from [`climate::simulate`](crate::climate::simulate):
+//!
+//! ```rust
+//! use terrain_forge::algorithms::DiamondSquare;
+//! use terrain_forge::climate::{self, ClimateConfig};
+//! use terrain_forge::vegetation::{self, VegetationConfig};
+//!
+//! let heights = DiamondSquare::default().heightmap(64, 48, 5);
+//! let climate = climate::simulate(&heights, &ClimateConfig::default());
+//! let road: Vec<(usize, usize)> = (0..64).map(|x| (x, 24)).collect();
+//!
+//! let plants = vegetation::distribute(
+//!     &heights,
+//!     &climate.moisture,
+//!     road,
+//!     &VegetationConfig::default(),
+//!     5,
+//! );
+//! assert_eq!(plants.density(10, 24), 0.0);
+//! ```
+
+use crate::noise::{NoiseExt, NoiseSource, Perlin};
+use crate::semantic::Marker;
+use crate::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A plant type and the conditions it grows in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Species {
+    /// Marker tag for scattered instances.
+    pub name: String,
+    /// Density contribution where conditions are ideal.
+    pub density: f64,
+    /// Moisture range, `0..=1`.
+    pub moisture: (f64, f64),
+    /// Height range, in heightmap units.
+    pub elevation: (f64, f64),
+    /// Steepest slope (height change per cell) it grows on.
+    pub max_slope: f64,
+    /// Minimum Chebyshev distance between scattered instances.
+    pub spacing: u32,
+}
+
+impl Species {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            density: 1.0,
+            moisture: (0.0, 1.0),
+            elevation: (0.0, 1.0),
+            max_slope: 0.1,
+            spacing: 1,
+        }
+    }
+
+    /// Sets `density`.
+    pub fn with_density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets the moisture range.
+    pub fn with_moisture(mut self, min: f64, max: f64) -> Self {
+        self.moisture = (min, max);
+        self
+    }
+
+    /// Sets the elevation range.
+    pub fn with_elevation(mut self, min: f64, max: f64) -> Self {
+        self.elevation = (min, max);
+        self
+    }
+
+    /// Sets `max_slope`.
+    pub fn with_max_slope(mut self, max_slope: f64) -> Self {
+        self.max_slope = max_slope;
+        self
+    }
+
+    /// Sets `spacing`.
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// How well a cell suits this species, `0..=1`.
+    fn fit(&self, moisture: f64, elevation: f64, slope: f64) -> f64 {
+        let slope_fit = if self.max_slope > 0.0 {
+            (1.0 - slope / self.max_slope).max(0.0)
+        } else {
+            0.0
+        };
+        range_fit(moisture, self.moisture) * range_fit(elevation, self.elevation) * slope_fit
+    }
+}
+
+/// Configuration for [`distribute`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VegetationConfig {
+    pub species: Vec<Species>,
+    /// Heights below this are water and stay bare. Default: 0.4.
+    pub sea_level: f64,
+    /// Cells within this distance of a blocked cell stay bare. Default: 2.
+    pub clearing_radius: usize,
+    /// Distance beyond the clearing over which density recovers. Default: 3.
+    pub clearing_falloff: usize,
+    /// Strength of the patch noise, `0..=1`. Default: 0.5.
+    pub noise: f64,
+    /// Patch noise frequency. Default: 0.08.
+    pub noise_frequency: f64,
+    /// Scatter markers per cell at density 1. Default: 0.2.
+    pub scatter: f64,
+}
+
+impl Default for VegetationConfig {
+    fn default() -> Self {
+        Self {
+            species: vec![
+                Species::new("broadleaf")
+                    .with_moisture(0.45, 1.0)
+                    .with_elevation(0.4, 0.62)
+                    .with_max_slope(0.06)
+                    .with_spacing(2),
+                Species::new("conifer")
+                    .with_density(0.8)
+                    .with_moisture(0.3, 1.0)
+                    .with_elevation(0.55, 0.85)
+                    .with_max_slope(0.08)
+                    .with_spacing(2),
+                Species::new("shrub")
+                    .with_density(0.5)
+                    .with_moisture(0.15, 0.6)
+                    .with_elevation(0.4, 0.8)
+                    .with_max_slope(0.12),
+            ],
+            sea_level: 0.4,
+            clearing_radius: 2,
+            clearing_falloff: 3,
+            noise: 0.5,
+            noise_frequency: 0.08,
+            scatter: 0.2,
+        }
+    }
+}
+
+impl VegetationConfig {
+    /// Replaces the species table.
+    pub fn with_species(mut self, species: Vec<Species>) -> Self {
+        self.species = species;
+        self
+    }
+
+    /// Sets the clearing radius and falloff.
+    pub fn with_clearing(mut self, radius: usize, falloff: usize) -> Self {
+        self.clearing_radius = radius;
+        self.clearing_falloff = falloff;
+        self
+    }
+
+    /// Sets `scatter`.
+    pub fn with_scatter(mut self, scatter: f64) -> Self {
+        self.scatter = scatter;
+        self
+    }
+}
+
+/// Vegetation density and scattered plants.
+#[derive(Debug, Clone)]
+pub struct Vegetation {
+    /// Density in `[0, 1]`, indexed `[y][x]`.
+    pub density: Vec<Vec<f64>>,
+    /// One marker per plant, tagged with the species name.
+    pub markers: Vec<Marker>,
+}
+
+impl Vegetation {
+    pub fn density(&self, x: usize, y: usize) -> f64 {
+        self.density[y][x]
+    }
+
+    /// Markers of one species.
+    pub fn markers_of<'a>(&'a self, species: &'a str) -> impl Iterator<Item = &'a Marker> + 'a {
+        self.markers.iter().filter(move |m| m.tag() == species)
+    }
+}
+
+/// Distributes vegetation over `heights` given per-cell `moisture`.
+///
+/// `blocked` cells (structures, roads) and everything within
+/// `clearing_radius` of them stay bare.
+pub fn distribute(
+    heights: &[Vec<f64>],
+    moisture: &[Vec<f64>],
+    blocked: impl IntoIterator<Item = (usize, usize)>,
+    config: &VegetationConfig,
+    seed: u64,
+) -> Vegetation {
+    let h = heights.len();
+    let w = heights.first().map_or(0, Vec::len);
+    let height = |x: usize, y: usize| heights[y].get(x).copied().unwrap_or(0.0);
+    let clearance = clearance(w, h, blocked);
+    let noise = Perlin::new(seed).fbm(3, 2.0, 0.5);
+
+    let mut density = vec![vec![0.0; w]; h];
+    let mut fits = vec![vec![Vec::new(); w]; h];
+    for y in 0..h {
+        for x in 0..w {
+            let elevation = height(x, y);
+            if elevation < config.sea_level {
+                continue;
+            }
+            let wet = moisture
+                .get(y)
+                .and_then(|row| row.get(x))
+                .copied()
+                .unwrap_or(0.0);
+            let slope = slope(&height, x, y, w, h);
+            let cell: Vec<f64> = config
+                .species
+                .iter()
+                .map(|s| s.density * s.fit(wet, elevation, slope))
+                .collect();
+
+            let clear = match clearance[y * w + x] {
+                Some(d) if d <= config.clearing_radius => 0.0,
+                Some(d) if config.clearing_falloff > 0 => {
+                    ((d - config.clearing_radius) as f64 / config.clearing_falloff as f64).min(1.0)
+                }
+                _ => 1.0,
+            };
+            let f = config.noise_frequency;
+            let patch = 1.0 - config.noise * (0.5 - 0.5 * noise.sample(x as f64 * f, y as f64 * f));
+            density[y][x] =
+                (cell.iter().sum::<f64>() * patch.clamp(0.0, 1.0) * clear).clamp(0.0, 1.0);
+            fits[y][x] = cell;
+        }
+    }
+
+    let mut rng = Rng::new(seed).stream(1);
+    let mut placed: Vec<Vec<Option<usize>>> = vec![vec![None; w]; h];
+    let mut markers = Vec::new();
+    for y in 0..h {
+        for x in 0..w {
+            if density[y][x] <= 0.0 || !rng.chance(density[y][x] * config.scatter) {
+                continue;
+            }
+            let Some(index) = rng.weighted_index(&fits[y][x]) else {
+                continue;
+            };
+            let spacing = config.species[index].spacing as usize;
+            if crowded(&placed, x, y, spacing, index) {
+                continue;
+            }
+            placed[y][x] = Some(index);
+            let mut marker =
+                Marker::with_tag(x as u32, y as u32, config.species[index].name.clone());
+            marker.weight = density[y][x] as f32;
+            markers.push(marker);
+        }
+    }
+
+    Vegetation { density, markers }
+}
+
+/// 1 inside `[min, max]`, fading to 0 over 0.1 outside it.
+fn range_fit(value: f64, (min, max): (f64, f64)) -> f64 {
+    const EDGE: f64 = 0.1;
+    let outside = (min - value).max(value - max).max(0.0);
+    (1.0 - outside / EDGE).max(0.0)
+}
+
+/// Largest height difference to a 4-neighbor.
+fn slope(height: &dyn Fn(usize, usize) -> f64, x: usize, y: usize, w: usize, h: usize) -> f64 {
+    let here = height(x, y);
+    let mut steepest = 0.0f64;
+    for (dx, dy) in [(-1i64, 0i64), (1, 0), (0, -1), (0, 1)] {
+        let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+        if nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h {
+            steepest = steepest.max((height(nx as usize, ny as usize) - here).abs());
+        }
+    }
+    steepest
+}
+
+/// Chebyshev distance from each cell to the nearest blocked cell.
+fn clearance(
+    w: usize,
+    h: usize,
+    blocked: impl IntoIterator<Item = (usize, usize)>,
+) -> Vec<Option<usize>> {
+    let mut distance = vec![None; w * h];
+    let mut queue = VecDeque::new();
+    for (x, y) in blocked {
+        if x < w && y < h && distance[y * w + x].is_none() {
+            distance[y * w + x] = Some(0);
+            queue.push_back((x, y));
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let d = distance[y * w + x].unwrap_or(0);
+        for ny in y.saturating_sub(1)..=(y + 1).min(h - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(w - 1) {
+                if distance[ny * w + nx].is_none() {
+                    distance[ny * w + nx] = Some(d + 1);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+    distance
+}
+
+/// Whether a plant of the same species lies within `spacing` of `(x, y)`.
+fn crowded(
+    placed: &[Vec<Option<usize>>],
+    x: usize,
+    y: usize,
+    spacing: usize,
+    species: usize,
+) -> bool {
+    let (top, left) = (y.saturating_sub(spacing), x.saturating_sub(spacing));
+    let mut rows = placed.iter().skip(top).take(y + spacing + 1 - top);
+    rows.any(|row| {
+        row.iter()
+            .skip(left)
+            .take(x + spacing + 1 - left)
+            .any(|&cell| cell == Some(species))
+    })
+}
@@ -0,0 +1,331 @@
+//! Clustered resource deposits (ore veins).
+//!
+//! [`place`] seeds veins where patch noise runs high, so deposits cluster
+//! into rich and barren areas instead of spreading evenly, then grows each
+//! vein as a random walk through host cells (rock by default). Each
+//! [`Resource`] has its own rarity, depth window, vein length, and spacing.
+//! Depth is the row (`0` at the top, `1` at the bottom) for side-on maps,
+//! or the distance from an origin for top-down ones (see [`DepthMode`]).
+//!
+//! The result is a per-cell deposit grid plus one marker per deposit cell
+//! tagged with the resource name; [`Veins::paint`] writes them into a
+//! [`TileSet`] grid as tiles.
+//!
+//! ```rust
+//! use terrain_forge::veins::{self, VeinConfig};
+//! use terrain_forge::{algorithms, Grid};
+//!
+//! let mut grid = Grid::new(80, 60);
+//! algorithms::get("cellular").unwrap().generate(&mut grid, 4);
+//!
+//! let veins = veins::place(&grid, &VeinConfig::default(), 4);
+//! for marker in &veins.markers {
+//!     assert!(!grid[(marker.x as usize, marker.y as usize)].is_floor());
+//! }
+//! assert!(veins.markers_of("coal").count() > veins.markers_of("gold").count());
+//! ```
+
+use crate::noise::{NoiseExt, NoiseSource, Perlin};
+use crate::semantic::Marker;
+use crate::tileset::{TileCell, TileSet};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Cells veins grow through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VeinHost {
+    /// Ore embedded in rock.
+    #[default]
+    Wall,
+    /// Deposits lying in open ground.
+    Floor,
+}
+
+/// How depth is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepthMode {
+    /// Row over height: `0` at the top, `1` at the bottom.
+    #[default]
+    Vertical,
+    /// Distance from a cell over the farthest corner's distance.
+    FromPoint(usize, usize),
+}
+
+/// A resource type and where its veins appear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    /// Marker tag and tile name.
+    pub name: String,
+    /// Veins per 1000 host cells inside the depth window.
+    pub rarity: f64,
+    /// Depth window, `0..=1`; rarity fades to 0 over 0.1 outside it.
+    pub depth: (f64, f64),
+    /// Cells per vein, inclusive range.
+    pub length: (usize, usize),
+    /// Minimum Chebyshev distance from a new vein's seed to any deposit.
+    pub spacing: u32,
+}
+
+impl Resource {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            rarity: 1.0,
+            depth: (0.0, 1.0),
+            length: (4, 10),
+            spacing: 4,
+        }
+    }
+
+    /// Sets `rarity`.
+    pub fn with_rarity(mut self, rarity: f64) -> Self {
+        self.rarity = rarity;
+        self
+    }
+
+    /// Sets the depth window.
+    pub fn with_depth(mut self, min: f64, max: f64) -> Self {
+        self.depth = (min, max);
+        self
+    }
+
+    /// Sets the vein length range.
+    pub fn with_length(mut self, min: usize, max: usize) -> Self {
+        self.length = (min, max);
+        self
+    }
+
+    /// Sets `spacing`.
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+}
+
+/// Configuration for [`place`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VeinConfig {
+    pub resources: Vec<Resource>,
+    /// Default: [`VeinHost::Wall`].
+    pub host: VeinHost,
+    /// Default: [`DepthMode::Vertical`].
+    pub depth_mode: DepthMode,
+    /// Seeding noise frequency; lower gives larger rich areas.
+    /// Default: 0.06.
+    pub noise_frequency: f64,
+    /// How strongly noise concentrates seeds, `0..=1`; 0 seeds uniformly.
+    /// Default: 0.8.
+    pub clustering: f64,
+    /// Chance per step that a vein keeps its direction. Default: 0.6.
+    pub straightness: f64,
+}
+
+impl Default for VeinConfig {
+    fn default() -> Self {
+        Self {
+            resources: vec![
+                Resource::new("coal")
+                    .with_rarity(4.0)
+                    .with_depth(0.0, 0.6)
+                    .with_length(6, 14),
+                Resource::new("iron")
+                    .with_rarity(2.0)
+                    .with_depth(0.3, 0.9)
+                    .with_length(4, 10),
+                Resource::new("gold")
+                    .with_rarity(0.6)
+                    .with_depth(0.7, 1.0)
+                    .with_length(3, 6)
+                    .with_spacing(8),
+            ],
+            host: VeinHost::Wall,
+            depth_mode: DepthMode::Vertical,
+            noise_frequency: 0.06,
+            clustering: 0.8,
+            straightness: 0.6,
+        }
+    }
+}
+
+impl VeinConfig {
+    /// Replaces the resource table.
+    pub fn with_resources(mut self, resources: Vec<Resource>) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    /// Sets `host`.
+    pub fn with_host(mut self, host: VeinHost) -> Self {
+        self.host = host;
+        self
+    }
+
+    /// Sets `depth_mode`.
+    pub fn with_depth_mode(mut self, depth_mode: DepthMode) -> Self {
+        self.depth_mode = depth_mode;
+        self
+    }
+
+    /// Sets `clustering`.
+    pub fn with_clustering(mut self, clustering: f64) -> Self {
+        self.clustering = clustering;
+        self
+    }
+}
+
+/// Placed deposits.
+#[derive(Debug, Clone)]
+pub struct Veins {
+    /// Resource index per cell, indexed `[y][x]`.
+    pub deposits: Vec<Vec<Option<usize>>>,
+    /// Resource names, by index.
+    pub names: Vec<String>,
+    /// One marker per deposit cell, tagged with the resource name; the
+    /// `vein` metadata key numbers the veins.
+    pub markers: Vec<Marker>,
+    /// Number of veins placed.
+    pub vein_count: usize,
+}
+
+impl Veins {
+    /// Resource at `(x, y)`.
+    pub fn resource_at(&self, x: usize, y: usize) -> Option<&str> {
+        let index = (*self.deposits.get(y)?.get(x)?)?;
+        Some(self.names[index].as_str())
+    }
+
+    /// Markers of one resource.
+    pub fn markers_of<'a>(&'a self, resource: &'a str) -> impl Iterator<Item = &'a Marker> + 'a {
+        self.markers.iter().filter(move |m| m.tag() == resource)
+    }
+
+    /// Writes deposits into `grid` as the tiles of `tileset` named after
+    /// each resource; resources without a tile are skipped.
+    pub fn paint(&self, grid: &mut Grid<TileCell>, tileset: &TileSet) {
+        let ids: Vec<_> = self
+            .names
+            .iter()
+            .map(|name| tileset.by_name(name).map(|t| t.id))
+            .collect();
+        for (y, row) in self.deposits.iter().enumerate() {
+            for (x, deposit) in row.iter().enumerate() {
+                if let Some(id) = deposit.and_then(|i| ids[i]) {
+                    grid.set(x as i32, y as i32, tileset.cell(id));
+                }
+            }
+        }
+    }
+}
+
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+/// Places resource veins in the host cells of `grid`.
+pub fn place(grid: &Grid<Tile>, config: &VeinConfig, seed: u64) -> Veins {
+    let (w, h) = (grid.width(), grid.height());
+    let is_host = |x: usize, y: usize| match config.host {
+        VeinHost::Wall => !grid[(x, y)].is_floor(),
+        VeinHost::Floor => grid[(x, y)].is_floor(),
+    };
+    let depth = |x: usize, y: usize| match config.depth_mode {
+        DepthMode::Vertical => y as f64 / (h.max(2) - 1) as f64,
+        DepthMode::FromPoint(ox, oy) => {
+            let far = [(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1)]
+                .iter()
+                .map(|&(cx, cy)| dist(ox, oy, cx, cy))
+                .fold(1.0, f64::max);
+            dist(ox, oy, x, y) / far
+        }
+    };
+    let noise = Perlin::new(seed).fbm(3, 2.0, 0.5);
+    let mut rng = Rng::new(seed).stream(1);
+
+    let mut deposits = vec![vec![None; w]; h];
+    let mut markers = Vec::new();
+    let mut vein_count = 0;
+    for y in 0..h {
+        for x in 0..w {
+            if !is_host(x, y) || deposits[y][x].is_some() {
+                continue;
+            }
+            let f = config.noise_frequency;
+            let richness = 0.5 + 0.5 * noise.sample(x as f64 * f, y as f64 * f);
+            let cluster = (1.0 - config.clustering) + config.clustering * 2.0 * richness.powi(2);
+            let d = depth(x, y);
+            let weights: Vec<f64> = config
+                .resources
+                .iter()
+                .map(|r| r.rarity / 1000.0 * depth_fit(d, r.depth) * cluster.max(0.0))
+                .collect();
+            if !rng.chance(weights.iter().sum()) {
+                continue;
+            }
+            let Some(index) = rng.weighted_index(&weights) else {
+                continue;
+            };
+            let resource = &config.resources[index];
+            if near_deposit(&deposits, x, y, resource.spacing as usize) {
+                continue;
+            }
+
+            let (min, max) = resource.length;
+            let length = rng.range_usize_inclusive(min.max(1), max.max(min).max(1));
+            let (mut cx, mut cy) = (x, y);
+            let mut dir = rng.range_usize(0, 4);
+            for _ in 0..length {
+                if deposits[cy][cx].is_none() {
+                    deposits[cy][cx] = Some(index);
+                    markers.push(
+                        Marker::with_tag(cx as u32, cy as u32, resource.name.clone())
+                            .with_metadata("vein", vein_count.to_string()),
+                    );
+                }
+                if !rng.chance(config.straightness) {
+                    dir = rng.range_usize(0, 4);
+                }
+                // Try the chosen direction, then the others, staying in host cells.
+                let next = (0..4)
+                    .map(|turn| DIRECTIONS[(dir + turn) % 4])
+                    .find_map(|(dx, dy)| {
+                        let (nx, ny) = (cx as i32 + dx, cy as i32 + dy);
+                        (grid.in_bounds(nx, ny) && is_host(nx as usize, ny as usize))
+                            .then_some((nx as usize, ny as usize))
+                    });
+                let Some(next) = next else {
+                    break;
+                };
+                (cx, cy) = next;
+            }
+            vein_count += 1;
+        }
+    }
+
+    Veins {
+        deposits,
+        names: config.resources.iter().map(|r| r.name.clone()).collect(),
+        markers,
+        vein_count,
+    }
+}
+
+fn dist(ax: usize, ay: usize, bx: usize, by: usize) -> f64 {
+    (ax as f64 - bx as f64).hypot(ay as f64 - by as f64)
+}
+
+/// 1 inside `[min, max]`, fading to 0 over 0.1 outside it.
+fn depth_fit(depth: f64, (min, max): (f64, f64)) -> f64 {
+    const EDGE: f64 = 0.1;
+    let outside = (min - depth).max(depth - max).max(0.0);
+    (1.0 - outside / EDGE).max(0.0)
+}
+
+/// Whether any deposit lies within `spacing` (Chebyshev) of `(x, y)`.
+fn near_deposit(deposits: &[Vec<Option<usize>>], x: usize, y: usize, spacing: usize) -> bool {
+    let h = deposits.len();
+    let w = deposits.first().map_or(0, Vec::len);
+    (y.saturating_sub(spacing)..=(y + spacing).min(h - 1)).any(|ny| {
+        (x.saturating_sub(spacing)..=(x + spacing).min(w - 1)).any(|nx| deposits[ny][nx].is_some())
+    })
+}
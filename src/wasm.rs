@@ -0,0 +1,132 @@
+//! WebAssembly bindings (feature `wasm`).
+//!
+//! Build for `wasm32-unknown-unknown` and generate the JS glue with
+//! `wasm-bindgen`:
+//!
+//! ```text
+//! cargo rustc --release --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/terrain_forge.wasm
+//! ```
+//!
+//! Filesystem prefab loading is compiled out on wasm32; pass prefabs inline.
+//! Seeds are `u64`, so JavaScript passes them as `BigInt` (`42n`).
+//!
+//! ```js
+//! import init, { generate } from "./pkg/terrain_forge.js";
+//!
+//! await init();
+//! const map = generate("cellular", 80, 60, 42n, JSON.stringify({ iterations: 5 }));
+//! map.extract("caves", 42n);
+//! const tiles = map.tiles();              // Uint8Array, 1 = floor, row-major
+//! const semantic = JSON.parse(map.semanticJson());
+//! ```
+
+use crate::ops::{self, OpScript, Params};
+use crate::semantic::SemanticLayers;
+use crate::{Grid, Rng, Tile};
+use wasm_bindgen::prelude::*;
+
+/// A generated map exposed to JavaScript as `TerrainMap`.
+#[wasm_bindgen(js_name = TerrainMap)]
+pub struct WasmMap {
+    grid: Grid<Tile>,
+    semantic: Option<SemanticLayers>,
+}
+
+#[wasm_bindgen(js_class = TerrainMap)]
+impl WasmMap {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    /// Row-major tiles, `1` for floor and `0` for wall.
+    pub fn tiles(&self) -> Vec<u8> {
+        self.grid
+            .iter()
+            .map(|(_, _, t)| t.is_floor() as u8)
+            .collect()
+    }
+
+    /// Applies a named effect with optional JSON params.
+    pub fn effect(&mut self, name: &str, params_json: Option<String>) -> Result<(), JsError> {
+        let params = parse_params(params_json)?;
+        ops::effect(
+            name,
+            &mut self.grid,
+            params.as_ref(),
+            self.semantic.as_ref(),
+        )
+        .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Extracts semantic layers (`default`, `caves`, `rooms`, `mazes`).
+    pub fn extract(&mut self, profile: &str, seed: u64) -> Result<(), JsError> {
+        let extractor = ops::script::extractor_for_profile(profile)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        self.semantic = Some(extractor.extract(&self.grid, &mut Rng::new(seed)));
+        Ok(())
+    }
+
+    /// Semantic layers as JSON, or `undefined` if none were extracted.
+    #[wasm_bindgen(js_name = semanticJson)]
+    pub fn semantic_json(&self) -> Option<String> {
+        self.semantic
+            .as_ref()
+            .and_then(|s| serde_json::to_string(s).ok())
+    }
+}
+
+/// Generates a map with a named algorithm and optional JSON params.
+#[wasm_bindgen]
+pub fn generate(
+    name: &str,
+    width: usize,
+    height: usize,
+    seed: u64,
+    params_json: Option<String>,
+) -> Result<WasmMap, JsError> {
+    let params = parse_params(params_json)?;
+    let mut grid = Grid::new(width, height);
+    ops::generate(name, &mut grid, Some(seed), params.as_ref())
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(WasmMap {
+        grid,
+        semantic: None,
+    })
+}
+
+/// Runs a JSON op script (see [`OpScript`]).
+#[wasm_bindgen(js_name = runScript)]
+pub fn run_script(script_json: &str) -> Result<WasmMap, JsError> {
+    let output = OpScript::from_json(script_json)
+        .and_then(|script| script.run())
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(WasmMap {
+        grid: output.grid,
+        semantic: output.semantic,
+    })
+}
+
+/// Lists registered algorithm names.
+#[wasm_bindgen]
+pub fn algorithms() -> Vec<String> {
+    crate::algorithms::list()
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn parse_params(params_json: Option<String>) -> Result<Option<Params>, JsError> {
+    match params_json.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(json) => serde_json::from_str(json)
+            .map(Some)
+            .map_err(|e| JsError::new(&format!("Invalid params JSON: {}", e))),
+    }
+}
@@ -0,0 +1,301 @@
+//! Hierarchical world generation: overworld markers linked to sub-maps.
+//!
+//! A marker links to a child map when its `child` metadata (set through
+//! [`MarkerSpec::child`](crate::config::MarkerSpec::child)) or its tag names
+//! an entry in [`Config::children`]. Each child is generated with a seed
+//! derived from the parent seed and the marker position ([`child_seed`]), so
+//! regenerating the overworld reproduces every sub-map and any one sub-map
+//! can be regenerated on its own. Children may declare children of their own.
+//!
+//! ```rust
+//! use terrain_forge::config::Config;
+//! use terrain_forge::world;
+//!
+//! let config = Config::from_json(r#"{
+//!     "width": 64,
+//!     "height": 48,
+//!     "pipeline": ["cellular"],
+//!     "markers": [
+//!         { "x": 10, "y": 12, "tag": "cave_mouth", "child": "dungeon" },
+//!         { "x": 40, "y": 30, "tag": "town" }
+//!     ],
+//!     "children": {
+//!         "dungeon": { "width": 40, "height": 30, "pipeline": ["bsp"] },
+//!         "town": { "width": 32, "height": 32, "pipeline": ["rooms"] }
+//!     }
+//! }"#).unwrap();
+//!
+//! let map = world::generate(&config, 7).unwrap();
+//! let dungeon = map.child_at(10, 12).unwrap();
+//! assert_eq!(dungeon.spec, "dungeon");
+//! assert_eq!(dungeon.map.seed, world::child_seed(7, 10, 12));
+//! ```
+//!
+//! [`cave_entrances`] finds entrance markers on a heightmap overworld; with
+//! [`CaveEntranceConfig::child`] set they link straight into
+//! [`generate_children`].
+
+use crate::config::Config;
+use crate::ops::OpResult;
+use crate::semantic::{Marker, SemanticLayers};
+use crate::{Grid, Rng, Tile};
+use serde::{Deserialize, Serialize};
+
+/// Marker metadata key naming the [`Config::children`] entry to generate.
+pub const CHILD_KEY: &str = "child";
+
+/// A generated map and the sub-maps linked from its markers.
+#[derive(Debug, Clone)]
+pub struct WorldMap {
+    pub seed: u64,
+    pub grid: Grid<Tile>,
+    pub semantic: SemanticLayers,
+    pub children: Vec<SubMap>,
+}
+
+impl WorldMap {
+    /// Sub-map linked from the marker at `(x, y)`.
+    pub fn child_at(&self, x: u32, y: u32) -> Option<&SubMap> {
+        self.children.iter().find(|c| c.x == x && c.y == y)
+    }
+
+    /// Number of maps in this tree, including this one.
+    pub fn map_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(|c| c.map.map_count())
+            .sum::<usize>()
+    }
+}
+
+/// A child map and the parent marker it hangs off.
+#[derive(Debug, Clone)]
+pub struct SubMap {
+    /// Marker position on the parent map.
+    pub x: u32,
+    pub y: u32,
+    /// Marker tag.
+    pub tag: String,
+    /// Key of the [`Config::children`] entry used.
+    pub spec: String,
+    pub map: WorldMap,
+}
+
+/// Seed for the child of the marker at `(x, y)` on a map generated with
+/// `parent_seed`.
+pub fn child_seed(parent_seed: u64, x: u32, y: u32) -> u64 {
+    Rng::new(parent_seed)
+        .stream(((x as u64) << 32) | y as u64)
+        .next_u64()
+}
+
+/// The [`Config::children`] entry `marker` links to, with its key.
+///
+/// `child` metadata wins over the tag; markers matching neither have no child.
+pub fn child_spec<'a>(config: &'a Config, marker: &Marker) -> Option<(&'a str, &'a Config)> {
+    let key = marker
+        .metadata
+        .get(CHILD_KEY)
+        .cloned()
+        .unwrap_or_else(|| marker.tag());
+    config
+        .children
+        .get_key_value(&key)
+        .map(|(k, v)| (k.as_str(), v))
+}
+
+/// Generates `config` with `seed`, then every linked sub-map recursively.
+pub fn generate(config: &Config, seed: u64) -> OpResult<WorldMap> {
+    let grid = config.generate(seed)?;
+    let mut semantic = config.extractor().extract(&grid, &mut Rng::new(seed));
+    config.apply_marker_overrides(&mut semantic);
+    let children = generate_children(config, seed, &semantic)?;
+    Ok(WorldMap {
+        seed,
+        grid,
+        semantic,
+        children,
+    })
+}
+
+/// Generates the sub-maps linked from `semantic`'s markers, for a parent
+/// generated from `config` with `seed`.
+pub fn generate_children(
+    config: &Config,
+    seed: u64,
+    semantic: &SemanticLayers,
+) -> OpResult<Vec<SubMap>> {
+    let mut children = Vec::new();
+    for marker in &semantic.markers {
+        let Some((spec, child)) = child_spec(config, marker) else {
+            continue;
+        };
+        children.push(SubMap {
+            x: marker.x,
+            y: marker.y,
+            tag: marker.tag(),
+            spec: spec.to_string(),
+            map: generate(child, child_seed(seed, marker.x, marker.y))?,
+        });
+    }
+    Ok(children)
+}
+
+/// Marker metadata key for what a cave entrance opens onto: `"cliff"` or
+/// `"coast"`.
+pub const ENTRANCE_KIND_KEY: &str = "kind";
+/// Marker metadata key for the direction a cave entrance faces, away from
+/// its rock face: `"north"`, `"east"`, `"south"`, or `"west"`.
+pub const FACING_KEY: &str = "facing";
+
+/// Configuration for [`cave_entrances`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaveEntranceConfig {
+    /// Heights below this are water. Default: 0.4.
+    pub sea_level: f64,
+    /// Smallest rise to the neighbouring cell that counts as a rock face.
+    /// Default: 0.15.
+    pub min_rise: f64,
+    /// Largest height change to the ground in front of an inland
+    /// entrance. Default: 0.05.
+    pub max_ground_slope: f64,
+    /// Also place entrances on shore cells under a cliff. Default: true.
+    pub coastal: bool,
+    /// Minimum Chebyshev distance between entrances. Default: 8.
+    pub min_spacing: usize,
+    /// Default: 8.
+    pub max_entrances: usize,
+    /// Marker tag. Default: `"cave_entrance"`.
+    pub tag: String,
+    /// [`Config::children`] key written as [`CHILD_KEY`] metadata. Default:
+    /// `None` (the tag is matched instead).
+    pub child: Option<String>,
+}
+
+impl Default for CaveEntranceConfig {
+    fn default() -> Self {
+        Self {
+            sea_level: 0.4,
+            min_rise: 0.15,
+            max_ground_slope: 0.05,
+            coastal: true,
+            min_spacing: 8,
+            max_entrances: 8,
+            tag: "cave_entrance".to_string(),
+            child: None,
+        }
+    }
+}
+
+impl CaveEntranceConfig {
+    /// Sets `sea_level`.
+    pub fn with_sea_level(mut self, sea_level: f64) -> Self {
+        self.sea_level = sea_level;
+        self
+    }
+
+    /// Sets `min_rise`.
+    pub fn with_min_rise(mut self, rise: f64) -> Self {
+        self.min_rise = rise;
+        self
+    }
+
+    /// Sets `coastal`.
+    pub fn with_coastal(mut self, coastal: bool) -> Self {
+        self.coastal = coastal;
+        self
+    }
+
+    /// Sets `min_spacing`.
+    pub fn with_min_spacing(mut self, spacing: usize) -> Self {
+        self.min_spacing = spacing;
+        self
+    }
+
+    /// Sets `max_entrances`.
+    pub fn with_max_entrances(mut self, max: usize) -> Self {
+        self.max_entrances = max;
+        self
+    }
+
+    /// Sets `child`.
+    pub fn with_child(mut self, child: impl Into<String>) -> Self {
+        self.child = Some(child.into());
+        self
+    }
+}
+
+/// Cave entrance markers on a heightmap (rows of `f64`).
+///
+/// An entrance is a land cell at the foot of a rock face: a neighbour
+/// rises by at least `min_rise`, and the cell on the opposite side is
+/// either near-level walkable ground or, with `coastal`, water. The
+/// tallest faces win, subject to `min_spacing`. Markers carry
+/// [`ENTRANCE_KIND_KEY`], [`FACING_KEY`], and, if configured,
+/// [`CHILD_KEY`].
+pub fn cave_entrances(heights: &[Vec<f64>], config: &CaveEntranceConfig) -> Vec<Marker> {
+    let h = heights.len();
+    let w = heights.first().map_or(0, |row| row.len());
+    let at = |x: i64, y: i64| {
+        (x >= 0 && y >= 0 && (x as usize) < w && (y as usize) < h)
+            .then(|| heights[y as usize][x as usize])
+    };
+    const FACES: [((i64, i64), &str); 4] = [
+        ((0, -1), "south"),
+        ((1, 0), "west"),
+        ((0, 1), "north"),
+        ((-1, 0), "east"),
+    ];
+
+    let mut candidates: Vec<(f64, usize, usize, &str, &str)> = Vec::new();
+    for (y, row) in heights.iter().enumerate() {
+        for (x, &here) in row.iter().enumerate() {
+            if here < config.sea_level {
+                continue;
+            }
+            let (xi, yi) = (x as i64, y as i64);
+            let best = FACES
+                .iter()
+                .filter_map(|&((dx, dy), facing)| {
+                    let rise = at(xi + dx, yi + dy)? - here;
+                    let front = at(xi - dx, yi - dy)?;
+                    let kind = if front < config.sea_level {
+                        config.coastal.then_some("coast")?
+                    } else if (front - here).abs() <= config.max_ground_slope {
+                        "cliff"
+                    } else {
+                        return None;
+                    };
+                    (rise >= config.min_rise).then_some((rise, kind, facing))
+                })
+                .max_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some((rise, kind, facing)) = best {
+                candidates.push((rise, x, y, kind, facing));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then((a.2, a.1).cmp(&(b.2, b.1))));
+
+    let mut markers: Vec<Marker> = Vec::new();
+    for (_, x, y, kind, facing) in candidates {
+        if markers.len() >= config.max_entrances {
+            break;
+        }
+        let spaced = markers.iter().all(|m| {
+            (m.x as usize).abs_diff(x).max((m.y as usize).abs_diff(y)) >= config.min_spacing.max(1)
+        });
+        if !spaced {
+            continue;
+        }
+        let mut marker = Marker::with_tag(x as u32, y as u32, config.tag.clone())
+            .with_metadata(ENTRANCE_KIND_KEY, kind)
+            .with_metadata(FACING_KEY, facing);
+        if let Some(child) = &config.child {
+            marker = marker.with_metadata(CHILD_KEY, child.clone());
+        }
+        markers.push(marker);
+    }
+    markers
+}
@@ -121,12 +121,114 @@ fn bsp_min_room_size_respected() {
         min_room_size: 8,
         max_depth: 3,
         room_padding: 1,
+        ..Default::default()
     });
     let mut grid = Grid::new(80, 60);
     algo.generate(&mut grid, 42);
     assert!(grid.count(|t| t.is_floor()) > 0);
 }
 
+#[test]
+fn bsp_room_shapes_stay_connected() {
+    let pillar = PrefabData {
+        name: "pillar_hall".to_string(),
+        width: 5,
+        height: 5,
+        pattern: [".....", ".....", "..#..", ".....", "....."]
+            .map(String::from)
+            .to_vec(),
+        weight: 1.0,
+        tags: Vec::new(),
+        legend: None,
+    };
+    // Walls with floor on all eight sides: only the prefab makes these.
+    let pillars = |grid: &Grid<Tile>| {
+        grid.iter()
+            .filter(|&(x, y, t)| {
+                t.is_wall()
+                    && (-1..=1).all(|dy| {
+                        (-1..=1).all(|dx| {
+                            (dx, dy) == (0, 0)
+                                || grid.get(x as i32 + dx, y as i32 + dy) == Some(&Tile::Floor)
+                        })
+                    })
+            })
+            .count()
+    };
+    let base = BspConfig::default().with_min_room_size(8);
+    let mut rects = Grid::new(80, 60);
+    Bsp::new(base.clone()).generate(&mut rects, 4);
+    let mut explicit = Grid::new(80, 60);
+    Bsp::new(
+        base.clone()
+            .with_room_shapes(vec![(BspRoomShape::Rectangle, 3.0)]),
+    )
+    .generate(&mut explicit, 4);
+    assert_eq!(explicit, rects);
+    assert_eq!(pillars(&rects), 0);
+
+    for shapes in [
+        vec![(BspRoomShape::LShape, 1.0)],
+        vec![(BspRoomShape::Circle, 1.0)],
+        vec![(BspRoomShape::Prefab(pillar.clone()), 1.0)],
+        vec![
+            (BspRoomShape::Rectangle, 1.0),
+            (BspRoomShape::LShape, 2.0),
+            (BspRoomShape::Circle, 1.0),
+        ],
+    ] {
+        let mut grid = Grid::new(80, 60);
+        Bsp::new(base.clone().with_room_shapes(shapes.clone())).generate(&mut grid, 4);
+        assert_ne!(grid, rects, "{:?}", shapes);
+        assert_eq!(grid.flood_regions().len(), 1, "{:?}", shapes);
+        assert!(grid.count(|t| t.is_floor()) < rects.count(|t| t.is_floor()) * 2);
+    }
+
+    let mut grid = Grid::new(80, 60);
+    Bsp::new(base.with_room_shapes(vec![(BspRoomShape::Prefab(pillar), 1.0)]))
+        .generate(&mut grid, 4);
+    assert!(pillars(&grid) > 1);
+
+    let mut params = terrain_forge::Params::new();
+    params.insert(
+        "room_shapes".to_string(),
+        serde_json::json!([["l_shape", 1.0], ["circle", 1.0]]),
+    );
+    let mut via_ops = Grid::new(80, 60);
+    terrain_forge::ops::generate("bsp", &mut via_ops, Some(4), Some(&params)).unwrap();
+    assert_eq!(via_ops.flood_regions().len(), 1);
+    params.insert("room_shapes".to_string(), serde_json::json!("hexagon"));
+    assert!(terrain_forge::ops::generate("bsp", &mut via_ops, Some(4), Some(&params)).is_err());
+}
+
+#[test]
+fn cellular_connect_keeps_one_cave() {
+    let config = CellularConfig::default().with_initial_floor_chance(0.4);
+    let mut plain = Grid::new(80, 60);
+    CellularAutomata::new(config.clone()).generate(&mut plain, 11);
+    assert!(plain.flood_regions().len() > 1, "seed should leave islands");
+
+    let connected = config.with_connect(true).with_min_cave_size(10);
+    let mut grid = Grid::new(80, 60);
+    CellularAutomata::new(connected.clone()).generate(&mut grid, 11);
+    assert_eq!(grid.flood_regions().len(), 1);
+    // Big caves survive; only tunnels are added and tiny caves filled.
+    for cave in plain.flood_regions().iter().filter(|c| c.len() >= 10) {
+        assert!(cave.iter().all(|&(x, y)| grid[(x, y)].is_floor()));
+    }
+    for (x, y, tile) in grid.iter() {
+        if x == 0 || y == 0 || x == 79 || y == 59 {
+            assert!(!tile.is_floor());
+        }
+    }
+
+    let mut frames = Vec::new();
+    let mut framed = Grid::new(80, 60);
+    CellularAutomata::new(connected)
+        .generate_frames(&mut framed, 11, &mut |g| frames.push(g.clone()));
+    assert_eq!(frames.last(), Some(&grid));
+}
+
 #[test]
 fn cellular_iterations_affect_output() {
     let mut g1 = Grid::new(40, 30);
@@ -206,6 +308,66 @@ fn diamond_square_different_thresholds_differ() {
     );
 }
 
+#[test]
+fn rooms_on_mask_stay_inside_the_site() {
+    use terrain_forge::Masked;
+
+    // An L-shaped site: rooms must fit inside it and corridors can't cut
+    // across the missing corner.
+    let (w, h) = (60, 40);
+    let site =
+        |x: usize, y: usize| (2..58).contains(&x) && (2..38).contains(&y) && (x < 30 || y < 18);
+    let mask: Vec<Vec<bool>> = (0..h)
+        .map(|y| (0..w).map(|x| site(x, y)).collect())
+        .collect();
+    let mut grid = Grid::new(w, h);
+    Masked::new(RoomsOnMask::default(), mask).generate(&mut grid, 5);
+
+    assert!(grid.count(|t| t.is_floor()) > 0);
+    for (x, y, tile) in grid.iter() {
+        assert!(
+            !tile.is_floor() || site(x, y),
+            "floor at ({x}, {y}) off the site"
+        );
+    }
+    assert_eq!(grid.flood_regions().len(), 1, "rooms should be connected");
+
+    // `on_floor` uses open ground from an earlier pass as the site.
+    let mut yard = Grid::new(w, h);
+    yard.fill_rect(10, 10, 20, 15, Tile::Floor);
+    RoomsOnMask::new(RoomsOnMaskConfig::default().with_on_floor(true)).generate(&mut yard, 5);
+    assert!(yard.count(|t| t.is_floor()) > 0);
+    for (x, y, tile) in yard.iter() {
+        assert!(!tile.is_floor() || ((10..30).contains(&x) && (10..25).contains(&y)));
+    }
+}
+
+#[test]
+fn soft_time_limit_keeps_partial_output() {
+    let unbounded = Dla::new(DlaConfig::default().with_num_particles(200));
+    let mut grid = Grid::new(60, 40);
+    let run = unbounded.generate_timed(&mut grid, 9);
+    assert!(!run.timed_out);
+    let mut plain = Grid::new(60, 40);
+    unbounded.generate(&mut plain, 9);
+    assert_eq!(grid, plain);
+
+    let limited = Dla::new(
+        DlaConfig::default()
+            .with_num_particles(usize::MAX)
+            .with_max_duration(std::time::Duration::from_millis(20)),
+    );
+    let mut grid = Grid::new(60, 40);
+    let run = limited.generate_timed(&mut grid, 9);
+    assert!(run.timed_out);
+    assert!(run.elapsed >= std::time::Duration::from_millis(20));
+    assert!(grid.count(|t| t.is_floor()) > 1);
+
+    let wfc = Wfc::new(WfcConfig::default().with_max_duration(std::time::Duration::ZERO));
+    let mut grid = Grid::new(20, 20);
+    assert!(wfc.generate_timed(&mut grid, 1).timed_out);
+}
+
 // --- WFC ---
 
 #[test]
@@ -221,6 +383,37 @@ fn wfc_pattern_extraction() {
     assert!(patterns.len() >= 2);
 }
 
+#[test]
+fn wfc_extraction_counts_frequencies_across_samples() {
+    let walls = Grid::<Tile>::new(4, 4);
+    let mut corner = Grid::new(4, 4);
+    corner.set(3, 3, Tile::Floor);
+
+    let config = PatternExtractConfig::default().with_rotations(false);
+    let patterns =
+        WfcPatternExtractor::extract_from_samples(&[walls.clone(), corner.clone()], &config);
+    // 4 windows per sample; the all-wall window occurs 4 + 3 times.
+    let total: u32 = patterns.iter().map(|p| p.frequency()).sum();
+    assert_eq!(total, 8);
+    assert_eq!(patterns[0].frequency(), 7);
+    assert!(patterns[0].tiles().iter().flatten().all(|t| t.is_wall()));
+
+    // Mirrors and rotations of the corner window: four distinct patterns.
+    let augmented = WfcPatternExtractor::extract_from_samples(
+        std::slice::from_ref(&corner),
+        &config.clone().with_reflections(true).with_rotations(true),
+    );
+    let corners = augmented
+        .iter()
+        .filter(|p| p.tiles().iter().flatten().any(|t| t.is_floor()));
+    assert_eq!(corners.count(), 4);
+
+    // Periodic samples start a window at every cell.
+    let periodic = WfcPatternExtractor::extract_from_samples(&[walls], &config.with_periodic(true));
+    assert_eq!(periodic.len(), 1);
+    assert_eq!(periodic[0].frequency(), 16);
+}
+
 #[test]
 fn wfc_enhanced_generation() {
     let mut grid = Grid::new(15, 15);
@@ -228,6 +421,7 @@ fn wfc_enhanced_generation() {
         floor_weight: 0.3,
         pattern_size: 3,
         enable_backtracking: true,
+        ..WfcConfig::default()
     });
     wfc.generate(&mut grid, 12345);
     assert!(grid.count(|t: &Tile| t.is_floor()) > 0);
@@ -356,3 +550,306 @@ fn layered_generator_union_adds_floors() {
     Bsp::default().generate(&mut bsp_only, 42);
     assert!(grid.count(|t| t.is_floor()) >= bsp_only.count(|t| t.is_floor()));
 }
+
+#[test]
+fn layered_generator_names_seeds_and_records_layers() {
+    use terrain_forge::compose::LayeredGenerator;
+
+    let mut layered = LayeredGenerator::new()
+        .base(Bsp::default())
+        .named("rooms")
+        .union(DrunkardWalk::default())
+        .named("tunnels")
+        .difference(CellularAutomata::default())
+        .seeded(11);
+    assert_eq!(layered.layer_names(), ["rooms", "tunnels", "layer2"]);
+
+    let mut grid = Grid::new(60, 40);
+    let history = layered.generate_history(&mut grid, 5);
+    let mut plain = Grid::new(60, 40);
+    layered.generate(&mut plain, 5);
+    assert_eq!(grid, plain);
+    assert_eq!(history.layers.len(), 3);
+    assert_eq!(history.layers[2].seed, 11);
+    assert_eq!(history.layers[2].after, grid);
+    for (x, y, tile) in grid.iter() {
+        let source = history.source(x, y);
+        if tile.is_floor() {
+            assert!(matches!(source, Some("rooms" | "tunnels")));
+            assert_eq!(
+                source == Some("tunnels"),
+                !history.layers[0].after[(x, y)].is_floor()
+            );
+        } else if history.layers[1].after[(x, y)].is_floor() {
+            assert_eq!(source, Some("layer2"));
+            assert!(history.layers[2].output[(x, y)].is_floor());
+        }
+    }
+
+    // Switching a layer off leaves the others' seeds alone.
+    assert!(layered.set_enabled("tunnels", false));
+    assert!(!layered.set_enabled("missing", false));
+    assert!(!layered.is_enabled("tunnels"));
+    let without = layered.generate_history(&mut Grid::new(60, 40), 5);
+    let names: Vec<_> = without.layers.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["rooms", "layer2"]);
+    assert_eq!(without.layers[0].after, history.layers[0].after);
+    assert_eq!(without.layers[1].output, history.layers[2].output);
+}
+
+#[test]
+fn progressive_zooms_from_macro_grid() {
+    use terrain_forge::compose::Progressive;
+
+    let sharp = Progressive::new(CellularAutomata::default())
+        .zoom(2)
+        .zoom(3)
+        .with_fuzzy(false);
+    assert_eq!(sharp.sizes(61, 40), [(11, 7), (21, 14), (61, 40)]);
+    let levels = sharp.levels(61, 40, 8);
+    // Without fuzz and refinement every fine cell copies its coarse cell.
+    for (x, y, tile) in levels[2].iter() {
+        assert_eq!(*tile, levels[1][(x * 21 / 61, y * 14 / 40)]);
+    }
+
+    // Adding a finer level doesn't reseed the coarser ones.
+    let lod = || Progressive::new(CellularAutomata::default()).zoom(2);
+    let shallow = lod().levels(40, 30, 8);
+    let deeper = lod()
+        .refine(CellularAutomata::default())
+        .zoom(2)
+        .levels(80, 60, 8);
+    assert_eq!(shallow[0], deeper[0]);
+    assert!(shallow[1].count(|t| t.is_floor()) > 0);
+    assert_eq!(deeper.len(), 3);
+}
+
+#[test]
+fn ensemble_votes_and_weights_members() {
+    use terrain_forge::compose::Ensemble;
+    use terrain_forge::constraints::{ConstraintSet, DensityConstraint};
+
+    let (w, h) = (60, 40);
+    let ensemble = Ensemble::new(CellularAutomata::default(), 5);
+    let members = ensemble.members(w, h, 9);
+    assert_eq!(members.len(), 5);
+    assert_ne!(members[0].0, members[1].0);
+
+    let mut grid = Grid::new(w, h);
+    ensemble.generate(&mut grid, 9);
+    for (x, y, tile) in grid.iter() {
+        let votes = members.iter().filter(|(m, _)| m[(x, y)].is_floor()).count();
+        assert_eq!(tile.is_floor(), votes >= 3, "({}, {})", x, y);
+    }
+    let mut again = Grid::new(w, h);
+    ensemble.generate(&mut again, 9);
+    assert_eq!(grid, again);
+
+    // A unanimous threshold keeps only cells every member opened.
+    let strict = Ensemble::new(CellularAutomata::default(), 5).with_threshold(1.0);
+    let mut core = Grid::new(w, h);
+    strict.generate(&mut core, 9);
+    assert!(core
+        .iter()
+        .all(|(x, y, t)| !t.is_floor() || grid[(x, y)].is_floor()));
+
+    // Members far outside the density window get a weak vote.
+    let mut set = ConstraintSet::new();
+    set.push(DensityConstraint::new(0.0, 0.01));
+    let weighted = Ensemble::new(CellularAutomata::default(), 5).with_constraints(set);
+    let weights: Vec<f64> = weighted.members(w, h, 9).iter().map(|m| m.1).collect();
+    assert!(
+        weights.iter().all(|&wt| wt > 0.0 && wt < 0.1),
+        "{:?}",
+        weights
+    );
+}
+
+// --- Custom cells ---
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct LitCell {
+    open: bool,
+    light: u8,
+}
+
+impl terrain_forge::Cell for LitCell {
+    fn is_passable(&self) -> bool {
+        self.open
+    }
+    fn set_passable(&mut self) {
+        self.open = true;
+    }
+    fn set_impassable(&mut self) {
+        self.open = false;
+    }
+}
+
+#[test]
+fn cell_adapter_runs_tile_algorithms_on_custom_cells() {
+    use terrain_forge::compose::LayeredGenerator;
+    use terrain_forge::CellAdapter;
+
+    for name in standard_algorithms() {
+        let algo = algorithms::get(name).unwrap();
+        let mut tiles = Grid::new(40, 30);
+        algo.generate(&mut tiles, 7);
+
+        let mut cells: Grid<LitCell> = Grid::new(40, 30);
+        for (x, y) in [(0, 0), (20, 15)] {
+            cells[(x, y)].light = 9;
+        }
+        CellAdapter::new(algorithms::get(name).unwrap()).generate(&mut cells, 7);
+
+        assert_eq!(cells.to_tiles(), tiles, "{} passability differs", name);
+        assert_eq!(cells[(0, 0)].light, 9);
+        assert_eq!(cells[(20, 15)].light, 9);
+    }
+
+    let mut grid: Grid<LitCell> = Grid::new(40, 30);
+    LayeredGenerator::new()
+        .base(CellAdapter::new(Bsp::default()))
+        .union(CellAdapter::new(DrunkardWalk::default()))
+        .generate(&mut grid, 42);
+    assert!(grid.count(|c| c.open) > 0);
+}
+
+// --- Configs ---
+
+#[test]
+fn configs_build_fluently_and_deserialize_partially() {
+    let built = CellularConfig::default()
+        .with_iterations(7)
+        .with_birth_limit(6);
+    let parsed: CellularConfig =
+        serde_json::from_str(r#"{ "iterations": 7, "birth_limit": 6 }"#).unwrap();
+    assert_eq!(parsed.iterations, built.iterations);
+    assert_eq!(parsed.birth_limit, built.birth_limit);
+    assert_eq!(
+        parsed.initial_floor_chance,
+        CellularConfig::default().initial_floor_chance
+    );
+
+    let mut from_built = Grid::new(40, 30);
+    let mut from_parsed = Grid::new(40, 30);
+    CellularAutomata::new(built).generate(&mut from_built, 3);
+    CellularAutomata::new(parsed).generate(&mut from_parsed, 3);
+    assert_eq!(from_built, from_parsed);
+
+    let prefab = PrefabConfig::default().with_tags(vec!["boss".to_string()]);
+    let json = serde_json::to_string(&prefab).unwrap();
+    let back: PrefabConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.tags, Some(vec!["boss".to_string()]));
+
+    let semantic: terrain_forge::SemanticConfig =
+        serde_json::from_str(r#"{ "max_markers_per_region": 1 }"#).unwrap();
+    assert_eq!(semantic.max_markers_per_region, 1);
+    assert_eq!(semantic.marker_placement.min_marker_distance, 3);
+}
+
+// --- Presets ---
+
+#[test]
+fn presets_build_and_apply_through_ops() {
+    use terrain_forge::ops;
+
+    for &name in algorithms::list() {
+        for &preset in algorithms::presets(name) {
+            let params = algorithms::preset_params(name, preset).unwrap();
+            ops::validate_algorithm_params(name, &params)
+                .unwrap_or_else(|e| panic!("{}/{}: {}", name, preset, e));
+            assert!(algorithms::preset(name, preset).is_some());
+        }
+    }
+    assert!(algorithms::preset("cellular_automata", "dense_caves").is_some());
+    assert!(algorithms::preset("cellular", "nope").is_none());
+
+    let by_preset = |preset: &str| {
+        let mut grid = Grid::new(60, 40);
+        algorithms::preset("cellular", preset)
+            .unwrap()
+            .generate(&mut grid, 9);
+        grid
+    };
+    assert_ne!(by_preset("dense_caves"), by_preset("sparse_pockets"));
+
+    // "preset" in ops params expands, with explicit params taking priority.
+    let mut params = ops::Params::new();
+    params.insert("preset".into(), serde_json::json!("dense_caves"));
+    params.insert("iterations".into(), serde_json::json!(2));
+    let mut via_ops = Grid::new(60, 40);
+    ops::generate("cellular", &mut via_ops, Some(9), Some(&params)).unwrap();
+    let mut direct = Grid::new(60, 40);
+    CellularAutomata::new(
+        CellularConfig::default()
+            .with_initial_floor_chance(0.55)
+            .with_iterations(2),
+    )
+    .generate(&mut direct, 9);
+    assert_eq!(via_ops, direct);
+
+    params.insert("preset".into(), serde_json::json!("nope"));
+    let err = ops::build_algorithm("cellular", Some(&params))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("dense_caves"));
+}
+
+#[test]
+fn masked_generation_only_writes_inside_mask() {
+    use terrain_forge::{Masked, Region};
+
+    let mut base = Grid::new(60, 40);
+    Bsp::default().generate(&mut base, 3);
+    let mut region = Region::new(1, "courtyard");
+    for y in 5..25 {
+        for x in 10..40 {
+            region.add_cell(x, y);
+        }
+    }
+
+    let masked = Masked::from_cells(CellularAutomata::default(), 60, 40, region.cells.clone());
+    let mut grid = base.clone();
+    masked.generate(&mut grid, 9);
+    let mut full = Grid::new(60, 40);
+    CellularAutomata::default().generate(&mut full, 9);
+    for (x, y, tile) in grid.iter() {
+        let inside = (10..40).contains(&x) && (5..25).contains(&y);
+        let expected = if inside { full[(x, y)] } else { base[(x, y)] };
+        assert_eq!(*tile, expected, "({}, {})", x, y);
+    }
+    assert_ne!(grid, base);
+
+    // Boxed algorithms forward the masked call.
+    let boxed: Box<dyn Algorithm + Send + Sync> = Box::new(CellularAutomata::default());
+    let mut via_box = base.clone();
+    boxed.generate_masked(&mut via_box, masked.mask(), 9);
+    assert_eq!(via_box, grid);
+}
+
+#[test]
+fn wfc_resumes_from_serialized_checkpoints() {
+    let wfc = Wfc::default();
+    let patterns = || {
+        let mut sample = Grid::new(40, 30);
+        wfc.generate(&mut sample, 12);
+        WfcPatternExtractor::extract_patterns(&sample, 3)
+    };
+    let mut direct = Grid::new(16, 12);
+    wfc.generate_with_patterns(&mut direct, patterns(), 5);
+
+    let mut grid = Grid::new(16, 12);
+    let mut checkpoint = wfc.start(16, 12, patterns(), 5);
+    let mut slices = 0;
+    while !checkpoint.is_finished() {
+        let before = checkpoint.collapses();
+        wfc.resume(&mut grid, &mut checkpoint, 50);
+        assert!(checkpoint.collapses() - before <= 50);
+        // Simulate a preempted job: save, drop, reload.
+        checkpoint = WfcCheckpoint::from_json(&checkpoint.to_json()).unwrap();
+        slices += 1;
+    }
+    assert!(slices > 2, "{}", slices);
+    assert_eq!(grid, direct);
+    assert!(WfcCheckpoint::from_json("{}").is_err());
+}
@@ -0,0 +1,74 @@
+//! Elevation band tests
+
+use terrain_forge::bands::{self, BandConfig};
+use terrain_forge::tileset::{TileDef, TileId};
+use terrain_forge::Cell;
+
+/// Heights rising linearly from 0 at x = 0 to just under 1 at the east edge.
+fn ramp(w: usize, h: usize) -> Vec<Vec<f64>> {
+    (0..h)
+        .map(|_| (0..w).map(|x| x as f64 / w as f64).collect())
+        .collect()
+}
+
+#[test]
+fn heights_map_to_ordered_bands() {
+    let heights = ramp(100, 20);
+    let config = BandConfig::default();
+    let classes = bands::classify(&heights, &config, 1);
+    let names: Vec<&str> = [0, 35, 42, 50, 70, 99]
+        .iter()
+        .map(|&x| config.bands[classes[0][x]].tile.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "deep_water",
+            "shallow_water",
+            "beach",
+            "grass",
+            "rock",
+            "snow"
+        ]
+    );
+    for row in &classes {
+        assert!(row.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    let grid = bands::paint(&heights, &config, 1);
+    assert_eq!(grid[(10, 5)].id(), TileId(2));
+    assert!(!grid[(10, 5)].is_passable());
+    assert!(grid[(50, 5)].is_passable());
+    assert!(config.tileset().render_text(&grid).starts_with("~~~"));
+
+    // Custom bands slot in by threshold.
+    let lava = TileDef::new(TileId(9), "lava").with_glyph('!');
+    let config = config.with_band(0.95, lava);
+    let classes = bands::classify(&heights, &config, 1);
+    assert_eq!(classes[0][90], config.band("lava").unwrap());
+    assert_eq!(classes[0][99], config.band("snow").unwrap());
+}
+
+#[test]
+fn dither_frays_band_edges() {
+    let heights = ramp(100, 60);
+    let plain = bands::classify(&heights, &BandConfig::default(), 4);
+    let config = BandConfig::default().with_dither(0.05);
+    let dithered = bands::classify(&heights, &config, 4);
+    assert_eq!(dithered, bands::classify(&heights, &config, 4));
+    assert_ne!(dithered, bands::classify(&heights, &config, 5));
+
+    // Edges move by at most the dither amplitude: never more than one band
+    // away, and the shoreline column varies from row to row.
+    let beach = config.band("beach").unwrap();
+    let mut shores = Vec::new();
+    for (row, plain_row) in dithered.iter().zip(&plain) {
+        for (a, b) in row.iter().zip(plain_row) {
+            assert!(a.abs_diff(*b) <= 1);
+        }
+        shores.push(row.iter().position(|&c| c == beach));
+    }
+    shores.sort();
+    shores.dedup();
+    assert!(shores.len() > 2, "{:?}", shores);
+}
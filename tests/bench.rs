@@ -0,0 +1,106 @@
+//! Benchmark and budget tests — per-size stats, budget fallback, abort, and
+//! cooperative cancellation.
+
+use std::time::Duration;
+use terrain_forge::algorithms::{Bsp, CellularAutomata, Dla, DlaConfig};
+use terrain_forge::bench::{self, BudgetOutcome, TimeBudgeted};
+use terrain_forge::{Algorithm, Error, Grid, Tile};
+
+/// Fills the grid with floor after sleeping.
+struct Slow(Duration);
+
+impl Algorithm for Slow {
+    fn generate(&self, grid: &mut Grid<Tile>, _seed: u64) {
+        std::thread::sleep(self.0);
+        grid.fill(Tile::Floor);
+    }
+
+    fn name(&self) -> &'static str {
+        "slow"
+    }
+}
+
+#[test]
+fn measure_reports_each_size() {
+    let report = bench::measure(
+        &CellularAutomata::default(),
+        &[(20, 20), (60, 40)],
+        &[1, 2, 3],
+    );
+    assert_eq!(report.algorithm, "CellularAutomata");
+    assert_eq!(report.sizes.len(), 2);
+    for stats in &report.sizes {
+        assert_eq!(stats.runs, 3);
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+    }
+    assert_eq!(report.sizes[1].width * report.sizes[1].height, 2400);
+    assert!(report.largest_within(Duration::from_secs(60)).is_some());
+    assert!(report.largest_within(Duration::ZERO).is_none());
+
+    let slow = bench::measure(&Slow(Duration::from_millis(5)), &[(4, 4)], &[0, 1]);
+    assert!(slow.sizes[0].min >= Duration::from_millis(5));
+}
+
+#[test]
+fn budget_degrades_or_aborts() {
+    let mut grid = Grid::new(30, 20);
+    let fast = TimeBudgeted::new(Slow(Duration::ZERO), Duration::from_secs(10));
+    assert!(matches!(
+        fast.try_generate(&mut grid, 1).unwrap(),
+        BudgetOutcome::Completed(_)
+    ));
+    assert_eq!(grid.count(|t| t.is_floor()), 600);
+
+    let slow = Slow(Duration::from_millis(500));
+    let mut grid = Grid::new(30, 20);
+    let aborted = TimeBudgeted::new(slow, Duration::from_millis(20));
+    let err = aborted.try_generate(&mut grid, 1).unwrap_err();
+    assert!(matches!(err, Error::BudgetExceeded { ref algorithm, .. } if algorithm == "slow"));
+    assert_eq!(
+        grid.count(|t| t.is_floor()),
+        0,
+        "aborted runs leave the grid"
+    );
+
+    // Runs for many seconds unless cancelled.
+    let endless = Dla::new(
+        DlaConfig::default()
+            .with_num_particles(10_000_000)
+            .with_max_walk_steps(1_000_000),
+    );
+    let degraded =
+        TimeBudgeted::new(endless, Duration::from_millis(20)).with_fallback(Bsp::default());
+    let outcome = degraded.try_generate(&mut grid, 4).unwrap();
+    let BudgetOutcome::Degraded(elapsed) = outcome else {
+        panic!("expected fallback, got {:?}", outcome);
+    };
+    assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+    let mut expected = Grid::new(30, 20);
+    Bsp::default().generate(&mut expected, 4);
+    assert_eq!(grid, expected);
+}
+
+#[test]
+fn budget_runs_on_the_calling_thread() {
+    let caller = std::thread::current().id();
+    struct Probe(std::thread::ThreadId);
+    impl Algorithm for Probe {
+        fn generate(&self, grid: &mut Grid<Tile>, _seed: u64) {
+            assert_eq!(std::thread::current().id(), self.0);
+            grid.fill(Tile::Floor);
+        }
+
+        fn name(&self) -> &'static str {
+            "probe"
+        }
+    }
+    let mut grid = Grid::new(10, 10);
+    let budgeted = TimeBudgeted::new(Probe(caller), Duration::from_secs(10));
+    assert!(budgeted.try_generate(&mut grid, 1).is_ok());
+    assert_eq!(grid.count(|t| t.is_floor()), 100);
+
+    // The budget only applies inside the run.
+    let mut grid = Grid::new(60, 40);
+    let run = Dla::new(DlaConfig::default()).generate_timed(&mut grid, 1);
+    assert!(!run.timed_out);
+}
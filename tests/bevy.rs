@@ -0,0 +1,117 @@
+#![cfg(feature = "bevy")]
+
+use bevy_app::{App, TaskPoolPlugin};
+use bevy_ecs::prelude::*;
+use terrain_forge::bevy::{
+    GenerateTerrain, TerrainForgePlugin, TerrainGenerated, TerrainMap, TerrainMarker,
+};
+
+#[test]
+fn plugin_generates_terrain_and_spawns_markers() {
+    let mut app = App::new();
+    app.add_plugins((TaskPoolPlugin::default(), TerrainForgePlugin));
+    let entity = app
+        .world_mut()
+        .spawn(GenerateTerrain::new("bsp", 60, 40, 42).with_semantics("rooms"))
+        .id();
+
+    for _ in 0..1000 {
+        app.update();
+        if app.world().get::<TerrainMap>(entity).is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    let map = app
+        .world()
+        .get::<TerrainMap>(entity)
+        .expect("terrain generated");
+    assert_eq!(map.tiles().count(), 60 * 40);
+    assert_eq!(map.flipped_y(0), 39);
+    let expected_markers = map
+        .semantic
+        .as_ref()
+        .expect("semantic layers")
+        .markers
+        .len();
+
+    let world = app.world_mut();
+    let markers = world
+        .query::<(&TerrainMarker, &ChildOf)>()
+        .iter(world)
+        .filter(|(_, parent)| parent.parent() == entity)
+        .count();
+    assert!(expected_markers > 0);
+    assert_eq!(markers, expected_markers);
+
+    let events = world.resource::<Events<TerrainGenerated>>();
+    assert_eq!(events.iter_current_update_events().count(), 1);
+}
+
+#[cfg(feature = "bevy-tilemap")]
+#[test]
+fn tilemap_plugin_builds_tile_and_marker_layers() {
+    use bevy_ecs_tilemap::prelude::{TilePos, TileStorage, TileTextureIndex, TilemapSize};
+    use terrain_forge::bevy::{TerrainMarkerLayer, TerrainTileIndices, TerrainTilemapPlugin};
+
+    let mut app = App::new();
+    app.add_plugins((
+        TaskPoolPlugin::default(),
+        TerrainForgePlugin,
+        TerrainTilemapPlugin,
+    ))
+    .insert_resource(TerrainTileIndices::default().with_marker("PlayerStart", 5));
+    let entity = app
+        .world_mut()
+        .spawn(GenerateTerrain::new("bsp", 60, 40, 42).with_semantics("rooms"))
+        .id();
+
+    for _ in 0..1000 {
+        app.update();
+        if app.world().get::<TileStorage>(entity).is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    app.update();
+
+    let world = app.world();
+    let map = world.get::<TerrainMap>(entity).expect("terrain generated");
+    assert_eq!(
+        world.get::<TilemapSize>(entity),
+        Some(&TilemapSize::new(60, 40))
+    );
+    let storage = world.get::<TileStorage>(entity).expect("tile storage");
+    for (x, y, tile) in map.tiles() {
+        let pos = TilePos::new(x, map.flipped_y(y));
+        let index = storage
+            .get(&pos)
+            .and_then(|tile| world.get::<TileTextureIndex>(tile))
+            .expect("every cell has a tile");
+        assert_eq!(index.0, if tile.is_floor() { 1 } else { 0 });
+    }
+
+    let starts: Vec<(u32, u32)> = map
+        .semantic
+        .as_ref()
+        .expect("semantic layers")
+        .markers
+        .iter()
+        .filter(|m| m.tag() == "PlayerStart")
+        .map(|m| (m.x, map.flipped_y(m.y)))
+        .collect();
+    assert!(!starts.is_empty());
+    let world = app.world_mut();
+    let (layer, parent) = world
+        .query_filtered::<(Entity, &ChildOf), With<TerrainMarkerLayer>>()
+        .single(world)
+        .expect("one marker layer");
+    assert_eq!(parent.parent(), entity);
+    let storage = world.get::<TileStorage>(layer).expect("marker storage");
+    assert_eq!(storage.iter().flatten().count(), starts.len());
+    for (x, y) in starts {
+        let tile = storage.get(&TilePos::new(x, y)).expect("marker tile");
+        assert_eq!(world.get::<TileTextureIndex>(tile).map(|i| i.0), Some(5));
+    }
+}
@@ -0,0 +1,71 @@
+//! Binary serialization tests
+#![cfg(all(feature = "bincode", feature = "msgpack"))]
+
+use terrain_forge::binary::{self, Codec, Header, Snapshot, FORMAT_VERSION};
+use terrain_forge::pipeline::Pipeline;
+use terrain_forge::{Grid, Rng, SemanticExtractor, Tile};
+
+fn snapshot() -> Snapshot {
+    let mut pipeline = Pipeline::new();
+    pipeline.add_algorithm("bsp", Some(3), None);
+    pipeline.store_grid("base");
+    let mut grid = Grid::new(60, 40);
+    let context = pipeline.execute_seed(&mut grid, 3).unwrap();
+    let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(3));
+    Snapshot::new(grid)
+        .with_seed(3)
+        .with_semantic(semantic)
+        .with_context(context)
+}
+
+#[test]
+fn snapshots_round_trip_with_both_codecs() {
+    let snapshot = snapshot();
+    let json = serde_json::to_vec(&snapshot).unwrap();
+    for codec in [Codec::Bincode, Codec::MessagePack] {
+        let bytes = binary::encode(&snapshot, codec).unwrap();
+        assert_eq!(
+            Header::read(&bytes).unwrap(),
+            Header {
+                version: FORMAT_VERSION,
+                codec
+            }
+        );
+        assert!(
+            bytes.len() < json.len() / 2,
+            "{:?}: {} bytes",
+            codec,
+            bytes.len()
+        );
+
+        let back: Snapshot = binary::decode(&bytes).unwrap();
+        assert_eq!(back.grid, snapshot.grid);
+        assert_eq!(back.seed, Some(3));
+        let (semantic, original) = (back.semantic.unwrap(), snapshot.semantic.as_ref().unwrap());
+        assert_eq!(semantic.regions.len(), original.regions.len());
+        assert_eq!(semantic.markers.len(), original.markers.len());
+        let context = back.context.unwrap();
+        assert_eq!(context.get_grid("base"), Some(&snapshot.grid));
+        assert_eq!(
+            context.execution_history(),
+            snapshot.context.as_ref().unwrap().execution_history()
+        );
+    }
+}
+
+#[test]
+fn invalid_payloads_are_rejected() {
+    let grid: Grid<Tile> = Grid::new(4, 4);
+    let mut bytes = binary::encode(&grid, Codec::Bincode).unwrap();
+    assert!(binary::decode::<Grid<Tile>>(&bytes[..4]).is_err());
+
+    bytes[4] = (FORMAT_VERSION + 1) as u8;
+    let err = binary::decode::<Grid<Tile>>(&bytes).unwrap_err();
+    assert!(err.to_string().contains("version"));
+
+    bytes[0] = b'X';
+    assert!(binary::decode::<Grid<Tile>>(&bytes).is_err());
+
+    let mismatched = r#"{ "width": 3, "height": 3, "cells": ["Wall"] }"#;
+    assert!(serde_json::from_str::<Grid<Tile>>(mismatched).is_err());
+}
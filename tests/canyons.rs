@@ -0,0 +1,82 @@
+//! Canyon carving tests
+
+use terrain_forge::canyons::{self, CanyonConfig, CanyonProfile};
+use terrain_forge::semantic::TerrainFeature;
+use terrain_forge::{Grid, Rng, SemanticExtractor, Tile};
+
+fn plateau(w: usize, h: usize) -> Vec<Vec<f64>> {
+    vec![vec![0.8; w]; h]
+}
+
+#[test]
+fn canyons_lower_terrain_along_branching_walks() {
+    let heights = plateau(120, 80);
+    let config = CanyonConfig::default()
+        .with_branching(0.08, 2)
+        .with_braid(1.0);
+    let carved = canyons::carve(&heights, &config, 5);
+    assert_eq!(carved.heights.len(), 80);
+    assert!(carved.heights.iter().all(|row| row.len() == 120));
+    assert_eq!(carved, canyons::carve(&heights, &config, 5));
+
+    // Never raised, never cut deeper than one main canyon.
+    let cut = |x: usize, y: usize| heights[y][x] - carved.heights[y][x];
+    for y in 0..80 {
+        for x in 0..120 {
+            assert!((-1e-12..=config.depth + 1e-9).contains(&cut(x, y)));
+            if carved.chasm[y][x] {
+                assert!(cut(x, y) > 0.0);
+            }
+        }
+    }
+    assert!(carved.paths.len() > config.count, "expected side ravines");
+    for path in &carved.paths {
+        for pair in path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert!(a.0.abs_diff(b.0) <= 1 && a.1.abs_diff(b.1) <= 1);
+        }
+    }
+    // The centerline is cut to full depth, so main canyons are chasm along
+    // most of their length.
+    let main = &carved.paths[0];
+    let deep = main.iter().filter(|&&(x, y)| carved.chasm[y][x]).count();
+    assert!(deep * 2 > main.len(), "{} of {}", deep, main.len());
+
+    // Braided ravines rejoin their parent, enclosing land: some non-chasm
+    // region is cut off from the map edge by chasm.
+    let mut land = Grid::new(120, 80);
+    land.fill_rect(0, 0, 120, 80, Tile::Floor);
+    let walled = carved.apply(&mut land);
+    assert_eq!(walled, carved.chasm_cells());
+    assert!(land.flood_regions().len() > 2);
+
+    let semantic =
+        SemanticExtractor::for_caves().extract_terrain(&land, &carved.features(), &mut Rng::new(5));
+    let chasm_mask = &semantic.masks.named[TerrainFeature::Chasm.mask_name()];
+    assert_eq!(chasm_mask, &carved.chasm);
+}
+
+#[test]
+fn canyon_profiles_and_sizes() {
+    for profile in [CanyonProfile::V, CanyonProfile::U, CanyonProfile::Slot] {
+        assert_eq!(profile.depth(0.0), 1.0);
+        assert_eq!(profile.depth(1.0), 0.0);
+        assert!(profile.depth(0.5) > 0.0 && profile.depth(0.5) <= 1.0);
+    }
+    assert!(CanyonProfile::Slot.depth(0.7) > CanyonProfile::U.depth(0.7));
+    assert!(CanyonProfile::U.depth(0.7) > CanyonProfile::V.depth(0.7));
+
+    let heights = plateau(80, 60);
+    let narrow = CanyonConfig::default().with_count(1).with_branching(0.0, 0);
+    let wide = narrow.clone().with_size(5.0, 0.3);
+    let a = canyons::carve(&heights, &narrow, 9);
+    let b = canyons::carve(&heights, &wide, 9);
+    assert_eq!(a.paths, b.paths);
+    assert!(b.chasm_cells() > a.chasm_cells());
+    assert!(canyons::carve(&heights, &narrow.with_count(0), 9)
+        .paths
+        .is_empty());
+    assert!(canyons::carve(&[], &CanyonConfig::default(), 9)
+        .paths
+        .is_empty());
+}
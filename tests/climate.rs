@@ -0,0 +1,56 @@
+//! Climate simulation tests
+
+use terrain_forge::climate::{self, ClimateConfig};
+
+/// Sea on the west, a north-south range at x = 30, plains beyond it.
+fn ridge_heights(w: usize, h: usize) -> Vec<Vec<f64>> {
+    (0..h)
+        .map(|_| {
+            (0..w)
+                .map(|x| match x {
+                    0..=9 => 0.1,
+                    _ => 0.5 + 0.4 * (1.0 - (x as f64 - 30.0).abs() / 8.0).max(0.0),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn ridge_casts_rain_shadow_downwind() {
+    let heights = ridge_heights(60, 20);
+    let climate = climate::simulate(&heights, &ClimateConfig::default());
+    let y = 10;
+
+    assert_eq!(climate.moisture(5, y), 1.0);
+    let windward = climate.moisture(27, y);
+    let leeward = climate.moisture(40, y);
+    assert!(windward > leeward * 3.0, "{} vs {}", windward, leeward);
+    assert!(climate.wind(40, y).0 < climate.wind(27, y).0);
+
+    // Flip the wind and the shadow moves to the other side.
+    let westerly = climate::simulate(&heights, &ClimateConfig::default().with_wind(-1.0, 0.0));
+    assert!(westerly.moisture(33, y) > westerly.moisture(20, y));
+    assert!(westerly.wind(20, y).0 < 0.0);
+
+    let diagonal = climate::simulate(&heights, &ClimateConfig::default().with_wind(0.3, -1.0));
+    assert!(diagonal
+        .moisture
+        .iter()
+        .flatten()
+        .all(|m| (0.0..=1.0).contains(m)));
+}
+
+#[test]
+fn temperature_follows_latitude_and_elevation() {
+    let flat = vec![vec![0.5; 10]; 21];
+    let climate = climate::simulate(&flat, &ClimateConfig::default().with_latitude(90.0, -90.0));
+    assert!(climate.temperature(5, 10) > climate.temperature(5, 3));
+    assert!(climate.temperature(5, 3) > climate.temperature(5, 0));
+    assert!((climate.temperature(5, 0) - climate.temperature(5, 20)).abs() < 1e-9);
+
+    let heights = ridge_heights(60, 20);
+    let climate = climate::simulate(&heights, &ClimateConfig::default());
+    assert!(climate.temperature(30, 10) < climate.temperature(45, 10));
+    assert_eq!((climate.width(), climate.height()), (60, 20));
+}
@@ -0,0 +1,533 @@
+//! Config loading tests
+
+use terrain_forge::config::{
+    CombineSourceSpec, Config, ConfigFormat, PipelineOpSpec, PipelineStepSpec,
+};
+use terrain_forge::semantic::MarkerType;
+
+const JSON: &str = r#"{
+    "name": "layered",
+    "width": 48,
+    "height": 32,
+    "pipeline": [
+        { "type": "bsp", "min_room_size": 5 },
+        { "op": "store", "key": "rooms" },
+        "cellular",
+        { "op": "combine", "mode": "union", "source": { "saved": "rooms" } },
+        { "op": "combine", "mode": "difference", "source": { "type": "maze" } }
+    ],
+    "effects": ["remove_dead_ends", { "name": "dilate", "config": { "iterations": 1 } }],
+    "validate": { "density": [0.05, 0.9] },
+    "requirements": { "required_markers": { "loot_tier_2": 1 }, "max_attempts": 3 }
+}"#;
+
+#[test]
+fn json_config_builds_and_generates() {
+    let config = Config::from_json(JSON).unwrap();
+    assert_eq!((config.width, config.height), (48, 32));
+    assert_eq!(config.primary_algorithm(), Some("bsp"));
+    assert!(matches!(
+        &config.pipeline[3],
+        PipelineStepSpec::Op(PipelineOpSpec::Combine {
+            source: CombineSourceSpec::Saved { saved },
+            ..
+        }) if saved == "rooms"
+    ));
+
+    let requirements = config.requirements.as_ref().unwrap();
+    assert_eq!(requirements.attempts(), 3);
+    assert_eq!(
+        requirements.to_requirements().required_markers[&MarkerType::LootTier { tier: 2 }],
+        1
+    );
+
+    let grid = config.generate(7).unwrap();
+    assert_eq!(grid.width(), 48);
+    assert_eq!(grid, config.generate(7).unwrap());
+
+    let round_trip = Config::from_json(&serde_json::to_string(&config).unwrap()).unwrap();
+    assert_eq!(round_trip.generate(7).unwrap(), grid);
+}
+
+#[test]
+fn config_errors_are_reported() {
+    assert!(Config::from_json("{ \"width\": \"wide\" }").is_err());
+    let unknown = Config::from_shorthand("bsp > no_such_algorithm").unwrap();
+    assert!(unknown.build_pipeline().is_err());
+    let bad_mode = Config::from_json(
+        r#"{ "pipeline": ["bsp", { "op": "combine", "mode": "xor", "source": "maze" }] }"#,
+    )
+    .unwrap();
+    assert!(bad_mode.generate(1).is_err());
+    assert_eq!(
+        ConfigFormat::from_extension("level.RON"),
+        Some(ConfigFormat::Ron)
+    );
+    assert_eq!(ConfigFormat::from_extension("level.yaml"), None);
+}
+
+#[test]
+fn shorthand_chains_and_layers() {
+    let chain = Config::from_shorthand("bsp > cellular").unwrap();
+    assert_eq!(chain.pipeline.len(), 2);
+    assert_eq!((chain.width, chain.height), (80, 60));
+
+    let layers = Config::from_shorthand("rooms | maze & cellular").unwrap();
+    let modes: Vec<&str> = layers
+        .pipeline
+        .iter()
+        .filter_map(|step| match step {
+            PipelineStepSpec::Op(PipelineOpSpec::Combine { mode, .. }) => Some(mode.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(modes, ["union", "intersect"]);
+    assert!(layers.generate(3).is_ok());
+
+    assert_eq!(
+        Config::from_shorthand(" maze ")
+            .unwrap()
+            .primary_algorithm(),
+        Some("maze")
+    );
+}
+
+#[test]
+fn shorthand_params_effects_and_groups() {
+    use terrain_forge::config::{parse_shorthand, AlgorithmSpec};
+
+    let steps = parse_shorthand("cellular(0.5, iterations=6) > erode(2)").unwrap();
+    let PipelineStepSpec::Algorithm(AlgorithmSpec::WithParams { type_name, params }) = &steps[0]
+    else {
+        panic!("expected algorithm with params");
+    };
+    assert_eq!(type_name, "cellular");
+    assert_eq!(params["iterations"], 6);
+    assert_eq!(params["initial_floor_chance"], 0.5);
+    let PipelineStepSpec::Op(PipelineOpSpec::Effect { name, params }) = &steps[1] else {
+        panic!("expected effect");
+    };
+    assert_eq!(name, "erode");
+    assert_eq!(params.as_ref().unwrap()["iterations"], 2);
+
+    let grouped = Config::from_shorthand("rooms & (cellular > dilate)").unwrap();
+    let json = serde_json::to_string(&grouped).unwrap();
+    let reloaded = Config::from_json(&json).unwrap();
+    assert_eq!(grouped.generate(5).unwrap(), reloaded.generate(5).unwrap());
+
+    for bad in [
+        "bsp > (cellular",
+        "erode(2, 3)",
+        "bsp | erode",
+        "bsp >",
+        "bsp(x=)",
+    ] {
+        assert!(parse_shorthand(bad).is_err(), "{bad}");
+    }
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_config_matches_json() {
+    let toml = r#"
+        name = "layered"
+        width = 48
+        height = 32
+        effects = ["remove_dead_ends", { name = "dilate", config = { iterations = 1 } }]
+
+        pipeline = [
+            { type = "bsp", min_room_size = 5 },
+            { op = "store", key = "rooms" },
+            "cellular",
+            { op = "combine", mode = "union", source = { saved = "rooms" } },
+            { op = "combine", mode = "difference", source = { type = "maze" } },
+        ]
+
+        [validate]
+        density = [0.05, 0.9]
+    "#;
+    let config = Config::parse(toml, ConfigFormat::Toml).unwrap();
+    let json = Config::from_json(JSON).unwrap();
+    assert_eq!(config.generate(7).unwrap(), json.generate(7).unwrap());
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn ron_config_matches_json() {
+    let ron = r#"(
+        name: Some("layered"),
+        width: 48,
+        height: 32,
+        pipeline: [
+            { "type": "bsp", "min_room_size": 5 },
+            { "op": "store", "key": "rooms" },
+            "cellular",
+            { "op": "combine", "mode": "union", "source": { "saved": "rooms" } },
+            { "op": "combine", "mode": "difference", "source": { "type": "maze" } },
+        ],
+        effects: ["remove_dead_ends", { "name": "dilate", "config": { "iterations": 1 } }],
+    )"#;
+    let config = Config::parse(ron, ConfigFormat::Ron).unwrap();
+    let json = Config::from_json(JSON).unwrap();
+    assert_eq!(config.generate(7).unwrap(), json.generate(7).unwrap());
+}
+
+#[test]
+fn world_children_follow_marker_specs() {
+    use terrain_forge::world;
+
+    let config = Config::from_json(
+        r#"{
+        "width": 48,
+        "height": 32,
+        "pipeline": ["cellular"],
+        "markers": [
+            { "x": 5, "y": 6, "tag": "entrance", "child": "dungeon" },
+            { "x": 20, "y": 9, "tag": "entrance", "child": "dungeon" },
+            { "x": 30, "y": 20, "tag": "town" },
+            { "x": 1, "y": 1, "tag": "ruin" }
+        ],
+        "children": {
+            "dungeon": {
+                "width": 30,
+                "height": 20,
+                "pipeline": ["bsp"],
+                "markers": [{ "x": 3, "y": 3, "tag": "crypt" }],
+                "children": { "crypt": { "width": 16, "height": 16 } }
+            },
+            "town": { "width": 24, "height": 24, "pipeline": ["rooms"] }
+        }
+    }"#,
+    )
+    .unwrap();
+
+    let map = world::generate(&config, 11).unwrap();
+    assert_eq!(map.children.len(), 3);
+    assert!(map.child_at(1, 1).is_none());
+    assert_eq!(map.map_count(), 1 + 3 + 2);
+
+    let first = map.child_at(5, 6).unwrap();
+    let second = map.child_at(20, 9).unwrap();
+    assert_eq!(first.spec, "dungeon");
+    assert_eq!((first.map.grid.width(), first.map.grid.height()), (30, 20));
+    assert_eq!(first.map.seed, world::child_seed(11, 5, 6));
+    assert_ne!(first.map.seed, second.map.seed);
+    assert_eq!(first.map.children[0].map.grid.width(), 16);
+    assert_eq!(map.child_at(30, 20).unwrap().spec, "town");
+
+    // A sub-map regenerates on its own from the derived seed.
+    let alone = world::generate(&config.children["dungeon"], first.map.seed).unwrap();
+    assert_eq!(alone.grid, first.map.grid);
+
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(Config::from_json(&json).unwrap().children.len(), 2);
+}
+
+#[test]
+fn map_bundle_round_trips_and_keeps_unknown_fields() {
+    use terrain_forge::bundle::{MapBundle, BUNDLE_VERSION};
+    use terrain_forge::Grid;
+
+    let config = Config::from_shorthand("cellular").unwrap();
+    let bundle = MapBundle::generate(&config, 9)
+        .unwrap()
+        .with_layer("preview", Grid::new(4, 4))
+        .with_metadata("tags", serde_json::json!(["cave", "small"]));
+    assert_eq!(bundle.version, BUNDLE_VERSION);
+    assert_eq!(bundle.crate_version, env!("CARGO_PKG_VERSION"));
+
+    let path = std::env::temp_dir().join("terrain_forge_bundle_test.json");
+    bundle.save(&path).unwrap();
+    let loaded = MapBundle::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.grid, bundle.grid);
+    assert_eq!(loaded.layers["preview"].width(), 4);
+    assert_eq!(loaded.metadata["tags"][0], "cave");
+    assert!(loaded.semantic.is_some());
+    assert!(loaded.reproduces().unwrap());
+
+    // A file from a newer version: unknown fields survive a load/save cycle.
+    let mut value: serde_json::Value = serde_json::from_str(&bundle.to_json().unwrap()).unwrap();
+    value["version"] = serde_json::json!(BUNDLE_VERSION + 1);
+    value["lighting"] = serde_json::json!({ "ambient": 0.3 });
+    let newer = MapBundle::from_json(&value.to_string()).unwrap();
+    assert!(newer.is_newer());
+    assert!(newer.to_json().unwrap().contains("ambient"));
+
+    // A minimal older file: missing fields take defaults.
+    let old = MapBundle::from_json(
+        r#"{ "grid": { "width": 2, "height": 1, "cells": ["Wall", "Floor"] } }"#,
+    )
+    .unwrap();
+    assert_eq!(old.grid.count(|t| t.is_floor()), 1);
+    assert!(old.seed.is_none() && old.regenerate().is_err());
+}
+
+#[test]
+fn map_bundle_exports_entities_from_config_rules() {
+    use terrain_forge::bundle::MapBundle;
+
+    let config = Config::from_json(
+        r#"{
+            "width": 60, "height": 40,
+            "pipeline": ["rooms"],
+            "entities": { "rules": [
+                { "marker": "loot", "name": "chest", "archetype": "container" },
+                { "marker": "*", "name": "marker", "archetype": "debug" }
+            ] }
+        }"#,
+    )
+    .unwrap();
+    let bundle = MapBundle::generate(&config, 3).unwrap();
+    let markers = &bundle.semantic.as_ref().unwrap().markers;
+    assert!(!markers.is_empty());
+    assert_eq!(bundle.entities.len(), markers.len());
+    for (entity, marker) in bundle.entities.iter().zip(markers) {
+        assert_eq!((entity.x, entity.y), (marker.x, marker.y));
+        let expected = if marker.marker_type.category() == "loot" {
+            "chest"
+        } else {
+            "marker"
+        };
+        assert_eq!(entity.name, expected);
+    }
+
+    let loaded = MapBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+    assert_eq!(loaded.entities, bundle.entities);
+    assert!(Config::from_shorthand("rooms").unwrap().entities.is_none());
+}
+
+#[test]
+fn map_bundle_rolls_spawns_with_its_seed() {
+    use terrain_forge::bundle::MapBundle;
+
+    let config = Config::from_json(
+        r#"{
+            "width": 60, "height": 40,
+            "pipeline": ["rooms"],
+            "spawns": { "tables": [
+                { "marker": "*", "entries": [
+                    { "name": "rat", "weight": 2.0, "max_count": 3 },
+                    { "name": "bat" }
+                ] }
+            ] }
+        }"#,
+    )
+    .unwrap();
+    let bundle = MapBundle::generate(&config, 3).unwrap();
+    let markers = &bundle.semantic.as_ref().unwrap().markers;
+    assert!(!markers.is_empty());
+    assert_eq!(bundle.spawns.len(), markers.len());
+    let tables = config.spawns.as_ref().unwrap();
+    assert_eq!(bundle.spawns, tables.sample(markers, 3));
+    assert_eq!(
+        bundle.spawns,
+        MapBundle::generate(&config, 3).unwrap().spawns
+    );
+
+    let loaded = MapBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+    assert_eq!(loaded.spawns, bundle.spawns);
+    assert!(Config::from_shorthand("rooms").unwrap().spawns.is_none());
+}
+
+#[test]
+fn cave_entrances_link_to_child_maps() {
+    use terrain_forge::world::{self, CaveEntranceConfig, ENTRANCE_KIND_KEY, FACING_KEY};
+    use terrain_forge::{ConnectivityGraph, Masks, SemanticLayers};
+
+    // Sea in the west; a plateau rises east of x = 24, and right off the
+    // shore in the top rows.
+    let heights: Vec<Vec<f64>> = (0..20)
+        .map(|y| {
+            (0..40)
+                .map(|x| match x {
+                    0..=4 => 0.1,
+                    _ if x >= 25 || (y < 4 && x >= 6) => 0.8,
+                    _ => 0.5,
+                })
+                .collect()
+        })
+        .collect();
+    let entrances = world::cave_entrances(
+        &heights,
+        &CaveEntranceConfig::default()
+            .with_min_spacing(4)
+            .with_max_entrances(20)
+            .with_child("cave"),
+    );
+    let kind_at = |x: u32, kind: &str| {
+        entrances
+            .iter()
+            .any(|m| m.x == x && m.metadata[ENTRANCE_KIND_KEY] == kind)
+    };
+    assert!(kind_at(5, "coast"));
+    assert!(kind_at(24, "cliff"));
+    assert!(entrances
+        .iter()
+        .filter(|m| m.x == 24)
+        .all(|m| m.metadata[FACING_KEY] == "west"));
+    for (i, a) in entrances.iter().enumerate() {
+        for b in &entrances[i + 1..] {
+            assert!(a.x.abs_diff(b.x).max(a.y.abs_diff(b.y)) >= 4);
+        }
+    }
+    let inland_only =
+        world::cave_entrances(&heights, &CaveEntranceConfig::default().with_coastal(false));
+    assert!(inland_only
+        .iter()
+        .all(|m| m.metadata[ENTRANCE_KIND_KEY] == "cliff"));
+
+    let config = Config::from_json(
+        r#"{
+        "width": 40,
+        "height": 20,
+        "pipeline": ["cellular"],
+        "children": { "cave": { "width": 20, "height": 16, "pipeline": ["cellular"] } }
+    }"#,
+    )
+    .unwrap();
+    let semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: entrances.clone(),
+        masks: Masks::new(40, 20),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let children = world::generate_children(&config, 3, &semantic).unwrap();
+    assert_eq!(children.len(), entrances.len());
+    assert!(children.iter().all(|c| c.spec == "cave"));
+}
+
+#[test]
+fn semantic_profiles_load_blend_and_build() {
+    use terrain_forge::config::ProfileSpec;
+    use terrain_forge::semantic::SemanticConfig;
+    use terrain_forge::{Rng, SemanticExtractor};
+
+    let config = Config::from_json(
+        r#"{
+            "pipeline": ["cellular"],
+            "semantic": { "blend": [
+                { "profile": "rooms", "weight": 3 },
+                { "profile": { "max_markers_per_region": 8, "marker_types": [["Shrine", 1.0]] } }
+            ] }
+        }"#,
+    )
+    .unwrap();
+    let profile = config.semantic.as_ref().unwrap().resolve().unwrap();
+    let rooms = SemanticConfig::room_system();
+    assert_eq!(profile.size_thresholds, rooms.size_thresholds);
+    assert_eq!(profile.max_markers_per_region, 5);
+    let weight = |kind: &str| profile.marker_types.iter().find(|m| m.0 == kind).unwrap().1;
+    assert_eq!(weight("Shrine"), 0.25);
+    assert_eq!(weight("Exit"), 0.75);
+
+    // The profile overrides the algorithm guess.
+    let grid = config.generate(3).unwrap();
+    let semantic = config.extractor().extract(&grid, &mut Rng::new(3));
+    let expected = SemanticExtractor::new(profile).extract(&grid, &mut Rng::new(3));
+    assert_eq!(semantic.markers.len(), expected.markers.len());
+
+    assert!(Config::from_json(r#"{ "semantic": "castles" }"#).is_err());
+    assert!(
+        ProfileSpec::from_json(r#"{ "blend": [{ "profile": "caves", "weight": 0 }] }"#)
+            .unwrap()
+            .resolve()
+            .is_err()
+    );
+
+    let custom = SemanticConfig::preset("Cave")
+        .unwrap()
+        .with_threshold(200, "Cavern")
+        .with_threshold(25, "Passage")
+        .with_marker("Enemy", 0.9)
+        .without_marker("Crystal");
+    let kinds: Vec<_> = custom
+        .size_thresholds
+        .iter()
+        .map(|t| t.1.as_str())
+        .collect();
+    assert_eq!(kinds, ["Cavern", "Chamber", "Passage", "Alcove", "Crevice"]);
+    assert_eq!(custom.marker_types.len(), 4);
+    assert!(custom.marker_types.contains(&("Enemy".to_string(), 0.9)));
+
+    #[cfg(feature = "toml")]
+    {
+        let toml = r#"
+            max_markers_per_region = 6
+            size_thresholds = [[40, "Big"], [0, "Small"]]
+            marker_types = [["Exit", 1.0]]
+            connectivity_type = "EightConnected"
+        "#;
+        let profile = ProfileSpec::parse(toml, ConfigFormat::Toml)
+            .unwrap()
+            .resolve()
+            .unwrap();
+        assert_eq!(profile.max_markers_per_region, 6);
+        assert_eq!(profile.size_thresholds[0], (40, "Big".to_string()));
+    }
+}
+
+#[test]
+fn config_manifest_records_effects_and_validation() {
+    let config = Config::from_json(JSON).unwrap();
+    let (grid, manifest) = config.generate_with_manifest(4).unwrap();
+    assert_eq!(grid, config.generate(4).unwrap());
+
+    assert_eq!(manifest.seed, Some(4));
+    assert_eq!(manifest.steps[0].name, "bsp");
+    assert_eq!(manifest.steps[0].params["min_room_size"], 5);
+    assert!(manifest.steps[0].params.contains_key("max_depth"));
+    let dilate = manifest.step("dilate").unwrap();
+    assert_eq!(dilate.params["iterations"], 1);
+
+    assert_eq!(manifest.constraints.len(), 1);
+    assert_eq!(manifest.constraints[0].id, "grid_density");
+    assert_eq!(manifest.passed(), manifest.constraints[0].passed);
+}
+
+#[test]
+fn generate_map_retries_until_constraints_pass() {
+    use terrain_forge::{generate_map, Error, MapConfig};
+
+    let config = MapConfig::from_json(
+        r#"{
+            "width": 50,
+            "height": 40,
+            "seed": 100,
+            "pipeline": [{ "type": "cellular", "iterations": 4 }],
+            "effects": ["remove_dead_ends"],
+            "semantic": "caves",
+            "validate": { "density": [0.2, 0.5] },
+            "requirements": { "required_markers": { "PlayerStart": 1 } },
+            "entities": { "rules": [{ "marker": "PlayerStart", "name": "player", "archetype": "actor" }] },
+            "retry": { "max_attempts": 30, "seed_step": 7 }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.attempts(), 30);
+    let bundle = generate_map(&config).unwrap();
+    let attempts = bundle.metadata["attempts"].as_u64().unwrap();
+    assert_eq!(bundle.seed, Some(100 + 7 * (attempts - 1)));
+    assert!(bundle.reproduces().unwrap());
+    assert!(bundle.entities.iter().any(|e| e.name == "player"));
+    let floor = bundle.grid.count(|t| t.is_floor()) as f64 / (50.0 * 40.0);
+    assert!((0.2..=0.5).contains(&floor), "{}", floor);
+
+    let mut impossible = config.clone();
+    impossible.retry = Some(serde_json::from_str(r#"{ "max_attempts": 3 }"#).unwrap());
+    impossible.validate = serde_json::from_str(r#"{ "density": [0.99, 1.0] }"#).unwrap();
+    match generate_map(&impossible) {
+        Err(Error::ConstraintUnsatisfied { attempts, report }) => {
+            assert_eq!(attempts, 3);
+            assert!(!report.passed);
+        }
+        other => panic!("unexpected {:?}", other.map(|b| b.seed)),
+    }
+
+    let mut broken = config;
+    broken.pipeline = serde_json::from_str(r#"["no_such_algorithm"]"#).unwrap();
+    assert!(matches!(
+        generate_map(&broken),
+        Err(Error::UnknownAlgorithm(_))
+    ));
+}
@@ -0,0 +1,89 @@
+//! Biome border dithering tests
+
+use terrain_forge::dither::{self, DitherConfig};
+
+/// Class 0 west of `split`, class 1 east of it, with a strip of class 2
+/// along the top row.
+fn halves(w: usize, h: usize, split: usize) -> Vec<Vec<usize>> {
+    (0..h)
+        .map(|y| {
+            (0..w)
+                .map(|x| match (y, x >= split) {
+                    (0, _) => 2,
+                    (_, east) => usize::from(east),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn thresholds_are_uniform_and_evenly_spread() {
+    let config = DitherConfig::default().with_tile_size(16);
+    let screen = dither::thresholds(32, 32, &config, 5);
+    let values: Vec<f64> = screen.iter().flatten().copied().collect();
+    assert!(values.iter().all(|&t| (0.0..1.0).contains(&t)));
+    for p in [0.1, 0.25, 0.5, 0.9] {
+        let below = values.iter().filter(|&&t| t < p).count() as f64 / values.len() as f64;
+        assert!((below - p).abs() < 0.01, "{} below {}", below, p);
+    }
+    // The screen tiles.
+    assert_eq!(screen[3][4], screen[19][20]);
+
+    // Blue noise: the lowest tenth of thresholds never touch each other.
+    let lit = |x: usize, y: usize| screen[y % 32][x % 32] < 0.1;
+    for y in 0..32 {
+        for x in 0..32 {
+            if lit(x, y) {
+                assert!(!lit(x + 1, y) && !lit(x, y + 1), "clump at {},{}", x, y);
+            }
+        }
+    }
+
+    // Green noise keeps 2x2 blocks together.
+    let green = dither::thresholds(32, 32, &config.clone().with_cluster(2), 5);
+    let mut clumped = 0;
+    for y in (0..32).step_by(2) {
+        for x in (0..32).step_by(2) {
+            let block = [
+                green[y][x],
+                green[y][x + 1],
+                green[y + 1][x],
+                green[y + 1][x + 1],
+            ];
+            let (lo, hi) = block
+                .iter()
+                .fold((1.0f64, 0.0f64), |(lo, hi), &t| (lo.min(t), hi.max(t)));
+            clumped += usize::from(hi - lo < 1.0 / 256.0);
+        }
+    }
+    assert_eq!(clumped, 256);
+}
+
+#[test]
+fn blend_interleaves_only_along_the_shared_boundary() {
+    let classes = halves(48, 24, 24);
+    let config = DitherConfig::default().with_width(4);
+    let blended = dither::blend(&classes, &0, &1, &config, 11);
+    assert_eq!(blended, dither::blend(&classes, &0, &1, &config, 11));
+    assert_ne!(blended, dither::blend(&classes, &0, &1, &config, 12));
+
+    // The third class and everything beyond the transition stay put.
+    assert_eq!(blended[0], classes[0]);
+    for row in &blended[1..] {
+        assert!(row[..20].iter().all(|&c| c == 0));
+        assert!(row[28..].iter().all(|&c| c == 1));
+    }
+    // Swaps thin out away from the boundary.
+    let swapped = |x: usize| (1..24).filter(|&y| blended[y][x] != classes[y][x]).count();
+    assert!(swapped(23) > swapped(20));
+    assert!(swapped(24) > swapped(27));
+    assert!(swapped(23) + swapped(24) > 0);
+
+    assert_eq!(
+        dither::blend(&classes, &0, &1, &config.clone().with_width(0), 11),
+        classes
+    );
+    // Classes that never touch don't blend.
+    assert_eq!(dither::blend(&classes, &0, &3, &config, 11), classes);
+}
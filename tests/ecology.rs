@@ -0,0 +1,94 @@
+//! Cave ecology placement tests
+
+use terrain_forge::ecology::{self, CaveFeature, EcologyConfig, Habitat};
+use terrain_forge::semantic::{
+    meta, ConnectivityGraph, Marker, MarkerType, Masks, Region, SemanticLayers,
+};
+use terrain_forge::{Grid, Rng, Tile};
+
+/// A 20x20 chamber with a dead-end tunnel off its east side and a pool in
+/// its south-west corner, all one region.
+fn cave() -> (Grid<Tile>, SemanticLayers) {
+    let mut grid = Grid::new(40, 24);
+    grid.fill_rect(2, 2, 20, 20, Tile::Floor);
+    grid.fill_rect(22, 10, 12, 1, Tile::Floor);
+    let mut region = Region::new(1, "cave");
+    for (x, y, tile) in grid.iter() {
+        if tile.is_floor() {
+            region.cells.push((x as u32, y as u32));
+        }
+    }
+    let mut masks = Masks::new(40, 24);
+    for y in 18..22 {
+        for x in 2..6 {
+            masks.set_named("water", x, y, true);
+        }
+    }
+    let semantic = SemanticLayers {
+        regions: vec![region],
+        markers: Vec::new(),
+        masks,
+        connectivity: ConnectivityGraph::new(),
+    };
+    (grid, semantic)
+}
+
+#[test]
+fn lairs_go_on_their_features() {
+    let (grid, mut semantic) = cave();
+    let placed = ecology::place(
+        &grid,
+        &mut semantic,
+        &EcologyConfig::default(),
+        &mut Rng::new(3),
+    );
+    assert_eq!(placed, 3);
+
+    let find = |tag: &str| semantic.markers.iter().find(|m| m.tag() == tag).unwrap();
+    let nest = find("nest");
+    assert!((5..=18).contains(&nest.x) && (5..=18).contains(&nest.y));
+    let den = find("den");
+    assert_eq!((den.x, den.y), (33, 10));
+    let pool = find("spawning_pool");
+    assert!(pool.x == 6 || pool.y == 17, "({}, {})", pool.x, pool.y);
+
+    for marker in &semantic.markers {
+        assert_eq!(marker.region_id, Some(1));
+        let population: usize = marker.metadata[meta::ECOLOGY_POPULATION].parse().unwrap();
+        assert!((1..=8).contains(&population));
+    }
+    assert_eq!(den.metadata[meta::ECOLOGY_FEATURE], "alcove");
+}
+
+#[test]
+fn lairs_respect_exclusion_and_safe_zones() {
+    let (grid, mut semantic) = cave();
+    let nests = Habitat::new("nest", CaveFeature::Shore)
+        .with_exclusion_radius(3.0)
+        .with_per_region(50)
+        .with_population(0.5, 2, 40);
+    let config = EcologyConfig::new(vec![nests]);
+    ecology::place(&grid, &mut semantic, &config, &mut Rng::new(5));
+
+    let nests: Vec<&Marker> = semantic.markers.iter().collect();
+    assert!(nests.len() > 1);
+    for (i, a) in nests.iter().enumerate() {
+        assert_eq!(a.metadata[meta::ECOLOGY_POPULATION], "40");
+        for b in &nests[i + 1..] {
+            let d = ((a.x as f32 - b.x as f32).powi(2) + (a.y as f32 - b.y as f32).powi(2)).sqrt();
+            assert!(d >= 3.0);
+        }
+    }
+
+    let (grid, mut semantic) = cave();
+    semantic
+        .markers
+        .push(Marker::new(10, 10, MarkerType::SafeZone).with_region(1));
+    let placed = ecology::place(
+        &grid,
+        &mut semantic,
+        &EcologyConfig::default(),
+        &mut Rng::new(5),
+    );
+    assert_eq!(placed, 0);
+}
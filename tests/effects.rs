@@ -98,3 +98,404 @@ fn effects_dont_panic_on_empty_grid() {
     effects::mirror(&mut grid, true, true);
     effects::invert(&mut grid);
 }
+
+#[test]
+fn water_table_floods_caverns_and_keeps_dry_path() {
+    use terrain_forge::effects::{WaterConfig, WaterLevel, WaterTable, WATER_MASK};
+    use terrain_forge::spatial::{shortest_path, PathfindingConstraints};
+    use terrain_forge::{algorithms, Rng, SemanticExtractor};
+
+    let mut grid = Grid::new(80, 50);
+    algorithms::get("cellular").unwrap().generate(&mut grid, 21);
+    let cave = grid
+        .flood_regions()
+        .into_iter()
+        .max_by_key(Vec::len)
+        .unwrap();
+    let (start, end) = (cave[0], cave[cave.len() - 1]);
+
+    let config = WaterConfig::default()
+        .with_level(WaterLevel::Global)
+        .with_fill(0.9);
+    let mut table = WaterTable::generate(&grid, &config, 21);
+    assert_eq!(table.levels.len(), 1);
+    let floor = grid.count(|t| t.is_floor());
+    assert!(table.count() > floor / 2);
+    assert!(grid
+        .iter()
+        .all(|(x, y, t)| t.is_floor() || !table.is_water(x, y)));
+
+    let flooded = table.count();
+    assert!(table.ensure_dry_path(&grid, start, end));
+    assert!(table.count() < flooded, "a ford was drained");
+    let dry = table.dry_grid(&grid);
+    assert!(shortest_path(&dry, start, end, &PathfindingConstraints::default()).is_some());
+    assert!(!table.ensure_dry_path(&grid, start, (0, 0)));
+
+    let per_cavern = WaterTable::generate(&grid, &WaterConfig::default(), 21);
+    let big = grid
+        .flood_regions()
+        .iter()
+        .filter(|c| c.len() >= 20)
+        .count();
+    assert_eq!(per_cavern.levels.len(), big);
+    assert!(per_cavern.count() < table.count());
+
+    let mut semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(21));
+    table.apply(&mut semantic);
+    let mask = semantic.masks.named(WATER_MASK).unwrap();
+    for (x, y, _) in grid.iter() {
+        assert_eq!(mask[y][x], table.is_water(x, y));
+        if table.is_water(x, y) {
+            assert!(!semantic.masks.walkable[y][x]);
+        }
+    }
+}
+
+#[test]
+fn bridges_cross_water_and_chasms() {
+    use terrain_forge::effects::{
+        mark_bridges, place_bridges, BridgeConfig, WaterTable, BRIDGE_MASK,
+    };
+    use terrain_forge::{ops, Rng, SemanticExtractor};
+
+    // One cave split by a three-cell river.
+    let (w, h) = (30, 12);
+    let mut grid = Grid::new(w, h);
+    grid.fill_rect(1, 1, w - 2, h - 2, Tile::Floor);
+    let mut table = WaterTable {
+        elevation: vec![vec![0.5; w]; h],
+        water: (0..h)
+            .map(|_| (0..w).map(|x| (14..17).contains(&x)).collect())
+            .collect(),
+        levels: vec![0.5],
+    };
+    let water_only = BridgeConfig::default().with_cross_walls(false);
+
+    let mut unchanged = grid.clone();
+    let short = water_only.clone().with_max_length(2);
+    assert!(place_bridges(&mut unchanged, Some(&mut table.clone()), &short).is_empty());
+
+    let bridges = place_bridges(&mut grid, Some(&mut table), &water_only);
+    assert_eq!(bridges.len(), 1, "one bridge joins two banks");
+    let bridge = &bridges[0];
+    assert_eq!(bridge.length(), 3);
+    assert_eq!(bridge.start.1, bridge.end.1);
+    assert!(bridge.cells.iter().all(|&(x, y)| !table.is_water(x, y)));
+    let dry = table.dry_grid(&grid);
+    assert_eq!(dry.flood_regions().len(), 1);
+
+    let mut semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(1));
+    mark_bridges(&mut semantic, &bridges);
+    let (x, y) = bridge.cells[1];
+    assert!(semantic.masks.named(BRIDGE_MASK).unwrap()[y][x]);
+
+    // Three rooms across wall chasms; `max_bridges` caps the crossings.
+    let mut rooms = Grid::new(40, 10);
+    for x in [1, 15, 29] {
+        rooms.fill_rect(x, 2, 10, 6, Tile::Floor);
+    }
+    let mut capped = rooms.clone();
+    place_bridges(
+        &mut capped,
+        None,
+        &BridgeConfig::default().with_max_bridges(1),
+    );
+    assert_eq!(capped.flood_regions().len(), 2);
+
+    ops::effect("bridges", &mut rooms, None, None).unwrap();
+    assert_eq!(rooms.flood_regions().len(), 1);
+}
+
+#[test]
+fn thicken_walls_keeps_regions_connected() {
+    let mut grid = Grid::new(60, 40);
+    terrain_forge::ops::generate("cellular", &mut grid, Some(7), None).unwrap();
+    let regions = grid.flood_regions();
+    let before = grid.count(|t| t.is_floor());
+
+    let walled = effects::thicken_walls(&mut grid, 3);
+    assert!(walled > 0);
+    assert_eq!(grid.count(|t| t.is_floor()), before - walled);
+    let after = grid.flood_regions();
+    assert_eq!(after.len(), regions.len());
+    // Every old region still holds exactly one new region.
+    for region in &regions {
+        let kept: Vec<_> = after
+            .iter()
+            .filter(|r| r.iter().any(|c| region.contains(c)))
+            .collect();
+        assert_eq!(kept.len(), 1);
+    }
+
+    // A one-wide corridor between two rooms and the room it leads into stay open.
+    let mut grid = Grid::new(30, 10);
+    grid.fill_rect(1, 1, 8, 8, Tile::Floor);
+    grid.fill_rect(21, 1, 8, 8, Tile::Floor);
+    grid.fill_rect(9, 4, 12, 1, Tile::Floor);
+    effects::thicken_walls(&mut grid, 10);
+    assert_eq!(grid.flood_regions().len(), 1);
+    assert!(grid[(15, 4)].is_floor());
+    assert!(grid[(9, 4)].is_floor() && grid[(20, 4)].is_floor());
+}
+
+#[test]
+fn roads_ford_narrow_rivers_and_bridge_wide_ones() {
+    use effects::{route_road, CrossingKind, RoadConfig};
+
+    // A north-south river at x = 20..20+width across an open field.
+    let field = |width: usize| {
+        let mut grid = Grid::new(50, 20);
+        grid.fill_rect(0, 0, 50, 20, Tile::Floor);
+        let water: Vec<Vec<bool>> = (0..20)
+            .map(|_| (0..50).map(|x| (20..20 + width).contains(&x)).collect())
+            .collect();
+        (grid, water)
+    };
+    let config = RoadConfig::default();
+
+    let (grid, water) = field(2);
+    let road = route_road(&grid, &water, (5, 10), (45, 10), &config).unwrap();
+    assert_eq!(road.crossings.len(), 1);
+    let ford = &road.crossings[0];
+    assert_eq!(ford.kind, CrossingKind::Ford);
+    assert_eq!(ford.cells, [(20, 10), (21, 10)]);
+    assert_eq!((ford.start, ford.end), ((19, 10), (22, 10)));
+    for pair in road.path.windows(2) {
+        assert_eq!(
+            pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1),
+            1
+        );
+    }
+
+    // Too deep to ford in the middle: bridge it, within the span limit.
+    let (mut grid, mut water) = field(5);
+    let road = route_road(&grid, &water, (5, 10), (45, 10), &config).unwrap();
+    let bridges: Vec<_> = road.crossings_of(CrossingKind::Bridge).collect();
+    assert_eq!(bridges.len(), 1);
+    assert_eq!(bridges[0].cells.len(), 5);
+    assert!(road.crossings_of(CrossingKind::Ford).next().is_none());
+    road.build_bridges(&mut grid, &mut water);
+    assert!(bridges[0].cells.iter().all(|&(x, y)| !water[y][x]));
+
+    let (grid, water) = field(8);
+    assert!(route_road(&grid, &water, (5, 10), (45, 10), &config).is_none());
+    let long = config.with_max_span(8);
+    assert!(route_road(&grid, &water, (5, 10), (45, 10), &long).is_some());
+    assert!(route_road(&grid, &water, (20, 10), (45, 10), &long).is_none());
+}
+
+#[test]
+fn obstacles_break_up_open_areas_without_narrowing_passages() {
+    use effects::{place_obstacles, ObstacleConfig, ObstacleStyle};
+
+    // A big hall, a small room, and a 3-wide corridor between them.
+    let mut grid = Grid::new(60, 30);
+    grid.fill_rect(1, 1, 30, 28, Tile::Floor);
+    grid.fill_rect(45, 10, 6, 6, Tile::Floor);
+    grid.fill_rect(31, 12, 14, 3, Tile::Floor);
+    let before = grid.clone();
+
+    let config = ObstacleConfig::default();
+    let walled = place_obstacles(&mut grid, &config, 3);
+    assert!(walled >= 10, "{}", walled);
+    assert_eq!(grid.flood_regions().len(), 1);
+    assert_eq!(
+        grid.count(|t| t.is_floor()),
+        before.count(|t| t.is_floor()) - walled
+    );
+    for (x, y, tile) in grid.iter() {
+        if tile.is_floor() || !before[(x, y)].is_floor() {
+            continue;
+        }
+        // New walls sit in the hall, with two clear cells to any old wall.
+        assert!((1..31).contains(&x), "({}, {})", x, y);
+        for (dx, dy) in (-2i32..=2).flat_map(|dy| (-2i32..=2).map(move |dx| (dx, dy))) {
+            let (nx, ny) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+            assert!(before[(nx, ny)].is_floor());
+        }
+    }
+
+    let mut walls = before.clone();
+    let segments = ObstacleConfig::default()
+        .with_style(ObstacleStyle::BrokenWalls)
+        .with_spacing(7)
+        .with_density(1.0);
+    assert!(place_obstacles(&mut walls, &segments, 3) > 0);
+    assert_eq!(walls.flood_regions().len(), 1);
+    assert_eq!(walls, {
+        let mut again = before.clone();
+        place_obstacles(&mut again, &segments, 3);
+        again
+    });
+
+    let mut small = before.clone();
+    place_obstacles(&mut small, &config.with_min_region(2000), 3);
+    assert_eq!(small, before);
+}
+
+#[test]
+fn budgeted_spanning_reuses_floor_and_respects_the_budget() {
+    use terrain_forge::effects::{connect_regions_budgeted, label_regions, SpanningConfig};
+    use terrain_forge::Rng;
+
+    // A big room, then two small ones further east in a row, 6 and 4 walls
+    // apart.
+    let rooms = || {
+        let mut grid = Grid::new(60, 20);
+        grid.fill_rect(2, 2, 20, 16, Tile::Floor);
+        grid.fill_rect(28, 8, 6, 4, Tile::Floor);
+        grid.fill_rect(38, 8, 6, 4, Tile::Floor);
+        grid
+    };
+    let config = SpanningConfig::default().with_extra_connection_chance(0.0);
+
+    let mut grid = rooms();
+    let report = connect_regions_budgeted(&mut grid, &config, &mut Rng::new(1));
+    assert!(report.connected());
+    assert_eq!(report.corridors.len(), 2);
+    assert_eq!(report.carved, 10);
+    assert_eq!(label_regions(&grid).1, 1);
+    // The far room branches off the middle one, not the big room.
+    let (labels, _) = label_regions(&rooms());
+    let middle = labels[8 * 60 + 28];
+    assert_eq!(report.corridors[1].regions.0, middle);
+    assert!(report.corridors[1].cells.iter().all(|&(x, _)| x > 33));
+
+    let mut grid = rooms();
+    let report = connect_regions_budgeted(
+        &mut grid,
+        &config.clone().with_corridor_budget(8),
+        &mut Rng::new(1),
+    );
+    assert_eq!((report.corridors.len(), report.carved), (1, 6));
+    assert_eq!(report.unconnected, [labels[8 * 60 + 38]]);
+
+    let mut grid = rooms();
+    let report = connect_regions_budgeted(
+        &mut grid,
+        &config.with_max_corridor_length(5),
+        &mut Rng::new(1),
+    );
+    assert!(report.corridors.is_empty());
+    assert_eq!(report.unconnected.len(), 2);
+    assert_eq!(grid, rooms());
+}
+
+#[test]
+fn shrink_to_area_keeps_regions_and_markers() {
+    use terrain_forge::semantic::{ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+
+    let mut grid = Grid::new(80, 60);
+    terrain_forge::ops::generate("cellular", &mut grid, Some(8), None).unwrap();
+    let regions = grid.flood_regions().len();
+    let floor = grid.count(|t| t.is_floor());
+    let markers: Vec<Marker> = grid
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .step_by(97)
+        .map(|(x, y, _)| Marker::new(x as u32, y as u32, MarkerType::Treasure))
+        .collect();
+    let semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers,
+        masks: Masks::new(80, 60),
+        connectivity: ConnectivityGraph::new(),
+    };
+
+    let target = floor * 6 / 10;
+    assert_eq!(
+        effects::shrink_to_area(&mut grid, target, Some(&semantic)),
+        target
+    );
+    assert_eq!(grid.count(|t| t.is_floor()), target);
+    assert_eq!(grid.flood_regions().len(), regions);
+    assert!(semantic
+        .markers
+        .iter()
+        .all(|m| grid[(m.x as usize, m.y as usize)].is_floor()));
+
+    // Regions shrink down to a single cell but are never split or erased.
+    let mut grid = Grid::new(40, 20);
+    grid.fill_rect(2, 2, 10, 10, Tile::Floor);
+    grid.fill_rect(28, 2, 10, 10, Tile::Floor);
+    grid.fill_rect(12, 6, 16, 1, Tile::Floor);
+    grid.fill_rect(2, 15, 6, 3, Tile::Floor);
+    assert_eq!(effects::shrink_to_area(&mut grid, 0, None), 2);
+    assert_eq!(grid.flood_regions().len(), 2);
+}
+
+#[test]
+fn cavify_keeps_a_perfect_maze_perfect() {
+    use effects::{cavify, CavifyConfig};
+
+    // Walls connected through diagonals; a perfect maze has one such
+    // component (no wall islands, so no loops in the floor).
+    fn wall_components(grid: &Grid<Tile>) -> usize {
+        let (w, h) = (grid.width() as i32, grid.height() as i32);
+        let mut seen = vec![false; (w * h) as usize];
+        let mut count = 0;
+        for (x, y, tile) in grid.iter() {
+            if tile.is_floor() || seen[y * w as usize + x] {
+                continue;
+            }
+            count += 1;
+            let mut stack = vec![(x as i32, y as i32)];
+            seen[y * w as usize + x] = true;
+            while let Some((cx, cy)) = stack.pop() {
+                for (dx, dy) in (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))) {
+                    let (nx, ny) = (cx + dx, cy + dy);
+                    if nx < 0 || ny < 0 || nx >= w || ny >= h {
+                        continue;
+                    }
+                    let idx = (ny * w + nx) as usize;
+                    if !seen[idx] && !grid[(nx as usize, ny as usize)].is_floor() {
+                        seen[idx] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    let mut maze = Grid::new(61, 41);
+    terrain_forge::ops::generate("maze", &mut maze, Some(5), None).unwrap();
+    assert_eq!(maze.flood_regions().len(), 1);
+    assert_eq!(wall_components(&maze), 1);
+
+    let mut cave = maze.clone();
+    let changed = cavify(&mut cave, &CavifyConfig::default(), 5);
+    assert!(changed > 100, "{}", changed);
+    assert!(cave.count(|t| t.is_floor()) > maze.count(|t| t.is_floor()));
+    assert_eq!(cave.flood_regions().len(), 1);
+    assert_eq!(wall_components(&cave), 1);
+    // Corridors widened somewhere into open 2x2 blocks.
+    let open_block = |x: usize, y: usize| {
+        [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]
+            .iter()
+            .all(|&c| cave[c].is_floor())
+    };
+    assert!((0..40).any(|y| (0..60).any(|x| open_block(x, y))));
+    for x in 0..61 {
+        assert!(!cave[(x, 0)].is_floor() && !cave[(x, 40)].is_floor());
+    }
+
+    let mut again = maze.clone();
+    cavify(&mut again, &CavifyConfig::default(), 5);
+    assert_eq!(again, cave);
+
+    // Heavy settings still keep the topology.
+    let mut heavy = maze.clone();
+    let config = CavifyConfig::default()
+        .with_widen(0.9, 4)
+        .with_chambers(10, 5);
+    cavify(&mut heavy, &config, 9);
+    assert_eq!(heavy.flood_regions().len(), 1);
+    assert_eq!(wall_components(&heavy), 1);
+
+    let mut via_op = maze.clone();
+    terrain_forge::ops::effect("cavify", &mut via_op, None, None).unwrap();
+    assert_eq!(via_op.flood_regions().len(), 1);
+    assert_eq!(wall_components(&via_op), 1);
+}
@@ -43,3 +43,381 @@ fn constraint_set_evaluates_all() {
 
     assert_eq!(set.evaluate(&ctx).results.len(), 3);
 }
+
+#[test]
+fn constraint_set_evaluates_seeds_in_order() {
+    use terrain_forge::constraints::*;
+
+    let mut set = ConstraintSet::new();
+    set.push(DensityConstraint::new(0.3, 0.6));
+    let generate = |seed| {
+        let mut grid = Grid::new(40, 30);
+        terrain_forge::ops::generate("cellular", &mut grid, Some(seed), None).unwrap();
+        grid
+    };
+
+    let seeds: Vec<u64> = (100..116).collect();
+    let reports = set.evaluate_seeds(&seeds, generate);
+    assert_eq!(reports.len(), seeds.len());
+    for (&seed, (reported, report)) in seeds.iter().zip(&reports) {
+        assert_eq!(*reported, seed);
+        let grid = generate(seed);
+        assert_eq!(
+            report.passed,
+            set.evaluate(&ConstraintContext::new(&grid)).passed
+        );
+    }
+}
+
+#[test]
+fn lock_constraint_reports_key_behind_its_door() {
+    use terrain_forge::constraints::*;
+    use terrain_forge::semantic::{ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+    use terrain_forge::Tile;
+
+    // Two rooms joined by a one-cell doorway at (10, 5); the far room and
+    // the doorway make 50 cells.
+    let mut grid = Grid::new(20, 11);
+    grid.fill_rect(2, 2, 8, 7, Tile::Floor);
+    grid.fill_rect(11, 2, 7, 7, Tile::Floor);
+    grid.set(10, 5, Tile::Floor);
+
+    let locked =
+        |x, y, tag: &str| Marker::with_tag(x, y, tag.to_string()).with_metadata(LOCK_KEY, "red");
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(3, 3, MarkerType::Spawn),
+            locked(10, 5, DOOR_TAG),
+            locked(15, 5, KEY_TAG),
+        ],
+        masks: Masks::new(20, 11),
+        connectivity: ConnectivityGraph::new(),
+    };
+
+    let issues = validate_locks(&grid, &semantic);
+    assert!(issues.contains(&LockIssue::KeyBehindDoor {
+        lock: "red".to_string(),
+        door: (10, 5),
+        key: (15, 5),
+    }));
+    assert!(issues
+        .iter()
+        .any(|i| matches!(i, LockIssue::Unreachable { size: 50, .. })));
+
+    let mut ctx = ConstraintContext::new(&grid);
+    ctx.semantic = Some(&semantic);
+    let result = LockConstraint.evaluate(&ctx);
+    assert!(!result.passed);
+    assert!(result.details["issue_0"].contains("(15, 5)"));
+
+    // Moving the key in front of the door fixes everything.
+    semantic.markers[2] = locked(5, 5, KEY_TAG);
+    assert!(validate_locks(&grid, &semantic).is_empty());
+}
+
+#[test]
+fn full_report_collects_findings_with_severities() {
+    use terrain_forge::semantic::{ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+    use terrain_forge::validate::{full_report, Severity, ValidationProfile};
+    use terrain_forge::Tile;
+
+    // Main room, a walled-off 2x2 pocket, and a gap in the border.
+    let mut grid = Grid::new(20, 12);
+    grid.fill_rect(1, 1, 10, 10, Tile::Floor);
+    grid.fill_rect(15, 4, 2, 2, Tile::Floor);
+    grid.set(0, 5, Tile::Floor);
+
+    let semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(2, 2, MarkerType::Spawn),
+            Marker::new(15, 4, MarkerType::Treasure),
+            Marker::new(13, 1, MarkerType::Exit),
+            Marker::new(3, 3, MarkerType::Treasure),
+            Marker::new(3, 3, MarkerType::Treasure),
+        ],
+        masks: Masks::new(20, 12),
+        connectivity: ConnectivityGraph::new(),
+    };
+
+    let report = full_report(&grid, Some(&semantic), &ValidationProfile::default());
+    assert!(!report.passed());
+    assert_eq!(
+        report.checks[..5],
+        ["connectivity", "border", "pockets", "markers", "regions"]
+    );
+    let pocket = report.findings_for("pockets").next().unwrap();
+    assert_eq!(pocket.severity, Severity::Error, "pocket holds a marker");
+    assert_eq!(
+        report.findings_for("border").next().unwrap().position,
+        Some((0, 5))
+    );
+    let markers: Vec<_> = report.findings_for("markers").collect();
+    assert!(markers
+        .iter()
+        .any(|f| f.message.contains("on a wall") && f.position == Some((13, 1))));
+    assert!(markers
+        .iter()
+        .any(|f| f.severity == Severity::Warning && f.position == Some((3, 3))));
+
+    // Without semantics only grid checks run; lenient allows the open border.
+    let grid_only = full_report(&grid, None, &ValidationProfile::lenient());
+    assert_eq!(grid_only.checks.len(), 3);
+    assert!(grid_only.passed());
+    assert_eq!(grid_only.worst(), Some(Severity::Warning));
+}
+
+#[test]
+fn arenas_are_symmetric_and_balanced() {
+    use terrain_forge::algorithms::CellularAutomata;
+    use terrain_forge::arena::{ArenaConfig, ArenaGenerator};
+    use terrain_forge::constraints::{spawn_imbalance, PLAYER_KEY, RESOURCE_TAG};
+
+    let arena = ArenaGenerator::new(CellularAutomata::default(), ArenaConfig::default())
+        .generate(60, 40, 8)
+        .unwrap();
+    let grid = &arena.grid;
+    for (x, y, tile) in grid.iter() {
+        assert_eq!(*tile, grid[(59 - x, 39 - y)]);
+    }
+    assert_eq!(grid.flood_regions().len(), 1);
+    assert_eq!(arena.spawns.len(), 2);
+    let (a, b) = (arena.spawns[0], arena.spawns[1]);
+    assert_eq!((a.0 + b.0, a.1 + b.1), (59, 39));
+    let resources: Vec<_> = arena
+        .semantic
+        .markers
+        .iter()
+        .filter(|m| m.tag() == RESOURCE_TAG)
+        .collect();
+    assert_eq!(resources.len(), 4);
+    assert!(resources
+        .iter()
+        .all(|m| m.metadata.contains_key(PLAYER_KEY)));
+    assert_eq!(spawn_imbalance(grid, &arena.semantic), Some(0));
+
+    let square = ArenaGenerator::new(
+        CellularAutomata::default(),
+        ArenaConfig::default().with_folds(4),
+    )
+    .generate(48, 48, 3)
+    .unwrap();
+    for (x, y, tile) in square.grid.iter() {
+        assert_eq!(*tile, square.grid[(47 - y, x)]);
+    }
+    assert!(ArenaGenerator::new(
+        CellularAutomata::default(),
+        ArenaConfig::default().with_folds(4)
+    )
+    .generate(48, 30, 3)
+    .is_err());
+}
+
+#[test]
+fn spawn_safety_clears_area_without_breaking_border() {
+    use terrain_forge::constraints::*;
+    use terrain_forge::semantic::{ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+    use terrain_forge::Tile;
+
+    // Spawn in the corner of a room, a pillar and two hazards next to it.
+    let mut grid = Grid::new(20, 12);
+    grid.fill_rect(1, 1, 12, 10, Tile::Floor);
+    grid.set(2, 2, Tile::Wall);
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(1, 1, MarkerType::Spawn),
+            Marker::with_tag(2, 3, "Enemy".to_string()),
+            Marker::with_tag(3, 1, "spike_trap".to_string()),
+            Marker::new(2, 1, MarkerType::Treasure),
+        ],
+        masks: Masks::new(20, 12),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let config = SpawnSafetyConfig::default();
+
+    // The border cuts the radius-3 disc off, so the area grows inward.
+    let area = spawn_safe_area(&grid, (1, 1), 3);
+    assert_eq!(area.len(), 29);
+    assert!(area.iter().all(|&(x, y)| x >= 1 && y >= 1));
+
+    let issues = spawn_safety_issues(&grid, &semantic, &config);
+    assert_eq!(issues.len(), 3);
+    assert!(issues.contains(&SpawnSafetyIssue::Blocked {
+        spawn: (1, 1),
+        cells: 1
+    }));
+    let mut ctx = ConstraintContext::new(&grid);
+    ctx.semantic = Some(&semantic);
+    assert!(
+        !SpawnSafetyConstraint {
+            config: config.clone()
+        }
+        .evaluate(&ctx)
+        .passed
+    );
+
+    let repair = enforce_spawn_safety(&mut grid, &mut semantic, &config);
+    assert_eq!(repair.carved, vec![(2, 2)]);
+    assert!(semantic.masks.walkable[2][2]);
+    assert_eq!(repair.moved.len(), 2);
+    assert_eq!(repair.removed, 0);
+    for &(_, (x, y)) in &repair.moved {
+        assert!(grid[(x as usize, y as usize)].is_floor());
+        assert!(!area.contains(&(x as usize, y as usize)));
+    }
+    assert_eq!((semantic.markers[3].x, semantic.markers[3].y), (2, 1));
+    assert!(spawn_safety_issues(&grid, &semantic, &config).is_empty());
+    assert!(validate_border(&grid));
+}
+
+#[test]
+fn danger_field_finds_stealth_route() {
+    use terrain_forge::constraints::*;
+    use terrain_forge::semantic::{ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+    use terrain_forge::Tile;
+
+    // Two rooms joined by a guarded corridor (y = 2) and a quiet one (y = 9).
+    let mut grid = Grid::new(30, 12);
+    grid.fill_rect(1, 1, 4, 10, Tile::Floor);
+    grid.fill_rect(25, 1, 4, 10, Tile::Floor);
+    grid.fill_rect(5, 2, 20, 1, Tile::Floor);
+    grid.fill_rect(5, 9, 20, 1, Tile::Floor);
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(2, 5, MarkerType::Spawn),
+            Marker::new(27, 5, MarkerType::Exit),
+            Marker::with_tag(14, 2, "Enemy".to_string()),
+        ],
+        masks: Masks::new(30, 12),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let config = DangerConfig::default();
+
+    let field = danger_field(&grid, &semantic, &config);
+    assert_eq!(field.get(14, 2), 1.0);
+    assert!((field.get(17, 2) - (1.0 - 3.0 / 7.0)).abs() < 1e-6);
+    assert!((field.get(20, 2) - 1.0 / 7.0).abs() < 1e-6);
+    assert_eq!(field.get(21, 2), 0.0);
+    assert_eq!(field.get(14, 9), 0.0, "walls block danger");
+
+    let (_, route) = safest_route(&grid, &semantic, &config).unwrap();
+    assert_eq!(route.max_danger, 0.0);
+    assert!(route.path.contains(&(14, 9)));
+    assert_eq!(route.path.first(), Some(&(2, 5)));
+    assert_eq!(route.path.last(), Some(&(27, 5)));
+
+    let stealth = StealthRouteConstraint {
+        config: config.clone(),
+        max_danger: 0.0,
+    };
+    let mut ctx = ConstraintContext::new(&grid);
+    ctx.semantic = Some(&semantic);
+    assert!(stealth.evaluate(&ctx).passed);
+
+    // A tougher encounter on the quiet corridor makes the guarded one safer.
+    semantic.markers.push(Marker::new(
+        14,
+        9,
+        MarkerType::EncounterZone { difficulty: 3 },
+    ));
+    let (field, route) = safest_route(&grid, &semantic, &config).unwrap();
+    assert_eq!(field.get(14, 9), 3.0);
+    assert_eq!(route.max_danger, 1.0);
+    assert!(route.path.contains(&(14, 2)));
+    let mut ctx = ConstraintContext::new(&grid);
+    ctx.semantic = Some(&semantic);
+    let result = stealth.evaluate(&ctx);
+    assert!(!result.passed);
+    assert_eq!(result.details["max_danger"], "1.00");
+}
+
+#[test]
+fn safest_route_reaches_recipe_exits() {
+    use terrain_forge::constraints::{safest_route, DangerConfig};
+    use terrain_forge::recipes;
+
+    // Recipe extractors place `"Exit"`-tagged custom markers.
+    let bundle = recipes::get("classic_dungeon")
+        .unwrap()
+        .generate(11)
+        .unwrap();
+    let semantic = bundle.semantic.as_ref().unwrap();
+    assert!(semantic.markers.iter().any(|m| m.tag() == "Exit"));
+
+    let (_, route) = safest_route(&bundle.grid, semantic, &DangerConfig::default()).unwrap();
+    let end = *route.path.last().unwrap();
+    assert!(semantic
+        .exits()
+        .any(|m| (m.x as usize, m.y as usize) == end));
+}
+
+#[test]
+fn unreachable_pockets_are_measured_and_repaired() {
+    use terrain_forge::constraints::*;
+    use terrain_forge::semantic::{ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+    use terrain_forge::Tile;
+
+    // A 10x10 room with the spawn, a 4x4 pocket two walls away, and a
+    // 2x2 pocket holding loot.
+    let mut grid = Grid::new(30, 14);
+    grid.fill_rect(1, 1, 10, 10, Tile::Floor);
+    grid.fill_rect(13, 2, 4, 4, Tile::Floor);
+    grid.fill_rect(20, 8, 2, 2, Tile::Floor);
+    let layers = |grid: &Grid<Tile>| SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(2, 2, MarkerType::Spawn),
+            Marker::new(20, 8, MarkerType::Treasure),
+        ],
+        masks: Masks::from_tiles(grid),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let semantic = layers(&grid);
+
+    let pockets = unreachable_pockets(&grid, &semantic).unwrap();
+    assert_eq!(pockets.len(), 2);
+    assert_eq!(unreachable_fraction(&grid, &semantic), Some(20.0 / 120.0));
+
+    let mut ctx = ConstraintContext::new(&grid);
+    ctx.semantic = Some(&semantic);
+    let loose = ReachabilityConstraint {
+        max_unreachable: 0.2,
+    };
+    let strict = ReachabilityConstraint {
+        max_unreachable: 0.1,
+    };
+    assert!(loose.evaluate(&ctx).passed);
+    let result = strict.evaluate(&ctx);
+    assert!(!result.passed);
+    assert_eq!(result.details["pockets"], "2");
+
+    let (mut sealed, mut sealed_semantic) = (grid.clone(), semantic.clone());
+    let repair = repair_pockets(&mut sealed, &mut sealed_semantic, PocketFix::Seal);
+    assert_eq!(repair.sealed.len(), 20);
+    assert_eq!(repair.removed, 1);
+    assert!(repair.carved.is_empty());
+    assert_eq!(sealed.count(|t| t.is_floor()), 100);
+    assert!(!sealed_semantic.masks.walkable[3][14]);
+
+    let (mut joined, mut joined_semantic) = (grid.clone(), semantic);
+    let repair = repair_pockets(&mut joined, &mut joined_semantic, PocketFix::Connect);
+    assert_eq!(joined.flood_regions().len(), 1);
+    assert_eq!(joined_semantic.markers.len(), 2);
+    assert!(repair.sealed.is_empty());
+    // Two walls to the big pocket, then six from its corner to the small one.
+    assert_eq!(repair.carved.len(), 8);
+    assert!(repair
+        .carved
+        .iter()
+        .all(|&(x, y)| joined_semantic.masks.walkable[y][x]));
+    assert!(validate_border(&joined));
+
+    let no_spawn = SemanticLayers {
+        markers: Vec::new(),
+        ..layers(&grid)
+    };
+    assert_eq!(unreachable_fraction(&grid, &no_spawn), None);
+}
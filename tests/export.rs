@@ -0,0 +1,312 @@
+//! Export format tests — Tiled, Godot, heightmaps, meshes, SVG.
+
+use terrain_forge::export::{tiled, TiledConfig, TiledTileset};
+use terrain_forge::{algorithms, Grid, Marker, Rng, SemanticExtractor, Tile};
+
+fn bsp_with_semantics() -> (Grid<Tile>, terrain_forge::SemanticLayers) {
+    let mut grid = Grid::new(40, 30);
+    algorithms::get("bsp").unwrap().generate(&mut grid, 7);
+    let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(7));
+    (grid, semantic)
+}
+
+#[test]
+fn tiled_tmj_contains_tiles_regions_and_markers() {
+    let (grid, mut semantic) = bsp_with_semantics();
+    let mut marker = Marker::with_tag(3, 4, "chest".to_string());
+    marker.metadata.insert("loot".into(), "gold & gems".into());
+    semantic.markers.push(marker);
+
+    let config = TiledConfig::default().with_tile_size(8, 8).with_gids(5, 6);
+    let tmj: serde_json::Value =
+        serde_json::from_str(&tiled::to_tmj(&grid, Some(&semantic), &config)).unwrap();
+
+    assert_eq!(tmj["width"], 40);
+    assert_eq!(tmj["tilewidth"], 8);
+    let layers = tmj["layers"].as_array().unwrap();
+    assert_eq!(layers.len(), 3);
+
+    let data = layers[0]["data"].as_array().unwrap();
+    assert_eq!(data.len(), 40 * 30);
+    for (i, (_, _, tile)) in grid.iter().enumerate() {
+        assert_eq!(data[i], if tile.is_floor() { 5 } else { 6 });
+    }
+
+    let regions = layers[1]["objects"].as_array().unwrap();
+    assert_eq!(regions.len(), semantic.regions.len());
+    let markers = layers[2]["objects"].as_array().unwrap();
+    let chest = markers.iter().find(|m| m["name"] == "chest").unwrap();
+    assert_eq!(chest["x"], 28.0);
+    assert_eq!(chest["point"], true);
+    assert!(chest["properties"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|p| p["name"] == "loot" && p["value"] == "gold & gems"));
+
+    // Object ids are unique across groups.
+    let mut ids: Vec<u64> = regions
+        .iter()
+        .chain(markers)
+        .map(|o| o["id"].as_u64().unwrap())
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), regions.len() + markers.len());
+    assert_eq!(tmj["nextobjectid"], ids.len() as u64 + 1);
+}
+
+#[test]
+fn tiled_tmx_and_region_gid_overrides() {
+    let (grid, semantic) = bsp_with_semantics();
+    let kind = semantic.regions[0].kind.clone();
+    let config = TiledConfig::default()
+        .with_region_floor_gid(kind, 9)
+        .with_tileset(TiledTileset {
+            source: Some("dungeon.tsx".into()),
+            ..Default::default()
+        });
+
+    let tmx = tiled::to_tmx(&grid, Some(&semantic), &config);
+    assert!(tmx.starts_with("<?xml"));
+    assert!(tmx.contains(r#"<tileset firstgid="1" source="dungeon.tsx"/>"#));
+    assert!(tmx.contains(r#"<data encoding="csv">"#));
+    assert!(tmx.contains("<point/>"));
+    assert!(tmx.trim_end().ends_with("</map>"));
+
+    let csv: Vec<u32> = tmx
+        .split(r#"<data encoding="csv">"#)
+        .nth(1)
+        .unwrap()
+        .split("</data>")
+        .next()
+        .unwrap()
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().unwrap())
+        .collect();
+    assert_eq!(csv.len(), 40 * 30);
+    assert!(csv.contains(&9));
+
+    let no_semantic = tiled::to_tmx(&grid, None, &TiledConfig::default());
+    assert!(!no_semantic.contains("<objectgroup"));
+}
+
+#[cfg(feature = "godot")]
+#[test]
+fn godot_scene_has_tilemap_collision_and_markers() {
+    use terrain_forge::export::{godot, GodotConfig};
+
+    let (grid, mut semantic) = bsp_with_semantics();
+    let mut marker = Marker::with_tag(3, 4, "boss.door".to_string());
+    marker
+        .metadata
+        .insert("key id".into(), "red \"skull\"".into());
+    semantic.markers.push(marker);
+    // Block one floor cell via the walkable mask.
+    let (bx, by, _) = grid.iter().find(|(_, _, t)| t.is_floor()).unwrap();
+    semantic.masks.walkable[by][bx] = false;
+
+    let scene = godot::to_tscn(&grid, Some(&semantic), &GodotConfig::default());
+    assert!(scene.starts_with("[gd_scene load_steps=4 format=3]"));
+    assert!(scene.contains("1:0/0/physics_layer_0/polygon_0/points"));
+    assert!(scene.contains("2:0/0/physics_layer_0/polygon_0/points"));
+    assert!(scene.contains(r#"[node name="TileMap" type="TileMap" parent="."]"#));
+
+    let data: Vec<i64> = scene
+        .lines()
+        .find_map(|l| l.strip_prefix("layer_0/tile_data = PackedInt32Array("))
+        .unwrap()
+        .trim_end_matches(')')
+        .split(", ")
+        .map(|v| v.parse().unwrap())
+        .collect();
+    assert_eq!(data.len(), 40 * 30 * 3);
+    let cell = |x: usize, y: usize| {
+        let i = (y * 40 + x) * 3;
+        assert_eq!(data[i], ((y as i64) << 16) | x as i64);
+        (data[i + 1] >> 16, data[i + 2])
+    };
+    assert_eq!(cell(bx, by), (2, 0));
+    assert_eq!(cell(0, 0), (1, 0));
+
+    assert!(scene.contains(r#"type="Marker2D" parent="Markers""#));
+    assert!(scene.contains("[node name=\"bossdoor_"));
+    assert!(scene.contains(r#"metadata/key_id = "red \"skull\"""#));
+    assert!(scene.contains("position = Vector2(56, 72)"));
+}
+
+#[test]
+fn heightmap_exports_full_16_bit_range() {
+    use terrain_forge::export::{heightmap, ByteOrder, HeightmapConfig};
+
+    let values = vec![vec![0.0, 0.25], vec![0.5, 1.0]];
+    let config = HeightmapConfig::default();
+    assert_eq!(
+        heightmap::to_u16(&values, &config),
+        vec![0, 16384, 32768, 65535]
+    );
+
+    let raw = heightmap::to_raw16(&values, &config);
+    assert_eq!(&raw[6..8], &[0xFF, 0xFF]);
+    assert_eq!(&raw[2..4], &16384u16.to_le_bytes());
+    let big = heightmap::to_raw16(&values, &config.clone().with_byte_order(ByteOrder::Big));
+    assert_eq!(&big[2..4], &16384u16.to_be_bytes());
+    let flipped = heightmap::to_u16(&values, &config.clone().with_flip_y(true));
+    assert_eq!(flipped, vec![32768, 65535, 0, 16384]);
+    let ranged = heightmap::to_u16(&values, &config.clone().with_range(0.0, 0.5));
+    assert_eq!(ranged[3], 65535);
+
+    let png = heightmap::to_png16(&values, &config).unwrap();
+    let decoder = png::Decoder::new(std::io::Cursor::new(png));
+    let mut reader = decoder.read_info().unwrap();
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    assert_eq!((info.width, info.height), (2, 2));
+    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+    assert_eq!(&buf[2..4], &16384u16.to_be_bytes());
+}
+
+#[cfg(feature = "exr")]
+#[test]
+fn heightmap_exr_keeps_float_values() {
+    use terrain_forge::algorithms::DiamondSquare;
+    use terrain_forge::export::{heightmap, HeightmapConfig};
+
+    let values = DiamondSquare::default().heightmap(33, 17, 3);
+    let exr = heightmap::to_exr(&values, &HeightmapConfig::default()).unwrap();
+    assert_eq!(&exr[..4], &[0x76, 0x2f, 0x31, 0x01]);
+}
+
+#[test]
+fn heightmap_mesh_decimates_and_faces_up() {
+    use terrain_forge::export::{mesh, Mesh, MeshConfig};
+
+    let values: Vec<Vec<f64>> = (0..10)
+        .map(|y| (0..10).map(|x| (x + y) as f64 / 18.0).collect())
+        .collect();
+    let full = Mesh::from_heightmap(&values, &MeshConfig::default());
+    assert_eq!(full.vertex_count(), 100);
+    assert_eq!(full.triangle_count(), 9 * 9 * 2);
+
+    let coarse = Mesh::from_heightmap(&values, &MeshConfig::default().with_step(4));
+    // Samples 0, 4, 8 plus the kept edge at 9.
+    assert_eq!(coarse.vertex_count(), 16);
+    assert!(coarse.normals.iter().all(|n| n[1] > 0.0));
+    let [a, b, c] = [0, 1, 2].map(|i| coarse.positions[coarse.indices[i] as usize]);
+    let (u, v) = ([b[0] - a[0], b[2] - a[2]], [c[0] - a[0], c[2] - a[2]]);
+    assert!(u[1] * v[0] - u[0] * v[1] > 0.0, "triangle should face +Y");
+
+    let obj = mesh::to_obj(&coarse);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 16);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 18);
+
+    let gltf: serde_json::Value = serde_json::from_str(&mesh::to_gltf(&coarse)).unwrap();
+    assert_eq!(gltf["accessors"][0]["count"], 16);
+    assert!(gltf["buffers"][0]["uri"]
+        .as_str()
+        .unwrap()
+        .starts_with("data:application/octet-stream;base64,"));
+}
+
+#[test]
+fn tile_mesh_extrudes_walls_around_floors() {
+    use terrain_forge::export::{mesh, Mesh, MeshConfig};
+
+    let mut grid = Grid::new(3, 3);
+    grid.set(1, 1, Tile::Floor);
+    let config = MeshConfig::default().with_wall_caps(false);
+    let room = Mesh::from_tiles(&grid, &config);
+    // One floor quad plus four walls.
+    assert_eq!(room.triangle_count(), 10);
+    assert!(room.positions.iter().any(|p| p[1] == 2.0));
+
+    let capped = Mesh::from_tiles(&grid, &MeshConfig::default());
+    assert_eq!(capped.triangle_count(), 10 + 8 * 2);
+
+    let glb = mesh::to_glb(&capped);
+    assert_eq!(&glb[..4], b"glTF");
+    assert_eq!(
+        u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize,
+        glb.len()
+    );
+    assert_eq!(glb.len() % 4, 0);
+}
+
+#[test]
+fn svg_outlines_enclose_exactly_the_floors() {
+    use terrain_forge::export::{svg, SvgConfig};
+
+    let mut grid = Grid::new(40, 30);
+    algorithms::get("cellular").unwrap().generate(&mut grid, 5);
+    let contours = svg::outlines(&grid);
+    assert!(!contours.is_empty());
+
+    // Even-odd ray casting from each cell center.
+    for (x, y, tile) in grid.iter() {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        let mut inside = false;
+        for contour in &contours {
+            for i in 0..contour.len() {
+                let (ax, ay) = contour[i];
+                let (bx, by) = contour[(i + 1) % contour.len()];
+                if (ay > py) != (by > py) && px < ax + (py - ay) * (bx - ax) / (by - ay) {
+                    inside = !inside;
+                }
+            }
+        }
+        assert_eq!(inside, tile.is_floor(), "cell ({}, {})", x, y);
+    }
+
+    let semantic = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(5));
+    let doc = svg::to_svg(
+        &grid,
+        Some(&semantic),
+        &SvgConfig::default().with_cell_size(4.0),
+    );
+    assert!(doc.contains(r#"width="160" height="120""#));
+    assert!(doc.contains(r#"<g id="regions""#));
+    assert_eq!(doc.matches("<text").count(), semantic.markers.len());
+
+    let bare = svg::to_svg(
+        &grid,
+        Some(&semantic),
+        &SvgConfig::default().with_layers(false, false, false),
+    );
+    assert!(!bare.contains("<g id="));
+}
+
+#[test]
+fn collision_mask_packs_bits_with_stride_and_rle() {
+    use terrain_forge::export::{collision, BitOrder, CollisionConfig, CollisionMask};
+
+    let mut grid = Grid::new(20, 3);
+    for x in 0..20 {
+        grid.set(x, 1, Tile::Floor);
+    }
+    grid.set(0, 0, Tile::Floor);
+
+    let config = CollisionConfig::default().with_row_align(4);
+    let mask = CollisionMask::from_grid(&grid, &config);
+    assert_eq!((mask.stride, mask.bits.len()), (4, 12));
+    assert_eq!(&mask.bits[..4], &[0b0000_0001, 0, 0, 0]);
+    assert_eq!(&mask.bits[4..8], &[0xff, 0xff, 0x0f, 0]);
+    assert!(mask.is_walkable(19, 1) && !mask.is_walkable(19, 2));
+    assert_eq!(mask.runs(), vec![0, 1, 19, 20, 20]);
+
+    let msb = CollisionMask::from_grid(
+        &grid,
+        &CollisionConfig::default().with_bit_order(BitOrder::Msb0),
+    );
+    assert_eq!(msb.bits[0], 0b1000_0000);
+
+    let solid = CollisionConfig::default().with_solid(true).with_rle(true);
+    let bytes = collision::to_bytes(&grid, &solid);
+    let back = CollisionMask::from_bytes(&bytes).unwrap();
+    assert!(back.solid && back.get(1, 0) && !back.get(0, 0));
+    assert!(back.is_walkable(0, 0));
+    assert_eq!(back, CollisionMask::from_grid(&grid, &solid));
+
+    assert!(CollisionMask::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(CollisionMask::from_bytes(b"nope").is_err());
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+use terrain_forge::ffi::*;
+
+#[test]
+fn c_api_generates_reads_tiles_and_markers() {
+    unsafe {
+        let grid = tf_grid_new(40, 30);
+        let name = CString::new("bsp").unwrap();
+        let params = CString::new(r#"{"min_room_size": 5}"#).unwrap();
+        assert_eq!(tf_generate(grid, name.as_ptr(), params.as_ptr(), 7), 0);
+        assert_eq!((tf_grid_width(grid), tf_grid_height(grid)), (40, 30));
+
+        let mut tiles = vec![0u8; 40 * 30];
+        assert_eq!(
+            tf_grid_copy_tiles(grid, tiles.as_mut_ptr(), tiles.len()),
+            1200
+        );
+        assert!(tiles.contains(&1));
+        assert_eq!(tf_grid_get(grid, -1, 0), -1);
+
+        assert_eq!(tf_marker_count(grid), 0);
+        let profile = CString::new("rooms").unwrap();
+        assert_eq!(tf_extract_semantic(grid, profile.as_ptr(), 7), 0);
+        let count = tf_marker_count(grid);
+        assert!(count > 0);
+        let mut marker = TfMarker {
+            x: 0,
+            y: 0,
+            weight: 0.0,
+            region_id: 0,
+            tag: ptr::null(),
+        };
+        assert_eq!(tf_marker_get(grid, 0, &mut marker), 0);
+        assert!(!CStr::from_ptr(marker.tag).to_str().unwrap().is_empty());
+        assert!(marker.x < 40 && marker.y < 30);
+
+        assert_eq!(tf_marker_get(grid, count, &mut marker), -1);
+        let bad = CString::new("nope").unwrap();
+        assert_eq!(tf_generate(grid, bad.as_ptr(), ptr::null(), 1), -1);
+        assert!(!tf_last_error().is_null());
+        tf_grid_free(grid);
+    }
+}
+
+#[test]
+fn c_api_runs_scripts() {
+    let script = CString::new(
+        r#"{"width": 30, "height": 20, "seed": 3, "ops": [
+            {"op": "generate", "name": "cellular"},
+            {"op": "extract", "profile": "caves"}
+        ]}"#,
+    )
+    .unwrap();
+    unsafe {
+        let grid = tf_run_script(script.as_ptr());
+        assert!(!grid.is_null());
+        assert_eq!(tf_grid_width(grid), 30);
+        tf_grid_free(grid);
+
+        let broken = CString::new("{").unwrap();
+        assert!(tf_run_script(broken.as_ptr()).is_null());
+        let message = CStr::from_ptr(tf_last_error()).to_str().unwrap();
+        assert!(message.contains("Invalid op script"));
+    }
+}
+
+#[test]
+fn c_api_reports_panics_instead_of_unwinding() {
+    // Width * height overflows the allocation size, which panics in Rust.
+    let grid = tf_grid_new(u32::MAX, u32::MAX);
+    assert!(grid.is_null());
+    let message = unsafe { CStr::from_ptr(tf_last_error()) };
+    assert!(message.to_str().unwrap().starts_with("panicked:"));
+}
+
+#[test]
+fn c_api_effects_clear_stale_semantics() {
+    unsafe {
+        let grid = tf_grid_new(40, 30);
+        let name = CString::new("bsp").unwrap();
+        assert_eq!(tf_generate(grid, name.as_ptr(), ptr::null(), 7), 0);
+        assert_eq!(tf_extract_semantic(grid, ptr::null(), 7), 0);
+        assert!(tf_marker_count(grid) > 0);
+
+        let bad = CString::new("nope").unwrap();
+        assert_eq!(tf_effect(grid, bad.as_ptr(), ptr::null()), -1);
+        assert!(tf_marker_count(grid) > 0);
+
+        let erode = CString::new("erode").unwrap();
+        assert_eq!(tf_effect(grid, erode.as_ptr(), ptr::null()), 0);
+        assert_eq!(tf_marker_count(grid), 0);
+        tf_grid_free(grid);
+    }
+}
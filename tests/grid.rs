@@ -1,4 +1,4 @@
-//! Grid utility tests — flood_fill, flood_regions, neighbors, line_points.
+//! Grid utility tests — flood_fill, flood_regions, neighbors, line_points, text rendering.
 
 use terrain_forge::{Grid, Tile};
 
@@ -50,3 +50,173 @@ fn line_points_includes_endpoints() {
     assert_eq!(pts.last(), Some(&(5, 0)));
     assert_eq!(pts.len(), 6);
 }
+
+#[test]
+fn text_render_overlays_and_heatmap_shading() {
+    use terrain_forge::render::{Heatmap, TextRender};
+    use terrain_forge::semantic::{ConnectivityGraph, Masks};
+    use terrain_forge::spatial::{distance_field, DistanceMetric};
+    use terrain_forge::{Marker, SemanticLayers};
+
+    let mut grid = Grid::new(4, 2);
+    grid.fill_rect(1, 0, 2, 2, Tile::Floor);
+    assert_eq!(TextRender::new(&grid).to_string(), "#..#\n#..#");
+    assert_eq!(
+        TextRender::new(&grid).with_chars('X', ' ').to_string(),
+        "X  X\nX  X"
+    );
+
+    let semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![Marker::with_tag(2, 1, "loot_slot".to_string())],
+        masks: Masks::new(4, 2),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let view = TextRender::new(&grid).with_semantic(&semantic);
+    assert_eq!(view.to_string(), "#..#\n#.$#");
+    let colored = view.with_color(true).to_string();
+    assert!(colored.contains("\x1b[1;93m$"));
+    assert!(colored.ends_with("\x1b[0m"));
+
+    let heat = Heatmap::from_rows(&[vec![0.0, 1.0, 2.0], vec![f64::INFINITY, 2.0, 0.0]]);
+    assert_eq!(heat.range(), Some((0.0, 2.0)));
+    assert_eq!(heat.to_string(), " +@\n @ ");
+    let field = distance_field(&grid, DistanceMetric::Manhattan);
+    assert_eq!(Heatmap::from(&field).to_string().lines().count(), 2);
+}
+
+#[test]
+fn legend_sets_marker_and_region_glyphs() {
+    use terrain_forge::render::{self, Legend, LegendEntry, TextRender};
+    use terrain_forge::semantic::{ConnectivityGraph, MarkerType, Masks, Region};
+    use terrain_forge::{Marker, SemanticLayers};
+
+    let mut grid = Grid::new(5, 2);
+    grid.fill_rect(1, 0, 3, 2, Tile::Floor);
+    let mut hall = Region::new(1, "hall");
+    hall.add_cell(3, 0);
+    hall.add_cell(3, 1);
+    let semantic = SemanticLayers {
+        regions: vec![hall],
+        markers: vec![
+            Marker::new(1, 0, MarkerType::LootTier { tier: 2 }),
+            Marker::with_tag(2, 1, "spawn".to_string()),
+        ],
+        masks: Masks::new(5, 2),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let legend = Legend::empty()
+        .with_marker(
+            "loot_tier",
+            LegendEntry::new("💰").with_color([255, 215, 0]),
+        )
+        .with_region("hall", LegendEntry::new(","))
+        .with_unknown(LegendEntry::new("@"));
+    assert_eq!(legend.marker("loot_tier_2").glyph, "💰");
+    assert_eq!(legend.marker("loot_tierx").glyph, "@");
+
+    let view = TextRender::new(&grid)
+        .with_semantic(&semantic)
+        .with_legend(&legend);
+    assert_eq!(view.to_string(), "#💰.,#\n#.@,#");
+    assert!(view
+        .with_color(true)
+        .to_string()
+        .contains("\x1b[38;2;255;215;0m💰"));
+    assert_eq!(
+        render::render_text_with_semantic(&grid, &semantic, &legend),
+        "#💰.,#\n#.@,#\n\n💰 loot_tier_2\n@ spawn\n, hall\n"
+    );
+
+    let json = serde_json::to_string(&legend).unwrap();
+    assert_eq!(serde_json::from_str::<Legend>(&json).unwrap(), legend);
+    assert_eq!(Legend::default().marker("boss_spawn").glyph, "B");
+}
+
+#[test]
+fn tileset_cells_work_with_algorithms_and_renderers() {
+    use terrain_forge::algorithms::Bsp;
+    use terrain_forge::tileset::{TileCell, TileDef, TileId, TileSet};
+    use terrain_forge::{Algorithm, Cell, CellAdapter};
+
+    let lava = TileId(7);
+    let tileset = TileSet::default()
+        .with_tile(
+            TileDef::new(lava, "lava")
+                .with_color([220, 60, 0])
+                .with_glyph('^'),
+        )
+        // Restyling the reserved ids can't change their passability.
+        .with_tile(TileDef::new(TileId::FLOOR, "stone").with_glyph(','));
+    assert!(tileset.cell(TileId::FLOOR).is_passable());
+    assert!(!tileset.cell(lava).is_passable());
+    assert!(tileset.is_opaque(TileId(99)));
+
+    let mut classic = Grid::new(30, 20);
+    Bsp::default().generate(&mut classic, 4);
+    let mut grid: Grid<TileCell> = Grid::new(30, 20);
+    CellAdapter::new(Bsp::default()).generate(&mut grid, 4);
+    assert_eq!(grid, tileset.from_tiles(&classic));
+    assert_eq!(grid.to_tiles(), classic);
+
+    grid.set(0, 0, tileset.cell(lava));
+    let text = tileset.render_text(&grid);
+    assert!(text.starts_with('^'));
+    assert_eq!(text.matches(',').count(), classic.count(|t| t.is_floor()));
+    assert!(tileset.cost_map(&grid)[0][0].is_infinite());
+    assert_eq!(&tileset.to_rgb(&grid)[..3], &[220, 60, 0]);
+    assert!(tileset.to_png(&grid).unwrap().starts_with(b"\x89PNG"));
+
+    let json = serde_json::to_string(&tileset).unwrap();
+    let back: TileSet = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, tileset);
+}
+
+#[test]
+fn dual_grid_views_round_trip() {
+    use terrain_forge::dual::{self, EdgeWalls, Facing, Orientation};
+
+    let mut grid = Grid::new(40, 30);
+    terrain_forge::ops::generate("cellular", &mut grid, Some(3), None).unwrap();
+    assert_eq!(dual::from_corner_masks(&dual::corner_masks(&grid)), grid);
+
+    // One edge per floor side that faces a wall or the map edge.
+    let exposed: usize = grid
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .map(|(x, y, _)| {
+            [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .filter(|&&(dx, dy)| {
+                    !grid
+                        .get(x as i32 + dx, y as i32 + dy)
+                        .is_some_and(|t| t.is_floor())
+                })
+                .count()
+        })
+        .sum();
+    let edges = dual::wall_edges(&grid);
+    assert_eq!(edges.len(), exposed);
+    for edge in &edges {
+        let (x, y) = edge.floor_cell();
+        assert!(grid[(x, y)].is_floor());
+    }
+    let runs = dual::merge_edges(&edges);
+    assert_eq!(runs.iter().map(|r| r.length()).sum::<usize>(), edges.len());
+    assert!(runs.len() < edges.len());
+
+    // A 2x1 thin-wall map with a wall between its cells.
+    let mut walls = EdgeWalls::new(2, 1);
+    assert_eq!(walls.to_tiles().count(|t| t.is_floor()), 3);
+    walls.vertical[0][1] = true;
+    let tiles = walls.to_tiles();
+    assert_eq!((tiles.width(), tiles.height()), (5, 3));
+    assert_eq!(tiles.count(|t| t.is_floor()), 2);
+    let thin = EdgeWalls::from_tiles(&tiles);
+    assert!(thin.horizontal[1][1] && thin.horizontal[2][1]);
+    let west = dual::wall_edges(&tiles)
+        .into_iter()
+        .find(|e| e.orientation == Orientation::Vertical && e.corner == (1, 1))
+        .unwrap();
+    assert_eq!(west.facing, Facing::East);
+}
@@ -0,0 +1,88 @@
+//! Hiddenness score tests
+
+use terrain_forge::hiding::{self, HidingConfig};
+use terrain_forge::semantic::{meta, ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+use terrain_forge::{Grid, Tile};
+
+/// An open hall from the spawn (west) to the exit (east), with a winding
+/// spur off its north wall ending in a dead end.
+fn hall_with_spur() -> (Grid<Tile>, SemanticLayers) {
+    let mut grid = Grid::new(50, 24);
+    grid.fill_rect(1, 14, 48, 7, Tile::Floor);
+    grid.fill_rect(20, 6, 1, 8, Tile::Floor);
+    grid.fill_rect(20, 6, 10, 1, Tile::Floor);
+    grid.fill_rect(29, 2, 1, 4, Tile::Floor);
+    let semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(2, 17, MarkerType::Spawn),
+            Marker::new(47, 17, MarkerType::Exit),
+        ],
+        masks: Masks::new(50, 24),
+        connectivity: ConnectivityGraph::new(),
+    };
+    (grid, semantic)
+}
+
+#[test]
+fn dead_end_spurs_score_above_the_open_path() {
+    let (grid, semantic) = hall_with_spur();
+    let config = HidingConfig::default();
+    let scores = hiding::score(&grid, &semantic, &config);
+    assert_eq!((scores.len(), scores[0].len()), (24, 50));
+    assert_eq!(scores[0][0], 0.0);
+    assert!(scores.iter().flatten().all(|s| (0.0..=1.0).contains(s)));
+
+    let tip = scores[2][29];
+    let hall = scores[17][25];
+    let spur_mouth = scores[12][20];
+    assert!(
+        tip > spur_mouth && spur_mouth > hall,
+        "{} {} {}",
+        tip,
+        spur_mouth,
+        hall
+    );
+    let best = scores.iter().flatten().copied().fold(0.0, f64::max);
+    assert_eq!(tip, best);
+
+    // Without a spawn the detour term drops out, but the spur still wins.
+    let mut bare = semantic.clone();
+    bare.markers.clear();
+    let bare_scores = hiding::score(&grid, &bare, &config);
+    assert!(bare_scores[2][29] > bare_scores[17][25]);
+
+    let spots = hiding::pick(&scores, 3, 6.0);
+    assert_eq!(spots[0], (29, 2));
+    for (i, a) in spots.iter().enumerate() {
+        for b in &spots[i + 1..] {
+            let (dx, dy) = (a.0 as f32 - b.0 as f32, a.1 as f32 - b.1 as f32);
+            assert!(dx * dx + dy * dy >= 36.0);
+        }
+    }
+    let hidden = hiding::mask(&scores, tip);
+    assert!(hidden[2][29] && !hidden[17][25] && !hidden[0][0]);
+}
+
+#[test]
+fn treasure_lands_in_hiding_spots() {
+    let (grid, mut semantic) = hall_with_spur();
+    let config = HidingConfig::default();
+    let placed = hiding::place(&grid, &mut semantic, &config, 2);
+    assert_eq!(placed[0], (29, 2));
+    let treasure: Vec<&Marker> = semantic
+        .markers
+        .iter()
+        .filter(|m| m.marker_type == MarkerType::Treasure)
+        .collect();
+    assert_eq!(treasure.len(), 2);
+    assert!(treasure
+        .iter()
+        .all(|m| m.metadata.contains_key(meta::HIDING_SCORE)));
+
+    // Existing markers keep treasure at a distance.
+    let again = hiding::place(&grid, &mut semantic, &config, 1);
+    assert!(again.iter().all(|&(x, y)| placed
+        .iter()
+        .all(|p| (p.0 as f32 - x as f32).hypot(p.1 as f32 - y as f32) >= 6.0)));
+}
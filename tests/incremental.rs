@@ -0,0 +1,179 @@
+//! Incremental regeneration tests — dirty tracking, region patching, semantic updates.
+
+use terrain_forge::incremental::{self, DirtyRect, DirtyTracker};
+use terrain_forge::pipeline::Pipeline;
+use terrain_forge::{Grid, Masks, Rng, SemanticExtractor, Tile};
+
+fn cave_pipeline() -> Pipeline {
+    let mut pipeline = Pipeline::new();
+    pipeline.add_algorithm("cellular", None, None);
+    pipeline
+}
+
+#[test]
+fn tracker_merges_touching_marks() {
+    let mut tracker = DirtyTracker::new();
+    tracker.mark(5, 5);
+    tracker.mark(6, 5);
+    tracker.mark_rect(DirtyRect::new(30, 30, 4, 4));
+    assert_eq!(tracker.rects().len(), 2);
+    tracker.mark_rect(DirtyRect::new(7, 5, 23, 26));
+    assert_eq!(tracker.rects(), &[DirtyRect::new(5, 5, 29, 29)]);
+    assert_eq!(tracker.drain().len(), 1);
+    assert!(tracker.is_empty());
+
+    let mut chunked = DirtyTracker::new().with_chunk_size(16);
+    chunked.mark(3, 20);
+    assert_eq!(chunked.rects(), &[DirtyRect::new(0, 16, 16, 16)]);
+}
+
+#[test]
+fn regenerate_region_only_touches_rect_and_margin() {
+    let pipeline = cave_pipeline();
+    let mut grid = Grid::new(100, 70);
+    pipeline.execute_seed(&mut grid, 1).unwrap();
+    let before = grid.clone();
+
+    let rect = DirtyRect::new(40, 20, 20, 15);
+    let update = incremental::regenerate_region(&pipeline, &mut grid, rect, 3, 9).unwrap();
+    assert_eq!(update.rect, DirtyRect::new(37, 17, 26, 21));
+
+    let mut changed_inside = 0;
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            if update.rect.contains(x, y) {
+                changed_inside += (grid[(x, y)] != before[(x, y)]) as usize;
+            } else {
+                assert_eq!(
+                    grid[(x, y)],
+                    before[(x, y)],
+                    "({}, {}) outside margin",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+    assert!(changed_inside > 0);
+
+    let mut again = before.clone();
+    incremental::regenerate_region(&pipeline, &mut again, rect, 3, 9).unwrap();
+    assert_eq!(again, grid, "same seed and rect must be deterministic");
+}
+
+#[test]
+fn semantic_update_keeps_markers_outside_rect() {
+    let pipeline = cave_pipeline();
+    let mut grid = Grid::new(100, 70);
+    pipeline.execute_seed(&mut grid, 4).unwrap();
+    let extractor = SemanticExtractor::for_caves();
+    let mut layers = extractor.extract(&grid, &mut Rng::new(4));
+
+    let update =
+        incremental::regenerate_region(&pipeline, &mut grid, DirtyRect::new(10, 10, 30, 20), 2, 5)
+            .unwrap();
+    let outside: Vec<(u32, u32)> = layers
+        .markers
+        .iter()
+        .filter(|m| !update.rect.contains(m.x as usize, m.y as usize))
+        .map(|m| (m.x, m.y))
+        .collect();
+    extractor.update(&mut layers, &grid, update.rect, &mut Rng::new(6));
+
+    assert_eq!(layers.masks.walkable, Masks::from_tiles(&grid).walkable);
+    let full = extractor.extract(&grid, &mut Rng::new(6));
+    assert_eq!(layers.regions.len(), full.regions.len());
+    for pos in outside {
+        assert!(layers.markers.iter().any(|m| (m.x, m.y) == pos));
+    }
+    for marker in &layers.markers {
+        assert_eq!(grid[(marker.x as usize, marker.y as usize)], Tile::Floor);
+    }
+}
+
+#[test]
+fn regenerate_semantic_region_rerolls_one_room() {
+    use terrain_forge::algorithms::CellularAutomata;
+
+    let pipeline = cave_pipeline();
+    let mut grid = Grid::new(100, 70);
+    pipeline.execute_seed(&mut grid, 4).unwrap();
+    let extractor = SemanticExtractor::for_caves();
+    let mut layers = extractor.extract(&grid, &mut Rng::new(4));
+    let before = grid.clone();
+    let region = layers
+        .regions
+        .iter()
+        .max_by_key(|r| r.cells.len())
+        .unwrap()
+        .clone();
+
+    let margin = 2;
+    let reroll = incremental::regenerate_semantic_region(
+        &mut grid,
+        &mut layers,
+        &extractor,
+        region.id,
+        &CellularAutomata::default(),
+        margin,
+        7,
+    )
+    .unwrap();
+
+    let near = |x: usize, y: usize| {
+        region
+            .cells
+            .iter()
+            .any(|&(rx, ry)| x.abs_diff(rx as usize).max(y.abs_diff(ry as usize)) <= margin)
+    };
+    let mut changed = 0;
+    for (x, y, tile) in grid.iter() {
+        if *tile != before[(x, y)] {
+            assert!(near(x, y), "({}, {}) is beyond the blend border", x, y);
+            assert!(reroll.rect.contains(x, y));
+            changed += 1;
+        }
+    }
+    assert!(changed > 0);
+    for &(x, y) in &reroll.tunnels {
+        assert!(grid[(x, y)].is_floor());
+    }
+
+    // No new floor is cut off from the floor that was already there.
+    for cave in grid.flood_regions() {
+        assert!(
+            cave.iter().any(|&(x, y)| !near(x, y)),
+            "cave at {:?} is cut off",
+            cave[0]
+        );
+    }
+    // Semantics were patched: masks follow the new tiles.
+    for (x, y, tile) in grid.iter() {
+        assert_eq!(layers.masks.walkable[y][x], tile.is_floor());
+    }
+
+    let mut again = before.clone();
+    let mut again_layers = extractor.extract(&again, &mut Rng::new(4));
+    incremental::regenerate_semantic_region(
+        &mut again,
+        &mut again_layers,
+        &extractor,
+        region.id,
+        &CellularAutomata::default(),
+        margin,
+        7,
+    )
+    .unwrap();
+    assert_eq!(again, grid);
+
+    assert!(incremental::regenerate_semantic_region(
+        &mut grid,
+        &mut layers,
+        &extractor,
+        9999,
+        &CellularAutomata::default(),
+        margin,
+        7,
+    )
+    .is_err());
+}
@@ -0,0 +1,67 @@
+//! Kingdom territory tests
+
+use terrain_forge::kingdoms::{self, KingdomConfig};
+use terrain_forge::{Grid, Tile};
+
+fn island(w: usize, h: usize) -> Grid<Tile> {
+    let mut land = Grid::new(w, h);
+    land.fill_rect(1, 1, w - 2, h - 2, Tile::Floor);
+    land
+}
+
+#[test]
+fn kingdoms_cover_land_and_trace_borders() {
+    let mut land = island(90, 60);
+    // A lake nobody claims.
+    land.fill_rect(40, 20, 10, 10, Tile::Wall);
+    let config = KingdomConfig::default().with_count(5);
+    let map = kingdoms::generate(&land, None, &config, 11);
+
+    assert_eq!(map.seeds.len(), 5);
+    for (x, y, tile) in land.iter() {
+        assert_eq!(tile.is_floor(), map.label(x, y).unwrap() > 0, "({x}, {y})");
+    }
+    assert!(map.areas().iter().all(|&a| a > 100), "{:?}", map.areas());
+    for (i, &(x, y)) in map.seeds.iter().enumerate() {
+        assert_eq!(map.label(x, y), Some(i as u8 + 1));
+    }
+
+    // Border polylines cover exactly the edges between different kingdoms.
+    let mut edges = 0;
+    for (x, y, &a) in map.labels.iter() {
+        for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+            if let Some(b) = map.label(nx, ny) {
+                edges += (a > 0 && b > 0 && a != b) as usize;
+            }
+        }
+    }
+    assert_eq!(map.borders.iter().map(|b| b.length()).sum::<usize>(), edges);
+    let first = &map.borders[0];
+    assert!(map.border(first.kingdoms.1, first.kingdoms.0).is_some());
+
+    let again = kingdoms::generate(&land, None, &config, 11);
+    assert_eq!(again.labels, map.labels);
+}
+
+#[test]
+fn steep_ridges_become_borders() {
+    let (w, h) = (80, 40);
+    let land = island(w, h);
+    let heights: Vec<Vec<f64>> = (0..h)
+        .map(|_| (0..w).map(|x| if x == 40 { 1.0 } else { 0.0 }).collect())
+        .collect();
+    let config = KingdomConfig::default()
+        .with_count(2)
+        .with_height_cost(1000.0);
+    let map = kingdoms::generate(&land, Some(&heights), &config, 4);
+
+    let (a, b) = (map.seeds[0].0 < 40, map.seeds[1].0 < 40);
+    assert_ne!(a, b, "seeds should sit on opposite sides");
+    let border = map.border(1, 2).unwrap();
+    for line in &border.lines {
+        assert!(
+            line.iter().all(|&(x, _)| (40..=41).contains(&x)),
+            "{line:?}"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Pacing curve tests
+
+use terrain_forge::pacing::{self, CurveShape, PacingConfig};
+use terrain_forge::semantic::{meta, ConnectivityGraph, Marker, MarkerType, Masks, SemanticLayers};
+use terrain_forge::{Grid, Rng, Tile};
+
+/// A long 5-wide hall with the spawn at the west end and the exit east.
+fn hall() -> (Grid<Tile>, SemanticLayers) {
+    let mut grid = Grid::new(100, 9);
+    grid.fill_rect(1, 2, 98, 5, Tile::Floor);
+    let semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(1, 4, MarkerType::Spawn),
+            Marker::new(98, 4, MarkerType::Exit),
+        ],
+        masks: Masks::new(100, 9),
+        connectivity: ConnectivityGraph::new(),
+    };
+    (grid, semantic)
+}
+
+#[test]
+fn sawtooth_rises_in_waves_to_the_boss() {
+    let shape = CurveShape::Sawtooth { waves: 3 };
+    assert_eq!(shape.tension(1.0), 1.0);
+    assert!(shape.tension(0.3) > shape.tension(0.0));
+    assert!(shape.tension(0.34) < shape.tension(0.32));
+    assert!(shape.tension(0.65) > shape.tension(0.3));
+    let custom = CurveShape::Custom {
+        points: vec![0.0, 1.0, 0.0],
+    };
+    assert_eq!(custom.tension(0.25), 0.5);
+
+    let (grid, semantic) = hall();
+    let config = PacingConfig::default().with_segments(9);
+    let plan = pacing::plan(&grid, &semantic, &config).unwrap();
+    assert_eq!(plan.path.first(), Some(&(1, 4)));
+    assert_eq!(plan.path.last(), Some(&(98, 4)));
+    assert_eq!(plan.segments.len(), 9);
+    let tension: Vec<f64> = plan.segments.iter().map(|s| s.tension).collect();
+    assert_eq!(tension[8], 1.0);
+    // Each wave of three climbs, then drops into the next.
+    assert!(tension[0] < tension[2] && tension[3] < tension[2]);
+    assert!(tension[3] < tension[5] && tension[6] < tension[5]);
+    assert!(tension[5] < tension[8]);
+}
+
+#[test]
+fn placement_follows_the_curve() {
+    let (grid, mut semantic) = hall();
+    let config = PacingConfig::default()
+        .with_segments(6)
+        .with_shape(CurveShape::Custom {
+            points: vec![0.0, 1.0, 0.0, 0.5, 0.0, 1.0],
+        });
+    let plan = pacing::plan(&grid, &semantic, &config).unwrap();
+    let placed = plan.place(&grid, &mut semantic, &config, &mut Rng::new(4));
+
+    let in_segment = |i: usize, category: &str| {
+        semantic
+            .markers
+            .iter()
+            .filter(|m| m.metadata.get(meta::PACING_SEGMENT) == Some(&i.to_string()))
+            .filter(|m| m.marker_type.category() == category)
+            .count()
+    };
+    let encounters: Vec<usize> = (0..6).map(|i| in_segment(i, "encounter")).collect();
+    // Three encounters at each full peak (plus the boss at the end), two at
+    // half tension, none in the lulls.
+    assert_eq!(encounters, [0, 3, 0, 2, 0, 4]);
+    let loot: Vec<usize> = (0..6).map(|i| in_segment(i, "loot")).collect();
+    assert_eq!(loot, [0, 0, 1, 0, 1, 0]);
+    assert_eq!(placed, 11);
+
+    let boss = semantic
+        .markers
+        .iter()
+        .find(|m| m.marker_type == MarkerType::BossRoom)
+        .unwrap();
+    assert!(boss.x >= 94);
+    let first_loot = semantic
+        .markers
+        .iter()
+        .find(|m| m.marker_type.category() == "loot")
+        .unwrap();
+    assert_eq!(first_loot.marker_type, MarkerType::LootTier { tier: 3 });
+
+    let measured = plan.measure(&grid, &semantic, &config);
+    assert_eq!(measured[5], 1.0);
+    assert_eq!((measured[0], measured[2], measured[4]), (0.0, 0.0, 0.0));
+    assert!(measured[3] < measured[1]);
+}
+
+#[test]
+fn hidden_loot_prefers_hiding_spots() {
+    use terrain_forge::hiding::{self, HidingConfig};
+
+    // Alcoves off the hall's north wall.
+    let (mut grid, semantic) = hall();
+    for x in (5..95).step_by(6) {
+        grid.set(x, 1, Tile::Floor);
+    }
+    let config = PacingConfig::default()
+        .with_segments(6)
+        .with_shape(CurveShape::Custom {
+            points: vec![0.0, 1.0, 0.0, 1.0, 0.0, 1.0],
+        });
+    let plan = pacing::plan(&grid, &semantic, &config).unwrap();
+    let scores = hiding::score(&grid, &semantic, &HidingConfig::default());
+    let loot_score = |config: &PacingConfig| {
+        let mut semantic = semantic.clone();
+        plan.place(&grid, &mut semantic, config, &mut Rng::new(2));
+        let loot: Vec<f64> = semantic
+            .markers
+            .iter()
+            .filter(|m| matches!(m.marker_type, MarkerType::LootTier { .. }))
+            .map(|m| scores[m.y as usize][m.x as usize])
+            .collect();
+        assert!(!loot.is_empty());
+        loot.iter().sum::<f64>() / loot.len() as f64
+    };
+    assert!(loot_score(&config.clone().with_hide_loot(true)) > loot_score(&config));
+}
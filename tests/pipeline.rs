@@ -49,6 +49,52 @@ fn ops_invalid_names_return_error() {
     assert!(ops::effect("not_an_effect", &mut grid, None, None).is_err());
 }
 
+#[test]
+fn errors_are_matchable_variants() {
+    use terrain_forge::semantic::SemanticRequirements;
+    use terrain_forge::Error;
+
+    let mut grid = Grid::new(20, 20);
+    assert!(matches!(
+        ops::generate("not_an_algo", &mut grid, None, None),
+        Err(Error::UnknownAlgorithm(name)) if name == "not_an_algo"
+    ));
+    assert!(matches!(
+        ops::effect("not_an_effect", &mut grid, None, None),
+        Err(Error::UnknownEffect(_))
+    ));
+    let err = ops::effect("resize", &mut grid, Some(&Params::new()), None).unwrap_err();
+    assert!(
+        matches!(&err, Error::InvalidParam { op, name, .. } if op == "resize" && name == "width")
+    );
+
+    let script = r#"{ "width": 10, "height": 10, "ops": [{ "op": "generate", "name": "nope" }] }"#;
+    match ops::run_script(script).unwrap_err() {
+        Error::Script { index, source } => {
+            assert_eq!(index, 0);
+            assert!(matches!(*source, Error::UnknownAlgorithm(_)));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(ops::run_script("{"), Err(Error::Parse { .. })));
+
+    let mut impossible = SemanticRequirements::none();
+    impossible
+        .min_regions
+        .insert("Room".to_string(), usize::MAX);
+    match terrain_forge::generate_with_requirements("bsp", 30, 20, impossible, Some(2), 1) {
+        Err(Error::ConstraintUnsatisfied { attempts, report }) => {
+            assert_eq!(attempts, 2);
+            assert!(!report.passed);
+            assert_eq!(report.results[0].id, "semantic_requirements");
+        }
+        other => panic!("unexpected {:?}", other.map(|_| ())),
+    }
+
+    let missing = terrain_forge::algorithms::PrefabLibrary::load_from_json("/no/such/prefabs.json");
+    assert!(matches!(missing, Err(Error::Io(_))));
+}
+
 // --- Step-based Pipeline ---
 
 #[test]
@@ -181,11 +227,11 @@ fn stage_result() {
 fn parameter_map() {
     let mut param_map = ParameterMap::new();
 
-    let mut branch1 = std::collections::HashMap::new();
+    let mut branch1 = std::collections::BTreeMap::new();
     branch1.insert("key1".to_string(), "value1".to_string());
     branch1.insert("shared".to_string(), "branch1".to_string());
 
-    let mut branch2 = std::collections::HashMap::new();
+    let mut branch2 = std::collections::BTreeMap::new();
     branch2.insert("key2".to_string(), "value2".to_string());
     branch2.insert("shared".to_string(), "branch2".to_string());
 
@@ -254,7 +300,7 @@ fn pipeline_template() {
         Some(&"test_template".to_string())
     );
 
-    let mut custom = std::collections::HashMap::new();
+    let mut custom = std::collections::BTreeMap::new();
     custom.insert("algorithm".to_string(), "bsp".to_string());
     let pipeline2 = template.instantiate(Some(custom));
     let mut grid2 = Grid::new(15, 15);
@@ -289,3 +335,369 @@ fn template_library() {
         assert!(grid.count(|t| t.is_floor()) > 0);
     }
 }
+
+struct FillFloor {
+    margin: usize,
+}
+
+impl terrain_forge::Algorithm<Tile> for FillFloor {
+    fn generate(&self, grid: &mut Grid<Tile>, _seed: u64) {
+        let m = self.margin;
+        let (w, h) = (grid.width() - 2 * m, grid.height() - 2 * m);
+        grid.fill_rect(m as i32, m as i32, w, h, Tile::Floor);
+    }
+
+    fn name(&self) -> &'static str {
+        "FillFloor"
+    }
+}
+
+#[test]
+fn ops_registry_exposes_custom_algorithm_and_effect() {
+    ops::register_algorithm("test_fill_floor", |params| {
+        let margin = params
+            .and_then(|p| p.get("margin"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        Ok(Box::new(FillFloor { margin }))
+    });
+    ops::register_effect("test_wall_border", |grid, _params, _semantic| {
+        let (w, h) = (grid.width() as i32, grid.height() as i32);
+        for x in 0..w {
+            grid.set(x, 0, Tile::Wall);
+            grid.set(x, h - 1, Tile::Wall);
+        }
+        Ok(())
+    });
+    assert!(ops::registered_algorithms().contains(&"test_fill_floor".to_string()));
+    assert!(ops::registered_effects().contains(&"test_wall_border".to_string()));
+
+    let mut params = Params::new();
+    params.insert("margin".to_string(), json!(0));
+    let mut pipe = Pipeline::new();
+    pipe.add_algorithm("test_fill_floor", Some(1), Some(params))
+        .add_effect("test_wall_border", None);
+    let mut grid = Grid::new(10, 10);
+    pipe.execute_seed(&mut grid, 1).unwrap();
+    assert_eq!(grid.count(|t| t.is_floor()), 10 * 8);
+}
+
+#[test]
+fn ops_schema_describes_and_validates_params() {
+    let schema = ops::describe("cellular_automata").expect("schema for alias");
+    assert_eq!(schema.name, "cellular");
+    let chance = schema.param("initial_floor_chance").unwrap();
+    assert_eq!(chance.default, Some(json!(0.45)));
+    assert_eq!(chance.range, Some((0.0, 1.0)));
+    for name in ops::ALGORITHM_NAMES {
+        assert!(ops::describe_algorithm(name).is_some(), "{}", name);
+    }
+    for name in ops::EFFECT_NAMES {
+        assert!(ops::describe_effect(name).is_some(), "{}", name);
+    }
+
+    let mut grid = Grid::new(20, 20);
+    let mut typo = Params::new();
+    typo.insert("iteratons".to_string(), json!(3));
+    let err = ops::generate("cellular", &mut grid, Some(1), Some(&typo)).unwrap_err();
+    assert!(err.to_string().contains("unknown param 'iteratons'"));
+    assert!(err.to_string().contains("did you mean 'iterations'"));
+
+    let mut bad_type = Params::new();
+    bad_type.insert("iterations".to_string(), json!("many"));
+    assert!(ops::effect("erode", &mut grid, Some(&bad_type), None).is_err());
+
+    let mut out_of_range = Params::new();
+    out_of_range.insert("initial_floor_chance".to_string(), json!(1.5));
+    let err = ops::build_algorithm("cellular", Some(&out_of_range))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("out of range"));
+
+    // String-typed values (as produced by templates) still validate.
+    let mut strings = Params::new();
+    strings.insert("iterations".to_string(), json!("2"));
+    assert!(ops::effect("dilate", &mut grid, Some(&strings), None).is_ok());
+
+    let err = ops::effect("resize", &mut grid, Some(&Params::new()), None).unwrap_err();
+    assert!(err.to_string().contains("missing required param 'width'"));
+}
+
+#[test]
+fn ops_run_script_executes_ops_in_order() {
+    let script = r#"{
+        "width": 40,
+        "height": 30,
+        "seed": 11,
+        "ops": [
+            { "op": "generate", "name": "bsp", "seed": 5 },
+            { "op": "extract", "profile": "rooms" },
+            { "op": "effect", "name": "dilate", "params": { "iterations": 1 } },
+            { "op": "combine", "mode": "difference", "name": "no_such_algorithm" }
+        ]
+    }"#;
+    let err = ops::run_script(script).unwrap_err();
+    assert!(err.to_string().starts_with("op 3:"));
+
+    let output = ops::run_script(&script.replace("no_such_algorithm", "rooms")).unwrap();
+    assert_eq!(output.grid.width(), 40);
+    assert!(output.semantic.is_some());
+    let kinds: Vec<&str> = output.log.iter().map(|e| e.op.as_str()).collect();
+    assert_eq!(kinds, ["generate", "extract", "effect", "combine"]);
+    assert_eq!(output.log[0].seed, Some(5));
+
+    let mut expected = Grid::new(40, 30);
+    ops::generate("bsp", &mut expected, Some(5), None).unwrap();
+    assert_eq!(output.log[0].floor_count, expected.count(|t| t.is_floor()));
+
+    let again = ops::run_script(&script.replace("no_such_algorithm", "rooms")).unwrap();
+    assert_eq!(again.grid, output.grid);
+}
+
+fn checker_png(size: u32) -> Vec<u8> {
+    let data: Vec<u8> = (0..size * size)
+        .map(|i| {
+            if (i % size + i / size).is_multiple_of(2) {
+                255
+            } else {
+                0
+            }
+        })
+        .collect();
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, size, size);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(&data)
+        .unwrap();
+    out
+}
+
+#[test]
+fn pipeline_image_steps_load_png() {
+    let bytes = checker_png(8);
+    let image = Grid::<Tile>::from_image_bytes(&bytes, 128).unwrap();
+    assert_eq!((image.width(), image.height()), (8, 8));
+    assert_eq!(image.count(|t| t.is_floor()), 32);
+    assert!(image[(0, 0)].is_floor() && !image[(1, 0)].is_floor());
+    assert!(Grid::<Tile>::from_image_bytes(b"not a png", 128).is_err());
+
+    let mut pipeline = Pipeline::new();
+    pipeline.add_image(ImageSource::Bytes(bytes.clone()), 128);
+    let mut grid = Grid::new(16, 16);
+    pipeline.execute_seed(&mut grid, 1).unwrap();
+    assert_eq!(grid, image.resized(16, 16));
+    assert_eq!(grid.count(|t| t.is_floor()), 128);
+
+    let mut pipeline = Pipeline::new();
+    pipeline.add_wfc_sample(ImageSource::Bytes(bytes), 128, 2, Some(3));
+    let mut grid = Grid::new(20, 20);
+    let context = pipeline.execute_seed(&mut grid, 1).unwrap();
+    assert!(grid.count(|t| t.is_floor()) > 0);
+    assert!(context
+        .execution_history()
+        .iter()
+        .any(|e| e.starts_with("WfcSample:")));
+}
+
+#[test]
+fn frames_capture_intermediate_states() {
+    use terrain_forge::algorithms::{CellularAutomata, CellularConfig};
+    use terrain_forge::Algorithm;
+
+    let cellular = CellularAutomata::new(CellularConfig {
+        iterations: 4,
+        ..Default::default()
+    });
+    let mut frames = Vec::new();
+    let mut grid = Grid::new(40, 30);
+    cellular.generate_frames(&mut grid, 5, &mut |g| frames.push(g.clone()));
+    let mut expected = Grid::new(40, 30);
+    cellular.generate(&mut expected, 5);
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames.last(), Some(&expected));
+
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .add_algorithm("cellular", Some(2), None)
+        .add_effect("erode", None);
+    let mut labels = Vec::new();
+    let mut grid = Grid::new(40, 30);
+    pipeline
+        .execute_frames(&mut grid, 9, &mut |label, _| labels.push(label.to_string()))
+        .unwrap();
+    let mut expected = Grid::new(40, 30);
+    pipeline.execute_seed(&mut expected, 9).unwrap();
+    assert_eq!(grid, expected);
+    assert_eq!(labels.last().map(String::as_str), Some("erode"));
+    assert!(labels.iter().filter(|l| *l == "cellular").count() > 1);
+}
+
+#[test]
+fn pipeline_records_manifest_with_resolved_params_and_seeds() {
+    use terrain_forge::manifest::{Manifest, StepKind};
+
+    let mut params = Params::new();
+    params.insert("iterations".to_string(), json!(6));
+    let mut pipe = Pipeline::new();
+    pipe.add_algorithm("cellular", None, Some(params))
+        .add_if(
+            PipelineCondition::FloorCount {
+                min: Some(1),
+                max: None,
+            },
+            vec![Step::Effect {
+                name: "erode".to_string(),
+                params: None,
+            }],
+            vec![],
+        )
+        .add_combine_with_algorithm(CombineMode::Union, "rooms", Some(5), None);
+
+    let mut grid = Grid::new(48, 32);
+    let context = pipe.execute_seed(&mut grid, 21).unwrap();
+    let manifest = context.manifest();
+    assert_eq!(
+        (manifest.width, manifest.height, manifest.seed),
+        (48, 32, Some(21))
+    );
+
+    let kinds: Vec<(StepKind, usize)> = manifest.steps.iter().map(|s| (s.kind, s.depth)).collect();
+    assert_eq!(
+        kinds,
+        [
+            (StepKind::Algorithm, 0),
+            (StepKind::If, 0),
+            (StepKind::Effect, 1),
+            (StepKind::Combine, 0)
+        ]
+    );
+    let cellular = &manifest.steps[0];
+    assert_eq!(cellular.params["iterations"], json!(6));
+    assert_eq!(cellular.params["birth_limit"], json!(5));
+    assert_eq!(manifest.steps[1].detail.as_deref(), Some("then"));
+    assert_eq!(manifest.step("rooms").unwrap().seed, Some(5));
+    assert!(manifest.micros() >= manifest.steps[1].micros);
+
+    // Re-running with the recorded seed reproduces the seeds and the grid.
+    let mut again = Grid::new(48, 32);
+    let rerun = pipe.execute_seed(&mut again, 21).unwrap();
+    assert_eq!(rerun.manifest().steps[0].seed, cellular.seed);
+    assert_eq!(again, grid);
+
+    let parsed = Manifest::from_json(&manifest.to_json()).unwrap();
+    assert_eq!(&parsed, manifest);
+}
+
+#[test]
+fn step_params_substitute_pipeline_parameters() {
+    let mut params = Params::new();
+    params.insert("iterations".to_string(), json!("${passes}"));
+    params.insert("preset".to_string(), json!("${style}"));
+    let mut pipe = Pipeline::new();
+    pipe.set_parameter("passes", "2")
+        .set_parameter("style", "open_caverns")
+        .add_algorithm("cellular", Some(3), Some(params));
+
+    // Context values override pipeline defaults; numbers stay numbers.
+    let mut context = PipelineContext::new();
+    context.set_parameter("passes", "7");
+    let mut grid = Grid::new(40, 30);
+    pipe.execute(&mut grid, &mut context, &mut Rng::new(1))
+        .unwrap();
+    let step = &context.manifest().steps[0];
+    assert_eq!(step.params["iterations"], json!(7));
+
+    // A reference to an unset parameter is an error naming the param.
+    let mut params = Params::new();
+    params.insert("iterations".to_string(), json!("x${missing}"));
+    let mut pipe = Pipeline::new();
+    pipe.add_effect("erode", Some(params));
+    let err = pipe.execute_seed(&mut grid, 1).unwrap_err();
+    assert!(
+        matches!(err, ops::OpError::InvalidParam { ref name, .. } if name == "iterations"),
+        "{err}"
+    );
+
+    // Template parameters fill `${..}` in effect params before execution.
+    let mut parameters = std::collections::BTreeMap::new();
+    parameters.insert("iterations".to_string(), "${passes}".to_string());
+    let template = PipelineTemplate::new("t", "")
+        .with_parameter("passes", "1")
+        .with_operation(ConditionalOperation::simple(PipelineOperation::Effect {
+            name: "erode".to_string(),
+            parameters,
+        }));
+    let mut custom = std::collections::BTreeMap::new();
+    custom.insert("passes".to_string(), "4".to_string());
+    let mut context = PipelineContext::new();
+    let result =
+        template
+            .instantiate(Some(custom))
+            .execute(&mut grid, &mut context, &mut Rng::new(1));
+    assert!(result.success);
+    assert_eq!(context.manifest().steps[0].params["iterations"], json!(4));
+}
+
+#[test]
+fn pipeline_resumes_from_any_checkpoint() {
+    let mut iterations = Params::new();
+    iterations.insert("iterations".to_string(), json!(1));
+    let mut pipe = Pipeline::new();
+    pipe.add_algorithm("cellular", None, None)
+        .add_effect("erode", Some(iterations))
+        .store_grid("caves")
+        .add_algorithm("drunkard", None, None)
+        .add_combine_with_saved(CombineMode::Union, "caves")
+        .add_combine_with_algorithm(CombineMode::Difference, "voronoi", None, None);
+
+    let mut expected = Grid::new(60, 40);
+    let context = pipe.execute_seed(&mut expected, 21).unwrap();
+    let seeds: Vec<Option<u64>> = context.manifest().steps.iter().map(|s| s.seed).collect();
+
+    let mut saved = Vec::new();
+    let mut grid = Grid::new(60, 40);
+    pipe.execute_checkpointed(&mut grid, 21, &mut |c| saved.push(c.to_json()))
+        .unwrap();
+    assert_eq!(grid, expected);
+    assert_eq!(saved.len(), 6);
+
+    for json in &saved {
+        let checkpoint = PipelineCheckpoint::from_json(json).unwrap();
+        let mut grid = Grid::new(1, 1);
+        let mut later = 0;
+        let context = pipe
+            .resume(checkpoint, &mut grid, &mut |_| later += 1)
+            .unwrap();
+        assert_eq!(grid, expected);
+        let resumed: Vec<Option<u64>> = context.manifest().steps.iter().map(|s| s.seed).collect();
+        assert_eq!(resumed, seeds);
+    }
+    let last = PipelineCheckpoint::from_json(&saved[5]).unwrap();
+    assert!(last.is_finished());
+
+    let mut shorter = Pipeline::new();
+    shorter.add_algorithm("cellular", None, None);
+    let first = PipelineCheckpoint::from_json(&saved[0]).unwrap();
+    assert!(shorter
+        .resume(first, &mut Grid::new(60, 40), &mut |_| {})
+        .is_err());
+}
+
+#[test]
+fn failed_generate_keeps_the_error() {
+    use terrain_forge::{Algorithm, Error};
+
+    let mut pipeline = Pipeline::new();
+    pipeline.add_algorithm("no_such_algorithm", None, None);
+    let mut grid = Grid::new(10, 10);
+    pipeline.generate(&mut grid, 1);
+    assert!(matches!(
+        Pipeline::take_last_error(),
+        Some(Error::UnknownAlgorithm(name)) if name == "no_such_algorithm"
+    ));
+    assert!(Pipeline::take_last_error().is_none());
+}
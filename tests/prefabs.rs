@@ -2,8 +2,8 @@
 
 use terrain_forge::{
     algorithms::{
-        Prefab, PrefabConfig, PrefabData, PrefabLegendEntry, PrefabLibrary, PrefabPlacementMode,
-        PrefabPlacer, PrefabTransform,
+        PathStampConfig, Prefab, PrefabConfig, PrefabData, PrefabLegendEntry, PrefabLibrary,
+        PrefabPlacementMode, PrefabPlacer, PrefabTransform,
     },
     semantic::{ConnectivityGraph, Masks, SemanticLayers},
     Algorithm, Grid, Rng, Tile,
@@ -116,13 +116,14 @@ fn prefab_placement_mode_merge_respects_floor() {
 
 #[test]
 fn prefab_semantic_markers_and_masks() {
-    let mut legend = std::collections::HashMap::new();
+    let mut legend = std::collections::BTreeMap::new();
     legend.insert(
         "M".to_string(),
         PrefabLegendEntry {
             tile: Some("floor".to_string()),
             marker: Some("loot_slot".to_string()),
             mask: None,
+            ..Default::default()
         },
     );
     legend.insert(
@@ -131,6 +132,7 @@ fn prefab_semantic_markers_and_masks() {
             tile: Some("floor".to_string()),
             marker: None,
             mask: Some("no_spawn".to_string()),
+            ..Default::default()
         },
     );
 
@@ -160,12 +162,14 @@ fn prefab_semantic_markers_and_masks() {
         masks: Masks {
             walkable: vec![vec![false; 10]; 10],
             no_spawn: vec![vec![false; 10]; 10],
+            named: Default::default(),
             width: 10,
             height: 10,
         },
         connectivity: ConnectivityGraph {
             regions: Vec::new(),
             edges: Vec::new(),
+            swim_edges: Vec::new(),
         },
     };
 
@@ -208,3 +212,180 @@ fn prefab_library_load_from_paths_and_dir() {
 
     let _ = std::fs::remove_dir_all(&dir);
 }
+
+#[test]
+fn prefab_stamp_along_path_aligns_to_direction() {
+    let mut library = PrefabLibrary::new();
+    library.add_prefab(Prefab::new(&["...", "###"]));
+    let config = PrefabConfig {
+        max_prefabs: 10,
+        allow_rotation: false,
+        allow_mirroring: false,
+        ..Default::default()
+    };
+    let placer = PrefabPlacer::new(config, library);
+
+    // Vertical path: the horizontal prefab should be rotated to run top-to-bottom.
+    let path: Vec<(usize, usize)> = (2..18).map(|y| (10, y)).collect();
+    let mut grid = Grid::new(20, 20);
+    let stamp = PathStampConfig {
+        interval: 5,
+        ..Default::default()
+    };
+    let stamped = placer.stamp_along_path(&mut grid, &path, &stamp, 7);
+    assert_eq!(stamped, vec![(10, 2), (10, 7), (10, 12), (10, 17)]);
+    for y in 6..=8 {
+        assert!(grid[(10, y)].is_floor());
+        assert!(grid[(9, y)].is_wall());
+    }
+}
+
+#[test]
+fn prefab_legend_multiple_markers_metadata_and_named_masks() {
+    let legend: std::collections::BTreeMap<String, PrefabLegendEntry> = serde_json::from_str(
+        r#"{
+            "S": {
+                "tile": "floor",
+                "marker": "shrine",
+                "markers": ["light_source"],
+                "masks": ["no_spawn", "sacred"],
+                "metadata": { "faction": "order" }
+            }
+        }"#,
+    )
+    .unwrap();
+    let prefab = Prefab::from_data(PrefabData {
+        name: "shrine".to_string(),
+        width: 1,
+        height: 1,
+        pattern: vec!["S".to_string()],
+        weight: 1.0,
+        tags: Vec::new(),
+        legend: Some(legend),
+    });
+    assert_eq!(prefab.cell_markers(0, 0), ["shrine", "light_source"]);
+    assert_eq!(prefab.cell_masks(0, 0), ["no_spawn", "sacred"]);
+
+    let mut library = PrefabLibrary::new();
+    library.add_prefab(prefab);
+    let config = PrefabConfig {
+        max_prefabs: 1,
+        allow_rotation: false,
+        ..Default::default()
+    };
+    let mut grid = Grid::new(8, 8);
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: Vec::new(),
+        masks: Masks::new(8, 8),
+        connectivity: ConnectivityGraph::new(),
+    };
+    PrefabPlacer::new(config, library).generate_with_semantic(&mut grid, 3, &mut semantic);
+
+    assert_eq!(semantic.markers.len(), 2);
+    assert!(semantic
+        .markers
+        .iter()
+        .all(|m| m.metadata.get("faction").map(String::as_str) == Some("order")));
+    let sacred = semantic.masks.named("sacred").expect("named mask layer");
+    let m = &semantic.markers[0];
+    assert!(sacred[m.y as usize][m.x as usize]);
+    assert!(semantic.masks.no_spawn[m.y as usize][m.x as usize]);
+}
+
+#[test]
+fn prefab_caps_dead_end_corridor() {
+    let mut grid = Grid::new(20, 20);
+    for x in 5..=10 {
+        grid.set(x, 10, Tile::Floor);
+    }
+
+    let mut library = PrefabLibrary::new();
+    let mut alcove = Prefab::new(&["...", "...", "..."]);
+    alcove.tags = vec!["alcove".to_string()];
+    library.add_prefab(alcove);
+    let config = PrefabConfig {
+        tags: Some(vec!["alcove".to_string()]),
+        ..Default::default()
+    };
+
+    let capped = PrefabPlacer::new(config, library).cap_dead_ends(&mut grid, 5);
+    assert_eq!(capped.len(), 2);
+    assert!(capped.contains(&(10, 10)));
+    for y in 9..=11 {
+        for x in 11..=13 {
+            assert!(grid[(x, y)].is_floor());
+        }
+    }
+    // Capped stubs are no longer dead ends.
+    assert!(terrain_forge::effects::find_dead_ends(&grid).is_empty());
+}
+
+#[test]
+fn decorator_furnishes_room_and_keeps_it_walkable() {
+    use terrain_forge::decoration::{DecorConfig, DecorRule, Decorator, Placement};
+
+    // One 10x8 room with a door on the left wall.
+    let mut grid = Grid::new(20, 14);
+    grid.fill_rect(5, 3, 10, 8, Tile::Floor);
+    grid.set(4, 6, Tile::Floor);
+    grid.set(3, 6, Tile::Floor);
+
+    let config = DecorConfig::default().with_rules(vec![
+        DecorRule::furniture(
+            "shelf",
+            &["SS", ".."],
+            &[('S', "bookshelf")],
+            Placement::AgainstWall,
+        )
+        .with_max_per_room(3),
+        DecorRule::furniture(
+            "throne",
+            &["T", "."],
+            &[('T', "throne")],
+            Placement::FacingDoor,
+        ),
+    ]);
+    let decoration = Decorator::new(config).decorate(&grid, &mut Rng::new(3));
+    assert_eq!(decoration.rooms.len(), 1);
+    assert_eq!(
+        decoration
+            .markers
+            .iter()
+            .filter(|m| m.tag() == "bookshelf")
+            .count(),
+        6
+    );
+
+    let throne = decoration
+        .markers
+        .iter()
+        .find(|m| m.tag() == "throne")
+        .unwrap();
+    assert_eq!(
+        throne.metadata.get("facing").map(String::as_str),
+        Some("west")
+    );
+
+    // Door area is clear and every free cell is still reachable from it.
+    let mut blocked = grid.clone();
+    for m in &decoration.markers {
+        assert_eq!(
+            decoration.item(m.x as usize, m.y as usize),
+            Some(m.tag().as_str())
+        );
+        assert!(m.x.abs_diff(5) > 1 || m.y.abs_diff(6) > 1);
+        blocked.set(m.x as i32, m.y as i32, Tile::Wall);
+    }
+    assert_eq!(blocked.flood_regions().len(), 1);
+
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: Vec::new(),
+        masks: Masks::new(20, 14),
+        connectivity: ConnectivityGraph::new(),
+    };
+    decoration.apply(&mut semantic);
+    assert_eq!(semantic.markers.len(), decoration.markers.len());
+    assert!(semantic.masks.no_spawn[throne.y as usize][throne.x as usize]);
+}
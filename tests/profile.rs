@@ -0,0 +1,61 @@
+//! Profiling tests — span nesting, allocation counts, report summaries.
+#![cfg(feature = "profile")]
+
+use terrain_forge::pipeline::Pipeline;
+use terrain_forge::profile::{self, CountingAllocator, ProfileKind};
+use terrain_forge::{ops, Grid};
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+#[test]
+fn pipeline_steps_nest_algorithms_and_effects() {
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .add_algorithm("cellular", Some(1), None)
+        .add_effect("erode", None)
+        .store_grid("base");
+
+    let (result, report) = profile::capture(|| {
+        let mut grid = Grid::new(120, 90);
+        pipeline.execute_seed(&mut grid, 3)
+    });
+    result.unwrap();
+
+    let shape: Vec<(ProfileKind, &str, usize)> = report
+        .entries
+        .iter()
+        .map(|e| (e.kind, e.label.as_str(), e.depth))
+        .collect();
+    assert_eq!(
+        shape,
+        vec![
+            (ProfileKind::PipelineStep, "cellular", 0),
+            (ProfileKind::Algorithm, "cellular", 1),
+            (ProfileKind::PipelineStep, "erode", 0),
+            (ProfileKind::Effect, "erode", 1),
+            (ProfileKind::PipelineStep, "store_grid", 0),
+        ]
+    );
+    assert!(report.entries[0].duration >= report.entries[1].duration);
+    assert!(report.entries[1].allocations > 0);
+    assert!(report.total() >= report.total_for(ProfileKind::Algorithm));
+    assert!(report.to_json().contains("\"cellular\""));
+}
+
+#[test]
+fn nothing_recorded_outside_capture() {
+    let mut grid = Grid::new(40, 30);
+    ops::generate("bsp", &mut grid, Some(1), None).unwrap();
+    let (_, report) = profile::capture(|| ());
+    assert!(report.entries.is_empty());
+
+    let (_, report) = profile::capture(|| {
+        for _ in 0..3 {
+            ops::effect("dilate", &mut grid, None, None).unwrap();
+        }
+    });
+    let summary = report.summary();
+    assert_eq!(summary.len(), 1);
+    assert_eq!(summary[0].2, 3);
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "python")]
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::ffi::CString;
+
+#[test]
+fn python_module_generates_and_extracts() {
+    Python::initialize();
+    Python::attach(|py| {
+        let module = PyModule::new(py, "terrain_forge").unwrap();
+        terrain_forge::python::terrain_forge(&module).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("tf", module).unwrap();
+        let code = CString::new(
+            r#"
+grid = tf.generate("bsp", 40, 30, seed=7, params={"max_depth": 4})
+assert (grid.width, grid.height) == (40, 30)
+assert grid.floor_count() > 0
+semantic = grid.extract_semantic("rooms", seed=7)
+assert len(semantic["regions"]) > 0
+assert grid.semantic() == semantic
+
+scripted = tf.run_script({
+    "width": 30, "height": 20, "seed": 3,
+    "ops": [{"op": "generate", "name": "cellular"}, {"op": "extract", "profile": "caves"}],
+})
+assert scripted.semantic() is not None
+assert "bsp" in tf.algorithms()
+
+try:
+    tf.generate("nope", 10, 10)
+    raise AssertionError("expected ValueError")
+except ValueError:
+    pass
+"#,
+        )
+        .unwrap();
+        py.run(&code, Some(&globals), None).unwrap();
+    });
+}
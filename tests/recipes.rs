@@ -0,0 +1,45 @@
+//! Recipe gallery tests — every built-in recipe parses, builds, and
+//! generates a map that passes its own constraints.
+
+use terrain_forge::config::Config;
+use terrain_forge::constraints::ConstraintContext;
+use terrain_forge::{recipes, Grid};
+
+#[test]
+fn every_recipe_generates_a_map_passing_its_constraints() {
+    assert_eq!(recipes::list().len(), 4);
+    for name in recipes::list() {
+        let recipe = recipes::get(name).unwrap();
+        assert_eq!(recipe.name, name);
+        assert_eq!(recipe.config.name.as_deref(), Some(name));
+        assert!(!recipe.description.is_empty());
+        assert!(Config::from_json(recipes::source(name).unwrap()).is_ok());
+
+        let bundle = recipe.generate(11).unwrap();
+        assert_eq!(bundle.grid.width(), recipe.config.width);
+        assert_eq!(bundle.grid.height(), recipe.config.height);
+        let mut ctx = ConstraintContext::new(&bundle.grid);
+        ctx.semantic = bundle.semantic.as_ref();
+        let report = recipe.constraints().evaluate(&ctx);
+        assert!(report.passed, "{}: {:?}", name, report.results);
+        assert!(bundle.reproduces().unwrap(), "{}", name);
+    }
+    let missing = recipes::get("missing").unwrap_err();
+    assert!(missing.to_string().contains("missing"), "{}", missing);
+    assert!(recipes::source("missing").is_none());
+}
+
+#[test]
+fn recipe_pipeline_includes_the_config_effects() {
+    for name in recipes::list() {
+        let recipe = recipes::get(name).unwrap();
+        let mut grid = Grid::new(recipe.config.width, recipe.config.height);
+        recipe
+            .pipeline()
+            .unwrap()
+            .execute_seed(&mut grid, 5)
+            .unwrap();
+        let expected = recipe.config.generate(5).unwrap();
+        assert_eq!(grid.to_string(), expected.to_string(), "{}", name);
+    }
+}
@@ -0,0 +1,71 @@
+//! RNG trace tests — per-step draw attribution and run diffs.
+#![cfg(feature = "rng-trace")]
+
+use terrain_forge::pipeline::Pipeline;
+use terrain_forge::rng_trace::{self, RngTrace, TraceKind};
+use terrain_forge::Grid;
+
+fn trace(pipeline: &Pipeline) -> RngTrace {
+    let (result, trace) = rng_trace::capture(|| {
+        let mut grid = Grid::new(60, 40);
+        pipeline.execute_seed(&mut grid, 11)
+    });
+    result.unwrap();
+    trace
+}
+
+#[test]
+fn draws_are_attributed_to_steps() {
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .add_algorithm("cellular", None, None)
+        .add_effect("scatter", None)
+        .add_algorithm("drunkard", None, None);
+    let baseline = trace(&pipeline);
+
+    let algorithms: Vec<&str> = baseline
+        .spans
+        .iter()
+        .filter(|s| s.kind == TraceKind::Algorithm)
+        .map(|s| s.label.as_str())
+        .collect();
+    assert_eq!(algorithms, ["cellular", "drunkard"]);
+    let cellular = baseline
+        .spans
+        .iter()
+        .find(|s| s.kind == TraceKind::Algorithm && s.label == "cellular")
+        .unwrap();
+    assert!(cellular.draws.len() > 1000);
+    assert!(cellular.generators >= 1);
+    assert!(baseline.total_draws() > cellular.draws.len());
+
+    let saved = RngTrace::from_json(&baseline.to_json()).unwrap();
+    let (_, divergence) = rng_trace::replay(&saved, || {
+        pipeline.execute_seed(&mut Grid::new(60, 40), 11).unwrap()
+    });
+    assert_eq!(divergence, None);
+}
+
+#[test]
+fn diff_pinpoints_the_first_divergent_draw() {
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .add_algorithm("cellular", None, None)
+        .add_algorithm("drunkard", Some(5), None);
+    let baseline = trace(&pipeline);
+
+    let mut changed = Pipeline::new();
+    changed
+        .add_algorithm("cellular", None, None)
+        .add_algorithm("drunkard", Some(6), None);
+    let divergence = baseline.diff(&trace(&changed)).unwrap();
+    assert_eq!(divergence.label, "drunkard");
+    assert_eq!(divergence.draw, Some(0));
+    assert!(divergence.to_string().contains("drunkard"));
+
+    let mut extra = pipeline.clone();
+    extra.add_effect("erode", None);
+    let divergence = baseline.diff(&trace(&extra)).unwrap();
+    assert_eq!(divergence.draw, None);
+    assert!(divergence.reason.contains("spans"));
+}
@@ -0,0 +1,71 @@
+//! Seed sweep tests — ranking, top-K truncation, constraint reports.
+
+use terrain_forge::constraints::{ConnectivityConstraint, ConstraintSet, DensityConstraint};
+use terrain_forge::{algorithms, search, Grid, Tile};
+
+fn cave(seed: u64) -> Grid<Tile> {
+    let mut grid = Grid::new(48, 32);
+    algorithms::get("cellular")
+        .unwrap()
+        .generate(&mut grid, seed);
+    grid
+}
+
+#[test]
+fn sweep_returns_best_by_metric() {
+    let floor = |grid: &Grid<Tile>| grid.count(|t| t.is_floor()) as f64;
+    let best = search::sweep(cave, &floor, 0..40, 3);
+
+    assert_eq!(best.len(), 3);
+    let max = (0..40).map(|s| floor(&cave(s))).fold(0.0, f64::max);
+    assert_eq!(best[0].score.value, max);
+    assert!(best
+        .windows(2)
+        .all(|w| w[0].score.value >= w[1].score.value));
+    for candidate in &best {
+        assert_eq!(candidate.grid, cave(candidate.seed));
+    }
+}
+
+#[test]
+fn sweep_ranks_passing_constraints_first() {
+    let mut set = ConstraintSet::new();
+    set.push(ConnectivityConstraint::new(0.9));
+    set.push(DensityConstraint::new(0.3, 0.6));
+
+    let best = search::sweep(cave, &set, 100..130, 30);
+    assert_eq!(best.len(), 30);
+    let first_fail = best
+        .iter()
+        .position(|c| !c.score.passed)
+        .unwrap_or(best.len());
+    assert!(best[first_fail..].iter().all(|c| !c.score.passed));
+    assert!(best.iter().all(|c| c.score.report.is_some()));
+
+    let again = search::sweep(cave, &set, 100..130, 5);
+    let seeds: Vec<u64> = again.iter().map(|c| c.seed).collect();
+    let expected: Vec<u64> = best.iter().take(5).map(|c| c.seed).collect();
+    assert_eq!(seeds, expected);
+}
+
+#[test]
+fn sweep_is_deterministic_with_nan_scores() {
+    let flaky = |grid: &Grid<Tile>| {
+        let floors = grid.count(|t| t.is_floor());
+        if floors.is_multiple_of(3) {
+            f64::NAN
+        } else {
+            floors as f64
+        }
+    };
+    let a: Vec<u64> = search::sweep(cave, &flaky, 0..40, 10)
+        .iter()
+        .map(|c| c.seed)
+        .collect();
+    let b: Vec<u64> = search::sweep(cave, &flaky, (0..40).rev(), 10)
+        .iter()
+        .map(|c| c.seed)
+        .collect();
+    assert_eq!(a.len(), 10);
+    assert_eq!(a, b);
+}
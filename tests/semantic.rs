@@ -74,3 +74,1011 @@ fn vertical_connectivity_basic() {
     assert!(!connectivity.stairs.is_empty());
     assert!(connectivity.stairs.len() <= 2);
 }
+
+#[test]
+fn multi_floor_stairs_link_adjacent_floors() {
+    use terrain_forge::compose::{MultiFloorConfig, MultiFloorGenerator};
+
+    let config = MultiFloorConfig::default()
+        .with_floors(4)
+        .with_algorithms(["bsp", "cellular", "maze", "rooms"])
+        .with_stairs(2);
+    for seed in 0..5 {
+        let map = MultiFloorGenerator::new(config.clone())
+            .generate(60, 40, seed)
+            .unwrap();
+        assert_eq!(map.floors.len(), 4);
+        assert_eq!(map.floors[2].algorithm, "maze");
+        assert!(map.is_connected(), "seed {}", seed);
+        assert!(map.floors[0].stairs_up.is_empty());
+        assert!(map.floors[3].stairs_down.is_empty());
+        for pair in map.floors.windows(2) {
+            assert_eq!(pair[0].stairs_down, pair[1].stairs_up);
+            for &(x, y) in &pair[0].stairs_down {
+                assert!(pair[0].grid[(x as usize, y as usize)].is_floor());
+                assert!(pair[1].grid[(x as usize, y as usize)].is_floor());
+            }
+        }
+        assert_eq!(
+            map.vertical.stairs.len(),
+            map.floors[1].stairs_up.len()
+                + map.floors[2].stairs_up.len()
+                + map.floors[3].stairs_up.len()
+        );
+        for depth in 0..4 {
+            assert_eq!(map.vertical.floor_accessibility[&depth], vec![0, 1, 2, 3]);
+        }
+    }
+
+    let a = MultiFloorGenerator::new(config.clone())
+        .generate(60, 40, 9)
+        .unwrap();
+    let b = MultiFloorGenerator::new(config)
+        .generate(60, 40, 9)
+        .unwrap();
+    for (fa, fb) in a.floors.iter().zip(&b.floors) {
+        assert_eq!(fa.grid, fb.grid);
+        assert_eq!(fa.stairs_down, fb.stairs_down);
+    }
+    assert!(
+        MultiFloorGenerator::new(MultiFloorConfig::default().with_algorithms(["nope"]))
+            .generate(20, 20, 1)
+            .is_err()
+    );
+    // A floor too small to hold any open cell can't get a stair.
+    assert!(MultiFloorGenerator::new(MultiFloorConfig::default())
+        .generate(2, 2, 1)
+        .is_err());
+}
+
+#[test]
+fn multi_floor_carved_links_are_4_connected() {
+    use terrain_forge::compose::{MultiFloorConfig, MultiFloorGenerator};
+
+    // The middle floor's stair up crowds out every candidate for its stair
+    // down, so that link is always carved.
+    let config = MultiFloorConfig::default()
+        .with_algorithms(["cellular", "drunkard", "maze"])
+        .with_min_stair_distance(1000);
+    for seed in 0..5 {
+        let map = MultiFloorGenerator::new(config.clone())
+            .generate(50, 40, seed)
+            .unwrap();
+        assert!(map.is_connected(), "seed {}", seed);
+        let &(x, y) = &map.floors[2].stairs_up[0];
+        assert!(map.floors[1].grid[(x as usize, y as usize)].is_floor());
+        assert!(map.floors[2].grid[(x as usize, y as usize)].is_floor());
+    }
+}
+
+#[test]
+fn traps_sit_on_busy_corridors() {
+    use terrain_forge::spatial::{shortest_path, PathfindingConstraints};
+    use terrain_forge::traps::{self, TrapConfig, TrapPlacer};
+    use terrain_forge::{Grid, Rng, Tile};
+
+    // Two rooms joined by a corridor along y = 5.
+    let mut grid = Grid::new(40, 20);
+    grid.fill_rect(1, 1, 8, 18, Tile::Floor);
+    grid.fill_rect(30, 1, 9, 18, Tile::Floor);
+    grid.fill_rect(9, 5, 21, 1, Tile::Floor);
+    let (spawn, goals) = ((2, 5), [(35, 5), (35, 15)]);
+
+    let heat = traps::traffic(&grid, spawn, &goals);
+    assert_eq!(heat[5][20], 2);
+    assert_eq!(heat[15][35], 1);
+    assert_eq!(heat[10][5], 0);
+
+    let config = TrapConfig::default()
+        .with_density(1.0)
+        .with_min_spacing(4)
+        .with_min_spawn_distance(12);
+    let placed = TrapPlacer::new(config.clone()).place(&grid, spawn, &goals, &mut Rng::new(1));
+    assert!(!placed.is_empty());
+    for trap in &placed {
+        assert_eq!(trap.tag(), "trap");
+        assert_eq!(trap.y, 5);
+        assert!((12..30).contains(&trap.x), "x = {}", trap.x);
+        assert_eq!(trap.metadata["traffic"], "2");
+    }
+    for (i, a) in placed.iter().enumerate() {
+        for b in &placed[i + 1..] {
+            assert!(a.x.abs_diff(b.x) >= 4);
+        }
+    }
+
+    // The only route can't hold an avoidable trap; a second corridor can.
+    let avoidable = TrapPlacer::new(config.with_avoidable(true));
+    assert!(avoidable
+        .place(&grid, spawn, &goals, &mut Rng::new(1))
+        .is_empty());
+    grid.fill_rect(9, 14, 21, 1, Tile::Floor);
+    let placed = avoidable.place(&grid, spawn, &goals, &mut Rng::new(1));
+    assert!(!placed.is_empty());
+    for trap in &placed {
+        grid.set(trap.x as i32, trap.y as i32, Tile::Wall);
+    }
+    for goal in goals {
+        let path = shortest_path(&grid, spawn, goal, &PathfindingConstraints::default());
+        assert!(path.is_some());
+    }
+}
+
+#[test]
+fn faction_territories_balance_and_mark_borders() {
+    use terrain_forge::factions::{self, Faction, FactionConfig};
+    use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor, Tile};
+
+    let config = FactionConfig::new(vec![
+        Faction::new("goblins", "goblin_patrol").with_weight(2.0),
+        Faction::new("undead", "skeleton_guard"),
+        Faction::new("bandits", "ambush"),
+    ]);
+
+    let mut open = Grid::new(60, 40);
+    open.fill_rect(1, 1, 58, 38, Tile::Floor);
+    let territories = factions::assign(&open, &config, &mut Rng::new(3));
+    let areas = territories.areas();
+    assert_eq!(areas.iter().sum::<usize>(), 58 * 38);
+    assert!(areas[0] > areas[1] * 3 / 2, "{:?}", areas);
+    assert!(areas[1].abs_diff(areas[2]) < areas[1] / 3, "{:?}", areas);
+
+    let mut grid = Grid::new(80, 60);
+    algorithms::get("bsp").unwrap().generate(&mut grid, 5);
+    let mut semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(5));
+    let territories = factions::assign(&grid, &config, &mut Rng::new(5));
+    for (x, y, tile) in grid.iter() {
+        assert_eq!(tile.is_floor(), territories.owner(x, y).is_some());
+    }
+
+    let before = semantic.markers.len();
+    let added = territories.apply(&mut semantic, &mut Rng::new(5));
+    assert!(added > 0);
+    assert_eq!(semantic.markers.len(), before + added);
+    for marker in &semantic.markers[before..] {
+        let (x, y) = (marker.x as usize, marker.y as usize);
+        let owner = territories.owner(x, y).unwrap();
+        let faction = &config.factions[owner];
+        assert_eq!(marker.tag(), faction.encounter);
+        assert_eq!(marker.metadata["faction"], faction.name);
+        assert_ne!(marker.metadata["rival"], faction.name);
+        assert!(territories.borders().iter().any(|b| (b.0, b.1) == (x, y)));
+    }
+    for region in &semantic.regions {
+        assert_eq!(
+            region
+                .tags
+                .iter()
+                .filter(|t| t.starts_with("faction:"))
+                .count(),
+            1
+        );
+    }
+    let goblins = semantic.masks.named("faction_goblins").unwrap();
+    assert!(grid
+        .iter()
+        .all(|(x, y, _)| goblins[y][x] == (territories.owner(x, y) == Some(0))));
+}
+
+#[test]
+fn quest_steps_follow_markers_outward() {
+    use terrain_forge::quests::{self, QuestConfig, StepKind};
+    use terrain_forge::{Grid, Rng, Tile};
+
+    let mut grid = Grid::new(50, 3);
+    grid.fill_rect(1, 1, 48, 1, Tile::Floor);
+    let tag = |x: u32, t: &str| Marker::with_tag(x, 1, t.to_string());
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            tag(45, "Exit"),
+            tag(25, "Enemy"),
+            Marker::new(1, 1, MarkerType::Spawn),
+            tag(3, "loot_slot"),
+            tag(10, "Treasure"),
+        ],
+        masks: Masks::new(50, 3),
+        connectivity: ConnectivityGraph::new(),
+    };
+
+    let config = QuestConfig::default().with_steps(3).with_min_distance(5);
+    let quest = quests::generate(&grid, &semantic, &config, &mut Rng::new(1)).unwrap();
+    assert_eq!(quest.start, (1, 1));
+    let kinds: Vec<StepKind> = quest.steps.iter().map(|s| s.kind).collect();
+    assert_eq!(kinds, [StepKind::Fetch, StepKind::Kill, StepKind::Reach]);
+    let xs: Vec<u32> = quest.steps.iter().map(|s| s.x).collect();
+    assert_eq!(xs, [10, 25, 45]);
+    assert_eq!(quest.steps[2].distance, 44);
+    assert_eq!(quest.steps[2].difficulty, 3);
+
+    let json = serde_json::to_string(&quest).unwrap();
+    assert!(json.contains("\"kind\":\"fetch\""));
+    let back: quests::Quest = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.steps.len(), 3);
+
+    quest.apply(&mut semantic);
+    let objectives = marker_positions(&semantic, &MarkerType::QuestObjective { priority: 2 });
+    assert_eq!(objectives, [(25, 1)]);
+    let second = semantic
+        .markers
+        .iter()
+        .find(|m| (m.x, m.y) == (25, 1) && m.marker_type.category() == "quest")
+        .unwrap();
+    assert_eq!(second.get_i64(meta::QUEST_STEP), Some(2));
+    assert_eq!(second.metadata[meta::QUEST_KIND], "kill");
+    assert_eq!(second.metadata[meta::QUEST_TARGET], "Enemy");
+    assert_eq!(second.get_i64(meta::QUEST_DIFFICULTY), Some(2));
+    assert_eq!(
+        marker_positions(&semantic, &MarkerType::QuestEnd),
+        [(45, 1)]
+    );
+    assert_eq!(
+        marker_positions(&semantic, &MarkerType::QuestStart),
+        [(1, 1)]
+    );
+
+    let far = QuestConfig::default().with_min_distance(100);
+    assert!(quests::generate(&grid, &semantic, &far, &mut Rng::new(1)).is_none());
+}
+
+#[test]
+fn regions_decompose_into_rectangles() {
+    use terrain_forge::{Grid, Rng, SemanticExtractor, Tile};
+
+    // L shape: a 6x2 bar with a 2x3 leg below its left end.
+    let mut region = Region::new(1, "room");
+    for y in 0..5 {
+        for x in 0..6 {
+            if y < 2 || x < 2 {
+                region.add_cell(x + 10, y + 20);
+            }
+        }
+    }
+    let rects = region.cover_rects();
+    assert_eq!(
+        rects,
+        vec![
+            RegionRect {
+                x: 10,
+                y: 20,
+                width: 6,
+                height: 2
+            },
+            RegionRect {
+                x: 10,
+                y: 22,
+                width: 2,
+                height: 3
+            },
+        ]
+    );
+    assert_eq!(rects.iter().map(|r| r.area()).sum::<usize>(), region.area());
+
+    let mut grid = Grid::new(30, 20);
+    grid.fill_rect(3, 4, 8, 5, Tile::Floor);
+    let semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(1));
+    assert_eq!(
+        semantic.regions[0].rects,
+        vec![RegionRect {
+            x: 3,
+            y: 4,
+            width: 8,
+            height: 5
+        }]
+    );
+    let caves = SemanticExtractor::for_caves().extract(&grid, &mut Rng::new(1));
+    assert!(caves.regions[0].rects.is_empty());
+}
+
+#[test]
+fn themes_follow_channels_and_depth() {
+    use std::collections::HashMap;
+    use terrain_forge::theme::{self, Theme, ThemeConfig, DEPTH};
+    use terrain_forge::tileset::{TileDef, TileId, TileSet};
+    use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+
+    let (w, h) = (80, 60);
+    let mut grid = Grid::new(w, h);
+    algorithms::get("bsp").unwrap().generate(&mut grid, 14);
+    // Cold on the left, hot on the right.
+    let temperature: Vec<Vec<f64>> = (0..h)
+        .map(|_| (0..w).map(|x| x as f64 / w as f64 * 60.0 - 20.0).collect())
+        .collect();
+    let channels = HashMap::from([("temperature".to_string(), temperature)]);
+    let config = ThemeConfig::new(vec![
+        Theme::new("ice")
+            .with_rule("temperature", f64::MIN, 0.0)
+            .with_wall("ice_wall"),
+        Theme::new("lava")
+            .with_rule("temperature", 25.0, f64::MAX)
+            .with_rule(DEPTH, 0.5, 1.0)
+            .with_floor("lava"),
+        Theme::new("missing").with_rule("humidity", 0.0, 1.0),
+    ]);
+    let themes = theme::assign(&grid, &channels, &config);
+    assert_eq!(themes.theme_at(0, 30), Some("ice"));
+    assert_eq!(themes.theme_at(79, 59), Some("lava"));
+    assert_eq!(themes.theme_at(79, 0), None, "lava needs depth");
+    assert_eq!(themes.theme_at(40, 30), None);
+    assert_eq!(themes.counts()[2], 0, "rules on absent channels never hold");
+
+    let tileset = TileSet::default()
+        .with_tile(TileDef::new(TileId(2), "ice_wall"))
+        .with_tile(TileDef::new(TileId(3), "lava").with_passable(true));
+    let tiles = themes.paint(&grid, &tileset);
+    for (x, y, tile) in grid.iter() {
+        let expected = match (themes.theme_at(x, y), tile.is_floor()) {
+            (Some("ice"), false) => TileId(2),
+            (Some("lava"), true) => TileId(3),
+            (_, true) => TileId::FLOOR,
+            (_, false) => TileId::WALL,
+        };
+        assert_eq!(tiles[(x, y)].id(), expected, "({}, {})", x, y);
+    }
+
+    let mut semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(14));
+    themes.apply(&mut semantic);
+    for region in &semantic.regions {
+        let mut counts = [0usize; 3];
+        for &(x, y) in &region.cells {
+            if let Some(id) = themes.id_at(x as usize, y as usize) {
+                counts[id] += 1;
+            }
+        }
+        let majority = if counts[0] >= counts[1] {
+            "ice"
+        } else {
+            "lava"
+        };
+        let tags: Vec<_> = region
+            .tags
+            .iter()
+            .filter(|t| t.starts_with("theme:"))
+            .collect();
+        assert_eq!(tags, [&format!("theme:{}", majority)]);
+    }
+    assert!(semantic.masks.named("theme_lava").is_some());
+}
+
+#[test]
+fn marker_metadata_typed_accessors() {
+    let marker = Marker::new(4, 5, MarkerType::LootTier { tier: 3 })
+        .with_metadata("mygame.weight", " 2.5")
+        .with_point("mygame.anchor", (-2, 7))
+        .with_metadata("mygame.bad_point", "1;2");
+    assert_eq!(marker.get_i64(meta::LOOT_TIER), Some(3));
+    assert_eq!(marker.get_f64("mygame.weight"), Some(2.5));
+    assert_eq!(marker.get_i64("mygame.weight"), None);
+    assert_eq!(marker.get_point("mygame.anchor"), Some((-2, 7)));
+    assert_eq!(marker.get_point("mygame.bad_point"), None);
+    assert_eq!(marker.get_f64("missing"), None);
+    let json = serde_json::to_string(&marker.metadata).unwrap();
+    assert!(
+        json.starts_with(r#"{"loot.tier":"3","mygame.anchor""#),
+        "{}",
+        json
+    );
+
+    let encounter = Marker::new(0, 0, MarkerType::EncounterZone { difficulty: 4 });
+    assert_eq!(encounter.get_i64(meta::ENCOUNTER_DIFFICULTY), Some(4));
+    assert!(Marker::new(0, 0, MarkerType::Spawn).metadata.is_empty());
+}
+
+#[test]
+fn auto_profile_follows_map_statistics() {
+    use terrain_forge::{algorithms, Grid, SemanticExtractor, Tile};
+
+    let generate = |name: &str, seed| {
+        let mut grid = Grid::new(80, 60);
+        algorithms::get(name).unwrap().generate(&mut grid, seed);
+        grid
+    };
+    let kinds = |config: &SemanticConfig| -> Vec<String> {
+        config.size_thresholds.iter().map(|t| t.1.clone()).collect()
+    };
+    for seed in 1..4 {
+        for (name, preset) in [
+            ("maze", SemanticConfig::maze_system()),
+            ("cellular", SemanticConfig::cave_system()),
+            ("bsp", SemanticConfig::room_system()),
+        ] {
+            let grid = generate(name, seed);
+            let auto = SemanticExtractor::auto(&grid);
+            assert_eq!(kinds(auto.config()), kinds(&preset), "{} {}", name, seed);
+        }
+    }
+
+    let maze = MapStats::measure(&generate("maze", 1));
+    assert!(maze.corridor_ratio > 0.5 && maze.openness < 0.05);
+    let caves = MapStats::measure(&generate("cellular", 1));
+    assert!(caves.regions > 1 && caves.largest_region < 1.0);
+
+    // Half maze, half caves: neither dominates, so the profiles blend.
+    let mut mixed = generate("maze", 2);
+    let caves = generate("cellular", 2);
+    for (x, y, tile) in caves.iter() {
+        if x >= 40 {
+            mixed.set(x as i32, y as i32, *tile);
+        }
+    }
+    let config = SemanticConfig::auto(&mixed);
+    let markers: Vec<_> = config.marker_types.iter().map(|m| m.0.as_str()).collect();
+    assert!(markers.contains(&"Trap") && markers.contains(&"Crystal"));
+
+    assert_eq!(
+        kinds(&SemanticConfig::auto(&Grid::<Tile>::new(10, 10))),
+        kinds(&SemanticConfig::default())
+    );
+}
+
+#[test]
+fn entity_mapping_matches_markers_by_metadata_and_region_tags() {
+    use serde_json::json;
+    use terrain_forge::entities::{EntityMapping, EntityRule};
+
+    let mut crypt = Region::new(1, "room");
+    crypt.tags.push("crypt".to_string());
+    let hall = Region::new(2, "room");
+    let mut in_crypt = Marker::new(2, 2, MarkerType::LootTier { tier: 3 });
+    in_crypt.region_id = Some(1);
+    let mut in_hall = Marker::new(8, 2, MarkerType::LootTier { tier: 1 });
+    in_hall.region_id = Some(2);
+    let mut boss = Marker::new(5, 5, MarkerType::EncounterZone { difficulty: 4 });
+    boss.region_id = Some(2);
+    let semantic = SemanticLayers {
+        regions: vec![crypt, hall],
+        markers: vec![
+            in_crypt,
+            in_hall,
+            boss,
+            Marker::with_tag(1, 1, "torch".to_string()),
+        ],
+        masks: Masks::new(10, 10),
+        connectivity: ConnectivityGraph::new(),
+    };
+
+    let mapping = EntityMapping::from_json(
+        r#"{ "rules": [
+            { "marker": "loot", "region_tags": ["crypt"], "name": "sarcophagus",
+              "archetype": "container", "properties": { "table": "crypt_{loot.tier}" } },
+            { "marker": "loot", "name": "chest", "archetype": "container",
+              "properties": { "table": "loot_{loot.tier}", "locked": false } },
+            { "marker": "encounter", "metadata": { "encounter.difficulty": "4" },
+              "name": "ogre_pack", "archetype": "spawner" }
+        ] }"#,
+    )
+    .unwrap();
+    let entities = mapping.map(&semantic);
+    let names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["sarcophagus", "chest", "ogre_pack"]);
+    assert_eq!(entities[0].properties["table"], json!("crypt_3"));
+    assert_eq!(entities[1].properties["table"], json!("loot_1"));
+    assert_eq!(entities[1].properties["locked"], json!(false));
+    assert_eq!((entities[2].x, entities[2].y), (5, 5));
+    assert_eq!(entities[2].marker, "encounter_4");
+
+    let unmapped = mapping.unmapped(&semantic);
+    assert_eq!(unmapped.len(), 1);
+    assert_eq!(unmapped[0].tag(), "torch");
+
+    let catch_all = mapping.with_rule(EntityRule::new("*", "{kind}", "prop"));
+    assert_eq!(catch_all.map(&semantic).len(), 4);
+    assert_eq!(catch_all.map(&semantic)[3].name, "{kind}");
+}
+
+#[test]
+fn terrain_extraction_emits_water_regions_and_swim_edges() {
+    use terrain_forge::{Grid, Rng, SemanticExtractor, Tile};
+
+    let mut grid = Grid::new(60, 40);
+    grid.fill_rect(1, 1, 58, 38, Tile::Floor);
+    let features: Vec<Vec<Option<TerrainFeature>>> = (0..40)
+        .map(|y| {
+            (0..60)
+                .map(|x| match (x, y) {
+                    (5..17, 5..17) => Some(TerrainFeature::Water),
+                    (40..43, 1..39) => Some(TerrainFeature::Water),
+                    (25..29, 30..34) => Some(TerrainFeature::Lava),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect();
+    let semantic =
+        SemanticExtractor::for_caves().extract_terrain(&grid, &features, &mut Rng::new(3));
+
+    let of_kind = |kind: &str| -> Vec<&Region> {
+        semantic.regions.iter().filter(|r| r.kind == kind).collect()
+    };
+    assert_eq!(of_kind("Lake").len(), 1);
+    assert_eq!(of_kind("Lake")[0].cells.len(), 144);
+    assert_eq!(of_kind("River").len(), 1);
+    assert_eq!(of_kind("Lava").len(), 1);
+    assert!(!of_kind("Shore").is_empty());
+    assert!(!semantic.masks.walkable[10][10]);
+    assert!(semantic.masks.named("water").unwrap()[20][41]);
+    assert!(semantic.masks.named("lava").unwrap()[31][26]);
+    assert!(semantic
+        .markers
+        .iter()
+        .all(|m| features[m.y as usize][m.x as usize].is_none()));
+
+    // Lava is isolated; water only joins through swim edges.
+    let lava = of_kind("Lava")[0].id;
+    let graph = &semantic.connectivity;
+    assert!(!graph
+        .edges
+        .iter()
+        .chain(&graph.swim_edges)
+        .any(|&(a, b)| a == lava || b == lava));
+    let lake = of_kind("Lake")[0].id;
+    assert!(graph
+        .swim_edges
+        .iter()
+        .any(|&(a, b)| a == lake || b == lake));
+    assert!(!graph.edges.iter().any(|&(a, b)| a == lake || b == lake));
+
+    // The river splits the land: walking alone never crosses it.
+    let region_at = |x: u32, y: u32| {
+        semantic
+            .regions
+            .iter()
+            .find(|r| r.cells.contains(&(x, y)))
+            .unwrap()
+            .id
+    };
+    let (west, east) = (region_at(30, 20), region_at(50, 20));
+    let mut reached = vec![west];
+    while let Some(next) =
+        graph.edges.iter().find_map(
+            |&(a, b)| match (reached.contains(&a), reached.contains(&b)) {
+                (true, false) => Some(b),
+                (false, true) => Some(a),
+                _ => None,
+            },
+        )
+    {
+        reached.push(next);
+    }
+    assert!(!reached.contains(&east));
+}
+
+#[test]
+fn tile_cell_extraction_reads_features_from_tile_names() {
+    use terrain_forge::bands::{self, BandConfig};
+    use terrain_forge::{Rng, SemanticExtractor};
+
+    // Deep water in the west, beach, then grass.
+    let heights: Vec<Vec<f64>> = (0..20)
+        .map(|_| (0..40).map(|x| if x < 12 { 0.1 } else { 0.6 }).collect())
+        .collect();
+    let config = BandConfig::default();
+    let cells = bands::paint(&heights, &config, 1);
+    let semantic =
+        SemanticExtractor::for_caves().extract_tiles(&cells, &config.tileset(), &mut Rng::new(1));
+    let water = semantic
+        .regions
+        .iter()
+        .find(|r| r.kind == "Lake" || r.kind == "River")
+        .unwrap();
+    assert_eq!(water.cells.len(), 12 * 20);
+    assert!(semantic.regions.iter().any(|r| r.kind == "Shore"));
+    assert!(!semantic.connectivity.swim_edges.is_empty());
+}
+
+#[test]
+fn marker_balancing_moves_surplus_to_barren_regions() {
+    use terrain_forge::{Rng, SemanticExtractor};
+
+    let mut crowded = Region::new(1, "room");
+    let mut barren = Region::new(2, "room");
+    for y in 1..11 {
+        for x in 1..11 {
+            crowded.add_cell(x, y);
+            barren.add_cell(x + 20, y);
+        }
+    }
+    let mut markers = vec![Marker::new(1, 1, MarkerType::Spawn).with_region(1)];
+    for i in 0..8 {
+        markers.push(Marker::with_tag(2 + i, 2 + i % 3, "Enemy".to_string()).with_region(1));
+    }
+    let mut semantic = SemanticLayers {
+        regions: vec![crowded, barren],
+        markers,
+        masks: Masks::new(32, 12),
+        connectivity: ConnectivityGraph::new(),
+    };
+
+    let config = MarkerBalanceConfig::default().with_density(2.0, 5.0);
+    assert_eq!(config.band(100), (2, 5));
+    let constraints = MarkerConstraints {
+        min_distance_any: Some(2.0),
+        ..MarkerConstraints::none()
+    };
+    let moved = balance_markers(&mut semantic, &config, &constraints, &mut Rng::new(1));
+    assert_eq!(moved, 4);
+    let count = |id| {
+        semantic
+            .markers
+            .iter()
+            .filter(|m| m.region_id == Some(id))
+            .count()
+    };
+    assert_eq!((count(1), count(2)), (5, 4));
+    let spawn = &semantic.markers[0];
+    assert_eq!((spawn.x, spawn.y, spawn.region_id), (1, 1, Some(1)));
+    for (i, m) in semantic.markers.iter().enumerate() {
+        let region = &semantic.regions[m.region_id.unwrap() as usize - 1];
+        assert!(region.cells.contains(&(m.x, m.y)));
+        if m.region_id == Some(2) {
+            let mut others = semantic.markers.clone();
+            others.remove(i);
+            assert!(constraints.allows(m, &others));
+        }
+    }
+
+    // Balancing through the extractor stays deterministic.
+    let mut grid = terrain_forge::Grid::new(80, 60);
+    terrain_forge::algorithms::get("bsp")
+        .unwrap()
+        .generate(&mut grid, 8);
+    let extractor = SemanticExtractor::new(
+        SemanticConfig::room_system().with_marker_balance(MarkerBalanceConfig::default()),
+    );
+    let a = extractor.extract(&grid, &mut Rng::new(8));
+    let b = extractor.extract(&grid, &mut Rng::new(8));
+    assert_eq!(
+        a.markers.iter().map(|m| (m.x, m.y)).collect::<Vec<_>>(),
+        b.markers.iter().map(|m| (m.x, m.y)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn entrances_mark_corridor_cells_at_room_edges() {
+    use terrain_forge::decoration::find_rooms;
+    use terrain_forge::entrances::{self, EntranceConfig};
+    use terrain_forge::{algorithms, Grid, Rng, SemanticExtractor};
+
+    let mut grid = Grid::new(80, 60);
+    algorithms::get("bsp").unwrap().generate(&mut grid, 11);
+    let mut semantic = SemanticExtractor::for_rooms().extract(&grid, &mut Rng::new(11));
+    let config = EntranceConfig::default();
+    let rooms = find_rooms(&grid, config.min_room_area);
+    let found = entrances::find(&grid, &config);
+    assert!(
+        found.len() >= rooms.len(),
+        "{} < {}",
+        found.len(),
+        rooms.len()
+    );
+
+    let before = semantic.markers.len();
+    assert_eq!(entrances::place(&grid, &mut semantic, &config), found.len());
+    for (marker, entrance) in semantic.markers[before..].iter().zip(&found) {
+        let (x, y) = (entrance.x, entrance.y);
+        assert!(grid[(x, y)].is_floor());
+        assert!(rooms.iter().all(|r| !r.cells.contains(&(x, y))));
+        let (dx, dy) = entrance.facing.offset();
+        let inside = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+        assert!(rooms[entrance.room].cells.contains(&inside));
+
+        assert_eq!(marker.tag(), "entrance");
+        assert_eq!((marker.x as usize, marker.y as usize), (x, y));
+        assert_eq!(
+            marker.metadata[meta::ENTRANCE_FACING],
+            entrance.facing.name()
+        );
+        assert_eq!(
+            marker.get_i64(meta::ENTRANCE_ROOM),
+            Some(entrance.room as i64)
+        );
+        assert!(marker.region_id.is_some());
+    }
+}
+
+#[test]
+fn spawn_tables_roll_deterministically_at_markers() {
+    use terrain_forge::spawns::SpawnTables;
+
+    let markers = vec![
+        Marker::new(2, 2, MarkerType::EncounterZone { difficulty: 5 }),
+        Marker::new(6, 3, MarkerType::EncounterZone { difficulty: 1 }),
+        Marker::new(4, 8, MarkerType::LootTier { tier: 2 }),
+        Marker::with_tag(1, 1, "torch".to_string()),
+    ];
+    let tables = SpawnTables::from_json(
+        r#"{ "tables": [
+            { "marker": "encounter", "levels": [4, 9],
+              "entries": [{ "name": "ogre" }] },
+            { "marker": "encounter", "rolls": 3,
+              "entries": [{ "name": "rat", "weight": 3.0, "min_count": 2, "max_count": 4 },
+                          { "name": "goblin" }] },
+            { "marker": "loot_tier_2",
+              "entries": [{ "name": "gold", "min_count": 10, "max_count": 20 }] }
+        ] }"#,
+    )
+    .unwrap();
+    assert_eq!(SpawnTables::from_json(&tables.to_json()).unwrap(), tables);
+    assert!(SpawnTables::from_json("{ \"tables\": 3 }").is_err());
+
+    let spawns = tables.sample(&markers, 7);
+    assert_eq!(spawns, tables.sample(&markers, 7));
+    for spawn in &spawns {
+        let marker = &markers[spawn.marker];
+        assert_eq!((spawn.x, spawn.y), (marker.x, marker.y));
+    }
+    let at = |i: usize| spawns.iter().filter(move |s| s.marker == i);
+    assert_eq!(at(0).map(|s| s.name.as_str()).collect::<Vec<_>>(), ["ogre"]);
+    assert_eq!(at(1).count(), 3);
+    assert!(at(1).all(|s| match s.name.as_str() {
+        "rat" => (2..=4).contains(&s.count),
+        "goblin" => s.count == 1,
+        _ => false,
+    }));
+    assert!(at(2).all(|s| s.name == "gold" && (10..=20).contains(&s.count)));
+    assert_eq!(at(3).count(), 0);
+
+    // A marker's rolls don't depend on the markers before it.
+    let alone = tables.sample(&markers[2..3], 7);
+    assert_eq!(alone.len(), 1);
+    let counts: Vec<usize> = (0..16)
+        .map(|seed| tables.sample(&markers[2..3], seed)[0].count)
+        .collect();
+    assert!(counts.iter().any(|&c| c != counts[0]));
+}
+
+#[test]
+fn ambience_zones_group_by_kind_theme_and_adjacency() {
+    use terrain_forge::ambience::{self, AmbienceConfig, AmbienceZone};
+
+    // Five 3x3 regions in a row, linked left to right.
+    let region = |id: u32, kind: &str, theme: &str| {
+        let mut region = Region::new(id, kind);
+        let x0 = 1 + (id - 1) * 4;
+        for y in 1..4 {
+            for x in x0..x0 + 3 {
+                region.add_cell(x, y);
+            }
+        }
+        region.add_tag(format!("theme:{}", theme));
+        region
+    };
+    let mut connectivity = ConnectivityGraph::new();
+    for id in 1..4 {
+        connectivity.add_edge(id, id + 1);
+    }
+    connectivity.add_swim_edge(4, 5);
+    let semantic = SemanticLayers {
+        regions: vec![
+            region(5, "cave", "ice"),
+            region(1, "cave", "ice"),
+            region(2, "cave", "ice"),
+            region(3, "cave", "lava"),
+            region(4, "lake", "ice"),
+        ],
+        markers: Vec::new(),
+        masks: Masks::new(22, 5),
+        connectivity,
+    };
+
+    let zones = ambience::zones(&semantic, &AmbienceConfig::default());
+    let summary: Vec<(Vec<u32>, &str, Option<&str>, usize)> = zones
+        .iter()
+        .map(|z| {
+            (
+                z.regions.clone(),
+                z.kind.as_str(),
+                z.theme.as_deref(),
+                z.area,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (vec![1, 2], "cave", Some("ice"), 18),
+            (vec![3], "cave", Some("lava"), 9),
+            (vec![4], "lake", Some("ice"), 9),
+            (vec![5], "cave", Some("ice"), 9),
+        ]
+    );
+    let neighbors: Vec<Vec<u32>> = zones.iter().map(|z| z.neighbors.clone()).collect();
+    assert_eq!(neighbors, vec![vec![1], vec![0, 2], vec![1, 3], vec![2]]);
+    // Regions 1 and 2 are separated by a wall column, so their zone has two rings.
+    assert_eq!(zones[0].polygons.len(), 2);
+    for polygon in zones.iter().flat_map(|z| &z.polygons) {
+        assert!(polygon.len() >= 4);
+        assert!(polygon
+            .iter()
+            .all(|&(x, y)| (0.0..=22.0).contains(&x) && (0.0..=5.0).contains(&y)));
+    }
+
+    let json = ambience::to_json(&zones);
+    let parsed: Vec<AmbienceZone> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, zones);
+
+    let anywhere = ambience::zones(&semantic, &AmbienceConfig::default().with_connected(false));
+    assert_eq!(anywhere[0].regions, vec![1, 2, 5]);
+    assert_eq!(anywhere.len(), 3);
+
+    let by_kind = ambience::zones(
+        &semantic,
+        &AmbienceConfig::default().with_grouping(true, false),
+    );
+    assert_eq!(by_kind.len(), 3);
+    assert_eq!(by_kind[0].regions, vec![1, 2, 3]);
+    assert_eq!(by_kind[0].theme, None);
+}
+
+#[test]
+fn exits_maximize_or_match_path_distance() {
+    use terrain_forge::exits::{self, ExitConfig};
+    use terrain_forge::{Grid, Tile};
+
+    // Two rooms joined by a corridor, plus a long spur ending in a dead end.
+    let mut grid = Grid::new(40, 12);
+    grid.fill_rect(2, 2, 7, 7, Tile::Floor);
+    grid.fill_rect(9, 5, 17, 1, Tile::Floor);
+    grid.fill_rect(26, 2, 9, 7, Tile::Floor);
+    grid.fill_rect(12, 6, 1, 5, Tile::Floor);
+    grid.fill_rect(13, 10, 26, 1, Tile::Floor);
+    let mut room = Region::new(1, "room");
+    let mut corridor = Region::new(2, "corridor");
+    for (x, y, tile) in grid.iter() {
+        if !tile.is_floor() {
+            continue;
+        }
+        let in_room = (2..9).contains(&x) && (2..9).contains(&y) || x >= 26 && y < 9;
+        let region = if in_room { &mut room } else { &mut corridor };
+        region.add_cell(x as u32, y as u32);
+    }
+    let mut semantic = SemanticLayers {
+        regions: vec![room, corridor],
+        markers: vec![Marker::new(30, 5, MarkerType::Exit)],
+        masks: Masks::new(40, 12),
+        connectivity: ConnectivityGraph::new(),
+    };
+    assert!(exits::find(&grid, &semantic, &ExitConfig::default()).is_none());
+    semantic.markers.push(Marker::new(3, 5, MarkerType::Spawn));
+
+    let far = exits::find(&grid, &semantic, &ExitConfig::default()).unwrap();
+    assert_eq!((far.x, far.y, far.region_id), (37, 10, Some(2)));
+    let dead_end = exits::find(
+        &grid,
+        &semantic,
+        &ExitConfig::default().with_dead_ends(true),
+    );
+    assert_eq!(dead_end.map(|e| (e.x, e.y)), Some((38, 10)));
+    assert!(dead_end.unwrap().distance > far.distance);
+
+    let in_room = ExitConfig::default().with_region_kind("room");
+    let room_exit = exits::find(&grid, &semantic, &in_room).unwrap();
+    assert!(room_exit.x >= 26 && room_exit.region_id == Some(1));
+    assert!(room_exit.distance < far.distance);
+
+    let near = exits::find(&grid, &semantic, &ExitConfig::default().with_target(12.0)).unwrap();
+    assert!((near.distance - 12.0).abs() <= 0.5, "{}", near.distance);
+
+    let placed = exits::place(&grid, &mut semantic, &in_room).unwrap();
+    assert_eq!(placed, room_exit);
+    let exits: Vec<&Marker> = semantic
+        .markers
+        .iter()
+        .filter(|m| m.marker_type == MarkerType::Exit)
+        .collect();
+    assert_eq!(exits.len(), 1);
+    assert_eq!(
+        (exits[0].x as usize, exits[0].y as usize),
+        (placed.x, placed.y)
+    );
+    assert_eq!(exits[0].region_id, Some(1));
+    assert!(exits[0].metadata.contains_key(meta::EXIT_DISTANCE));
+}
+
+#[test]
+fn multi_exits_cover_edges_with_balanced_distances() {
+    use terrain_forge::constraints::{BalancedExitsConstraint, Constraint, ConstraintContext};
+    use terrain_forge::exits::{self, MultiExitConfig};
+    use terrain_forge::{Grid, Tile};
+
+    // A room far from every edge: exits need repaired corridors.
+    let mut grid = Grid::new(30, 30);
+    grid.fill_rect(8, 8, 14, 14, Tile::Floor);
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![
+            Marker::new(15, 15, MarkerType::Spawn),
+            Marker::new(20, 9, MarkerType::Exit),
+        ],
+        masks: Masks::new(30, 30),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let config = MultiExitConfig::default().with_count(4);
+    assert!(exits::find_multi(&grid, &semantic, &config).is_none());
+    let unrepaired = config.clone().with_repair(false);
+    assert!(exits::place_multi(&mut grid.clone(), &mut semantic.clone(), &unrepaired).is_none());
+
+    let constraint = BalancedExitsConstraint::new(&config);
+    let check = |grid: &Grid<Tile>, semantic: &SemanticLayers| {
+        let mut ctx = ConstraintContext::new(grid);
+        ctx.semantic = Some(semantic);
+        constraint.evaluate(&ctx)
+    };
+    assert!(!check(&grid, &semantic).passed);
+
+    let multi = exits::place_multi(&mut grid, &mut semantic, &config).unwrap();
+    assert!(multi.carved > 0);
+    assert!(multi.balanced(0.15));
+    let edges: Vec<&str> = multi.exits.iter().map(|e| e.edge.name()).collect();
+    assert_eq!(edges, ["north", "south", "east", "west"]);
+    for exit in &multi.exits {
+        assert!(grid[(exit.placement.x, exit.placement.y)].is_floor());
+    }
+    let placed: Vec<&Marker> = semantic
+        .markers
+        .iter()
+        .filter(|m| m.marker_type == MarkerType::Exit)
+        .collect();
+    assert_eq!(placed.len(), 4);
+    assert!(placed
+        .iter()
+        .all(|m| m.metadata.contains_key(meta::EXIT_EDGE)));
+
+    let balance = exits::balance(&grid, &semantic, 2).unwrap();
+    assert_eq!((balance.exits, balance.edges), (4, 4));
+    assert!((balance.spread.unwrap() - multi.spread).abs() < 1e-6);
+    let result = check(&grid, &semantic);
+    assert!(result.passed);
+    assert_eq!(result.details.get("edges").map(String::as_str), Some("4"));
+
+    // From a corner of the room the same exits are far from balanced.
+    semantic.markers[0] = Marker::new(9, 9, MarkerType::Spawn);
+    let result = check(&grid, &semantic);
+    assert!(!result.passed);
+    assert!(exits::balance(&grid, &semantic, 2).unwrap().spread.unwrap() > 0.15);
+}
+
+#[test]
+fn multi_exits_shortcut_far_edges_or_give_up() {
+    use terrain_forge::exits::{self, MultiExitConfig};
+    use terrain_forge::{Grid, Tile};
+
+    let layers = |x: u32, y: u32| SemanticLayers {
+        regions: Vec::new(),
+        markers: vec![Marker::new(x, y, MarkerType::Spawn)],
+        masks: Masks::new(40, 20),
+        connectivity: ConnectivityGraph::new(),
+    };
+    let config = MultiExitConfig::default();
+
+    // A straight arm west and a long detour east.
+    let mut grid = Grid::new(40, 20);
+    grid.fill_rect(1, 10, 20, 1, Tile::Floor);
+    grid.fill_rect(20, 4, 1, 7, Tile::Floor);
+    grid.fill_rect(20, 4, 11, 1, Tile::Floor);
+    grid.fill_rect(30, 4, 1, 12, Tile::Floor);
+    grid.fill_rect(30, 15, 9, 1, Tile::Floor);
+    let mut semantic = layers(20, 10);
+    let found = exits::find_multi(&grid, &semantic, &config).unwrap();
+    assert!(!found.balanced(config.tolerance));
+
+    let multi = exits::place_multi(&mut grid, &mut semantic, &config).unwrap();
+    assert!(multi.carved > 0);
+    assert!(multi.balanced(config.tolerance));
+    assert!(multi.spread < found.spread);
+    let balance = exits::balance(&grid, &semantic, 2).unwrap();
+    assert!(balance.spread.unwrap() <= config.tolerance);
+
+    // A spawn at one end of a straight hall can't be balanced; nothing is
+    // carved or placed.
+    let mut grid = Grid::new(40, 20);
+    grid.fill_rect(1, 10, 38, 1, Tile::Floor);
+    let mut semantic = layers(4, 10);
+    let before = grid.clone();
+    assert!(exits::place_multi(&mut grid, &mut semantic, &config).is_none());
+    assert_eq!(grid, before);
+    assert!(semantic
+        .markers
+        .iter()
+        .all(|m| m.marker_type != MarkerType::Exit));
+}
@@ -0,0 +1,83 @@
+//! Settlement network tests
+
+use terrain_forge::effects::{CrossingKind, ROAD_MASK};
+use terrain_forge::semantic::{ConnectivityGraph, Masks, SemanticLayers};
+use terrain_forge::settlements::{
+    self, RoadClass, SettlementConfig, SettlementTier, HIGHWAY_MASK, SETTLEMENT_KEY, TRAIL_MASK,
+};
+use terrain_forge::{Grid, Tile};
+
+/// Open land split by an 8-wide north-south river at x = 46..54.
+fn river_valley() -> (Grid<Tile>, Vec<Vec<bool>>) {
+    let mut grid = Grid::new(100, 60);
+    grid.fill_rect(1, 1, 98, 58, Tile::Floor);
+    let water = (0..60)
+        .map(|_| (0..100).map(|x| (46..54).contains(&x)).collect())
+        .collect();
+    (grid, water)
+}
+
+#[test]
+fn settlements_link_cities_by_highway_and_villages_by_trail() {
+    let (mut grid, mut water) = river_valley();
+    let config = SettlementConfig::default().with_counts(4, 4, 8);
+    let network = settlements::generate(&grid, &water, &config, 11);
+    assert_eq!(network, settlements::generate(&grid, &water, &config, 11));
+
+    assert_eq!(network.of_tier(SettlementTier::City).count(), 4);
+    assert_eq!(network.settlements.len(), 16);
+    for (i, a) in network.settlements.iter().enumerate() {
+        assert!(!water[a.y][a.x] && grid[(a.x, a.y)].is_floor());
+        for b in &network.settlements[i + 1..] {
+            let d = ((a.x as f64 - b.x as f64).powi(2) + (a.y as f64 - b.y as f64).powi(2)).sqrt();
+            assert!(d >= config.min_spacing);
+        }
+    }
+
+    // Highways bridge the river, so they span all four cities.
+    assert_eq!(network.links_of(RoadClass::Highway).count(), 3);
+    for link in network.links_of(RoadClass::Highway) {
+        assert_eq!(network.settlements[link.from].tier, SettlementTier::City);
+        assert_eq!(network.settlements[link.to].tier, SettlementTier::City);
+    }
+    // Towns link up the hierarchy; trails never bridge.
+    for link in network.links_of(RoadClass::Road) {
+        assert_eq!(network.settlements[link.from].tier, SettlementTier::Town);
+        assert_ne!(network.settlements[link.to].tier, SettlementTier::Village);
+    }
+    for link in network.links_of(RoadClass::Trail) {
+        assert!(link
+            .road
+            .crossings_of(CrossingKind::Bridge)
+            .next()
+            .is_none());
+    }
+    assert!(network.links_of(RoadClass::Trail).count() >= 6);
+
+    let mut semantic = SemanticLayers {
+        regions: Vec::new(),
+        markers: Vec::new(),
+        masks: Masks::new(100, 60),
+        connectivity: ConnectivityGraph::new(),
+    };
+    network.apply(&mut grid, &mut water, &mut semantic);
+    assert_eq!(semantic.markers.len(), 16);
+    assert_eq!(semantic.markers[0].tag(), "city");
+    assert_eq!(semantic.markers[15].metadata[SETTLEMENT_KEY], "15");
+    let highway = network.links_of(RoadClass::Highway).next().unwrap();
+    let (x, y) = highway.road.path[highway.road.path.len() / 2];
+    assert!(semantic.masks.named(HIGHWAY_MASK).unwrap()[y][x]);
+    assert!(semantic.masks.named(ROAD_MASK).unwrap()[y][x]);
+    if let Some(trail) = network.links_of(RoadClass::Trail).next() {
+        let (x, y) = trail.road.path[0];
+        assert!(semantic.masks.named(TRAIL_MASK).unwrap()[y][x]);
+    }
+    // Some highway crosses the river on a bridge, now dry floor.
+    let bridged = network
+        .links_of(RoadClass::Highway)
+        .flat_map(|l| l.road.crossings_of(CrossingKind::Bridge))
+        .flat_map(|c| &c.cells)
+        .collect::<Vec<_>>();
+    assert!(!bridged.is_empty());
+    assert!(bridged.iter().all(|&&(x, y)| !water[y][x]));
+}
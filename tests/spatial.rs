@@ -84,6 +84,39 @@ fn flow_field_generation() {
     assert!(dx >= 0 && dy >= 0);
 }
 
+#[test]
+fn weighted_costs_route_around_swamps() {
+    use terrain_forge::spatial::{dijkstra_map_with, shortest_path};
+
+    // A swamp column at x = 4 with a road crossing at the bottom row.
+    let mut grid = Grid::new(9, 5);
+    grid.fill_rect(0, 0, 9, 5, Tile::Floor);
+    let mut costs = vec![vec![1.0; 9]; 5];
+    for row in costs.iter_mut().take(4) {
+        row[4] = 10.0;
+    }
+    let constraints = PathfindingConstraints::default().with_cell_costs(costs);
+
+    let path = shortest_path(&grid, (0, 0), (8, 0), &constraints).unwrap();
+    assert!(path.contains(&(4, 4)));
+    let uniform = shortest_path(&grid, (0, 0), (8, 0), &PathfindingConstraints::default()).unwrap();
+    assert!(!uniform.contains(&(4, 4)));
+
+    let dijkstra = dijkstra_map(&grid, &[(8, 0)], &constraints);
+    assert!(dijkstra.is_weighted());
+    assert_eq!(dijkstra.entry_cost(4, 0), 10.0);
+    let flow = flow_field_from_dijkstra(&dijkstra);
+    assert_eq!(flow.get_direction(8, 0), (0, 0));
+    assert_eq!(flow.get_direction(3, 3), (1, 1));
+
+    // Predicates block cells beyond walls: a closed gate at the road.
+    let gated = dijkstra_map_with(&grid, &[(8, 0)], &constraints, |x, y, _| {
+        (x != 4).then(|| constraints.cell_cost(x, y)).flatten()
+    });
+    assert_eq!(gated.get(0, 0), f32::INFINITY);
+    assert!(gated.get(5, 0).is_finite());
+}
+
 #[test]
 fn morphological_erosion() {
     let mut grid = Grid::new(5, 5);
@@ -135,3 +168,119 @@ fn structuring_elements() {
     assert!(cross.get(1, 1));
     assert!(!cross.get(0, 0));
 }
+
+#[test]
+fn manhattan_distance_field_matches_brute_force() {
+    let mut grid = Grid::new(23, 17);
+    terrain_forge::ops::generate("cellular", &mut grid, Some(9), None).unwrap();
+    let floors: Vec<(usize, usize)> = grid
+        .iter()
+        .filter(|(_, _, t)| t.is_floor())
+        .map(|(x, y, _)| (x, y))
+        .collect();
+
+    let transform = distance_field(&grid, DistanceMetric::Manhattan);
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let expected = floors
+                .iter()
+                .map(|&(fx, fy)| (fx.abs_diff(x) + fy.abs_diff(y)) as f32)
+                .fold(f32::INFINITY, f32::min);
+            assert_eq!(transform.get(x, y), expected, "({}, {})", x, y);
+        }
+    }
+
+    let empty = distance_field(&Grid::<Tile>::new(4, 3), DistanceMetric::Manhattan);
+    assert_eq!(empty.get(3, 2), f32::INFINITY);
+}
+
+#[test]
+fn slope_and_aspect_of_tilted_plane() {
+    use terrain_forge::spatial::{aspect, block_steep, slope, terrain::FLAT};
+
+    // Rises 0.1 per column toward the east, flat on the right half.
+    let heights: Vec<Vec<f64>> = (0..6)
+        .map(|_| (0..10).map(|x| 0.1 * x.min(5) as f64).collect())
+        .collect();
+    let slopes = slope(&heights);
+    let aspects = aspect(&heights);
+    assert!((slopes[3][2] - 0.1).abs() < 1e-9);
+    assert!((slopes[3][0] - 0.1).abs() < 1e-9);
+    assert_eq!(slopes[3][8], 0.0);
+    // Downhill faces west.
+    assert!((aspects[3][2] - 270.0).abs() < 1e-9);
+    assert_eq!(aspects[3][8], FLAT);
+
+    let transposed: Vec<Vec<f64>> = (0..10)
+        .map(|y| (0..6).map(|_| 0.1 * (y as f64)).collect())
+        .collect();
+    assert!((aspect(&transposed)[4][3]).abs() < 1e-9, "faces north");
+
+    let mut grid = Grid::new(10, 6);
+    grid.fill_rect(0, 0, 10, 6, Tile::Floor);
+    assert_eq!(block_steep(&mut grid, &slopes, 0.05), 5 * 6);
+    assert!(grid[(2, 3)].is_wall());
+    assert!(grid[(8, 3)].is_floor());
+}
+
+#[test]
+fn priority_flood_finds_basins_and_spill_points() {
+    use terrain_forge::spatial::fill_depressions;
+
+    // Ground falls 0.01 per column to the east. Pit A (x 2..=4, y 2..=4)
+    // drains through a channel along y = 3; pit B is a single cell.
+    let mut heights: Vec<Vec<f64>> = (0..8)
+        .map(|_| (0..12).map(|x| 0.6 - 0.01 * x as f64).collect())
+        .collect();
+    for row in heights.iter_mut().take(5).skip(2) {
+        row[2..=4].fill(0.2);
+    }
+    for (i, cell) in heights[3][5..].iter_mut().enumerate() {
+        *cell = 0.45 - 0.01 * i as f64;
+    }
+    heights[6][9] = 0.3;
+
+    let result = fill_depressions(&heights);
+    assert_eq!(result.basins.len(), 2);
+    let a = &result.basins[0];
+    assert_eq!(a.cells.len(), 9);
+    assert_eq!(a.spill, (5, 3));
+    assert!((a.fill_level - 0.45).abs() < 1e-9);
+    assert!((a.depth - 0.25).abs() < 1e-9);
+    assert_eq!(result.basin_ids[3][3], a.id);
+    assert!((result.filled[2][2] - 0.45).abs() < 1e-9);
+
+    let b = &result.basins[1];
+    assert_eq!(b.cells, vec![(9, 6)]);
+    assert_eq!(b.spill, (10, 5));
+    assert!((b.fill_level - 0.5).abs() < 1e-9);
+
+    // Filled terrain has no pits left and leaves the rest alone.
+    assert!(fill_depressions(&result.filled).basins.is_empty());
+    assert_eq!(result.filled[0], heights[0]);
+}
+
+#[test]
+fn equal_cost_paths_do_not_depend_on_map_order() {
+    use terrain_forge::spatial::shortest_path;
+
+    // An open room has many equally short routes; every fresh constraint set
+    // must pick the same one.
+    let mut grid = Grid::new(20, 20);
+    grid.fill_rect(1, 1, 18, 18, Tile::Floor);
+    let mut four_way = PathfindingConstraints::default();
+    four_way
+        .movement_cost
+        .retain(|&(dx, dy), _| dx == 0 || dy == 0);
+    let first = shortest_path(&grid, (1, 1), (18, 12), &four_way).unwrap();
+    for _ in 0..20 {
+        let mut fresh = PathfindingConstraints::default();
+        fresh
+            .movement_cost
+            .retain(|&(dx, dy), _| dx == 0 || dy == 0);
+        assert_eq!(
+            shortest_path(&grid, (1, 1), (18, 12), &fresh).unwrap(),
+            first
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! Streaming generation tests — chunk stitching, row bands, PNG output.
+
+use terrain_forge::algorithms::{NoiseFill, NoiseFillConfig, Percolation, Voronoi};
+use terrain_forge::stream::{self, ChunkGenerator};
+use terrain_forge::{Algorithm, Grid, Tile};
+
+fn stitch<G: ChunkGenerator>(gen: &G, w: usize, h: usize, chunk: usize, seed: u64) -> Grid<Tile> {
+    let mut grid = Grid::new(w, h);
+    for piece in stream::chunks(gen, w, h, chunk, seed) {
+        for (x, y, tile) in piece.grid.iter() {
+            grid.set((piece.x + x) as i32, (piece.y + y) as i32, *tile);
+        }
+    }
+    grid
+}
+
+#[test]
+fn chunks_match_full_generation() {
+    let noise = NoiseFill::new(NoiseFillConfig::default().with_octaves(3));
+    let mut full = Grid::new(150, 90);
+    noise.generate(&mut full, 11);
+    assert_eq!(stitch(&noise, 150, 90, 32, 11), full);
+
+    let voronoi = Voronoi::default();
+    let mut full = Grid::new(150, 90);
+    voronoi.generate(&mut full, 11);
+    assert_eq!(stitch(&voronoi, 150, 90, 40, 11), full);
+}
+
+#[test]
+fn percolation_chunks_are_layout_independent() {
+    let perc = Percolation::default();
+    let a = stitch(&perc, 100, 70, 16, 3);
+    let b = stitch(&perc, 100, 70, 33, 3);
+    assert_eq!(a, b);
+    assert!(a.count(|t| t.is_floor()) > 0);
+
+    let bands: Vec<_> = stream::rows(&perc, 100, 70, 8, 3).collect();
+    assert_eq!(bands.len(), 9);
+    assert_eq!(bands[8].grid.height(), 6);
+    assert!(bands.iter().all(|b| b.grid.width() == 100));
+}
+
+#[test]
+fn write_png_streams_whole_map() {
+    let noise = NoiseFill::default();
+    let mut bytes = Vec::new();
+    stream::write_png(&noise, 120, 50, 7, 5, &mut bytes).unwrap();
+
+    let decoded = Grid::from_image_bytes(&bytes, 128).unwrap();
+    let mut full = Grid::new(120, 50);
+    noise.generate(&mut full, 5);
+    assert_eq!(decoded, full);
+}
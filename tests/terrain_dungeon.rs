@@ -0,0 +1,61 @@
+//! Terrain-fitted dungeon tests
+
+use terrain_forge::algorithms::{Bsp, DiamondSquare};
+use terrain_forge::terrain_dungeon::{TerrainDungeonConfig, TerrainDungeonGenerator};
+
+/// Low ground in the west, high ground in the east, joined over `ramp`
+/// columns (a cliff when `ramp` is 1).
+fn step_heights(w: usize, h: usize, ramp: usize) -> Vec<Vec<f64>> {
+    (0..h)
+        .map(|_| {
+            (0..w)
+                .map(|x| {
+                    let t = (x as f64 - 40.0) / ramp as f64;
+                    0.2 + 0.4 * t.clamp(0.0, 1.0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn cliffs_split_dungeon_and_ramps_join_it() {
+    let generator = TerrainDungeonGenerator::new(Bsp::default(), TerrainDungeonConfig::default());
+
+    let cliff = generator.generate(&step_heights(80, 40, 1), 3);
+    assert!(cliff.cliffs[20][40] && cliff.cliffs[20][41]);
+    let regions = cliff.grid.flood_regions();
+    assert_eq!(regions.len(), 2);
+    for region in &regions {
+        let west = region[0].0 <= 40;
+        assert!(region.iter().all(|&(x, _)| (x <= 40) == west));
+    }
+
+    let ramp = generator.generate(&step_heights(80, 40, 20), 3);
+    assert!(ramp.cliffs.iter().flatten().all(|&c| !c));
+    assert_eq!(ramp.grid.flood_regions().len(), 1);
+}
+
+#[test]
+fn rooms_sit_on_flat_levelled_ground() {
+    let config = TerrainDungeonConfig::default();
+    let heights = DiamondSquare::default().heightmap(80, 60, 11);
+    let dungeon =
+        TerrainDungeonGenerator::new(Bsp::default(), config.clone()).generate(&heights, 11);
+
+    assert!(!dungeon.rooms.is_empty());
+    for room in &dungeon.rooms {
+        let level = dungeon.elevation[room[0].1][room[0].0];
+        let (lo, hi) = room.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &(x, y)| {
+            (lo.min(heights[y][x]), hi.max(heights[y][x]))
+        });
+        assert!(hi - lo <= config.max_room_relief);
+        for &(x, y) in room {
+            assert!(dungeon.grid[(x, y)].is_floor());
+            assert_eq!(dungeon.elevation[y][x], level);
+        }
+    }
+    for (x, y, tile) in dungeon.grid.iter() {
+        assert!(!(dungeon.cliffs[y][x] && tile.is_floor()));
+    }
+}
@@ -0,0 +1,67 @@
+//! Property harness tests — built-ins pass, broken algorithms are caught.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use terrain_forge::testing::{self, Property, PropertyConfig};
+use terrain_forge::{algorithms, Algorithm, Grid, Tile};
+
+#[test]
+fn built_in_algorithms_keep_the_properties() {
+    // Open-edged terrain and refiners of existing maps relax border and
+    // floor expectations; everything else gets the defaults.
+    for name in algorithms::list() {
+        let algorithm = algorithms::get(name).unwrap();
+        let config = match *name {
+            "diamond_square" | "fractal" | "room_accretion" => PropertyConfig::default()
+                .with_wall_border(false)
+                .with_min_floor_size(usize::MAX),
+            "glass_seam" => PropertyConfig::default().with_min_floor_size(usize::MAX),
+            // Slow in debug builds; small grids still hit the edge cases.
+            "wfc" => PropertyConfig::default().with_sizes(vec![(1, 1), (3, 3), (5, 4), (16, 9)]),
+            _ => PropertyConfig::default(),
+        };
+        let report = testing::check(&algorithm, &config);
+        assert_eq!(report.runs, config.sizes.len() * config.seeds.len());
+        assert!(report.passed(), "{}", report);
+    }
+}
+
+/// Panics on narrow grids, writes the border, and draws from a global
+/// counter instead of its seed.
+struct Careless {
+    calls: AtomicU64,
+}
+
+impl Algorithm for Careless {
+    fn generate(&self, grid: &mut Grid<Tile>, seed: u64) {
+        assert!(grid.width() > 4, "too narrow");
+        let call = self.calls.fetch_add(1, Ordering::Relaxed);
+        grid.set(0, 0, Tile::Floor);
+        grid.set((seed ^ call) as i32 % grid.width() as i32, 1, Tile::Floor);
+    }
+
+    fn name(&self) -> &'static str {
+        "Careless"
+    }
+}
+
+#[test]
+fn broken_algorithms_are_reported() {
+    let careless = Careless {
+        calls: AtomicU64::new(0),
+    };
+    let config = PropertyConfig::default()
+        .with_sizes(vec![(3, 3), (20, 20)])
+        .with_seeds(vec![7]);
+    let report = testing::check(&careless, &config);
+    assert_eq!(report.algorithm, "Careless");
+    assert_eq!(report.runs, 2);
+
+    let panics: Vec<_> = report.violations_of(Property::NoPanic).collect();
+    assert_eq!(panics.len(), 1);
+    assert_eq!((panics[0].width, panics[0].height), (3, 3));
+    assert!(panics[0].message.contains("too narrow"));
+    assert_eq!(report.violations_of(Property::Bounds).count(), 1);
+    assert_eq!(report.violations_of(Property::Deterministic).count(), 1);
+    assert_eq!(report.violations_of(Property::Floor).count(), 0);
+    assert!(report.to_string().contains("bounds at 20x20 seed 7"));
+}
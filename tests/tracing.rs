@@ -0,0 +1,110 @@
+//! Tracing hook tests — spans for pipeline steps and algorithms, and the
+//! error event for a failing `Pipeline::generate`.
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+use terrain_forge::pipeline::Pipeline;
+use terrain_forge::{Algorithm, Grid};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records span names and event messages with their fields, in order.
+#[derive(Clone, Default)]
+struct Recorder {
+    lines: Arc<Mutex<Vec<String>>>,
+    next: Arc<Mutex<u64>>,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Fields(format!("span {}", span.metadata().name()));
+        span.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0);
+        let mut next = self.next.lock().unwrap();
+        *next += 1;
+        Id::from_u64(*next)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(format!("event {}", event.metadata().level()));
+        event.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn capture(run: impl FnOnce()) -> Vec<String> {
+    let recorder = Recorder::default();
+    let lines = recorder.lines.clone();
+    tracing::subscriber::with_default(recorder, run);
+    let lines = lines.lock().unwrap().clone();
+    lines
+}
+
+#[test]
+fn pipeline_steps_and_algorithms_are_traced() {
+    let mut pipeline = Pipeline::new();
+    pipeline.add_algorithm("cellular", Some(7), None);
+    pipeline.add_effect("erode", None);
+    let lines = capture(|| {
+        let mut grid = Grid::new(40, 30);
+        pipeline.execute_seed(&mut grid, 3).unwrap();
+    });
+
+    let find = |prefix: &str| {
+        lines
+            .iter()
+            .position(|l| l.starts_with(prefix))
+            .unwrap_or_else(|| panic!("no {prefix:?} in {lines:#?}"))
+    };
+    let pipeline_span = find("span pipeline seed=Some(3)");
+    let algorithm = find("span algorithm name=\"cellular\" seed=7");
+    let effect = find("span effect name=\"erode\"");
+    assert!(pipeline_span < algorithm && algorithm < effect);
+    assert!(
+        lines
+            .iter()
+            .filter(|l| l.contains("span pipeline_step"))
+            .count()
+            == 2
+    );
+    assert!(lines
+        .iter()
+        .any(|l| l.starts_with("event DEBUG message=finished micros=")));
+}
+
+#[test]
+fn failing_pipeline_generate_emits_an_error() {
+    let mut pipeline = Pipeline::new();
+    pipeline.add_algorithm("no_such_algorithm", None, None);
+    let lines = capture(|| {
+        let mut grid = Grid::new(20, 20);
+        pipeline.generate(&mut grid, 9);
+    });
+    assert!(lines
+        .iter()
+        .any(|l| l.contains("pipeline step failed") && l.contains("no_such_algorithm")));
+    assert!(lines
+        .iter()
+        .any(|l| l.starts_with("event ERROR message=pipeline execution failed seed=9")));
+}
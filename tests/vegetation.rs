@@ -0,0 +1,80 @@
+//! Vegetation distribution tests
+
+use terrain_forge::vegetation::{self, Species, VegetationConfig};
+
+/// Sea on the left third, a gentle land slope rising to the right.
+fn heights(w: usize, h: usize) -> Vec<Vec<f64>> {
+    (0..h)
+        .map(|_| {
+            (0..w)
+                .map(|x| {
+                    if x < w / 3 {
+                        0.2
+                    } else {
+                        0.45 + x as f64 * 0.002
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn density_follows_moisture_and_clears_roads() {
+    let (w, h) = (60, 40);
+    let heights = heights(w, h);
+    // Wet top half, dry bottom half.
+    let moisture: Vec<Vec<f64>> = (0..h)
+        .map(|y| vec![if y < h / 2 { 0.9 } else { 0.05 }; w])
+        .collect();
+    let road: Vec<(usize, usize)> = (0..h).map(|y| (40, y)).collect();
+    let config = VegetationConfig::default().with_clearing(2, 3);
+    let plants = vegetation::distribute(&heights, &moisture, road, &config, 9);
+
+    assert_eq!(plants.density(5, 5), 0.0, "water stays bare");
+    for y in 0..h {
+        for x in 38..=42 {
+            assert_eq!(plants.density(x, y), 0.0, "clearing at ({}, {})", x, y);
+        }
+    }
+    let mean = |ys: std::ops::Range<usize>| {
+        let cells: Vec<f64> = ys
+            .flat_map(|y| (21..36).map(move |x| (x, y)))
+            .map(|(x, y)| plants.density(x, y))
+            .collect();
+        cells.iter().sum::<f64>() / cells.len() as f64
+    };
+    assert!(mean(0..20) > mean(20..40) * 2.0);
+
+    assert!(!plants.markers.is_empty());
+    for marker in &plants.markers {
+        let (x, y) = (marker.x as usize, marker.y as usize);
+        assert!(plants.density(x, y) > 0.0);
+        assert!(["broadleaf", "conifer", "shrub"].contains(&marker.tag().as_str()));
+    }
+    let broadleaf: Vec<_> = plants.markers_of("broadleaf").collect();
+    for (i, a) in broadleaf.iter().enumerate() {
+        for b in &broadleaf[i + 1..] {
+            assert!(a.x.abs_diff(b.x).max(a.y.abs_diff(b.y)) > 2);
+        }
+    }
+
+    let again = vegetation::distribute(&heights, &moisture, [], &config, 9);
+    let other = vegetation::distribute(&heights, &moisture, [], &config, 9);
+    assert_eq!(again.density, other.density);
+    assert_eq!(again.markers.len(), other.markers.len());
+}
+
+#[test]
+fn custom_species_table_respects_slope() {
+    let steep: Vec<Vec<f64>> = (0..10)
+        .map(|_| (0..10).map(|x| 0.4 + x as f64 * 0.05).collect())
+        .collect();
+    let moisture = vec![vec![0.5; 10]; 10];
+    let flat_only = VegetationConfig::default()
+        .with_species(vec![Species::new("grass").with_max_slope(0.02)])
+        .with_scatter(1.0);
+    let plants = vegetation::distribute(&steep, &moisture, [], &flat_only, 1);
+    assert!(plants.density.iter().flatten().all(|&d| d == 0.0));
+    assert!(plants.markers.is_empty());
+}
@@ -0,0 +1,79 @@
+//! Resource vein placement tests
+
+use terrain_forge::tileset::{TileDef, TileId, TileSet};
+use terrain_forge::veins::{self, DepthMode, Resource, VeinConfig, VeinHost};
+use terrain_forge::{Grid, Tile};
+
+#[test]
+fn veins_follow_depth_spacing_and_host() {
+    // Solid rock with a floor gallery along the middle row.
+    let (w, h) = (80, 80);
+    let mut grid = Grid::new(w, h);
+    grid.fill_rect(0, 40, w, 1, Tile::Floor);
+
+    let config = VeinConfig::default().with_resources(vec![
+        Resource::new("coal")
+            .with_rarity(6.0)
+            .with_depth(0.0, 0.3)
+            .with_spacing(3),
+        Resource::new("gold")
+            .with_rarity(3.0)
+            .with_depth(0.7, 1.0)
+            .with_length(3, 5)
+            .with_spacing(6),
+    ]);
+    let placed = veins::place(&grid, &config, 17);
+    assert!(placed.vein_count > 0);
+    assert_eq!(
+        placed.markers.len(),
+        placed.deposits.iter().flatten().flatten().count()
+    );
+    for marker in &placed.markers {
+        let (x, y) = (marker.x as usize, marker.y as usize);
+        assert!(!grid[(x, y)].is_floor());
+        match marker.tag().as_str() {
+            // Seeds stay inside the window plus its fade; walks may drift a few cells.
+            "coal" => assert!(y < 45, "coal at depth row {}", y),
+            "gold" => assert!(y > 35, "gold at depth row {}", y),
+            other => panic!("unexpected resource {}", other),
+        }
+        assert_eq!(placed.resource_at(x, y), Some(marker.tag().as_str()));
+    }
+    assert!(placed.markers_of("coal").count() > 0);
+    assert!(placed.markers_of("gold").count() > 0);
+
+    // Gold vein seeds are at least `spacing` apart from other veins: no two
+    // distinct gold veins touch within 6 cells of their first cells.
+    let mut firsts: Vec<(u32, u32)> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for marker in placed.markers_of("gold") {
+        if seen.insert(marker.metadata["vein"].clone()) {
+            firsts.push((marker.x, marker.y));
+        }
+    }
+    for (i, a) in firsts.iter().enumerate() {
+        for b in &firsts[i + 1..] {
+            assert!(a.0.abs_diff(b.0).max(a.1.abs_diff(b.1)) > 6);
+        }
+    }
+
+    let again = veins::place(&grid, &config, 17);
+    assert_eq!(again.deposits, placed.deposits);
+
+    // Radial depth on open ground: deposits only on floor.
+    let floor_config = VeinConfig::default()
+        .with_host(VeinHost::Floor)
+        .with_depth_mode(DepthMode::FromPoint(40, 40))
+        .with_resources(vec![Resource::new("herb").with_rarity(200.0)]);
+    let herbs = veins::place(&grid, &floor_config, 3);
+    assert!(herbs.vein_count > 0);
+    assert!(herbs.markers.iter().all(|m| m.y == 40));
+
+    let tileset = TileSet::default().with_tile(TileDef::new(TileId(9), "coal"));
+    let mut tiles = tileset.from_tiles(&grid);
+    placed.paint(&mut tiles, &tileset);
+    let coal = placed.markers_of("coal").next().unwrap();
+    assert_eq!(tiles[(coal.x as usize, coal.y as usize)].id(), TileId(9));
+    let gold = placed.markers_of("gold").next().unwrap();
+    assert_ne!(tiles[(gold.x as usize, gold.y as usize)].id(), TileId(9));
+}
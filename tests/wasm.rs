@@ -0,0 +1,19 @@
+#![cfg(feature = "wasm")]
+
+use terrain_forge::wasm;
+
+#[test]
+fn wasm_bindings_return_tiles_and_semantic_json() {
+    let mut map = wasm::generate("bsp", 40, 30, 7, Some(r#"{"max_depth": 4}"#.into()))
+        .unwrap_or_else(|_| panic!("generate failed"));
+    let tiles = map.tiles();
+    assert_eq!(tiles.len(), 40 * 30);
+    assert!(tiles.contains(&1));
+    assert!(map.semantic_json().is_none());
+
+    map.extract("rooms", 7)
+        .unwrap_or_else(|_| panic!("extract failed"));
+    let json: serde_json::Value = serde_json::from_str(&map.semantic_json().unwrap()).unwrap();
+    assert!(!json["regions"].as_array().unwrap().is_empty());
+    assert!(wasm::algorithms().contains(&"bsp".to_string()));
+}